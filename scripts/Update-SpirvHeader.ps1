@@ -1,2 +1,2 @@
-Invoke-WebRequest "https://raw.githubusercontent.com/KhronosGroup/SPIRV-Headers/main/include/spirv/unified1/spirv.core.grammar.json" -OutFile assets/spirv/spirv.core.grammar.json
+Invoke-WebRequest "https://raw.githubusercontent.com/KhronosGroup/SPIRV-Headers/main/include/spirv/unified1/spirv.core.grammar.json" -OutFile spirq/grammar/spirv.core.grammar.json
 Invoke-WebRequest "https://raw.githubusercontent.com/KhronosGroup/SPIRV-Headers/main/include/spirv/spir-v.xml" -OutFile assets/spirv/spir-v.xml
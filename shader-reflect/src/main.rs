@@ -1,7 +1,15 @@
-use clap::Parser;
+mod schema;
+
+use clap::{Parser, Subcommand, ValueEnum};
 use serde_json::json;
+use spirq::header::generate_c_header;
+use spirq::layout_desc::LayoutDescription;
+use spirq::markdown::generate_markdown;
+use spirq::patch::Patcher;
 use spirq::prelude::*;
+use spirq::stub::{generate_stubs, StubLanguage};
 use spirq::ty;
+use spirq::wgsl::generate_wgsl;
 use std::{
     borrow::Borrow,
     fs::File,
@@ -10,22 +18,42 @@ use std::{
     process::exit,
 };
 
+/// Input files or output files cannot be accessed, or the command line is
+/// malformed.
+const EXIT_FAILURE: i32 = 1;
+/// The input is valid but cannot be reflected.
+const EXIT_REFLECT_FAILED: i32 = 2;
+/// The input is not a valid SPIR-V binary or shader source.
+const EXIT_VALIDATION_FAILED: i32 = 3;
+
 #[derive(Parser, Debug)]
-#[command(author, version, about, long_about = None)]
+#[command(
+    author,
+    version,
+    about,
+    long_about = None,
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
+)]
 struct Args {
-    #[arg(help = "Input SPIR-V file path.")]
-    in_path: String,
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[arg(required = true, help = "Input SPIR-V file path.")]
+    in_path: Option<String>,
 
     #[arg(
         short,
         long,
-        help = "Output JSON file path. The output is printed to stdout if this \
+        global = true,
+        help = "Output file path. The output is printed to stdout if this \
         path is not given."
     )]
     out_path: Option<String>,
 
     #[arg(
         long,
+        global = true,
         help = "Reference all resources even they are never used by the entry \
         points. By default, only the referenced resources are reflected."
     )]
@@ -33,6 +61,7 @@ struct Args {
 
     #[arg(
         long,
+        global = true,
         help = "Combine separate sampled image and sampler at a same \
         descriptor set and binding. By default, they are listed as separate \
         objects."
@@ -41,14 +70,27 @@ struct Args {
 
     #[arg(
         long,
+        global = true,
         help = "Generate unique names for every resource variable, structure \
         types, and type members. By default, the names are assigned with debug \
         annotations in the input SPIR-V."
     )]
     generate_unique_names: bool,
 
+    #[arg(
+        long,
+        global = true,
+        value_name = "SPEC_ID=VALUE",
+        help = "Specialize the constant of a specialization ID with a 32-bit \
+        integer, a float or a boolean. Can be specified multiple times. \
+        Specialized constants are not listed in the output and the resolved \
+        execution modes use the specialized values."
+    )]
+    spec: Vec<String>,
+
     #[arg(
         short = 'I',
+        global = true,
         help = "The base directories of standard includes (`#include <...>`) \
         in compilation of GLSL or HLSL shader sources."
     )]
@@ -56,6 +98,7 @@ struct Args {
 
     #[arg(
         short = 'D',
+        global = true,
         help = "Compiler definitions in compilation of GLSL or HLSL shader \
         sources."
     )]
@@ -64,10 +107,155 @@ struct Args {
     #[arg(
         short,
         long,
+        global = true,
         help = "Shader entry point function name in compilation of GLSL or \
         HLSL shader."
     )]
     entry_point: Option<String>,
+
+    #[arg(
+        long,
+        help = "Print each entry point as a compact JSON object in a line \
+        (JSON Lines). By default, the JSON objects are pretty-printed."
+    )]
+    jsonl: bool,
+
+    #[arg(
+        long,
+        value_enum,
+        help = "Sort the reflected variables by descriptor set and binding, \
+        by location and component, or by name. By default, the variables are \
+        listed in declaration order."
+    )]
+    sort: Option<SortKey>,
+
+    #[arg(
+        long,
+        value_enum,
+        help = "Only output variables of the given kind. Can be specified \
+        multiple times. By default, variables of all kinds are output."
+    )]
+    only: Vec<VariableKind>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    #[command(about = "Emit declaration-only interface stubs of the shader in a \
+    shading language.")]
+    Stubs {
+        #[arg(help = "Input SPIR-V file path.")]
+        in_path: String,
+
+        #[arg(
+            long,
+            value_enum,
+            default_value = "glsl",
+            help = "Shading language of the stubs."
+        )]
+        lang: Lang,
+    },
+    #[command(about = "Emit C/C++ struct declarations of the uniform buffer, \
+    storage buffer and push constant blocks of the shader.")]
+    Header {
+        #[arg(help = "Input SPIR-V file path.")]
+        in_path: String,
+    },
+    #[command(about = "Emit WGSL declarations of the descriptors, push \
+    constants and specialization constants of the shader.")]
+    Wgsl {
+        #[arg(help = "Input SPIR-V file path.")]
+        in_path: String,
+    },
+    #[command(about = "Emit Markdown documentation of the descriptors, push \
+    constants, stage interface and specialization constants of the shader.")]
+    Markdown {
+        #[arg(help = "Input SPIR-V file path.")]
+        in_path: String,
+    },
+    #[command(about = "Emit the descriptor set layout description in YAML, \
+    merged from the entry points of all the input shaders.")]
+    Layout {
+        #[arg(required = true, help = "Input SPIR-V file paths.")]
+        in_paths: Vec<String>,
+    },
+    #[command(about = "Upgrade reflection JSONs emitted by an older version \
+    of shader-reflect to the current schema.")]
+    Upgrade {
+        #[arg(help = "Input reflection JSON file path.")]
+        in_path: String,
+
+        #[arg(
+            long,
+            help = "Print each entry point as a compact JSON object in a \
+            line (JSON Lines). By default, the JSON objects are \
+            pretty-printed."
+        )]
+        jsonl: bool,
+    },
+    #[command(about = "Patch the shader to work around driver quirks and \
+    emit the patched SPIR-V binary.")]
+    Patch {
+        #[arg(help = "Input SPIR-V file path.")]
+        in_path: String,
+
+        #[arg(
+            long,
+            help = "Store 1.0 to PointSize in the vertex shaders not writing \
+            it, for drivers drawing points incorrectly otherwise."
+        )]
+        point_size: bool,
+    },
+}
+
+/// What is emitted for each entry point.
+#[derive(Clone, Copy, Debug)]
+enum OutputKind {
+    Json,
+    Stubs(Lang),
+    Header,
+    Wgsl,
+    Markdown,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum Lang {
+    Glsl,
+    Hlsl,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum SortKey {
+    #[value(name = "set,binding")]
+    SetBinding,
+    Location,
+    Name,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum VariableKind {
+    Descriptors,
+    Inputs,
+    Outputs,
+    PushConstants,
+    SpecConstants,
+}
+
+/// Stable-sort `vars` by `key`. Variables without the key, e.g., inputs when
+/// sorted by binding, keep their relative order.
+fn sort_vars(vars: &mut [Variable], key: SortKey) {
+    match key {
+        SortKey::SetBinding => vars.sort_by_key(|x| match x {
+            Variable::Descriptor { desc_bind, .. } => Some((desc_bind.set(), desc_bind.bind())),
+            _ => None,
+        }),
+        SortKey::Location => vars.sort_by_key(|x| match x {
+            Variable::Input { location, .. } | Variable::Output { location, .. } => {
+                Some((location.loc(), location.comp()))
+            }
+            _ => None,
+        }),
+        SortKey::Name => vars.sort_by(|a, b| a.name().cmp(&b.name())),
+    }
 }
 
 fn read_spirv_bianry(path: &str) -> SpirvBinary {
@@ -76,17 +264,37 @@ fn read_spirv_bianry(path: &str) -> SpirvBinary {
         Err(e) => {
             writeln!(stderr(), "{}", e.to_string()).unwrap();
             writeln!(stderr(), "cannot read from SPIR-V binary: {}", path).unwrap();
-            exit(-1);
+            exit(EXIT_FAILURE);
         }
     };
     if spv.len() % 4 != 0 {
         // Misaligned input.
         writeln!(stderr(), "spirv binary must align to 4 bytes: {}", path).unwrap();
-        exit(-1);
+        exit(EXIT_VALIDATION_FAILED);
     }
     SpirvBinary::from(spv)
 }
 
+/// Parse a `SPEC_ID=VALUE` specialization. The value is typeless and typed by
+/// the specialization constant.
+fn parse_spec(spec: &str) -> Option<(u32, ConstantValue)> {
+    let (spec_id, value) = spec.split_once('=')?;
+    let spec_id = spec_id.trim().parse().ok()?;
+    let value = value.trim();
+    let bits = if let Ok(x) = value.parse::<u32>() {
+        x
+    } else if let Ok(x) = value.parse::<i32>() {
+        x as u32
+    } else if let Ok(x) = value.parse::<f32>() {
+        x.to_bits()
+    } else if let Ok(x) = value.parse::<bool>() {
+        x as u32
+    } else {
+        return None;
+    };
+    Some((spec_id, ConstantValue::from(bits.to_le_bytes())))
+}
+
 fn compile_shader_source(
     path: &str,
     args: &Args,
@@ -98,7 +306,7 @@ fn compile_shader_source(
         Err(e) => {
             writeln!(stderr(), "{}", e.to_string()).unwrap();
             writeln!(stderr(), "cannot read from input shader source: {}", path).unwrap();
-            exit(-1);
+            exit(EXIT_FAILURE);
         }
     };
 
@@ -106,7 +314,7 @@ fn compile_shader_source(
         Some(x) => x,
         None => {
             writeln!(stderr(), "cannot create shaderc compile option").unwrap();
-            exit(-1);
+            exit(EXIT_FAILURE);
         }
     };
     opt.set_target_env(
@@ -161,7 +369,7 @@ fn compile_shader_source(
         Some(x) => x,
         None => {
             writeln!(stderr(), "cannot create compiler instance").unwrap();
-            exit(-1);
+            exit(EXIT_FAILURE);
         }
     };
     let art = match compiler.compile_into_spirv(&src, shader_kind, &path, entry_point, Some(&opt)) {
@@ -169,7 +377,7 @@ fn compile_shader_source(
         Err(e) => {
             writeln!(stderr(), "{}", e.to_string()).unwrap();
             writeln!(stderr(), "cannot compile shader source: {}", path).unwrap();
-            exit(-1);
+            exit(EXIT_VALIDATION_FAILED);
         }
     };
 
@@ -180,7 +388,7 @@ fn get_spirv_bianry(path: &str, args: &Args) -> SpirvBinary {
     // Ensure the source file exists.
     if !Path::new(path).is_file() {
         writeln!(stderr(), "input file doesn't exist").unwrap();
-        exit(-1);
+        exit(EXIT_FAILURE);
     }
 
     // Extension names to shader types.
@@ -441,7 +649,9 @@ fn ty2json(ty: &Type) -> serde_json::Value {
         _ => json!(ty.to_string()),
     }
 }
-fn entry_point2json(entry_point: &EntryPoint) -> serde_json::Value {
+/// Convert `entry_point` to json. Only variables of the kinds in `only` are
+/// output unless it's empty.
+fn entry_point2json(entry_point: &EntryPoint, only: &[VariableKind]) -> serde_json::Value {
     let mut inputs = Vec::new();
     let mut outputs = Vec::new();
     let mut descs = Vec::new();
@@ -504,17 +714,53 @@ fn entry_point2json(entry_point: &EntryPoint) -> serde_json::Value {
     }
 
     let mut exec_modes = Vec::new();
-    for exec_mode in entry_point.exec_modes.iter() {
+    // Known execution modes are interpreted in declaration order, except
+    // those with operands that can't be evaluated.
+    let mut known_exec_modes = entry_point.exec_modes.iter().peekable();
+    for raw_exec_mode in entry_point.raw_exec_modes.iter() {
+        let kind = match raw_exec_mode.known_exec_mode() {
+            Some(x) => x,
+            None => {
+                let j = json!({
+                    "ExecutionMode": "Unknown",
+                    "Value": raw_exec_mode.exec_mode,
+                    "RawOperands": raw_exec_mode.operands,
+                    "IsIdOperands": raw_exec_mode.is_id,
+                });
+                exec_modes.push(j);
+                continue;
+            }
+        };
+        let exec_mode = match known_exec_modes.next_if(|x| x.exec_mode == kind) {
+            Some(x) => x,
+            None => {
+                let j = json!({
+                    "ExecutionMode": format!("{:?}", kind),
+                    "RawOperands": raw_exec_mode.operands,
+                    "IsIdOperands": raw_exec_mode.is_id,
+                });
+                exec_modes.push(j);
+                continue;
+            }
+        };
         let operands = exec_mode
             .operands
             .iter()
             .map(|operand| {
-                let value = match operand.value {
+                let value = match &operand.value {
                     ConstantValue::Bool(x) => x.to_string(),
+                    ConstantValue::S8(x) => x.to_string(),
+                    ConstantValue::S16(x) => x.to_string(),
                     ConstantValue::S32(x) => x.to_string(),
+                    ConstantValue::S64(x) => x.to_string(),
+                    ConstantValue::U8(x) => x.to_string(),
+                    ConstantValue::U16(x) => x.to_string(),
                     ConstantValue::U32(x) => x.to_string(),
+                    ConstantValue::U64(x) => x.to_string(),
+                    ConstantValue::F16(x) => x.to_string(),
                     ConstantValue::F32(x) => x.to_string(),
-                    _ => todo!(),
+                    ConstantValue::F64(x) => x.to_string(),
+                    x => format!("{:?}", x),
                 };
                 json!({
                     "Value": value,
@@ -529,61 +775,237 @@ fn entry_point2json(entry_point: &EntryPoint) -> serde_json::Value {
         exec_modes.push(j);
     }
 
-    json!({
+    let mut vars = serde_json::Map::new();
+    let sections = [
+        (VariableKind::Inputs, "Inputs", inputs),
+        (VariableKind::Outputs, "Outputs", outputs),
+        (VariableKind::Descriptors, "Descriptors", descs),
+        (VariableKind::PushConstants, "PushConstants", push_consts),
+        (VariableKind::SpecConstants, "SpecConstants", spec_consts),
+    ];
+    for (kind, key, section) in sections {
+        if only.is_empty() || only.contains(&kind) {
+            vars.insert(key.to_owned(), section.into());
+        }
+    }
+
+    let resolved_exec_modes = schema::resolve_exec_modes(&exec_modes);
+    schema::tag(json!({
         "EntryPoint": entry_point.name,
         "ExecutionModel": format!("{:?}", entry_point.exec_model),
         "ExecutionModes": exec_modes,
-        "Variables": {
-            "Inputs": inputs,
-            "Outputs": outputs,
-            "Descriptors": descs,
-            "PushConstants": push_consts,
-            "SpecConstants": spec_consts
-        },
-    })
+        "ResolvedExecutionModes": resolved_exec_modes,
+        "Variables": vars,
+    }))
 }
 
-fn main() {
-    let args = Args::parse();
+/// Upgrade the reflection JSONs at `in_path`, either pretty-printed or in JSON
+/// Lines.
+fn upgrade_reflection_jsons(in_path: &str, jsonl: bool) -> Vec<String> {
+    let text = match std::fs::read_to_string(in_path) {
+        Ok(x) => x,
+        Err(e) => {
+            writeln!(stderr(), "{e}").unwrap();
+            writeln!(stderr(), "cannot read reflection json: {in_path}").unwrap();
+            exit(EXIT_FAILURE);
+        }
+    };
+    let mut outputs = Vec::new();
+    for j in serde_json::Deserializer::from_str(&text).into_iter::<serde_json::Value>() {
+        let j = j.map_err(|e| e.to_string()).and_then(schema::upgrade);
+        let j = match j {
+            Ok(x) => x,
+            Err(e) => {
+                writeln!(stderr(), "{e}").unwrap();
+                writeln!(stderr(), "cannot upgrade reflection json: {in_path}").unwrap();
+                exit(EXIT_VALIDATION_FAILED);
+            }
+        };
+        let output = if jsonl {
+            serde_json::to_string(&j).unwrap()
+        } else {
+            serde_json::to_string_pretty(&j).unwrap()
+        };
+        outputs.push(output);
+    }
+    outputs
+}
 
-    let in_path: &str = &args.in_path;
+fn write_outputs(outputs: Vec<String>, out_path: Option<&str>) {
+    if let Some(out_path) = out_path {
+        let mut f = match File::create(out_path) {
+            Ok(x) => x,
+            Err(e) => {
+                writeln!(stderr(), "{e}").unwrap();
+                writeln!(stderr(), "cannot create output file: {out_path}").unwrap();
+                exit(EXIT_FAILURE);
+            }
+        };
+        for output in outputs {
+            if let Err(e) = writeln!(f, "{output}") {
+                writeln!(stderr(), "{e}").unwrap();
+                writeln!(stderr(), "cannot write to output file: {out_path}").unwrap();
+                exit(EXIT_FAILURE);
+            };
+        }
+    } else {
+        for output in outputs {
+            println!("{output}");
+        }
+    }
+}
+
+fn write_spirv_binary(spv: &SpirvBinary, out_path: Option<&str>) {
+    let bytes = spv
+        .words()
+        .iter()
+        .flat_map(|x| x.to_le_bytes())
+        .collect::<Vec<_>>();
+    let res = if let Some(out_path) = out_path {
+        std::fs::write(out_path, bytes)
+    } else {
+        std::io::stdout().write_all(&bytes)
+    };
+    if let Err(e) = res {
+        writeln!(stderr(), "{e}").unwrap();
+        writeln!(stderr(), "cannot write patched spirv binary").unwrap();
+        exit(EXIT_FAILURE);
+    }
+}
 
-    let spv = get_spirv_bianry(in_path, &args);
+fn reflect_entry_points(in_path: &str, args: &Args) -> Vec<EntryPoint> {
+    let spv = get_spirv_bianry(in_path, args);
+    if let Err(e) = spv.quick_scan() {
+        writeln!(stderr(), "{e}").unwrap();
+        writeln!(stderr(), "invalid spirv binary: {in_path}").unwrap();
+        exit(EXIT_VALIDATION_FAILED);
+    }
     let mut reflect_cfg = ReflectConfig::new();
     reflect_cfg
         .spv(spv)
         .ref_all_rscs(args.reference_all_resources)
         .combine_img_samplers(args.combine_image_samplers)
         .gen_unique_names(args.generate_unique_names);
-    let entry_points = match reflect_cfg.reflect() {
+    for spec in args.spec.iter() {
+        let (spec_id, value) = parse_spec(spec).unwrap_or_else(|| {
+            writeln!(stderr(), "invalid specialization: {spec}").unwrap();
+            exit(EXIT_FAILURE);
+        });
+        reflect_cfg.specialize(spec_id, value);
+    }
+    match reflect_cfg.reflect() {
         Ok(x) => x,
         Err(e) => {
             writeln!(stderr(), "{e}").unwrap();
             writeln!(stderr(), "cannot reflect spirv: {in_path}").unwrap();
-            exit(-1);
+            exit(EXIT_REFLECT_FAILED);
         }
-    };
+    }
+}
 
-    for entry_point in entry_points {
-        let j = entry_point2json(&entry_point);
-        let json = serde_json::to_string_pretty(&j).unwrap();
+fn main() {
+    let args = Args::try_parse().unwrap_or_else(|e| {
+        // Don't let usage errors collide with reflection failures.
+        e.print().unwrap();
+        exit(if e.use_stderr() { EXIT_FAILURE } else { 0 })
+    });
 
-        if let Some(ref out_path) = args.out_path {
-            let mut f = match File::create(out_path) {
+    let (in_path, output_kind) = match &args.command {
+        Some(Command::Stubs { in_path, lang }) => (in_path.as_str(), OutputKind::Stubs(*lang)),
+        Some(Command::Header { in_path }) => (in_path.as_str(), OutputKind::Header),
+        Some(Command::Wgsl { in_path }) => (in_path.as_str(), OutputKind::Wgsl),
+        Some(Command::Markdown { in_path }) => (in_path.as_str(), OutputKind::Markdown),
+        Some(Command::Upgrade { in_path, jsonl }) => {
+            let outputs = upgrade_reflection_jsons(in_path, *jsonl);
+            write_outputs(outputs, args.out_path.as_deref());
+            return;
+        }
+        Some(Command::Patch {
+            in_path,
+            point_size,
+        }) => {
+            let spv = get_spirv_bianry(in_path, &args);
+            let patched = match Patcher::new().inject_point_size(*point_size).patch(&spv) {
                 Ok(x) => x,
                 Err(e) => {
                     writeln!(stderr(), "{e}").unwrap();
-                    writeln!(stderr(), "cannot create output file: {out_path}").unwrap();
-                    exit(-1);
+                    writeln!(stderr(), "cannot patch spirv: {in_path}").unwrap();
+                    exit(EXIT_REFLECT_FAILED);
                 }
             };
-            if let Err(e) = writeln!(f, "{json}") {
-                writeln!(stderr(), "{e}").unwrap();
-                writeln!(stderr(), "cannot write to output file: {out_path}").unwrap();
-                exit(-1);
+            write_spirv_binary(&patched, args.out_path.as_deref());
+            return;
+        }
+        Some(Command::Layout { in_paths }) => {
+            let mut entry_points = Vec::new();
+            for in_path in in_paths {
+                entry_points.extend(reflect_entry_points(in_path, &args));
+            }
+            let layout_desc = match LayoutDescription::from_entry_points(&entry_points) {
+                Ok(x) => x,
+                Err(e) => {
+                    writeln!(stderr(), "{e}").unwrap();
+                    writeln!(stderr(), "cannot merge descriptor set layouts").unwrap();
+                    exit(EXIT_REFLECT_FAILED);
+                }
             };
-        } else {
-            println!("{}", serde_json::to_string_pretty(&j).unwrap());
+            let output = layout_desc.to_yaml().trim_end().to_owned();
+            write_outputs(vec![output], args.out_path.as_deref());
+            return;
         }
+        None => (args.in_path.as_deref().unwrap(), OutputKind::Json),
+    };
+
+    let entry_points = reflect_entry_points(in_path, &args);
+
+    let mut outputs = Vec::with_capacity(entry_points.len());
+    for mut entry_point in entry_points {
+        if let Some(key) = args.sort {
+            sort_vars(&mut entry_point.vars, key);
+        }
+        let output = match output_kind {
+            OutputKind::Stubs(lang) => {
+                let lang = match lang {
+                    Lang::Glsl => StubLanguage::Glsl,
+                    Lang::Hlsl => StubLanguage::Hlsl,
+                };
+                match generate_stubs(&entry_point, lang) {
+                    Ok(x) => x,
+                    Err(e) => {
+                        writeln!(stderr(), "{e}").unwrap();
+                        writeln!(stderr(), "cannot generate stubs: {in_path}").unwrap();
+                        exit(EXIT_REFLECT_FAILED);
+                    }
+                }
+            }
+            OutputKind::Header => match generate_c_header(&entry_point) {
+                Ok(x) => x,
+                Err(e) => {
+                    writeln!(stderr(), "{e}").unwrap();
+                    writeln!(stderr(), "cannot generate header: {in_path}").unwrap();
+                    exit(EXIT_REFLECT_FAILED);
+                }
+            },
+            OutputKind::Wgsl => match generate_wgsl(&entry_point) {
+                Ok(x) => x,
+                Err(e) => {
+                    writeln!(stderr(), "{e}").unwrap();
+                    writeln!(stderr(), "cannot generate wgsl: {in_path}").unwrap();
+                    exit(EXIT_REFLECT_FAILED);
+                }
+            },
+            OutputKind::Markdown => generate_markdown(&entry_point),
+            OutputKind::Json => {
+                let j = entry_point2json(&entry_point, &args.only);
+                if args.jsonl {
+                    serde_json::to_string(&j).unwrap()
+                } else {
+                    serde_json::to_string_pretty(&j).unwrap()
+                }
+            }
+        };
+        outputs.push(output);
     }
+
+    write_outputs(outputs, args.out_path.as_deref());
 }
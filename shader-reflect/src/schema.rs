@@ -0,0 +1,163 @@
+//! Versioning of the reflection JSON output.
+//!
+//! The schema version has a major and a minor number. The minor number is
+//! bumped when fields are added, so a reader can read any output of the same
+//! major version by ignoring the fields it doesn't know. The major number is
+//! bumped when fields are removed, renamed or change their meanings. Outputs
+//! of older schemas can be upgraded with [`upgrade`].
+use std::{fmt, str::FromStr};
+
+use serde_json::{Map, Value};
+
+/// Key of the schema version in every reflected entry point.
+pub const SCHEMA_VERSION_KEY: &str = "SchemaVersion";
+
+/// Version of the reflection JSON schema.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ReflectionSchema {
+    pub major: u32,
+    pub minor: u32,
+}
+impl ReflectionSchema {
+    /// Schema of the output emitted by this version of `shader-reflect`.
+    pub const CURRENT: ReflectionSchema = ReflectionSchema { major: 1, minor: 2 };
+    /// Schema of the output emitted before the schema was versioned, which
+    /// has no `SchemaVersion` field.
+    pub const UNVERSIONED: ReflectionSchema = ReflectionSchema { major: 1, minor: 0 };
+
+    /// Get the schema of a reflected entry point.
+    pub fn of(j: &Value) -> Result<Self, String> {
+        let obj = j
+            .as_object()
+            .ok_or_else(|| "reflected entry point is not a json object".to_owned())?;
+        match obj.get(SCHEMA_VERSION_KEY) {
+            Some(Value::String(x)) => x.parse(),
+            Some(x) => Err(format!("invalid schema version {x}")),
+            None => Ok(Self::UNVERSIONED),
+        }
+    }
+    /// Whether output of schema `other` can be read by a reader of this
+    /// schema.
+    pub fn can_read(&self, other: &ReflectionSchema) -> bool {
+        self.major == other.major
+    }
+}
+impl fmt::Display for ReflectionSchema {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+impl FromStr for ReflectionSchema {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid schema version {s:?}");
+        let (major, minor) = s.split_once('.').ok_or_else(invalid)?;
+        let major = major.parse().map_err(|_| invalid())?;
+        let minor = minor.parse().map_err(|_| invalid())?;
+        Ok(ReflectionSchema { major, minor })
+    }
+}
+
+/// Tag a reflected entry point with the current schema version. The version
+/// is the first field so it can be checked before the rest is parsed.
+pub fn tag(j: Value) -> Value {
+    tag_with(j, ReflectionSchema::CURRENT)
+}
+fn tag_with(j: Value, schema: ReflectionSchema) -> Value {
+    match j {
+        Value::Object(obj) => {
+            let mut out = Map::with_capacity(obj.len() + 1);
+            out.insert(
+                SCHEMA_VERSION_KEY.to_owned(),
+                Value::String(schema.to_string()),
+            );
+            out.extend(obj.into_iter().filter(|(k, _)| k != SCHEMA_VERSION_KEY));
+            Value::Object(out)
+        }
+        j => j,
+    }
+}
+
+/// Upgrade a reflected entry point emitted by an older `shader-reflect` to
+/// the current schema. Outputs of newer minor versions are returned as-is.
+pub fn upgrade(j: Value) -> Result<Value, String> {
+    let schema = ReflectionSchema::of(&j)?;
+    let current = ReflectionSchema::CURRENT;
+    if !current.can_read(&schema) {
+        return Err(format!(
+            "schema version {schema} is incompatible with {current}"
+        ));
+    }
+    if schema >= current {
+        return Ok(j);
+    }
+    // 1.0 -> 1.1: Only `SchemaVersion` is added.
+    // 1.1 -> 1.2: `ResolvedExecutionModes` is derived from `ExecutionModes`.
+    let j = match j {
+        Value::Object(obj) => {
+            let mut out = Map::with_capacity(obj.len() + 1);
+            for (k, v) in obj {
+                let is_exec_modes = k == EXEC_MODES_KEY;
+                if k != RESOLVED_EXEC_MODES_KEY {
+                    out.insert(k, v);
+                }
+                if is_exec_modes {
+                    let exec_modes = out[EXEC_MODES_KEY].as_array().cloned().unwrap_or_default();
+                    out.insert(
+                        RESOLVED_EXEC_MODES_KEY.to_owned(),
+                        resolve_exec_modes(&exec_modes),
+                    );
+                }
+            }
+            Value::Object(out)
+        }
+        j => j,
+    };
+    Ok(tag_with(j, current))
+}
+
+/// Key of the interpreted execution modes in every reflected entry point.
+pub const EXEC_MODES_KEY: &str = "ExecutionModes";
+/// Key of the resolved execution mode operands in every reflected entry point.
+pub const RESOLVED_EXEC_MODES_KEY: &str = "ResolvedExecutionModes";
+
+/// Resolve the operands of interpreted execution modes to json numbers, keyed
+/// by the execution mode names. The operands are the specialized values, or
+/// the default values of unspecialized specialization constants. `*Id`
+/// execution modes are keyed by their literal counterparts, e.g., `LocalSizeId`
+/// by `LocalSize`. Execution modes without operands are skipped.
+pub fn resolve_exec_modes(exec_modes: &[Value]) -> Value {
+    let mut out = Map::new();
+    for exec_mode in exec_modes {
+        let name = match exec_mode.get("ExecutionMode").and_then(Value::as_str) {
+            Some(x) => x,
+            None => continue,
+        };
+        let operands = match exec_mode.get("Operands").and_then(Value::as_array) {
+            Some(x) if !x.is_empty() => x,
+            _ => continue,
+        };
+        let values = operands
+            .iter()
+            .map(|x| {
+                let value = x.get("Value")?.as_str()?;
+                match serde_json::from_str(value).ok()? {
+                    x @ Value::Number(_) | x @ Value::Bool(_) => Some(x),
+                    _ => None,
+                }
+            })
+            .collect::<Option<Vec<_>>>();
+        let mut values = match values {
+            Some(x) => x,
+            None => continue,
+        };
+        let value = if values.len() == 1 {
+            values.remove(0)
+        } else {
+            Value::Array(values)
+        };
+        let name = name.strip_suffix("Id").unwrap_or(name);
+        out.insert(name.to_owned(), value);
+    }
+    Value::Object(out)
+}
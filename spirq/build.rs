@@ -0,0 +1,316 @@
+//! Generate SPIR-V instruction grammar tables from the vendored
+//! `spirv.core.grammar.json`.
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+use serde_json::Value;
+
+const GRAMMAR_PATH: &str = "grammar/spirv.core.grammar.json";
+
+/// Instructions SPIR-Q frequently looks into, given typed views in
+/// `crate::instr`.
+const TYPED_OPS: &[&str] = &[
+    "OpCapability",
+    "OpExtInstImport",
+    "OpExtension",
+    "OpExtInst",
+    "OpMemoryModel",
+    "OpEntryPoint",
+    "OpString",
+    "OpName",
+    "OpMemberName",
+    "OpModuleProcessed",
+    "OpDecorate",
+    "OpMemberDecorate",
+    "OpTypeVoid",
+    "OpTypeBool",
+    "OpTypeInt",
+    "OpTypeFloat",
+    "OpTypeVector",
+    "OpTypeMatrix",
+    "OpTypeImage",
+    "OpTypeSampler",
+    "OpTypeSampledImage",
+    "OpTypeArray",
+    "OpTypeRuntimeArray",
+    "OpTypeStruct",
+    "OpTypePointer",
+    "OpTypeForwardPointer",
+    "OpTypeAccelerationStructureKHR",
+    "OpTypeRayQueryKHR",
+    "OpTypeCooperativeMatrixKHR",
+    "OpConstant",
+    "OpConstantSampler",
+    "OpConstantComposite",
+    "OpSpecConstant",
+    "OpSpecConstantOp",
+    "OpVariable",
+    "OpFunction",
+    "OpFunctionParameter",
+    "OpFunctionCall",
+    "OpLoad",
+    "OpStore",
+    "OpAccessChain",
+];
+/// Enum kinds kept as raw words so that enumerants added after the SPIR-V
+/// headers SPIR-Q is built with don't fail the module.
+const RAW_ENUM_KINDS: &[&str] = &["Capability"];
+
+fn parse_value(x: &Value) -> u32 {
+    match x {
+        Value::Number(x) => x.as_u64().unwrap() as u32,
+        Value::String(x) => u32::from_str_radix(x.trim_start_matches("0x"), 16).unwrap(),
+        _ => panic!("unexpected enumerant value {}", x),
+    }
+}
+fn quantifier(x: &Value) -> &'static str {
+    match x["quantifier"].as_str() {
+        None => "One",
+        Some("?") => "Optional",
+        Some("*") => "Variadic",
+        Some(x) => panic!("unexpected quantifier {}", x),
+    }
+}
+fn operand_specs(operands: Option<&Value>) -> String {
+    let mut out = String::new();
+    for operand in operands.and_then(Value::as_array).into_iter().flatten() {
+        write!(
+            out,
+            "OperandSpec {{ kind: OperandKind::{}, quantifier: Quantifier::{} }}, ",
+            operand["kind"].as_str().unwrap(),
+            quantifier(operand)
+        )
+        .unwrap();
+    }
+    format!("&[{}]", out)
+}
+
+fn snake_case(x: &str) -> String {
+    let mut out = String::new();
+    for (i, c) in x.chars().enumerate() {
+        if c.is_ascii_uppercase() && i > 0 {
+            out.push('_');
+        }
+        out.push(c.to_ascii_lowercase());
+    }
+    out
+}
+/// Field name of an operand. Operand names like `'Member 0 type', +\n...` of
+/// variadic operands are reduced to the first name without the index.
+fn field_name(operand: &Value, kind: &str, category: &str) -> String {
+    let name = match operand["name"].as_str() {
+        Some(name) => {
+            let name = name.split(',').next().unwrap().trim_matches('\'');
+            name.split(' ')
+                .filter(|x| !x.chars().all(|c| c.is_ascii_digit()))
+                .map(|x| x.to_ascii_lowercase())
+                .collect::<Vec<_>>()
+                .join("_")
+        }
+        None if category == "Id" => snake_case(kind.trim_start_matches("Id")),
+        None => snake_case(kind),
+    };
+    if category != "Id" {
+        return name;
+    }
+    match quantifier(operand) {
+        "Variadic" => {
+            let singular = name
+                .strip_suffix("es")
+                .filter(|x| x.ends_with('x'))
+                .or_else(|| name.strip_suffix('s'))
+                .unwrap_or(&name);
+            format!("{}_ids", singular)
+        }
+        _ => format!("{}_id", name),
+    }
+}
+/// Typed views of `TYPED_OPS` as an invocation of `define_ops!`.
+fn typed_ops(instrs: &[Value], operand_kinds: &[Value]) -> String {
+    let mut out = String::new();
+    writeln!(out, "// Generated by `build.rs`. DO NOT EDIT.").unwrap();
+    writeln!(out, "define_ops! {{").unwrap();
+    for opname in TYPED_OPS {
+        let instr = instrs
+            .iter()
+            .find(|x| x["opname"] == *opname)
+            .unwrap_or_else(|| panic!("unknown instruction {}", opname));
+        let operands = instr["operands"].as_array().cloned().unwrap_or_default();
+        writeln!(out, "    {} {{", opname).unwrap();
+        for (i, operand) in operands.iter().enumerate() {
+            let kind = operand["kind"].as_str().unwrap();
+            let operand_kind = operand_kinds.iter().find(|x| x["kind"] == kind).unwrap();
+            let category = operand_kind["category"].as_str().unwrap();
+            let name = field_name(operand, kind, category);
+            let (ty, read_fn) = match (quantifier(operand), category, kind) {
+                ("Variadic", _, _) | (_, _, "LiteralContextDependentNumber") => {
+                    ("&'a [u32]", "read_list")
+                }
+                ("Optional", "Id" | "Literal" | "BitEnum" | "ValueEnum", _) => {
+                    ("Option<u32>", "read_opt_u32")
+                }
+                ("One", _, "LiteralString") => ("&'a str", "read_str"),
+                ("One", "ValueEnum", _) if !RAW_ENUM_KINDS.contains(&kind) => (kind, "read_enum"),
+                ("One", "Id" | "Literal" | "BitEnum" | "ValueEnum", _) => ("u32", "read_u32"),
+                x => panic!("unsupported operand {:?} of {}", x, opname),
+            };
+            writeln!(out, "        {}: {} = {}(),", name, ty, read_fn).unwrap();
+            // Enumerant parameters follow their enumerants, and operands of
+            // the opcode wrapped by `OpSpecConstantOp` follow the last
+            // operand.
+            let has_params = operand_kind["enumerants"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .any(|x| x.get("parameters").is_some());
+            let is_last = i + 1 == operands.len();
+            if has_params {
+                let value = match read_fn {
+                    "read_enum" => format!("{} as u32", name),
+                    "read_opt_u32" => format!("{}.unwrap_or(0)", name),
+                    _ => name.clone(),
+                };
+                writeln!(
+                    out,
+                    "        {}_params: &'a [u32] = read_enum_params({}, {}),",
+                    name, value, kind
+                )
+                .unwrap();
+            } else if is_last && kind == "LiteralSpecConstantOpInteger" {
+                writeln!(out, "        operands: &'a [u32] = read_list(),").unwrap();
+            }
+        }
+        writeln!(out, "    }}").unwrap();
+    }
+    writeln!(out, "}}").unwrap();
+    out
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed={}", GRAMMAR_PATH);
+    println!("cargo:rerun-if-changed=build.rs");
+
+    let grammar = fs::read_to_string(GRAMMAR_PATH).unwrap();
+    let grammar: Value = serde_json::from_str(&grammar).unwrap();
+    let operand_kinds = grammar["operand_kinds"].as_array().unwrap();
+    let instrs = grammar["instructions"].as_array().unwrap();
+    let typed_ops = typed_ops(instrs, operand_kinds);
+
+    let mut out = String::new();
+    writeln!(out, "// Generated by `build.rs`. DO NOT EDIT.").unwrap();
+
+    // Operand kinds.
+    writeln!(out, "/// SPIR-V operand kinds.").unwrap();
+    writeln!(out, "#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]").unwrap();
+    writeln!(out, "pub enum OperandKind {{").unwrap();
+    for operand_kind in operand_kinds {
+        writeln!(out, "    {},", operand_kind["kind"].as_str().unwrap()).unwrap();
+    }
+    writeln!(out, "}}").unwrap();
+
+    writeln!(out, "impl OperandKind {{").unwrap();
+    writeln!(out, "    /// Category of the operand kind.").unwrap();
+    writeln!(out, "    pub fn category(&self) -> OperandCategory {{").unwrap();
+    writeln!(out, "        match self {{").unwrap();
+    for operand_kind in operand_kinds {
+        writeln!(
+            out,
+            "            OperandKind::{} => OperandCategory::{},",
+            operand_kind["kind"].as_str().unwrap(),
+            operand_kind["category"].as_str().unwrap()
+        )
+        .unwrap();
+    }
+    writeln!(out, "        }}").unwrap();
+    writeln!(out, "    }}").unwrap();
+
+    writeln!(
+        out,
+        "    /// Constituent kinds of a composite operand kind."
+    )
+    .unwrap();
+    writeln!(out, "    pub fn bases(&self) -> &'static [OperandKind] {{").unwrap();
+    writeln!(out, "        match self {{").unwrap();
+    for operand_kind in operand_kinds {
+        if let Some(bases) = operand_kind["bases"].as_array() {
+            let bases = bases
+                .iter()
+                .map(|x| format!("OperandKind::{}", x.as_str().unwrap()))
+                .collect::<Vec<_>>()
+                .join(", ");
+            writeln!(
+                out,
+                "            OperandKind::{} => &[{}],",
+                operand_kind["kind"].as_str().unwrap(),
+                bases
+            )
+            .unwrap();
+        }
+    }
+    writeln!(out, "            _ => &[],").unwrap();
+    writeln!(out, "        }}").unwrap();
+    writeln!(out, "    }}").unwrap();
+
+    writeln!(
+        out,
+        "    /// Extra operands following an enumerant. For bit enums, `value` must"
+    )
+    .unwrap();
+    writeln!(out, "    /// be a single bit.").unwrap();
+    writeln!(
+        out,
+        "    pub fn enumerant_params(&self, value: u32) -> &'static [OperandSpec] {{"
+    )
+    .unwrap();
+    writeln!(out, "        match (self, value) {{").unwrap();
+    for operand_kind in operand_kinds {
+        // Aliased enumerants share a same value so only take the first one.
+        let mut params = BTreeMap::new();
+        for enumerant in operand_kind["enumerants"].as_array().into_iter().flatten() {
+            let value = parse_value(&enumerant["value"]);
+            if enumerant.get("parameters").is_some() {
+                params
+                    .entry(value)
+                    .or_insert_with(|| operand_specs(enumerant.get("parameters")));
+            }
+        }
+        for (value, params) in params {
+            writeln!(
+                out,
+                "            (OperandKind::{}, {}) => {},",
+                operand_kind["kind"].as_str().unwrap(),
+                value,
+                params
+            )
+            .unwrap();
+        }
+    }
+    writeln!(out, "            _ => &[],").unwrap();
+    writeln!(out, "        }}").unwrap();
+    writeln!(out, "    }}").unwrap();
+    writeln!(out, "}}").unwrap();
+
+    // Instructions, sorted by opcode.
+    let mut instrs = instrs.iter().collect::<Vec<_>>();
+    instrs.sort_by_key(|x| x["opcode"].as_u64().unwrap());
+    writeln!(out, "static INSTR_SPECS: &[InstructionSpec] = &[").unwrap();
+    for instr in instrs {
+        writeln!(
+            out,
+            "    InstructionSpec {{ opname: {:?}, opcode: {}, class: {:?}, operands: {} }},",
+            instr["opname"].as_str().unwrap(),
+            instr["opcode"].as_u64().unwrap(),
+            instr["class"].as_str().unwrap(),
+            operand_specs(instr.get("operands"))
+        )
+        .unwrap();
+    }
+    writeln!(out, "];").unwrap();
+
+    let out_dir = env::var("OUT_DIR").unwrap();
+    fs::write(Path::new(&out_dir).join("grammar.rs"), out).unwrap();
+    fs::write(Path::new(&out_dir).join("instr.rs"), typed_ops).unwrap();
+}
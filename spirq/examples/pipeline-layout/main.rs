@@ -0,0 +1,136 @@
+//! Build the create infos of a `VkPipelineLayout` from the reflection of a
+//! vertex and a fragment stage, with `ash`.
+use ash::vk;
+use inline_spirv::inline_spirv;
+use spirq::layout_desc::{LayoutDescription, LayoutSet};
+use spirq::prelude::*;
+
+static VERT_SPV: &[u32] = inline_spirv!(
+    r#"
+    #version 450 core
+    layout(set = 0, binding = 0) uniform Camera {
+        mat4 view_proj;
+    } camera;
+    layout(push_constant) uniform Model {
+        mat4 model;
+    } model;
+    layout(location = 0) in vec3 pos;
+    layout(location = 1) in vec2 uv;
+    layout(location = 0) out vec2 v_uv;
+    void main() {
+        v_uv = uv;
+        gl_Position = camera.view_proj * model.model * vec4(pos, 1.0);
+    }
+    "#,
+    vert,
+    vulkan1_2
+);
+static FRAG_SPV: &[u32] = inline_spirv!(
+    r#"
+    #version 450 core
+    layout(set = 1, binding = 0) uniform sampler2D albedo;
+    layout(push_constant) uniform Material {
+        layout(offset = 64) vec4 tint;
+    } material;
+    layout(location = 0) in vec2 v_uv;
+    layout(location = 0) out vec4 color;
+    void main() {
+        color = texture(albedo, v_uv) * material.tint;
+    }
+    "#,
+    frag,
+    vulkan1_2
+);
+
+/// Bindings of a `VkDescriptorSetLayout`. Sets not used by any stage still
+/// need an empty layout if a set with a greater number is used.
+fn desc_set_layout_bindings(
+    set: Option<&LayoutSet>,
+) -> Vec<vk::DescriptorSetLayoutBinding<'static>> {
+    set.into_iter()
+        .flat_map(|x| x.bindings.iter())
+        .map(|x| {
+            vk::DescriptorSetLayoutBinding::default()
+                .binding(x.binding)
+                .descriptor_type(vk::DescriptorType::from_raw(x.vk_desc_ty() as i32))
+                .descriptor_count(x.count)
+                .stage_flags(vk::ShaderStageFlags::from_raw(x.vk_stage_flags()))
+        })
+        .collect()
+}
+fn push_const_ranges(layout_desc: &LayoutDescription) -> Vec<vk::PushConstantRange> {
+    layout_desc
+        .push_const_ranges
+        .iter()
+        .map(|x| {
+            vk::PushConstantRange::default()
+                .stage_flags(vk::ShaderStageFlags::from_raw(x.vk_stage_flags()))
+                .offset(x.offset)
+                .size(x.size)
+        })
+        .collect()
+}
+
+/// Create the `VkPipelineLayout` and its `VkDescriptorSetLayout`s on
+/// `device`.
+#[allow(dead_code)]
+unsafe fn create_pipeline_layout(
+    device: &ash::Device,
+    layout_desc: &LayoutDescription,
+) -> ash::prelude::VkResult<(Vec<vk::DescriptorSetLayout>, vk::PipelineLayout)> {
+    let nset = layout_desc.sets.last().map_or(0, |x| x.set + 1);
+    let mut set_layouts = Vec::new();
+    for i in 0..nset {
+        let set = layout_desc.sets.iter().find(|x| x.set == i);
+        let bindings = desc_set_layout_bindings(set);
+        let create_info = vk::DescriptorSetLayoutCreateInfo::default().bindings(&bindings);
+        set_layouts.push(device.create_descriptor_set_layout(&create_info, None)?);
+    }
+    let push_const_ranges = push_const_ranges(layout_desc);
+    let create_info = vk::PipelineLayoutCreateInfo::default()
+        .set_layouts(&set_layouts)
+        .push_constant_ranges(&push_const_ranges);
+    let pipeline_layout = device.create_pipeline_layout(&create_info, None)?;
+    Ok((set_layouts, pipeline_layout))
+}
+
+fn main() {
+    let mut entry_points = Vec::new();
+    for spv in [VERT_SPV, FRAG_SPV] {
+        // Lay out all declared resources so that the layout is stable when
+        // the shaders change.
+        let stage_entry_points = ReflectConfig::new()
+            .spv(spv)
+            .ref_all_rscs(true)
+            .reflect()
+            .unwrap();
+        entry_points.extend(stage_entry_points);
+    }
+    // Merge the resources of all stages of the pipeline.
+    let pipeline = PipelineConfig::new().merge(&entry_points).unwrap();
+    let layout_desc = LayoutDescription::from_pipeline(&pipeline);
+
+    let nset = layout_desc.sets.last().map_or(0, |x| x.set + 1);
+    for i in 0..nset {
+        let set = layout_desc.sets.iter().find(|x| x.set == i);
+        let bindings = desc_set_layout_bindings(set);
+        println!("VkDescriptorSetLayoutCreateInfo (set={})", i);
+        for binding in bindings.iter() {
+            println!(
+                "- binding={} descriptorType={:?} descriptorCount={} stageFlags={:?}",
+                binding.binding,
+                binding.descriptor_type,
+                binding.descriptor_count,
+                binding.stage_flags
+            );
+        }
+    }
+    println!("VkPipelineLayoutCreateInfo");
+    println!("- setLayoutCount={}", nset);
+    for range in push_const_ranges(&layout_desc) {
+        println!(
+            "- VkPushConstantRange stageFlags={:?} offset={} size={}",
+            range.stage_flags, range.offset, range.size
+        );
+    }
+}
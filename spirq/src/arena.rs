@@ -0,0 +1,121 @@
+//! Allocation of reflection intermediates.
+//!
+//! The hash tables of [`ReflectIntermediate`] and [`FunctionInspector`] are
+//! allocated by an [`Arena`]. With the `bumpalo` feature, an arena can be
+//! backed by a [`bumpalo::Bump`] passed to [`ReflectConfig::reflect_in`], so
+//! that the intermediates of many modules can be reflected in a reused bump
+//! and released at once. Otherwise, and by default, the tables are ordinary
+//! hash maps allocated by the global allocator.
+//!
+//! Values in the tables, and the registries of `spq-core`, are always
+//! allocated by the global allocator.
+//!
+//! [`ReflectIntermediate`]: crate::reflect::ReflectIntermediate
+//! [`FunctionInspector`]: crate::reflect::FunctionInspector
+//! [`ReflectConfig::reflect_in`]: crate::reflect_cfg::ReflectConfig::reflect_in
+use std::marker::PhantomData;
+
+#[cfg(not(feature = "bumpalo"))]
+use fnv::FnvHasher;
+#[cfg(not(feature = "bumpalo"))]
+use std::{
+    collections::{HashMap, HashSet},
+    hash::BuildHasher,
+};
+
+#[cfg(feature = "bumpalo")]
+use allocator_api2::alloc::{AllocError, Allocator, Global, Layout};
+#[cfg(feature = "bumpalo")]
+use fnv::FnvBuildHasher;
+#[cfg(feature = "bumpalo")]
+use std::ptr::NonNull;
+
+/// Allocator of intermediate tables, either the global allocator or a bump
+/// arena borrowed for `'a`.
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct Arena<'a> {
+    #[cfg(feature = "bumpalo")]
+    bump: Option<&'a bumpalo::Bump>,
+    _marker: PhantomData<&'a ()>,
+}
+impl<'a> Arena<'a> {
+    /// Allocate tables in `bump`.
+    #[cfg(feature = "bumpalo")]
+    pub(crate) fn bump(bump: &'a bumpalo::Bump) -> Self {
+        Arena {
+            bump: Some(bump),
+            _marker: PhantomData,
+        }
+    }
+    pub(crate) fn map<K, V>(self) -> ArenaMap<'a, K, V> {
+        #[cfg(feature = "bumpalo")]
+        return ArenaMap::with_hasher_in(Default::default(), self);
+        #[cfg(not(feature = "bumpalo"))]
+        return ArenaMap::default();
+    }
+    pub(crate) fn set<K>(self) -> ArenaSet<'a, K> {
+        #[cfg(feature = "bumpalo")]
+        return ArenaSet::with_hasher_in(Default::default(), self);
+        #[cfg(not(feature = "bumpalo"))]
+        return ArenaSet::default();
+    }
+    #[cfg(feature = "access-analysis")]
+    pub(crate) fn vec<T>(self) -> ArenaVec<'a, T> {
+        #[cfg(feature = "bumpalo")]
+        return ArenaVec::new_in(self);
+        #[cfg(not(feature = "bumpalo"))]
+        return ArenaVec::new();
+    }
+}
+
+// `Bump` is not `Sync` so arenas borrowing one are neither `Send` nor `Sync`,
+// which would make the futures of `ReflectConfig::reflect_async` not `Send`.
+// Arenas only borrow a bump in `ReflectConfig::reflect_full_in`, where all
+// the tables are created and dropped on the calling thread.
+#[cfg(feature = "bumpalo")]
+unsafe impl Send for Arena<'_> {}
+#[cfg(feature = "bumpalo")]
+unsafe impl Sync for Arena<'_> {}
+#[cfg(feature = "bumpalo")]
+unsafe impl Allocator for Arena<'_> {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        match self.bump {
+            Some(bump) => bump.allocate(layout),
+            None => Global.allocate(layout),
+        }
+    }
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        match self.bump {
+            Some(bump) => bump.deallocate(ptr, layout),
+            None => Global.deallocate(ptr, layout),
+        }
+    }
+}
+
+#[cfg(feature = "bumpalo")]
+pub(crate) type ArenaMap<'a, K, V> = hashbrown::HashMap<K, V, FnvBuildHasher, Arena<'a>>;
+#[cfg(feature = "bumpalo")]
+pub(crate) type ArenaSet<'a, K> = hashbrown::HashSet<K, FnvBuildHasher, Arena<'a>>;
+#[cfg(all(feature = "bumpalo", feature = "access-analysis"))]
+pub(crate) type ArenaVec<'a, T> = allocator_api2::vec::Vec<T, Arena<'a>>;
+
+/// FNV hasher of the tables allocated by the global allocator. It carries the
+/// lifetime of the arena so that the tables have the same lifetime parameters
+/// with and without the `bumpalo` feature.
+#[cfg(not(feature = "bumpalo"))]
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct ArenaHasher<'a>(PhantomData<&'a ()>);
+#[cfg(not(feature = "bumpalo"))]
+impl BuildHasher for ArenaHasher<'_> {
+    type Hasher = FnvHasher;
+    fn build_hasher(&self) -> FnvHasher {
+        FnvHasher::default()
+    }
+}
+
+#[cfg(not(feature = "bumpalo"))]
+pub(crate) type ArenaMap<'a, K, V> = HashMap<K, V, ArenaHasher<'a>>;
+#[cfg(not(feature = "bumpalo"))]
+pub(crate) type ArenaSet<'a, K> = HashSet<K, ArenaHasher<'a>>;
+#[cfg(all(not(feature = "bumpalo"), feature = "access-analysis"))]
+pub(crate) type ArenaVec<'a, T> = Vec<T>;
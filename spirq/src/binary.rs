@@ -0,0 +1,472 @@
+//! SPIR-V binary utilities that don't require a full reflection.
+//!
+//! [`InstrIter`] walks the instructions of a module with their word offsets,
+//! so custom passes can be built on the same parser as the reflection. Each
+//! [`InstrRef`] derefs to a [`Instr`], which can be converted to a typed
+//! instruction or decoded with [`grammar::decode_operands`].
+//!
+//! [`grammar::decode_operands`]: crate::grammar::decode_operands
+use std::{convert::TryFrom, fmt, ops::Deref};
+
+use crate::{
+    entry_point::ExecutionModel,
+    error::{anyhow, Result},
+    grammar::{instr_spec, visit_operands, Operand},
+    instr::*,
+    parse::{Instr, SpirvBinary},
+    spirv::{self, Op},
+};
+
+const SPIRV_MAGIC: u32 = 0x07230203;
+const SPIRV_HEADER_LEN: usize = 5;
+
+/// Entry point declared in a SPIR-V module.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct EntryPointInfo {
+    /// Name of the entry point.
+    pub name: String,
+    /// Entry point execution model.
+    pub exec_model: ExecutionModel,
+}
+
+/// Registered generator tools as `(tool ID, vendor, tool name)`, from the
+/// generator magic number registry in `spir-v.xml` of SPIRV-Headers. Some
+/// vendors registered an ID without naming a tool.
+const GENERATORS: &[(u32, &str, Option<&str>)] = &[
+    (0, "Khronos", None),
+    (1, "LunarG", None),
+    (2, "Valve", None),
+    (3, "Codeplay", None),
+    (4, "NVIDIA", None),
+    (5, "ARM", None),
+    (6, "Khronos", Some("LLVM/SPIR-V Translator")),
+    (7, "Khronos", Some("SPIR-V Tools Assembler")),
+    (8, "Khronos", Some("Glslang Reference Front End")),
+    (9, "Qualcomm", None),
+    (10, "AMD", None),
+    (11, "Intel", None),
+    (12, "Imagination", None),
+    (13, "Google", Some("Shaderc over Glslang")),
+    (14, "Google", Some("spiregg")),
+    (15, "Google", Some("rspirv")),
+    (16, "X-LEGEND", Some("Mesa-IR/SPIR-V Translator")),
+    (17, "Khronos", Some("SPIR-V Tools Linker")),
+    (18, "Wine", Some("VKD3D Shader Compiler")),
+    (19, "Tellusim", Some("Clay Shader Compiler")),
+    (20, "W3C WebGPU Group", Some("WHLSL Shader Translator")),
+    (21, "Google", Some("Clspv")),
+    (22, "Google", Some("MLIR SPIR-V Serializer")),
+    (23, "Google", Some("Tint Compiler")),
+    (24, "Google", Some("ANGLE Shader Compiler")),
+    (25, "Netease Games", Some("Messiah Shader Compiler")),
+    (26, "Xenia", Some("Xenia Emulator Microcode Translator")),
+    (27, "Embark Studios", Some("Rust GPU Compiler Backend")),
+    (28, "gfx-rs community", Some("Naga")),
+    (29, "Mikkosoft Productions", Some("MSP Shader Compiler")),
+    (30, "SpvGenTwo community", Some("SpvGenTwo SPIR-V IR Tools")),
+    (31, "Google", Some("Skia SkSL")),
+    (32, "TornadoVM", Some("Beehive SPIRV Toolkit")),
+    (33, "DragonJoker", Some("ShaderWriter")),
+    (34, "Rayan Hatout", Some("SPIRVSmith")),
+    (35, "Saarland University", Some("Shady")),
+    (36, "Taichi Graphics", Some("Taichi")),
+    (37, "heroseh", Some("Hero C Compiler")),
+    (38, "Meta", Some("SparkSL")),
+    (39, "SirLynix", Some("Nazara ShaderLang Compiler")),
+    (40, "NVIDIA", Some("Slang Compiler")),
+    (41, "Zig Software Foundation", Some("Zig Compiler")),
+    (42, "Rendong Liang", Some("spq")),
+    (43, "LLVM", Some("LLVM SPIR-V Backend")),
+    (44, "Robert Konrad", Some("Kongruent")),
+];
+
+/// Tool that generated a SPIR-V module, decoded from the generator magic
+/// number in the module header.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct GeneratorInfo {
+    /// Registered tool ID in the high 16 bits of the generator magic number.
+    pub tool_id: u32,
+    /// Tool-specific version number in the low 16 bits.
+    pub version: u32,
+    /// Vendor of the tool, or `None` if the tool ID is not registered when
+    /// spirq is released.
+    pub vendor: Option<&'static str>,
+    /// Name of the tool, if the vendor has registered one.
+    pub tool: Option<&'static str>,
+}
+impl GeneratorInfo {
+    /// Decode the generator magic number `generator` in a module header.
+    pub fn new(generator: u32) -> GeneratorInfo {
+        let tool_id = generator >> 16;
+        let entry = GENERATORS.iter().find(|x| x.0 == tool_id);
+        GeneratorInfo {
+            tool_id,
+            version: generator & 0xFFFF,
+            vendor: entry.map(|x| x.1),
+            tool: entry.and_then(|x| x.2),
+        }
+    }
+}
+
+/// SPIR-V version of a module, declared in the module header. Versions are
+/// ordered so a module can be checked against the latest version supported by
+/// a target, e.g., SPIR-V 1.3 for Vulkan 1.1.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SpirvVersion {
+    pub major: u8,
+    pub minor: u8,
+}
+impl SpirvVersion {
+    pub fn new(major: u8, minor: u8) -> SpirvVersion {
+        SpirvVersion { major, minor }
+    }
+    /// Decode the version number `version` in a module header.
+    pub fn from_word(version: u32) -> SpirvVersion {
+        SpirvVersion {
+            major: (version >> 16) as u8,
+            minor: (version >> 8) as u8,
+        }
+    }
+}
+impl fmt::Display for SpirvVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+/// Metadata declared in the header sections of a SPIR-V module.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct ModuleInfo {
+    /// SPIR-V version of the module.
+    pub version: SpirvVersion,
+    /// Upper bound of all result IDs declared in the module header. Every ID
+    /// in the module is in `1..id_bound`.
+    pub id_bound: u32,
+    /// Tool that generated the module.
+    pub generator: GeneratorInfo,
+    /// Capabilities declared by `OpCapability`.
+    pub caps: Vec<spirv::Capability>,
+    /// Raw enumerants of capabilities declared by `OpCapability` but unknown
+    /// to the SPIR-V headers SPIR-Q is built with, e.g., those of vendor
+    /// extensions newer than SPIR-Q.
+    pub unknown_caps: Vec<u32>,
+    /// Entry points declared by `OpEntryPoint`, in declaration order.
+    pub entry_points: Vec<EntryPointInfo>,
+    /// Processes the module went through, like optimizer passes, declared by
+    /// `OpModuleProcessed` in declaration order. Tools are free to choose
+    /// the wording, e.g., glslang records its command line options.
+    pub processes: Vec<String>,
+}
+
+/// Extra queries on [`SpirvBinary`].
+pub trait SpirvBinaryExt {
+    /// Extract the SPIR-V module in `bytes` with the container format
+    /// detected automatically. The following formats are supported:
+    ///
+    /// - Raw SPIR-V binaries in either endianness;
+    /// - DXBC containers with a part holding a SPIR-V module;
+    /// - Any other blob with a SPIR-V module at a 4-byte-aligned offset, e.g.,
+    ///   compiler artifacts with extra debug sections. The module ends at the
+    ///   last complete instruction.
+    ///
+    /// SPIR-V assembly is not supported and has to be assembled beforehand.
+    fn from_container(bytes: &[u8]) -> Result<Self>
+    where
+        Self: Sized;
+    /// Collect module metadata by parsing only the header and the sections
+    /// before annotations. This is much cheaper than a full reflection and is
+    /// useful for indexing large amount of shader modules.
+    fn quick_scan(&self) -> Result<ModuleInfo>;
+    /// Check that every ID referenced by the module is in the ID bound
+    /// declared in the header. Instructions not in the SPIR-V grammar are
+    /// skipped. Returns the ID bound.
+    fn validate_ids(&self) -> Result<u32>;
+    /// Check the module header and get the ID bound it declares.
+    fn id_bound(&self) -> Result<u32>;
+    /// Check the module header and get the SPIR-V version it declares.
+    fn version(&self) -> Result<SpirvVersion>;
+    /// Check the module header and get the tool that generated the module.
+    fn generator(&self) -> Result<GeneratorInfo>;
+    /// Iterate over the instructions after the module header.
+    fn instr_iter(&self) -> Result<InstrIter<'_>>;
+}
+impl SpirvBinaryExt for SpirvBinary {
+    fn from_container(bytes: &[u8]) -> Result<Self> {
+        if bytes.starts_with(b"DXBC") {
+            return extract_dxbc_part(bytes);
+        }
+        if let Some(spv) = find_embedded_spv(bytes) {
+            return Ok(spv);
+        }
+        let is_text = std::str::from_utf8(bytes).is_ok();
+        if is_text && bytes.iter().any(|x| !x.is_ascii_whitespace()) {
+            Err(anyhow!(
+                "spirv assembly must be assembled before reflection"
+            ))
+        } else {
+            Err(anyhow!("no spirv module found in container"))
+        }
+    }
+    fn quick_scan(&self) -> Result<ModuleInfo> {
+        let mut out = ModuleInfo {
+            version: self.version()?,
+            id_bound: self.id_bound()?,
+            generator: self.generator()?,
+            ..Default::default()
+        };
+        let mut instrs = self.instrs()?;
+        while let Some(instr) = instrs.next()? {
+            match Op::from_u32(instr.opcode()) {
+                Some(Op::Capability) => {
+                    let op = OpCapability::try_from(instr)?;
+                    match spirv::Capability::from_u32(op.capability) {
+                        Some(cap) => out.caps.push(cap),
+                        None => out.unknown_caps.push(op.capability),
+                    }
+                }
+                Some(Op::EntryPoint) => {
+                    let op = OpEntryPoint::try_from(instr)?;
+                    let entry_point = EntryPointInfo {
+                        name: op.name.to_owned(),
+                        exec_model: op.execution_model,
+                    };
+                    out.entry_points.push(entry_point);
+                }
+                Some(Op::ModuleProcessed) => {
+                    let op = OpModuleProcessed::try_from(instr)?;
+                    out.processes.push(op.process.to_owned());
+                }
+                Some(Op::Extension)
+                | Some(Op::ExtInstImport)
+                | Some(Op::MemoryModel)
+                | Some(Op::ExecutionMode)
+                | Some(Op::ExecutionModeId)
+                | Some(Op::String)
+                | Some(Op::SourceExtension)
+                | Some(Op::Source)
+                | Some(Op::SourceContinued)
+                | Some(Op::Name)
+                | Some(Op::MemberName) => {}
+                // Module processes are the last debug instructions.
+                _ => break,
+            }
+        }
+        Ok(out)
+    }
+    fn validate_ids(&self) -> Result<u32> {
+        let id_bound = self.id_bound()?;
+        let mut instrs = self.instrs()?;
+        let mut i = 0;
+        while let Some(instr) = instrs.next()? {
+            if let Some(spec) = instr_spec(instr.opcode()) {
+                let mut bad_id = None;
+                // Malformed instructions are reported by the parser later.
+                // Operands following unknown enumerants can't be decoded
+                // either, so only the leading operands are checked.
+                let _ = visit_operands(instr, |x| match x {
+                    Operand::Id(id) if id == 0 || id >= id_bound => {
+                        bad_id.get_or_insert(id);
+                    }
+                    _ => {}
+                });
+                if let Some(id) = bad_id {
+                    return Err(anyhow!(
+                        "{} (instruction #{}) references id {} out of the id bound {}",
+                        spec.opname,
+                        i,
+                        id,
+                        id_bound
+                    ));
+                }
+            }
+            i += 1;
+        }
+        Ok(id_bound)
+    }
+    fn id_bound(&self) -> Result<u32> {
+        let words = self.words();
+        check_header(words)?;
+        Ok(words[3])
+    }
+    fn version(&self) -> Result<SpirvVersion> {
+        let words = self.words();
+        check_header(words)?;
+        Ok(SpirvVersion::from_word(words[1]))
+    }
+    fn generator(&self) -> Result<GeneratorInfo> {
+        let words = self.words();
+        check_header(words)?;
+        Ok(GeneratorInfo::new(words[2]))
+    }
+    fn instr_iter(&self) -> Result<InstrIter<'_>> {
+        InstrIter::new(self.words())
+    }
+}
+
+fn check_header(words: &[u32]) -> Result<()> {
+    if words.len() < SPIRV_HEADER_LEN {
+        return Err(anyhow!("spirv header is truncated"));
+    }
+    if words[0] != SPIRV_MAGIC {
+        return Err(anyhow!("invalid spirv magic number"));
+    }
+    Ok(())
+}
+
+/// An instruction in a SPIR-V module and its position.
+#[derive(Clone, Copy)]
+pub struct InstrRef<'a> {
+    offset: usize,
+    instr: &'a Instr,
+}
+impl<'a> InstrRef<'a> {
+    /// Offset of the first word of the instruction in words, counting from
+    /// the beginning of the module header.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+    /// Offset of the instruction in bytes.
+    pub fn byte_offset(&self) -> usize {
+        self.offset * 4
+    }
+    /// The instruction op, or `None` if the opcode is unknown to the SPIR-V
+    /// headers spirq is built with.
+    pub fn op(&self) -> Option<Op> {
+        Op::from_u32(self.instr.opcode())
+    }
+    /// All words of the instruction, including the first word containing the
+    /// word count and opcode.
+    pub fn words(&self) -> &'a [u32] {
+        self.instr.as_ref()
+    }
+    /// Operand words of the instruction, undecoded.
+    pub fn operands(&self) -> &'a [u32] {
+        &self.words()[1..]
+    }
+    /// The underlying instruction, to be converted to typed instructions.
+    pub fn instr(&self) -> &'a Instr {
+        self.instr
+    }
+}
+impl<'a> Deref for InstrRef<'a> {
+    type Target = Instr;
+    fn deref(&self) -> &Instr {
+        self.instr
+    }
+}
+impl<'a> fmt::Debug for InstrRef<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match instr_spec(self.opcode()) {
+            Some(spec) => write!(f, "{}: {}", self.offset, spec.opname)?,
+            None => write!(f, "{}: <opcode {}>", self.offset, self.opcode())?,
+        }
+        write!(f, " {:?}", self.operands())
+    }
+}
+
+/// Iterator over the instructions of a SPIR-V module. Malformed instructions
+/// are reported as errors, after which the iteration ends.
+#[derive(Clone)]
+pub struct InstrIter<'a> {
+    words: &'a [u32],
+    offset: usize,
+}
+impl<'a> InstrIter<'a> {
+    /// Iterate over the instructions of module `words`, which starts with the
+    /// module header. The header is checked but not yielded.
+    pub fn new(words: &'a [u32]) -> Result<InstrIter<'a>> {
+        check_header(words)?;
+        let out = InstrIter {
+            words,
+            offset: SPIRV_HEADER_LEN,
+        };
+        Ok(out)
+    }
+}
+impl<'a> Iterator for InstrIter<'a> {
+    type Item = Result<InstrRef<'a>>;
+    fn next(&mut self) -> Option<Self::Item> {
+        let offset = self.offset;
+        let head = *self.words.get(offset)?;
+        let word_count = (head >> 16) as usize;
+        let err = if word_count == 0 {
+            anyhow!("instruction at word {} has zero length", offset)
+        } else if let Some(words) = self.words.get(offset..offset + word_count) {
+            self.offset += word_count;
+            let instr = Instr::new(words).map(|instr| InstrRef { offset, instr });
+            return Some(instr);
+        } else {
+            anyhow!("instruction at word {} is truncated", offset)
+        };
+        // Nothing after a malformed instruction can be located.
+        self.offset = self.words.len();
+        Some(Err(err))
+    }
+}
+
+fn read_u32_le(bytes: &[u8], offset: usize) -> Option<u32> {
+    let word = bytes.get(offset..offset.checked_add(4)?)?;
+    Some(u32::from_le_bytes([word[0], word[1], word[2], word[3]]))
+}
+/// Find a SPIR-V module in a DXBC container. The container header is followed
+/// by a table of part offsets, each part starting with a four-character code
+/// and the part size.
+fn extract_dxbc_part(bytes: &[u8]) -> Result<SpirvBinary> {
+    const DXBC_PART_COUNT_OFFSET: usize = 28;
+    let truncated = || anyhow!("dxbc container is truncated");
+    let overflow = || anyhow!("dxbc part offset overflows");
+    let npart = read_u32_le(bytes, DXBC_PART_COUNT_OFFSET).ok_or_else(truncated)? as usize;
+    for i in 0..npart {
+        let entry_offset = i
+            .checked_mul(4)
+            .and_then(|x| x.checked_add(DXBC_PART_COUNT_OFFSET + 4))
+            .ok_or_else(overflow)?;
+        let part_offset = read_u32_le(bytes, entry_offset).ok_or_else(truncated)? as usize;
+        let size_offset = part_offset.checked_add(4).ok_or_else(overflow)?;
+        let part_size = read_u32_le(bytes, size_offset).ok_or_else(truncated)? as usize;
+        let begin = part_offset.checked_add(8).ok_or_else(overflow)?;
+        let end = begin.checked_add(part_size).ok_or_else(overflow)?;
+        let part = bytes.get(begin..end).ok_or_else(truncated)?;
+        if let Some(spv) = find_embedded_spv(part) {
+            return Ok(spv);
+        }
+    }
+    Err(anyhow!("no spirv module found in dxbc container"))
+}
+/// Find the first SPIR-V module at a 4-byte-aligned offset in `bytes`.
+fn find_embedded_spv(bytes: &[u8]) -> Option<SpirvBinary> {
+    let magic_le = SPIRV_MAGIC.to_le_bytes();
+    let magic_be = SPIRV_MAGIC.to_be_bytes();
+    (0..bytes.len() / 4).map(|i| i * 4).find_map(|offset| {
+        let head = bytes.get(offset..offset + 4)?;
+        let read_word: fn([u8; 4]) -> u32 = if head == magic_le {
+            u32::from_le_bytes
+        } else if head == magic_be {
+            u32::from_be_bytes
+        } else {
+            return None;
+        };
+        let words = bytes[offset..]
+            .chunks_exact(4)
+            .map(|x| read_word([x[0], x[1], x[2], x[3]]))
+            .collect::<Vec<_>>();
+        trim_instrs(words)
+    })
+}
+/// Drop anything after the last complete instruction.
+fn trim_instrs(mut words: Vec<u32>) -> Option<SpirvBinary> {
+    if words.len() < SPIRV_HEADER_LEN {
+        return None;
+    }
+    let mut len = SPIRV_HEADER_LEN;
+    while let Some(head) = words.get(len) {
+        let word_count = (head >> 16) as usize;
+        if word_count == 0 || len + word_count > words.len() {
+            break;
+        }
+        len += word_count;
+    }
+    words.truncate(len);
+    Some(SpirvBinary::from(words))
+}
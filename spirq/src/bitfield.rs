@@ -0,0 +1,228 @@
+//! User annotations of bitfields packed in integer members of blocks.
+//!
+//! SPIR-V has no bitfield types, so flags and small values packed into a
+//! `uint` are reflected as a plain integer. An annotation file declares how
+//! the bits of such members are split, one struct member per line:
+//!
+//! ```text
+//! # Comments start with `#`.
+//! Material.flags = kind:4, enabled:1, _:3, mask:8
+//! ```
+//!
+//! The struct type is matched by its debug name and the member by its name,
+//! or its index if it's unnamed. Like C bitfields, fields are packed from the
+//! least significant bit in declaration order, each field taking as many bits
+//! as its width. Fields named `_` are padding and are not reported.
+//!
+//! [`BitfieldAnnotations::walk`] merges the annotations into the type tree of
+//! a variable, reporting each field as a synthetic member nested in the
+//! annotated integer.
+use std::collections::BTreeMap;
+
+use crate::{
+    error::{anyhow, Result},
+    ty::{ScalarType, Type},
+};
+
+/// A bitfield declared in an annotation file.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Bitfield {
+    pub name: String,
+    /// Offset of the lowest bit of the field in the integer.
+    pub bit_offset: u32,
+    /// Number of bits the field takes.
+    pub nbit: u32,
+}
+
+/// A bitfield nested in a type, found by [`BitfieldAnnotations::walk`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BitfieldMember {
+    /// Symbol path of the field, like `materials[1].flags.kind`. The path
+    /// without the last segment can be resolved by
+    /// [`TypeResolveExt::resolve`](crate::symbol::TypeResolveExt::resolve) to
+    /// the annotated integer.
+    pub sym: String,
+    /// Offset in bytes of the annotated integer from the beginning of the
+    /// root type.
+    pub offset: usize,
+    /// Integer type of the annotated member.
+    pub ty: Type,
+    /// Offset of the lowest bit of the field in the integer.
+    pub bit_offset: u32,
+    /// Number of bits the field takes.
+    pub nbit: u32,
+}
+
+/// Bitfields of struct members declared in annotation files, in the format
+/// documented in the [module](self) docs.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct BitfieldAnnotations {
+    /// Bitfields keyed by struct type names and member names.
+    members: BTreeMap<(String, String), Vec<Bitfield>>,
+}
+impl BitfieldAnnotations {
+    pub fn new() -> Self {
+        Default::default()
+    }
+    /// Parse an annotation file. Annotations of a member already declared
+    /// are replaced.
+    pub fn parse(src: &str) -> Result<Self> {
+        let mut out = Self::new();
+        out.add(src)?;
+        Ok(out)
+    }
+    /// Add the annotations in another annotation file.
+    pub fn add(&mut self, src: &str) -> Result<&mut Self> {
+        for (i, line) in src.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (key, fields) = parse_line(line).map_err(|e| anyhow!("line {}: {}", i + 1, e))?;
+            self.members.insert(key, fields);
+        }
+        Ok(self)
+    }
+    /// Bitfields declared for member `member_name` of struct type
+    /// `struct_name`.
+    pub fn get(&self, struct_name: &str, member_name: &str) -> Option<&[Bitfield]> {
+        self.members
+            .get(&(struct_name.to_owned(), member_name.to_owned()))
+            .map(|x| x.as_slice())
+    }
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+
+    /// Find all the annotated bitfields nested in `ty`, in the order of the
+    /// members. Elements of sized arrays are visited one by one, while
+    /// runtime arrays are treated as empty, the same as [`Type::walk`]. An
+    /// annotated member that is not an integer wide enough for its bitfields
+    /// is an error.
+    pub fn walk(&self, ty: &Type) -> Result<Vec<BitfieldMember>> {
+        let mut out = Vec::new();
+        if !self.is_empty() {
+            self.walk_impl(ty, String::new(), 0, &mut out)?;
+        }
+        Ok(out)
+    }
+    fn walk_impl(
+        &self,
+        ty: &Type,
+        sym: String,
+        offset: usize,
+        out: &mut Vec<BitfieldMember>,
+    ) -> Result<()> {
+        match ty {
+            Type::Struct(struct_ty) => {
+                for (i, member) in struct_ty.members.iter().enumerate() {
+                    let member_name = match &member.name {
+                        Some(x) => x.clone(),
+                        None => i.to_string(),
+                    };
+                    let member_sym = if sym.is_empty() {
+                        member_name.clone()
+                    } else {
+                        format!("{}.{}", sym, member_name)
+                    };
+                    let member_offset = offset + member.offset.unwrap_or_default();
+                    let bitfields = struct_ty
+                        .name
+                        .as_deref()
+                        .and_then(|x| self.get(x, &member_name));
+                    if let Some(bitfields) = bitfields {
+                        check_bitfield_ty(&member.ty, bitfields)
+                            .map_err(|e| anyhow!("cannot annotate `{}`: {}", member_sym, e))?;
+                        for bitfield in bitfields {
+                            out.push(BitfieldMember {
+                                sym: format!("{}.{}", member_sym, bitfield.name),
+                                offset: member_offset,
+                                ty: member.ty.clone(),
+                                bit_offset: bitfield.bit_offset,
+                                nbit: bitfield.nbit,
+                            });
+                        }
+                    } else {
+                        self.walk_impl(&member.ty, member_sym, member_offset, out)?;
+                    }
+                }
+            }
+            Type::Array(arr_ty) => {
+                let stride = arr_ty.stride.unwrap_or_default();
+                for i in 0..arr_ty.nelement.unwrap_or_default() as usize {
+                    let element_sym = format!("{}[{}]", sym, i);
+                    let element_offset = offset + stride * i;
+                    self.walk_impl(&arr_ty.element_ty, element_sym, element_offset, out)?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+fn parse_line(line: &str) -> Result<((String, String), Vec<Bitfield>)> {
+    let (key, fields) = line
+        .split_once('=')
+        .ok_or_else(|| anyhow!("expected `=` in `{}`", line))?;
+    let (struct_name, member_name) = key
+        .trim()
+        .split_once('.')
+        .ok_or_else(|| anyhow!("expected `Struct.member` in `{}`", key.trim()))?;
+    let (struct_name, member_name) = (struct_name.trim(), member_name.trim());
+    if struct_name.is_empty() || member_name.is_empty() {
+        return Err(anyhow!("expected `Struct.member` in `{}`", key.trim()));
+    }
+
+    let mut out = Vec::new();
+    let mut bit_offset = 0u32;
+    for field in fields.split(',') {
+        let (name, nbit) = field
+            .split_once(':')
+            .ok_or_else(|| anyhow!("expected `name:width` in `{}`", field.trim()))?;
+        let name = name.trim();
+        let nbit = nbit
+            .trim()
+            .parse::<u32>()
+            .ok()
+            .filter(|x| *x > 0)
+            .ok_or_else(|| anyhow!("invalid width of bitfield `{}`", name))?;
+        if name.is_empty() {
+            return Err(anyhow!("empty bitfield name in `{}`", field.trim()));
+        }
+        if name != "_" && out.iter().any(|x: &Bitfield| x.name == name) {
+            return Err(anyhow!("duplicate bitfield `{}`", name));
+        }
+        if name != "_" {
+            out.push(Bitfield {
+                name: name.to_owned(),
+                bit_offset,
+                nbit,
+            });
+        }
+        bit_offset = bit_offset
+            .checked_add(nbit)
+            .filter(|x| *x <= 64)
+            .ok_or_else(|| anyhow!("bitfields take more than 64 bits"))?;
+    }
+    let key = (struct_name.to_owned(), member_name.to_owned());
+    Ok((key, out))
+}
+
+fn check_bitfield_ty(ty: &Type, bitfields: &[Bitfield]) -> Result<()> {
+    let nbit = bitfields
+        .iter()
+        .map(|x| x.bit_offset + x.nbit)
+        .max()
+        .unwrap_or_default();
+    match ty {
+        Type::Scalar(ScalarType::Integer { bits, .. }) if nbit <= *bits => Ok(()),
+        Type::Scalar(ScalarType::Integer { .. }) => {
+            Err(anyhow!("bitfields take {} bits but it's a `{}`", nbit, ty))
+        }
+        _ => Err(anyhow!(
+            "bitfields must be packed in an integer, not `{}`",
+            ty
+        )),
+    }
+}
@@ -0,0 +1,357 @@
+//! OpenCL kernel argument metadata of modules compiled by
+//! [clspv](https://github.com/google/clspv).
+//!
+//! clspv lowers kernel arguments to descriptor bindings, push constants and
+//! specialization constants, and records how each argument is mapped with
+//! the `NonSemantic.ClspvReflection` extended instruction set. Vulkan-based
+//! OpenCL runtimes need this mapping to bind `clSetKernelArg` values.
+use std::convert::TryFrom;
+
+use fnv::FnvHashMap as HashMap;
+
+use crate::{
+    error::{anyhow, Result},
+    instr::*,
+    parse::SpirvBinary,
+    spirv::Op,
+    var::{DescriptorBinding, SpecId},
+};
+
+/// Prefix of the versioned names of the clspv reflection instruction set,
+/// like `NonSemantic.ClspvReflection.5`.
+const CLSPV_REFLECTION_SET_PREFIX: &str = "NonSemantic.ClspvReflection.";
+
+const CLSPV_KERNEL: u32 = 1;
+const CLSPV_ARGUMENT_INFO: u32 = 2;
+const CLSPV_ARGUMENT_STORAGE_BUFFER: u32 = 3;
+const CLSPV_ARGUMENT_UNIFORM: u32 = 4;
+const CLSPV_ARGUMENT_POD_STORAGE_BUFFER: u32 = 5;
+const CLSPV_ARGUMENT_POD_UNIFORM: u32 = 6;
+const CLSPV_ARGUMENT_POD_PUSH_CONSTANT: u32 = 7;
+const CLSPV_ARGUMENT_SAMPLED_IMAGE: u32 = 8;
+const CLSPV_ARGUMENT_STORAGE_IMAGE: u32 = 9;
+const CLSPV_ARGUMENT_SAMPLER: u32 = 10;
+const CLSPV_ARGUMENT_WORKGROUP: u32 = 11;
+const CLSPV_ARGUMENT_POINTER_PUSH_CONSTANT: u32 = 26;
+const CLSPV_ARGUMENT_POINTER_UNIFORM: u32 = 27;
+const CLSPV_ARGUMENT_STORAGE_TEXEL_BUFFER: u32 = 34;
+const CLSPV_ARGUMENT_UNIFORM_TEXEL_BUFFER: u32 = 35;
+
+/// OpenCL address space qualifier of a kernel argument, as reported by
+/// `CL_KERNEL_ARG_ADDRESS_QUALIFIER`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ClspvAddressSpace {
+    Global,
+    Local,
+    Constant,
+    Private,
+}
+impl ClspvAddressSpace {
+    fn from_cl_enum(x: u32) -> Option<Self> {
+        match x {
+            0x119B => Some(ClspvAddressSpace::Global),
+            0x119C => Some(ClspvAddressSpace::Local),
+            0x119D => Some(ClspvAddressSpace::Constant),
+            0x119E => Some(ClspvAddressSpace::Private),
+            _ => None,
+        }
+    }
+}
+
+/// How a kernel argument is passed to the kernel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ClspvArgumentKind {
+    /// A buffer bound to a storage buffer descriptor.
+    StorageBuffer { desc_bind: DescriptorBinding },
+    /// A constant buffer bound to a uniform buffer descriptor.
+    Uniform { desc_bind: DescriptorBinding },
+    /// A plain-old-data argument at `offset` in a storage buffer.
+    PodStorageBuffer {
+        desc_bind: DescriptorBinding,
+        offset: u32,
+        nbyte: u32,
+    },
+    /// A plain-old-data argument at `offset` in a uniform buffer.
+    PodUniform {
+        desc_bind: DescriptorBinding,
+        offset: u32,
+        nbyte: u32,
+    },
+    /// A plain-old-data argument at `offset` in the push constants.
+    PodPushConstant { offset: u32, nbyte: u32 },
+    /// A read-only image bound to a sampled image descriptor.
+    SampledImage { desc_bind: DescriptorBinding },
+    /// A writable image bound to a storage image descriptor.
+    StorageImage { desc_bind: DescriptorBinding },
+    /// A sampler bound to a sampler descriptor.
+    Sampler { desc_bind: DescriptorBinding },
+    /// A `__local` buffer whose number of elements is given by the
+    /// specialization constant `spec_id`.
+    Workgroup { spec_id: SpecId, elem_nbyte: u32 },
+    /// A buffer passed by its physical address at `offset` in the push
+    /// constants.
+    PointerPushConstant { offset: u32, nbyte: u32 },
+    /// A buffer passed by its physical address at `offset` in a uniform
+    /// buffer.
+    PointerUniform {
+        desc_bind: DescriptorBinding,
+        offset: u32,
+        nbyte: u32,
+    },
+    /// A writable image buffer bound to a storage texel buffer descriptor.
+    StorageTexelBuffer { desc_bind: DescriptorBinding },
+    /// A read-only image buffer bound to a uniform texel buffer descriptor.
+    UniformTexelBuffer { desc_bind: DescriptorBinding },
+}
+impl ClspvArgumentKind {
+    /// Descriptor binding the argument is passed through, if any.
+    pub fn desc_bind(&self) -> Option<DescriptorBinding> {
+        match self {
+            ClspvArgumentKind::StorageBuffer { desc_bind }
+            | ClspvArgumentKind::Uniform { desc_bind }
+            | ClspvArgumentKind::PodStorageBuffer { desc_bind, .. }
+            | ClspvArgumentKind::PodUniform { desc_bind, .. }
+            | ClspvArgumentKind::SampledImage { desc_bind }
+            | ClspvArgumentKind::StorageImage { desc_bind }
+            | ClspvArgumentKind::Sampler { desc_bind }
+            | ClspvArgumentKind::PointerUniform { desc_bind, .. }
+            | ClspvArgumentKind::StorageTexelBuffer { desc_bind }
+            | ClspvArgumentKind::UniformTexelBuffer { desc_bind } => Some(*desc_bind),
+            ClspvArgumentKind::PodPushConstant { .. }
+            | ClspvArgumentKind::Workgroup { .. }
+            | ClspvArgumentKind::PointerPushConstant { .. } => None,
+        }
+    }
+}
+
+/// An OpenCL kernel argument.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ClspvArgument {
+    /// Index of the argument in the original kernel signature.
+    pub ordinal: u32,
+    /// Original argument name. Only available if the module was compiled
+    /// with `-cl-kernel-arg-info`, like all the argument info below.
+    pub name: Option<String>,
+    /// Original argument type name, like `float*`.
+    pub ty_name: Option<String>,
+    pub addr_space: Option<ClspvAddressSpace>,
+    pub kind: ClspvArgumentKind,
+}
+
+/// An OpenCL kernel and its arguments.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ClspvKernel {
+    /// Kernel name, which is also the name of its entry point.
+    pub name: String,
+    /// Arguments sorted by ordinals. Arguments unused by the kernel can be
+    /// optimized away by clspv so ordinals can be non-contiguous.
+    pub args: Vec<ClspvArgument>,
+}
+
+struct ArgumentInfo {
+    name: String,
+    ty_name: Option<String>,
+    addr_space: Option<ClspvAddressSpace>,
+}
+
+/// Collect the kernels declared with the clspv reflection instruction set in
+/// declaration order. Returns an empty list if the module is not compiled by
+/// clspv.
+pub fn clspv_kernels(spv: &SpirvBinary) -> Result<Vec<ClspvKernel>> {
+    let mut refl_set_ids = Vec::new();
+    let mut strs = HashMap::<u32, String>::default();
+    let mut consts = HashMap::<u32, u32>::default();
+    let mut ext_instrs = Vec::new();
+    let mut instrs = spv.instrs()?;
+    while let Some(instr) = instrs.next()? {
+        match Op::from_u32(instr.opcode()) {
+            Some(Op::ExtInstImport) => {
+                let op = OpExtInstImport::try_from(instr)?;
+                if op.name.starts_with(CLSPV_REFLECTION_SET_PREFIX) {
+                    refl_set_ids.push(op.result_id);
+                }
+            }
+            Some(Op::String) => {
+                let op = OpString::try_from(instr)?;
+                strs.insert(op.result_id, op.string.to_owned());
+            }
+            Some(Op::Constant) => {
+                let op = OpConstant::try_from(instr)?;
+                if let Some(x) = op.value.first() {
+                    consts.insert(op.result_id, *x);
+                }
+            }
+            Some(Op::ExtInst) => {
+                let op = OpExtInst::try_from(instr)?;
+                if refl_set_ids.contains(&op.set_id) {
+                    ext_instrs.push((op.result_id, op.instruction, op.operand_ids.to_vec()));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let get_str = |id: u32| {
+        strs.get(&id)
+            .cloned()
+            .ok_or_else(|| anyhow!("clspv reflection string {} is not declared", id))
+    };
+    let get_u32 = |id: u32| {
+        consts
+            .get(&id)
+            .copied()
+            .ok_or_else(|| anyhow!("clspv reflection constant {} is not declared", id))
+    };
+
+    let mut kernels = Vec::<ClspvKernel>::new();
+    // Kernel declaration IDs to indices in `kernels`.
+    let mut kernel_idxs = HashMap::<u32, usize>::default();
+    let mut arg_infos = HashMap::<u32, ArgumentInfo>::default();
+    for (result_id, opcode, operands) in ext_instrs {
+        let operand = |i: usize| {
+            operands
+                .get(i)
+                .copied()
+                .ok_or_else(|| anyhow!("clspv reflection instruction {} is truncated", opcode))
+        };
+        let desc_bind = || -> Result<DescriptorBinding> {
+            Ok(DescriptorBinding::new(
+                get_u32(operand(2)?)?,
+                get_u32(operand(3)?)?,
+            ))
+        };
+        let (kind, arg_info_idx) = match opcode {
+            CLSPV_KERNEL => {
+                let kernel = ClspvKernel {
+                    name: get_str(operand(1)?)?,
+                    args: Vec::new(),
+                };
+                kernel_idxs.insert(result_id, kernels.len());
+                kernels.push(kernel);
+                continue;
+            }
+            CLSPV_ARGUMENT_INFO => {
+                let ty_name = operands.get(1).map(|x| get_str(*x)).transpose()?;
+                let addr_space = operands
+                    .get(2)
+                    .map(|x| get_u32(*x))
+                    .transpose()?
+                    .and_then(ClspvAddressSpace::from_cl_enum);
+                let arg_info = ArgumentInfo {
+                    name: get_str(operand(0)?)?,
+                    ty_name,
+                    addr_space,
+                };
+                arg_infos.insert(result_id, arg_info);
+                continue;
+            }
+            CLSPV_ARGUMENT_STORAGE_BUFFER => (
+                ClspvArgumentKind::StorageBuffer {
+                    desc_bind: desc_bind()?,
+                },
+                4,
+            ),
+            CLSPV_ARGUMENT_UNIFORM => (
+                ClspvArgumentKind::Uniform {
+                    desc_bind: desc_bind()?,
+                },
+                4,
+            ),
+            CLSPV_ARGUMENT_POD_STORAGE_BUFFER => (
+                ClspvArgumentKind::PodStorageBuffer {
+                    desc_bind: desc_bind()?,
+                    offset: get_u32(operand(4)?)?,
+                    nbyte: get_u32(operand(5)?)?,
+                },
+                6,
+            ),
+            CLSPV_ARGUMENT_POD_UNIFORM => (
+                ClspvArgumentKind::PodUniform {
+                    desc_bind: desc_bind()?,
+                    offset: get_u32(operand(4)?)?,
+                    nbyte: get_u32(operand(5)?)?,
+                },
+                6,
+            ),
+            CLSPV_ARGUMENT_POD_PUSH_CONSTANT => (
+                ClspvArgumentKind::PodPushConstant {
+                    offset: get_u32(operand(2)?)?,
+                    nbyte: get_u32(operand(3)?)?,
+                },
+                4,
+            ),
+            CLSPV_ARGUMENT_SAMPLED_IMAGE => (
+                ClspvArgumentKind::SampledImage {
+                    desc_bind: desc_bind()?,
+                },
+                4,
+            ),
+            CLSPV_ARGUMENT_STORAGE_IMAGE => (
+                ClspvArgumentKind::StorageImage {
+                    desc_bind: desc_bind()?,
+                },
+                4,
+            ),
+            CLSPV_ARGUMENT_SAMPLER => (
+                ClspvArgumentKind::Sampler {
+                    desc_bind: desc_bind()?,
+                },
+                4,
+            ),
+            CLSPV_ARGUMENT_WORKGROUP => (
+                ClspvArgumentKind::Workgroup {
+                    spec_id: get_u32(operand(2)?)?,
+                    elem_nbyte: get_u32(operand(3)?)?,
+                },
+                4,
+            ),
+            CLSPV_ARGUMENT_POINTER_PUSH_CONSTANT => (
+                ClspvArgumentKind::PointerPushConstant {
+                    offset: get_u32(operand(2)?)?,
+                    nbyte: get_u32(operand(3)?)?,
+                },
+                4,
+            ),
+            CLSPV_ARGUMENT_POINTER_UNIFORM => (
+                ClspvArgumentKind::PointerUniform {
+                    desc_bind: desc_bind()?,
+                    offset: get_u32(operand(4)?)?,
+                    nbyte: get_u32(operand(5)?)?,
+                },
+                6,
+            ),
+            CLSPV_ARGUMENT_STORAGE_TEXEL_BUFFER => (
+                ClspvArgumentKind::StorageTexelBuffer {
+                    desc_bind: desc_bind()?,
+                },
+                4,
+            ),
+            CLSPV_ARGUMENT_UNIFORM_TEXEL_BUFFER => (
+                ClspvArgumentKind::UniformTexelBuffer {
+                    desc_bind: desc_bind()?,
+                },
+                4,
+            ),
+            // All argument kinds are matched above. Other instructions
+            // describe module-wide resources like literal sampler maps and
+            // push constants of builtins.
+            _ => continue,
+        };
+        let kernel_idx = *kernel_idxs
+            .get(&operand(0)?)
+            .ok_or_else(|| anyhow!("clspv argument of an undeclared kernel"))?;
+        let arg_info = operands.get(arg_info_idx).and_then(|x| arg_infos.get(x));
+        let arg = ClspvArgument {
+            ordinal: get_u32(operand(1)?)?,
+            name: arg_info.map(|x| x.name.clone()),
+            ty_name: arg_info.and_then(|x| x.ty_name.clone()),
+            addr_space: arg_info.and_then(|x| x.addr_space),
+            kind,
+        };
+        kernels[kernel_idx].args.push(arg);
+    }
+    for kernel in kernels.iter_mut() {
+        kernel.args.sort_by_key(|x| x.ordinal);
+    }
+    Ok(kernels)
+}
@@ -0,0 +1,72 @@
+//! Demangling of entry point names.
+//!
+//! Some compilers name entry points after the mangled symbols of the source
+//! functions, e.g., Slang when entry points are overloaded or nested in
+//! namespaces, and DXC when compiling shader libraries. Such names must be
+//! kept as-is to create pipelines, but they are meaningless to users.
+//! [`demangle`] recovers the source function names from the following
+//! manglings:
+//!
+//! - Slang, like `_S4main` and `_S6shader4main`;
+//! - Itanium C++ ABI, like `_Z4mainv` and `_ZN6shader4mainEv`;
+//! - MSVC C++ ABI, like `?main@@YAXXZ` and `?main@shader@@YAXXZ`.
+//!
+//! Namespaces are joined with `::`. Parameter and return types are dropped
+//! since entry points are identified by names only. Other manglings can be
+//! handled by [`ReflectConfig::demangler`].
+//!
+//! [`ReflectConfig::demangler`]: crate::reflect_cfg::ReflectConfig::demangler
+
+/// Demangle entry point name `name`, or `None` if it's not a mangled name
+/// known to SPIR-Q.
+pub fn demangle(name: &str) -> Option<String> {
+    let segs = if let Some(x) = name.strip_prefix("_S") {
+        read_len_prefixed_segs(x).0
+    } else if let Some(x) = name.strip_prefix("_ZN") {
+        let (segs, rest) = read_len_prefixed_segs(x);
+        if !rest.starts_with('E') {
+            return None;
+        }
+        segs
+    } else if let Some(x) = name.strip_prefix("_Z") {
+        read_len_prefixed_segs(x).0.into_iter().take(1).collect()
+    } else if let Some(x) = name.strip_prefix('?') {
+        // MSVC lists the function name first and then the enclosing
+        // namespaces from the innermost, terminated by an empty one.
+        let (x, _) = x.split_once("@@")?;
+        let mut segs = x.split('@').collect::<Vec<_>>();
+        segs.reverse();
+        segs
+    } else {
+        return None;
+    };
+    if segs.is_empty() || segs.iter().any(|x| !is_ident(x)) {
+        return None;
+    }
+    Some(segs.join("::"))
+}
+
+/// Read identifiers prefixed by their lengths in decimal, like `4main`.
+/// Returns the identifiers and the rest of `x`.
+fn read_len_prefixed_segs(mut x: &str) -> (Vec<&str>, &str) {
+    let mut out = Vec::new();
+    loop {
+        let ndigit = x.find(|c: char| !c.is_ascii_digit()).unwrap_or(x.len());
+        let len = match x[..ndigit].parse::<usize>() {
+            Ok(len) if len > 0 && ndigit + len <= x.len() => len,
+            _ => break,
+        };
+        match x.get(ndigit..ndigit + len) {
+            Some(seg) => out.push(seg),
+            None => break,
+        }
+        x = &x[ndigit + len..];
+    }
+    (out, x)
+}
+
+fn is_ident(x: &str) -> bool {
+    let mut chars = x.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
@@ -0,0 +1,282 @@
+//! Non-fatal findings reported alongside reflection results.
+use std::fmt;
+
+use crate::{
+    binary::SpirvVersion,
+    spirv,
+    var::{DescriptorBinding, SpecId},
+};
+
+/// Image wrapped by an `OpTypeSampledImage` that can't be sampled through a
+/// combined image sampler.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DegenerateSampledImage {
+    /// Subpass data can only be read with `OpImageRead`.
+    SubpassData,
+    /// Storage images (`Sampled` operand 2), including storage texel
+    /// buffers, can't be sampled.
+    StorageImage,
+}
+impl fmt::Display for DegenerateSampledImage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DegenerateSampledImage::SubpassData => write!(f, "subpass data"),
+            DegenerateSampledImage::StorageImage => write!(f, "storage image"),
+        }
+    }
+}
+
+/// Arrangement of an image not allowed for its dimensionality.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ImageArrangement {
+    /// Only 1D, 2D and cube images can be arrayed.
+    Arrayed,
+    /// Only 2D images and subpass data can be multisampled.
+    Multisampled,
+}
+impl fmt::Display for ImageArrangement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ImageArrangement::Arrayed => write!(f, "arrayed"),
+            ImageArrangement::Multisampled => write!(f, "multisampled"),
+        }
+    }
+}
+
+/// Convention of SPIR-V versions a variable doesn't follow in a module of
+/// another version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum VersionConvention {
+    /// Since SPIR-V 1.4, entry points list all the global variables they
+    /// use in their interfaces, but the variable is not listed.
+    UnlistedGlobal,
+    /// Before SPIR-V 1.4, entry points only list stage inputs and outputs in
+    /// their interfaces, but the variable is listed.
+    ListedGlobal,
+    /// The `StorageBuffer` storage class is only available since SPIR-V 1.3
+    /// or with `SPV_KHR_storage_buffer_storage_class`.
+    StorageBufferClass,
+}
+impl fmt::Display for VersionConvention {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            VersionConvention::UnlistedGlobal => write!(
+                f,
+                "is used but not listed in the entry point interface, as required since SPIR-V 1.4"
+            ),
+            VersionConvention::ListedGlobal => write!(
+                f,
+                "is listed in the entry point interface, as only allowed since SPIR-V 1.4"
+            ),
+            VersionConvention::StorageBufferClass => write!(
+                f,
+                "is in the `StorageBuffer` storage class, which is only available since SPIR-V 1.3"
+            ),
+        }
+    }
+}
+
+/// A warning found during reflection. Diagnostics don't stop the reflection
+/// process but they usually indicate a problem in the shader module or in the
+/// way it's going to be used.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Diagnostic {
+    /// A descriptor resource has no `Binding` decoration and the reported
+    /// binding point `desc_bind` was assumed.
+    MissingBinding {
+        name: Option<String>,
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::desc_bind"))]
+        desc_bind: DescriptorBinding,
+    },
+    /// Descriptor resources of a same type but very different names are bound
+    /// to a same binding point in different stages, which is usually a
+    /// copy-paste mistake.
+    ShadowedDescriptor {
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::desc_bind"))]
+        desc_bind: DescriptorBinding,
+        name: String,
+        shadowed_name: String,
+    },
+    /// A descriptor resource is a sampled image of an image that can't be
+    /// sampled. It is reflected as the wrapped image instead of a combined
+    /// image sampler.
+    DegenerateSampledImage {
+        name: Option<String>,
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::desc_bind"))]
+        desc_bind: DescriptorBinding,
+        kind: DegenerateSampledImage,
+    },
+    /// Image type `ty_id` of `dim` is declared `arrangement`, which is not
+    /// allowed for images of the dimensionality. The image is reflected as
+    /// declared.
+    InvalidImageArrangement {
+        ty_id: u32,
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::spirv_enum"))]
+        dim: spirv::Dim,
+        arrangement: ImageArrangement,
+    },
+    /// An instruction of unknown `opcode` among the type, constant and global
+    /// variable declarations is skipped. It usually declares a type of an
+    /// extension unknown to SPIR-Q, and variables of the type are not
+    /// reflected.
+    UnknownDeclaration { opcode: u32 },
+    /// An array member of a uniform buffer, storage buffer or push constant
+    /// block is sized by a specialization constant, which is undefined
+    /// behavior in Vulkan. The array is reflected with the default or
+    /// specialized value of the constant. `block` is the struct directly
+    /// containing the member, which can be nested in the block.
+    SpecConstantSizedArray {
+        block: Option<String>,
+        member: Option<String>,
+        spec_id: SpecId,
+    },
+    /// ID `id`, or its member `member_idx` if it's a struct type, is
+    /// decorated by `deco` more than once with different operands. `operands`
+    /// are kept by the decoration collision policy and `discarded` are
+    /// ignored.
+    DecorationCollision {
+        id: u32,
+        member_idx: Option<u32>,
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::spirv_enum"))]
+        deco: spirv::Decoration,
+        operands: Vec<u32>,
+        discarded: Vec<u32>,
+    },
+    /// An entry point uses derivatives, explicitly or by sampling images
+    /// with implicit LODs, but derivatives are not available in its
+    /// execution model.
+    UnsupportedDerivatives {
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::spirv_enum"))]
+        exec_model: spirv::ExecutionModel,
+    },
+    /// Variable `name` breaks a convention of the SPIR-V version `version`
+    /// declared by the module, usually because the module mixes conventions
+    /// of different versions. The variable is reflected as declared.
+    MixedVersionConventions {
+        name: Option<String>,
+        version: SpirvVersion,
+        convention: VersionConvention,
+    },
+    /// Push constant member `member` at byte `offset` of `size` bytes ends
+    /// beyond the push constant budget of `max_size` bytes, so pipeline
+    /// layouts created for `stages` exceed `maxPushConstantsSize`.
+    PushConstantOverBudget {
+        member: Option<String>,
+        offset: u32,
+        size: u32,
+        max_size: u32,
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::spirv_enums"))]
+        stages: Vec<spirv::ExecutionModel>,
+    },
+}
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Diagnostic::MissingBinding { name, desc_bind } => write!(
+                f,
+                "descriptor resource `{}` has no binding decoration, assumed {}",
+                name.as_deref().unwrap_or("<unnamed>"),
+                desc_bind
+            ),
+            Diagnostic::ShadowedDescriptor {
+                desc_bind,
+                name,
+                shadowed_name,
+            } => write!(
+                f,
+                "descriptor resource `{}` shadows `{}` at {}",
+                name, shadowed_name, desc_bind
+            ),
+            Diagnostic::DegenerateSampledImage {
+                name,
+                desc_bind,
+                kind,
+            } => write!(
+                f,
+                "descriptor resource `{}` at {} is a sampled image of {}, reflected as the {} itself",
+                name.as_deref().unwrap_or("<unnamed>"),
+                desc_bind,
+                kind,
+                kind
+            ),
+            Diagnostic::InvalidImageArrangement {
+                ty_id,
+                dim,
+                arrangement,
+            } => write!(
+                f,
+                "image type {} of {:?} cannot be {}, reflected as declared",
+                ty_id, dim, arrangement
+            ),
+            Diagnostic::UnknownDeclaration { opcode } => write!(
+                f,
+                "declaration of unknown opcode {} is skipped",
+                opcode
+            ),
+            Diagnostic::SpecConstantSizedArray {
+                block,
+                member,
+                spec_id,
+            } => write!(
+                f,
+                "array member `{}` of `{}` is sized by specialization constant {}",
+                member.as_deref().unwrap_or("<unnamed>"),
+                block.as_deref().unwrap_or("<unnamed>"),
+                spec_id
+            ),
+            Diagnostic::DecorationCollision {
+                id,
+                member_idx,
+                deco,
+                operands,
+                discarded,
+            } => {
+                write!(f, "decoration {:?} at id {}", deco, id)?;
+                if let Some(member_idx) = member_idx {
+                    write!(f, " member {}", member_idx)?;
+                }
+                write!(
+                    f,
+                    " is declared more than once, kept {:?} and discarded {:?}",
+                    operands, discarded
+                )
+            }
+            Diagnostic::UnsupportedDerivatives { exec_model } => write!(
+                f,
+                "derivatives are used but not available in {:?} shaders",
+                exec_model
+            ),
+            Diagnostic::MixedVersionConventions {
+                name,
+                version,
+                convention,
+            } => write!(
+                f,
+                "variable `{}` in a SPIR-V {} module {}",
+                name.as_deref().unwrap_or("<unnamed>"),
+                version,
+                convention
+            ),
+            Diagnostic::PushConstantOverBudget {
+                member,
+                offset,
+                size,
+                max_size,
+                stages,
+            } => write!(
+                f,
+                "push constant member `{}` at bytes {}..{} of {:?} stages exceeds the budget of {} bytes",
+                member.as_deref().unwrap_or("<unnamed>"),
+                offset,
+                offset + size,
+                stages,
+                max_size
+            ),
+        }
+    }
+}
@@ -1,10 +1,473 @@
 //! Entry-point function record.
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fmt;
+use std::ops::Range;
 
-use crate::{func::ExecutionMode, spirv, var::Variable};
+use crate::{
+    constant::ConstantValue,
+    diagnostic::Diagnostic,
+    func::ExecutionMode,
+    gl::GlVariable,
+    spirv,
+    ty::{walk::MemberVariableRouting, AccessType, DescriptorType, ImageFormat, ScalarType, Type},
+    var::{DescriptorBinding, InterfaceLocation, SpecId, Variable},
+};
 
 pub use spirv::ExecutionModel;
 
+/// Additional information about a descriptor binding point that is not
+/// carried by [`Variable::Descriptor`].
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DescriptorInfo {
+    /// The binding point is not decorated in SPIR-V and is chosen by SPIR-Q
+    /// according to [`MissingBindingPolicy`].
+    ///
+    /// [`MissingBindingPolicy`]: crate::reflect_cfg::MissingBindingPolicy
+    pub is_binding_implicit: bool,
+    /// Texel format and access of the texel buffer bound, or `None` if the
+    /// binding is not a texel buffer.
+    pub texel_buffer: Option<TexelBufferInfo>,
+    /// The descriptor array is indexed by values decorated `NonUniform`, so
+    /// the `shader*ArrayNonUniformIndexing` feature of the descriptor type
+    /// must be enabled. Such bindless arrays are usually also created with
+    /// `VK_DESCRIPTOR_BINDING_PARTIALLY_BOUND_BIT`. Always `false` without the
+    /// `access-analysis` feature or if function bodies are not scanned, see
+    /// [`ReflectConfig::track_static_use`].
+    ///
+    /// [`ReflectConfig::track_static_use`]: crate::reflect_cfg::ReflectConfig::track_static_use
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub is_nonuniform_indexed: bool,
+    /// The image is sampled, fetched or gathered with the `MinLod` image
+    /// operand, so the `shaderResourceMinLod` feature must be enabled. Always
+    /// `false` without the `access-analysis` feature or if function bodies
+    /// are not scanned, see [`ReflectConfig::track_static_use`].
+    ///
+    /// [`ReflectConfig::track_static_use`]: crate::reflect_cfg::ReflectConfig::track_static_use
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub uses_min_lod: bool,
+    /// The image is accessed by `OpImageSparse*` instructions for residency
+    /// feedback, so the `shaderResourceResidency` feature must be enabled.
+    /// Always `false` without the `access-analysis` feature or if function
+    /// bodies are not scanned, see [`ReflectConfig::track_static_use`].
+    ///
+    /// [`ReflectConfig::track_static_use`]: crate::reflect_cfg::ReflectConfig::track_static_use
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub uses_sparse_residency: bool,
+    /// The acceleration structure is traced by `OpTraceRayKHR` or
+    /// `OpTraceNV` in a ray tracing pipeline. Always `false` without the
+    /// `access-analysis` feature.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub uses_trace_ray: bool,
+    /// The acceleration structure is queried inline by
+    /// `OpRayQueryInitializeKHR`. Always `false` without the
+    /// `access-analysis` feature.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub uses_ray_query: bool,
+    /// Names of the descriptor resources aliased at the binding point, in
+    /// declaration order, or empty if only one resource is bound. Each
+    /// aliased resource is reported as a separate variable.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub aliases: Vec<Option<String>>,
+    /// The aliased resources have different `VkDescriptorType`s, which can't
+    /// be described by a single `VkDescriptorSetLayoutBinding` without
+    /// `VK_EXT_mutable_descriptor_type`. See
+    /// [`ReflectConfig::strict_desc_aliasing`].
+    ///
+    /// [`ReflectConfig::strict_desc_aliasing`]: crate::reflect_cfg::ReflectConfig::strict_desc_aliasing
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub has_aliased_desc_tys: bool,
+    /// A resource bound at the binding point is listed in the interface of
+    /// `OpEntryPoint`. Modules of SPIR-V 1.4 and later list all the global
+    /// variables an entry point references.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub is_interface_listed: bool,
+    /// A resource bound at the binding point is accessed by the entry point or
+    /// functions it calls, i.e., it's statically used as Vulkan validation
+    /// defines it. Resources not statically used are only reported with
+    /// [`ReflectConfig::ref_all_rscs`]. Always `true` if function bodies are
+    /// not scanned, see [`ReflectConfig::track_static_use`].
+    ///
+    /// [`ReflectConfig::ref_all_rscs`]: crate::reflect_cfg::ReflectConfig::ref_all_rscs
+    /// [`ReflectConfig::track_static_use`]: crate::reflect_cfg::ReflectConfig::track_static_use
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub is_statically_used: bool,
+    /// Elements of the descriptor array referenced by the entry point, so
+    /// that arrays created with `VK_DESCRIPTOR_BINDING_PARTIALLY_BOUND_BIT`
+    /// only need the referenced elements to be written. A binding not
+    /// arrayed has the only element `0` if it's statically used. Always
+    /// [`UsedElements::Unbounded`] if function bodies are not scanned.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub used_elements: UsedElements,
+}
+impl DescriptorInfo {
+    /// Capabilities the module has to declare for the image operations on the
+    /// binding.
+    pub fn required_caps(&self) -> Vec<spirv::Capability> {
+        let mut out = Vec::new();
+        if self.uses_min_lod {
+            out.push(spirv::Capability::MinLod);
+        }
+        if self.uses_sparse_residency {
+            out.push(spirv::Capability::SparseResidency);
+        }
+        out
+    }
+}
+
+/// Format requirements of a uniform or storage texel buffer, to validate the
+/// format of `VkBufferView`s bound to it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TexelBufferInfo {
+    /// Texel format declared in SPIR-V, or `ImageFormat::Unknown` if any
+    /// format of the same numeric type is accepted. Formats of uniform texel
+    /// buffers are usually unknown.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::spirv_enum"))]
+    pub fmt: ImageFormat,
+    /// How the texel buffer is accessed. Uniform texel buffers are always
+    /// read-only.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::access_ty"))]
+    pub access: AccessType,
+    /// The binding is a storage texel buffer rather than a uniform texel
+    /// buffer.
+    pub is_storage: bool,
+}
+impl TexelBufferInfo {
+    /// The buffer view format must support storage reads without a declared
+    /// format (`VK_FORMAT_FEATURE_2_STORAGE_READ_WITHOUT_FORMAT_BIT`).
+    pub fn requires_read_without_fmt(&self) -> bool {
+        self.is_storage && self.fmt == ImageFormat::Unknown && self.access != AccessType::WriteOnly
+    }
+    /// The buffer view format must support storage writes without a declared
+    /// format (`VK_FORMAT_FEATURE_2_STORAGE_WRITE_WITHOUT_FORMAT_BIT`).
+    pub fn requires_write_without_fmt(&self) -> bool {
+        self.is_storage && self.fmt == ImageFormat::Unknown && self.access != AccessType::ReadOnly
+    }
+}
+
+/// Elements of a descriptor array referenced by an entry point.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum UsedElements {
+    /// Only the listed elements are referenced, in ascending order. The
+    /// elements are indexed by constants, including constant expressions
+    /// folded by SPIR-Q, but not specialization constants.
+    Elements(Vec<u32>),
+    /// Any element can be referenced, e.g., the array is indexed by a
+    /// dynamic value or a specialization constant.
+    #[default]
+    Unbounded,
+}
+impl UsedElements {
+    /// Element `idx` might be referenced.
+    pub fn contains(&self, idx: u32) -> bool {
+        match self {
+            UsedElements::Elements(x) => x.binary_search(&idx).is_ok(),
+            UsedElements::Unbounded => true,
+        }
+    }
+}
+
+/// An execution mode as declared by `OpExecutionMode` or `OpExecutionModeId`,
+/// without interpretation. Execution modes unknown to SPIR-Q are only
+/// available in this form.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RawExecutionMode {
+    /// Execution mode enumerant value.
+    pub exec_mode: u32,
+    /// Operand words following the execution mode.
+    pub operands: Vec<u32>,
+    /// The operands are IDs of constants, declared by `OpExecutionModeId`.
+    pub is_id: bool,
+}
+impl RawExecutionMode {
+    /// Get the execution mode if it's known to SPIR-Q.
+    pub fn known_exec_mode(&self) -> Option<spirv::ExecutionMode> {
+        spirv::ExecutionMode::from_u32(self.exec_mode)
+    }
+}
+
+/// A literal sampler declared by `OpConstantSampler`, as used by OpenCL
+/// kernels and modules generated by clspv.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LiteralSampler {
+    /// Debug name of the sampler constant.
+    pub name: Option<String>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::spirv_enum"))]
+    pub addr_mode: spirv::SamplerAddressingMode,
+    /// Texture coordinates are normalized.
+    pub is_normalized: bool,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::spirv_enum"))]
+    pub filter_mode: spirv::SamplerFilterMode,
+}
+
+/// A variable in workgroup memory, shared by the invocations in a workgroup
+/// of compute, task and mesh shaders.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SharedVariable {
+    pub name: Option<String>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::ty"))]
+    pub ty: Type,
+    /// Size of the variable in bytes, or `None` if the size is unknown.
+    /// Blocks explicitly laid out with `WorkgroupMemoryExplicitLayoutKHR`
+    /// follow their offset and stride decorations, while other variables are
+    /// laid out by the `std430` rules. Drivers might lay out workgroup memory
+    /// more tightly.
+    pub nbyte: Option<usize>,
+    /// The variable is an explicitly laid out block, which aliases all the
+    /// other such blocks in the entry point.
+    pub is_aliased: bool,
+}
+
+/// A module-scope variable in the `Private` storage class, collected when
+/// [`ReflectConfig::private_vars`] is set, or in the `CrossWorkgroup`
+/// storage class.
+///
+/// [`ReflectConfig::private_vars`]: crate::reflect_cfg::ReflectConfig::private_vars
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PrivateVariable {
+    pub name: Option<String>,
+    pub ty: Type,
+    /// Value the variable is initialized with, or `None` if it's not
+    /// initialized or it's initialized with a composite constant.
+    pub init: Option<ConstantValue>,
+}
+
+/// Primitive type output by a mesh shader.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MeshPrimitiveType {
+    Points,
+    Lines,
+    Triangles,
+}
+
+/// Output limits and primitive type of a mesh shader, found by
+/// [`EntryPoint::mesh_outputs`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MeshOutputs {
+    /// Maximum number of vertices output by a workgroup.
+    pub max_vertices: u32,
+    /// Maximum number of primitives output by a workgroup.
+    pub max_primitives: u32,
+    pub prim_ty: MeshPrimitiveType,
+}
+
+/// Granularity of the critical sections of fragment shader interlock.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InterlockScope {
+    /// Fragments covering a same pixel are interlocked.
+    Pixel,
+    /// Fragments covering a same sample are interlocked.
+    Sample,
+    /// Fragments covering a same shading rate region are interlocked.
+    ShadingRate,
+}
+
+/// Fragment shader interlock declared by the `*InterlockOrderedEXT` and
+/// `*InterlockUnorderedEXT` execution modes, found by
+/// [`EntryPoint::fragment_interlock`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FragmentInterlock {
+    pub scope: InterlockScope,
+    /// Critical sections are executed in primitive order.
+    pub is_ordered: bool,
+}
+impl FragmentInterlock {
+    /// Name of the `VkPhysicalDeviceFragmentShaderInterlockFeaturesEXT`
+    /// feature that must be enabled.
+    pub fn vk_feature(&self) -> &'static str {
+        match self.scope {
+            InterlockScope::Pixel => "fragmentShaderPixelInterlock",
+            InterlockScope::Sample => "fragmentShaderSampleInterlock",
+            InterlockScope::ShadingRate => "fragmentShaderShadingRateInterlock",
+        }
+    }
+}
+
+/// An input attachment read by an entry point, found by
+/// [`EntryPoint::input_attachments`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct InputAttachment {
+    /// Input attachment index, i.e., the index of the attachment in
+    /// `VkSubpassDescription::pInputAttachments`.
+    pub input_attm_idx: u32,
+    /// Name of the variable the input attachment is bound to.
+    pub name: Option<String>,
+    pub desc_bind: DescriptorBinding,
+    /// Index of the input attachment in the descriptor array at `desc_bind`,
+    /// or 0 if the variable is not an array.
+    pub bind_idx: u32,
+    /// Scalar type of the subpass data read. SPIR-V doesn't declare the
+    /// formats of input attachments, but the numeric format of the
+    /// attachment has to match this type, e.g., an unsigned integer format
+    /// for an unsigned integer type.
+    pub scalar_ty: ScalarType,
+    /// The attachment is multisampled and read per sample.
+    pub is_multisampled: bool,
+}
+
+/// Variable owning a value found by [`EntryPoint::walk_all`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RouteOwner {
+    /// The push constant block.
+    PushConstant,
+    /// A uniform buffer or storage buffer bound at a descriptor binding.
+    Descriptor(DescriptorBinding),
+}
+
+/// A leaf value in a push constant block or a buffer, found by
+/// [`EntryPoint::walk_all`].
+#[derive(Debug, Clone)]
+pub struct VariableRoute<'a> {
+    pub owner: RouteOwner,
+    /// Name of the variable owning the value.
+    pub var_name: Option<&'a str>,
+    /// Symbol path, offset and type of the value in the variable.
+    pub route: MemberVariableRouting<'a>,
+}
+
+/// A stage input or output variable identified by its location.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum InterfaceVariable {
+    Input(
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::location"))]
+        InterfaceLocation,
+    ),
+    Output(
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::location"))]
+        InterfaceLocation,
+    ),
+}
+impl InterfaceVariable {
+    /// Identify a stage input or output variable, or `None` if `var` is not
+    /// one.
+    pub fn of(var: &Variable) -> Option<Self> {
+        match var {
+            Variable::Input { location, .. } => Some(InterfaceVariable::Input(*location)),
+            Variable::Output { location, .. } => Some(InterfaceVariable::Output(*location)),
+            _ => None,
+        }
+    }
+}
+
+/// Minimum SPIR-V and Vulkan versions, capabilities and extensions required
+/// to run an execution model.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ExecutionModelRequirements {
+    /// Minimum SPIR-V version as `(major, minor)`.
+    pub spirv_version: (u8, u8),
+    /// Minimum Vulkan API version as `(major, minor)`, or `None` if the
+    /// execution model is not available in Vulkan.
+    pub vulkan_version: Option<(u32, u32)>,
+    /// Capabilities the module has to declare.
+    pub caps: &'static [spirv::Capability],
+    /// SPIR-V extensions the module has to declare.
+    pub spirv_exts: &'static [&'static str],
+    /// Vulkan device extensions to be enabled, on top of
+    /// `vulkan_version`. `VK_KHR_spirv_1_4` is core since Vulkan 1.2.
+    pub vulkan_exts: &'static [&'static str],
+}
+impl ExecutionModelRequirements {
+    /// Get the requirements of an execution model. Ray tracing execution
+    /// models share their values with the `SPV_NV_ray_tracing` ones, and the
+    /// requirements of `SPV_KHR_ray_tracing` are returned.
+    pub fn of(exec_model: ExecutionModel) -> Self {
+        use spirv::Capability as Cap;
+        let base = ExecutionModelRequirements {
+            spirv_version: (1, 0),
+            vulkan_version: Some((1, 0)),
+            caps: &[Cap::Shader],
+            spirv_exts: &[],
+            vulkan_exts: &[],
+        };
+        match exec_model {
+            ExecutionModel::Vertex | ExecutionModel::Fragment | ExecutionModel::GLCompute => base,
+            ExecutionModel::TessellationControl | ExecutionModel::TessellationEvaluation => {
+                ExecutionModelRequirements {
+                    caps: &[Cap::Tessellation],
+                    ..base
+                }
+            }
+            ExecutionModel::Geometry => ExecutionModelRequirements {
+                caps: &[Cap::Geometry],
+                ..base
+            },
+            ExecutionModel::Kernel => ExecutionModelRequirements {
+                vulkan_version: None,
+                caps: &[Cap::Kernel],
+                ..base
+            },
+            ExecutionModel::TaskNV | ExecutionModel::MeshNV => ExecutionModelRequirements {
+                caps: &[Cap::MeshShadingNV],
+                spirv_exts: &["SPV_NV_mesh_shader"],
+                vulkan_exts: &["VK_NV_mesh_shader"],
+                ..base
+            },
+            ExecutionModel::TaskEXT | ExecutionModel::MeshEXT => ExecutionModelRequirements {
+                spirv_version: (1, 4),
+                vulkan_version: Some((1, 1)),
+                caps: &[Cap::MeshShadingEXT],
+                spirv_exts: &["SPV_EXT_mesh_shader"],
+                vulkan_exts: &["VK_EXT_mesh_shader", "VK_KHR_spirv_1_4"],
+            },
+            ExecutionModel::RayGenerationNV
+            | ExecutionModel::IntersectionNV
+            | ExecutionModel::AnyHitNV
+            | ExecutionModel::ClosestHitNV
+            | ExecutionModel::MissNV
+            | ExecutionModel::CallableNV => ExecutionModelRequirements {
+                spirv_version: (1, 4),
+                vulkan_version: Some((1, 1)),
+                caps: &[Cap::RayTracingKHR],
+                spirv_exts: &["SPV_KHR_ray_tracing"],
+                vulkan_exts: &[
+                    "VK_KHR_ray_tracing_pipeline",
+                    "VK_KHR_acceleration_structure",
+                    "VK_KHR_spirv_1_4",
+                ],
+            },
+        }
+    }
+}
+
+/// Check if derivatives are available in `exec_model` with invocations
+/// grouped by the `DerivativeGroup*` execution modes.
+pub(crate) fn has_derivative_groups(exec_model: ExecutionModel) -> bool {
+    matches!(
+        exec_model,
+        ExecutionModel::GLCompute | ExecutionModel::TaskEXT | ExecutionModel::MeshEXT
+    )
+}
+
+/// Execution mode, capabilities and extensions required to use derivatives
+/// outside fragment shaders, where derivatives are taken between the
+/// invocations grouped by the execution mode. `SPV_NV_compute_shader_derivatives`
+/// shares its values with `SPV_KHR_compute_shader_derivatives`, and the
+/// requirements of the latter are returned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DerivativeRequirements {
+    /// Execution mode grouping invocations for derivatives, either
+    /// `DerivativeGroupQuadsNV` or `DerivativeGroupLinearNV`.
+    pub exec_mode: spirv::ExecutionMode,
+    /// `exec_mode` is declared by the entry point. Otherwise
+    /// `DerivativeGroupQuadsNV` is suggested, which groups invocations the
+    /// same way as fragments.
+    pub is_exec_mode_declared: bool,
+    /// Capabilities the module has to declare.
+    pub caps: &'static [spirv::Capability],
+    /// SPIR-V extensions the module has to declare.
+    pub spirv_exts: &'static [&'static str],
+    /// Vulkan device extensions to be enabled. Mesh and task shaders also
+    /// need the `meshAndTaskShaderDerivatives` property.
+    pub vulkan_exts: &'static [&'static str],
+}
+
 /// Representing an entry point described in a SPIR-V.
 #[derive(Clone, PartialEq, Eq, Hash)]
 pub struct EntryPoint {
@@ -12,6 +475,12 @@ pub struct EntryPoint {
     pub exec_model: spirv::ExecutionModel,
     /// Name of the entry point.
     pub name: String,
+    /// Source function name demangled from `name`, or `None` if `name` is not
+    /// mangled. Only for display; pipelines are created with `name`. See
+    /// [`ReflectConfig::demangler`].
+    ///
+    /// [`ReflectConfig::demangler`]: crate::reflect_cfg::ReflectConfig::demangler
+    pub demangled_name: Option<String>,
     /// Variables that contains specialization constant, input, output and
     /// descriptor type information.
     ///
@@ -22,14 +491,422 @@ pub struct EntryPoint {
     /// compute shader local sizes and specialization constant IDs of local
     /// sizes.
     pub exec_modes: Vec<ExecutionMode>,
+    /// All execution modes the entry point declares, in declaration order,
+    /// including those unknown to SPIR-Q and thus absent in `exec_modes`.
+    pub raw_exec_modes: Vec<RawExecutionMode>,
+    /// Additional descriptor information indexed by binding points. Every
+    /// descriptor binding point used in `vars` has an entry in this map.
+    pub desc_infos: BTreeMap<DescriptorBinding, DescriptorInfo>,
+    /// Components read from each input location, where bit `i` is set if
+    /// component `i` of the location is read. Every location of the inputs in
+    /// `vars` has an entry, so vertex formats can be trimmed to the components
+    /// actually used. Components are only tracked when they are extracted by
+    /// constant indices, otherwise the entire input is considered read. All
+    /// components are considered read if function bodies are not scanned.
+    /// Always empty for tessellation and geometry shaders.
+    pub input_comp_masks: BTreeMap<u32, u32>,
+    /// Decorations of stage inputs and outputs in `vars` other than
+    /// `Location` and `Component`, like transform feedback `Offset`,
+    /// `XfbBuffer`, `XfbStride` and `Stream`, and interpolation qualifiers,
+    /// with their operands. Every input and output in `vars` has an entry.
+    pub io_decos: BTreeMap<InterfaceVariable, BTreeMap<spirv::Decoration, Vec<u32>>>,
+    /// Decorations of the members of stage input and output blocks in
+    /// `vars`, like transform feedback `Offset`s of captured members,
+    /// indexed by member indices. Only blocks with decorated members have
+    /// an entry.
+    pub io_member_decos:
+        BTreeMap<InterfaceVariable, BTreeMap<u32, BTreeMap<spirv::Decoration, Vec<u32>>>>,
+    /// Variables as seen by an OpenGL application, including default-block
+    /// uniforms and atomic counters which have no Vulkan counterparts. Only
+    /// collected when [`ReflectConfig::gl`] is set.
+    ///
+    /// [`ReflectConfig::gl`]: crate::reflect_cfg::ReflectConfig::gl
+    pub gl_vars: Vec<GlVariable>,
+    /// Variables in workgroup memory, i.e., `shared` variables in GLSL.
+    pub shared_vars: Vec<SharedVariable>,
+    /// Payload emitted by a task shader or received by a mesh shader, in
+    /// the `TaskPayloadWorkgroupEXT` storage class.
+    pub task_payload: Option<SharedVariable>,
+    /// Built-ins of the input variables used by the entry point, including
+    /// the members of built-in blocks, e.g., the shading rate read by
+    /// fragment shaders.
+    pub input_builtins: BTreeSet<spirv::BuiltIn>,
+    /// Built-ins of the output variables used by the entry point, including
+    /// the members of built-in blocks like `gl_PerVertex`, e.g., the
+    /// primitive indices written by mesh shaders.
+    pub output_builtins: BTreeSet<spirv::BuiltIn>,
+    /// Module-scope variables in the `Private` storage class. Only collected
+    /// when [`ReflectConfig::private_vars`] is set.
+    ///
+    /// [`ReflectConfig::private_vars`]: crate::reflect_cfg::ReflectConfig::private_vars
+    pub private_vars: Vec<PrivateVariable>,
+    /// Module-scope variables in the `CrossWorkgroup` storage class, i.e.,
+    /// program-scope `global` variables of OpenCL kernels.
+    pub global_vars: Vec<PrivateVariable>,
+    /// Literal samplers declared in the module, in declaration order.
+    pub literal_samplers: Vec<LiteralSampler>,
+    /// Default values of the specialization constants in `vars`, indexed by
+    /// specialization IDs. Constants specialized by
+    /// [`ReflectConfig::specialize`] are not listed.
+    ///
+    /// [`ReflectConfig::specialize`]: crate::reflect_cfg::ReflectConfig::specialize
+    pub spec_defaults: BTreeMap<SpecId, ConstantValue>,
+    /// The entry point or functions it calls use derivatives, explicitly or
+    /// by sampling images with implicit LODs. Always `false` if function
+    /// bodies are not scanned.
+    pub uses_derivatives: bool,
+    /// Stage inputs and outputs in `vars` without location decorations,
+    /// whose locations are assigned by SPIR-Q. Only collected when
+    /// [`ReflectConfig::auto_assign_locations`] is set.
+    ///
+    /// [`ReflectConfig::auto_assign_locations`]: crate::reflect_cfg::ReflectConfig::auto_assign_locations
+    pub implicit_locations: BTreeSet<InterfaceVariable>,
+    /// Bytes of the push constant block read by the entry point, following
+    /// access chains with constant indices through struct members, array
+    /// elements and vector components. The entire block is considered read
+    /// if function bodies are not scanned, or if it's read in other ways.
+    /// `None` if no push constant is read.
+    pub push_const_bytes: Option<Range<u32>>,
+    /// The entry point or functions it calls write external memory, by
+    /// stores or atomics to storage buffers and physical storage buffers, or
+    /// by writes or atomics to storage images and texel buffers. Entry points
+    /// without it have no side effects other than their stage outputs. If
+    /// function bodies are not scanned, any writable storage descriptor is
+    /// considered written.
+    pub writes_external_memory: bool,
+    /// The entry point or functions it calls write the `PointSize`
+    /// built-in. If function bodies are not scanned, `PointSize` is
+    /// considered written if it's declared in an output used by the entry
+    /// point.
+    pub writes_point_size: bool,
+    /// Specialization IDs of the constants sizing arrays in the variables
+    /// used by the entry point, including descriptor arrays and workgroup
+    /// variables. Only constants sizing arrays directly are listed, not
+    /// those they are computed from by `OpSpecConstantOp`.
+    pub array_size_spec_ids: BTreeSet<SpecId>,
+    /// Specialization IDs of the constants the operands of
+    /// `OpExecutionModeId` depend on, like the local size declared by
+    /// `LocalSizeId`, including those the operands are computed from by
+    /// `OpSpecConstantOp`. Execution modes with operands that can't be folded
+    /// are not listed in [`EntryPoint::exec_modes`] but their dependencies are
+    /// listed here.
+    pub exec_mode_spec_ids: BTreeSet<SpecId>,
+    /// Warnings found when the entry point is reflected.
+    pub diags: Vec<Diagnostic>,
 }
 impl fmt::Debug for EntryPoint {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         f.debug_struct(&self.name)
             .field("exec_model", &self.exec_model)
             .field("name", &self.name)
+            .field("demangled_name", &self.demangled_name)
             .field("vars", &self.vars)
             .field("exec_modes", &self.exec_modes)
+            .field("raw_exec_modes", &self.raw_exec_modes)
+            .field("desc_infos", &self.desc_infos)
+            .field("input_comp_masks", &self.input_comp_masks)
+            .field("io_decos", &self.io_decos)
+            .field("io_member_decos", &self.io_member_decos)
+            .field("gl_vars", &self.gl_vars)
+            .field("shared_vars", &self.shared_vars)
+            .field("task_payload", &self.task_payload)
+            .field("input_builtins", &self.input_builtins)
+            .field("output_builtins", &self.output_builtins)
+            .field("private_vars", &self.private_vars)
+            .field("global_vars", &self.global_vars)
+            .field("literal_samplers", &self.literal_samplers)
+            .field("spec_defaults", &self.spec_defaults)
+            .field("uses_derivatives", &self.uses_derivatives)
+            .field("implicit_locations", &self.implicit_locations)
+            .field("push_const_bytes", &self.push_const_bytes)
+            .field("writes_external_memory", &self.writes_external_memory)
+            .field("writes_point_size", &self.writes_point_size)
+            .field("array_size_spec_ids", &self.array_size_spec_ids)
+            .field("exec_mode_spec_ids", &self.exec_mode_spec_ids)
+            .field("diags", &self.diags)
             .finish()
     }
 }
+/// Entry point reflected by [`ReflectConfig::reflect_borrowed`], whose name
+/// borrows from the SPIR-V binary instead of being copied out of it.
+///
+/// [`ReflectConfig::reflect_borrowed`]: crate::reflect_cfg::ReflectConfig::reflect_borrowed
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BorrowedEntryPoint<'a> {
+    /// Name of the entry point.
+    pub name: Cow<'a, str>,
+    /// The reflected entry point, with an empty [`EntryPoint::name`].
+    pub entry_point: EntryPoint,
+}
+impl<'a> BorrowedEntryPoint<'a> {
+    /// Copy the name into the entry point, so that it no longer borrows from
+    /// the SPIR-V binary.
+    pub fn into_owned(self) -> EntryPoint {
+        let mut entry_point = self.entry_point;
+        entry_point.name = self.name.into_owned();
+        entry_point
+    }
+}
+
+impl EntryPoint {
+    /// Name of the entry point to show to users, i.e., the demangled name if
+    /// `name` is mangled.
+    pub fn display_name(&self) -> &str {
+        self.demangled_name.as_deref().unwrap_or(&self.name)
+    }
+    /// Minimum versions, capabilities and extensions required by the
+    /// execution model of the entry point.
+    pub fn exec_model_requirements(&self) -> ExecutionModelRequirements {
+        ExecutionModelRequirements::of(self.exec_model)
+    }
+    /// Requirements to use derivatives in the entry point, or `None` if it
+    /// doesn't use derivatives or it's a fragment shader. Derivatives are
+    /// not available in execution models other than fragment, compute, mesh
+    /// and task, which is reported as
+    /// [`Diagnostic::UnsupportedDerivatives`] and `None` is returned.
+    pub fn derivative_requirements(&self) -> Option<DerivativeRequirements> {
+        use spirv::{Capability as Cap, ExecutionMode as Mode};
+        if !self.uses_derivatives || !has_derivative_groups(self.exec_model) {
+            return None;
+        }
+        let declared_mode = self
+            .raw_exec_modes
+            .iter()
+            .filter_map(|x| x.known_exec_mode())
+            .find(|x| {
+                matches!(
+                    x,
+                    Mode::DerivativeGroupQuadsNV | Mode::DerivativeGroupLinearNV
+                )
+            });
+        let exec_mode = declared_mode.unwrap_or(Mode::DerivativeGroupQuadsNV);
+        let caps: &'static [Cap] = match exec_mode {
+            Mode::DerivativeGroupLinearNV => &[Cap::ComputeDerivativeGroupLinearNV],
+            _ => &[Cap::ComputeDerivativeGroupQuadsNV],
+        };
+        let out = DerivativeRequirements {
+            exec_mode,
+            is_exec_mode_declared: declared_mode.is_some(),
+            caps,
+            spirv_exts: &["SPV_KHR_compute_shader_derivatives"],
+            vulkan_exts: &["VK_KHR_compute_shader_derivatives"],
+        };
+        Some(out)
+    }
+    /// Byte range of the push constant block read by the entry point, as in
+    /// [`EntryPoint::push_const_bytes`] but aligned to 4 bytes as required by
+    /// `VkPushConstantRange`. Pipelines sharing a push constant block can
+    /// use it to declare a tight range for each stage.
+    pub fn push_constant_range(&self) -> Option<Range<u32>> {
+        let range = self.push_const_bytes.as_ref()?;
+        Some((range.start & !3)..((range.end + 3) & !3))
+    }
+    /// Specialization IDs of the constants that change the layout or the
+    /// execution modes of the entry point, i.e., array sizes in
+    /// [`EntryPoint::array_size_spec_ids`] and operands of execution modes
+    /// like the local size. Changing them can change descriptor set layouts,
+    /// buffer sizes or dispatch sizes, while other specialization constants
+    /// are only used as values and changing them only requires new
+    /// specialization info. Operands of [`EntryPoint::exec_modes`] are only
+    /// considered with the `exec-modes` feature.
+    pub fn pipeline_affecting_spec_ids(&self) -> BTreeSet<SpecId> {
+        let mut out = self.array_size_spec_ids.clone();
+        out.extend(self.exec_mode_spec_ids.iter().copied());
+        for exec_mode in self.exec_modes.iter() {
+            out.extend(exec_mode.operands.iter().filter_map(|x| x.spec_id));
+        }
+        out
+    }
+    /// Get the decorations of a stage input or output variable, as in
+    /// [`EntryPoint::io_decos`]. Returns `None` if `var` is not an input or
+    /// output of the entry point.
+    pub fn io_decorations(&self, var: &Variable) -> Option<&BTreeMap<spirv::Decoration, Vec<u32>>> {
+        self.io_decos.get(&InterfaceVariable::of(var)?)
+    }
+    /// Get the decorations of the `member`-th member of a stage input or
+    /// output block, as in [`EntryPoint::io_member_decos`]. Returns `None`
+    /// if the member is not decorated.
+    pub fn io_member_decorations(
+        &self,
+        var: &Variable,
+        member: u32,
+    ) -> Option<&BTreeMap<spirv::Decoration, Vec<u32>>> {
+        self.io_member_decos
+            .get(&InterfaceVariable::of(var)?)?
+            .get(&member)
+    }
+    /// Total size in bytes of the workgroup memory used by the entry point,
+    /// to be checked against `maxComputeSharedMemorySize` or
+    /// `maxMeshSharedMemorySize`. Aliased blocks share the size of the
+    /// largest of them. Variables of unknown sizes are not counted.
+    pub fn shared_mem_nbyte(&self) -> usize {
+        let mut nbyte = 0;
+        let mut aliased_nbyte = 0;
+        for var in self.shared_vars.iter() {
+            let var_nbyte = var.nbyte.unwrap_or_default();
+            if var.is_aliased {
+                aliased_nbyte = aliased_nbyte.max(var_nbyte);
+            } else {
+                nbyte += var_nbyte;
+            }
+        }
+        nbyte + aliased_nbyte
+    }
+    /// The entry point outputs points regardless of the input primitive
+    /// topology, i.e., a geometry or mesh shader with the `OutputPoints`
+    /// execution mode, or a tessellation evaluation shader with the
+    /// `PointMode` execution mode. Vertex shaders output points only if the
+    /// pipeline is created with `VK_PRIMITIVE_TOPOLOGY_POINT_LIST`.
+    pub fn outputs_points(&self) -> bool {
+        use spirv::ExecutionMode as Mode;
+        let point_mode = match self.exec_model {
+            ExecutionModel::Geometry | ExecutionModel::MeshNV | ExecutionModel::MeshEXT => {
+                Mode::OutputPoints
+            }
+            ExecutionModel::TessellationEvaluation => Mode::PointMode,
+            _ => return false,
+        };
+        self.raw_exec_modes
+            .iter()
+            .any(|x| x.known_exec_mode() == Some(point_mode))
+    }
+    /// The entry point outputs points without writing `PointSize`, so the
+    /// size of the points is undefined unless the `maintenance5` feature is
+    /// enabled, in which case it's 1.0. Some drivers require `PointSize` to
+    /// be written anyway.
+    pub fn misses_point_size(&self) -> bool {
+        self.outputs_points() && !self.writes_point_size
+    }
+    /// Fragment shader interlock of the entry point, or `None` if it's not
+    /// declared.
+    pub fn fragment_interlock(&self) -> Option<FragmentInterlock> {
+        use spirv::ExecutionMode as Mode;
+        self.raw_exec_modes.iter().find_map(|x| {
+            let (scope, is_ordered) = match x.known_exec_mode()? {
+                Mode::PixelInterlockOrderedEXT => (InterlockScope::Pixel, true),
+                Mode::PixelInterlockUnorderedEXT => (InterlockScope::Pixel, false),
+                Mode::SampleInterlockOrderedEXT => (InterlockScope::Sample, true),
+                Mode::SampleInterlockUnorderedEXT => (InterlockScope::Sample, false),
+                Mode::ShadingRateInterlockOrderedEXT => (InterlockScope::ShadingRate, true),
+                Mode::ShadingRateInterlockUnorderedEXT => (InterlockScope::ShadingRate, false),
+                _ => return None,
+            };
+            Some(FragmentInterlock { scope, is_ordered })
+        })
+    }
+    /// Output limits and primitive type of a mesh shader, declared by the
+    /// `OutputVertices`, `OutputPrimitivesEXT` and `OutputPoints`,
+    /// `OutputLinesEXT` or `OutputTrianglesEXT` execution modes. `None` if
+    /// the entry point is not a mesh shader or any of them is missing.
+    pub fn mesh_outputs(&self) -> Option<MeshOutputs> {
+        use spirv::ExecutionMode as Mode;
+        if !matches!(
+            self.exec_model,
+            ExecutionModel::MeshEXT | ExecutionModel::MeshNV
+        ) {
+            return None;
+        }
+        let mut max_vertices = None;
+        let mut max_primitives = None;
+        let mut prim_ty = None;
+        for exec_mode in self.raw_exec_modes.iter() {
+            // `OutputVertices` is also used by geometry and tessellation
+            // shaders and `OutputPoints` by geometry shaders, but only the
+            // mesh shader interpretations apply here.
+            match (exec_mode.known_exec_mode(), exec_mode.operands.as_slice()) {
+                (Some(Mode::OutputVertices), [x]) if !exec_mode.is_id => max_vertices = Some(*x),
+                (Some(Mode::OutputPrimitivesEXT), [x]) if !exec_mode.is_id => {
+                    max_primitives = Some(*x)
+                }
+                (Some(Mode::OutputPoints), _) => prim_ty = Some(MeshPrimitiveType::Points),
+                (Some(Mode::OutputLinesEXT), _) => prim_ty = Some(MeshPrimitiveType::Lines),
+                (Some(Mode::OutputTrianglesEXT), _) => prim_ty = Some(MeshPrimitiveType::Triangles),
+                _ => {}
+            }
+        }
+        Some(MeshOutputs {
+            max_vertices: max_vertices?,
+            max_primitives: max_primitives?,
+            prim_ty: prim_ty?,
+        })
+    }
+    /// Get the input attachments read by the entry point, sorted by input
+    /// attachment indices. Elements of input attachment arrays take
+    /// consecutive input attachment indices and are listed separately, while
+    /// runtime-sized arrays only list their first element. Multiple
+    /// variables aliased at a same input attachment index are all listed.
+    pub fn input_attachments(&self) -> Vec<InputAttachment> {
+        let mut out = Vec::new();
+        for var in self.vars.iter() {
+            if let Variable::Descriptor {
+                name,
+                desc_bind,
+                desc_ty: DescriptorType::InputAttachment(input_attm_idx),
+                ty: Type::SubpassData(subpass_data_ty),
+                nbind,
+            } = var
+            {
+                for bind_idx in 0..(*nbind).max(1) {
+                    out.push(InputAttachment {
+                        input_attm_idx: input_attm_idx + bind_idx,
+                        name: name.clone(),
+                        desc_bind: *desc_bind,
+                        bind_idx,
+                        scalar_ty: subpass_data_ty.scalar_ty.clone(),
+                        is_multisampled: subpass_data_ty.is_multisampled,
+                    });
+                }
+            }
+        }
+        out.sort_by_key(|x| (x.input_attm_idx, x.desc_bind));
+        out
+    }
+    /// Walk the leaf values, i.e., scalars, vectors and matrices, in all the
+    /// push constant blocks and buffers used by the entry point, in the
+    /// order of `vars`. Each value is routed as in [`Type::walk`] and tagged
+    /// with the variable it's in. Buffers in descriptor arrays are walked
+    /// once for all the array elements, and runtime arrays are treated as
+    /// empty.
+    pub fn walk_all(&self) -> impl Iterator<Item = VariableRoute<'_>> {
+        self.vars
+            .iter()
+            .filter_map(|var| match var {
+                Variable::PushConstant { name, ty } => {
+                    Some((RouteOwner::PushConstant, name.as_deref(), ty))
+                }
+                Variable::Descriptor {
+                    name,
+                    desc_bind,
+                    desc_ty: DescriptorType::UniformBuffer() | DescriptorType::StorageBuffer(_),
+                    ty,
+                    ..
+                } => Some((RouteOwner::Descriptor(*desc_bind), name.as_deref(), ty)),
+                _ => None,
+            })
+            .flat_map(|(owner, var_name, ty)| {
+                ty.walk()
+                    .filter(|route| !route.ty.is_struct() && !route.ty.is_array())
+                    .map(move |route| VariableRoute {
+                        owner,
+                        var_name,
+                        route,
+                    })
+            })
+    }
+    /// Get descriptor resources in descriptor set `desc_set` keyed by
+    /// binding. Bindings not used by the entry point are absent, so sparse
+    /// bindings don't cost memory. If multiple resources are bound to a same
+    /// binding point, the first one in `vars` is returned. The returned map
+    /// is empty if the set is not used at all.
+    pub fn set_binding_map(&self, desc_set: u32) -> BTreeMap<u32, &Variable> {
+        let mut out = BTreeMap::new();
+        for var in self.vars.iter() {
+            if let Variable::Descriptor { desc_bind, .. } = var {
+                if desc_bind.set() == desc_set {
+                    out.entry(desc_bind.bind()).or_insert(var);
+                }
+            }
+        }
+        out
+    }
+}
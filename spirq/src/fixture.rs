@@ -0,0 +1,675 @@
+//! Synthetic SPIR-V modules reconstructed from reflection results.
+//!
+//! A fixture declares the same interface as the reflected entry point, i.e.,
+//! types, descriptor resources, push constants, specialization constants,
+//! stage inputs and outputs, and execution modes, with an empty function
+//! body. Reflecting a fixture gives back the same variables when all
+//! resources are referenced (see [`ReflectConfig::ref_all_rscs`]). It's
+//! useful for downstream tests to build pipelines against mock shaders
+//! without a shader compiler. Fixtures are meant to be reflected and are not
+//! guaranteed to pass `spirv-val`.
+//!
+//! [`ReflectConfig::ref_all_rscs`]: crate::reflect_cfg::ReflectConfig::ref_all_rscs
+use fnv::{FnvHashMap as HashMap, FnvHashSet as HashSet};
+
+use crate::{
+    constant::{Constant, ConstantValue},
+    entry_point::EntryPoint,
+    error::{anyhow, Result},
+    parse::SpirvBinary,
+    spirv::{
+        AddressingModel, Capability, Decoration, Dim, ImageFormat, MemoryModel, Op, StorageClass,
+    },
+    stub::fmt_prefix,
+    ty::{
+        AccessType, ArrayType, DescriptorType, MatrixAxisOrder, MatrixType, PointerType,
+        ScalarType, SpirvType, StructType, Type,
+    },
+    var::{SpecId, Variable},
+};
+
+const SPIRV_MAGIC: u32 = 0x07230203;
+/// Fixtures are SPIR-V 1.5 modules so `StorageBuffer` storage class is
+/// available without extensions.
+const SPIRV_VERSION: u32 = 0x00010500;
+
+/// Generate a SPIR-V module declaring the interface of `entry_point`.
+pub fn generate_fixture(entry_point: &EntryPoint) -> Result<SpirvBinary> {
+    let mut writer = FixtureWriter::default();
+    let mut interface = Vec::new();
+    for var in entry_point.vars.iter() {
+        if let Some(var_id) = writer.var(var)? {
+            interface.push(var_id);
+        }
+    }
+
+    let main_id = writer.alloc_id();
+    let mut entry_point_operands = vec![entry_point.exec_model as u32, main_id];
+    entry_point_operands.extend(str_words(&entry_point.name));
+    entry_point_operands.extend(interface);
+    let mut entry_point_sec = Vec::new();
+    push_instr(&mut entry_point_sec, Op::EntryPoint, &entry_point_operands);
+
+    let mut exec_mode_sec = Vec::new();
+    for raw in entry_point.raw_exec_modes.iter() {
+        let mut operands = vec![main_id, raw.exec_mode];
+        if raw.is_id {
+            // Operand IDs in the original module are meaningless here, so
+            // the interpreted operands are declared again.
+            let exec_mode = entry_point
+                .exec_modes
+                .iter()
+                .find(|x| x.exec_mode as u32 == raw.exec_mode)
+                .ok_or_else(|| {
+                    anyhow!(
+                        "execution mode {} has no interpreted operands",
+                        raw.exec_mode
+                    )
+                })?;
+            for operand in exec_mode.operands.iter() {
+                operands.push(writer.constant(operand)?);
+            }
+            push_instr(&mut exec_mode_sec, Op::ExecutionModeId, &operands);
+        } else {
+            operands.extend_from_slice(&raw.operands);
+            push_instr(&mut exec_mode_sec, Op::ExecutionMode, &operands);
+        }
+    }
+
+    let void_id = writer.ty(&Type::Scalar(ScalarType::Void))?;
+    let func_ty_id = writer.alloc_id();
+    push_instr(
+        &mut writer.globals,
+        Op::TypeFunction,
+        &[func_ty_id, void_id],
+    );
+    let mut func_sec = Vec::new();
+    push_instr(
+        &mut func_sec,
+        Op::Function,
+        &[void_id, main_id, 0, func_ty_id],
+    );
+    let label_id = writer.alloc_id();
+    push_instr(&mut func_sec, Op::Label, &[label_id]);
+    push_instr(&mut func_sec, Op::Return, &[]);
+    push_instr(&mut func_sec, Op::FunctionEnd, &[]);
+
+    let id_bound = writer.next_id + 1;
+    let mut out = vec![SPIRV_MAGIC, SPIRV_VERSION, 0, id_bound, 0];
+    push_instr(&mut out, Op::Capability, &[Capability::Shader as u32]);
+    for cap in writer.caps.iter() {
+        push_instr(&mut out, Op::Capability, &[*cap as u32]);
+    }
+    for ext in writer.exts.iter() {
+        push_instr(&mut out, Op::Extension, &str_words(ext));
+    }
+    let addr_model = if writer
+        .caps
+        .contains(&Capability::PhysicalStorageBufferAddresses)
+    {
+        AddressingModel::PhysicalStorageBuffer64
+    } else {
+        AddressingModel::Logical
+    };
+    push_instr(
+        &mut out,
+        Op::MemoryModel,
+        &[addr_model as u32, MemoryModel::GLSL450 as u32],
+    );
+    out.extend(entry_point_sec);
+    out.extend(exec_mode_sec);
+    out.extend(writer.names);
+    out.extend(writer.decos);
+    out.extend(writer.globals);
+    out.extend(func_sec);
+    Ok(SpirvBinary::from(out))
+}
+
+pub(crate) fn push_instr(out: &mut Vec<u32>, op: Op, operands: &[u32]) {
+    out.push(((operands.len() as u32 + 1) << 16) | op as u32);
+    out.extend_from_slice(operands);
+}
+/// Encode a null-terminated literal string.
+fn str_words(x: &str) -> Vec<u32> {
+    let mut bytes = x.as_bytes().to_owned();
+    bytes.resize(bytes.len() / 4 * 4 + 4, 0);
+    bytes
+        .chunks_exact(4)
+        .map(|x| u32::from_le_bytes([x[0], x[1], x[2], x[3]]))
+        .collect()
+}
+
+#[derive(Default)]
+struct FixtureWriter {
+    next_id: u32,
+    /// Capabilities other than `Shader`, in declaration order.
+    caps: Vec<Capability>,
+    exts: Vec<&'static str>,
+    /// Debug instructions.
+    names: Vec<u32>,
+    /// Annotation instructions.
+    decos: Vec<u32>,
+    /// Types, constants and global variables.
+    globals: Vec<u32>,
+    ty_ids: HashMap<Type, u32>,
+    u32_const_ids: HashMap<u32, u32>,
+    spec_const_ids: HashMap<SpecId, u32>,
+    block_ids: HashSet<u32>,
+}
+impl FixtureWriter {
+    fn alloc_id(&mut self) -> u32 {
+        self.next_id += 1;
+        self.next_id
+    }
+    fn require_cap(&mut self, cap: Capability) {
+        if !self.caps.contains(&cap) {
+            self.caps.push(cap);
+        }
+    }
+    fn require_ext(&mut self, ext: &'static str) {
+        if !self.exts.contains(&ext) {
+            self.exts.push(ext);
+        }
+    }
+    fn name(&mut self, id: u32, name: Option<&str>) {
+        if let Some(name) = name {
+            let mut operands = vec![id];
+            operands.extend(str_words(name));
+            push_instr(&mut self.names, Op::Name, &operands);
+        }
+    }
+    fn decorate(&mut self, id: u32, deco: Decoration, params: &[u32]) {
+        let mut operands = vec![id, deco as u32];
+        operands.extend_from_slice(params);
+        push_instr(&mut self.decos, Op::Decorate, &operands);
+    }
+    fn decorate_member(&mut self, id: u32, member_idx: u32, deco: Decoration, params: &[u32]) {
+        let mut operands = vec![id, member_idx, deco as u32];
+        operands.extend_from_slice(params);
+        push_instr(&mut self.decos, Op::MemberDecorate, &operands);
+    }
+    fn decorate_access(&mut self, id: u32, access: AccessType) {
+        match access {
+            AccessType::ReadOnly => self.decorate(id, Decoration::NonWritable, &[]),
+            AccessType::WriteOnly => self.decorate(id, Decoration::NonReadable, &[]),
+            AccessType::ReadWrite => {}
+        }
+    }
+
+    fn u32_const(&mut self, value: u32) -> Result<u32> {
+        if let Some(id) = self.u32_const_ids.get(&value) {
+            return Ok(*id);
+        }
+        let ty_id = self.ty(&Type::Scalar(ScalarType::u32()))?;
+        let id = self.alloc_id();
+        push_instr(&mut self.globals, Op::Constant, &[ty_id, id, value]);
+        self.u32_const_ids.insert(value, id);
+        Ok(id)
+    }
+    /// Declare a constant operand of an execution mode. Specialization
+    /// constants declared by the interface are referred to directly.
+    fn constant(&mut self, constant: &Constant) -> Result<u32> {
+        if let Some(id) = constant.spec_id.and_then(|x| self.spec_const_ids.get(&x)) {
+            return Ok(*id);
+        }
+        let ty_id = self.ty(&constant.ty)?;
+        let id = self.alloc_id();
+        match &constant.value {
+            ConstantValue::Bool(true) => {
+                push_instr(&mut self.globals, Op::ConstantTrue, &[ty_id, id])
+            }
+            ConstantValue::Bool(false) => {
+                push_instr(&mut self.globals, Op::ConstantFalse, &[ty_id, id])
+            }
+            value => {
+                let mut bytes = value
+                    .to_typeless()
+                    .ok_or_else(|| anyhow!("cannot encode constant {:?}", value))?
+                    .into_vec();
+                while bytes.len() % 4 != 0 {
+                    bytes.push(0);
+                }
+                let mut operands = vec![ty_id, id];
+                operands.extend(
+                    bytes
+                        .chunks_exact(4)
+                        .map(|x| u32::from_le_bytes([x[0], x[1], x[2], x[3]])),
+                );
+                push_instr(&mut self.globals, Op::Constant, &operands);
+            }
+        }
+        Ok(id)
+    }
+
+    fn scalar_ty(&mut self, scalar_ty: &ScalarType, id: u32) -> Result<()> {
+        match scalar_ty {
+            ScalarType::Void => push_instr(&mut self.globals, Op::TypeVoid, &[id]),
+            ScalarType::Boolean => push_instr(&mut self.globals, Op::TypeBool, &[id]),
+            ScalarType::Integer { bits, is_signed } => {
+                match bits {
+                    8 => self.require_cap(Capability::Int8),
+                    16 => self.require_cap(Capability::Int16),
+                    32 => {}
+                    64 => self.require_cap(Capability::Int64),
+                    _ => return Err(anyhow!("unsupported integer width {}", bits)),
+                }
+                push_instr(
+                    &mut self.globals,
+                    Op::TypeInt,
+                    &[id, *bits, *is_signed as u32],
+                );
+            }
+            ScalarType::Float { bits } => {
+                match bits {
+                    16 => self.require_cap(Capability::Float16),
+                    32 => {}
+                    64 => self.require_cap(Capability::Float64),
+                    _ => return Err(anyhow!("unsupported float width {}", bits)),
+                }
+                push_instr(&mut self.globals, Op::TypeFloat, &[id, *bits]);
+            }
+        }
+        Ok(())
+    }
+    #[allow(clippy::too_many_arguments)]
+    fn image_ty(
+        &mut self,
+        id: u32,
+        scalar_ty: &ScalarType,
+        dim: Dim,
+        is_depth: Option<bool>,
+        is_array: bool,
+        is_multisampled: bool,
+        is_sampled: Option<bool>,
+        fmt: ImageFormat,
+    ) -> Result<()> {
+        let is_storage = is_sampled == Some(false);
+        match (dim, is_storage) {
+            (Dim::Dim1D, false) => self.require_cap(Capability::Sampled1D),
+            (Dim::Dim1D, true) => self.require_cap(Capability::Image1D),
+            (Dim::DimRect, false) => self.require_cap(Capability::SampledRect),
+            (Dim::DimRect, true) => self.require_cap(Capability::ImageRect),
+            (Dim::DimBuffer, false) => self.require_cap(Capability::SampledBuffer),
+            (Dim::DimBuffer, true) => self.require_cap(Capability::ImageBuffer),
+            (Dim::DimCube, false) if is_array => self.require_cap(Capability::SampledCubeArray),
+            (Dim::DimCube, true) if is_array => self.require_cap(Capability::ImageCubeArray),
+            (Dim::DimSubpassData, _) => self.require_cap(Capability::InputAttachment),
+            _ => {}
+        }
+        let scalar_ty_id = self.ty(&Type::Scalar(scalar_ty.clone()))?;
+        let is_depth = match is_depth {
+            Some(false) => 0,
+            Some(true) => 1,
+            None => 2,
+        };
+        let is_sampled = match is_sampled {
+            None => 0,
+            Some(true) => 1,
+            Some(false) => 2,
+        };
+        push_instr(
+            &mut self.globals,
+            Op::TypeImage,
+            &[
+                id,
+                scalar_ty_id,
+                dim as u32,
+                is_depth,
+                is_array as u32,
+                is_multisampled as u32,
+                is_sampled,
+                fmt as u32,
+            ],
+        );
+        Ok(())
+    }
+    fn struct_ty(&mut self, struct_ty: &StructType) -> Result<u32> {
+        let mut operands = Vec::with_capacity(struct_ty.members.len() + 1);
+        for member in struct_ty.members.iter() {
+            operands.push(self.ty(&member.ty)?);
+        }
+        let id = self.alloc_id();
+        operands.insert(0, id);
+        push_instr(&mut self.globals, Op::TypeStruct, &operands);
+        self.name(id, struct_ty.name.as_deref());
+        for (i, member) in struct_ty.members.iter().enumerate() {
+            let i = i as u32;
+            if let Some(name) = member.name.as_deref() {
+                let mut operands = vec![id, i];
+                operands.extend(str_words(name));
+                push_instr(&mut self.names, Op::MemberName, &operands);
+            }
+            if let Some(offset) = member.offset {
+                self.decorate_member(id, i, Decoration::Offset, &[offset as u32]);
+            }
+            match member.access_ty {
+                AccessType::ReadOnly => self.decorate_member(id, i, Decoration::NonWritable, &[]),
+                AccessType::WriteOnly => self.decorate_member(id, i, Decoration::NonReadable, &[]),
+                AccessType::ReadWrite => {}
+            }
+            // Matrix layouts are decorated on struct members.
+            let mut element_ty = &member.ty;
+            while let Type::Array(arr_ty) = element_ty {
+                element_ty = &*arr_ty.element_ty;
+            }
+            if let Type::Matrix(mat_ty) = element_ty {
+                if let Some(stride) = mat_ty.stride {
+                    self.decorate_member(id, i, Decoration::MatrixStride, &[stride as u32]);
+                }
+                match mat_ty.axis_order {
+                    Some(MatrixAxisOrder::ColumnMajor) => {
+                        self.decorate_member(id, i, Decoration::ColMajor, &[])
+                    }
+                    Some(MatrixAxisOrder::RowMajor) => {
+                        self.decorate_member(id, i, Decoration::RowMajor, &[])
+                    }
+                    None => {}
+                }
+            }
+        }
+        Ok(id)
+    }
+    fn ty(&mut self, ty: &Type) -> Result<u32> {
+        // Matrix layouts are member decorations rather than a part of the
+        // matrix type.
+        let key = match ty {
+            Type::Matrix(mat_ty) => Type::Matrix(MatrixType {
+                axis_order: None,
+                stride: None,
+                ..mat_ty.clone()
+            }),
+            ty => ty.clone(),
+        };
+        if let Some(id) = self.ty_ids.get(&key) {
+            return Ok(*id);
+        }
+        let id = match ty {
+            Type::Scalar(scalar_ty) => {
+                let id = self.alloc_id();
+                self.scalar_ty(scalar_ty, id)?;
+                id
+            }
+            Type::Vector(vec_ty) => {
+                let scalar_ty_id = self.ty(&Type::Scalar(vec_ty.scalar_ty.clone()))?;
+                let id = self.alloc_id();
+                push_instr(
+                    &mut self.globals,
+                    Op::TypeVector,
+                    &[id, scalar_ty_id, vec_ty.nscalar],
+                );
+                id
+            }
+            Type::Matrix(mat_ty) => {
+                let vec_ty_id = self.ty(&Type::Vector(mat_ty.vector_ty.clone()))?;
+                let id = self.alloc_id();
+                push_instr(
+                    &mut self.globals,
+                    Op::TypeMatrix,
+                    &[id, vec_ty_id, mat_ty.nvector],
+                );
+                id
+            }
+            Type::Image(x) => {
+                let id = self.alloc_id();
+                self.image_ty(
+                    id,
+                    &x.scalar_ty,
+                    x.dim,
+                    x.is_depth,
+                    x.is_array,
+                    x.is_multisampled,
+                    x.is_sampled,
+                    x.fmt,
+                )?;
+                id
+            }
+            Type::SampledImage(x) => {
+                let id = self.alloc_id();
+                self.image_ty(
+                    id,
+                    &x.scalar_ty,
+                    x.dim,
+                    x.is_depth,
+                    x.is_array,
+                    x.is_multisampled,
+                    Some(true),
+                    ImageFormat::Unknown,
+                )?;
+                id
+            }
+            Type::StorageImage(x) => {
+                let id = self.alloc_id();
+                self.image_ty(
+                    id,
+                    &fmt_scalar_ty(x.fmt),
+                    x.dim,
+                    Some(false),
+                    x.is_array,
+                    x.is_multisampled,
+                    Some(false),
+                    x.fmt,
+                )?;
+                id
+            }
+            Type::CombinedImageSampler(x) => {
+                let x = &x.sampled_image_ty;
+                let image_ty_id = self.ty(&Type::SampledImage(x.clone()))?;
+                let id = self.alloc_id();
+                push_instr(&mut self.globals, Op::TypeSampledImage, &[id, image_ty_id]);
+                id
+            }
+            Type::Sampler(_) => {
+                let id = self.alloc_id();
+                push_instr(&mut self.globals, Op::TypeSampler, &[id]);
+                id
+            }
+            Type::SubpassData(x) => {
+                let id = self.alloc_id();
+                self.image_ty(
+                    id,
+                    &x.scalar_ty,
+                    Dim::DimSubpassData,
+                    Some(false),
+                    false,
+                    x.is_multisampled,
+                    Some(false),
+                    ImageFormat::Unknown,
+                )?;
+                id
+            }
+            Type::Array(arr_ty) => {
+                let element_ty_id = self.ty(&arr_ty.element_ty)?;
+                let id = match arr_ty.nelement {
+                    Some(nelement) => {
+                        let nelement_id = self.u32_const(nelement)?;
+                        let id = self.alloc_id();
+                        push_instr(
+                            &mut self.globals,
+                            Op::TypeArray,
+                            &[id, element_ty_id, nelement_id],
+                        );
+                        id
+                    }
+                    None => {
+                        let id = self.alloc_id();
+                        push_instr(
+                            &mut self.globals,
+                            Op::TypeRuntimeArray,
+                            &[id, element_ty_id],
+                        );
+                        id
+                    }
+                };
+                if let Some(stride) = arr_ty.stride {
+                    self.decorate(id, Decoration::ArrayStride, &[stride as u32]);
+                }
+                id
+            }
+            Type::Struct(struct_ty) => self.struct_ty(struct_ty)?,
+            Type::AccelStruct(_) => {
+                self.require_cap(Capability::RayQueryKHR);
+                self.require_ext("SPV_KHR_ray_query");
+                let id = self.alloc_id();
+                push_instr(&mut self.globals, Op::TypeAccelerationStructureKHR, &[id]);
+                id
+            }
+            Type::RayQuery(_) => {
+                self.require_cap(Capability::RayQueryKHR);
+                self.require_ext("SPV_KHR_ray_query");
+                let id = self.alloc_id();
+                push_instr(&mut self.globals, Op::TypeRayQueryKHR, &[id]);
+                id
+            }
+            Type::DeviceAddress(_) => {
+                // The pointee type is unknown so the forward declared pointer
+                // is never defined.
+                self.require_cap(Capability::PhysicalStorageBufferAddresses);
+                let id = self.alloc_id();
+                push_instr(
+                    &mut self.globals,
+                    Op::TypeForwardPointer,
+                    &[id, StorageClass::PhysicalStorageBuffer as u32],
+                );
+                id
+            }
+            Type::DevicePointer(ptr_ty) => {
+                if ptr_ty.store_cls == StorageClass::PhysicalStorageBuffer {
+                    self.require_cap(Capability::PhysicalStorageBufferAddresses);
+                }
+                let pointee_ty_id = self.ty(&ptr_ty.pointee_ty)?;
+                let id = self.alloc_id();
+                push_instr(
+                    &mut self.globals,
+                    Op::TypePointer,
+                    &[id, ptr_ty.store_cls as u32, pointee_ty_id],
+                );
+                id
+            }
+            _ => return Err(anyhow!("unsupported type {}", ty)),
+        };
+        self.ty_ids.insert(key, id);
+        Ok(id)
+    }
+
+    fn global_var(
+        &mut self,
+        name: Option<&str>,
+        store_cls: StorageClass,
+        ty: &Type,
+    ) -> Result<u32> {
+        let ptr_ty = Type::DevicePointer(PointerType {
+            pointee_ty: Box::new(ty.clone()),
+            store_cls,
+        });
+        let ptr_ty_id = self.ty(&ptr_ty)?;
+        let id = self.alloc_id();
+        push_instr(
+            &mut self.globals,
+            Op::Variable,
+            &[ptr_ty_id, id, store_cls as u32],
+        );
+        self.name(id, name);
+        Ok(id)
+    }
+    fn block(&mut self, ty: &Type) -> Result<()> {
+        let id = self.ty(ty)?;
+        if self.block_ids.insert(id) {
+            self.decorate(id, Decoration::Block, &[]);
+        }
+        Ok(())
+    }
+    /// Declare `var`. Returns the variable ID if it's a global variable.
+    fn var(&mut self, var: &Variable) -> Result<Option<u32>> {
+        let id = match var {
+            Variable::Input { name, location, ty } | Variable::Output { name, location, ty } => {
+                let store_cls = match var {
+                    Variable::Input { .. } => StorageClass::Input,
+                    _ => StorageClass::Output,
+                };
+                let id = self.global_var(name.as_deref(), store_cls, ty)?;
+                self.decorate(id, Decoration::Location, &[location.loc()]);
+                if location.comp() != 0 {
+                    self.decorate(id, Decoration::Component, &[location.comp()]);
+                }
+                id
+            }
+            Variable::Descriptor {
+                name,
+                desc_bind,
+                desc_ty,
+                ty,
+                nbind,
+            } => {
+                let store_cls = match desc_ty {
+                    DescriptorType::UniformBuffer() => StorageClass::Uniform,
+                    DescriptorType::StorageBuffer(_) => StorageClass::StorageBuffer,
+                    _ => StorageClass::UniformConstant,
+                };
+                if let Type::Struct(_) = ty {
+                    self.block(ty)?;
+                }
+                let var_ty = match nbind {
+                    1 => ty.clone(),
+                    nbind => Type::Array(ArrayType {
+                        element_ty: Box::new(ty.clone()),
+                        nelement: if *nbind == 0 { None } else { Some(*nbind) },
+                        stride: None,
+                    }),
+                };
+                let id = self.global_var(name.as_deref(), store_cls, &var_ty)?;
+                self.decorate(id, Decoration::DescriptorSet, &[desc_bind.set()]);
+                self.decorate(id, Decoration::Binding, &[desc_bind.bind()]);
+                match desc_ty {
+                    DescriptorType::StorageBuffer(access)
+                    | DescriptorType::StorageImage(access)
+                    | DescriptorType::StorageTexelBuffer(access) => {
+                        self.decorate_access(id, *access);
+                    }
+                    DescriptorType::InputAttachment(idx) => {
+                        self.decorate(id, Decoration::InputAttachmentIndex, &[*idx]);
+                    }
+                    _ => {}
+                }
+                id
+            }
+            Variable::PushConstant { name, ty } => {
+                self.block(ty)?;
+                self.global_var(name.as_deref(), StorageClass::PushConstant, ty)?
+            }
+            Variable::SpecConstant { name, spec_id, ty } => {
+                let ty_id = self.ty(ty)?;
+                let id = self.alloc_id();
+                match ty {
+                    Type::Scalar(ScalarType::Boolean) => {
+                        push_instr(&mut self.globals, Op::SpecConstantFalse, &[ty_id, id]);
+                    }
+                    Type::Scalar(scalar_ty) => {
+                        let nword = scalar_ty.nbyte().unwrap_or(4).max(4) / 4;
+                        let mut operands = vec![ty_id, id];
+                        operands.resize(2 + nword, 0);
+                        push_instr(&mut self.globals, Op::SpecConstant, &operands);
+                    }
+                    _ => return Err(anyhow!("unsupported specialization constant type {}", ty)),
+                }
+                self.name(id, name.as_deref());
+                self.decorate(id, Decoration::SpecId, &[*spec_id]);
+                self.spec_const_ids.insert(*spec_id, id);
+                return Ok(None);
+            }
+        };
+        Ok(Some(id))
+    }
+}
+
+/// Sampled type of a storage image of format `fmt`.
+fn fmt_scalar_ty(fmt: ImageFormat) -> ScalarType {
+    let bits = match fmt {
+        ImageFormat::R64i | ImageFormat::R64ui => 64,
+        _ => 32,
+    };
+    match fmt_prefix(fmt) {
+        "u" => ScalarType::uint(bits),
+        "i" => ScalarType::int(bits),
+        _ => ScalarType::f32(),
+    }
+}
@@ -0,0 +1,234 @@
+//! Vulkan counterparts of image formats declared in SPIR-V, to validate the
+//! formats of image views bound to storage images and texel buffers.
+use std::fmt;
+
+use crate::{
+    entry_point::EntryPoint,
+    ty::{AccessType, DescriptorType, ImageFormat, Type},
+    var::{DescriptorBinding, Variable},
+};
+
+/// `(format, VkFormat value, VkFormat name)` as listed in the Vulkan spec
+/// section *Compatibility Between SPIR-V Image Formats and Vulkan Formats*.
+const VK_FORMATS: &[(ImageFormat, u32, &str)] = &[
+    (ImageFormat::Rgba32f, 109, "VK_FORMAT_R32G32B32A32_SFLOAT"),
+    (ImageFormat::Rgba16f, 97, "VK_FORMAT_R16G16B16A16_SFLOAT"),
+    (ImageFormat::R32f, 100, "VK_FORMAT_R32_SFLOAT"),
+    (ImageFormat::Rgba8, 37, "VK_FORMAT_R8G8B8A8_UNORM"),
+    (ImageFormat::Rgba8Snorm, 38, "VK_FORMAT_R8G8B8A8_SNORM"),
+    (ImageFormat::Rg32f, 103, "VK_FORMAT_R32G32_SFLOAT"),
+    (ImageFormat::Rg16f, 83, "VK_FORMAT_R16G16_SFLOAT"),
+    (
+        ImageFormat::R11fG11fB10f,
+        122,
+        "VK_FORMAT_B10G11R11_UFLOAT_PACK32",
+    ),
+    (ImageFormat::R16f, 76, "VK_FORMAT_R16_SFLOAT"),
+    (ImageFormat::Rgba16, 91, "VK_FORMAT_R16G16B16A16_UNORM"),
+    (
+        ImageFormat::Rgb10A2,
+        64,
+        "VK_FORMAT_A2B10G10R10_UNORM_PACK32",
+    ),
+    (ImageFormat::Rg16, 77, "VK_FORMAT_R16G16_UNORM"),
+    (ImageFormat::Rg8, 16, "VK_FORMAT_R8G8_UNORM"),
+    (ImageFormat::R16, 70, "VK_FORMAT_R16_UNORM"),
+    (ImageFormat::R8, 9, "VK_FORMAT_R8_UNORM"),
+    (ImageFormat::Rgba16Snorm, 92, "VK_FORMAT_R16G16B16A16_SNORM"),
+    (ImageFormat::Rg16Snorm, 78, "VK_FORMAT_R16G16_SNORM"),
+    (ImageFormat::Rg8Snorm, 17, "VK_FORMAT_R8G8_SNORM"),
+    (ImageFormat::R16Snorm, 71, "VK_FORMAT_R16_SNORM"),
+    (ImageFormat::R8Snorm, 10, "VK_FORMAT_R8_SNORM"),
+    (ImageFormat::Rgba32i, 108, "VK_FORMAT_R32G32B32A32_SINT"),
+    (ImageFormat::Rgba16i, 96, "VK_FORMAT_R16G16B16A16_SINT"),
+    (ImageFormat::Rgba8i, 42, "VK_FORMAT_R8G8B8A8_SINT"),
+    (ImageFormat::R32i, 99, "VK_FORMAT_R32_SINT"),
+    (ImageFormat::Rg32i, 102, "VK_FORMAT_R32G32_SINT"),
+    (ImageFormat::Rg16i, 82, "VK_FORMAT_R16G16_SINT"),
+    (ImageFormat::Rg8i, 21, "VK_FORMAT_R8G8_SINT"),
+    (ImageFormat::R16i, 75, "VK_FORMAT_R16_SINT"),
+    (ImageFormat::R8i, 14, "VK_FORMAT_R8_SINT"),
+    (ImageFormat::Rgba32ui, 107, "VK_FORMAT_R32G32B32A32_UINT"),
+    (ImageFormat::Rgba16ui, 95, "VK_FORMAT_R16G16B16A16_UINT"),
+    (ImageFormat::Rgba8ui, 41, "VK_FORMAT_R8G8B8A8_UINT"),
+    (ImageFormat::R32ui, 98, "VK_FORMAT_R32_UINT"),
+    (
+        ImageFormat::Rgb10a2ui,
+        68,
+        "VK_FORMAT_A2B10G10R10_UINT_PACK32",
+    ),
+    (ImageFormat::Rg32ui, 101, "VK_FORMAT_R32G32_UINT"),
+    (ImageFormat::Rg16ui, 81, "VK_FORMAT_R16G16_UINT"),
+    (ImageFormat::Rg8ui, 20, "VK_FORMAT_R8G8_UINT"),
+    (ImageFormat::R16ui, 74, "VK_FORMAT_R16_UINT"),
+    (ImageFormat::R8ui, 13, "VK_FORMAT_R8_UINT"),
+    (ImageFormat::R64ui, 110, "VK_FORMAT_R64_UINT"),
+    (ImageFormat::R64i, 111, "VK_FORMAT_R64_SINT"),
+];
+
+fn vk_format_entry(fmt: ImageFormat) -> Option<&'static (ImageFormat, u32, &'static str)> {
+    VK_FORMATS.iter().find(|x| x.0 == fmt)
+}
+/// `VkFormat` value followed by its name if it's listed in `VK_FORMATS`.
+fn fmt_vk_format(f: &mut fmt::Formatter, vk_fmt: u32) -> fmt::Result {
+    match VK_FORMATS.iter().find(|x| x.1 == vk_fmt) {
+        Some(x) => write!(f, "{} ({})", x.2, vk_fmt),
+        None => write!(f, "VkFormat {}", vk_fmt),
+    }
+}
+
+/// Value of the `VkFormat` equivalent to `fmt`, or `None` if the format is
+/// `ImageFormat::Unknown`.
+pub fn vk_format(fmt: ImageFormat) -> Option<u32> {
+    vk_format_entry(fmt).map(|x| x.1)
+}
+/// Name of the `VkFormat` equivalent to `fmt`, like
+/// `VK_FORMAT_R32G32B32A32_SFLOAT`, or `None` if the format is
+/// `ImageFormat::Unknown`.
+pub fn vk_format_name(fmt: ImageFormat) -> Option<&'static str> {
+    vk_format_entry(fmt).map(|x| x.2)
+}
+
+/// Image format declared on an image or storage image type, or arrays of
+/// them. Returns `None` for other types, which have no format, and for
+/// images declared with `ImageFormat::Unknown`.
+pub fn declared_image_fmt(ty: &Type) -> Option<ImageFormat> {
+    let fmt = match ty {
+        Type::Image(x) => x.fmt,
+        Type::StorageImage(x) => x.fmt,
+        Type::Array(x) => return declared_image_fmt(&x.element_ty),
+        _ => return None,
+    };
+    if fmt == ImageFormat::Unknown {
+        None
+    } else {
+        Some(fmt)
+    }
+}
+
+/// Device features allowing storage images without declared formats to be
+/// accessed. A feature should also be set if the `VkFormat` to be checked
+/// supports `VK_FORMAT_FEATURE_2_STORAGE_READ_WITHOUT_FORMAT_BIT` or
+/// `VK_FORMAT_FEATURE_2_STORAGE_WRITE_WITHOUT_FORMAT_BIT` respectively.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StorageImageFeatures {
+    /// `shaderStorageImageReadWithoutFormat`.
+    pub read_without_fmt: bool,
+    /// `shaderStorageImageWriteWithoutFormat`.
+    pub write_without_fmt: bool,
+}
+
+/// A `VkFormat` that can't be used with a storage image or storage texel
+/// buffer binding.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum StorageImageFmtError {
+    /// No storage image or storage texel buffer is bound at `desc_bind`.
+    NotStorageImage { desc_bind: DescriptorBinding },
+    /// `vk_fmt` is not the equivalent of the format `fmt` declared in SPIR-V.
+    Mismatch {
+        desc_bind: DescriptorBinding,
+        fmt: ImageFormat,
+        vk_fmt: u32,
+    },
+    /// The image is read without a declared format but
+    /// `shaderStorageImageReadWithoutFormat` is not enabled.
+    ReadWithoutFormat { desc_bind: DescriptorBinding },
+    /// The image is written without a declared format but
+    /// `shaderStorageImageWriteWithoutFormat` is not enabled.
+    WriteWithoutFormat { desc_bind: DescriptorBinding },
+}
+impl fmt::Display for StorageImageFmtError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StorageImageFmtError::NotStorageImage { desc_bind } => write!(
+                f,
+                "no storage image or storage texel buffer is bound at {}",
+                desc_bind
+            ),
+            StorageImageFmtError::Mismatch {
+                desc_bind,
+                fmt,
+                vk_fmt,
+            } => {
+                write!(
+                    f,
+                    "storage image at {} is declared {:?} but ",
+                    desc_bind, fmt
+                )?;
+                fmt_vk_format(f, *vk_fmt)?;
+                write!(f, " is given; use ")?;
+                fmt_vk_format(f, vk_format(*fmt).unwrap())?;
+                write!(f, " or change the declared format")
+            }
+            StorageImageFmtError::ReadWithoutFormat { desc_bind } => write!(
+                f,
+                "storage image at {} is read without a declared format; enable \
+                shaderStorageImageReadWithoutFormat or declare the format",
+                desc_bind
+            ),
+            StorageImageFmtError::WriteWithoutFormat { desc_bind } => write!(
+                f,
+                "storage image at {} is written without a declared format; \
+                enable shaderStorageImageWriteWithoutFormat or declare the format",
+                desc_bind
+            ),
+        }
+    }
+}
+
+/// Check the `VkFormat`s of image views to be bound to the storage images
+/// and storage texel buffers of `entry_point`, given as pairs of binding
+/// points and `VkFormat` values. Images with declared formats only accept
+/// the equivalent `VkFormat`s. Images without declared formats accept any
+/// `VkFormat` but need `features` for the way they are accessed. Returns all
+/// the problems found.
+pub fn check_storage_image_fmts(
+    entry_point: &EntryPoint,
+    vk_fmts: &[(DescriptorBinding, u32)],
+    features: StorageImageFeatures,
+) -> Vec<StorageImageFmtError> {
+    let mut out = Vec::new();
+    for (desc_bind, vk_fmt) in vk_fmts.iter().copied() {
+        let img = entry_point.vars.iter().find_map(|var| match var {
+            Variable::Descriptor {
+                desc_bind: x,
+                desc_ty: DescriptorType::StorageImage(access),
+                ty: Type::StorageImage(ty),
+                ..
+            }
+            | Variable::Descriptor {
+                desc_bind: x,
+                desc_ty: DescriptorType::StorageTexelBuffer(access),
+                ty: Type::StorageImage(ty),
+                ..
+            } if *x == desc_bind => Some((*access, ty.fmt)),
+            _ => None,
+        });
+        let (access, fmt) = match img {
+            Some(x) => x,
+            None => {
+                out.push(StorageImageFmtError::NotStorageImage { desc_bind });
+                continue;
+            }
+        };
+        if fmt != ImageFormat::Unknown {
+            if vk_format(fmt) != Some(vk_fmt) {
+                out.push(StorageImageFmtError::Mismatch {
+                    desc_bind,
+                    fmt,
+                    vk_fmt,
+                });
+            }
+            continue;
+        }
+        let is_read = access != AccessType::WriteOnly;
+        let is_written = access != AccessType::ReadOnly;
+        if is_read && !features.read_without_fmt {
+            out.push(StorageImageFmtError::ReadWithoutFormat { desc_bind });
+        }
+        if is_written && !features.write_without_fmt {
+            out.push(StorageImageFmtError::WriteWithoutFormat { desc_bind });
+        }
+    }
+    out
+}
@@ -0,0 +1,200 @@
+//! OpenGL flavored view of interface variables, for SPIR-V modules consumed
+//! with `ARB_gl_spirv`.
+use crate::{
+    annotation::DecorationRegistry,
+    reflect::make_desc_var,
+    spirv::Decoration,
+    ty::{DescriptorType, StorageClass, Type},
+    var::{InterfaceLocation, Variable, VariableAlloc},
+};
+
+type VariableId = u32;
+
+/// Transform feedback capture of an output variable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct XfbCapture {
+    /// Transform feedback buffer the output is captured to.
+    pub buffer: u32,
+    /// Stride of the transform feedback buffer, if declared on the variable.
+    pub stride: Option<u32>,
+    /// Byte offset of the output in each captured vertex.
+    pub offset: u32,
+}
+
+/// Interface variable as seen by an OpenGL application. In OpenGL, resources
+/// are identified by uniform locations and per-kind binding points rather
+/// than descriptor sets and bindings.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GlVariable {
+    /// Input interface variable.
+    Input {
+        name: Option<String>,
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::location"))]
+        location: InterfaceLocation,
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::ty"))]
+        ty: Type,
+    },
+    /// Output interface variable.
+    Output {
+        name: Option<String>,
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::location"))]
+        location: InterfaceLocation,
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::ty"))]
+        ty: Type,
+        /// Transform feedback capture if the output is a varying recorded by
+        /// transform feedback.
+        xfb: Option<XfbCapture>,
+    },
+    /// Uniform in the default uniform block, set with `glUniform*`.
+    Uniform {
+        name: Option<String>,
+        /// Uniform location, or `None` if it has to be queried at runtime.
+        location: Option<u32>,
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::ty"))]
+        ty: Type,
+    },
+    /// Sampler or image uniform bound to a texture or image unit.
+    Opaque {
+        name: Option<String>,
+        /// Uniform location, or `None` if it has to be queried at runtime.
+        location: Option<u32>,
+        /// Texture unit for samplers and image unit for images.
+        binding: u32,
+        /// Number of consecutive units used by the uniform.
+        nbind: u32,
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::ty"))]
+        ty: Type,
+    },
+    /// Uniform block bound to a `GL_UNIFORM_BUFFER` binding point.
+    UniformBlock {
+        name: Option<String>,
+        binding: u32,
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::ty"))]
+        ty: Type,
+    },
+    /// Shader storage block bound to a `GL_SHADER_STORAGE_BUFFER` binding
+    /// point.
+    ShaderStorageBlock {
+        name: Option<String>,
+        binding: u32,
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::ty"))]
+        ty: Type,
+    },
+    /// Atomic counter in a `GL_ATOMIC_COUNTER_BUFFER`.
+    AtomicCounter {
+        name: Option<String>,
+        binding: u32,
+        /// Byte offset of the counter in the buffer.
+        offset: u32,
+        #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::ty"))]
+        ty: Type,
+    },
+}
+impl GlVariable {
+    pub fn name(&self) -> Option<&str> {
+        match self {
+            GlVariable::Input { name, .. }
+            | GlVariable::Output { name, .. }
+            | GlVariable::Uniform { name, .. }
+            | GlVariable::Opaque { name, .. }
+            | GlVariable::UniformBlock { name, .. }
+            | GlVariable::ShaderStorageBlock { name, .. }
+            | GlVariable::AtomicCounter { name, .. } => name.as_deref(),
+        }
+    }
+}
+
+pub(crate) fn make_gl_var(
+    deco_reg: &DecorationRegistry,
+    name: Option<String>,
+    var_id: VariableId,
+    var_alloc: &VariableAlloc,
+) -> Option<GlVariable> {
+    let ptr_ty = &var_alloc.ptr_ty;
+    let ty = &*ptr_ty.pointee_ty;
+    let location = deco_reg.get_u32(var_id, Decoration::Location).ok();
+    let binding = deco_reg
+        .get_u32(var_id, Decoration::Binding)
+        .unwrap_or_default();
+    match ptr_ty.store_cls {
+        StorageClass::Input => {
+            let location = deco_reg.get_var_location(var_id).ok()?;
+            let var = GlVariable::Input {
+                name,
+                location,
+                ty: ty.clone(),
+            };
+            Some(var)
+        }
+        StorageClass::Output => {
+            let location = deco_reg.get_var_location(var_id).ok()?;
+            let xfb = deco_reg
+                .get_u32(var_id, Decoration::XfbBuffer)
+                .ok()
+                .map(|buffer| XfbCapture {
+                    buffer,
+                    stride: deco_reg.get_u32(var_id, Decoration::XfbStride).ok(),
+                    offset: deco_reg
+                        .get_u32(var_id, Decoration::Offset)
+                        .unwrap_or_default(),
+                });
+            let var = GlVariable::Output {
+                name,
+                location,
+                ty: ty.clone(),
+                xfb,
+            };
+            Some(var)
+        }
+        StorageClass::AtomicCounter => {
+            let offset = deco_reg
+                .get_u32(var_id, Decoration::Offset)
+                .unwrap_or_default();
+            let var = GlVariable::AtomicCounter {
+                name,
+                binding,
+                offset,
+                ty: ty.clone(),
+            };
+            Some(var)
+        }
+        StorageClass::Uniform | StorageClass::StorageBuffer | StorageClass::UniformConstant => {
+            let desc_var = match make_desc_var(deco_reg, name.clone(), var_id, ptr_ty, ty) {
+                Some(x) => x,
+                // Default-block uniforms are not descriptors in Vulkan.
+                None if ptr_ty.store_cls == StorageClass::UniformConstant => {
+                    let var = GlVariable::Uniform {
+                        name,
+                        location,
+                        ty: ty.clone(),
+                    };
+                    return Some(var);
+                }
+                None => return None,
+            };
+            let (desc_ty, ty, nbind) = match desc_var {
+                Variable::Descriptor {
+                    desc_ty, ty, nbind, ..
+                } => (desc_ty, ty, nbind),
+                _ => return None,
+            };
+            let var = match desc_ty {
+                DescriptorType::UniformBuffer() => GlVariable::UniformBlock { name, binding, ty },
+                DescriptorType::StorageBuffer(_) => {
+                    GlVariable::ShaderStorageBlock { name, binding, ty }
+                }
+                _ => GlVariable::Opaque {
+                    name,
+                    location,
+                    binding,
+                    nbind,
+                    ty,
+                },
+            };
+            Some(var)
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,181 @@
+//! SPIR-V instruction grammar and a generic operand decoder.
+//!
+//! The tables are generated at build time from the machine-readable grammar
+//! published by Khronos, so new opcodes and operand kinds can be decoded
+//! without hand-written decoding structs.
+use crate::{
+    error::{anyhow, Result},
+    parse::{Instr, Operands},
+};
+
+include!(concat!(env!("OUT_DIR"), "/grammar.rs"));
+
+/// Operand kind categories.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OperandCategory {
+    /// A bit mask; each set bit may be followed by extra operands.
+    BitEnum,
+    /// An enumeration; an enumerant may be followed by extra operands.
+    ValueEnum,
+    /// An `<id>` reference.
+    Id,
+    /// A literal number or string.
+    Literal,
+    /// A sequence of other kinds.
+    Composite,
+}
+
+/// How many times an operand can appear.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Quantifier {
+    /// Exactly once.
+    One,
+    /// Zero or one time.
+    Optional,
+    /// Zero or more times.
+    Variadic,
+}
+
+/// Logical operand of an instruction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct OperandSpec {
+    pub kind: OperandKind,
+    pub quantifier: Quantifier,
+}
+
+/// Instruction layout described in the SPIR-V grammar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct InstructionSpec {
+    /// Instruction name like `OpTypeInt`.
+    pub opname: &'static str,
+    pub opcode: u32,
+    /// Instruction class like `Type-Declaration`.
+    pub class: &'static str,
+    pub operands: &'static [OperandSpec],
+}
+
+/// Get the grammar of instruction `opcode`. Instructions aliased under
+/// another name share the same specification.
+pub fn instr_spec(opcode: u32) -> Option<&'static InstructionSpec> {
+    let i = INSTR_SPECS.partition_point(|x| x.opcode < opcode);
+    INSTR_SPECS.get(i).filter(|x| x.opcode == opcode)
+}
+
+/// A decoded instruction operand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Operand<'a> {
+    Id(u32),
+    Literal(u32),
+    /// Literal whose width depends on the result type, like the value of
+    /// `OpConstant`.
+    LiteralWords(&'a [u32]),
+    String(&'a str),
+    /// Enumerant or bit mask of enum kind `kind`.
+    Enum {
+        kind: OperandKind,
+        value: u32,
+    },
+}
+
+/// Decode all operands of `instr` following the SPIR-V grammar. Composite
+/// operands are flattened and enumerant parameters follow their enumerants.
+pub fn decode_operands(instr: &Instr) -> Result<Vec<Operand<'_>>> {
+    let mut out = Vec::with_capacity(instr.operands().len());
+    visit_operands(instr, |x| out.push(x))?;
+    Ok(out)
+}
+/// Like `decode_operands` but feed each operand to `f` in order without
+/// collecting them.
+pub fn visit_operands<'a, F: FnMut(Operand<'a>)>(instr: &'a Instr, mut f: F) -> Result<()> {
+    let spec =
+        instr_spec(instr.opcode()).ok_or_else(|| anyhow!("unknown opcode {}", instr.opcode()))?;
+    let mut operands = instr.operands();
+    decode_specs(&mut operands, spec.operands, &mut f)
+        .and_then(|_| {
+            if operands.len() == 0 {
+                Ok(())
+            } else {
+                Err(anyhow!("{} trailing words", operands.len()))
+            }
+        })
+        .map_err(|e| e.context(format!("malformed {}", spec.opname)))
+}
+fn decode_specs<'a>(
+    operands: &mut Operands<'a>,
+    specs: &[OperandSpec],
+    out: &mut dyn FnMut(Operand<'a>),
+) -> Result<()> {
+    for spec in specs {
+        match spec.quantifier {
+            Quantifier::One => decode_one(operands, spec.kind, out)?,
+            Quantifier::Optional => {
+                if operands.len() > 0 {
+                    decode_one(operands, spec.kind, out)?;
+                }
+            }
+            Quantifier::Variadic => {
+                while operands.len() > 0 {
+                    decode_one(operands, spec.kind, out)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+fn decode_one<'a>(
+    operands: &mut Operands<'a>,
+    kind: OperandKind,
+    out: &mut dyn FnMut(Operand<'a>),
+) -> Result<()> {
+    match kind.category() {
+        OperandCategory::Id => out(Operand::Id(operands.read_u32()?)),
+        OperandCategory::Literal => match kind {
+            OperandKind::LiteralString => out(Operand::String(operands.read_str()?)),
+            // Always the last operand of an instruction.
+            OperandKind::LiteralContextDependentNumber => {
+                out(Operand::LiteralWords(operands.read_list()?))
+            }
+            _ => out(Operand::Literal(operands.read_u32()?)),
+        },
+        OperandCategory::Composite => {
+            for base in kind.bases() {
+                decode_one(operands, *base, out)?;
+            }
+        }
+        OperandCategory::ValueEnum | OperandCategory::BitEnum => {
+            let value = operands.read_u32()?;
+            out(Operand::Enum { kind, value });
+            decode_enum_params(operands, kind, value, out)?;
+        }
+    }
+    Ok(())
+}
+fn decode_enum_params<'a>(
+    operands: &mut Operands<'a>,
+    kind: OperandKind,
+    value: u32,
+    out: &mut dyn FnMut(Operand<'a>),
+) -> Result<()> {
+    if kind.category() == OperandCategory::BitEnum {
+        for i in 0..32 {
+            let bit = 1 << i;
+            if value & bit != 0 {
+                decode_specs(operands, kind.enumerant_params(bit), out)?;
+            }
+        }
+        Ok(())
+    } else {
+        decode_specs(operands, kind.enumerant_params(value), out)
+    }
+}
+/// Read the extra operands following enumerant or bit mask `value` of enum
+/// kind `kind` as raw words.
+pub(crate) fn read_enum_params<'a>(
+    operands: &mut Operands<'a>,
+    kind: OperandKind,
+    value: u32,
+) -> Result<&'a [u32]> {
+    let words = operands.clone().read_list()?;
+    decode_enum_params(operands, kind, value, &mut |_| {})?;
+    Ok(&words[..words.len() - operands.len()])
+}
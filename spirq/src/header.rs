@@ -0,0 +1,441 @@
+//! C/C++ struct declarations of shader blocks, to share buffer layouts
+//! between shaders and host code.
+//!
+//! Uniform buffer, storage buffer and push constant blocks are declared as C
+//! structs whose members are placed at the offsets decorated in SPIR-V. Gaps
+//! between members are filled with explicit `uint8_t` padding arrays rather
+//! than `alignas`, because decorated offsets can follow any of the std140,
+//! std430 and scalar layouts. Array elements and matrix columns (or rows)
+//! with strides larger than their sizes are wrapped in padded structs. The
+//! header is valid C11 and C++11, and `static_assert`s check that the
+//! compiler agrees with the member offsets. Runtime arrays are flexible array
+//! members in C, and one-element arrays in C++ which has no flexible array
+//! members. Names that are not valid identifiers, or are C/C++ keywords, are
+//! renamed.
+use std::fmt::Write;
+
+use crate::{
+    entry_point::EntryPoint,
+    error::{anyhow, Result},
+    stub::sanitize,
+    ty::{DescriptorType, MatrixAxisOrder, ScalarType, StructType, Type},
+    var::Variable,
+};
+
+/// Generate a C/C++ header declaring the uniform buffer, storage buffer and
+/// push constant block types used by `entry_point`. Blocks without debug
+/// names are given placeholder names.
+pub fn generate_c_header(entry_point: &EntryPoint) -> Result<String> {
+    let mut writer = HeaderWriter::default();
+    for var in entry_point.vars.iter() {
+        let (name, ty) = match var {
+            Variable::Descriptor {
+                desc_bind,
+                desc_ty: DescriptorType::UniformBuffer() | DescriptorType::StorageBuffer(_),
+                ty,
+                ..
+            } => (
+                format!("Block_{}_{}", desc_bind.set(), desc_bind.bind()),
+                ty,
+            ),
+            Variable::PushConstant { ty, .. } => ("PushConstants".to_owned(), ty),
+            _ => continue,
+        };
+        if let Type::Struct(struct_ty) = ty {
+            writer.declare_struct(struct_ty, &name)?;
+        }
+    }
+
+    let mut out = format!(
+        "// Block layouts of entry point `{}` ({:?}).\n",
+        entry_point.name, entry_point.exec_model
+    );
+    out += "#pragma once\n";
+    out += "#include <assert.h>\n";
+    out += "#include <stddef.h>\n";
+    out += "#include <stdint.h>\n";
+    if writer.has_rt_arr {
+        out += "#ifndef SPIRQ_RUNTIME_ARRAY_LEN\n";
+        out += "#ifdef __cplusplus\n";
+        out += "#define SPIRQ_RUNTIME_ARRAY_LEN 1\n";
+        out += "#else\n";
+        out += "#define SPIRQ_RUNTIME_ARRAY_LEN\n";
+        out += "#endif\n";
+        out += "#endif\n";
+    }
+    out += &writer.out;
+    Ok(out)
+}
+
+/// Keywords of C11 and C++11 which cannot be used as identifiers.
+const C_KEYWORDS: &[&str] = &[
+    "_Alignas",
+    "_Alignof",
+    "_Atomic",
+    "_Bool",
+    "_Complex",
+    "_Generic",
+    "_Imaginary",
+    "_Noreturn",
+    "_Static_assert",
+    "_Thread_local",
+    "alignas",
+    "alignof",
+    "and",
+    "and_eq",
+    "asm",
+    "auto",
+    "bitand",
+    "bitor",
+    "bool",
+    "break",
+    "case",
+    "catch",
+    "char",
+    "char16_t",
+    "char32_t",
+    "class",
+    "compl",
+    "const",
+    "const_cast",
+    "constexpr",
+    "continue",
+    "decltype",
+    "default",
+    "delete",
+    "do",
+    "double",
+    "dynamic_cast",
+    "else",
+    "enum",
+    "explicit",
+    "export",
+    "extern",
+    "false",
+    "float",
+    "for",
+    "friend",
+    "goto",
+    "if",
+    "inline",
+    "int",
+    "long",
+    "mutable",
+    "namespace",
+    "new",
+    "noexcept",
+    "not",
+    "not_eq",
+    "nullptr",
+    "operator",
+    "or",
+    "or_eq",
+    "private",
+    "protected",
+    "public",
+    "register",
+    "reinterpret_cast",
+    "restrict",
+    "return",
+    "short",
+    "signed",
+    "sizeof",
+    "static",
+    "static_assert",
+    "static_cast",
+    "struct",
+    "switch",
+    "template",
+    "this",
+    "thread_local",
+    "throw",
+    "true",
+    "try",
+    "typedef",
+    "typeid",
+    "typename",
+    "union",
+    "unsigned",
+    "using",
+    "virtual",
+    "void",
+    "volatile",
+    "wchar_t",
+    "while",
+    "xor",
+    "xor_eq",
+];
+
+/// Sanitize `name` into an identifier that is valid in both C and C++.
+fn c_ident(name: &str) -> String {
+    let mut out = sanitize(name);
+    if C_KEYWORDS.contains(&out.as_str()) {
+        out.push('_');
+    }
+    out
+}
+
+/// Append a numeric suffix to `base` until it's not in `names`, and take
+/// the result.
+fn take_unique_name(names: &mut Vec<String>, base: String) -> String {
+    let mut name = base.clone();
+    let mut i = 1;
+    while names.contains(&name) {
+        name = format!("{}_{}", base, i);
+        i += 1;
+    }
+    names.push(name.clone());
+    name
+}
+
+/// Size and alignment of a type in C.
+#[derive(Clone, Copy)]
+struct CLayout {
+    nbyte: usize,
+    align: usize,
+}
+
+#[derive(Default)]
+struct HeaderWriter {
+    /// Struct types already declared, their names and C layouts.
+    structs: Vec<(StructType, String, CLayout)>,
+    /// Struct names already taken, including element wrappers.
+    names: Vec<String>,
+    /// Whether any runtime array is declared.
+    has_rt_arr: bool,
+    out: String,
+}
+impl HeaderWriter {
+    fn unique_name(&mut self, base: String) -> String {
+        take_unique_name(&mut self.names, base)
+    }
+
+    fn scalar_decl(scalar_ty: &ScalarType) -> Result<(&'static str, CLayout)> {
+        let name = match scalar_ty {
+            // Booleans are not allowed in blocks, but 32-bit integers are the
+            // closest thing.
+            ScalarType::Boolean => "uint32_t",
+            ScalarType::Integer { bits, is_signed } => {
+                let names = match bits {
+                    8 => ["uint8_t", "int8_t"],
+                    16 => ["uint16_t", "int16_t"],
+                    32 => ["uint32_t", "int32_t"],
+                    64 => ["uint64_t", "int64_t"],
+                    _ => return Err(anyhow!("{} cannot be declared in c", scalar_ty)),
+                };
+                names[*is_signed as usize]
+            }
+            // C has no portable half-precision type, so the bits are kept.
+            ScalarType::Float { bits: 16 } => "uint16_t",
+            ScalarType::Float { bits: 32 } => "float",
+            ScalarType::Float { bits: 64 } => "double",
+            _ => return Err(anyhow!("{} cannot be declared in c", scalar_ty)),
+        };
+        let nbyte = match scalar_ty {
+            ScalarType::Boolean => 4,
+            ScalarType::Integer { bits, .. } | ScalarType::Float { bits } => *bits as usize / 8,
+            _ => unreachable!(),
+        };
+        Ok((
+            name,
+            CLayout {
+                nbyte,
+                align: nbyte,
+            },
+        ))
+    }
+
+    /// Type name, array suffix and C layout of `ty`. Structs and padded
+    /// wrappers are declared on the first use, and are named after `hint`.
+    fn ty_decl(&mut self, ty: &Type, hint: &str) -> Result<(String, String, CLayout)> {
+        let out = match ty {
+            Type::Scalar(x) => {
+                let (name, layout) = Self::scalar_decl(x)?;
+                (name.to_owned(), String::new(), layout)
+            }
+            Type::Vector(x) => {
+                let (name, layout) = Self::scalar_decl(&x.scalar_ty)?;
+                let nscalar = x.nscalar as usize;
+                let layout = CLayout {
+                    nbyte: layout.nbyte * nscalar,
+                    ..layout
+                };
+                (name.to_owned(), format!("[{}]", nscalar), layout)
+            }
+            Type::Matrix(x) => {
+                let (scalar_name, scalar_layout) = Self::scalar_decl(&x.vector_ty.scalar_ty)?;
+                let (nmajor, nminor) = match x.axis_order {
+                    Some(MatrixAxisOrder::RowMajor) => (x.vector_ty.nscalar, x.nvector),
+                    _ => (x.nvector, x.vector_ty.nscalar),
+                };
+                let vec_layout = CLayout {
+                    nbyte: scalar_layout.nbyte * nminor as usize,
+                    ..scalar_layout
+                };
+                let stride = x.stride.unwrap_or(vec_layout.nbyte);
+                let (name, suffix, layout) = self.strided(
+                    scalar_name.to_owned(),
+                    format!("[{}]", nminor),
+                    vec_layout,
+                    stride,
+                    &format!("{}_Vec", hint),
+                )?;
+                let layout = CLayout {
+                    nbyte: layout.nbyte * nmajor as usize,
+                    ..layout
+                };
+                (name, format!("[{}]{}", nmajor, suffix), layout)
+            }
+            Type::Array(x) => {
+                let (name, suffix, elem_layout) =
+                    self.ty_decl(&x.element_ty, &format!("{}_Elem", hint))?;
+                let stride = x.stride.unwrap_or(elem_layout.nbyte);
+                let (name, suffix, layout) =
+                    self.strided(name, suffix, elem_layout, stride, &format!("{}_Elem", hint))?;
+                match x.nelement {
+                    Some(n) => {
+                        let layout = CLayout {
+                            nbyte: layout.nbyte * n as usize,
+                            ..layout
+                        };
+                        (name, format!("[{}]{}", n, suffix), layout)
+                    }
+                    // Runtime arrays are flexible array members.
+                    None => {
+                        self.has_rt_arr = true;
+                        (
+                            name,
+                            format!("[SPIRQ_RUNTIME_ARRAY_LEN]{}", suffix),
+                            CLayout { nbyte: 0, ..layout },
+                        )
+                    }
+                }
+            }
+            Type::Struct(x) => {
+                let (name, layout) = self.declare_struct(x, hint)?;
+                (format!("struct {}", name), String::new(), layout)
+            }
+            Type::DeviceAddress(_) | Type::DevicePointer(_) => {
+                let layout = CLayout { nbyte: 8, align: 8 };
+                ("uint64_t".to_owned(), String::new(), layout)
+            }
+            _ => return Err(anyhow!("type {} cannot be declared in c", ty)),
+        };
+        Ok(out)
+    }
+    /// Wrap an array element in a padded struct if `stride` is larger than
+    /// its size in C.
+    fn strided(
+        &mut self,
+        name: String,
+        suffix: String,
+        layout: CLayout,
+        stride: usize,
+        hint: &str,
+    ) -> Result<(String, String, CLayout)> {
+        if stride == layout.nbyte {
+            return Ok((name, suffix, layout));
+        }
+        if stride < layout.nbyte || stride & (layout.align - 1) != 0 {
+            return Err(anyhow!(
+                "stride {} of `{}` cannot be expressed in c",
+                stride,
+                hint
+            ));
+        }
+        let wrapper_name = self.unique_name(hint.to_owned());
+        writeln!(
+            self.out,
+            "\nstruct {} {{\n    {} value{};\n    uint8_t _pad0[{}];\n}};",
+            wrapper_name,
+            name,
+            suffix,
+            stride - layout.nbyte
+        )
+        .unwrap();
+        let layout = CLayout {
+            nbyte: stride,
+            align: layout.align,
+        };
+        Ok((format!("struct {}", wrapper_name), String::new(), layout))
+    }
+
+    fn declare_struct(&mut self, struct_ty: &StructType, hint: &str) -> Result<(String, CLayout)> {
+        if let Some((_, name, layout)) = self.structs.iter().find(|(x, _, _)| x == struct_ty) {
+            return Ok((name.clone(), *layout));
+        }
+        let base = struct_ty
+            .name
+            .as_deref()
+            .map(c_ident)
+            .unwrap_or_else(|| hint.to_owned());
+        let name = self.unique_name(base);
+
+        // Member names unique in the struct, in declaration order so that
+        // renaming is stable.
+        let mut member_names = Vec::new();
+        for (i, member) in struct_ty.members.iter().enumerate() {
+            let base = member
+                .name
+                .as_deref()
+                .map(c_ident)
+                .unwrap_or_else(|| format!("m{}", i));
+            take_unique_name(&mut member_names, base);
+        }
+        let mut members = struct_ty.members.iter().enumerate().collect::<Vec<_>>();
+        members.sort_by_key(|(_, x)| x.offset);
+        let mut body = String::new();
+        let mut asserts = String::new();
+        let mut cur = 0;
+        let mut align = 1;
+        let mut npad = 0;
+        for (i, member) in members {
+            let member_name = member_names[i].clone();
+            let offset = member
+                .offset
+                .ok_or_else(|| anyhow!("member `{}` of `{}` has no offset", member_name, name))?;
+            let (ty_name, suffix, layout) =
+                self.ty_decl(&member.ty, &format!("{}_{}", name, member_name))?;
+            if offset < cur || offset & (layout.align - 1) != 0 {
+                return Err(anyhow!(
+                    "offset {} of member `{}` of `{}` cannot be expressed in c",
+                    offset,
+                    member_name,
+                    name
+                ));
+            }
+            if offset > cur {
+                let mut pad_name = format!("_pad{}", npad);
+                while member_names.contains(&pad_name) {
+                    npad += 1;
+                    pad_name = format!("_pad{}", npad);
+                }
+                writeln!(body, "    uint8_t {}[{}];", pad_name, offset - cur).unwrap();
+                npad += 1;
+            }
+            writeln!(body, "    {} {}{};", ty_name, member_name, suffix).unwrap();
+            writeln!(
+                asserts,
+                "static_assert(offsetof(struct {}, {}) == {}, \"offset of {}::{}\");",
+                name, member_name, offset, name, member_name
+            )
+            .unwrap();
+            cur = offset + layout.nbyte;
+            align = align.max(layout.align);
+        }
+        writeln!(
+            self.out,
+            "\nstruct {} {{\n{}}};\n{}",
+            name,
+            body,
+            asserts.trim_end()
+        )
+        .unwrap();
+        let layout = CLayout {
+            nbyte: (cur + align - 1) & !(align - 1),
+            align,
+        };
+        self.structs.push((struct_ty.clone(), name.clone(), layout));
+        Ok((name, layout))
+    }
+}
@@ -1,5 +1,13 @@
 //! Inspect SPIR-V function parsing.
-use crate::{error::Result, parse::Instr, reflect::ReflectIntermediate};
+use std::convert::TryFrom;
+
+use crate::{
+    error::Result,
+    instr::{decode_op, OpExtInst},
+    parse::Instr,
+    reflect::ReflectIntermediate,
+    spirv::Op,
+};
 
 pub trait Inspector {
     /// For each instruction iterated in a function parse, the inspector receive
@@ -37,3 +45,68 @@ impl<I1: Inspector, I2: Inspector> Inspector for Chain<'_, I1, I2> {
         self.second.inspect(itm, instr)
     }
 }
+
+/// An `OpExtInst` instruction with its extended instruction set resolved.
+pub struct ExtInst<'a> {
+    pub result_ty_id: u32,
+    pub result_id: u32,
+    /// Name of the extended instruction set, e.g., `GLSL.std.450`.
+    pub set_name: &'a str,
+    /// Instruction number in the extended instruction set.
+    pub opcode: u32,
+    /// Operands of the extended instruction.
+    pub operands: &'a [u32],
+}
+
+type ExtInstHandler<'h> =
+    Box<dyn FnMut(&mut ReflectIntermediate<'_>, &ExtInst<'_>) -> Result<()> + 'h>;
+
+/// Inspector that dispatches `OpExtInst`s in function bodies to the handlers
+/// registered for their extended instruction sets.
+#[derive(Default)]
+pub struct ExtInstRegistry<'h> {
+    handlers: Vec<(String, ExtInstHandler<'h>)>,
+}
+impl<'h> ExtInstRegistry<'h> {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Register a handler for the extended instruction set `set_name`. A name
+    /// ending with `*` matches all sets with the preceding prefix, e.g.,
+    /// `NonSemantic.*`. Handlers are called in the order of registration.
+    pub fn register<F>(&mut self, set_name: &str, handler: F) -> &mut Self
+    where
+        F: FnMut(&mut ReflectIntermediate<'_>, &ExtInst<'_>) -> Result<()> + 'h,
+    {
+        self.handlers.push((set_name.to_owned(), Box::new(handler)));
+        self
+    }
+}
+impl Inspector for ExtInstRegistry<'_> {
+    fn inspect<'a>(&mut self, itm: &mut ReflectIntermediate<'a>, instr: &Instr) -> Result<()> {
+        if decode_op(instr) != Some(Op::ExtInst) {
+            return Ok(());
+        }
+        let op = OpExtInst::try_from(instr)?;
+        let set_name = itm.interp.get_ext_instr_set_name(op.set_id)?.to_owned();
+        let ext_instr = ExtInst {
+            result_ty_id: op.result_type_id,
+            result_id: op.result_id,
+            set_name: &set_name,
+            opcode: op.instruction,
+            operands: op.operand_ids,
+        };
+        for (pattern, handler) in self.handlers.iter_mut() {
+            let is_match = if let Some(prefix) = pattern.strip_suffix('*') {
+                set_name.starts_with(prefix)
+            } else {
+                *pattern == set_name
+            };
+            if is_match {
+                handler(itm, &ext_instr)?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,72 @@
+//! Parsing of descriptor binding points and interface locations written in
+//! config files and command-line arguments.
+//!
+//! [`DescriptorBinding`] and [`InterfaceLocation`] are defined in `spq-core`
+//! so they can't implement `FromStr` here. [`parse_desc_bind`] and
+//! [`parse_location`] accept their `Display` forms, so the text written out
+//! is read back to the same value:
+//!
+//! - Descriptor bindings: `1.3`, `1,3` and `(set=1, bind=3)`. `binding` is
+//!   accepted in place of `bind`.
+//! - Interface locations: `2`, `2.1`, `2,1` and `(loc=2, comp=1)`.
+//!   `location` and `component` are accepted in place of `loc` and `comp`.
+//!   The component is 0 if omitted.
+use crate::{
+    error::{anyhow, Result},
+    var::{DescriptorBinding, InterfaceLocation},
+};
+
+/// Split `x` into two numbers named by `keys` in the forms documented in the
+/// [module](self) docs. The second number is `default` if it's omitted.
+fn parse_pair(x: &str, keys: [&[&str]; 2], default: Option<u32>) -> Result<(u32, u32)> {
+    let inner = x.trim();
+    let inner = inner
+        .strip_prefix('(')
+        .and_then(|x| x.strip_suffix(')'))
+        .unwrap_or(inner);
+    let mut out = [None, None];
+    if inner.contains('=') {
+        for field in inner.split(',') {
+            let (key, value) = field
+                .split_once('=')
+                .ok_or_else(|| anyhow!("expected `key=value` but got `{}`", field.trim()))?;
+            let key = key.trim();
+            let i = keys
+                .iter()
+                .position(|x| x.contains(&key))
+                .ok_or_else(|| anyhow!("unknown key `{}` in `{}`", key, x))?;
+            if out[i].is_some() {
+                return Err(anyhow!("duplicate key `{}` in `{}`", key, x));
+            }
+            out[i] = Some(value.trim());
+        }
+    } else {
+        let mut values = inner.split(['.', ','].as_ref());
+        out[0] = values.next().map(str::trim);
+        out[1] = values.next().map(str::trim);
+        if values.next().is_some() {
+            return Err(anyhow!("too many numbers in `{}`", x));
+        }
+    }
+    let parse = |value: Option<&str>, i: usize| -> Result<u32> {
+        match (value, default) {
+            (Some(value), _) => value
+                .parse()
+                .map_err(|_| anyhow!("invalid {} `{}` in `{}`", keys[i][0], value, x)),
+            (None, Some(default)) if i == 1 => Ok(default),
+            (None, _) => Err(anyhow!("missing {} in `{}`", keys[i][0], x)),
+        }
+    };
+    Ok((parse(out[0], 0)?, parse(out[1], 1)?))
+}
+
+/// Parse a descriptor binding point, like `1.3` or `(set=1, bind=3)`.
+pub fn parse_desc_bind(x: &str) -> Result<DescriptorBinding> {
+    let (set, bind) = parse_pair(x, [&["set"], &["bind", "binding"]], None)?;
+    Ok(DescriptorBinding::new(set, bind))
+}
+/// Parse an interface location, like `2`, `2.1` or `(loc=2, comp=1)`.
+pub fn parse_location(x: &str) -> Result<InterfaceLocation> {
+    let (loc, comp) = parse_pair(x, [&["loc", "location"], &["comp", "component"]], Some(0))?;
+    Ok(InterfaceLocation::new(loc, comp))
+}
@@ -0,0 +1,164 @@
+//! Compact human-readable labels of descriptor bindings, for GPU debug
+//! markers and capture tool annotations.
+//!
+//! A label is formatted from a template, by default
+//! [`DEFAULT_LABEL_TEMPLATE`], which gives labels like
+//! `s0b3 texture2D albedo_tex (fs)`. The following placeholders are
+//! substituted:
+//!
+//! - `{set}` and `{binding}`: the descriptor set and binding numbers.
+//! - `{ty}`: the GLSL-style resource type, like `texture2D`, `sampler2D`,
+//!   `image2D`, `ubo` and `ssbo`, followed by `[N]` if the binding is an
+//!   array of `N` descriptors, or `[]` if it's a runtime descriptor array.
+//! - `{name}`: the debug name of the first variable bound.
+//! - `{stages}`: comma-separated short names of the stages accessing the
+//!   binding, like `vs,fs`.
+//! - `{count}`: the number of descriptors, or 0 for runtime descriptor
+//!   arrays.
+use std::collections::BTreeMap;
+
+use crate::{
+    entry_point::{EntryPoint, ExecutionModel},
+    error::Result,
+    pipeline::{Pipeline, PipelineConfig, PipelineDescriptor},
+    stub::{fmt_prefix, glsl_dim, glsl_prefix},
+    ty::{DescriptorType, Type},
+    var::DescriptorBinding,
+};
+
+/// Label template used by [`LabelConfig::new`].
+pub const DEFAULT_LABEL_TEMPLATE: &str = "s{set}b{binding} {ty} {name} ({stages})";
+
+/// Formatting of binding labels.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LabelConfig {
+    template: String,
+    unnamed: String,
+}
+impl Default for LabelConfig {
+    fn default() -> Self {
+        LabelConfig {
+            template: DEFAULT_LABEL_TEMPLATE.to_owned(),
+            unnamed: "_".to_owned(),
+        }
+    }
+}
+impl LabelConfig {
+    pub fn new() -> Self {
+        Default::default()
+    }
+    /// Template of labels with placeholders documented in the
+    /// [module](self) docs.
+    pub fn template(&mut self, x: &str) -> &mut Self {
+        self.template = x.to_owned();
+        self
+    }
+    /// Substitute of `{name}` for bindings without debug names. `_` by
+    /// default.
+    pub fn unnamed(&mut self, x: &str) -> &mut Self {
+        self.unnamed = x.to_owned();
+        self
+    }
+
+    /// Label a descriptor binding of a merged pipeline.
+    pub fn label(&self, desc_bind: DescriptorBinding, desc: &PipelineDescriptor) -> String {
+        let mut ty = ty_label(&desc.desc_ty, &desc.ty);
+        match desc.nbind {
+            0 => ty.push_str("[]"),
+            1 => {}
+            n => ty.push_str(&format!("[{}]", n)),
+        }
+        let name = desc.name.as_deref().unwrap_or(&self.unnamed);
+        let stages = desc
+            .stages
+            .iter()
+            .map(|x| stage_label(*x))
+            .collect::<Vec<_>>()
+            .join(",");
+        self.template
+            .replace("{set}", &desc_bind.set().to_string())
+            .replace("{binding}", &desc_bind.bind().to_string())
+            .replace("{count}", &desc.nbind.to_string())
+            .replace("{stages}", &stages)
+            .replace("{ty}", &ty)
+            .replace("{name}", name)
+    }
+    /// Label all descriptor bindings of a merged pipeline.
+    pub fn label_pipeline(&self, pipeline: &Pipeline) -> BTreeMap<DescriptorBinding, String> {
+        pipeline
+            .descs
+            .iter()
+            .map(|(desc_bind, desc)| (*desc_bind, self.label(*desc_bind, desc)))
+            .collect()
+    }
+    /// Label all descriptor bindings of the entry points of all stages of a
+    /// pipeline. The entry points are merged with the default
+    /// [`PipelineConfig`].
+    pub fn label_entry_points<'a, I: IntoIterator<Item = &'a EntryPoint>>(
+        &self,
+        entry_points: I,
+    ) -> Result<BTreeMap<DescriptorBinding, String>> {
+        let pipeline = PipelineConfig::new().merge(entry_points)?;
+        Ok(self.label_pipeline(&pipeline))
+    }
+}
+
+fn ty_label(desc_ty: &DescriptorType, ty: &Type) -> String {
+    let dim = |dim, is_multisampled, is_array| {
+        glsl_dim(dim, is_multisampled, is_array).unwrap_or_default()
+    };
+    match (desc_ty, ty) {
+        (DescriptorType::UniformBuffer(), _) => "ubo".to_owned(),
+        (DescriptorType::StorageBuffer(_), _) => "ssbo".to_owned(),
+        (DescriptorType::Sampler(), _) => "sampler".to_owned(),
+        (_, Type::SampledImage(x)) => format!(
+            "{}texture{}",
+            glsl_prefix(&x.scalar_ty),
+            dim(x.dim, x.is_multisampled, x.is_array)
+        ),
+        (_, Type::CombinedImageSampler(x)) => {
+            let x = &x.sampled_image_ty;
+            let shadow = if x.is_depth == Some(true) {
+                "Shadow"
+            } else {
+                ""
+            };
+            format!(
+                "{}sampler{}{}",
+                glsl_prefix(&x.scalar_ty),
+                dim(x.dim, x.is_multisampled, x.is_array),
+                shadow
+            )
+        }
+        (_, Type::StorageImage(x)) => format!(
+            "{}image{}",
+            fmt_prefix(x.fmt),
+            dim(x.dim, x.is_multisampled, x.is_array)
+        ),
+        (_, Type::SubpassData(x)) => {
+            let ms = if x.is_multisampled { "MS" } else { "" };
+            format!("{}subpassInput{}", glsl_prefix(&x.scalar_ty), ms)
+        }
+        (DescriptorType::AccelStruct(), _) => "accelerationStructure".to_owned(),
+        _ => format!("{:?}", desc_ty),
+    }
+}
+fn stage_label(exec_model: ExecutionModel) -> &'static str {
+    match exec_model {
+        ExecutionModel::Vertex => "vs",
+        ExecutionModel::TessellationControl => "tcs",
+        ExecutionModel::TessellationEvaluation => "tes",
+        ExecutionModel::Geometry => "gs",
+        ExecutionModel::Fragment => "fs",
+        ExecutionModel::GLCompute => "cs",
+        ExecutionModel::Kernel => "kernel",
+        ExecutionModel::TaskNV | ExecutionModel::TaskEXT => "ts",
+        ExecutionModel::MeshNV | ExecutionModel::MeshEXT => "ms",
+        ExecutionModel::RayGenerationNV => "rgen",
+        ExecutionModel::IntersectionNV => "rint",
+        ExecutionModel::AnyHitNV => "rahit",
+        ExecutionModel::ClosestHitNV => "rchit",
+        ExecutionModel::MissNV => "rmiss",
+        ExecutionModel::CallableNV => "rcall",
+    }
+}
@@ -0,0 +1,279 @@
+//! Memory layout queries complementing [`SpirvType`](crate::ty::SpirvType).
+use crate::ty::{
+    MatrixAxisOrder, MatrixType, PointerType, ScalarType, SpirvType, StorageClass, StructType, Type,
+};
+
+/// Size of a physical storage buffer pointer (`buffer_reference`) in bytes.
+pub const POINTER_NBYTE: usize = 8;
+/// Size of a boolean in buffer memory in bytes. Booleans have no physical size
+/// in SPIR-V but translators emitting them in interface blocks expect them to
+/// be stored as 32-bit integers, as in std140 and std430.
+pub const BOOL_NBYTE: usize = 4;
+
+/// Block layout rules of uniform and storage buffers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BlockLayout {
+    /// `std140`, the default layout of uniform buffers. Arrays and structs
+    /// are aligned to 16 bytes.
+    Std140,
+    /// `std430`, the default layout of storage buffers and push constants.
+    Std430,
+    /// `scalar` (`VK_EXT_scalar_block_layout`). Vectors and matrices are
+    /// aligned as their scalars.
+    Scalar,
+}
+
+/// Bytes in a struct not covered by any member.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PaddingHole {
+    /// Offset of the first unused byte from the beginning of the struct.
+    pub offset: usize,
+    /// Size of the hole in bytes.
+    pub nbyte: usize,
+    /// Index of the member following the hole, or `None` if the hole is at
+    /// the tail of the struct.
+    pub member_idx: Option<usize>,
+}
+
+/// Find the padding holes between the members of `struct_ty` and at its
+/// tail, in the order of offsets. Member sizes are derived from decorated
+/// strides and offsets as in [`TypeExt::layout_nbyte`], and the struct is
+/// padded to its alignment under `layout`. Padding inside members, like
+/// array strides larger than elements and holes in nested structs, is not
+/// reported; nested struct types can be analyzed separately.
+///
+/// Returns `None` if the size of any member is unknown.
+pub fn padding_holes(struct_ty: &StructType, layout: BlockLayout) -> Option<Vec<PaddingHole>> {
+    let mut members = struct_ty.members.iter().enumerate().collect::<Vec<_>>();
+    members.sort_by_key(|(_, x)| x.offset);
+    let mut out = Vec::new();
+    let mut cur = 0;
+    for (i, member) in members {
+        let offset = member.offset?;
+        if offset > cur {
+            let hole = PaddingHole {
+                offset: cur,
+                nbyte: offset - cur,
+                member_idx: Some(i),
+            };
+            out.push(hole);
+        }
+        cur = cur.max(offset + member.ty.layout_nbyte()?);
+    }
+    let (_, align) = struct_layout(struct_ty, layout, false)?;
+    let nbyte = round_up(cur, align);
+    if nbyte > cur {
+        let hole = PaddingHole {
+            offset: cur,
+            nbyte: nbyte - cur,
+            member_idx: None,
+        };
+        out.push(hole);
+    }
+    Some(out)
+}
+
+/// Extra queries on [`Type`].
+pub trait TypeExt {
+    /// Storage class of the memory the type points to, or `None` if it's not
+    /// a pointer. Pointers forward-declared by `OpTypeForwardPointer` are
+    /// reflected as [`Type::DeviceAddress`], which always point into
+    /// `PhysicalStorageBuffer` in shaders.
+    fn pointer_store_cls(&self) -> Option<StorageClass>;
+    /// Whether the type is a physical storage buffer pointer, which takes
+    /// [`POINTER_NBYTE`] bytes in memory.
+    fn is_physical_pointer(&self) -> bool {
+        self.pointer_store_cls() == Some(StorageClass::PhysicalStorageBuffer)
+    }
+    /// Like [`SpirvType::min_nbyte`] but booleans, including those in vectors
+    /// and structs, take [`BOOL_NBYTE`] bytes.
+    fn layout_min_nbyte(&self) -> Option<usize>;
+    /// Like [`SpirvType::nbyte`] but booleans, including those in vectors and
+    /// structs, take [`BOOL_NBYTE`] bytes.
+    fn layout_nbyte(&self) -> Option<usize>;
+    /// Minimum size of the type in bytes under the block layout `layout`,
+    /// with at least one element in runtime arrays. Offsets and strides
+    /// decorated in SPIR-V are ignored and re-derived from the layout rules.
+    fn block_min_nbyte(&self, layout: BlockLayout) -> Option<usize>;
+    /// Size of the type in bytes under the block layout `layout`, with no
+    /// element in runtime arrays. Offsets and strides decorated in SPIR-V are
+    /// ignored and re-derived from the layout rules.
+    fn block_nbyte(&self, layout: BlockLayout) -> Option<usize>;
+    /// Required alignment of the type in bytes under the block layout
+    /// `layout`.
+    fn block_align(&self, layout: BlockLayout) -> Option<usize>;
+    /// Whether the type structurally matches `other` as required by Vulkan
+    /// shader interface matching. Struct and member names are ignored but
+    /// offsets, strides and matrix axis orders have to be identical.
+    fn is_compatible_with(&self, other: &Self) -> bool;
+}
+impl TypeExt for Type {
+    fn pointer_store_cls(&self) -> Option<StorageClass> {
+        match self {
+            Type::DevicePointer(x) => x.pointer_store_cls(),
+            Type::DeviceAddress(_) => Some(StorageClass::PhysicalStorageBuffer),
+            _ => None,
+        }
+    }
+    fn layout_min_nbyte(&self) -> Option<usize> {
+        layout_nbyte_impl(self, true)
+    }
+    fn layout_nbyte(&self) -> Option<usize> {
+        layout_nbyte_impl(self, false)
+    }
+    fn block_min_nbyte(&self, layout: BlockLayout) -> Option<usize> {
+        block_layout_impl(self, layout, true).map(|(nbyte, _)| nbyte)
+    }
+    fn block_nbyte(&self, layout: BlockLayout) -> Option<usize> {
+        block_layout_impl(self, layout, false).map(|(nbyte, _)| nbyte)
+    }
+    fn block_align(&self, layout: BlockLayout) -> Option<usize> {
+        block_layout_impl(self, layout, false).map(|(_, align)| align)
+    }
+    fn is_compatible_with(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Type::Array(a), Type::Array(b)) => {
+                a.nelement == b.nelement
+                    && a.stride == b.stride
+                    && a.element_ty.is_compatible_with(&b.element_ty)
+            }
+            (Type::Struct(a), Type::Struct(b)) => {
+                a.members.len() == b.members.len()
+                    && a.members
+                        .iter()
+                        .zip(b.members.iter())
+                        .all(|(a, b)| a.offset == b.offset && a.ty.is_compatible_with(&b.ty))
+            }
+            (Type::DevicePointer(a), Type::DevicePointer(b)) => a.is_compatible_with(b),
+            (a, b) => a == b,
+        }
+    }
+}
+impl TypeExt for PointerType {
+    fn pointer_store_cls(&self) -> Option<StorageClass> {
+        Some(self.store_cls)
+    }
+    fn layout_min_nbyte(&self) -> Option<usize> {
+        Some(POINTER_NBYTE)
+    }
+    fn layout_nbyte(&self) -> Option<usize> {
+        Some(POINTER_NBYTE)
+    }
+    fn block_min_nbyte(&self, _layout: BlockLayout) -> Option<usize> {
+        Some(POINTER_NBYTE)
+    }
+    fn block_nbyte(&self, _layout: BlockLayout) -> Option<usize> {
+        Some(POINTER_NBYTE)
+    }
+    fn block_align(&self, _layout: BlockLayout) -> Option<usize> {
+        Some(POINTER_NBYTE)
+    }
+    fn is_compatible_with(&self, other: &Self) -> bool {
+        self.store_cls == other.store_cls && self.pointee_ty.is_compatible_with(&other.pointee_ty)
+    }
+}
+
+fn scalar_nbyte(scalar_ty: &ScalarType) -> Option<usize> {
+    match scalar_ty {
+        ScalarType::Boolean => Some(BOOL_NBYTE),
+        x => x.nbyte(),
+    }
+}
+fn layout_nbyte_impl(ty: &Type, is_min: bool) -> Option<usize> {
+    match ty {
+        Type::Scalar(x) => scalar_nbyte(x),
+        Type::Vector(x) => Some(scalar_nbyte(&x.scalar_ty)? * x.nscalar as usize),
+        Type::Array(x) => {
+            // Runtime arrays have at least one element.
+            let nelement = x.nelement.unwrap_or(0) as usize;
+            let nelement = if is_min { nelement.max(1) } else { nelement };
+            Some(x.stride? * nelement)
+        }
+        Type::Struct(x) => {
+            let last_member = x.members.last()?;
+            Some(last_member.offset? + layout_nbyte_impl(&last_member.ty, is_min)?)
+        }
+        Type::DevicePointer(_) | Type::DeviceAddress(_) => Some(POINTER_NBYTE),
+        x if is_min => x.min_nbyte(),
+        x => x.nbyte(),
+    }
+}
+
+/// Round `x` up to a multiple of `align`, which is a power of two.
+fn round_up(x: usize, align: usize) -> usize {
+    (x + align - 1) & !(align - 1)
+}
+/// Size and alignment of a vector of `nscalar` scalars of `scalar_ty`.
+fn vector_layout(
+    scalar_ty: &ScalarType,
+    nscalar: u32,
+    layout: BlockLayout,
+) -> Option<(usize, usize)> {
+    let scalar_nbyte = scalar_nbyte(scalar_ty)?;
+    let nbyte = scalar_nbyte * nscalar as usize;
+    let align = match layout {
+        BlockLayout::Scalar => scalar_nbyte,
+        // Three-component vectors are aligned as four-component ones.
+        _ if nscalar == 3 => scalar_nbyte * 4,
+        _ => nbyte,
+    };
+    Some((nbyte, align))
+}
+/// Stride and alignment of elements of arrays of elements of `nbyte` bytes
+/// aligned to `align`.
+fn array_layout(nbyte: usize, align: usize, layout: BlockLayout) -> (usize, usize) {
+    let align = match layout {
+        BlockLayout::Std140 => round_up(align, 16),
+        _ => align,
+    };
+    (round_up(nbyte, align), align)
+}
+fn matrix_layout(matrix_ty: &MatrixType, layout: BlockLayout) -> Option<(usize, usize)> {
+    // A matrix is laid out as an array of its columns, or its rows if it's
+    // row-major.
+    let (nvector, nscalar) = match matrix_ty.axis_order {
+        Some(MatrixAxisOrder::RowMajor) => (matrix_ty.vector_ty.nscalar, matrix_ty.nvector),
+        _ => (matrix_ty.nvector, matrix_ty.vector_ty.nscalar),
+    };
+    let (nbyte, align) = vector_layout(&matrix_ty.vector_ty.scalar_ty, nscalar, layout)?;
+    let (stride, align) = array_layout(nbyte, align, layout);
+    Some((stride * nvector as usize, align))
+}
+fn block_layout_impl(ty: &Type, layout: BlockLayout, is_min: bool) -> Option<(usize, usize)> {
+    match ty {
+        Type::Scalar(x) => {
+            let nbyte = scalar_nbyte(x)?;
+            Some((nbyte, nbyte))
+        }
+        Type::Vector(x) => vector_layout(&x.scalar_ty, x.nscalar, layout),
+        Type::Matrix(x) => matrix_layout(x, layout),
+        Type::Array(x) => {
+            let (nbyte, align) = block_layout_impl(&x.element_ty, layout, is_min)?;
+            let (stride, align) = array_layout(nbyte, align, layout);
+            // Runtime arrays have at least one element.
+            let nelement = x.nelement.unwrap_or(0) as usize;
+            let nelement = if is_min { nelement.max(1) } else { nelement };
+            Some((stride * nelement, align))
+        }
+        Type::Struct(x) => struct_layout(x, layout, is_min),
+        Type::DevicePointer(_) | Type::DeviceAddress(_) => Some((POINTER_NBYTE, POINTER_NBYTE)),
+        _ => None,
+    }
+}
+fn struct_layout(
+    struct_ty: &StructType,
+    layout: BlockLayout,
+    is_min: bool,
+) -> Option<(usize, usize)> {
+    let mut offset = 0;
+    let mut align = match layout {
+        BlockLayout::Std140 => 16,
+        _ => 1,
+    };
+    for member in struct_ty.members.iter() {
+        let (member_nbyte, member_align) = block_layout_impl(&member.ty, layout, is_min)?;
+        offset = round_up(offset, member_align) + member_nbyte;
+        align = align.max(member_align);
+    }
+    Some((round_up(offset, align), align))
+}
@@ -0,0 +1,319 @@
+//! Descriptor set layout descriptions, the interchange format between shader
+//! builds and engine runtimes.
+//!
+//! A layout description lists the descriptor sets of a pipeline and the
+//! bindings in each set, merged from the entry points of all stages. It's
+//! serialized to YAML by [`LayoutDescription::to_yaml`]:
+//!
+//! ```yaml
+//! version: 1
+//! sets:
+//!   - set: 0
+//!     bindings:
+//!       - binding: 0
+//!         name: "ubo"
+//!         type: uniform_buffer
+//!         count: 1
+//!         stages: [vertex, fragment]
+//!         flags: []
+//!       - binding: 1
+//!         type: combined_image_sampler
+//!         count: 0
+//!         stages: [fragment]
+//!         flags: [variable_count]
+//! ```
+//!
+//! - `version` is [`LAYOUT_DESCRIPTION_VERSION`]. It's bumped when keys are
+//!   removed, renamed or change their meanings. Keys can be added without a
+//!   version bump so readers should ignore unknown keys.
+//! - `sets` and `bindings` are sorted by set and binding numbers. Sets without
+//!   any binding are omitted.
+//! - `name` is the debug name of the first variable bound, and is omitted if
+//!   the variable is not named.
+//! - `type` is the descriptor type, one of `sampler`,
+//!   `combined_image_sampler`, `sampled_image`, `storage_image`,
+//!   `uniform_texel_buffer`, `storage_texel_buffer`, `uniform_buffer`,
+//!   `storage_buffer`, `input_attachment`, `acceleration_structure` and
+//!   `mutable`. Candidate types of `mutable` descriptors are listed in
+//!   `mutable_types`.
+//! - `count` is the number of descriptors at the binding, or 0 if it's a
+//!   runtime descriptor array.
+//! - `stages` are the shader stages accessing the binding, one of `vertex`,
+//!   `tessellation_control`, `tessellation_evaluation`, `geometry`,
+//!   `fragment`, `compute`, `kernel`, `task`, `mesh`, `raygen`,
+//!   `intersection`, `any_hit`, `closest_hit`, `miss` and `callable`.
+//! - `flags` are any of `variable_count` for runtime descriptor arrays,
+//!   `non_writable` and `non_readable` for storage resources only read or
+//!   written, and `ycbcr_sampler` for bindings requiring immutable samplers
+//!   with a sampler Y'CbCr conversion.
+//! - `push_constant_ranges` lists the `offset`, `size` and `stages` of push
+//!   constant ranges, and is omitted if there is no push constant.
+//!
+//! [`LayoutBinding::vk_desc_ty`], [`LayoutBinding::vk_stage_flags`] and
+//! [`PushConstantRange::vk_stage_flags`] give the Vulkan values to create
+//! `VkDescriptorSetLayout`s and `VkPipelineLayout`s with.
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use crate::{
+    entry_point::{EntryPoint, ExecutionModel},
+    error::Result,
+    pipeline::{Pipeline, PipelineConfig, PipelineDescriptor, PushConstantRange},
+    ty::{AccessType, DescriptorType},
+};
+
+/// Version of the layout description format emitted by
+/// [`LayoutDescription::to_yaml`].
+pub const LAYOUT_DESCRIPTION_VERSION: u32 = 1;
+
+/// A binding in a descriptor set layout.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LayoutBinding {
+    pub binding: u32,
+    /// Name of the first variable bound.
+    pub name: Option<String>,
+    pub desc_ty: DescriptorType,
+    /// Candidate descriptor types if the binding is a mutable descriptor.
+    pub mutable_desc_tys: Option<Vec<DescriptorType>>,
+    /// Number of descriptors, or 0 for runtime descriptor arrays.
+    pub count: u32,
+    /// Execution models of the stages accessing the binding.
+    pub stages: Vec<ExecutionModel>,
+    /// The binding requires immutable samplers with a sampler Y'CbCr
+    /// conversion.
+    pub requires_ycbcr_sampler: bool,
+}
+impl LayoutBinding {
+    fn from_pipeline_desc(binding: u32, desc: &PipelineDescriptor) -> Self {
+        LayoutBinding {
+            binding,
+            name: desc.name.clone(),
+            desc_ty: desc.desc_ty.clone(),
+            mutable_desc_tys: desc.mutable_desc_tys.clone(),
+            count: desc.nbind,
+            stages: desc.stages.clone(),
+            requires_ycbcr_sampler: desc.requires_ycbcr_sampler,
+        }
+    }
+    /// `VkDescriptorType` of the binding.
+    pub fn vk_desc_ty(&self) -> u32 {
+        if self.mutable_desc_tys.is_some() {
+            // VK_DESCRIPTOR_TYPE_MUTABLE_EXT
+            return 1000351000;
+        }
+        vk_desc_ty(&self.desc_ty)
+    }
+    /// `VkShaderStageFlags` of the stages accessing the binding.
+    pub fn vk_stage_flags(&self) -> u32 {
+        self.stages
+            .iter()
+            .filter_map(|x| vk_shader_stage(*x))
+            .fold(0, |seed, x| seed | x)
+    }
+    /// Flag names of the binding in the layout description format.
+    pub fn flags(&self) -> Vec<&'static str> {
+        let mut out = Vec::new();
+        if self.count == 0 {
+            out.push("variable_count");
+        }
+        match desc_ty_access(&self.desc_ty) {
+            Some(AccessType::ReadOnly) => out.push("non_writable"),
+            Some(AccessType::WriteOnly) => out.push("non_readable"),
+            _ => {}
+        }
+        if self.requires_ycbcr_sampler {
+            out.push("ycbcr_sampler");
+        }
+        out
+    }
+}
+
+/// Bindings of a descriptor set layout, sorted by binding numbers.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LayoutSet {
+    pub set: u32,
+    pub bindings: Vec<LayoutBinding>,
+}
+
+/// Descriptor set layouts and push constant ranges of a pipeline, sorted by
+/// set numbers and offsets.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct LayoutDescription {
+    pub sets: Vec<LayoutSet>,
+    pub push_const_ranges: Vec<PushConstantRange>,
+}
+impl LayoutDescription {
+    /// Describe the descriptor set layouts of the entry points of all stages
+    /// of a pipeline. The entry points are merged with the default
+    /// [`PipelineConfig`].
+    pub fn from_entry_points<'a, I: IntoIterator<Item = &'a EntryPoint>>(
+        entry_points: I,
+    ) -> Result<Self> {
+        let pipeline = PipelineConfig::new().merge(entry_points)?;
+        Ok(Self::from_pipeline(&pipeline))
+    }
+    /// Describe the descriptor set layouts of a merged pipeline.
+    pub fn from_pipeline(pipeline: &Pipeline) -> Self {
+        let mut sets = BTreeMap::<u32, Vec<LayoutBinding>>::new();
+        for (desc_bind, desc) in pipeline.descs.iter() {
+            let binding = LayoutBinding::from_pipeline_desc(desc_bind.bind(), desc);
+            sets.entry(desc_bind.set()).or_default().push(binding);
+        }
+        let sets = sets
+            .into_iter()
+            .map(|(set, bindings)| LayoutSet { set, bindings })
+            .collect();
+        LayoutDescription {
+            sets,
+            push_const_ranges: pipeline.push_const_ranges.clone(),
+        }
+    }
+
+    /// Serialize the layout description to YAML in the format documented in
+    /// the [module](self) docs.
+    pub fn to_yaml(&self) -> String {
+        let mut out = String::new();
+        writeln!(out, "version: {}", LAYOUT_DESCRIPTION_VERSION).unwrap();
+        if self.sets.is_empty() {
+            out.push_str("sets: []\n");
+        } else {
+            out.push_str("sets:\n");
+        }
+        for set in self.sets.iter() {
+            writeln!(out, "  - set: {}", set.set).unwrap();
+            out.push_str("    bindings:\n");
+            for binding in set.bindings.iter() {
+                writeln!(out, "      - binding: {}", binding.binding).unwrap();
+                if let Some(name) = &binding.name {
+                    writeln!(out, "        name: {}", yaml_str(name)).unwrap();
+                }
+                let ty = if binding.mutable_desc_tys.is_some() {
+                    "mutable"
+                } else {
+                    desc_ty_name(&binding.desc_ty)
+                };
+                writeln!(out, "        type: {}", ty).unwrap();
+                if let Some(desc_tys) = &binding.mutable_desc_tys {
+                    let desc_tys = desc_tys.iter().map(desc_ty_name).collect::<Vec<_>>();
+                    writeln!(out, "        mutable_types: [{}]", desc_tys.join(", ")).unwrap();
+                }
+                writeln!(out, "        count: {}", binding.count).unwrap();
+                let stages = binding
+                    .stages
+                    .iter()
+                    .map(|x| stage_name(*x))
+                    .collect::<Vec<_>>();
+                writeln!(out, "        stages: [{}]", stages.join(", ")).unwrap();
+                writeln!(out, "        flags: [{}]", binding.flags().join(", ")).unwrap();
+            }
+        }
+        if !self.push_const_ranges.is_empty() {
+            out.push_str("push_constant_ranges:\n");
+            for range in self.push_const_ranges.iter() {
+                writeln!(out, "  - offset: {}", range.offset).unwrap();
+                writeln!(out, "    size: {}", range.size).unwrap();
+                let stages = range
+                    .stages
+                    .iter()
+                    .map(|x| stage_name(*x))
+                    .collect::<Vec<_>>();
+                writeln!(out, "    stages: [{}]", stages.join(", ")).unwrap();
+            }
+        }
+        out
+    }
+}
+
+fn desc_ty_name(desc_ty: &DescriptorType) -> &'static str {
+    match desc_ty {
+        DescriptorType::Sampler() => "sampler",
+        DescriptorType::CombinedImageSampler() => "combined_image_sampler",
+        DescriptorType::SampledImage() => "sampled_image",
+        DescriptorType::StorageImage(_) => "storage_image",
+        DescriptorType::UniformTexelBuffer() => "uniform_texel_buffer",
+        DescriptorType::StorageTexelBuffer(_) => "storage_texel_buffer",
+        DescriptorType::UniformBuffer() => "uniform_buffer",
+        DescriptorType::StorageBuffer(_) => "storage_buffer",
+        DescriptorType::InputAttachment(_) => "input_attachment",
+        DescriptorType::AccelStruct() => "acceleration_structure",
+    }
+}
+/// `VkDescriptorType` of a descriptor type.
+pub fn vk_desc_ty(desc_ty: &DescriptorType) -> u32 {
+    match desc_ty {
+        DescriptorType::Sampler() => 0,
+        DescriptorType::CombinedImageSampler() => 1,
+        DescriptorType::SampledImage() => 2,
+        DescriptorType::StorageImage(_) => 3,
+        DescriptorType::UniformTexelBuffer() => 4,
+        DescriptorType::StorageTexelBuffer(_) => 5,
+        DescriptorType::UniformBuffer() => 6,
+        DescriptorType::StorageBuffer(_) => 7,
+        DescriptorType::InputAttachment(_) => 10,
+        DescriptorType::AccelStruct() => 1000150000,
+    }
+}
+/// `VkShaderStageFlagBits` of an execution model, or `None` if it's not
+/// available in Vulkan.
+pub fn vk_shader_stage(exec_model: ExecutionModel) -> Option<u32> {
+    let x = match exec_model {
+        ExecutionModel::Vertex => 0x1,
+        ExecutionModel::TessellationControl => 0x2,
+        ExecutionModel::TessellationEvaluation => 0x4,
+        ExecutionModel::Geometry => 0x8,
+        ExecutionModel::Fragment => 0x10,
+        ExecutionModel::GLCompute => 0x20,
+        ExecutionModel::Kernel => return None,
+        ExecutionModel::TaskNV | ExecutionModel::TaskEXT => 0x40,
+        ExecutionModel::MeshNV | ExecutionModel::MeshEXT => 0x80,
+        ExecutionModel::RayGenerationNV => 0x100,
+        ExecutionModel::AnyHitNV => 0x200,
+        ExecutionModel::ClosestHitNV => 0x400,
+        ExecutionModel::MissNV => 0x800,
+        ExecutionModel::IntersectionNV => 0x1000,
+        ExecutionModel::CallableNV => 0x2000,
+    };
+    Some(x)
+}
+fn desc_ty_access(desc_ty: &DescriptorType) -> Option<AccessType> {
+    match desc_ty {
+        DescriptorType::StorageImage(x)
+        | DescriptorType::StorageTexelBuffer(x)
+        | DescriptorType::StorageBuffer(x) => Some(*x),
+        _ => None,
+    }
+}
+fn stage_name(exec_model: ExecutionModel) -> &'static str {
+    match exec_model {
+        ExecutionModel::Vertex => "vertex",
+        ExecutionModel::TessellationControl => "tessellation_control",
+        ExecutionModel::TessellationEvaluation => "tessellation_evaluation",
+        ExecutionModel::Geometry => "geometry",
+        ExecutionModel::Fragment => "fragment",
+        ExecutionModel::GLCompute => "compute",
+        ExecutionModel::Kernel => "kernel",
+        ExecutionModel::TaskNV | ExecutionModel::TaskEXT => "task",
+        ExecutionModel::MeshNV | ExecutionModel::MeshEXT => "mesh",
+        ExecutionModel::RayGenerationNV => "raygen",
+        ExecutionModel::IntersectionNV => "intersection",
+        ExecutionModel::AnyHitNV => "any_hit",
+        ExecutionModel::ClosestHitNV => "closest_hit",
+        ExecutionModel::MissNV => "miss",
+        ExecutionModel::CallableNV => "callable",
+    }
+}
+/// Double-quoted YAML string.
+fn yaml_str(x: &str) -> String {
+    let mut out = String::with_capacity(x.len() + 2);
+    out.push('"');
+    for c in x.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if c.is_control() => write!(out, "\\u{:04x}", c as u32).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
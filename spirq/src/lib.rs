@@ -58,12 +58,41 @@
 //! [`EntryPoint`]: struct.EntryPoint.html
 //! [`reflect`]: reflect/struct.ReflectConfig.html#method.reflect
 //! [`Type`]: ty/enum.Type.html
+#[macro_use]
+mod trace;
+mod arena;
 mod instr;
 
+pub mod binary;
+pub mod bitfield;
+pub mod clspv;
+pub mod demangle;
+pub mod diagnostic;
 pub mod entry_point;
+pub mod fixture;
+pub mod format;
+pub mod gl;
+pub mod grammar;
+pub mod header;
 pub mod inspect;
+pub mod key;
+pub mod label;
+pub mod layout;
+pub mod layout_desc;
+pub mod line_info;
+pub mod markdown;
+pub mod patch;
+pub mod pipeline;
+pub mod pool;
 pub mod reflect;
 pub mod reflect_cfg;
+pub mod reflection;
+#[cfg(feature = "serde")]
+pub mod serde_support;
+pub mod stub;
+pub mod symbol;
+pub mod ty_arena;
+pub mod wgsl;
 
 #[cfg(test)]
 mod tests;
@@ -78,17 +107,34 @@ pub use spq_core::spirv;
 pub use spq_core::ty;
 pub use spq_core::var;
 
-pub use reflect_cfg::ReflectConfig;
+pub use reflect_cfg::{
+    DecorationCollisionPolicy, MissingBindingPolicy, NameCollisionPolicy, ReflectConfig,
+};
 
 // Re-exports.
 pub mod prelude {
-    pub use super::ReflectConfig;
     pub use super::{
+        binary::SpirvBinaryExt,
         constant::ConstantValue,
-        entry_point::{EntryPoint, ExecutionModel},
+        diagnostic::Diagnostic,
+        entry_point::{
+            BorrowedEntryPoint, DerivativeRequirements, DescriptorInfo, EntryPoint, ExecutionModel,
+            ExecutionModelRequirements, FragmentInterlock, InputAttachment, InterfaceVariable,
+            InterlockScope, LiteralSampler, MeshOutputs, MeshPrimitiveType, PrivateVariable,
+            RouteOwner, SharedVariable, TexelBufferInfo, UsedElements, VariableRoute,
+        },
         error::{Error, Result},
+        layout::{BlockLayout, TypeExt},
         parse::SpirvBinary,
+        pipeline::{
+            Pipeline, PipelineConfig, PipelineHints, PipelineLibraryPart, PipelineLibraryPartition,
+        },
+        reflection::Reflection,
+        symbol::TypeResolveExt,
         ty::{AccessType, DescriptorType, SpirvType, Type},
         var::{DescriptorBinding, InterfaceLocation, SpecId, Variable},
     };
+    pub use super::{
+        DecorationCollisionPolicy, MissingBindingPolicy, NameCollisionPolicy, ReflectConfig,
+    };
 }
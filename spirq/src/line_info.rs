@@ -0,0 +1,194 @@
+//! Source locations of instructions, from the line information emitted by
+//! shader compilers with debug information enabled.
+//!
+//! Locations are declared by `OpLine`, and by `DebugLine` of the
+//! `NonSemantic.Shader.DebugInfo.100` extended instruction set. A location
+//! applies to the instructions following it, up to the next location, the
+//! next `OpNoLine` or `DebugNoLine`, or the end of the block. [`LineTable`]
+//! resolves these scopes so that tools built on [`InstrIter`] can report
+//! locations in the original shader source.
+//!
+//! [`InstrIter`]: crate::binary::InstrIter
+use fnv::FnvHashMap as HashMap;
+
+use crate::{
+    binary::InstrIter,
+    error::Result,
+    parse::{Instr, SpirvBinary},
+    spirv::Op,
+};
+
+/// Name of the extended instruction set of non-semantic debug information.
+const SHADER_DEBUG_INFO_SET_NAME: &str = "NonSemantic.Shader.DebugInfo.100";
+const DEBUG_SOURCE: u32 = 35;
+const DEBUG_LINE: u32 = 103;
+const DEBUG_NO_LINE: u32 = 104;
+
+/// A location in shader source.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SourceLocation {
+    /// Path of the source file, or `None` if it's not declared by an
+    /// `OpString`.
+    pub file: Option<String>,
+    pub line: u32,
+    /// Column of the location, or 0 if it's unknown.
+    pub column: u32,
+}
+
+#[derive(Debug, Clone)]
+struct LineEntry {
+    /// Index of the instruction in the module, excluding the header.
+    idx: usize,
+    /// Word offset of the instruction in the module, including the header.
+    offset: usize,
+    loc: Option<usize>,
+}
+
+/// Source locations of the instructions in a module.
+#[derive(Debug, Default, Clone)]
+pub struct LineTable {
+    locs: Vec<SourceLocation>,
+    /// Instructions where the source location changes, in module order.
+    /// Each entry applies until the next one.
+    entries: Vec<LineEntry>,
+}
+impl LineTable {
+    /// Collect the source locations declared in `spv`. Modules without line
+    /// information give an empty table.
+    pub fn new(spv: &SpirvBinary) -> Result<LineTable> {
+        let mut out = LineTable::default();
+        let mut strs = HashMap::<u32, String>::default();
+        let mut consts = HashMap::<u32, u32>::default();
+        let mut srcs = HashMap::<u32, Option<String>>::default();
+        let mut debug_info_set_id = None;
+        let mut cur_loc = None;
+        for (idx, instr) in InstrIter::new(spv.words())?.enumerate() {
+            let instr = instr?;
+            let operands = instr.operands();
+            let mut next_loc = cur_loc;
+            let mut is_loc_instr = true;
+            match instr.op() {
+                Some(Op::String) => {
+                    if let (Some(&id), Ok(value)) = (operands.first(), read_str(instr.instr())) {
+                        strs.insert(id, value);
+                    }
+                    is_loc_instr = false;
+                }
+                Some(Op::Constant) => {
+                    if let [_, id, value] = operands {
+                        consts.insert(*id, *value);
+                    }
+                    is_loc_instr = false;
+                }
+                Some(Op::ExtInstImport) => {
+                    if read_str(instr.instr()).ok().as_deref() == Some(SHADER_DEBUG_INFO_SET_NAME) {
+                        debug_info_set_id = operands.first().copied();
+                    }
+                    is_loc_instr = false;
+                }
+                Some(Op::Line) => {
+                    if let [file_id, line, column] = operands {
+                        let loc = SourceLocation {
+                            file: strs.get(file_id).cloned(),
+                            line: *line,
+                            column: *column,
+                        };
+                        next_loc = Some(out.intern(loc));
+                    }
+                }
+                Some(Op::NoLine) => next_loc = None,
+                Some(Op::ExtInst) if operands.get(2).copied() == debug_info_set_id => {
+                    match operands.get(3..).unwrap_or_default() {
+                        [DEBUG_SOURCE, file_id, ..] => {
+                            srcs.insert(operands[1], strs.get(file_id).cloned());
+                        }
+                        [DEBUG_LINE, src_id, line_id, _, column_id, ..] => {
+                            let loc = SourceLocation {
+                                file: srcs.get(src_id).cloned().flatten(),
+                                line: consts.get(line_id).copied().unwrap_or_default(),
+                                column: consts.get(column_id).copied().unwrap_or_default(),
+                            };
+                            next_loc = Some(out.intern(loc));
+                        }
+                        [DEBUG_NO_LINE, ..] => next_loc = None,
+                        _ => {}
+                    }
+                }
+                _ => {}
+            }
+            if next_loc != cur_loc {
+                out.entries.push(LineEntry {
+                    idx,
+                    offset: instr.offset(),
+                    loc: next_loc,
+                });
+                cur_loc = next_loc;
+            }
+            // Block terminators are the last instructions a location applies
+            // to.
+            if is_loc_instr && cur_loc.is_some() && is_block_terminator(instr.op()) {
+                out.entries.push(LineEntry {
+                    idx: idx + 1,
+                    offset: instr.offset() + instr.words().len(),
+                    loc: None,
+                });
+                cur_loc = None;
+            }
+        }
+        Ok(out)
+    }
+
+    fn intern(&mut self, loc: SourceLocation) -> usize {
+        match self.locs.iter().position(|x| *x == loc) {
+            Some(i) => i,
+            None => {
+                self.locs.push(loc);
+                self.locs.len() - 1
+            }
+        }
+    }
+    fn find<F: Fn(&LineEntry) -> usize>(&self, key: usize, f: F) -> Option<&SourceLocation> {
+        let i = self.entries.partition_point(|x| f(x) <= key);
+        let entry = self.entries.get(i.checked_sub(1)?)?;
+        Some(&self.locs[entry.loc?])
+    }
+
+    /// Source location of the `idx`-th instruction after the module header,
+    /// counted the same way as [`InstrIter`] yields them.
+    pub fn get(&self, idx: usize) -> Option<&SourceLocation> {
+        self.find(idx, |x| x.idx)
+    }
+    /// Source location of the instruction at word offset `offset` in the
+    /// module, e.g., [`InstrRef::offset`].
+    ///
+    /// [`InstrRef::offset`]: crate::binary::InstrRef::offset
+    pub fn get_by_offset(&self, offset: usize) -> Option<&SourceLocation> {
+        self.find(offset, |x| x.offset)
+    }
+    /// Whether the module has no line information.
+    pub fn is_empty(&self) -> bool {
+        self.locs.is_empty()
+    }
+}
+
+fn read_str(instr: &Instr) -> Result<String> {
+    let mut operands = instr.operands();
+    operands.read_u32()?;
+    Ok(operands.read_str()?.to_owned())
+}
+fn is_block_terminator(op: Option<Op>) -> bool {
+    matches!(
+        op,
+        Some(Op::Branch)
+            | Some(Op::BranchConditional)
+            | Some(Op::Switch)
+            | Some(Op::Return)
+            | Some(Op::ReturnValue)
+            | Some(Op::Kill)
+            | Some(Op::Unreachable)
+            | Some(Op::TerminateInvocation)
+            | Some(Op::IgnoreIntersectionKHR)
+            | Some(Op::TerminateRayKHR)
+            | Some(Op::EmitMeshTasksEXT)
+    )
+}
@@ -0,0 +1,243 @@
+//! Markdown documentation of shader interfaces, to be published alongside
+//! shaders and kept up to date by build scripts.
+//!
+//! Each entry point is documented by tables of its descriptors, push
+//! constants, stage inputs and outputs and specialization constants, followed
+//! by the member layouts of all struct types used. Sections without any
+//! entry are omitted.
+use std::fmt::Write;
+
+use crate::{
+    constant::ConstantValue,
+    entry_point::EntryPoint,
+    layout::TypeExt,
+    ty::{StructType, Type},
+    var::Variable,
+};
+
+/// Generate Markdown documentation of the interface of `entry_point`. Struct
+/// types without debug names are named after the variables or members of
+/// them.
+pub fn generate_markdown(entry_point: &EntryPoint) -> String {
+    let mut writer = MarkdownWriter::default();
+    let mut descs = Vec::new();
+    let mut push_consts = Vec::new();
+    let mut inputs = Vec::new();
+    let mut outputs = Vec::new();
+    let mut specs = Vec::new();
+    for var in entry_point.vars.iter() {
+        match var {
+            Variable::Descriptor {
+                name,
+                desc_bind,
+                desc_ty,
+                ty,
+                nbind,
+            } => {
+                let hint = format!("Block_{}_{}", desc_bind.set(), desc_bind.bind());
+                let ty = writer.ty_name(ty, &hint);
+                let nbind = match nbind {
+                    0 => "runtime".to_owned(),
+                    x => x.to_string(),
+                };
+                let row = vec![
+                    desc_bind.set().to_string(),
+                    desc_bind.bind().to_string(),
+                    var_name(name),
+                    format!("{:?}", desc_ty),
+                    ty,
+                    nbind,
+                ];
+                descs.push(((desc_bind.set(), desc_bind.bind()), row));
+            }
+            Variable::PushConstant { name, ty } => {
+                let ty = writer.ty_name(ty, "PushConstants");
+                push_consts.push(vec![var_name(name), ty]);
+            }
+            Variable::Input { name, location, ty } | Variable::Output { name, location, ty } => {
+                let hint = format!("Location{}", location.loc());
+                let row = vec![
+                    location.loc().to_string(),
+                    location.comp().to_string(),
+                    var_name(name),
+                    writer.ty_name(ty, &hint),
+                ];
+                let key = (location.loc(), location.comp());
+                if let Variable::Input { .. } = var {
+                    inputs.push((key, row));
+                } else {
+                    outputs.push((key, row));
+                }
+            }
+            Variable::SpecConstant { name, spec_id, ty } => {
+                let default = entry_point
+                    .spec_defaults
+                    .get(spec_id)
+                    .map(const_value)
+                    .unwrap_or_default();
+                let row = vec![
+                    spec_id.to_string(),
+                    var_name(name),
+                    format!("`{}`", ty),
+                    default,
+                ];
+                specs.push((*spec_id, row));
+            }
+        }
+    }
+    descs.sort_by_key(|x| x.0);
+    inputs.sort_by_key(|x| x.0);
+    outputs.sort_by_key(|x| x.0);
+    specs.sort_by_key(|x| x.0);
+
+    let mut out = format!("# `{}` ({:?})\n", entry_point.name, entry_point.exec_model);
+    write_table(
+        &mut out,
+        "Descriptors",
+        &["Set", "Binding", "Name", "Descriptor Type", "Type", "Count"],
+        descs.into_iter().map(|x| x.1),
+    );
+    write_table(&mut out, "Push Constants", &["Name", "Type"], push_consts);
+    write_table(
+        &mut out,
+        "Inputs",
+        &["Location", "Component", "Name", "Type"],
+        inputs.into_iter().map(|x| x.1),
+    );
+    write_table(
+        &mut out,
+        "Outputs",
+        &["Location", "Component", "Name", "Type"],
+        outputs.into_iter().map(|x| x.1),
+    );
+    write_table(
+        &mut out,
+        "Specialization Constants",
+        &["ID", "Name", "Type", "Default"],
+        specs.into_iter().map(|x| x.1),
+    );
+    if !writer.out.is_empty() {
+        out += "\n## Types\n";
+        out += &writer.out;
+    }
+    out
+}
+
+#[derive(Default)]
+struct MarkdownWriter {
+    /// Struct types already documented and their names.
+    structs: Vec<(StructType, String)>,
+    out: String,
+}
+impl MarkdownWriter {
+    /// Name of `ty` in a table cell. Struct types are documented on their
+    /// first use.
+    fn ty_name(&mut self, ty: &Type, hint: &str) -> String {
+        match ty {
+            Type::Struct(struct_ty) => {
+                let name = self.declare_struct(struct_ty, hint);
+                // Heading anchors are lowercased by most renderers.
+                format!("[`{}`](#{})", name, name.to_lowercase())
+            }
+            Type::Array(arr_ty) => {
+                let element_ty = self.ty_name(&arr_ty.element_ty, hint);
+                match arr_ty.nelement {
+                    Some(nelement) => format!("{}\\[{}\\]", element_ty, nelement),
+                    None => format!("{}\\[\\]", element_ty),
+                }
+            }
+            _ => format!("`{}`", ty),
+        }
+    }
+    fn declare_struct(&mut self, struct_ty: &StructType, hint: &str) -> String {
+        if let Some((_, name)) = self.structs.iter().find(|x| x.0 == *struct_ty) {
+            return name.clone();
+        }
+        let base = struct_ty.name.as_deref().unwrap_or(hint).to_owned();
+        let mut name = base.clone();
+        let mut i = 1;
+        while self.structs.iter().any(|x| x.1 == name) {
+            name = format!("{}_{}", base, i);
+            i += 1;
+        }
+        self.structs.push((struct_ty.clone(), name.clone()));
+
+        let mut rows = Vec::new();
+        for (i, member) in struct_ty.members.iter().enumerate() {
+            let member_name = match &member.name {
+                Some(x) => x.clone(),
+                None => i.to_string(),
+            };
+            let hint = format!("{}_{}", name, member_name);
+            let offset = member.offset.map(|x| x.to_string()).unwrap_or_default();
+            let nbyte = match member.ty.layout_nbyte() {
+                Some(x) => x.to_string(),
+                None => "runtime".to_owned(),
+            };
+            let ty = self.ty_name(&member.ty, &hint);
+            rows.push(vec![
+                offset,
+                nbyte,
+                format!("`{}`", cell(&member_name)),
+                ty,
+                format!("{:?}", member.access_ty),
+            ]);
+        }
+        // Nested struct types have been documented by now, before this one.
+        let mut section = String::new();
+        write_table(
+            &mut section,
+            &format!("`{}`", name),
+            &["Offset", "Size", "Name", "Type", "Access"],
+            rows,
+        );
+        self.out += &section.replacen("\n## ", "\n### ", 1);
+        name
+    }
+}
+
+fn var_name(name: &Option<String>) -> String {
+    match name {
+        Some(x) => format!("`{}`", cell(x)),
+        None => "*unnamed*".to_owned(),
+    }
+}
+fn const_value(x: &ConstantValue) -> String {
+    match x {
+        ConstantValue::Bool(x) => x.to_string(),
+        ConstantValue::S8(x) => x.to_string(),
+        ConstantValue::S16(x) => x.to_string(),
+        ConstantValue::S32(x) => x.to_string(),
+        ConstantValue::S64(x) => x.to_string(),
+        ConstantValue::U8(x) => x.to_string(),
+        ConstantValue::U16(x) => x.to_string(),
+        ConstantValue::U32(x) => x.to_string(),
+        ConstantValue::U64(x) => x.to_string(),
+        ConstantValue::F16(x) => x.into_inner().to_f32().to_string(),
+        ConstantValue::F32(x) => x.into_inner().to_string(),
+        ConstantValue::F64(x) => x.into_inner().to_string(),
+        x => format!("{:?}", x),
+    }
+}
+/// Escape pipes which separate table cells.
+fn cell(x: &str) -> String {
+    x.replace('|', "\\|")
+}
+fn write_table<I: IntoIterator<Item = Vec<String>>>(
+    out: &mut String,
+    title: &str,
+    header: &[&str],
+    rows: I,
+) {
+    let mut rows = rows.into_iter().peekable();
+    if rows.peek().is_none() {
+        return;
+    }
+    writeln!(out, "\n## {}\n", title).unwrap();
+    writeln!(out, "| {} |", header.join(" | ")).unwrap();
+    let seps = header.iter().map(|_| "---").collect::<Vec<_>>();
+    writeln!(out, "| {} |", seps.join(" | ")).unwrap();
+    for row in rows {
+        writeln!(out, "| {} |", row.join(" | ")).unwrap();
+    }
+}
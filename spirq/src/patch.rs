@@ -0,0 +1,330 @@
+//! Binary patches of SPIR-V modules working around driver quirks without
+//! recompiling the shaders.
+//!
+//! [`Patcher`] reflects the module to find the entry points a patch applies
+//! to, and inserts the instructions of the patch into the module. IDs of the
+//! original module are kept and new IDs are allocated beyond the original ID
+//! bound, so the patched module can still be reflected as the original one.
+use crate::{
+    error::{anyhow, Result},
+    fixture::push_instr,
+    parse::SpirvBinary,
+    reflect_cfg::ReflectConfig,
+    spirv::{BuiltIn, Decoration, ExecutionModel, Op, StorageClass},
+};
+
+/// Bits of `1.0f32`.
+const F32_ONE: u32 = 0x3f80_0000;
+
+/// Binary patcher configuration builder.
+#[derive(Debug, Default, Clone)]
+pub struct Patcher {
+    inject_point_size: bool,
+}
+impl Patcher {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Store `1.0` to `PointSize` at the beginning of the vertex entry points
+    /// not writing it. Vertex shaders drawing points must write `PointSize`
+    /// unless the `maintenance5` feature is enabled, and some drivers,
+    /// notably on Android, draw nothing or garbage otherwise. `PointSize`
+    /// is written to the member of `gl_PerVertex` if it's declared there.
+    ///
+    /// Requires the `access-analysis` feature to find the entry points
+    /// writing `PointSize`, or [`Patcher::patch`] fails.
+    pub fn inject_point_size(&mut self, x: bool) -> &mut Self {
+        self.inject_point_size = x;
+        self
+    }
+
+    /// Apply the enabled patches to `spv`.
+    pub fn patch(&self, spv: &SpirvBinary) -> Result<SpirvBinary> {
+        let mut module = Module::parse(spv)?;
+        if self.inject_point_size {
+            // Without access analysis, `PointSize` is assumed to be written
+            // wherever it's declared, which is exactly where it's missed.
+            if !cfg!(feature = "access-analysis") {
+                return Err(anyhow!(
+                    "point size injection requires the access-analysis feature"
+                ));
+            }
+            let entry_points = ReflectConfig::new().spv(spv.clone()).reflect()?;
+            for entry_point in entry_points.iter() {
+                if entry_point.exec_model == ExecutionModel::Vertex
+                    && !entry_point.writes_point_size
+                {
+                    module.inject_point_size(&entry_point.name)?;
+                }
+            }
+        }
+        Ok(module.into_spv())
+    }
+}
+
+/// A module broken into instructions to be edited.
+struct Module {
+    header: Vec<u32>,
+    instrs: Vec<Vec<u32>>,
+}
+impl Module {
+    fn parse(spv: &SpirvBinary) -> Result<Module> {
+        let header = spv
+            .words()
+            .get(..5)
+            .ok_or_else(|| anyhow!("spirv binary is too short"))?
+            .to_owned();
+        let mut instrs = Vec::new();
+        let mut it = spv.instrs()?;
+        while let Some(instr) = it.next()? {
+            instrs.push(instr.as_ref().to_owned());
+        }
+        Ok(Module { header, instrs })
+    }
+    fn into_spv(self) -> SpirvBinary {
+        let mut out = self.header;
+        for instr in self.instrs {
+            out.extend(instr);
+        }
+        SpirvBinary::from(out)
+    }
+
+    fn alloc_id(&mut self) -> u32 {
+        let id = self.header[3];
+        self.header[3] += 1;
+        id
+    }
+    /// Index of the first instruction of `op` whose operands match `pred`.
+    fn find(&self, op: Op, pred: impl Fn(&[u32]) -> bool) -> Option<usize> {
+        self.instrs
+            .iter()
+            .position(|x| x[0] & 0xffff == op as u32 && pred(&x[1..]))
+    }
+    /// Insert `instr` at the end of the types, constants and global
+    /// variables.
+    fn declare_global(&mut self, op: Op, operands: &[u32]) {
+        let i = self
+            .find(Op::Function, |_| true)
+            .unwrap_or(self.instrs.len());
+        let mut instr = Vec::new();
+        push_instr(&mut instr, op, operands);
+        self.instrs.insert(i, instr);
+    }
+    /// Insert `instr` at the end of the annotations.
+    fn decorate(&mut self, operands: &[u32]) {
+        let i = self
+            .instrs
+            .iter()
+            .position(|x| !is_preamble_op(x[0] & 0xffff))
+            .unwrap_or(self.instrs.len());
+        let mut instr = Vec::new();
+        push_instr(&mut instr, Op::Decorate, operands);
+        self.instrs.insert(i, instr);
+    }
+
+    fn f32_ty(&mut self) -> u32 {
+        // Floats with an explicit encoding are not IEEE 754 floats.
+        match self.find(Op::TypeFloat, |x| matches!(x, [_, 32])) {
+            Some(i) => self.instrs[i][1],
+            None => {
+                let id = self.alloc_id();
+                self.declare_global(Op::TypeFloat, &[id, 32]);
+                id
+            }
+        }
+    }
+    fn i32_ty(&mut self) -> u32 {
+        match self.find(Op::TypeInt, |x| matches!(x, [_, 32, _])) {
+            Some(i) => self.instrs[i][1],
+            None => {
+                let id = self.alloc_id();
+                self.declare_global(Op::TypeInt, &[id, 32, 0]);
+                id
+            }
+        }
+    }
+    fn ptr_ty(&mut self, store_cls: StorageClass, pointee_ty_id: u32) -> u32 {
+        let store_cls = store_cls as u32;
+        match self.find(
+            Op::TypePointer,
+            |x| matches!(x, [_, a, b] if *a == store_cls && *b == pointee_ty_id),
+        ) {
+            Some(i) => self.instrs[i][1],
+            None => {
+                let id = self.alloc_id();
+                self.declare_global(Op::TypePointer, &[id, store_cls, pointee_ty_id]);
+                id
+            }
+        }
+    }
+    fn constant(&mut self, ty_id: u32, bits: u32) -> u32 {
+        match self.find(
+            Op::Constant,
+            |x| matches!(x, [a, _, b] if *a == ty_id && *b == bits),
+        ) {
+            Some(i) => self.instrs[i][2],
+            None => {
+                let id = self.alloc_id();
+                self.declare_global(Op::Constant, &[ty_id, id, bits]);
+                id
+            }
+        }
+    }
+    fn is_builtin(&self, id: u32, builtin: BuiltIn) -> bool {
+        let (deco, builtin) = (Decoration::BuiltIn as u32, builtin as u32);
+        self.find(Op::Decorate, |x| x == [id, deco, builtin])
+            .is_some()
+    }
+    fn builtin_member(&self, struct_ty_id: u32, builtin: BuiltIn) -> Option<u32> {
+        let (deco, builtin) = (Decoration::BuiltIn as u32, builtin as u32);
+        let i = self.find(Op::MemberDecorate, |x| {
+            x.len() == 4 && x[0] == struct_ty_id && x[2] == deco && x[3] == builtin
+        })?;
+        Some(self.instrs[i][2])
+    }
+
+    fn inject_point_size(&mut self, name: &str) -> Result<()> {
+        let entry_point_idx = self
+            .find(Op::EntryPoint, |x| {
+                x.len() > 2 && x[0] == ExecutionModel::Vertex as u32 && read_str(&x[2..]).0 == name
+            })
+            .ok_or_else(|| anyhow!("vertex entry point `{}` not found", name))?;
+        let operands = &self.instrs[entry_point_idx][1..];
+        let func_id = operands[1];
+        let interface_ids = operands[2 + read_str(&operands[2..]).1..].to_owned();
+
+        // Find where `PointSize` is declared in the interface.
+        let mut point_size = None;
+        for var_id in interface_ids {
+            let store_cls = StorageClass::Output as u32;
+            let ptr_ty_id = match self.find(
+                Op::Variable,
+                |x| matches!(x, [_, a, b, ..] if *a == var_id && *b == store_cls),
+            ) {
+                Some(i) => self.instrs[i][1],
+                None => continue,
+            };
+            if self.is_builtin(var_id, BuiltIn::PointSize) {
+                point_size = Some((var_id, None));
+                break;
+            }
+            let pointee_ty_id = match self.find(
+                Op::TypePointer,
+                |x| matches!(x, [a, _, _] if *a == ptr_ty_id),
+            ) {
+                Some(i) => self.instrs[i][3],
+                None => continue,
+            };
+            if let Some(member_idx) = self.builtin_member(pointee_ty_id, BuiltIn::PointSize) {
+                point_size = Some((var_id, Some(member_idx)));
+                break;
+            }
+        }
+
+        let f32_ty_id = self.f32_ty();
+        let ptr_ty_id = self.ptr_ty(StorageClass::Output, f32_ty_id);
+        let one_id = self.constant(f32_ty_id, F32_ONE);
+        let mut instrs = Vec::new();
+        let dst_id = match point_size {
+            Some((var_id, None)) => var_id,
+            Some((var_id, Some(member_idx))) => {
+                let i32_ty_id = self.i32_ty();
+                let idx_id = self.constant(i32_ty_id, member_idx);
+                let id = self.alloc_id();
+                let mut instr = Vec::new();
+                push_instr(
+                    &mut instr,
+                    Op::AccessChain,
+                    &[ptr_ty_id, id, var_id, idx_id],
+                );
+                instrs.push(instr);
+                id
+            }
+            None => {
+                let var_id = self.alloc_id();
+                let store_cls = StorageClass::Output as u32;
+                self.declare_global(Op::Variable, &[ptr_ty_id, var_id, store_cls]);
+                self.decorate(&[
+                    var_id,
+                    Decoration::BuiltIn as u32,
+                    BuiltIn::PointSize as u32,
+                ]);
+                // Declarations are inserted after the entry points so the
+                // index is still valid.
+                let entry_point = &mut self.instrs[entry_point_idx];
+                entry_point.push(var_id);
+                entry_point[0] += 1 << 16;
+                var_id
+            }
+        };
+        let mut instr = Vec::new();
+        push_instr(&mut instr, Op::Store, &[dst_id, one_id]);
+        instrs.push(instr);
+
+        // Function-local variables must be declared at the beginning of the
+        // first block.
+        let func_idx = self
+            .find(Op::Function, |x| x.get(1) == Some(&func_id))
+            .ok_or_else(|| anyhow!("entry point function {} not found", func_id))?;
+        let mut i = func_idx;
+        while i < self.instrs.len() && self.instrs[i][0] & 0xffff != Op::Label as u32 {
+            i += 1;
+        }
+        i += 1;
+        while i < self.instrs.len()
+            && matches!(
+                Op::from_u32(self.instrs[i][0] & 0xffff),
+                Some(Op::Variable) | Some(Op::Line) | Some(Op::NoLine)
+            )
+        {
+            i += 1;
+        }
+        self.instrs.splice(i..i, instrs);
+        Ok(())
+    }
+}
+
+/// Check if `opcode` belongs to the sections before types, constants and
+/// global variables.
+fn is_preamble_op(opcode: u32) -> bool {
+    matches!(
+        Op::from_u32(opcode),
+        Some(Op::Capability)
+            | Some(Op::Extension)
+            | Some(Op::ExtInstImport)
+            | Some(Op::MemoryModel)
+            | Some(Op::EntryPoint)
+            | Some(Op::ExecutionMode)
+            | Some(Op::ExecutionModeId)
+            | Some(Op::String)
+            | Some(Op::SourceExtension)
+            | Some(Op::Source)
+            | Some(Op::SourceContinued)
+            | Some(Op::Name)
+            | Some(Op::MemberName)
+            | Some(Op::ModuleProcessed)
+            | Some(Op::Decorate)
+            | Some(Op::MemberDecorate)
+            | Some(Op::DecorationGroup)
+            | Some(Op::GroupDecorate)
+            | Some(Op::GroupMemberDecorate)
+            | Some(Op::DecorateId)
+            | Some(Op::DecorateString)
+            | Some(Op::MemberDecorateString)
+    )
+}
+/// Read a null-terminated literal string. Returns the string and the number
+/// of words it occupies.
+fn read_str(words: &[u32]) -> (String, usize) {
+    let mut bytes = Vec::new();
+    for (i, word) in words.iter().enumerate() {
+        for byte in word.to_le_bytes() {
+            if byte == 0 {
+                return (String::from_utf8_lossy(&bytes).into_owned(), i + 1);
+            }
+            bytes.push(byte);
+        }
+    }
+    (String::from_utf8_lossy(&bytes).into_owned(), words.len())
+}
@@ -0,0 +1,750 @@
+//! Pipeline-wide reflection merged from multiple shader stages.
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::{
+    diagnostic::Diagnostic,
+    entry_point::{EntryPoint, ExecutionModel, FragmentInterlock},
+    error::{anyhow, Result},
+    layout::TypeExt,
+    layout_desc::vk_shader_stage,
+    spirv,
+    ty::{DescriptorType, Dim, ScalarType, Type},
+    var::{DescriptorBinding, Variable},
+};
+
+/// A descriptor binding point shared by one or more stages of a pipeline.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PipelineDescriptor {
+    /// Name of the first variable bound to this binding point.
+    pub name: Option<String>,
+    /// Descriptor resource type matching `VkDescriptorType`.
+    pub desc_ty: DescriptorType,
+    /// The concrete SPIR-V type definition of the first variable bound to this
+    /// binding point.
+    pub ty: Type,
+    /// Number of bindings at the binding point.
+    pub nbind: u32,
+    /// Execution models of the stages accessing this binding point.
+    pub stages: Vec<ExecutionModel>,
+    /// Candidate descriptor types if the binding point is laid out as
+    /// `VK_DESCRIPTOR_TYPE_MUTABLE_EXT` by [`PipelineConfig::mutable_desc`].
+    /// `desc_ty` is then the type of the first variable bound.
+    pub mutable_desc_tys: Option<Vec<DescriptorType>>,
+    /// The binding point has to be laid out with immutable samplers created
+    /// with a sampler Y'CbCr conversion, as hinted by
+    /// [`PipelineConfig::ycbcr_desc`].
+    pub requires_ycbcr_sampler: bool,
+    /// Execution models of the stages tracing rays against the acceleration
+    /// structure bound, with `OpTraceRayKHR` or `OpTraceNV`.
+    pub trace_ray_stages: Vec<ExecutionModel>,
+    /// Execution models of the stages querying the acceleration structure
+    /// bound inline, with `OpRayQueryInitializeKHR`.
+    pub ray_query_stages: Vec<ExecutionModel>,
+}
+
+/// A range of push constants accessed by one or more stages of a pipeline,
+/// matching `VkPushConstantRange`. A stage is never included in more than one
+/// range.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PushConstantRange {
+    /// Offset of the range in bytes, a multiple of 4.
+    pub offset: u32,
+    /// Size of the range in bytes, a multiple of 4.
+    pub size: u32,
+    /// Execution models of the stages accessing the range.
+    pub stages: Vec<ExecutionModel>,
+}
+impl PushConstantRange {
+    /// `VkShaderStageFlags` of the stages accessing the range.
+    pub fn vk_stage_flags(&self) -> u32 {
+        self.stages
+            .iter()
+            .filter_map(|x| vk_shader_stage(*x))
+            .fold(0, |seed, x| seed | x)
+    }
+}
+
+/// A member of the push constant blocks of a pipeline and the stages it's
+/// visible to. Members of blocks of a same layout declared in multiple
+/// stages share a same entry.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PushConstantMember {
+    /// Name of the member in the first block declaring it.
+    pub name: Option<String>,
+    /// Offset of the member in bytes.
+    pub offset: u32,
+    /// Size of the member in bytes.
+    pub size: u32,
+    /// Execution models of the stages whose push constant ranges overlap the
+    /// member. These are the stages `vkCmdPushConstants` has to be called
+    /// with to update the member.
+    pub stages: Vec<ExecutionModel>,
+}
+impl PushConstantMember {
+    /// `VkShaderStageFlags` of the stages the member is visible to.
+    pub fn vk_stage_flags(&self) -> u32 {
+        self.stages
+            .iter()
+            .filter_map(|x| vk_shader_stage(*x))
+            .fold(0, |seed, x| seed | x)
+    }
+}
+
+/// Features of `VK_KHR_ray_tracing_pipeline` and `VK_KHR_ray_query`.
+const RAY_TRACING_PIPELINE_FEATURE: &str = "rayTracingPipeline";
+const RAY_QUERY_FEATURE: &str = "rayQuery";
+
+/// Resources of a pipeline merged from the entry points of its stages.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Pipeline {
+    /// Descriptor resources indexed by binding points.
+    pub descs: BTreeMap<DescriptorBinding, PipelineDescriptor>,
+    /// Push constant ranges sorted by offsets. Stages accessing a same range
+    /// share a same entry.
+    pub push_const_ranges: Vec<PushConstantRange>,
+    /// Members of push constant blocks visible to at least one stage, sorted
+    /// by offsets.
+    pub push_const_members: Vec<PushConstantMember>,
+    /// Warnings found when the stages are merged.
+    pub diags: Vec<Diagnostic>,
+}
+impl Pipeline {
+    /// Vulkan features required to access the acceleration structures bound
+    /// to the pipeline, `rayTracingPipeline` if rays are traced against any
+    /// of them and `rayQuery` if any of them is queried inline. Always empty
+    /// without the `access-analysis` feature.
+    pub fn accel_struct_features(&self) -> Vec<&'static str> {
+        let mut out = Vec::new();
+        if self.descs.values().any(|x| !x.trace_ray_stages.is_empty()) {
+            out.push(RAY_TRACING_PIPELINE_FEATURE);
+        }
+        if self.descs.values().any(|x| !x.ray_query_stages.is_empty()) {
+            out.push(RAY_QUERY_FEATURE);
+        }
+        out
+    }
+
+    /// Byte ranges to update with `vkCmdPushConstants` to push all the
+    /// members in `push_const_members`, one range per run of adjacent
+    /// members visible to the same stages. Offsets and sizes are aligned to 4
+    /// bytes.
+    pub fn push_const_updates(&self) -> Vec<PushConstantRange> {
+        let mut out: Vec<PushConstantRange> = Vec::new();
+        for member in self.push_const_members.iter() {
+            let offset = member.offset & !3;
+            let end = (member.offset + member.size + 3) & !3;
+            match out.last_mut() {
+                Some(last) if last.stages == member.stages => {
+                    let last_end = last.offset + last.size;
+                    last.size = last_end.max(end) - last.offset;
+                }
+                _ => out.push(PushConstantRange {
+                    offset,
+                    size: end - offset,
+                    stages: member.stages.clone(),
+                }),
+            }
+        }
+        out
+    }
+
+    /// Check the push constant members against a budget of `max_size` bytes,
+    /// i.e., the `maxPushConstantsSize` limit of a device. Returns a
+    /// [`Diagnostic::PushConstantOverBudget`] for each member ending beyond
+    /// the budget, so a merged pipeline can be checked against the limits of
+    /// multiple devices.
+    pub fn validate_push_const_budget(&self, max_size: u32) -> Vec<Diagnostic> {
+        self.push_const_members
+            .iter()
+            .filter(|x| x.offset + x.size > max_size)
+            .map(|x| Diagnostic::PushConstantOverBudget {
+                member: x.name.clone(),
+                offset: x.offset,
+                size: x.size,
+                max_size,
+                stages: x.stages.clone(),
+            })
+            .collect()
+    }
+}
+
+/// The `maxPushConstantsSize` guaranteed by all Vulkan implementations.
+const DEFAULT_MAX_PUSH_CONST_SIZE: u32 = 128;
+
+/// Pipeline merging configuration builder.
+#[derive(Default, Clone)]
+pub struct PipelineConfig {
+    strict: bool,
+    mutable_descs: BTreeMap<DescriptorBinding, Vec<DescriptorType>>,
+    ycbcr_descs: BTreeSet<DescriptorBinding>,
+    max_push_const_size: Option<u32>,
+}
+impl PipelineConfig {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Treat suspicious findings like shadowed descriptors as errors rather
+    /// than diagnostics.
+    pub fn strict(&mut self, x: bool) -> &mut Self {
+        self.strict = x;
+        self
+    }
+
+    /// Lay out the binding point at `desc_bind` as a mutable descriptor
+    /// (`VK_DESCRIPTOR_TYPE_MUTABLE_EXT`) holding any of `desc_tys`. Variables
+    /// of different descriptor types can then share the binding point as long
+    /// as they are all candidates. Access types and input attachment indices
+    /// are ignored when a descriptor type is matched against the candidates.
+    pub fn mutable_desc(
+        &mut self,
+        desc_bind: DescriptorBinding,
+        desc_tys: &[DescriptorType],
+    ) -> &mut Self {
+        self.mutable_descs.insert(desc_bind, desc_tys.to_owned());
+        self
+    }
+
+    /// Hint that images bound at `desc_bind` have formats that can only be
+    /// sampled through a sampler Y'CbCr conversion, e.g., multi-planar formats
+    /// like `VK_FORMAT_G8_B8R8_2PLANE_420_UNORM`. The variables bound there
+    /// must be sampled as non-multisampled 2D float combined image samplers,
+    /// otherwise the merge fails.
+    pub fn ycbcr_desc(&mut self, desc_bind: DescriptorBinding) -> &mut Self {
+        self.ycbcr_descs.insert(desc_bind);
+        self
+    }
+
+    /// Budget of push constants in bytes, i.e., the `maxPushConstantsSize`
+    /// limit of the target devices. Members ending beyond the budget are
+    /// reported as diagnostics, or errors in strict mode. 128 bytes by
+    /// default, as guaranteed by all Vulkan implementations; many desktop
+    /// devices support 256 bytes. See
+    /// [`Pipeline::validate_push_const_budget`] to check a merged pipeline
+    /// against other budgets.
+    pub fn max_push_const_size(&mut self, x: u32) -> &mut Self {
+        self.max_push_const_size = Some(x);
+        self
+    }
+
+    /// Merge the entry points of all pipeline stages.
+    pub fn merge<'a, I: IntoIterator<Item = &'a EntryPoint>>(
+        &self,
+        entry_points: I,
+    ) -> Result<Pipeline> {
+        let mut out = Pipeline::default();
+        let mut push_const_ranges = BTreeMap::<ExecutionModel, (u32, u32)>::new();
+        let mut push_const_members = BTreeMap::<(u32, u32), Option<String>>::new();
+        for entry_point in entry_points {
+            for var in entry_point.vars.iter() {
+                self.merge_desc(&mut out, entry_point.exec_model, var)?;
+                if let Variable::Descriptor { desc_bind, .. } = var {
+                    let desc = out.descs.get_mut(desc_bind);
+                    let desc_info = entry_point.desc_infos.get(desc_bind);
+                    if let (Some(desc), Some(desc_info)) = (desc, desc_info) {
+                        let stage = entry_point.exec_model;
+                        if desc_info.uses_trace_ray {
+                            add_stage(&mut desc.trace_ray_stages, stage);
+                        }
+                        if desc_info.uses_ray_query {
+                            add_stage(&mut desc.ray_query_stages, stage);
+                        }
+                    }
+                }
+                if let Variable::PushConstant { ty, .. } = var {
+                    let (offset, end) = push_const_range(entry_point.exec_model, ty)?;
+                    for member in ty.as_struct().into_iter().flat_map(|x| x.members.iter()) {
+                        // Checked by `push_const_range`.
+                        let member_offset = member.offset.unwrap_or_default() as u32;
+                        let size = member.ty.layout_nbyte().unwrap_or_default() as u32;
+                        push_const_members
+                            .entry((member_offset, size))
+                            .or_insert_with(|| member.name.clone());
+                    }
+                    // Only the bytes read by the entry point are visible to
+                    // it.
+                    let (offset, end) = match entry_point.push_constant_range() {
+                        Some(x) => (offset.max(x.start), end.min(x.end)),
+                        None => (offset, end),
+                    };
+                    // Vulkan allows only one range per stage.
+                    push_const_ranges
+                        .entry(entry_point.exec_model)
+                        .and_modify(|x| *x = (x.0.min(offset), x.1.max(end)))
+                        .or_insert((offset, end));
+                }
+            }
+        }
+        for (stage, (offset, end)) in push_const_ranges {
+            let size = end - offset;
+            let range = out
+                .push_const_ranges
+                .iter_mut()
+                .find(|x| x.offset == offset && x.size == size);
+            match range {
+                Some(range) => range.stages.push(stage),
+                None => out.push_const_ranges.push(PushConstantRange {
+                    offset,
+                    size,
+                    stages: vec![stage],
+                }),
+            }
+        }
+        out.push_const_ranges
+            .sort_by_key(|x| (x.offset, x.size, x.stages.clone()));
+        for ((offset, size), name) in push_const_members {
+            let start = offset & !3;
+            let end = (offset + size + 3) & !3;
+            let mut stages = out
+                .push_const_ranges
+                .iter()
+                .filter(|x| x.offset < end && start < x.offset + x.size)
+                .flat_map(|x| x.stages.iter().copied())
+                .collect::<Vec<_>>();
+            if stages.is_empty() {
+                continue;
+            }
+            stages.sort();
+            stages.dedup();
+            out.push_const_members.push(PushConstantMember {
+                name,
+                offset,
+                size,
+                stages,
+            });
+        }
+        self.check_push_const_budget(&mut out)?;
+        Ok(out)
+    }
+
+    fn check_push_const_budget(&self, out: &mut Pipeline) -> Result<()> {
+        let max_size = self
+            .max_push_const_size
+            .unwrap_or(DEFAULT_MAX_PUSH_CONST_SIZE);
+        for diag in out.validate_push_const_budget(max_size) {
+            if self.strict {
+                return Err(anyhow!("{}", diag));
+            }
+            out.diags.push(diag);
+        }
+        Ok(())
+    }
+
+    fn merge_desc(&self, out: &mut Pipeline, stage: ExecutionModel, var: &Variable) -> Result<()> {
+        use std::collections::btree_map::Entry;
+        let (name, desc_bind, desc_ty, ty, nbind) = match var {
+            Variable::Descriptor {
+                name,
+                desc_bind,
+                desc_ty,
+                ty,
+                nbind,
+            } => (name, *desc_bind, desc_ty, ty, *nbind),
+            _ => return Ok(()),
+        };
+        let mutable_desc_tys = self.mutable_descs.get(&desc_bind);
+        if let Some(mutable_desc_tys) = mutable_desc_tys {
+            let is_candidate = mutable_desc_tys
+                .iter()
+                .any(|x| std::mem::discriminant(x) == std::mem::discriminant(desc_ty));
+            if !is_candidate {
+                return Err(anyhow!(
+                    "descriptor type {:?} at {} is not a candidate of the mutable descriptor",
+                    desc_ty,
+                    desc_bind
+                ));
+            }
+        }
+        let requires_ycbcr_sampler = self.ycbcr_descs.contains(&desc_bind);
+        if requires_ycbcr_sampler {
+            check_ycbcr_sampling(desc_bind, ty)?;
+        }
+        match out.descs.entry(desc_bind) {
+            Entry::Vacant(e) => {
+                e.insert(PipelineDescriptor {
+                    name: name.clone(),
+                    desc_ty: desc_ty.clone(),
+                    ty: ty.clone(),
+                    nbind,
+                    stages: vec![stage],
+                    mutable_desc_tys: mutable_desc_tys.cloned(),
+                    requires_ycbcr_sampler,
+                    trace_ray_stages: Vec::new(),
+                    ray_query_stages: Vec::new(),
+                });
+            }
+            Entry::Occupied(mut e) => {
+                let desc = e.get_mut();
+                if desc.desc_ty != *desc_ty && mutable_desc_tys.is_none() {
+                    return Err(anyhow!(
+                        "incompatible descriptor types at {}: {:?} and {:?}",
+                        desc_bind,
+                        desc.desc_ty,
+                        desc_ty
+                    ));
+                }
+                if desc.stages.contains(&stage) {
+                    // Aliasing in a same stage is intended.
+                    return Ok(());
+                }
+                desc.stages.push(stage);
+                desc.stages.sort();
+                // Resources of a same type but named very differently are
+                // likely to be copy-pasted without updating the binding.
+                if let (Some(name), Some(shadowed_name)) = (name, &desc.name) {
+                    if desc.ty == *ty && are_names_distinct(name, shadowed_name) {
+                        let diag = Diagnostic::ShadowedDescriptor {
+                            desc_bind,
+                            name: name.clone(),
+                            shadowed_name: shadowed_name.clone(),
+                        };
+                        if self.strict {
+                            return Err(anyhow!("{}", diag));
+                        }
+                        if !out.diags.contains(&diag) {
+                            out.diags.push(diag);
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Descriptor arrays of at least this many bindings are considered large
+/// enough to benefit from descriptor buffers.
+const LARGE_DESC_ARRAY_NBIND: u32 = 1024;
+
+/// Hints on how the Vulkan pipeline of a set of shader stages should be
+/// created, derived from the capabilities declared by the shader modules and
+/// the reflected entry points. These are heuristics; a hint being set doesn't
+/// mean the pipeline cannot be created without following it.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PipelineHints {
+    /// Ray flags culling triangles or AABBs are used. The
+    /// `rayTraversalPrimitiveCulling` feature must be enabled, same as for
+    /// pipelines created with `VK_PIPELINE_CREATE_RAY_TRACING_SKIP_TRIANGLES_BIT_KHR`
+    /// or `VK_PIPELINE_CREATE_RAY_TRACING_SKIP_AABBS_BIT_KHR`.
+    pub uses_primitive_culling: bool,
+    /// Ray tracing with motion blur is used. The pipeline has to be created
+    /// with `VK_PIPELINE_CREATE_RAY_TRACING_ALLOW_MOTION_BIT_NV`.
+    pub uses_ray_tracing_motion_blur: bool,
+    /// Opacity micromaps are queried. The pipeline has to be created with
+    /// `VK_PIPELINE_CREATE_RAY_TRACING_OPACITY_MICROMAP_BIT_EXT`.
+    pub uses_opacity_micromap: bool,
+    /// Ray queries are used so acceleration structures must be bound even if
+    /// the pipeline is not a ray tracing pipeline.
+    pub uses_ray_query: bool,
+    /// A pre-rasterization stage writes the primitive shading rate. The
+    /// `primitiveFragmentShadingRate` feature must be enabled.
+    pub uses_primitive_shading_rate: bool,
+    /// The fragment stage reads the shading rate of the fragment, which is
+    /// only meaningful if fragment shading rates are set for the pipeline.
+    pub reads_fragment_shading_rate: bool,
+    /// Fragment shader interlock used by the fragment stage. The feature
+    /// returned by [`FragmentInterlock::vk_feature`] must be enabled.
+    pub fragment_interlock: Option<FragmentInterlock>,
+    /// The fragment stage reads the fragment size of a fragment density map.
+    /// Dynamic rendering pipelines have to be created with
+    /// `VK_PIPELINE_CREATE_RENDERING_FRAGMENT_DENSITY_MAP_ATTACHMENT_BIT_EXT`.
+    pub uses_fragment_density_map: bool,
+    /// The view index is read, so the pipeline is used in a multiview render
+    /// pass.
+    pub uses_multiview: bool,
+    /// There are runtime-sized or very large descriptor arrays, which are
+    /// cheaper to update in descriptor buffers with
+    /// `VK_PIPELINE_CREATE_DESCRIPTOR_BUFFER_BIT_EXT`.
+    pub benefits_from_descriptor_buffer: bool,
+}
+impl PipelineHints {
+    /// Derive hints from capabilities `caps` declared by the shader modules,
+    /// e.g., from [`ModuleInfo::caps`], and the entry points of all pipeline
+    /// stages.
+    ///
+    /// [`ModuleInfo::caps`]: crate::binary::ModuleInfo::caps
+    pub fn new<'a, I: IntoIterator<Item = &'a EntryPoint>>(
+        caps: &[spirv::Capability],
+        entry_points: I,
+    ) -> PipelineHints {
+        use spirv::Capability as Cap;
+        let mut out = PipelineHints {
+            uses_primitive_culling: caps.contains(&Cap::RayTraversalPrimitiveCullingKHR),
+            uses_ray_tracing_motion_blur: caps.contains(&Cap::RayTracingMotionBlurNV),
+            uses_opacity_micromap: caps.contains(&Cap::RayTracingOpacityMicromapEXT),
+            uses_ray_query: caps.contains(&Cap::RayQueryKHR),
+            uses_fragment_density_map: caps.contains(&Cap::FragmentDensityEXT),
+            uses_multiview: caps.contains(&Cap::MultiView),
+            ..Default::default()
+        };
+        for entry_point in entry_points {
+            // The fragment stage reads the shading rate rather than writes.
+            let is_pre_rasterization = matches!(
+                entry_point.exec_model,
+                ExecutionModel::Vertex
+                    | ExecutionModel::TessellationControl
+                    | ExecutionModel::TessellationEvaluation
+                    | ExecutionModel::Geometry
+                    | ExecutionModel::MeshNV
+                    | ExecutionModel::MeshEXT
+            );
+            out.uses_primitive_shading_rate |= is_pre_rasterization
+                && entry_point
+                    .output_builtins
+                    .contains(&spirv::BuiltIn::PrimitiveShadingRateKHR);
+            if entry_point.exec_model == ExecutionModel::Fragment {
+                out.reads_fragment_shading_rate |= entry_point
+                    .input_builtins
+                    .contains(&spirv::BuiltIn::ShadingRateKHR);
+                out.fragment_interlock = out
+                    .fragment_interlock
+                    .or_else(|| entry_point.fragment_interlock());
+            }
+            for var in entry_point.vars.iter() {
+                if let Variable::Descriptor { nbind, .. } = var {
+                    // Runtime-sized arrays have zero bindings.
+                    if *nbind == 0 || *nbind >= LARGE_DESC_ARRAY_NBIND {
+                        out.benefits_from_descriptor_buffer = true;
+                    }
+                }
+            }
+        }
+        out
+    }
+}
+
+/// Parts of a graphics pipeline that can be created separately as pipeline
+/// libraries with `VK_EXT_graphics_pipeline_library`, matching
+/// `VkGraphicsPipelineLibraryFlagBitsEXT`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PipelineLibraryPart {
+    /// Vertex input and input assembly states.
+    VertexInputInterface,
+    /// Pre-rasterization shader stages, with the viewport, rasterization and
+    /// tessellation states.
+    PreRasterizationShaders,
+    /// The fragment shader stage, with the depth stencil state.
+    FragmentShader,
+    /// Color blend and multisample states, and the attachment formats.
+    FragmentOutputInterface,
+}
+impl PipelineLibraryPart {
+    /// `VkGraphicsPipelineLibraryFlagBitsEXT` of the part.
+    pub fn vk_flag(&self) -> u32 {
+        match self {
+            PipelineLibraryPart::VertexInputInterface => 0x1,
+            PipelineLibraryPart::PreRasterizationShaders => 0x2,
+            PipelineLibraryPart::FragmentShader => 0x4,
+            PipelineLibraryPart::FragmentOutputInterface => 0x8,
+        }
+    }
+    /// The part shader stage `exec_model` is created in, or `None` if it's
+    /// not a graphics stage.
+    pub fn of_stage(exec_model: ExecutionModel) -> Option<PipelineLibraryPart> {
+        match exec_model {
+            ExecutionModel::Vertex
+            | ExecutionModel::TessellationControl
+            | ExecutionModel::TessellationEvaluation
+            | ExecutionModel::Geometry
+            | ExecutionModel::TaskNV
+            | ExecutionModel::MeshNV
+            | ExecutionModel::TaskEXT
+            | ExecutionModel::MeshEXT => Some(PipelineLibraryPart::PreRasterizationShaders),
+            ExecutionModel::Fragment => Some(PipelineLibraryPart::FragmentShader),
+            _ => None,
+        }
+    }
+}
+
+/// Reflected state of a graphics pipeline partitioned into the parts created
+/// as separate pipeline libraries.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct PipelineLibraryPartition {
+    /// Shader stages created in each part. Only
+    /// [`PipelineLibraryPart::PreRasterizationShaders`] and
+    /// [`PipelineLibraryPart::FragmentShader`] contain stages.
+    pub stages: BTreeMap<PipelineLibraryPart, Vec<ExecutionModel>>,
+    /// Vertex attributes read by the vertex stage, which have to be described
+    /// by the [`PipelineLibraryPart::VertexInputInterface`] library.
+    pub vertex_inputs: Vec<Variable>,
+    /// Color attachments written by the fragment stage, which have to be
+    /// described by the [`PipelineLibraryPart::FragmentOutputInterface`]
+    /// library.
+    pub fragment_outputs: Vec<Variable>,
+    /// Parts accessing each descriptor binding point. The set layouts
+    /// provided to these parts must be compatible.
+    pub descs: BTreeMap<DescriptorBinding, BTreeSet<PipelineLibraryPart>>,
+    /// Parts accessing push constants.
+    pub push_const_parts: BTreeSet<PipelineLibraryPart>,
+    /// The fragment stage runs per sample, so the multisample states provided
+    /// to the [`PipelineLibraryPart::FragmentShader`] and the
+    /// [`PipelineLibraryPart::FragmentOutputInterface`] libraries must be
+    /// identical.
+    pub requires_matching_multisample_state: bool,
+}
+impl PipelineLibraryPartition {
+    /// Partition `pipeline` merged from `entry_points`. Non-graphics stages
+    /// are ignored.
+    pub fn new<'a, I: IntoIterator<Item = &'a EntryPoint>>(
+        pipeline: &Pipeline,
+        entry_points: I,
+    ) -> PipelineLibraryPartition {
+        let mut out = PipelineLibraryPartition::default();
+        for entry_point in entry_points {
+            let part = match PipelineLibraryPart::of_stage(entry_point.exec_model) {
+                Some(x) => x,
+                None => continue,
+            };
+            let stages = out.stages.entry(part).or_default();
+            if !stages.contains(&entry_point.exec_model) {
+                stages.push(entry_point.exec_model);
+                stages.sort();
+            }
+            for var in entry_point.vars.iter() {
+                match (entry_point.exec_model, var) {
+                    (ExecutionModel::Vertex, Variable::Input { .. }) => {
+                        out.vertex_inputs.push(var.clone())
+                    }
+                    (ExecutionModel::Fragment, Variable::Output { .. }) => {
+                        out.fragment_outputs.push(var.clone())
+                    }
+                    _ => {}
+                }
+            }
+            if entry_point.exec_model == ExecutionModel::Fragment {
+                out.requires_matching_multisample_state |=
+                    entry_point.input_builtins.iter().any(|x| {
+                        matches!(x, spirv::BuiltIn::SampleId | spirv::BuiltIn::SamplePosition)
+                    });
+            }
+        }
+        for (desc_bind, desc) in pipeline.descs.iter() {
+            let parts = desc
+                .stages
+                .iter()
+                .filter_map(|x| PipelineLibraryPart::of_stage(*x))
+                .collect::<BTreeSet<_>>();
+            if !parts.is_empty() {
+                out.descs.insert(*desc_bind, parts);
+            }
+        }
+        out.push_const_parts = pipeline
+            .push_const_ranges
+            .iter()
+            .flat_map(|x| x.stages.iter())
+            .filter_map(|x| PipelineLibraryPart::of_stage(*x))
+            .collect();
+        out
+    }
+
+    /// `VkGraphicsPipelineLibraryFlagsEXT` of the parts accessing descriptor
+    /// binding point `desc_bind`.
+    pub fn desc_vk_flags(&self, desc_bind: &DescriptorBinding) -> u32 {
+        self.descs
+            .get(desc_bind)
+            .into_iter()
+            .flatten()
+            .fold(0, |seed, x| seed | x.vk_flag())
+    }
+}
+
+/// Add `stage` to sorted stage list `stages` if it's not listed yet.
+fn add_stage(stages: &mut Vec<ExecutionModel>, stage: ExecutionModel) {
+    if !stages.contains(&stage) {
+        stages.push(stage);
+        stages.sort();
+    }
+}
+
+/// Byte range `[offset, end)` of the members of push constant block `ty`,
+/// aligned to 4 bytes as required by `VkPushConstantRange`.
+fn push_const_range(stage: ExecutionModel, ty: &Type) -> Result<(u32, u32)> {
+    let struct_ty = ty
+        .as_struct()
+        .ok_or_else(|| anyhow!("push constant block of {:?} stage is not a struct", stage))?;
+    let mut range: Option<(usize, usize)> = None;
+    for member in struct_ty.members.iter() {
+        let offset = member.offset.ok_or_else(|| {
+            anyhow!(
+                "push constant block of {:?} stage has a member without offset",
+                stage
+            )
+        })?;
+        let nbyte = member.ty.layout_nbyte().ok_or_else(|| {
+            anyhow!(
+                "push constant block of {:?} stage has an unsized member",
+                stage
+            )
+        })?;
+        let end = offset + nbyte;
+        range = Some(match range {
+            Some((x, y)) => (x.min(offset), y.max(end)),
+            None => (offset, end),
+        });
+    }
+    let (offset, end) = range.unwrap_or_default();
+    Ok(((offset & !3) as u32, ((end + 3) & !3) as u32))
+}
+
+/// Check if a descriptor of type `ty` can be sampled through a sampler Y'CbCr
+/// conversion. The conversion is bound as an immutable sampler so the image
+/// has to be sampled with a combined image sampler.
+fn check_ycbcr_sampling(desc_bind: DescriptorBinding, ty: &Type) -> Result<()> {
+    let img_ty = match ty {
+        Type::CombinedImageSampler(x) => &x.sampled_image_ty,
+        Type::SampledImage(_) => {
+            return Err(anyhow!(
+                "image at {} requires a sampler ycbcr conversion but it's not \
+                 sampled with a combined image sampler",
+                desc_bind
+            ))
+        }
+        _ => {
+            return Err(anyhow!(
+                "descriptor at {} requires a sampler ycbcr conversion but it's \
+                 not a sampled image",
+                desc_bind
+            ))
+        }
+    };
+    let is_float = matches!(img_ty.scalar_ty, ScalarType::Float { .. });
+    if img_ty.dim != Dim::Dim2D || img_ty.is_multisampled || !is_float {
+        return Err(anyhow!(
+            "image at {} requires a sampler ycbcr conversion but it's not a \
+             single-sampled 2d float image",
+            desc_bind
+        ));
+    }
+    Ok(())
+}
+
+/// Normalize the naming convention and check if two names are more different
+/// than alike.
+fn are_names_distinct(a: &str, b: &str) -> bool {
+    let normalize = |x: &str| {
+        x.chars()
+            .filter(|c| *c != '_')
+            .flat_map(char::to_lowercase)
+            .collect::<Vec<_>>()
+    };
+    let a = normalize(a);
+    let b = normalize(b);
+    let max_len = a.len().max(b.len());
+    edit_distance(&a, &b) * 2 > max_len
+}
+/// Levenshtein distance between two strings.
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut prev_row = (0..=b.len()).collect::<Vec<_>>();
+    let mut row = vec![0; b.len() + 1];
+    for (i, ca) in a.iter().enumerate() {
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let subst_cost = if ca == cb { 0 } else { 1 };
+            row[j + 1] = (prev_row[j] + subst_cost)
+                .min(prev_row[j + 1] + 1)
+                .min(row[j] + 1);
+        }
+        std::mem::swap(&mut prev_row, &mut row);
+    }
+    prev_row[b.len()]
+}
@@ -0,0 +1,215 @@
+//! Simulation of descriptor set allocations from descriptor pools, to tune
+//! pool sizes with the descriptor sets reflected from real shaders.
+//!
+//! [`PoolSimulator`] allocates descriptor sets laid out as in
+//! [`LayoutSet`]s the way a typical growing allocator does: sets are
+//! allocated from the latest pool, and a new pool is created when the latest
+//! one can't hold the next set. Capacity left in the pools given up on is
+//! fragmentation, and sets that don't fit even in an empty pool overflow.
+//! Descriptor sets are never freed in the simulation.
+use std::collections::BTreeMap;
+
+use crate::layout_desc::LayoutSet;
+
+/// Capacity of a descriptor pool, matching `VkDescriptorPoolCreateInfo`.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct PoolSizes {
+    /// Maximum number of descriptor sets allocated from a pool.
+    pub max_sets: u32,
+    /// Number of descriptors of each `VkDescriptorType` in a pool.
+    pub desc_counts: BTreeMap<u32, u32>,
+}
+impl PoolSizes {
+    pub fn new(max_sets: u32) -> Self {
+        PoolSizes {
+            max_sets,
+            desc_counts: BTreeMap::new(),
+        }
+    }
+    /// Add `count` descriptors of `VkDescriptorType` `vk_desc_ty` to each
+    /// pool.
+    pub fn desc_count(&mut self, vk_desc_ty: u32, count: u32) -> &mut Self {
+        *self.desc_counts.entry(vk_desc_ty).or_default() += count;
+        self
+    }
+}
+
+/// A descriptor set that can't be allocated even from an empty pool.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PoolOverflow {
+    /// Set number of the descriptor set.
+    pub set: u32,
+    /// `VkDescriptorType` of the descriptors exceeding the pool capacity, or
+    /// `None` if pools can't hold any set at all.
+    pub vk_desc_ty: Option<u32>,
+    /// Number of descriptors of the type required by the set.
+    pub required: u32,
+    /// Number of descriptors of the type in a pool.
+    pub capacity: u32,
+}
+
+/// Outcome of the allocations simulated by a [`PoolSimulator`].
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct PoolReport {
+    /// Number of pools created.
+    pub npool: u32,
+    /// Number of descriptor sets allocated.
+    pub nset: u32,
+    /// Descriptor sets not allocated because they overflow the pool
+    /// capacity.
+    pub overflows: Vec<PoolOverflow>,
+    /// Descriptors of each `VkDescriptorType` left unused in the pools given
+    /// up on. Capacity left in the latest pool is not counted.
+    pub wasted_desc_counts: BTreeMap<u32, u32>,
+    /// Descriptor sets left unallocated in the pools given up on.
+    pub wasted_nset: u32,
+    /// Total number of descriptors in the pools given up on.
+    pub abandoned_desc_count: u32,
+}
+impl PoolReport {
+    /// Ratio of descriptors wasted in the pools given up on, from 0 to 1.
+    pub fn fragmentation(&self) -> f32 {
+        if self.abandoned_desc_count == 0 {
+            return 0.0;
+        }
+        let wasted: u32 = self.wasted_desc_counts.values().sum();
+        wasted as f32 / self.abandoned_desc_count as f32
+    }
+}
+
+/// Descriptor pool allocation simulator.
+#[derive(Debug, Clone)]
+pub struct PoolSimulator {
+    pool_sizes: PoolSizes,
+    /// Number of descriptors allocated for runtime descriptor arrays.
+    variable_count: u32,
+    /// Remaining capacity of the latest pool.
+    cur_pool: Option<PoolSizes>,
+    report: PoolReport,
+}
+impl PoolSimulator {
+    pub fn new(pool_sizes: PoolSizes) -> Self {
+        PoolSimulator {
+            pool_sizes,
+            variable_count: 0,
+            cur_pool: None,
+            report: PoolReport::default(),
+        }
+    }
+    /// Allocate `count` descriptors for runtime descriptor arrays, as in
+    /// `VkDescriptorSetVariableDescriptorCountAllocateInfo`. No descriptor
+    /// is allocated for them by default.
+    pub fn variable_count(&mut self, count: u32) -> &mut Self {
+        self.variable_count = count;
+        self
+    }
+
+    /// Number of descriptors of each `VkDescriptorType` required by
+    /// descriptor set `set`.
+    pub fn set_desc_counts(&self, set: &LayoutSet) -> BTreeMap<u32, u32> {
+        let mut out = BTreeMap::new();
+        for binding in set.bindings.iter() {
+            let count = match binding.count {
+                0 => self.variable_count,
+                x => x,
+            };
+            if count > 0 {
+                *out.entry(binding.vk_desc_ty()).or_default() += count;
+            }
+        }
+        out
+    }
+
+    /// Allocate descriptor set `set`. Returns the index of the pool it's
+    /// allocated from, or `None` if it overflows the pool capacity.
+    pub fn alloc(&mut self, set: &LayoutSet) -> Option<u32> {
+        let desc_counts = self.set_desc_counts(set);
+        if let Some(overflow) = self.find_overflow(set.set, &desc_counts) {
+            if !self.report.overflows.contains(&overflow) {
+                self.report.overflows.push(overflow);
+            }
+            return None;
+        }
+        let fits = match &self.cur_pool {
+            Some(pool) => fits_in(pool, &desc_counts),
+            None => false,
+        };
+        if !fits {
+            if let Some(pool) = self.cur_pool.take() {
+                self.abandon(pool);
+            }
+            self.cur_pool = Some(self.pool_sizes.clone());
+            self.report.npool += 1;
+        }
+        let pool = self.cur_pool.as_mut().unwrap();
+        pool.max_sets -= 1;
+        for (vk_desc_ty, count) in desc_counts {
+            *pool.desc_counts.get_mut(&vk_desc_ty).unwrap() -= count;
+        }
+        self.report.nset += 1;
+        Some(self.report.npool - 1)
+    }
+    /// Allocate each of descriptor sets `sets` `n` times, interleaved as if
+    /// `n` objects each allocate all the sets.
+    pub fn alloc_n(&mut self, sets: &[LayoutSet], n: u32) -> &mut Self {
+        for _ in 0..n {
+            for set in sets {
+                self.alloc(set);
+            }
+        }
+        self
+    }
+    /// Report the allocations simulated so far.
+    pub fn report(&self) -> &PoolReport {
+        &self.report
+    }
+
+    fn find_overflow(&self, set: u32, desc_counts: &BTreeMap<u32, u32>) -> Option<PoolOverflow> {
+        if self.pool_sizes.max_sets == 0 {
+            return Some(PoolOverflow {
+                set,
+                vk_desc_ty: None,
+                required: 1,
+                capacity: 0,
+            });
+        }
+        desc_counts.iter().find_map(|(vk_desc_ty, count)| {
+            let capacity = self
+                .pool_sizes
+                .desc_counts
+                .get(vk_desc_ty)
+                .copied()
+                .unwrap_or_default();
+            if *count > capacity {
+                Some(PoolOverflow {
+                    set,
+                    vk_desc_ty: Some(*vk_desc_ty),
+                    required: *count,
+                    capacity,
+                })
+            } else {
+                None
+            }
+        })
+    }
+    fn abandon(&mut self, pool: PoolSizes) {
+        self.report.wasted_nset += pool.max_sets;
+        for (vk_desc_ty, count) in pool.desc_counts {
+            if count > 0 {
+                *self
+                    .report
+                    .wasted_desc_counts
+                    .entry(vk_desc_ty)
+                    .or_default() += count;
+            }
+        }
+        self.report.abandoned_desc_count += self.pool_sizes.desc_counts.values().sum::<u32>();
+    }
+}
+
+fn fits_in(pool: &PoolSizes, desc_counts: &BTreeMap<u32, u32>) -> bool {
+    pool.max_sets > 0
+        && desc_counts.iter().all(
+            |(vk_desc_ty, count)| matches!(pool.desc_counts.get(vk_desc_ty), Some(x) if x >= count),
+        )
+}
@@ -1,33 +1,60 @@
 //! Reflection procedures and types.
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::convert::TryFrom;
+use std::sync::Arc;
 
 use fnv::{FnvHashMap as HashMap, FnvHashSet as HashSet};
 use spq_core::parse::Instrs;
 
 use crate::{
     annotation::{DecorationRegistry, NameRegistry},
+    arena::{Arena, ArenaMap, ArenaSet},
+    binary::{ModuleInfo, SpirvVersion},
     constant::{Constant, ConstantValue},
-    entry_point::{EntryPoint, ExecutionModel},
+    diagnostic::{DegenerateSampledImage, Diagnostic, ImageArrangement, VersionConvention},
+    entry_point::{
+        has_derivative_groups, DescriptorInfo, EntryPoint, ExecutionModel, InterfaceVariable,
+        LiteralSampler, PrivateVariable, RawExecutionMode, SharedVariable, TexelBufferInfo,
+        UsedElements,
+    },
     error::{anyhow, Error, Result},
     evaluator::Evaluator,
-    func::{ExecutionMode, Function, FunctionRegistry},
+    func::{ExecutionMode, FunctionRegistry},
+    gl::{make_gl_var, GlVariable},
     inspect::Inspector,
     instr::*,
+    layout::{BlockLayout, TypeExt},
+    layout_desc::vk_desc_ty,
     parse::Instr,
-    reflect_cfg::ReflectConfig,
+    reflect_cfg::{
+        DecorationCollisionPolicy, MissingBindingPolicy, NameCollisionPolicy, ReflectConfig,
+    },
+    reflection::{CooperativeMatrixType, Reflection},
     spirv::{self, Op},
     ty::{
         AccelStructType, AccessType, ArrayType, CombinedImageSamplerType, DescriptorType,
-        DeviceAddressType, ImageType, MatrixAxisOrder, MatrixType, PointerType, RayQueryType,
-        SampledImageType, SamplerType, ScalarType, StorageClass, StorageImageType, StructMember,
-        StructType, SubpassDataType, Type, TypeRegistry, VectorType,
+        DeviceAddressType, ImageFormat, ImageType, MatrixAxisOrder, MatrixType, PointerType,
+        RayQueryType, SampledImageType, SamplerType, ScalarType, StorageClass, StorageImageType,
+        StructMember, StructType, SubpassDataType, Type, TypeRegistry, VectorType,
     },
-    var::{Variable, VariableAlloc, VariableRegistry},
+    ty_arena::TypeArena,
+    var::{
+        DescriptorBinding, InterfaceLocation, SpecId, Variable, VariableAlloc, VariableRegistry,
+    },
+};
+#[cfg(feature = "access-analysis")]
+use crate::{
+    arena::ArenaVec,
+    func::Function,
+    grammar::{decode_operands, visit_operands, Operand, OperandKind},
 };
+#[cfg(feature = "spec-folding")]
+use num_traits::FromPrimitive;
+#[cfg(feature = "spec-folding")]
+use ordered_float::OrderedFloat;
 
-type ConstantId = u32;
 type FunctionId = u32;
+type InstrId = u32;
 type TypeId = u32;
 type VariableId = u32;
 
@@ -36,16 +63,9 @@ type VariableId = u32;
 struct EntryPointDeclaration<'a> {
     name: &'a str,
     exec_model: ExecutionModel,
-    exec_modes: Vec<ExecutionModeDeclaration>,
-}
-enum ExecutionModeOperand {
-    Literal(u32),
-    Id(ConstantId),
-}
-struct ExecutionModeDeclaration {
-    func_id: FunctionId,
-    exec_mode: spirv::ExecutionMode,
-    operands: Vec<ExecutionModeOperand>,
+    exec_modes: Vec<RawExecutionMode>,
+    /// Global variables listed in the interface of the entry point.
+    interface_ids: &'a [u32],
 }
 
 // The actual reflection to take place.
@@ -78,6 +98,7 @@ fn is_ty_op(op: Op) -> bool {
         Op::TypeRayQueryKHR => true,
         Op::TypeAccelerationStructureKHR => true,
         Op::TypeCooperativeMatrixNV => true,
+        Op::TypeCooperativeMatrixKHR => true,
         Op::TypeVmeImageINTEL => true,
         Op::TypeAvcImePayloadINTEL => true,
         Op::TypeAvcRefPayloadINTEL => true,
@@ -94,6 +115,25 @@ fn is_ty_op(op: Op) -> bool {
         _ => false,
     }
 }
+/// Decorations of stage inputs and outputs collected in
+/// [`EntryPoint::io_decos`].
+const IO_DECOS: &[spirv::Decoration] = &[
+    spirv::Decoration::Offset,
+    spirv::Decoration::XfbBuffer,
+    spirv::Decoration::XfbStride,
+    spirv::Decoration::Stream,
+    spirv::Decoration::Index,
+    spirv::Decoration::Flat,
+    spirv::Decoration::NoPerspective,
+    spirv::Decoration::Centroid,
+    spirv::Decoration::Sample,
+    spirv::Decoration::Patch,
+    spirv::Decoration::Invariant,
+    spirv::Decoration::PerPrimitiveNV,
+    spirv::Decoration::PerViewNV,
+    spirv::Decoration::PerTaskNV,
+    spirv::Decoration::PerVertexKHR,
+];
 fn is_const_op(op: Op) -> bool {
     match op {
         Op::ConstantTrue => true,
@@ -111,6 +151,7 @@ fn is_const_op(op: Op) -> bool {
         _ => false,
     }
 }
+#[cfg(feature = "access-analysis")]
 fn is_atomic_load_op(op: Op) -> bool {
     match op {
         Op::AtomicLoad => true,
@@ -131,6 +172,7 @@ fn is_atomic_load_op(op: Op) -> bool {
         _ => false,
     }
 }
+#[cfg(feature = "access-analysis")]
 fn is_atomic_store_op(op: Op) -> bool {
     match op {
         Op::AtomicStore => true,
@@ -141,164 +183,545 @@ fn is_atomic_store_op(op: Op) -> bool {
 /// SPIR-V reflection intermediate.
 pub struct ReflectIntermediate<'a> {
     pub cfg: &'a ReflectConfig,
+    /// Allocator of the hash tables below.
+    arena: Arena<'a>,
     pub name_reg: NameRegistry<'a>,
     pub deco_reg: DecorationRegistry<'a>,
     pub ty_reg: TypeRegistry,
     pub var_reg: VariableRegistry,
     pub func_reg: FunctionRegistry,
     pub interp: Evaluator,
-    entry_point_declrs: HashMap<FunctionId, EntryPointDeclaration<'a>>,
+    /// Types interned as they are registered in `ty_reg`, see
+    /// [`TypeArena`].
+    ty_arena: TypeArena,
+    /// Interned types of type IDs.
+    interned_tys: ArenaMap<'a, TypeId, Arc<Type>>,
+    /// Cooperative matrix types, which are not registered in `ty_reg`.
+    coop_mat_tys: ArenaMap<'a, TypeId, CooperativeMatrixType>,
+    /// ID bound declared in the module header. All IDs referenced by the
+    /// module are checked to be below it before parsing.
+    pub id_bound: u32,
+    /// SPIR-V version declared in the module header.
+    pub version: SpirvVersion,
+    /// Extensions declared by `OpExtension`.
+    exts: ArenaSet<'a, &'a str>,
+    entry_point_declrs: ArenaMap<'a, FunctionId, EntryPointDeclaration<'a>>,
+    /// Components of input variables read by each function, see
+    /// [`FunctionInspector`].
+    input_reads: ArenaMap<'a, FunctionId, ArenaMap<'a, VariableId, u32>>,
+    /// Byte ranges of push constant variables read by each function, see
+    /// [`FunctionInspector`].
+    push_const_reads: ArenaMap<'a, FunctionId, ArenaMap<'a, VariableId, (usize, usize)>>,
+    /// Variables and parameters written by each function, see
+    /// [`FunctionInspector`].
+    written_vars: ArenaMap<'a, FunctionId, ArenaSet<'a, VariableId>>,
+    /// Functions writing through physical storage buffer pointers, see
+    /// [`FunctionInspector`].
+    device_write_func_ids: ArenaSet<'a, FunctionId>,
+    /// Functions writing the `PointSize` built-in, see
+    /// [`FunctionInspector`].
+    point_size_write_func_ids: ArenaSet<'a, FunctionId>,
+    is_fn_scanned: bool,
+    /// Parameters and call sites of each function, see [`FunctionLinkage`].
+    func_linkages: ArenaMap<'a, FunctionId, FunctionLinkage>,
+    /// Sampled image types, arrays and pointers of them, and variables of
+    /// them that wrap an image that can't be sampled.
+    degenerate_sampled_imgs: ArenaMap<'a, u32, DegenerateSampledImage>,
+    /// Texel formats declared on buffer images, sampled images, arrays and
+    /// pointers of them, and variables of them.
+    texel_buffer_fmts: ArenaMap<'a, u32, ImageFormat>,
+    /// Arrays sized by specialization constants in array and struct types,
+    /// pointers to them, and variables of them.
+    spec_sized_arrays: ArenaMap<'a, u32, Vec<SpecSizedArray>>,
+    /// Forward-declared pointers nested in types and variables.
+    fwd_ptr_refs: ArenaMap<'a, u32, Vec<ForwardPointerRef>>,
+    /// Locations of the first members of struct types, arrays and pointers
+    /// of them, and variables of them.
+    io_block_locations: ArenaMap<'a, u32, InterfaceLocation>,
+    /// Decorations in `IO_DECOS` of the members of struct types, arrays and
+    /// pointers of them, and variables of them, indexed by member indices.
+    io_member_decos: ArenaMap<'a, u32, BTreeMap<u32, BTreeMap<spirv::Decoration, Vec<u32>>>>,
+    /// Built-ins of the members of struct types with built-in members like
+    /// `gl_PerVertex`, arrays and pointers of them, and variables of them.
+    builtin_blocks: ArenaMap<'a, u32, Vec<spirv::BuiltIn>>,
+    /// Indices of the `PointSize` members of built-in blocks, arrays and
+    /// pointers of them, and variables of them.
+    point_size_members: ArenaMap<'a, u32, u32>,
+    /// Struct types decorated `Block`, pointers to them and variables of
+    /// them.
+    blocks: ArenaSet<'a, u32>,
+    /// Initializers of variables.
+    var_inits: ArenaMap<'a, VariableId, InstrId>,
+    /// Variables accessed through pointers or indices decorated
+    /// `NonUniform` in each function, see [`FunctionInspector`].
+    nonuniform_var_ids: ArenaMap<'a, FunctionId, ArenaSet<'a, VariableId>>,
+    /// Image variables accessed with the `MinLod` image operand in each
+    /// function, see [`FunctionInspector`].
+    min_lod_var_ids: ArenaMap<'a, FunctionId, ArenaSet<'a, VariableId>>,
+    /// Image variables accessed by `OpImageSparse*` instructions in each
+    /// function, see [`FunctionInspector`].
+    sparse_var_ids: ArenaMap<'a, FunctionId, ArenaSet<'a, VariableId>>,
+    /// Acceleration structure variables traced by `OpTraceRayKHR` or
+    /// `OpTraceNV` in each function, see [`FunctionInspector`].
+    trace_ray_var_ids: ArenaMap<'a, FunctionId, ArenaSet<'a, VariableId>>,
+    /// Acceleration structure variables queried by
+    /// `OpRayQueryInitializeKHR` in each function, see
+    /// [`FunctionInspector`].
+    ray_query_var_ids: ArenaMap<'a, FunctionId, ArenaSet<'a, VariableId>>,
+    /// Elements of descriptor arrays referenced in each function, see
+    /// [`DescriptorElements`].
+    desc_elems: ArenaMap<'a, FunctionId, DescriptorElements<'a>>,
+    /// Functions using derivatives, explicitly or implicitly by sampling
+    /// images with implicit LODs, see [`FunctionInspector`].
+    derivative_func_ids: ArenaSet<'a, FunctionId>,
+    /// Specialization IDs each specialization constant or specialization
+    /// constant operation depends on, whether or not its value is folded.
+    spec_const_deps: ArenaMap<'a, InstrId, BTreeSet<SpecId>>,
+    /// Literal samplers declared by `OpConstantSampler`.
+    literal_samplers: Vec<LiteralSampler>,
+    /// Diagnostics of module-level declarations reported to all entry
+    /// points, e.g., conflicting decorations resolved by the decoration
+    /// collision policy.
+    module_diags: Vec<Diagnostic>,
+    /// Constituents of the constant decorated with `BuiltIn WorkgroupSize`.
+    #[cfg(feature = "exec-modes")]
+    workgroup_size: Option<Vec<Constant>>,
 }
 impl<'a> ReflectIntermediate<'a> {
     pub fn new(cfg: &'a ReflectConfig) -> Result<Self> {
+        Self::new_in(cfg, Arena::default())
+    }
+    pub(crate) fn new_in(cfg: &'a ReflectConfig, arena: Arena<'a>) -> Result<Self> {
         let out = ReflectIntermediate {
             cfg,
+            arena,
             name_reg: Default::default(),
             deco_reg: Default::default(),
             ty_reg: Default::default(),
             var_reg: Default::default(),
             func_reg: Default::default(),
             interp: Default::default(),
-            entry_point_declrs: Default::default(),
+            ty_arena: Default::default(),
+            interned_tys: arena.map(),
+            coop_mat_tys: arena.map(),
+            id_bound: 0,
+            version: Default::default(),
+            exts: arena.set(),
+            entry_point_declrs: arena.map(),
+            input_reads: arena.map(),
+            push_const_reads: arena.map(),
+            written_vars: arena.map(),
+            device_write_func_ids: arena.set(),
+            point_size_write_func_ids: arena.set(),
+            is_fn_scanned: false,
+            func_linkages: arena.map(),
+            degenerate_sampled_imgs: arena.map(),
+            texel_buffer_fmts: arena.map(),
+            spec_sized_arrays: arena.map(),
+            fwd_ptr_refs: arena.map(),
+            io_block_locations: arena.map(),
+            io_member_decos: arena.map(),
+            builtin_blocks: arena.map(),
+            point_size_members: arena.map(),
+            blocks: arena.set(),
+            var_inits: arena.map(),
+            nonuniform_var_ids: arena.map(),
+            min_lod_var_ids: arena.map(),
+            sparse_var_ids: arena.map(),
+            trace_ray_var_ids: arena.map(),
+            ray_query_var_ids: arena.map(),
+            desc_elems: arena.map(),
+            derivative_func_ids: arena.set(),
+            spec_const_deps: arena.map(),
+            literal_samplers: Default::default(),
+            module_diags: Default::default(),
+            #[cfg(feature = "exec-modes")]
+            workgroup_size: None,
         };
         Ok(out)
     }
 }
+/// Arrangements of an image of `dim` not allowed for the dimensionality.
+/// Rectangle and buffer images from OpenGL- and OpenCL-derived modules are
+/// accepted but they can't be arrayed.
+fn invalid_image_arrangements(
+    dim: spirv::Dim,
+    is_array: bool,
+    is_multisampled: bool,
+) -> Vec<ImageArrangement> {
+    use spirv::Dim;
+    let can_be_array = match dim {
+        Dim::Dim1D | Dim::Dim2D | Dim::DimCube => true,
+        Dim::Dim3D
+        | Dim::DimRect
+        | Dim::DimBuffer
+        | Dim::DimSubpassData
+        | Dim::DimTileImageDataEXT => false,
+    };
+    let can_be_multisampled = matches!(dim, Dim::Dim2D | Dim::DimSubpassData);
+    let mut out = Vec::new();
+    if is_array && !can_be_array {
+        out.push(ImageArrangement::Arrayed);
+    }
+    if is_multisampled && !can_be_multisampled {
+        out.push(ImageArrangement::Multisampled);
+    }
+    out
+}
+/// Array sized by a specialization constant. `member` is `None` until the
+/// array is found to be a member of a struct.
+#[derive(Clone)]
+struct SpecSizedArray {
+    member: Option<(Option<String>, Option<String>)>,
+    spec_id: SpecId,
+}
+
+/// Step from a type to one of its inner types.
+#[derive(Clone, Copy)]
+enum TypeStep {
+    Member(usize),
+    Element,
+    Pointee,
+}
+/// Type forward-declared by `OpTypeForwardPointer` nested in a type, at
+/// `path` from the type. The nested type is a `Type::DeviceAddress`
+/// placeholder until it's resolved after all types are declared.
+#[derive(Clone)]
+struct ForwardPointerRef {
+    path: Vec<TypeStep>,
+    ptr_ty_id: TypeId,
+}
+/// Types with forward-declared pointers resolved, or `None` if being
+/// resolved.
+type FwdPtrMemo = HashMap<TypeId, Option<Type>>;
+fn nested_ty_mut<'t>(ty: &'t mut Type, path: &[TypeStep]) -> Option<&'t mut Type> {
+    let mut ty = ty;
+    for step in path {
+        ty = match (step, ty) {
+            (TypeStep::Member(i), Type::Struct(x)) => &mut x.members.get_mut(*i)?.ty,
+            (TypeStep::Element, Type::Array(x)) => &mut *x.element_ty,
+            (TypeStep::Pointee, Type::DevicePointer(x)) => &mut *x.pointee_ty,
+            _ => return None,
+        };
+    }
+    Some(ty)
+}
+
 fn broken_nested_ty(id: TypeId) -> Error {
     Error::msg(format!("broken nested type: {}", id))
 }
 impl<'a> ReflectIntermediate<'a> {
+    /// Register type `ty_id` and intern it, so equal types declared more
+    /// than once share a node.
+    fn set_ty(&mut self, ty_id: TypeId, ty: Type) -> Result<()> {
+        self.interned_tys.insert(ty_id, self.ty_arena.intern(&ty));
+        self.ty_reg.set(ty_id, ty)
+    }
+    /// Reflect sampled image `ty_id` as the wrapped `image_ty_id` which can't
+    /// be sampled.
+    fn set_degenerate_sampled_img(
+        &mut self,
+        ty_id: TypeId,
+        image_ty_id: TypeId,
+        kind: DegenerateSampledImage,
+    ) -> Result<()> {
+        let ty = self.ty_reg.get(image_ty_id)?.clone();
+        self.set_ty(ty_id, ty)?;
+        self.degenerate_sampled_imgs.insert(ty_id, kind);
+        Ok(())
+    }
+    /// Track degenerate sampled images through arrays, pointers and
+    /// variables of them.
+    fn inherit_degenerate_sampled_img(&mut self, id: u32, inner_id: u32) {
+        if let Some(kind) = self.degenerate_sampled_imgs.get(&inner_id).copied() {
+            self.degenerate_sampled_imgs.insert(id, kind);
+        }
+    }
+    /// Track locations of I/O blocks whose members are decorated with
+    /// locations through arrays, pointers and variables of them.
+    fn inherit_io_block_location(&mut self, id: u32, inner_id: u32) {
+        if let Some(location) = self.io_block_locations.get(&inner_id).copied() {
+            self.io_block_locations.insert(id, location);
+        }
+    }
+    /// Track member decorations of I/O blocks, like transform feedback
+    /// offsets, through arrays, pointers and variables of them.
+    fn inherit_io_member_decos(&mut self, id: u32, inner_id: u32) {
+        if let Some(x) = self.io_member_decos.get(&inner_id).cloned() {
+            self.io_member_decos.insert(id, x);
+        }
+    }
+    /// Track built-in blocks through arrays, pointers and variables of them.
+    fn inherit_builtin_block(&mut self, id: u32, inner_id: u32) {
+        if let Some(x) = self.builtin_blocks.get(&inner_id).cloned() {
+            self.builtin_blocks.insert(id, x);
+        }
+        if let Some(x) = self.point_size_members.get(&inner_id).copied() {
+            self.point_size_members.insert(id, x);
+        }
+    }
+    /// Track blocks through pointers and variables of them.
+    fn inherit_block(&mut self, id: u32, inner_id: u32) {
+        if self.blocks.contains(&inner_id) {
+            self.blocks.insert(id);
+        }
+    }
+    /// Track texel formats of buffer images through sampled images, arrays,
+    /// pointers and variables of them.
+    fn inherit_texel_buffer_fmt(&mut self, id: u32, inner_id: u32) {
+        if let Some(fmt) = self.texel_buffer_fmts.get(&inner_id).copied() {
+            self.texel_buffer_fmts.insert(id, fmt);
+        }
+    }
+    /// Track arrays sized by specialization constants through arrays,
+    /// pointers and variables of them.
+    fn inherit_spec_sized_arrays(&mut self, id: u32, inner_id: u32) {
+        if let Some(x) = self.spec_sized_arrays.get(&inner_id).cloned() {
+            self.spec_sized_arrays.entry(id).or_default().extend(x);
+        }
+    }
+    /// Track forward-declared pointers through arrays, structs, pointers and
+    /// variables of them.
+    fn inherit_fwd_ptr_refs(&mut self, id: u32, inner_id: u32, step: Option<TypeStep>) {
+        if let Some(x) = self.fwd_ptr_refs.get(&inner_id).cloned() {
+            let x = x.into_iter().map(|mut x| {
+                if let Some(step) = step {
+                    x.path.insert(0, step);
+                }
+                x
+            });
+            self.fwd_ptr_refs.entry(id).or_default().extend(x);
+        }
+    }
+    /// Clone type `ty_id` with the forward-declared pointers in it resolved.
+    /// Each type is resolved once and memoized in `memo`. Cyclic references
+    /// are cut at the first revisit with `Type::DeviceAddress`, so the
+    /// resolution is linear in the number of types. Returns `None` if
+    /// `ty_id` is already being resolved or is never defined.
+    fn resolve_fwd_ptr_ty(&self, ty_id: TypeId, memo: &mut FwdPtrMemo) -> Option<Type> {
+        match memo.get(&ty_id) {
+            Some(Some(x)) => return Some(x.clone()),
+            // Being resolved.
+            Some(None) => return None,
+            None => {}
+        }
+        let mut ty = self.ty_reg.get(ty_id).ok()?.clone();
+        if ty.is_device_address() {
+            return None;
+        }
+        memo.insert(ty_id, None);
+        self.resolve_fwd_ptr_refs(&mut ty, ty_id, memo);
+        memo.insert(ty_id, Some(ty.clone()));
+        Some(ty)
+    }
+    fn resolve_fwd_ptr_refs(&self, ty: &mut Type, id: u32, memo: &mut FwdPtrMemo) {
+        for x in self.fwd_ptr_refs.get(&id).into_iter().flatten() {
+            if let Some(ptr_ty) = self.resolve_fwd_ptr_ty(x.ptr_ty_id, memo) {
+                if let Some(nested_ty) = nested_ty_mut(ty, &x.path) {
+                    *nested_ty = ptr_ty;
+                }
+            }
+        }
+    }
+    /// Replace the `Type::DeviceAddress` placeholders of forward-declared
+    /// pointers with the pointer types defined later in types and variables.
+    fn resolve_fwd_ptrs(&mut self) -> Result<()> {
+        if self.fwd_ptr_refs.is_empty() {
+            return Ok(());
+        }
+        let mut memo = FwdPtrMemo::default();
+        let mut ty_reg = TypeRegistry::default();
+        let mut ty_arena = TypeArena::default();
+        let mut interned_tys = self.arena.map();
+        for (id, ty) in self.ty_reg.iter() {
+            let ty = self
+                .resolve_fwd_ptr_ty(*id, &mut memo)
+                .unwrap_or_else(|| ty.clone());
+            interned_tys.insert(*id, ty_arena.intern(&ty));
+            ty_reg.set(*id, ty)?;
+        }
+        let mut var_reg = VariableRegistry::default();
+        for (id, var) in self.var_reg.iter() {
+            let mut ty = Type::DevicePointer(var.ptr_ty.clone());
+            self.resolve_fwd_ptr_refs(&mut ty, *id, &mut memo);
+            let ptr_ty = match ty {
+                Type::DevicePointer(x) => x,
+                _ => unreachable!(),
+            };
+            let var = VariableAlloc {
+                name: var.name.clone(),
+                store_cls: var.store_cls,
+                ptr_ty,
+            };
+            var_reg.set(*id, var)?;
+        }
+        self.ty_reg = ty_reg;
+        self.ty_arena = ty_arena;
+        self.interned_tys = interned_tys;
+        self.var_reg = var_reg;
+        Ok(())
+    }
     fn populate_one_ty(&mut self, instr: &Instr) -> Result<()> {
         match instr.op() {
             Op::TypeFunction => {}
             Op::TypeVoid => {
                 let op = OpTypeVoid::try_from(instr)?;
                 let scalar_ty = ScalarType::Void;
-                self.ty_reg.set(op.ty_id, Type::Scalar(scalar_ty))?;
+                self.set_ty(op.result_id, Type::Scalar(scalar_ty))?;
             }
             Op::TypeBool => {
                 let op = OpTypeBool::try_from(instr)?;
                 let scalar_ty = ScalarType::Boolean;
-                self.ty_reg.set(op.ty_id, Type::Scalar(scalar_ty))?;
+                self.set_ty(op.result_id, Type::Scalar(scalar_ty))?;
             }
             Op::TypeInt => {
                 let op = OpTypeInt::try_from(instr)?;
                 let scalar_ty = ScalarType::Integer {
-                    bits: op.bits,
-                    is_signed: op.is_signed,
+                    bits: op.width,
+                    is_signed: op.signedness != 0,
                 };
-                self.ty_reg.set(op.ty_id, Type::Scalar(scalar_ty))?;
+                self.set_ty(op.result_id, Type::Scalar(scalar_ty))?;
             }
             Op::TypeFloat => {
                 let op = OpTypeFloat::try_from(instr)?;
-                let scalar_ty = ScalarType::Float { bits: op.bits };
-                self.ty_reg.set(op.ty_id, Type::Scalar(scalar_ty))?;
+                let scalar_ty = ScalarType::Float { bits: op.width };
+                self.set_ty(op.result_id, Type::Scalar(scalar_ty))?;
             }
             Op::TypeVector => {
                 let op = OpTypeVector::try_from(instr)?;
-                if let Type::Scalar(scalar_ty) = self.ty_reg.get(op.scalar_ty_id)? {
+                if let Type::Scalar(scalar_ty) = self.ty_reg.get(op.component_type_id)? {
                     let vector_ty = VectorType {
                         scalar_ty: scalar_ty.clone(),
-                        nscalar: op.nscalar,
+                        nscalar: op.component_count,
                     };
-                    self.ty_reg.set(op.ty_id, Type::Vector(vector_ty))?;
+                    self.set_ty(op.result_id, Type::Vector(vector_ty))?;
                 } else {
-                    return Err(broken_nested_ty(op.ty_id));
+                    return Err(broken_nested_ty(op.result_id));
                 }
             }
             Op::TypeMatrix => {
                 let op = OpTypeMatrix::try_from(instr)?;
-                if let Type::Vector(vector_ty) = self.ty_reg.get(op.vector_ty_id)? {
+                if let Type::Vector(vector_ty) = self.ty_reg.get(op.column_type_id)? {
                     let mat_ty = MatrixType {
                         vector_ty: vector_ty.clone(),
-                        nvector: op.nvector,
+                        nvector: op.column_count,
                         axis_order: None,
                         stride: None,
                     };
-                    self.ty_reg.set(op.ty_id, Type::Matrix(mat_ty))?;
+                    self.set_ty(op.result_id, Type::Matrix(mat_ty))?;
                 } else {
-                    return Err(broken_nested_ty(op.ty_id));
+                    return Err(broken_nested_ty(op.result_id));
                 }
             }
             Op::TypeImage => {
                 let op = OpTypeImage::try_from(instr)?;
-                let scalar_ty = match self.ty_reg.get(op.scalar_ty_id)? {
+                let is_array = op.arrayed != 0;
+                let is_multisampled = op.ms != 0;
+                let scalar_ty = match self.ty_reg.get(op.sampled_type_id)? {
                     Type::Scalar(scalar_ty) => scalar_ty.clone(),
-                    _ => return Err(broken_nested_ty(op.ty_id)),
+                    _ => return Err(broken_nested_ty(op.result_id)),
                 };
                 if op.dim == spirv::Dim::DimSubpassData {
                     let subpass_data_ty = SubpassDataType {
                         scalar_ty,
-                        is_multisampled: op.is_multisampled,
+                        is_multisampled,
                     };
-                    self.ty_reg
-                        .set(op.ty_id, Type::SubpassData(subpass_data_ty))?;
+                    self.set_ty(op.result_id, Type::SubpassData(subpass_data_ty))?;
                 } else {
+                    for arrangement in invalid_image_arrangements(op.dim, is_array, is_multisampled)
+                    {
+                        self.module_diags.push(Diagnostic::InvalidImageArrangement {
+                            ty_id: op.result_id,
+                            dim: op.dim,
+                            arrangement,
+                        });
+                    }
                     // Only unit types allowed to be stored in storage images
                     // can have given format.
-                    let is_sampled = match op.is_sampled {
+                    let is_sampled = match op.sampled {
                         0 => None,
                         1 => Some(true),
                         2 => Some(false),
-                        _ => return Err(anyhow!("unsupported image sampling type")),
+                        x => return Err(anyhow!("unsupported image sampling type {}", x)),
                     };
-                    let is_depth = match op.is_depth {
+                    let is_depth = match op.depth {
                         0 => Some(false),
                         1 => Some(true),
                         2 => None,
-                        _ => return Err(anyhow!("unsupported image depth type")),
+                        x => return Err(anyhow!("unsupported image depth type {}", x)),
                     };
+                    if op.dim == spirv::Dim::DimBuffer {
+                        self.texel_buffer_fmts.insert(op.result_id, op.image_format);
+                    }
                     let image_ty = ImageType {
                         scalar_ty,
                         dim: op.dim,
                         is_depth,
-                        is_array: op.is_array,
-                        is_multisampled: op.is_multisampled,
+                        is_array,
+                        is_multisampled,
                         is_sampled,
-                        fmt: op.color_fmt,
+                        fmt: op.image_format,
                     };
-                    self.ty_reg.set(op.ty_id, Type::Image(image_ty))?;
+                    self.set_ty(op.result_id, Type::Image(image_ty))?;
                 }
             }
             Op::TypeSampler => {
                 let op = OpTypeSampler::try_from(instr)?;
                 // Note that SPIR-V doesn't discriminate color and depth/stencil
                 // samplers. `sampler` and `samplerShadow` means the same thing.
-                self.ty_reg.set(op.ty_id, Type::Sampler(SamplerType {}))?;
+                self.set_ty(op.result_id, Type::Sampler(SamplerType {}))?;
             }
             Op::TypeSampledImage => {
                 let op = OpTypeSampledImage::try_from(instr)?;
-                if let Type::Image(image_ty) = self.ty_reg.get(op.image_ty_id)? {
-                    let sampled_image_ty = SampledImageType {
-                        scalar_ty: image_ty.scalar_ty.clone(),
-                        dim: image_ty.dim,
-                        is_depth: image_ty.is_depth,
-                        is_array: image_ty.is_array,
-                        is_multisampled: image_ty.is_multisampled,
-                    };
-                    let combined_img_sampler_ty = CombinedImageSamplerType { sampled_image_ty };
-                    self.ty_reg.set(
-                        op.ty_id,
-                        Type::CombinedImageSampler(combined_img_sampler_ty),
-                    )?;
-                } else {
-                    return Err(broken_nested_ty(op.ty_id));
+                self.inherit_texel_buffer_fmt(op.result_id, op.image_type_id);
+                let image_ty = match self.ty_reg.get(op.image_type_id)? {
+                    Type::Image(image_ty) => image_ty,
+                    // Some toolchains wrap subpass data and storage images in
+                    // sampled images. Reflect them as the wrapped images and
+                    // report them in diagnostics.
+                    Type::SubpassData(_) => {
+                        return self.set_degenerate_sampled_img(
+                            op.result_id,
+                            op.image_type_id,
+                            DegenerateSampledImage::SubpassData,
+                        );
+                    }
+                    _ => return Err(broken_nested_ty(op.result_id)),
+                };
+                if image_ty.is_sampled == Some(false) {
+                    return self.set_degenerate_sampled_img(
+                        op.result_id,
+                        op.image_type_id,
+                        DegenerateSampledImage::StorageImage,
+                    );
                 }
+                let sampled_image_ty = SampledImageType {
+                    scalar_ty: image_ty.scalar_ty.clone(),
+                    dim: image_ty.dim,
+                    is_depth: image_ty.is_depth,
+                    is_array: image_ty.is_array,
+                    is_multisampled: image_ty.is_multisampled,
+                };
+                let combined_img_sampler_ty = CombinedImageSamplerType { sampled_image_ty };
+                self.set_ty(
+                    op.result_id,
+                    Type::CombinedImageSampler(combined_img_sampler_ty),
+                )?;
             }
             Op::TypeArray => {
                 let op = OpTypeArray::try_from(instr)?;
                 // FIXME: Workaround old storage buffers.
                 if self
                     .deco_reg
-                    .contains(op.element_ty_id, spirv::Decoration::BufferBlock)
+                    .contains(op.element_type_id, spirv::Decoration::BufferBlock)
                 {
                     let _ = self.deco_reg.set(
-                        op.ty_id,
+                        op.result_id,
                         spirv::Decoration::BufferBlock,
                         &[] as &'static [u32],
                     );
                 }
-                let element_ty = if let Ok(x) = self.ty_reg.get(op.element_ty_id) {
+                let element_ty = if let Ok(x) = self.ty_reg.get(op.element_type_id) {
                     x
                 } else {
                     return Ok(());
@@ -312,15 +735,30 @@ impl<'a> ReflectIntermediate<'a> {
                 // runtime according to Khronos members, but the default
                 // behavior of `glslang` is to treat the specialization
                 // constants as normal constants, then I would say...
-                // probably it's fine to size array with them?
-                let nelement = match self.interp.get_value(op.nelement_const_id)? {
+                // probably it's fine to size array with them? Arrays in
+                // blocks sized this way are reported in diagnostics.
+                if let Some(spec_id) = self.interp.get(op.length_id)?.spec_id {
+                    let spec_sized_arr = SpecSizedArray {
+                        member: None,
+                        spec_id,
+                    };
+                    self.spec_sized_arrays
+                        .entry(op.result_id)
+                        .or_default()
+                        .push(spec_sized_arr);
+                }
+                let nelement = match self.interp.get_value(op.length_id)? {
+                    ConstantValue::S8(x) if *x > 0 => *x as u32,
+                    ConstantValue::S16(x) if *x > 0 => *x as u32,
                     ConstantValue::S32(x) if *x > 0 => *x as u32,
+                    ConstantValue::U8(x) if *x > 0 => *x as u32,
+                    ConstantValue::U16(x) if *x > 0 => *x as u32,
                     ConstantValue::U32(x) if *x > 0 => *x,
                     _ => return Err(anyhow!("invalid array size")),
                 };
                 let stride = self
                     .deco_reg
-                    .get_u32(op.ty_id, spirv::Decoration::ArrayStride)
+                    .get_u32(op.result_id, spirv::Decoration::ArrayStride)
                     .map(|x| x as usize);
 
                 let arr_ty = if let Ok(stride) = stride {
@@ -338,18 +776,30 @@ impl<'a> ReflectIntermediate<'a> {
                         stride: None,
                     }
                 };
-                self.ty_reg.set(op.ty_id, Type::Array(arr_ty))?;
+                self.set_ty(op.result_id, Type::Array(arr_ty))?;
+                self.inherit_degenerate_sampled_img(op.result_id, op.element_type_id);
+                self.inherit_texel_buffer_fmt(op.result_id, op.element_type_id);
+                self.inherit_spec_sized_arrays(op.result_id, op.element_type_id);
+                self.inherit_fwd_ptr_refs(
+                    op.result_id,
+                    op.element_type_id,
+                    Some(TypeStep::Element),
+                );
+                // Per-vertex I/O blocks of tessellation and geometry stages.
+                self.inherit_io_block_location(op.result_id, op.element_type_id);
+                self.inherit_io_member_decos(op.result_id, op.element_type_id);
+                self.inherit_builtin_block(op.result_id, op.element_type_id);
             }
             Op::TypeRuntimeArray => {
                 let op = OpTypeRuntimeArray::try_from(instr)?;
-                let element_ty = if let Ok(x) = self.ty_reg.get(op.element_ty_id) {
+                let element_ty = if let Ok(x) = self.ty_reg.get(op.element_type_id) {
                     x
                 } else {
                     return Ok(());
                 };
                 let stride = self
                     .deco_reg
-                    .get_u32(op.ty_id, spirv::Decoration::ArrayStride)
+                    .get_u32(op.result_id, spirv::Decoration::ArrayStride)
                     .map(|x| x as usize);
                 let arr_ty = if let Ok(stride) = stride {
                     // Unsized data arrays.
@@ -367,23 +817,31 @@ impl<'a> ReflectIntermediate<'a> {
                         stride: None,
                     }
                 };
-                self.ty_reg.set(op.ty_id, Type::Array(arr_ty))?;
+                self.set_ty(op.result_id, Type::Array(arr_ty))?;
+                self.inherit_degenerate_sampled_img(op.result_id, op.element_type_id);
+                self.inherit_texel_buffer_fmt(op.result_id, op.element_type_id);
+                self.inherit_spec_sized_arrays(op.result_id, op.element_type_id);
+                self.inherit_fwd_ptr_refs(
+                    op.result_id,
+                    op.element_type_id,
+                    Some(TypeStep::Element),
+                );
             }
             Op::TypeStruct => {
                 let op = OpTypeStruct::try_from(instr)?;
-                let struct_name =
-                    self.name_reg
-                        .get(op.ty_id)
-                        .map(ToOwned::to_owned)
-                        .or_else(|| {
-                            if self.cfg.gen_unique_names {
-                                Some(format!("type_{}", op.ty_id))
-                            } else {
-                                None
-                            }
-                        });
+                let struct_name = self
+                    .name_reg
+                    .get(op.result_id)
+                    .map(ToOwned::to_owned)
+                    .or_else(|| {
+                        if self.cfg.gen_unique_names {
+                            Some(format!("type_{}", op.result_id))
+                        } else {
+                            None
+                        }
+                    });
                 let mut members = Vec::new();
-                for (i, &member_ty_id) in op.member_ty_ids.iter().enumerate() {
+                for (i, &member_ty_id) in op.member_type_ids.iter().enumerate() {
                     let i = i as u32;
                     let mut member_ty = if let Ok(member_ty) = self.ty_reg.get(member_ty_id) {
                         member_ty.clone()
@@ -396,7 +854,7 @@ impl<'a> ReflectIntermediate<'a> {
                     }
                     if let Type::Matrix(ref mut mat_ty) = element_ty {
                         let mat_stride = self.deco_reg.get_member_u32(
-                            op.ty_id,
+                            op.result_id,
                             i,
                             spirv::Decoration::MatrixStride,
                         );
@@ -404,12 +862,16 @@ impl<'a> ReflectIntermediate<'a> {
                             mat_ty.stride = Some(mat_stride as usize);
                         }
 
-                        let is_row_major =
-                            self.deco_reg
-                                .contains_member(op.ty_id, i, spirv::Decoration::RowMajor);
-                        let is_col_major =
-                            self.deco_reg
-                                .contains_member(op.ty_id, i, spirv::Decoration::ColMajor);
+                        let is_row_major = self.deco_reg.contains_member(
+                            op.result_id,
+                            i,
+                            spirv::Decoration::RowMajor,
+                        );
+                        let is_col_major = self.deco_reg.contains_member(
+                            op.result_id,
+                            i,
+                            spirv::Decoration::ColMajor,
+                        );
 
                         mat_ty.axis_order = if is_row_major {
                             Some(MatrixAxisOrder::RowMajor)
@@ -421,11 +883,11 @@ impl<'a> ReflectIntermediate<'a> {
                     }
                     let name = self
                         .name_reg
-                        .get_member(op.ty_id, i)
+                        .get_member(op.result_id, i)
                         .map(ToOwned::to_owned)
                         .or_else(|| {
                             if self.cfg.gen_unique_names {
-                                Some(format!("type_{}_member_{}", op.ty_id, i))
+                                Some(format!("type_{}_member_{}", op.result_id, i))
                             } else {
                                 None
                             }
@@ -435,13 +897,34 @@ impl<'a> ReflectIntermediate<'a> {
                     // accessible we don't have to worry about them.
                     let offset = self
                         .deco_reg
-                        .get_member_u32(op.ty_id, i, spirv::Decoration::Offset)
+                        .get_member_u32(op.result_id, i, spirv::Decoration::Offset)
                         .map(|x| x as usize)
                         .ok();
                     let access_ty = self
                         .deco_reg
-                        .get_member_access_ty_from_deco(op.ty_id, i)
+                        .get_member_access_ty_from_deco(op.result_id, i)
                         .ok_or_else(|| anyhow!("missing access type"))?;
+                    if let Some(spec_sized_arrs) = self.spec_sized_arrays.get(&member_ty_id) {
+                        let spec_sized_arrs = spec_sized_arrs
+                            .iter()
+                            .map(|x| SpecSizedArray {
+                                member: x
+                                    .member
+                                    .clone()
+                                    .or_else(|| Some((struct_name.clone(), name.clone()))),
+                                spec_id: x.spec_id,
+                            })
+                            .collect::<Vec<_>>();
+                        self.spec_sized_arrays
+                            .entry(op.result_id)
+                            .or_default()
+                            .extend(spec_sized_arrs);
+                    }
+                    self.inherit_fwd_ptr_refs(
+                        op.result_id,
+                        member_ty_id,
+                        Some(TypeStep::Member(i as usize)),
+                    );
                     let member = StructMember {
                         name,
                         offset,
@@ -454,48 +937,172 @@ impl<'a> ReflectIntermediate<'a> {
                     name: struct_name,
                     members: members,
                 };
+                // I/O blocks can be located by their members rather than the
+                // variables. The block begins at the location of the first
+                // member.
+                if let Ok(loc) =
+                    self.deco_reg
+                        .get_member_u32(op.result_id, 0, spirv::Decoration::Location)
+                {
+                    let comp = self
+                        .deco_reg
+                        .get_member_u32(op.result_id, 0, spirv::Decoration::Component)
+                        .unwrap_or(0);
+                    self.io_block_locations
+                        .insert(op.result_id, InterfaceLocation::new(loc, comp));
+                }
+                // Members of I/O blocks can be captured by transform
+                // feedback individually. Members of other blocks are
+                // recorded too, but only looked up for I/O variables.
+                let mut member_decos = BTreeMap::new();
+                for i in 0..struct_ty.members.len() as u32 {
+                    let decos = IO_DECOS
+                        .iter()
+                        .filter_map(|deco| {
+                            let operands = self.deco_reg.get_member(op.result_id, i, *deco).ok()?;
+                            Some((*deco, operands.to_owned()))
+                        })
+                        .collect::<BTreeMap<_, _>>();
+                    if !decos.is_empty() {
+                        member_decos.insert(i, decos);
+                    }
+                }
+                if !member_decos.is_empty() {
+                    self.io_member_decos.insert(op.result_id, member_decos);
+                }
+                let is_builtin_block = (0..struct_ty.members.len() as u32).any(|i| {
+                    self.deco_reg
+                        .contains_member(op.result_id, i, spirv::Decoration::BuiltIn)
+                });
+                if is_builtin_block {
+                    let builtins = (0..struct_ty.members.len() as u32)
+                        .filter_map(|i| {
+                            self.deco_reg
+                                .get_member_u32(op.result_id, i, spirv::Decoration::BuiltIn)
+                                .ok()
+                                .and_then(spirv::BuiltIn::from_u32)
+                        })
+                        .collect();
+                    self.builtin_blocks.insert(op.result_id, builtins);
+                    let point_size_member = (0..struct_ty.members.len() as u32).find(|i| {
+                        let builtin = self.deco_reg.get_member_u32(
+                            op.result_id,
+                            *i,
+                            spirv::Decoration::BuiltIn,
+                        );
+                        builtin.ok() == Some(spirv::BuiltIn::PointSize as u32)
+                    });
+                    if let Some(i) = point_size_member {
+                        self.point_size_members.insert(op.result_id, i);
+                    }
+                }
+                if self
+                    .deco_reg
+                    .contains(op.result_id, spirv::Decoration::Block)
+                {
+                    self.blocks.insert(op.result_id);
+                }
                 // Don't have to shrink-to-fit because the types in `ty_map`
                 // won't be used directly and will be cloned later.
-                self.ty_reg.set(op.ty_id, Type::Struct(struct_ty))?;
+                self.set_ty(op.result_id, Type::Struct(struct_ty))?;
             }
             Op::TypePointer => {
                 let op = OpTypePointer::try_from(instr)?;
-                if let Ok(pointee_ty) = self.ty_reg.get(op.target_ty_id) {
+                if let Ok(pointee_ty) = self.ty_reg.get(op.type_id) {
                     // Before SPIR-V 1.3, there is no `StorageBuffer` storage
                     // class. And from a pointer perspective you can't tell if
                     // it's a uniform block or a buffer block.
                     let is_storage_buffer = self
                         .deco_reg
-                        .contains(op.target_ty_id, spirv::Decoration::BufferBlock);
-                    let store_cls = if op.store_cls == StorageClass::Uniform && is_storage_buffer {
-                        StorageClass::StorageBuffer
-                    } else {
-                        op.store_cls
-                    };
+                        .contains(op.type_id, spirv::Decoration::BufferBlock);
+                    let store_cls =
+                        if op.storage_class == StorageClass::Uniform && is_storage_buffer {
+                            StorageClass::StorageBuffer
+                        } else {
+                            op.storage_class
+                        };
                     let pointer_ty = PointerType {
                         pointee_ty: Box::new(pointee_ty.clone()),
                         store_cls,
                     };
-                    self.ty_reg.set(op.ty_id, Type::DevicePointer(pointer_ty))?;
+                    self.set_ty(op.result_id, Type::DevicePointer(pointer_ty))?;
+                    self.inherit_degenerate_sampled_img(op.result_id, op.type_id);
+                    self.inherit_texel_buffer_fmt(op.result_id, op.type_id);
+                    self.inherit_spec_sized_arrays(op.result_id, op.type_id);
+                    self.inherit_io_block_location(op.result_id, op.type_id);
+                    self.inherit_io_member_decos(op.result_id, op.type_id);
+                    self.inherit_builtin_block(op.result_id, op.type_id);
+                    self.inherit_block(op.result_id, op.type_id);
+                    // The placeholder of a forward-declared pointer is now
+                    // defined.
+                    self.fwd_ptr_refs.remove(&op.result_id);
+                    self.inherit_fwd_ptr_refs(op.result_id, op.type_id, Some(TypeStep::Pointee));
                 } else {
-                    // Ignore unknown types. Currently only funtion pointers can
-                    // step into this.
+                    // Ignore unknown types. Currently only funtion pointers and
+                    // pointers to OpenCL opaque types can step into this.
                     return Ok(());
                 }
             }
             Op::TypeForwardPointer => {
                 let op = OpTypeForwardPointer::try_from(instr)?;
-                self.ty_reg
-                    .set(op.ty_id, Type::DeviceAddress(DeviceAddressType {}))?;
+                self.set_ty(
+                    op.pointer_type_id,
+                    Type::DeviceAddress(DeviceAddressType {}),
+                )?;
+                let fwd_ptr_ref = ForwardPointerRef {
+                    path: Vec::new(),
+                    ptr_ty_id: op.pointer_type_id,
+                };
+                self.fwd_ptr_refs
+                    .insert(op.pointer_type_id, vec![fwd_ptr_ref]);
             }
+            // Also `OpTypeAccelerationStructureNV`, which shares the opcode.
             Op::TypeAccelerationStructureKHR => {
                 let op = OpTypeAccelerationStructureKHR::try_from(instr)?;
-                self.ty_reg
-                    .set(op.ty_id, Type::AccelStruct(AccelStructType {}))?;
+                self.set_ty(op.result_id, Type::AccelStruct(AccelStructType {}))?;
             }
             Op::TypeRayQueryKHR => {
                 let op = OpTypeRayQueryKHR::try_from(instr)?;
-                self.ty_reg.set(op.ty_id, Type::RayQuery(RayQueryType {}))?;
+                self.set_ty(op.result_id, Type::RayQuery(RayQueryType {}))?;
+            }
+            Op::TypeCooperativeMatrixKHR => {
+                // `Type` has no cooperative matrix variant, so they are kept
+                // aside and left out of the type registry. They only live in
+                // function-local and private variables.
+                let op = OpTypeCooperativeMatrixKHR::try_from(instr)?;
+                let component_ty = match self.ty_reg.get(op.component_type_id)? {
+                    Type::Scalar(x) => x.clone(),
+                    _ => return Err(broken_nested_ty(op.component_type_id)),
+                };
+                let get_u32 = |id| match self.interp.get_value(id)? {
+                    ConstantValue::S32(x) if *x >= 0 => Ok(*x as u32),
+                    ConstantValue::U32(x) => Ok(*x),
+                    _ => Err(anyhow!("invalid cooperative matrix operand")),
+                };
+                let scope = get_u32(op.scope_id)?;
+                let usage = get_u32(op.use_id)?;
+                let coop_mat_ty = CooperativeMatrixType {
+                    component_ty,
+                    scope: spirv::Scope::from_u32(scope)
+                        .ok_or_else(|| anyhow!("invalid scope {}", scope))?,
+                    nrow: get_u32(op.rows_id)?,
+                    ncolumn: get_u32(op.columns_id)?,
+                    usage: spirv::CooperativeMatrixUse::from_u32(usage)
+                        .ok_or_else(|| anyhow!("invalid cooperative matrix use {}", usage))?,
+                };
+                self.coop_mat_tys.insert(op.result_id, coop_mat_ty);
+            }
+            Op::TypeOpaque
+            | Op::TypeEvent
+            | Op::TypeDeviceEvent
+            | Op::TypeReserveId
+            | Op::TypeQueue
+            | Op::TypePipe
+            | Op::TypePipeStorage
+            | Op::TypeNamedBarrier => {
+                // OpenCL opaque types can't be represented by `Type` either.
+                // They can't be struct members and pointers to them are
+                // ignored, so kernels using them are still reflected.
             }
             _ => return Err(anyhow!("unexpected opcode {:?}", instr.op())),
         }
@@ -505,8 +1112,10 @@ impl<'a> ReflectIntermediate<'a> {
         let opcode = instr.op();
         match opcode {
             Op::ConstantTrue | Op::ConstantFalse | Op::Constant => {
-                let op = OpConstantScalarCommonSPQ::try_from(instr)?;
-                let ty = self.ty_reg.get(op.ty_id)?.clone();
+                // Boolean constants are laid out like `OpConstant` without
+                // the value.
+                let op = OpConstant::try_from(instr)?;
+                let ty = self.ty_reg.get(op.result_type_id)?.clone();
                 let value = match instr.op() {
                     Op::ConstantTrue => ConstantValue::from(true),
                     Op::ConstantFalse => ConstantValue::from(false),
@@ -515,30 +1124,43 @@ impl<'a> ReflectIntermediate<'a> {
                 };
                 let name = self
                     .name_reg
-                    .get(op.const_id)
+                    .get(op.result_id)
                     .map(ToOwned::to_owned)
                     .or_else(|| {
                         if self.cfg.gen_unique_names {
-                            Some(format!("const_{}", op.const_id))
+                            Some(format!("const_{}", op.result_id))
                         } else {
                             None
                         }
                     });
                 let constant = Constant::new(name, ty, value);
-                self.interp.set(op.const_id, constant)?;
+                self.interp.set(op.result_id, constant)?;
+                Ok(())
+            }
+            Op::ConstantComposite => self.populate_composite_const(instr),
+            Op::ConstantSampler => {
+                let op = OpConstantSampler::try_from(instr)?;
+                match self.ty_reg.get(op.result_type_id)? {
+                    Type::Sampler(_) => {}
+                    _ => return Err(anyhow!("literal sampler {} is not a sampler", op.result_id)),
+                }
+                let literal_sampler = LiteralSampler {
+                    name: self.name_reg.get(op.result_id).map(ToOwned::to_owned),
+                    addr_mode: op.sampler_addressing_mode,
+                    is_normalized: op.param != 0,
+                    filter_mode: op.sampler_filter_mode,
+                };
+                self.literal_samplers.push(literal_sampler);
                 Ok(())
             }
-            Op::ConstantComposite
-            | Op::ConstantSampler
-            | Op::ConstantNull
-            | Op::ConstantPipeStorage => Ok(()),
+            Op::ConstantNull | Op::ConstantPipeStorage => Ok(()),
             Op::SpecConstantTrue | Op::SpecConstantFalse | Op::SpecConstant => {
-                let op = OpConstantScalarCommonSPQ::try_from(instr)?;
-                let name = self.name_reg.get(op.const_id).map(ToString::to_string);
+                let op = OpSpecConstant::try_from(instr)?;
+                let name = self.name_reg.get(op.result_id).map(ToString::to_string);
                 let spec_id = self
                     .deco_reg
-                    .get_u32(op.const_id, spirv::Decoration::SpecId)?;
-                let ty = self.ty_reg.get(op.ty_id)?.clone();
+                    .get_u32(op.result_id, spirv::Decoration::SpecId)?;
+                let ty = self.ty_reg.get(op.result_type_id)?.clone();
                 let constant = if let Some(user_value) = self.cfg.spec_values.get(&spec_id) {
                     let user_value = if matches!(user_value, ConstantValue::Typeless(_)) {
                         user_value.to_typed(&ty)?
@@ -555,117 +1177,1145 @@ impl<'a> ReflectIntermediate<'a> {
                     };
                     Constant::new_spec(name, ty, value, spec_id)
                 };
-                self.interp.set(op.const_id, constant)?;
+                self.interp.set(op.result_id, constant)?;
+                self.spec_const_deps
+                    .insert(op.result_id, std::iter::once(spec_id).collect());
                 Ok(())
             }
             // `SpecId` decorations will be specified to each of the constituents so we don't have to register a `Constant` for the composite of them. `Constant` is registered only for those will be interacting with Vulkan.
-            Op::SpecConstantComposite => Ok(()),
+            Op::SpecConstantComposite => self.populate_composite_const(instr),
             Op::SpecConstantOp => {
-                let op = OpSpecConstantHeadSPQ::try_from(instr)?;
+                let op = OpSpecConstantOp::try_from(instr)?;
                 let opcode = Op::from_u32(op.opcode)
                     .ok_or_else(|| anyhow!("invalid specialization constant op opcode"))?;
-                let result_id = op.spec_const_id;
-                let result_ty = self.ty_reg.get(op.ty_id)?;
-                self.interp
-                    .interpret(opcode, result_id, result_ty, &instr.as_ref()[4..])?;
-                Ok(())
+                self.populate_spec_const_deps(opcode, op.result_id, op.operands);
+                self.fold_spec_const_op(opcode, op.result_id, op.result_type_id, op.operands)
             }
             _ => Err(anyhow!("unexpected opcode {:?}", instr.op())),
         }
     }
+    /// Inherit the specialization IDs the ID operands of a specialization
+    /// constant operation depend on.
+    fn populate_spec_const_deps(&mut self, opcode: Op, spec_const_id: InstrId, operands: &[u32]) {
+        // Literal indices follow the composite operands.
+        let nid = match opcode {
+            Op::CompositeExtract => 1,
+            Op::VectorShuffle | Op::CompositeInsert => 2,
+            _ => operands.len(),
+        };
+        let deps = operands
+            .iter()
+            .take(nid)
+            .filter_map(|x| self.spec_const_deps.get(x))
+            .flatten()
+            .copied()
+            .collect::<BTreeSet<_>>();
+        if !deps.is_empty() {
+            self.spec_const_deps.insert(spec_const_id, deps);
+        }
+    }
+    #[cfg(feature = "spec-folding")]
+    fn fold_spec_const_op(
+        &mut self,
+        opcode: Op,
+        spec_const_id: InstrId,
+        ty_id: TypeId,
+        operands: &[u32],
+    ) -> Result<()> {
+        let result_ty = self.ty_reg.get(ty_id)?;
+        let operands = operands
+            .iter()
+            .map(|x| self.interp.get_value(*x).cloned())
+            .collect::<Result<Vec<_>>>()
+            .map_err(|_| anyhow!("broken expression tree at id {}", spec_const_id))?;
+        let value = match Evaluator::evaluate(opcode, result_ty, &operands) {
+            Ok(x) => x,
+            Err(e) => match evaluate_widened(opcode, result_ty, &operands) {
+                Some(x) => x,
+                None => return Err(e),
+            },
+        };
+        self.interp
+            .set(spec_const_id, Constant::new_itm(result_ty.clone(), value))?;
+        Ok(())
+    }
+    /// Results of specialization constant operations are unknown without the
+    /// `spec-folding` feature.
+    #[cfg(not(feature = "spec-folding"))]
+    fn fold_spec_const_op(&mut self, _: Op, _: InstrId, _: TypeId, _: &[u32]) -> Result<()> {
+        Ok(())
+    }
+    /// Composite constants are not registered, except the `WorkgroupSize`
+    /// built-in which overrides `LocalSize` and `LocalSizeId` execution modes.
+    #[cfg(feature = "exec-modes")]
+    fn populate_composite_const(&mut self, instr: &Instr) -> Result<()> {
+        // `OpSpecConstantComposite` is laid out like `OpConstantComposite`.
+        let op = OpConstantComposite::try_from(instr)?;
+        let builtin = self
+            .deco_reg
+            .get_u32(op.result_id, spirv::Decoration::BuiltIn)
+            .ok();
+        if builtin == Some(spirv::BuiltIn::WorkgroupSize as u32) {
+            // Constituents produced by unfolded specialization constant
+            // operations are unknown, in which case `LocalSize` is kept.
+            self.workgroup_size = op
+                .constituent_ids
+                .iter()
+                .map(|x| self.interp.get(*x).cloned())
+                .collect::<Result<Vec<_>>>()
+                .ok();
+        }
+        Ok(())
+    }
+    #[cfg(not(feature = "exec-modes"))]
+    fn populate_composite_const(&mut self, _: &Instr) -> Result<()> {
+        Ok(())
+    }
     fn populate_one_var(&mut self, instr: &Instr) -> Result<()> {
         let op = OpVariable::try_from(instr)?;
-        let ptr_ty = if let Ok(ty) = self.ty_reg.get(op.ty_id) {
+        let ptr_ty = if let Ok(ty) = self.ty_reg.get(op.result_type_id) {
             match ty {
                 Type::DevicePointer(ptr_ty) => ptr_ty.clone(),
-                _ => return Err(broken_nested_ty(op.ty_id)),
+                _ => return Err(broken_nested_ty(op.result_type_id)),
             }
         } else {
             return Ok(());
         };
-        let name = self.name_reg.get(op.var_id).map(ToString::to_string);
+        let name = self.name_reg.get(op.result_id).map(ToString::to_string);
         let var = VariableAlloc {
             name,
             ptr_ty,
-            store_cls: op.store_cls,
+            store_cls: op.storage_class,
         };
-        self.var_reg.set(op.var_id, var)?;
+        self.var_reg.set(op.result_id, var)?;
+        if let Some(init_id) = op.initializer_id {
+            self.var_inits.insert(op.result_id, init_id);
+        }
+        self.inherit_degenerate_sampled_img(op.result_id, op.result_type_id);
+        self.inherit_texel_buffer_fmt(op.result_id, op.result_type_id);
+        self.inherit_spec_sized_arrays(op.result_id, op.result_type_id);
+        self.inherit_fwd_ptr_refs(op.result_id, op.result_type_id, None);
+        self.inherit_io_block_location(op.result_id, op.result_type_id);
+        self.inherit_io_member_decos(op.result_id, op.result_type_id);
+        self.inherit_builtin_block(op.result_id, op.result_type_id);
+        self.inherit_block(op.result_id, op.result_type_id);
         Ok(())
     }
 }
 
-pub struct FunctionInspector {
+/// Evaluate `opcode` on 8-bit and 16-bit scalars, which are not supported by
+/// the evaluator for most operations, as 32-bit scalars. Integer operands are
+/// sign- or zero-extended as the operation interprets them, regardless of
+/// their declared signedness, and the result is narrowed back to the result
+/// type. Returns `None` if no scalar is narrower than 32 bits or the
+/// operation can't be evaluated.
+#[cfg(feature = "spec-folding")]
+fn evaluate_widened(
+    opcode: Op,
+    result_ty: &Type,
+    operands: &[ConstantValue],
+) -> Option<ConstantValue> {
+    let is_signed = match opcode {
+        Op::SConvert
+        | Op::ConvertSToF
+        | Op::SNegate
+        | Op::SDiv
+        | Op::SRem
+        | Op::SMod
+        | Op::ShiftRightArithmetic
+        | Op::SLessThan
+        | Op::SLessThanEqual
+        | Op::SGreaterThan
+        | Op::SGreaterThanEqual => true,
+        // The low bits of the results don't depend on the extension of
+        // operands for wrapping arithmetic and bitwise operations.
+        Op::UConvert
+        | Op::ConvertUToF
+        | Op::UDiv
+        | Op::UMod
+        | Op::ShiftRightLogical
+        | Op::ULessThan
+        | Op::ULessThanEqual
+        | Op::UGreaterThan
+        | Op::UGreaterThanEqual
+        | Op::IEqual
+        | Op::INotEqual
+        | Op::IAdd
+        | Op::ISub
+        | Op::IMul
+        | Op::ShiftLeftLogical
+        | Op::BitwiseOr
+        | Op::BitwiseXor
+        | Op::BitwiseAnd
+        | Op::Not => false,
+        Op::FConvert
+        | Op::ConvertFToS
+        | Op::ConvertFToU
+        | Op::FNegate
+        | Op::FAdd
+        | Op::FSub
+        | Op::FMul
+        | Op::FDiv
+        | Op::FRem
+        | Op::FMod => false,
+        // Bit patterns of different widths are not interchangeable, and
+        // other operations are not known to be safe to widen.
+        _ => return None,
+    };
+    let mut is_widened = false;
+    let operands = operands
+        .iter()
+        .map(|x| {
+            let x = match (x, is_signed) {
+                (ConstantValue::S8(x), true) => ConstantValue::S32(*x as i32),
+                (ConstantValue::U8(x), true) => ConstantValue::S32(*x as i8 as i32),
+                (ConstantValue::S16(x), true) => ConstantValue::S32(*x as i32),
+                (ConstantValue::U16(x), true) => ConstantValue::S32(*x as i16 as i32),
+                (ConstantValue::S8(x), false) => ConstantValue::U32(*x as u8 as u32),
+                (ConstantValue::U8(x), false) => ConstantValue::U32(*x as u32),
+                (ConstantValue::S16(x), false) => ConstantValue::U32(*x as u16 as u32),
+                (ConstantValue::U16(x), false) => ConstantValue::U32(*x as u32),
+                (ConstantValue::F16(x), _) => ConstantValue::from(x.into_inner().to_f32()),
+                _ => return x.clone(),
+            };
+            is_widened = true;
+            x
+        })
+        .collect::<Vec<_>>();
+    let wide_ty = match result_ty {
+        Type::Scalar(ScalarType::Integer {
+            bits: 8 | 16,
+            is_signed,
+        }) => Type::Scalar(ScalarType::Integer {
+            bits: 32,
+            is_signed: *is_signed,
+        }),
+        Type::Scalar(ScalarType::Float { bits: 16 }) => {
+            Type::Scalar(ScalarType::Float { bits: 32 })
+        }
+        _ if is_widened => result_ty.clone(),
+        _ => return None,
+    };
+    let value = Evaluator::evaluate(opcode, &wide_ty, &operands).ok()?;
+    // Integer results wrap around as in the narrower type.
+    let value = match (result_ty.as_scalar()?, value) {
+        (ScalarType::Integer { bits: 8, .. }, ConstantValue::S32(x)) => ConstantValue::S8(x as i8),
+        (ScalarType::Integer { bits: 16, .. }, ConstantValue::S32(x)) => {
+            ConstantValue::S16(x as i16)
+        }
+        (ScalarType::Integer { bits: 8, .. }, ConstantValue::U32(x)) => ConstantValue::U8(x as u8),
+        (ScalarType::Integer { bits: 16, .. }, ConstantValue::U32(x)) => {
+            ConstantValue::U16(x as u16)
+        }
+        (ScalarType::Float { bits: 16 }, ConstantValue::F32(x)) => {
+            ConstantValue::F16(OrderedFloat(FromPrimitive::from_f32(x.into_inner())?))
+        }
+        (ScalarType::Integer { bits: 8 | 16, .. }, _) | (ScalarType::Float { bits: 16 }, _) => {
+            return None
+        }
+        (_, x) => x,
+    };
+    Some(value)
+}
+
+/// Feed every `<id>` operand of `instr` to `f`. Operands following unknown
+/// enumerants can't be decoded, in which case every operand word is fed as
+/// it might be an ID.
+#[cfg(feature = "access-analysis")]
+fn visit_id_operands(instr: &Instr, mut f: impl FnMut(u32)) {
+    let res = visit_operands(instr, |x| {
+        if let Operand::Id(id) = x {
+            f(id);
+        }
+    });
+    if res.is_err() {
+        instr.as_ref()[1..].iter().copied().for_each(f);
+    }
+}
+
+/// Map indexed by SPIR-V IDs. IDs are dense and bounded by the ID bound in the
+/// module header, so IDs below the bound are looked up in a table without
+/// hashing. The table only holds indices into `values` so it stays small even
+/// if `T` is large. IDs at or above the bound, e.g., in modules with stale
+/// bounds, fall back to a hash map.
+#[cfg(feature = "access-analysis")]
+struct IdMap<'a, T> {
+    bound: u32,
+    /// Indices into `values` plus one, or zero if the ID is absent. Allocated
+    /// on the first insertion.
+    slots: ArenaVec<'a, u32>,
+    values: ArenaVec<'a, T>,
+    fallback: ArenaMap<'a, u32, T>,
+}
+#[cfg(feature = "access-analysis")]
+impl<'a, T> IdMap<'a, T> {
+    fn with_bound_in(bound: u32, arena: Arena<'a>) -> Self {
+        IdMap {
+            bound,
+            slots: arena.vec(),
+            values: arena.vec(),
+            fallback: arena.map(),
+        }
+    }
+    fn insert(&mut self, id: u32, x: T) -> Option<T> {
+        if id >= self.bound {
+            return self.fallback.insert(id, x);
+        }
+        if self.slots.is_empty() {
+            self.slots.resize(self.bound as usize, 0);
+        }
+        let slot = &mut self.slots[id as usize];
+        if *slot == 0 {
+            self.values.push(x);
+            *slot = self.values.len() as u32;
+            None
+        } else {
+            Some(std::mem::replace(&mut self.values[*slot as usize - 1], x))
+        }
+    }
+    fn get(&self, id: &u32) -> Option<&T> {
+        match self.slots.get(*id as usize) {
+            Some(0) => None,
+            Some(slot) => Some(&self.values[*slot as usize - 1]),
+            None => self.fallback.get(id),
+        }
+    }
+}
+
+/// Parameters of a function and the arguments it passes to its callees.
+/// Resources can be passed to functions by pointer parameters, in which case
+/// the function accesses the parameters instead of the resource variables.
+#[derive(Default)]
+struct FunctionLinkage {
+    params: Vec<InstrId>,
+    /// Callee and arguments of each call. Arguments pointing into resource
+    /// variables are resolved to the variables.
+    calls: Vec<(FunctionId, Vec<InstrId>)>,
+}
+
+#[cfg(feature = "access-analysis")]
+pub struct FunctionInspector<'a> {
     cur_func: Option<(FunctionId, Function)>,
-    access_chain_map: HashMap<VariableId, VariableId>,
+    /// Parameters and calls of the current function.
+    cur_linkage: FunctionLinkage,
+    access_chain_map: IdMap<'a, VariableId>,
+    /// Pointers into input variables and the components they point to.
+    input_ptrs: IdMap<'a, (VariableId, u32)>,
+    /// Values loaded from entire vector input variables, whose components are
+    /// yet to be extracted.
+    input_vals: ArenaMap<'a, InstrId, VariableId>,
+    /// Images, samplers and sampled images loaded from resource variables in
+    /// the current function.
+    rsc_vals: ArenaMap<'a, InstrId, VariableId>,
+    /// Components of input variables read by the current function.
+    input_reads: ArenaMap<'a, VariableId, u32>,
+    /// Pointers into push constant variables, see [`PushConstantPointer`].
+    push_const_ptrs: IdMap<'a, PushConstantPointer>,
+    /// Byte ranges of push constant variables read by the current function.
+    push_const_reads: ArenaMap<'a, VariableId, (usize, usize)>,
+    /// Physical storage buffer pointers.
+    device_ptrs: ArenaSet<'a, InstrId>,
+    /// Texel pointers into image variables, for image atomics.
+    texel_ptrs: ArenaMap<'a, InstrId, VariableId>,
+    /// Pointers to `PointSize` members of built-in blocks.
+    point_size_ptrs: ArenaSet<'a, InstrId>,
+    /// Variables and parameters written by the current function.
+    written_vars: ArenaSet<'a, VariableId>,
+}
+/// A pointer into a push constant variable, pointing to bytes
+/// `[offset, end)` of the block.
+#[cfg(feature = "access-analysis")]
+struct PushConstantPointer {
+    var_id: VariableId,
+    offset: usize,
+    end: usize,
+    /// Type of the pointee, or `None` if the pointee is not statically known
+    /// and further indexing can't narrow the range.
+    ty: Option<Type>,
+}
+/// Resource accesses are not analyzed without the `access-analysis` feature,
+/// and all resources are considered referenced by every entry point.
+#[cfg(not(feature = "access-analysis"))]
+pub struct FunctionInspector<'a> {
+    _marker: std::marker::PhantomData<&'a ()>,
 }
-impl FunctionInspector {
+#[cfg(not(feature = "access-analysis"))]
+impl<'a> FunctionInspector<'a> {
     pub fn new() -> Self {
+        Self::with_id_bound(0)
+    }
+    pub fn with_id_bound(id_bound: u32) -> Self {
+        Self::with_id_bound_in(id_bound, Arena::default())
+    }
+    pub(crate) fn with_id_bound_in(_: u32, _: Arena<'a>) -> Self {
         Self {
-            cur_func: None,
-            access_chain_map: HashMap::default(),
+            _marker: std::marker::PhantomData,
         }
     }
 }
-impl Inspector for FunctionInspector {
-    fn inspect(&mut self, itm: &mut ReflectIntermediate<'_>, instr: &Instr) -> Result<()> {
-        let opcode = instr.op();
-        match opcode {
-            Op::Function => {
-                let op = OpFunction::try_from(instr)?;
-                let func_id = op.func_id;
-                self.cur_func = Some((func_id, Function::default()));
+#[cfg(not(feature = "access-analysis"))]
+impl Inspector for FunctionInspector<'_> {
+    fn inspect(&mut self, _: &mut ReflectIntermediate<'_>, _: &Instr) -> Result<()> {
+        Ok(())
+    }
+}
+#[cfg(feature = "access-analysis")]
+impl<'a> FunctionInspector<'a> {
+    pub fn new() -> Self {
+        Self::with_id_bound(0)
+    }
+    /// Inspector looking up the IDs below `id_bound` in dense tables rather
+    /// than hash maps.
+    pub fn with_id_bound(id_bound: u32) -> Self {
+        Self::with_id_bound_in(id_bound, Arena::default())
+    }
+    pub(crate) fn with_id_bound_in(id_bound: u32, arena: Arena<'a>) -> Self {
+        Self {
+            cur_func: None,
+            cur_linkage: FunctionLinkage::default(),
+            access_chain_map: IdMap::with_bound_in(id_bound, arena),
+            input_ptrs: IdMap::with_bound_in(id_bound, arena),
+            input_vals: arena.map(),
+            rsc_vals: arena.map(),
+            input_reads: arena.map(),
+            push_const_ptrs: IdMap::with_bound_in(id_bound, arena),
+            push_const_reads: arena.map(),
+            device_ptrs: arena.set(),
+            texel_ptrs: arena.map(),
+            point_size_ptrs: arena.set(),
+            written_vars: arena.set(),
+        }
+    }
+
+    fn mark_input_read(&mut self, var_id: VariableId, comp_mask: u32) {
+        *self.input_reads.entry(var_id).or_default() |= comp_mask;
+    }
+    /// Track how input variables are read so that the components that are
+    /// never used can be reported. Only vector components extracted by
+    /// constant indices are tracked; inputs are otherwise conservatively
+    /// considered entirely read.
+    fn inspect_input_reads(&mut self, itm: &ReflectIntermediate<'_>, instr: &Instr) -> Result<()> {
+        match instr.op() {
+            Op::AccessChain | Op::InBoundsAccessChain => {
+                let operands = decode_operands(instr)?;
+                if let [Operand::Id(_), Operand::Id(ptr_id), Operand::Id(base_id), idxs @ ..] =
+                    operands.as_slice()
+                {
+                    if let Some(ty) = input_var_ty(itm, *base_id) {
+                        let comp_mask = match (ty, idxs) {
+                            (Type::Vector(_), [Operand::Id(idx_id)]) => {
+                                match itm.interp.get_value(*idx_id) {
+                                    Ok(ConstantValue::S32(x)) if (0..32).contains(x) => 1 << *x,
+                                    Ok(ConstantValue::U32(x)) if *x < 32 => 1 << *x,
+                                    _ => !0,
+                                }
+                            }
+                            _ => !0,
+                        };
+                        self.input_ptrs.insert(*ptr_id, (*base_id, comp_mask));
+                    } else if let Some(&(var_id, _)) = self.input_ptrs.get(base_id) {
+                        self.input_ptrs.insert(*ptr_id, (var_id, !0));
+                    }
+                }
             }
-            Op::FunctionEnd => {
-                if let Some((func_id, func)) = self.cur_func.take() {
-                    itm.func_reg.set(func_id, func)?;
-                } else {
-                    return Err(anyhow!("unexpected OpFunctionEnd"));
+            Op::Load => {
+                let op = OpLoad::try_from(instr)?;
+                if let Some(&(var_id, comp_mask)) = self.input_ptrs.get(&op.pointer_id) {
+                    self.mark_input_read(var_id, comp_mask);
+                } else if let Some(ty) = input_var_ty(itm, op.pointer_id) {
+                    if let Type::Vector(_) = ty {
+                        self.input_vals.insert(op.result_id, op.pointer_id);
+                    } else {
+                        self.mark_input_read(op.pointer_id, !0);
+                    }
                 }
-                self.cur_func = None;
+                return Ok(());
             }
-            Op::FunctionCall => {
-                let op = OpFunctionCall::try_from(instr)?;
-                if let Some((_, func)) = self.cur_func.as_mut() {
-                    func.callees.insert(op.func_id);
-                } else {
-                    return Err(anyhow!("unexpected OpFunctionCall"));
+            Op::CompositeExtract => {
+                let operands = decode_operands(instr)?;
+                if let [_, _, Operand::Id(val_id), Operand::Literal(idx), ..] = operands.as_slice()
+                {
+                    if let Some(&var_id) = self.input_vals.get(val_id) {
+                        let comp_mask = if *idx < 32 { 1 << *idx } else { !0 };
+                        self.mark_input_read(var_id, comp_mask);
+                    }
                 }
+                return Ok(());
             }
-            _ => {
-                if let Some((_func_id, func)) = self.cur_func.as_mut() {
-                    let op = instr.op();
-                    if op == Op::AccessChain {
-                        let op = OpAccessChain::try_from(instr)?;
-                        if self
-                            .access_chain_map
-                            .insert(op.var_id, op.accessed_var_id)
-                            .is_some()
-                        {
-                            return Err(anyhow!("duplicate access chain at a same id"));
-                        }
-                    } else if op == Op::Load || is_atomic_load_op(op) {
-                        let op = OpLoad::try_from(instr)?;
-                        let mut var_id = op.var_id;
-                        // Resolve access chain.
-                        if let Some(&x) = self.access_chain_map.get(&var_id) {
-                            var_id = x
-                        }
-                        func.accessed_vars.insert(var_id);
-                    } else if op == Op::Store || is_atomic_store_op(op) {
-                        let op = OpStore::try_from(instr)?;
-                        let mut var_id = op.var_id;
-                        // Resolve access chain.
-                        if let Some(&x) = self.access_chain_map.get(&var_id) {
-                            var_id = x
+            Op::VectorShuffle => {
+                let operands = decode_operands(instr)?;
+                if let [_, _, Operand::Id(val_id1), Operand::Id(val_id2), comps @ ..] =
+                    operands.as_slice()
+                {
+                    let var_id1 = self.input_vals.get(val_id1).copied();
+                    let var_id2 = self.input_vals.get(val_id2).copied();
+                    // The number of components of the first vector is only
+                    // known if it's loaded from an input variable.
+                    let ncomp1 = var_id1
+                        .and_then(|x| input_var_ty(itm, x))
+                        .and_then(|x| match x {
+                            Type::Vector(x) => Some(x.nscalar),
+                            _ => None,
+                        });
+                    for comp in comps {
+                        let comp = match comp {
+                            // Undefined components are `0xFFFFFFFF`.
+                            Operand::Literal(x) if *x != u32::MAX => *x,
+                            _ => continue,
+                        };
+                        match (var_id1, var_id2, ncomp1) {
+                            (Some(var_id1), _, Some(ncomp1)) if comp < ncomp1 => {
+                                self.mark_input_read(var_id1, 1 << comp);
+                            }
+                            (_, Some(var_id2), Some(ncomp1)) => {
+                                let comp_mask = 1u32.checked_shl(comp - ncomp1).unwrap_or(!0);
+                                self.mark_input_read(var_id2, comp_mask);
+                            }
+                            (_, Some(var_id2), None) => self.mark_input_read(var_id2, !0),
+                            _ => {}
                         }
-                        func.accessed_vars.insert(var_id);
                     }
-                } else {
-                    return Err(anyhow!("unexpected opcode {:?}", instr.op()));
                 }
+                return Ok(());
             }
+            _ => {}
         }
-        Ok(())
+        // Loaded input values used in any other way are entirely read.
+        if !self.input_vals.is_empty() {
+            let mut var_ids = Vec::new();
+            let _ = visit_operands(instr, |x| {
+                if let Operand::Id(id) = x {
+                    if let Some(&var_id) = self.input_vals.get(&id) {
+                        var_ids.push(var_id);
+                    }
+                }
+            });
+            for var_id in var_ids {
+                self.mark_input_read(var_id, !0);
+            }
+        }
+        Ok(())
+    }
+
+    fn mark_push_const_read(&mut self, var_id: VariableId, offset: usize, end: usize) {
+        mark_push_const_range(&mut self.push_const_reads, var_id, (offset, end));
+    }
+    /// Track the bytes of push constant variables read through access chains
+    /// so that tight push constant ranges can be reported. Indices into
+    /// arrays and vectors that are not constants, and indices into matrices,
+    /// select the entire array, vector or matrix. Entire variables loaded or
+    /// copied are marked with an unbounded range which is clamped to the
+    /// block members later.
+    fn inspect_push_const_reads(
+        &mut self,
+        itm: &ReflectIntermediate<'_>,
+        instr: &Instr,
+    ) -> Result<()> {
+        match instr.op() {
+            Op::AccessChain | Op::InBoundsAccessChain => {
+                let op = OpAccessChain::try_from(instr)?;
+                let base = match self.push_const_ptrs.get(&op.base_id) {
+                    Some(x) => (x.var_id, x.offset, x.end, x.ty.clone()),
+                    None => match push_const_var_ty(itm, op.base_id) {
+                        Some(ty) => (op.base_id, 0, usize::MAX, Some(ty.clone())),
+                        None => return Ok(()),
+                    },
+                };
+                let (var_id, mut offset, mut end, mut ty) = base;
+                for idx_id in op.index_ids.iter() {
+                    let cur_ty = match ty.take() {
+                        Some(x) => x,
+                        None => break,
+                    };
+                    let idx = match itm.interp.get_value(*idx_id) {
+                        Ok(ConstantValue::S32(x)) => usize::try_from(*x).ok(),
+                        Ok(ConstantValue::U32(x)) => Some(*x as usize),
+                        Ok(ConstantValue::S64(x)) => usize::try_from(*x).ok(),
+                        Ok(ConstantValue::U64(x)) => usize::try_from(*x).ok(),
+                        _ => None,
+                    };
+                    let elem = match (&cur_ty, idx) {
+                        (Type::Struct(x), Some(i)) => x
+                            .members
+                            .get(i)
+                            .and_then(|x| Some((x.offset?, x.ty.clone()))),
+                        (Type::Array(x), Some(i)) => {
+                            x.stride.map(|stride| (stride * i, (*x.element_ty).clone()))
+                        }
+                        (Type::Vector(x), Some(i)) => {
+                            let scalar_ty = Type::Scalar(x.scalar_ty.clone());
+                            scalar_ty.layout_nbyte().map(|nbyte| (nbyte * i, scalar_ty))
+                        }
+                        _ => None,
+                    };
+                    match elem {
+                        Some((elem_offset, elem_ty)) => {
+                            offset += elem_offset;
+                            end = elem_ty
+                                .layout_nbyte()
+                                .map_or(usize::MAX, |nbyte| offset + nbyte);
+                            ty = Some(elem_ty);
+                        }
+                        // The range of the enclosing type is kept.
+                        None => break,
+                    }
+                }
+                let ptr = PushConstantPointer {
+                    var_id,
+                    offset,
+                    end,
+                    ty,
+                };
+                self.push_const_ptrs.insert(op.result_id, ptr);
+            }
+            Op::Load => {
+                let op = OpLoad::try_from(instr)?;
+                if let Some(ptr) = self.push_const_ptrs.get(&op.pointer_id) {
+                    let (var_id, offset, end) = (ptr.var_id, ptr.offset, ptr.end);
+                    self.mark_push_const_read(var_id, offset, end);
+                } else if push_const_var_ty(itm, op.pointer_id).is_some() {
+                    self.mark_push_const_read(op.pointer_id, 0, usize::MAX);
+                }
+            }
+            Op::CopyMemory | Op::CopyMemorySized => {
+                let operands = decode_operands(instr)?;
+                if let [_, Operand::Id(src_id), ..] = operands.as_slice() {
+                    if let Some(ptr) = self.push_const_ptrs.get(src_id) {
+                        let (var_id, offset, end) = (ptr.var_id, ptr.offset, ptr.end);
+                        self.mark_push_const_read(var_id, offset, end);
+                    } else if push_const_var_ty(itm, *src_id).is_some() {
+                        self.mark_push_const_read(*src_id, 0, usize::MAX);
+                    }
+                }
+            }
+            // Pointers escaping through any other instruction, e.g., passed
+            // to functions or copied by `OpCopyObject`, can read the entire
+            // block.
+            _ => {
+                let mut var_ids = Vec::new();
+                visit_id_operands(instr, |id| {
+                    if let Some(ptr) = self.push_const_ptrs.get(&id) {
+                        var_ids.push(ptr.var_id);
+                    } else if push_const_var_ty(itm, id).is_some() {
+                        var_ids.push(id);
+                    }
+                });
+                for var_id in var_ids {
+                    self.mark_push_const_read(var_id, 0, usize::MAX);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+#[cfg(feature = "access-analysis")]
+impl FunctionInspector<'_> {
+    /// Track the variables written by stores, atomics and image writes to
+    /// find the entry points writing external memory. Stores through
+    /// physical storage buffer pointers are recorded on the function.
+    fn inspect_external_writes(
+        &mut self,
+        itm: &mut ReflectIntermediate<'_>,
+        func_id: FunctionId,
+        instr: &Instr,
+    ) -> Result<()> {
+        let op = instr.op();
+        let ptr_id = match op {
+            Op::AccessChain
+            | Op::InBoundsAccessChain
+            | Op::PtrAccessChain
+            | Op::ConvertUToPtr
+            | Op::Bitcast
+            | Op::CopyObject
+            | Op::Load => {
+                let operands = instr.operands().collect::<Vec<_>>();
+                if let [ty_id, result_id, ..] = operands.as_slice() {
+                    if let Ok(Type::DevicePointer(_)) = itm.ty_reg.get(*ty_id) {
+                        self.device_ptrs.insert(*result_id);
+                    }
+                }
+                return Ok(());
+            }
+            Op::ImageTexelPointer => {
+                let operands = instr.operands().collect::<Vec<_>>();
+                if let [_, result_id, img_id, ..] = operands.as_slice() {
+                    let var_id = self
+                        .access_chain_map
+                        .get(img_id)
+                        .copied()
+                        .unwrap_or(*img_id);
+                    self.texel_ptrs.insert(*result_id, var_id);
+                }
+                return Ok(());
+            }
+            Op::ImageWrite => {
+                let img_id = instr.operands().next();
+                if let Some(&var_id) = img_id.and_then(|x| self.rsc_vals.get(&x)) {
+                    self.written_vars.insert(var_id);
+                }
+                return Ok(());
+            }
+            // The written pointer is the first operand of stores and memory
+            // copies, and follows the result type and the result of atomics
+            // returning values.
+            Op::Store | Op::CooperativeMatrixStoreKHR | Op::CopyMemory | Op::CopyMemorySized => {
+                instr.operands().next()
+            }
+            _ if is_atomic_store_op(op) => instr.operands().next(),
+            _ if is_atomic_load_op(op) && op != Op::AtomicLoad => instr.operands().nth(2),
+            _ => return Ok(()),
+        };
+        let ptr_id = ptr_id.ok_or_else(|| anyhow!("malformed {:?}", op))?;
+        if self.device_ptrs.contains(&ptr_id) {
+            itm.device_write_func_ids.insert(func_id);
+        } else if let Some(&var_id) = self.texel_ptrs.get(&ptr_id) {
+            self.written_vars.insert(var_id);
+        } else {
+            let var_id = self
+                .access_chain_map
+                .get(&ptr_id)
+                .copied()
+                .unwrap_or(ptr_id);
+            self.written_vars.insert(var_id);
+        }
+        Ok(())
+    }
+    /// Track stores to the `PointSize` built-in, either to a variable
+    /// decorated `BuiltIn PointSize` or to the member of a built-in block.
+    /// Stores to entire built-in blocks are considered to write all the
+    /// members.
+    fn inspect_point_size_writes(
+        &mut self,
+        itm: &mut ReflectIntermediate<'_>,
+        func_id: FunctionId,
+        instr: &Instr,
+    ) -> Result<()> {
+        match instr.op() {
+            Op::AccessChain | Op::InBoundsAccessChain => {
+                let op = OpAccessChain::try_from(instr)?;
+                let member_idx = match itm.point_size_members.get(&op.base_id) {
+                    Some(x) => *x,
+                    None => return Ok(()),
+                };
+                // Per-vertex blocks are arrayed in tessellation, geometry and
+                // mesh shaders.
+                let is_arrayed = match itm.var_reg.get(op.base_id) {
+                    Ok(x) => matches!(*x.ptr_ty.pointee_ty, Type::Array(_)),
+                    Err(_) => false,
+                };
+                let idx_id = op.index_ids.get(is_arrayed as usize);
+                let idx = idx_id.and_then(|x| itm.interp.get_value(*x).ok());
+                let is_point_size = match idx {
+                    Some(ConstantValue::U32(x)) => *x == member_idx,
+                    Some(ConstantValue::S32(x)) => *x as u32 == member_idx,
+                    _ => false,
+                };
+                if is_point_size {
+                    self.point_size_ptrs.insert(op.result_id);
+                }
+            }
+            Op::Store | Op::CopyMemory | Op::CopyMemorySized => {
+                // The target pointer is the first operand.
+                let ptr_id = instr
+                    .operands()
+                    .next()
+                    .ok_or_else(|| anyhow!("malformed {:?}", instr.op()))?;
+                let is_point_size = self.point_size_ptrs.contains(&ptr_id)
+                    || itm.point_size_members.contains_key(&ptr_id)
+                    || itm
+                        .deco_reg
+                        .get_u32(ptr_id, spirv::Decoration::BuiltIn)
+                        .ok()
+                        == Some(spirv::BuiltIn::PointSize as u32);
+                if is_point_size {
+                    itm.point_size_write_func_ids.insert(func_id);
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+    /// Track the elements of descriptor arrays referenced by access chains.
+    /// Arrays indexed by values other than constants, or loaded or passed to
+    /// functions as a whole, are considered to be entirely referenced.
+    fn inspect_desc_elems(
+        &mut self,
+        itm: &mut ReflectIntermediate<'_>,
+        func_id: FunctionId,
+        instr: &Instr,
+    ) -> Result<()> {
+        let is_desc_arr = |id: InstrId| match itm.var_reg.get(id) {
+            Ok(x) => {
+                matches!(*x.ptr_ty.pointee_ty, Type::Array(_))
+                    && matches!(
+                        x.ptr_ty.store_cls,
+                        StorageClass::UniformConstant
+                            | StorageClass::Uniform
+                            | StorageClass::StorageBuffer
+                    )
+            }
+            Err(_) => false,
+        };
+        let mut marks = Vec::new();
+        match instr.op() {
+            Op::AccessChain | Op::InBoundsAccessChain => {
+                let op = OpAccessChain::try_from(instr)?;
+                if is_desc_arr(op.base_id) {
+                    // Specialization constants can be overridden so they don't
+                    // bound the elements.
+                    let idx = op.index_ids.first().and_then(|x| {
+                        if itm.spec_const_deps.get(x).is_some_and(|x| !x.is_empty()) {
+                            return None;
+                        }
+                        match itm.interp.get_value(*x).ok()? {
+                            ConstantValue::U32(x) => Some(*x),
+                            ConstantValue::S32(x) if *x >= 0 => Some(*x as u32),
+                            ConstantValue::U64(x) => u32::try_from(*x).ok(),
+                            ConstantValue::S64(x) => u32::try_from(*x).ok(),
+                            _ => None,
+                        }
+                    });
+                    marks.push((op.base_id, idx));
+                }
+            }
+            // Descriptor arrays used in any other way, e.g., loaded entirely,
+            // copied, passed to functions or indexed by `OpPtrAccessChain`,
+            // can reach any of their elements.
+            _ => visit_id_operands(instr, |id| {
+                if is_desc_arr(id) {
+                    marks.push((id, None));
+                }
+            }),
+        }
+        for (var_id, idx) in marks {
+            let arena = itm.arena;
+            itm.desc_elems
+                .entry(func_id)
+                .or_insert_with(|| DescriptorElements(arena.map()))
+                .mark(var_id, idx);
+        }
+        Ok(())
+    }
+    /// Track images and acceleration structures loaded from resource
+    /// variables to find the variables accessed with the `MinLod` image
+    /// operand, sparse image instructions, ray tracing or ray queries.
+    fn inspect_image_ops(
+        &mut self,
+        itm: &mut ReflectIntermediate<'_>,
+        instr: &Instr,
+    ) -> Result<()> {
+        match instr.op() {
+            Op::Load => {
+                let op = OpLoad::try_from(instr)?;
+                let var_id = self
+                    .access_chain_map
+                    .get(&op.pointer_id)
+                    .copied()
+                    .unwrap_or(op.pointer_id);
+                let is_rsc = matches!(
+                    itm.var_reg.get(var_id),
+                    Ok(x) if x.store_cls == StorageClass::UniformConstant
+                );
+                if is_rsc {
+                    self.rsc_vals.insert(op.result_id, var_id);
+                }
+            }
+            // Images extracted from or combined into sampled images are
+            // still accesses to the image variables.
+            Op::SampledImage | Op::Image | Op::CopyObject => {
+                let operands = instr.operands().collect::<Vec<_>>();
+                if let [_, result_id, src_id, ..] = operands.as_slice() {
+                    if let Some(&var_id) = self.rsc_vals.get(src_id) {
+                        self.rsc_vals.insert(*result_id, var_id);
+                    }
+                }
+            }
+            Op::ImageSampleImplicitLod
+            | Op::ImageSampleExplicitLod
+            | Op::ImageSampleDrefImplicitLod
+            | Op::ImageSampleDrefExplicitLod
+            | Op::ImageSampleProjImplicitLod
+            | Op::ImageSampleProjExplicitLod
+            | Op::ImageSampleProjDrefImplicitLod
+            | Op::ImageSampleProjDrefExplicitLod
+            | Op::ImageFetch
+            | Op::ImageGather
+            | Op::ImageDrefGather
+            | Op::ImageSparseSampleImplicitLod
+            | Op::ImageSparseSampleExplicitLod
+            | Op::ImageSparseSampleDrefImplicitLod
+            | Op::ImageSparseSampleDrefExplicitLod
+            | Op::ImageSparseSampleProjImplicitLod
+            | Op::ImageSparseSampleProjExplicitLod
+            | Op::ImageSparseSampleProjDrefImplicitLod
+            | Op::ImageSparseSampleProjDrefExplicitLod
+            | Op::ImageSparseFetch
+            | Op::ImageSparseGather
+            | Op::ImageSparseDrefGather
+            | Op::ImageSparseRead => {
+                let operands = decode_operands(instr)?;
+                let var_id = match operands.as_slice() {
+                    [_, _, Operand::Id(img_id), ..] => self.rsc_vals.get(img_id).copied(),
+                    _ => None,
+                };
+                let (var_id, func_id) = match (var_id, &self.cur_func) {
+                    (Some(x), Some((func_id, _))) => (x, *func_id),
+                    _ => return Ok(()),
+                };
+                let uses_min_lod = operands.iter().any(|x| match x {
+                    Operand::Enum {
+                        kind: OperandKind::ImageOperands,
+                        value,
+                    } => value & spirv::ImageOperands::MIN_LOD.bits() != 0,
+                    _ => false,
+                });
+                if uses_min_lod {
+                    mark_func_var(itm.arena, &mut itm.min_lod_var_ids, func_id, var_id);
+                }
+                if is_sparse_img_op(instr.op()) {
+                    mark_func_var(itm.arena, &mut itm.sparse_var_ids, func_id, var_id);
+                }
+            }
+            // Acceleration structures are loaded from resource variables
+            // the same way as images.
+            Op::TraceRayKHR | Op::TraceNV | Op::TraceRayMotionNV => {
+                let accel_id = instr.operands().next();
+                let var_id = accel_id.and_then(|x| self.rsc_vals.get(&x));
+                if let (Some(&var_id), Some((func_id, _))) = (var_id, &self.cur_func) {
+                    mark_func_var(itm.arena, &mut itm.trace_ray_var_ids, *func_id, var_id);
+                }
+            }
+            Op::RayQueryInitializeKHR => {
+                let accel_id = instr.operands().nth(1);
+                let var_id = accel_id.and_then(|x| self.rsc_vals.get(&x));
+                if let (Some(&var_id), Some((func_id, _))) = (var_id, &self.cur_func) {
+                    mark_func_var(itm.arena, &mut itm.ray_query_var_ids, *func_id, var_id);
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+/// Check if `op` accesses an image with sparse residency feedback.
+#[cfg(feature = "access-analysis")]
+fn is_sparse_img_op(op: Op) -> bool {
+    matches!(
+        op,
+        Op::ImageSparseSampleImplicitLod
+            | Op::ImageSparseSampleExplicitLod
+            | Op::ImageSparseSampleDrefImplicitLod
+            | Op::ImageSparseSampleDrefExplicitLod
+            | Op::ImageSparseSampleProjImplicitLod
+            | Op::ImageSparseSampleProjExplicitLod
+            | Op::ImageSparseSampleProjDrefImplicitLod
+            | Op::ImageSparseSampleProjDrefExplicitLod
+            | Op::ImageSparseFetch
+            | Op::ImageSparseGather
+            | Op::ImageSparseDrefGather
+            | Op::ImageSparseRead
+    )
+}
+/// Check if `op` computes derivatives, explicitly or implicitly to select the
+/// level of detail to sample.
+#[cfg(feature = "access-analysis")]
+fn is_derivative_op(op: Op) -> bool {
+    matches!(
+        op,
+        Op::DPdx
+            | Op::DPdy
+            | Op::Fwidth
+            | Op::DPdxFine
+            | Op::DPdyFine
+            | Op::FwidthFine
+            | Op::DPdxCoarse
+            | Op::DPdyCoarse
+            | Op::FwidthCoarse
+            | Op::ImageSampleImplicitLod
+            | Op::ImageSampleDrefImplicitLod
+            | Op::ImageSampleProjImplicitLod
+            | Op::ImageSampleProjDrefImplicitLod
+            | Op::ImageSparseSampleImplicitLod
+            | Op::ImageSparseSampleDrefImplicitLod
+            | Op::ImageSparseSampleProjImplicitLod
+            | Op::ImageSparseSampleProjDrefImplicitLod
+            | Op::ImageQueryLod
+    )
+}
+/// Get the type of input variable `var_id`, or `None` if it's not an input
+/// variable.
+#[cfg(feature = "access-analysis")]
+fn input_var_ty<'b>(itm: &'b ReflectIntermediate<'_>, var_id: VariableId) -> Option<&'b Type> {
+    let var_alloc = itm.var_reg.get(var_id).ok()?;
+    if var_alloc.store_cls == StorageClass::Input {
+        Some(&var_alloc.ptr_ty.pointee_ty)
+    } else {
+        None
+    }
+}
+/// Get the type of push constant variable `var_id`, or `None` if it's not a
+/// push constant variable.
+#[cfg(feature = "access-analysis")]
+fn push_const_var_ty<'b>(itm: &'b ReflectIntermediate<'_>, var_id: VariableId) -> Option<&'b Type> {
+    let var_alloc = itm.var_reg.get(var_id).ok()?;
+    if var_alloc.store_cls == StorageClass::PushConstant {
+        Some(&var_alloc.ptr_ty.pointee_ty)
+    } else {
+        None
+    }
+}
+/// Check if the result `id` is decorated `NonUniform`.
+#[cfg(feature = "access-analysis")]
+fn is_nonuniform(itm: &ReflectIntermediate<'_>, id: u32) -> bool {
+    itm.deco_reg.get(id, spirv::Decoration::NonUniform).is_ok()
+}
+#[cfg(feature = "access-analysis")]
+impl Inspector for FunctionInspector<'_> {
+    fn inspect(&mut self, itm: &mut ReflectIntermediate<'_>, instr: &Instr) -> Result<()> {
+        let opcode = instr.op();
+        match opcode {
+            Op::Function => {
+                let op = OpFunction::try_from(instr)?;
+                let func_id = op.result_id;
+                self.cur_func = Some((func_id, Function::default()));
+            }
+            Op::FunctionEnd => {
+                if let Some((func_id, func)) = self.cur_func.take() {
+                    itm.func_reg.set(func_id, func)?;
+                    // The tables are copied into the arena of `itm`, which
+                    // can be different from the arena of the inspector.
+                    let mut input_reads = itm.arena.map();
+                    input_reads.extend(self.input_reads.drain());
+                    itm.input_reads.insert(func_id, input_reads);
+                    let mut push_const_reads = itm.arena.map();
+                    push_const_reads.extend(self.push_const_reads.drain());
+                    itm.push_const_reads.insert(func_id, push_const_reads);
+                    let mut written_vars = itm.arena.set();
+                    written_vars.extend(self.written_vars.drain());
+                    itm.written_vars.insert(func_id, written_vars);
+                    let linkage = std::mem::take(&mut self.cur_linkage);
+                    itm.func_linkages.insert(func_id, linkage);
+                    self.input_vals.clear();
+                    self.rsc_vals.clear();
+                    self.device_ptrs.clear();
+                    self.texel_ptrs.clear();
+                } else {
+                    return Err(anyhow!("unexpected OpFunctionEnd"));
+                }
+                self.cur_func = None;
+            }
+            Op::FunctionParameter => {
+                let op = OpFunctionParameter::try_from(instr)?;
+                if self.cur_func.is_some() {
+                    if let Ok(Type::DevicePointer(_)) = itm.ty_reg.get(op.result_type_id) {
+                        self.device_ptrs.insert(op.result_id);
+                    }
+                    self.cur_linkage.params.push(op.result_id);
+                } else {
+                    return Err(anyhow!("unexpected OpFunctionParameter"));
+                }
+            }
+            Op::FunctionCall => {
+                if let Some(func_id) = self.cur_func.as_ref().map(|x| x.0) {
+                    self.inspect_push_const_reads(itm, instr)?;
+                    self.inspect_desc_elems(itm, func_id, instr)?;
+                }
+                let op = OpFunctionCall::try_from(instr)?;
+                if let Some((_, func)) = self.cur_func.as_mut() {
+                    func.callees.insert(op.function_id);
+                    // Resolve access chain.
+                    let arg_ids = op
+                        .argument_ids
+                        .iter()
+                        .map(|&x| self.access_chain_map.get(&x).copied().unwrap_or(x))
+                        .collect();
+                    self.cur_linkage.calls.push((op.function_id, arg_ids));
+                } else {
+                    return Err(anyhow!("unexpected OpFunctionCall"));
+                }
+            }
+            _ => {
+                if let Some(func_id) = self.cur_func.as_ref().map(|x| x.0) {
+                    self.inspect_input_reads(itm, instr)?;
+                    self.inspect_push_const_reads(itm, instr)?;
+                    self.inspect_image_ops(itm, instr)?;
+                    self.inspect_external_writes(itm, func_id, instr)?;
+                    self.inspect_point_size_writes(itm, func_id, instr)?;
+                    self.inspect_desc_elems(itm, func_id, instr)?;
+                }
+                if let Some((func_id, func)) = self.cur_func.as_mut() {
+                    let op = instr.op();
+                    if is_derivative_op(op) {
+                        itm.derivative_func_ids.insert(*func_id);
+                    }
+                    if op == Op::AccessChain {
+                        let op = OpAccessChain::try_from(instr)?;
+                        if self
+                            .access_chain_map
+                            .insert(op.result_id, op.base_id)
+                            .is_some()
+                        {
+                            return Err(anyhow!("duplicate access chain at a same id"));
+                        }
+                        // The first index selects the descriptor in a
+                        // descriptor array.
+                        let is_nonuniform = is_nonuniform(itm, op.result_id)
+                            || matches!(op.index_ids.first(), Some(x) if is_nonuniform(itm, *x));
+                        if is_nonuniform {
+                            mark_func_var(
+                                itm.arena,
+                                &mut itm.nonuniform_var_ids,
+                                *func_id,
+                                op.base_id,
+                            );
+                        }
+                    } else if op == Op::Load
+                        || is_atomic_load_op(op)
+                        || op == Op::CooperativeMatrixLoadKHR
+                        || op == Op::ImageTexelPointer
+                    {
+                        // The pointer follows the result type and the result.
+                        let operands = instr.operands().collect::<Vec<_>>();
+                        let (result_id, mut var_id) = match operands.as_slice() {
+                            [_, result_id, ptr_id, ..] => (*result_id, *ptr_id),
+                            _ => return Err(anyhow!("malformed {:?}", op)),
+                        };
+                        // Resolve access chain.
+                        if let Some(&x) = self.access_chain_map.get(&var_id) {
+                            var_id = x;
+                            // Images and samplers loaded from descriptor
+                            // arrays are decorated instead of the pointers.
+                            if is_nonuniform(itm, result_id) {
+                                mark_func_var(
+                                    itm.arena,
+                                    &mut itm.nonuniform_var_ids,
+                                    *func_id,
+                                    var_id,
+                                );
+                            }
+                        }
+                        func.accessed_vars.insert(var_id);
+                    } else if op == Op::Store
+                        || is_atomic_store_op(op)
+                        || op == Op::CooperativeMatrixStoreKHR
+                    {
+                        // The pointer is the first operand.
+                        let mut var_id = instr
+                            .operands()
+                            .next()
+                            .ok_or_else(|| anyhow!("malformed {:?}", op))?;
+                        // Resolve access chain.
+                        if let Some(&x) = self.access_chain_map.get(&var_id) {
+                            var_id = x
+                        }
+                        func.accessed_vars.insert(var_id);
+                    }
+                } else {
+                    return Err(anyhow!("unexpected opcode {:?}", instr.op()));
+                }
+            }
+        }
+        Ok(())
     }
 }
 
@@ -674,13 +2324,14 @@ pub fn reflect<'a, I: Inspector>(
     instrs: &mut Instrs<'a>,
     mut inspector: I,
 ) -> Result<Vec<EntryPoint>> {
+    let _span = trace_span!("reflect");
     itm.parse_global_declrs(instrs)?;
     itm.parse_functions(instrs, &mut inspector)?;
 
     itm.collect_entry_points()
 }
 
-fn make_desc_var(
+pub(crate) fn make_desc_var(
     deco_reg: &DecorationRegistry,
     name: Option<String>,
     var_id: VariableId,
@@ -700,7 +2351,7 @@ fn make_desc_var(
     };
 
     // Elevate image type to concrete storage/sampled image type.
-    let ty = match ty {
+    let mut ty = match ty {
         Type::Image(image_ty) => {
             if let Some(false) = image_ty.is_sampled {
                 // Guaranteed a storage image.
@@ -725,6 +2376,21 @@ fn make_desc_var(
         }
         _ => ty.clone(),
     };
+    // `NonWritable` and `NonReadable` on a buffer block variable apply to all
+    // of its members, so that the access types of members are as precise as
+    // they're decorated on the members.
+    if let Type::Struct(struct_ty) = &mut ty {
+        match deco_reg.get_access_ty_from_deco(var_id) {
+            Some(AccessType::ReadWrite) | None => {}
+            Some(var_access_ty) => {
+                for member in struct_ty.members.iter_mut() {
+                    if member.access_ty == AccessType::ReadWrite {
+                        member.access_ty = var_access_ty;
+                    }
+                }
+            }
+        }
+    }
 
     let desc_bind = deco_reg.get_var_desc_bind_or_default(var_id);
     let desc_ty = match &ty {
@@ -781,6 +2447,7 @@ fn make_var<'a>(
     name: Option<String>,
     var_id: VariableId,
     var_alloc: &VariableAlloc,
+    block_location: Option<InterfaceLocation>,
 ) -> Option<Variable> {
     let ptr_ty = &var_alloc.ptr_ty;
     let ty = &*ptr_ty.pointee_ty;
@@ -788,7 +2455,8 @@ fn make_var<'a>(
     // pointer.
     match ptr_ty.store_cls {
         StorageClass::Input => {
-            if let Ok(location) = deco_reg.get_var_location(var_id) {
+            let location = deco_reg.get_var_location(var_id).ok().or(block_location);
+            if let Some(location) = location {
                 let var = Variable::Input {
                     name,
                     location,
@@ -806,7 +2474,8 @@ fn make_var<'a>(
             }
         }
         StorageClass::Output => {
-            if let Ok(location) = deco_reg.get_var_location(var_id) {
+            let location = deco_reg.get_var_location(var_id).ok().or(block_location);
+            if let Some(location) = location {
                 let var = Variable::Output {
                     name,
                     location,
@@ -844,10 +2513,11 @@ impl<'a> ReflectIntermediate<'a> {
     pub fn parse_global_declrs(&mut self, instrs: &mut Instrs<'a>) -> Result<()> {
         // Don't change the order. See _2.4 Logical Layout of a Module_ of the
         // SPIR-V specification for more information.
+        let span = trace_span!("parse", nentry_point);
 
         // 1. All OpCapability instructions.
         while let Some(instr) = instrs.peek() {
-            if instr.op() == Op::Capability {
+            if decode_op(instr) == Some(Op::Capability) {
                 instrs.next()?;
             } else {
                 break;
@@ -855,7 +2525,9 @@ impl<'a> ReflectIntermediate<'a> {
         }
         // 2. Optional OpExtension instructions (extensions to SPIR-V).
         while let Some(instr) = instrs.peek() {
-            if instr.op() == Op::Extension {
+            if decode_op(instr) == Some(Op::Extension) {
+                let op = OpExtension::try_from(instr)?;
+                self.exts.insert(op.name);
                 instrs.next()?;
             } else {
                 break;
@@ -863,10 +2535,10 @@ impl<'a> ReflectIntermediate<'a> {
         }
         // 3. Optional OpExtInstImport instructions.
         while let Some(instr) = instrs.peek() {
-            if instr.op() == Op::ExtInstImport {
+            if decode_op(instr) == Some(Op::ExtInstImport) {
                 let op = OpExtInstImport::try_from(instr)?;
                 self.interp
-                    .import_ext_instr_set(op.instr_set_id, op.name.to_owned())?;
+                    .import_ext_instr_set(op.result_id, op.name.to_owned())?;
                 instrs.next()?;
             } else {
                 break;
@@ -876,16 +2548,19 @@ impl<'a> ReflectIntermediate<'a> {
         // NOTE: (penguinliong): We relax the requirement here for better
         // flexibility as a tool.
         if let Some(instr) = instrs.peek() {
-            if instr.op() == Op::MemoryModel {
+            if decode_op(instr) == Some(Op::MemoryModel) {
                 let op = OpMemoryModel::try_from(instr)?;
-                match op.addr_model {
-                    spirv::AddressingModel::Logical => {}
-                    spirv::AddressingModel::PhysicalStorageBuffer64 => {}
-                    _ => return Err(anyhow!("unsupported addressing model")),
+                // All addressing models are supported.
+                match op.addressing_model {
+                    spirv::AddressingModel::Logical
+                    | spirv::AddressingModel::Physical32
+                    | spirv::AddressingModel::Physical64
+                    | spirv::AddressingModel::PhysicalStorageBuffer64 => {}
                 }
-                match op.mem_model {
+                match op.memory_model {
                     spirv::MemoryModel::GLSL450 => {}
                     spirv::MemoryModel::Vulkan => {}
+                    spirv::MemoryModel::OpenCL => {}
                     _ => return Err(anyhow!("unsupported memory model")),
                 }
                 instrs.next()?;
@@ -893,21 +2568,20 @@ impl<'a> ReflectIntermediate<'a> {
         }
         // 5. All entry point declarations, using OpEntryPoint.
         while let Some(instr) = instrs.peek() {
-            if instr.op() == Op::EntryPoint {
+            if decode_op(instr) == Some(Op::EntryPoint) {
                 let op = OpEntryPoint::try_from(instr)?;
                 let entry_point_declr = EntryPointDeclaration {
-                    exec_model: op.exec_model,
+                    exec_model: op.execution_model,
                     name: op.name,
                     exec_modes: Default::default(),
+                    interface_ids: op.interface_ids,
                 };
-                use std::collections::hash_map::Entry;
-                match self.entry_point_declrs.entry(op.func_id) {
-                    Entry::Occupied(_) => {
-                        return Err(anyhow!("duplicate entry point at a same id"))
-                    }
-                    Entry::Vacant(e) => {
-                        e.insert(entry_point_declr);
-                    }
+                if self
+                    .entry_point_declrs
+                    .insert(op.entry_point_id, entry_point_declr)
+                    .is_some()
+                {
+                    return Err(anyhow!("duplicate entry point at a same id"));
                 }
                 instrs.next()?;
             } else {
@@ -917,28 +2591,17 @@ impl<'a> ReflectIntermediate<'a> {
         // 6. All execution-mode declarations, using OpExecutionMode or
         //    OpExecutionModeId.
         while let Some(instr) = instrs.peek() {
-            let op = instr.op();
-            match op {
-                Op::ExecutionMode | Op::ExecutionModeId => {
+            match decode_op(instr) {
+                Some(op @ Op::ExecutionMode) | Some(op @ Op::ExecutionModeId) => {
                     let mut operands = instr.operands();
-                    let operand_ctor = match op {
-                        Op::ExecutionMode => |x: &u32| ExecutionModeOperand::Literal(*x),
-                        Op::ExecutionModeId => |x: &u32| ExecutionModeOperand::Id(*x),
-                        _ => unreachable!(),
-                    };
-
                     let func_id = operands.read_u32()?;
-                    let exec_mode = spirv::ExecutionMode::from_u32(operands.read_u32()?)
-                        .ok_or_else(|| anyhow!("invalid execution mode"))?;
-                    let operands = operands
-                        .read_list()?
-                        .into_iter()
-                        .map(operand_ctor)
-                        .collect();
-                    let exec_mode_declr = ExecutionModeDeclaration {
-                        func_id,
+                    // Execution modes introduced by newer extensions are kept
+                    // raw rather than failing the reflection.
+                    let exec_mode = operands.read_u32()?;
+                    let exec_mode_declr = RawExecutionMode {
                         exec_mode,
-                        operands,
+                        operands: operands.read_list()?.to_owned(),
+                        is_id: op == Op::ExecutionModeId,
                     };
                     self.entry_point_declrs
                         .get_mut(&func_id)
@@ -956,62 +2619,137 @@ impl<'a> ReflectIntermediate<'a> {
         //      OpSourceContinued, without forward references.
         //   b. All OpName and all OpMemberName.
         //   c. All OpModuleProcessed instructions.
+        //
+        // `NameRegistry` keeps the first name of an ID, so names are resolved
+        // by the collision policy before they are registered.
+        let mut names = HashMap::<(u32, Option<u32>), &'a str>::default();
         while let Some(instr) = instrs.peek() {
-            match instr.op() {
-                Op::String
-                | Op::SourceExtension
-                | Op::Source
-                | Op::SourceContinued
-                | Op::ModuleProcessed => {
+            let (key, name) = match decode_op(instr) {
+                Some(Op::String)
+                | Some(Op::SourceExtension)
+                | Some(Op::Source)
+                | Some(Op::SourceContinued)
+                | Some(Op::ModuleProcessed) => {
                     instrs.next()?;
+                    continue;
                 }
-                Op::Name => {
+                Some(Op::Name) => {
                     let op = OpName::try_from(instr)?;
-                    if !op.name.is_empty() {
-                        // Ignore empty names.
-                        self.name_reg.set(op.target_id, op.name);
-                    }
-                    instrs.next()?;
+                    ((op.target_id, None), op.name)
                 }
-                Op::MemberName => {
+                Some(Op::MemberName) => {
                     let op = OpMemberName::try_from(instr)?;
-                    if !op.name.is_empty() {
-                        self.name_reg
-                            .set_member(op.target_id, op.member_idx, op.name);
-                    }
-                    instrs.next()?;
+                    ((op.type_id, Some(op.member)), op.name)
                 }
                 _ => break,
+            };
+            instrs.next()?;
+            // Ignore empty names.
+            if name.is_empty() {
+                continue;
+            }
+            match names.get_mut(&key) {
+                Some(old_name) if *old_name != name => match self.cfg.name_collision_policy {
+                    NameCollisionPolicy::Error => {
+                        let member = match key.1 {
+                            Some(member_idx) => format!(" member {}", member_idx),
+                            None => String::new(),
+                        };
+                        return Err(anyhow!(
+                            "name collision at id {}{}: `{}` vs `{}`",
+                            key.0,
+                            member,
+                            old_name,
+                            name
+                        ));
+                    }
+                    NameCollisionPolicy::FirstWins => {}
+                    NameCollisionPolicy::LastWins => *old_name = name,
+                },
+                Some(_) => {}
+                None => {
+                    names.insert(key, name);
+                }
             }
         }
+        for ((id, member_idx), name) in names {
+            match member_idx {
+                Some(member_idx) => self.name_reg.set_member(id, member_idx, name),
+                None => self.name_reg.set(id, name),
+            }
+        }
+        span.record("nentry_point", self.entry_point_declrs.len());
+        span.exit();
+
         // 8. All annotation instructions:
         //   a. All decoration instructions.
+        //
+        // `DecorationRegistry` rejects duplicate decorations, so decorations
+        // are resolved by the collision policy before they are registered.
+        let span = trace_span!("decos", ndeco);
+        let mut ndeco = 0;
+        let mut decos = HashMap::<(u32, Option<u32>, spirv::Decoration), &'a [u32]>::default();
         while let Some(instr) = instrs.peek() {
-            match instr.op() {
-                Op::Decorate => {
+            let (key, operands) = match decode_op(instr) {
+                Some(Op::Decorate) => {
                     let op = OpDecorate::try_from(instr)?;
-                    let deco = op.deco;
-                    self.deco_reg.set(op.target_id, deco, op.params)?;
-                    instrs.next()?;
+                    ((op.target_id, None, op.decoration), op.decoration_params)
                 }
-                Op::MemberDecorate => {
+                Some(Op::MemberDecorate) => {
                     let op = OpMemberDecorate::try_from(instr)?;
-                    let deco = op.deco;
-                    self.deco_reg
-                        .set_member(op.target_id, op.member_idx, deco, op.params)?;
-                    instrs.next()?;
+                    (
+                        (op.structure_type_id, Some(op.member), op.decoration),
+                        op.decoration_params,
+                    )
                 }
-                Op::DecorationGroup
-                | Op::GroupDecorate
-                | Op::GroupMemberDecorate
-                | Op::DecorateId
-                | Op::DecorateString
-                | Op::MemberDecorateString => {
+                Some(Op::DecorationGroup)
+                | Some(Op::GroupDecorate)
+                | Some(Op::GroupMemberDecorate)
+                | Some(Op::DecorateId)
+                | Some(Op::DecorateString)
+                | Some(Op::MemberDecorateString) => {
                     instrs.next()?;
+                    ndeco += 1;
+                    continue;
                 }
                 _ => break,
             };
+            instrs.next()?;
+            ndeco += 1;
+            match decos.get_mut(&key) {
+                Some(old_operands) if *old_operands != operands => {
+                    let policy = self.cfg.deco_collision_policy;
+                    let (kept, discarded) = match policy {
+                        DecorationCollisionPolicy::LastWins => (operands, *old_operands),
+                        _ => (*old_operands, operands),
+                    };
+                    let diag = Diagnostic::DecorationCollision {
+                        id: key.0,
+                        member_idx: key.1,
+                        deco: key.2,
+                        operands: kept.to_owned(),
+                        discarded: discarded.to_owned(),
+                    };
+                    if policy == DecorationCollisionPolicy::Error {
+                        return Err(anyhow!("{}", diag));
+                    }
+                    *old_operands = kept;
+                    self.module_diags.push(diag);
+                }
+                Some(_) => {}
+                None => {
+                    decos.insert(key, operands);
+                }
+            }
+        }
+        for ((id, member_idx, deco), operands) in decos {
+            match member_idx {
+                Some(member_idx) => self.deco_reg.set_member(id, member_idx, deco, operands)?,
+                None => self.deco_reg.set(id, deco, operands)?,
+            }
         }
+        span.record("ndeco", ndeco);
+        span.exit();
         // 9. All type declarations (OpTypeXXX instructions), all constant
         //    instructions, and all global variable declarations (all OpVariable
         //    instructions whose Storage Class is not Function). This is the
@@ -1021,23 +2759,45 @@ impl<'a> ReflectIntermediate<'a> {
         //    order. This section is the first section to allow use of:
         //   a. OpLine and OpNoLine debug information.
         //   b. Non-semantic instructions with OpExtInst.
+        let span = trace_span!("defs", nty, nconst, nvar);
+        let (mut nty, mut nconst, mut nvar) = (0, 0, 0);
         while let Some(instr) = instrs.peek() {
-            let opcode = instr.op();
+            let opcode = if let Some(x) = decode_op(instr) {
+                x
+            } else {
+                // Skip instructions unknown to us. They are most likely
+                // declaring types from extensions we don't support.
+                let diag = Diagnostic::UnknownDeclaration {
+                    opcode: instr.opcode(),
+                };
+                if !self.module_diags.contains(&diag) {
+                    self.module_diags.push(diag);
+                }
+                instrs.next()?;
+                continue;
+            };
             if let Op::Line | Op::NoLine | Op::ExtInst | Op::Undef = opcode {
                 instrs.next()?;
                 continue;
             }
             if is_ty_op(opcode) {
                 self.populate_one_ty(instr)?;
+                nty += 1;
             } else if opcode == Op::Variable {
                 self.populate_one_var(instr)?;
+                nvar += 1;
             } else if is_const_op(opcode) {
                 self.populate_one_const(instr)?;
+                nconst += 1;
             } else {
                 break;
             }
             instrs.next()?;
         }
+        span.record("nty", nty);
+        span.record("nconst", nconst);
+        span.record("nvar", nvar);
+        self.resolve_fwd_ptrs()?;
 
         Ok(())
     }
@@ -1062,66 +2822,233 @@ impl<'a> ReflectIntermediate<'a> {
         //   e. ...
         //   f. Function end, using OpFunctionEnd.
 
-        while let Some(instr) = instrs.peek() {
-            let opcode = instr.op();
-            if let Op::Line | Op::NoLine = opcode {
-                instrs.next()?;
-                continue;
+        let span = trace_span!("access_scan", ninstr);
+        let ninstr = self.parse_functions_chunk(instrs, inspector, usize::MAX)?;
+        span.record("ninstr", ninstr);
+
+        Ok(())
+    }
+    /// Scan at most `max_ninstr` instructions of function declarations and
+    /// definitions. Returns the number of instructions scanned, which is less
+    /// than `max_ninstr` only if all functions have been scanned.
+    pub(crate) fn parse_functions_chunk(
+        &mut self,
+        instrs: &mut Instrs<'a>,
+        inspector: &mut impl Inspector,
+        max_ninstr: usize,
+    ) -> Result<usize> {
+        self.is_fn_scanned = true;
+        let mut ninstr = 0;
+        while ninstr < max_ninstr {
+            let instr = match instrs.peek() {
+                Some(x) => x,
+                None => break,
+            };
+            // Inspectors only receive instructions with known opcodes so that
+            // `Instr::op` is safe to call.
+            match decode_op(instr) {
+                None | Some(Op::Line) | Some(Op::NoLine) => {
+                    instrs.next()?;
+                    continue;
+                }
+                _ => {}
             }
             inspector.inspect(self, instr)?;
             instrs.next()?;
+            ninstr += 1;
         }
-
-        Ok(())
+        Ok(ninstr)
     }
 
-    fn collect_vars_impl(&self) -> BTreeMap<VariableId, Variable> {
-        // `BTreeMap` to ensure a stable order.
+    fn var_name(&self, var_id: VariableId) -> Option<String> {
+        self.name_reg
+            .get(var_id)
+            .map(ToOwned::to_owned)
+            .or_else(|| {
+                if self.cfg.gen_unique_names {
+                    Some(format!("var_{}", var_id))
+                } else {
+                    None
+                }
+            })
+    }
+    /// Collect the OpenGL view of all variables in the module.
+    fn collect_gl_vars_impl(&self) -> BTreeMap<VariableId, GlVariable> {
         let mut vars = BTreeMap::new();
         for (var_id, var_alloc) in self.var_reg.iter() {
-            let name = self
-                .name_reg
-                .get(*var_id)
-                .map(ToOwned::to_owned)
-                .or_else(|| {
-                    if self.cfg.gen_unique_names {
-                        Some(format!("var_{}", var_id))
-                    } else {
-                        None
-                    }
-                });
-            if let Some(var) = make_var(&self.deco_reg, name, *var_id, var_alloc) {
+            let name = self.var_name(*var_id);
+            if let Some(var) = make_gl_var(&self.deco_reg, name, *var_id, var_alloc) {
                 vars.insert(*var_id, var);
             }
         }
         vars
     }
-    fn collect_vars(&self) -> Vec<Variable> {
-        self.collect_vars_impl()
-            .into_iter()
-            .map(|(_, var)| var)
-            .collect()
+    /// Collect variables in workgroup memory of storage class `store_cls`,
+    /// i.e., `Workgroup` or `TaskPayloadWorkgroupEXT`.
+    fn collect_shared_vars_impl(
+        &self,
+        store_cls: StorageClass,
+    ) -> BTreeMap<VariableId, SharedVariable> {
+        let mut vars = BTreeMap::new();
+        for (var_id, var_alloc) in self.var_reg.iter() {
+            if var_alloc.store_cls != store_cls {
+                continue;
+            }
+            let ty = (*var_alloc.ptr_ty.pointee_ty).clone();
+            // Only blocks are explicitly laid out.
+            let is_aliased = self.blocks.contains(var_id);
+            let nbyte = if is_aliased {
+                ty.layout_nbyte()
+            } else {
+                ty.block_nbyte(BlockLayout::Std430)
+            };
+            let var = SharedVariable {
+                name: self.var_name(*var_id),
+                ty,
+                nbyte,
+                is_aliased,
+            };
+            vars.insert(*var_id, var);
+        }
+        vars
+    }
+    fn collect_private_vars_impl(
+        &self,
+        store_cls: StorageClass,
+    ) -> BTreeMap<VariableId, PrivateVariable> {
+        let mut vars = BTreeMap::new();
+        for (var_id, var_alloc) in self.var_reg.iter() {
+            if var_alloc.store_cls != store_cls {
+                continue;
+            }
+            let init = self
+                .var_inits
+                .get(var_id)
+                .and_then(|x| self.interp.get_value(*x).ok())
+                .cloned();
+            let var = PrivateVariable {
+                name: self.var_name(*var_id),
+                ty: (*var_alloc.ptr_ty.pointee_ty).clone(),
+                init,
+            };
+            vars.insert(*var_id, var);
+        }
+        vars
     }
+    /// Collect all variables in the module, returned with the IDs of the
+    /// descriptor resources whose binding points are not decorated.
+    fn collect_vars_impl(&self) -> Result<(BTreeMap<VariableId, Variable>, HashSet<VariableId>)> {
+        // `BTreeMap` to ensure a stable order.
+        let mut vars = BTreeMap::new();
+        for (var_id, var_alloc) in self.var_reg.iter() {
+            let name = self.var_name(*var_id);
+            let block_location = if self.cfg.member_located_io_blocks {
+                self.io_block_locations.get(var_id).copied()
+            } else {
+                None
+            };
+            if let Some(var) = make_var(&self.deco_reg, name, *var_id, var_alloc, block_location) {
+                vars.insert(*var_id, var);
+            }
+        }
 
-    fn collect_entry_point_vars(&self, func_id: FunctionId) -> Vec<Variable> {
-        let accessed_var_ids = self
-            .func_reg
-            .collect_fn_vars(func_id)
-            .into_iter()
-            .collect::<HashSet<_>>();
-        let vars = self
-            .collect_vars_impl()
-            .into_iter()
-            .filter_map(|(var_id, var)| {
-                if accessed_var_ids.contains(&var_id) {
-                    Some(var)
-                } else {
-                    None
+        // Resolve the descriptors without binding decorations.
+        let policy = self.cfg.missing_binding_policy;
+        let mut next_binds = HashMap::<u32, u32>::default();
+        if policy == MissingBindingPolicy::AutoAssign {
+            for (var_id, var) in vars.iter() {
+                if let Variable::Descriptor { desc_bind, .. } = var {
+                    if self.deco_reg.contains(*var_id, spirv::Decoration::Binding) {
+                        let next_bind = next_binds.entry(desc_bind.set()).or_default();
+                        *next_bind = (*next_bind).max(desc_bind.bind() + 1);
+                    }
                 }
-            })
-            .collect::<Vec<_>>();
-        vars
+            }
+        }
+        let mut implicit_bind_var_ids = HashSet::default();
+        for (var_id, var) in vars.iter_mut() {
+            if let Variable::Descriptor {
+                name, desc_bind, ..
+            } = var
+            {
+                if self.deco_reg.contains(*var_id, spirv::Decoration::Binding) {
+                    continue;
+                }
+                match policy {
+                    MissingBindingPolicy::Error => {
+                        return Err(anyhow!(
+                            "descriptor resource {:?} has no binding decoration",
+                            name.as_deref().unwrap_or("<unnamed>")
+                        ));
+                    }
+                    MissingBindingPolicy::WarnAndDefault => {}
+                    MissingBindingPolicy::AutoAssign => {
+                        let desc_set = self
+                            .deco_reg
+                            .get_u32(*var_id, spirv::Decoration::DescriptorSet)
+                            .unwrap_or(0);
+                        let next_bind = next_binds.entry(desc_set).or_default();
+                        *desc_bind = DescriptorBinding::new(desc_set, *next_bind);
+                        *next_bind += 1;
+                    }
+                }
+                implicit_bind_var_ids.insert(*var_id);
+            }
+        }
+
+        Ok((vars, implicit_bind_var_ids))
     }
+
+    /// Assign locations to the stage inputs and outputs without location
+    /// decorations, following the largest location declared by the inputs
+    /// or outputs in the module, in the order of variable IDs. Built-in
+    /// variables and I/O blocks located by their members are left out.
+    /// Returns the IDs of the variables assigned.
+    fn assign_locations(&self, vars: &mut BTreeMap<VariableId, Variable>) -> HashSet<VariableId> {
+        let mut next_input_loc = 0;
+        let mut next_output_loc = 0;
+        for var in vars.values() {
+            match var {
+                Variable::Input { location, ty, .. } => {
+                    next_input_loc = next_input_loc.max(location.loc() + nloc(ty));
+                }
+                Variable::Output { location, ty, .. } => {
+                    next_output_loc = next_output_loc.max(location.loc() + nloc(ty));
+                }
+                _ => {}
+            }
+        }
+        let mut var_allocs = self.var_reg.iter().collect::<Vec<_>>();
+        var_allocs.sort_by_key(|x| *x.0);
+        let mut implicit_loc_var_ids = HashSet::default();
+        for (var_id, var_alloc) in var_allocs {
+            let is_skipped = vars.contains_key(var_id)
+                || self.io_block_locations.contains_key(var_id)
+                || self.builtin_blocks.contains_key(var_id)
+                || self.deco_reg.contains(*var_id, spirv::Decoration::BuiltIn);
+            if is_skipped {
+                continue;
+            }
+            let next_loc = match var_alloc.ptr_ty.store_cls {
+                StorageClass::Input => &mut next_input_loc,
+                StorageClass::Output => &mut next_output_loc,
+                _ => continue,
+            };
+            let name = self.var_name(*var_id);
+            let location = InterfaceLocation::new(*next_loc, 0);
+            let ty = (*var_alloc.ptr_ty.pointee_ty).clone();
+            *next_loc += nloc(&ty);
+            let var = if var_alloc.ptr_ty.store_cls == StorageClass::Input {
+                Variable::Input { name, location, ty }
+            } else {
+                Variable::Output { name, location, ty }
+            };
+            vars.insert(*var_id, var);
+            implicit_loc_var_ids.insert(*var_id);
+        }
+        implicit_loc_var_ids
+    }
+
     fn collect_entry_point_specs(&self) -> Result<Vec<Variable>> {
         // TODO: (penguinlion) Report only specialization constants that have
         // been refered to by the specified function. (Do we actually need this?
@@ -1139,34 +3066,54 @@ impl<'a> ReflectIntermediate<'a> {
         }
         Ok(vars)
     }
+    fn collect_spec_defaults(&self) -> BTreeMap<SpecId, ConstantValue> {
+        self.interp
+            .constants()
+            .into_iter()
+            .filter_map(|x| Some((x.spec_id?, x.value)))
+            .collect()
+    }
+    #[cfg(feature = "exec-modes")]
     fn collect_exec_modes(
         &self,
-        func_id: FunctionId,
-        exec_mode_declrs: &[ExecutionModeDeclaration],
+        exec_mode_declrs: &[RawExecutionMode],
     ) -> Result<Vec<ExecutionMode>> {
         let mut exec_modes = Vec::with_capacity(exec_mode_declrs.len());
 
-        for declr in exec_mode_declrs.iter() {
-            if declr.func_id != func_id {
-                continue;
-            }
+        'declrs: for declr in exec_mode_declrs.iter() {
+            let exec_mode = match declr.known_exec_mode() {
+                Some(x) => x,
+                None => continue,
+            };
 
             let mut operands = Vec::with_capacity(declr.operands.len());
-            for operand in declr.operands.iter() {
-                let operand = match operand {
-                    ExecutionModeOperand::Literal(x) => {
-                        let scalar_ty = ScalarType::u32();
-                        let ty = Type::Scalar(scalar_ty);
-                        let value = ConstantValue::from(*x);
-                        Constant::new_itm(ty, value)
+            for &operand in declr.operands.iter() {
+                let operand = if declr.is_id {
+                    match self.interp.get(operand) {
+                        Ok(x) => x.clone(),
+                        // Results of specialization constant operations that
+                        // can't be folded are unknown. Only the
+                        // specialization IDs they depend on are reported,
+                        // see `collect_exec_mode_spec_ids`.
+                        Err(_) if self.spec_const_deps.contains_key(&operand) => continue 'declrs,
+                        Err(e) => return Err(e),
                     }
-                    ExecutionModeOperand::Id(x) => self.interp.get(*x)?.clone(),
+                } else {
+                    let scalar_ty = ScalarType::u32();
+                    let ty = Type::Scalar(scalar_ty);
+                    let value = ConstantValue::from(operand);
+                    Constant::new_itm(ty, value)
                 };
                 operands.push(operand);
             }
+            if let spirv::ExecutionMode::LocalSize | spirv::ExecutionMode::LocalSizeId = exec_mode {
+                if let Some(workgroup_size) = &self.workgroup_size {
+                    operands = workgroup_size.clone();
+                }
+            }
 
             let exec_mode = ExecutionMode {
-                exec_mode: declr.exec_mode,
+                exec_mode,
                 operands,
             };
             exec_modes.push(exec_mode)
@@ -1174,10 +3121,342 @@ impl<'a> ReflectIntermediate<'a> {
 
         Ok(exec_modes)
     }
+    /// Specialization IDs the ID operands of execution modes
+    /// `exec_mode_declrs` depend on.
+    fn collect_exec_mode_spec_ids(
+        &self,
+        exec_mode_declrs: &[RawExecutionMode],
+    ) -> BTreeSet<SpecId> {
+        exec_mode_declrs
+            .iter()
+            .filter(|x| x.is_id)
+            .flat_map(|x| x.operands.iter())
+            .filter_map(|x| self.spec_const_deps.get(x))
+            .flatten()
+            .copied()
+            .collect()
+    }
+    /// Execution modes are only reported raw without the `exec-modes`
+    /// feature.
+    #[cfg(not(feature = "exec-modes"))]
+    fn collect_exec_modes(&self, _: &[RawExecutionMode]) -> Result<Vec<ExecutionMode>> {
+        Ok(Vec::new())
+    }
+}
+
+/// Variables accessed by a function and its callees.
+#[derive(Default)]
+struct FunctionAccess<'a> {
+    vars: HashSet<VariableId>,
+    /// Components read from input variables.
+    input_reads: ArenaMap<'a, VariableId, u32>,
+    /// Byte ranges read from push constant variables.
+    push_const_reads: ArenaMap<'a, VariableId, (usize, usize)>,
+    /// Derivatives are used.
+    uses_derivatives: bool,
+    /// Variables written, including through parameters.
+    written_vars: ArenaSet<'a, VariableId>,
+    /// Memory is written through physical storage buffer pointers.
+    writes_device_mem: bool,
+    /// The `PointSize` built-in is written.
+    writes_point_size: bool,
+    /// Acceleration structure variables rays are traced against.
+    trace_ray_vars: ArenaSet<'a, VariableId>,
+    /// Acceleration structure variables queried inline.
+    ray_query_vars: ArenaSet<'a, VariableId>,
+    /// Variables indexed non-uniformly.
+    nonuniform_vars: ArenaSet<'a, VariableId>,
+    /// Image variables accessed with the `MinLod` image operand.
+    min_lod_vars: ArenaSet<'a, VariableId>,
+    /// Image variables accessed by sparse image instructions.
+    sparse_vars: ArenaSet<'a, VariableId>,
+    /// Elements of descriptor arrays referenced.
+    desc_elems: DescriptorElements<'a>,
+}
+
+/// Elements of descriptor array variables referenced, or `None` if the
+/// elements referenced are not known statically.
+#[derive(Default, Clone)]
+struct DescriptorElements<'a>(ArenaMap<'a, VariableId, Option<BTreeSet<u32>>>);
+impl<'a> DescriptorElements<'a> {
+    /// Mark element `idx` of `var_id` referenced, or all the elements if
+    /// `idx` is `None`.
+    #[cfg(feature = "access-analysis")]
+    fn mark(&mut self, var_id: VariableId, idx: Option<u32>) {
+        self.mark_all(var_id, idx.map(|x| std::iter::once(x).collect()).as_ref());
+    }
+    fn mark_all(&mut self, var_id: VariableId, idxs: Option<&BTreeSet<u32>>) {
+        let elems = self
+            .0
+            .entry(var_id)
+            .or_insert_with(|| Some(BTreeSet::new()));
+        match (elems.as_mut(), idxs) {
+            (Some(elems), Some(idxs)) => elems.extend(idxs.iter().copied()),
+            (Some(_), None) => *elems = None,
+            (None, _) => {}
+        }
+    }
+    fn extend(&mut self, other: &DescriptorElements<'_>) {
+        for (var_id, idxs) in other.0.iter() {
+            self.mark_all(*var_id, idxs.as_ref());
+        }
+    }
+}
+
+/// Variables accessed by functions, including those accessed by their callees.
+/// Functions are only traversed once no matter how many times they are called
+/// so the access map can be shared by all entry points.
+struct AccessMap<'a, 'b> {
+    itm: &'a ReflectIntermediate<'b>,
+    memo: HashMap<FunctionId, FunctionAccess<'b>>,
+}
+impl<'a, 'b> AccessMap<'a, 'b> {
+    fn new(itm: &'a ReflectIntermediate<'b>) -> Self {
+        AccessMap {
+            itm,
+            memo: HashMap::default(),
+        }
+    }
+    fn get(&mut self, func_id: FunctionId) -> &FunctionAccess<'b> {
+        self.populate(func_id);
+        &self.memo[&func_id]
+    }
+    fn populate(&mut self, func_id: FunctionId) {
+        if self.memo.contains_key(&func_id) {
+            return;
+        }
+        // Recursion is not allowed in SPIR-V but don't loop forever on a
+        // malformed call graph.
+        self.memo.insert(func_id, FunctionAccess::default());
+        let itm = self.itm;
+        let func = match itm.func_reg.get(func_id) {
+            Ok(x) => x,
+            Err(_) => return,
+        };
+        let mut access = FunctionAccess {
+            vars: func.accessed_vars.clone(),
+            input_reads: itm.input_reads.get(&func_id).cloned().unwrap_or_default(),
+            push_const_reads: itm
+                .push_const_reads
+                .get(&func_id)
+                .cloned()
+                .unwrap_or_default(),
+            uses_derivatives: itm.derivative_func_ids.contains(&func_id),
+            written_vars: itm.written_vars.get(&func_id).cloned().unwrap_or_default(),
+            writes_device_mem: itm.device_write_func_ids.contains(&func_id),
+            writes_point_size: itm.point_size_write_func_ids.contains(&func_id),
+            trace_ray_vars: itm
+                .trace_ray_var_ids
+                .get(&func_id)
+                .cloned()
+                .unwrap_or_default(),
+            ray_query_vars: itm
+                .ray_query_var_ids
+                .get(&func_id)
+                .cloned()
+                .unwrap_or_default(),
+            desc_elems: itm.desc_elems.get(&func_id).cloned().unwrap_or_default(),
+            nonuniform_vars: itm
+                .nonuniform_var_ids
+                .get(&func_id)
+                .cloned()
+                .unwrap_or_default(),
+            min_lod_vars: itm
+                .min_lod_var_ids
+                .get(&func_id)
+                .cloned()
+                .unwrap_or_default(),
+            sparse_vars: itm
+                .sparse_var_ids
+                .get(&func_id)
+                .cloned()
+                .unwrap_or_default(),
+        };
+        for callee in func.callees.iter() {
+            self.populate(*callee);
+            let callee_access = &self.memo[callee];
+            access.vars.extend(callee_access.vars.iter().copied());
+            access.uses_derivatives |= callee_access.uses_derivatives;
+            access.writes_device_mem |= callee_access.writes_device_mem;
+            access.writes_point_size |= callee_access.writes_point_size;
+            access
+                .written_vars
+                .extend(callee_access.written_vars.iter().copied());
+            access
+                .trace_ray_vars
+                .extend(callee_access.trace_ray_vars.iter().copied());
+            access
+                .ray_query_vars
+                .extend(callee_access.ray_query_vars.iter().copied());
+            access.desc_elems.extend(&callee_access.desc_elems);
+            access
+                .nonuniform_vars
+                .extend(callee_access.nonuniform_vars.iter().copied());
+            access
+                .min_lod_vars
+                .extend(callee_access.min_lod_vars.iter().copied());
+            access
+                .sparse_vars
+                .extend(callee_access.sparse_vars.iter().copied());
+            for (var_id, comp_mask) in callee_access.input_reads.iter() {
+                *access.input_reads.entry(*var_id).or_default() |= comp_mask;
+            }
+            for (var_id, range) in callee_access.push_const_reads.iter() {
+                mark_push_const_range(&mut access.push_const_reads, *var_id, *range);
+            }
+        }
+        // Arguments are accessed if the callee accesses the parameters they
+        // are passed to. Accessed parameters of this function are in turn
+        // resolved by its callers.
+        let calls = itm.func_linkages.get(&func_id).map(|x| &x.calls);
+        for (callee, arg_ids) in calls.into_iter().flatten() {
+            let params = match itm.func_linkages.get(callee) {
+                Some(x) => &x.params,
+                None => continue,
+            };
+            let callee_access = &self.memo[callee];
+            for (param_id, arg_id) in params.iter().zip(arg_ids.iter()) {
+                if callee_access.vars.contains(param_id) {
+                    access.vars.insert(*arg_id);
+                    // Components and bytes read through parameters are not
+                    // tracked.
+                    *access.input_reads.entry(*arg_id).or_default() |= !0;
+                    mark_push_const_range(&mut access.push_const_reads, *arg_id, (0, usize::MAX));
+                }
+                if callee_access.written_vars.contains(param_id) {
+                    access.written_vars.insert(*arg_id);
+                }
+            }
+        }
+        self.memo.insert(func_id, access);
+    }
+}
+
+/// Check if descriptors of type `desc_ty` can be written by shaders.
+fn is_writable_desc_ty(desc_ty: &DescriptorType) -> bool {
+    matches!(
+        desc_ty,
+        DescriptorType::StorageBuffer(x)
+            | DescriptorType::StorageImage(x)
+            | DescriptorType::StorageTexelBuffer(x)
+            if *x != AccessType::ReadOnly
+    )
+}
+/// Mark `var_id` in the variables of function `func_id` in `func_var_ids`.
+#[cfg(feature = "access-analysis")]
+fn mark_func_var<'a>(
+    arena: Arena<'a>,
+    func_var_ids: &mut ArenaMap<'a, FunctionId, ArenaSet<'a, VariableId>>,
+    func_id: FunctionId,
+    var_id: VariableId,
+) {
+    func_var_ids
+        .entry(func_id)
+        .or_insert_with(|| arena.set())
+        .insert(var_id);
+}
+/// Extend the byte range read from push constant variable `var_id` with
+/// `[offset, end)`.
+fn mark_push_const_range(
+    ranges: &mut ArenaMap<'_, VariableId, (usize, usize)>,
+    var_id: VariableId,
+    (offset, end): (usize, usize),
+) {
+    ranges
+        .entry(var_id)
+        .and_modify(|x| *x = (x.0.min(offset), x.1.max(end)))
+        .or_insert((offset, end));
+}
+/// Byte range `[offset, end)` of the members of push constant block `ty`.
+/// Members without offsets or sizes are ignored.
+fn push_const_block_range(ty: &Type) -> Option<(usize, usize)> {
+    let mut out: Option<(usize, usize)> = None;
+    for member in ty.as_struct()?.members.iter() {
+        let offset = match member.offset {
+            Some(x) => x,
+            None => continue,
+        };
+        let end = match member.ty.layout_nbyte() {
+            Some(nbyte) => offset + nbyte,
+            None => continue,
+        };
+        out = Some(match out {
+            Some((x, y)) => (x.min(offset), y.max(end)),
+            None => (offset, end),
+        });
+    }
+    out
+}
+
+/// Number of locations an input or output of type `ty` occupies. Members of
+/// I/O blocks take consecutive locations.
+fn nloc(ty: &Type) -> u32 {
+    let nloc = match ty {
+        Type::Struct(x) => x.members.iter().map(|x| nloc(&x.ty)).sum(),
+        Type::Array(x) => nloc(&x.element_ty) * x.nelement.unwrap_or(0),
+        _ => mark_input_comps(&mut BTreeMap::new(), 0, 0, ty, 0),
+    };
+    nloc.max(1)
+}
+/// Mark the components read from an input of type `ty` at `loc` and `comp` in
+/// `out`, where bit `i` of `comp_mask` is the `i`-th component of a scalar or
+/// vector input. Matrix and array inputs are entirely read if any component
+/// is read. Returns the number of locations the input occupies.
+fn mark_input_comps(
+    out: &mut BTreeMap<u32, u32>,
+    loc: u32,
+    comp: u32,
+    ty: &Type,
+    comp_mask: u32,
+) -> u32 {
+    let comp_mask = match ty {
+        Type::Scalar(_) | Type::Vector(_) => comp_mask,
+        _ if comp_mask != 0 => !0,
+        _ => 0,
+    };
+    let (scalar_ty, nscalar) = match ty {
+        Type::Scalar(x) => (x, 1),
+        Type::Vector(x) => (&x.scalar_ty, x.nscalar),
+        Type::Matrix(x) => {
+            let col_ty = Type::Vector(x.vector_ty.clone());
+            let mut nloc = 0;
+            for _ in 0..x.nvector {
+                nloc += mark_input_comps(out, loc + nloc, 0, &col_ty, comp_mask);
+            }
+            return nloc;
+        }
+        Type::Array(x) => {
+            let mut nloc = 0;
+            for _ in 0..x.nelement.unwrap_or(0) {
+                nloc += mark_input_comps(out, loc + nloc, 0, &x.element_ty, comp_mask);
+            }
+            return nloc;
+        }
+        _ => return 0,
+    };
+    // 64-bit components take up two 32-bit components.
+    let width = match scalar_ty {
+        ScalarType::Integer { bits: 64, .. } | ScalarType::Float { bits: 64 } => 2,
+        _ => 1,
+    };
+    let mut nloc = 1;
+    out.entry(loc).or_default();
+    for i in 0..nscalar {
+        for j in 0..width {
+            let slot = comp + i * width + j;
+            let x = out.entry(loc + slot / 4).or_default();
+            if comp_mask & (1 << i) != 0 {
+                *x |= 1 << (slot % 4);
+            }
+            nloc = nloc.max(slot / 4 + 1);
+        }
+    }
+    nloc
 }
 
 /// Merge `DescriptorType::SampledImage` and `DescriptorType::Sampler` if
 /// they are bound to a same binding point with a same number of bindings.
+#[cfg(feature = "combine-img-samplers")]
 fn combine_img_samplers(vars: Vec<Variable>) -> Vec<Variable> {
     let mut samplers = Vec::<Variable>::new();
     let mut imgs = Vec::<Variable>::new();
@@ -1273,28 +3552,490 @@ fn combine_img_samplers(vars: Vec<Variable>) -> Vec<Variable> {
 }
 
 impl<'a> ReflectIntermediate<'a> {
+    /// Group descriptor resources aliased at a same binding point. Separate
+    /// images and samplers are grouped after they are combined.
+    fn collect_desc_aliases(
+        &self,
+        vars: &[Variable],
+        desc_infos: &mut BTreeMap<DescriptorBinding, DescriptorInfo>,
+    ) -> Result<()> {
+        let mut descs = BTreeMap::<DescriptorBinding, Vec<(&Option<String>, u32)>>::new();
+        for var in vars.iter() {
+            if let Variable::Descriptor {
+                name,
+                desc_bind,
+                desc_ty,
+                ..
+            } = var
+            {
+                let desc = (name, vk_desc_ty(desc_ty));
+                descs.entry(*desc_bind).or_default().push(desc);
+            }
+        }
+        for (desc_bind, descs) in descs {
+            if descs.len() < 2 {
+                continue;
+            }
+            let has_aliased_desc_tys = descs.iter().any(|x| x.1 != descs[0].1);
+            if has_aliased_desc_tys && self.cfg.strict_desc_aliasing {
+                return Err(anyhow!(
+                    "descriptor resources of different types are aliased at {}",
+                    desc_bind
+                ));
+            }
+            let desc_info = desc_infos.entry(desc_bind).or_default();
+            desc_info.aliases = descs.iter().map(|x| x.0.clone()).collect();
+            desc_info.has_aliased_desc_tys = has_aliased_desc_tys;
+        }
+        Ok(())
+    }
+
     pub fn collect_entry_points(&self) -> Result<Vec<EntryPoint>> {
+        let entry_points = self.collect_entry_points_impl(true)?;
+        Ok(entry_points.into_iter().map(|(_, x)| x).collect())
+    }
+    /// Collect the entry points along with the IDs of their functions. The
+    /// names of the entry points are left empty unless `own_names` is set.
+    pub(crate) fn collect_entry_points_impl(
+        &self,
+        own_names: bool,
+    ) -> Result<Vec<(FunctionId, EntryPoint)>> {
+        let span = trace_span!("collection", nentry_point, nvar);
+        let (mut module_vars, implicit_bind_var_ids) = self.collect_vars_impl()?;
+        let implicit_loc_var_ids = if self.cfg.auto_assign_locations {
+            self.assign_locations(&mut module_vars)
+        } else {
+            HashSet::default()
+        };
+        span.record("nvar", module_vars.len());
+        let module_shared_vars = self.collect_shared_vars_impl(StorageClass::Workgroup);
+        let module_task_payloads =
+            self.collect_shared_vars_impl(StorageClass::TaskPayloadWorkgroupEXT);
+        let module_private_vars = if self.cfg.private_vars {
+            self.collect_private_vars_impl(StorageClass::Private)
+        } else {
+            BTreeMap::new()
+        };
+        let module_global_vars = self.collect_private_vars_impl(StorageClass::CrossWorkgroup);
+        let module_gl_vars = if self.cfg.gl {
+            self.collect_gl_vars_impl()
+        } else {
+            BTreeMap::new()
+        };
+        let mut access_map = AccessMap::new(self);
         let mut entry_points = Vec::with_capacity(self.entry_point_declrs.len());
         for (id, entry_point_declr) in self.entry_point_declrs.iter() {
-            let mut vars = if self.cfg.ref_all_rscs {
-                self.collect_vars()
+            let access = access_map.get(*id);
+            let is_access_known = self.is_fn_scanned && cfg!(feature = "access-analysis");
+            let interface_var_ids = entry_point_declr
+                .interface_ids
+                .iter()
+                .copied()
+                .collect::<HashSet<_>>();
+            let accessed_var_ids = if self.cfg.ref_all_rscs {
+                None
+            } else if is_access_known {
+                Some(&access.vars)
+            } else if self.version >= SpirvVersion::new(1, 4) {
+                // Entry points list all the global variables they use since
+                // SPIR-V 1.4.
+                Some(&interface_var_ids)
             } else {
-                self.collect_entry_point_vars(*id)
+                None
             };
+            // Inputs of these stages are arrays of per-vertex values which
+            // don't occupy extra locations.
+            let has_per_vertex_inputs = matches!(
+                entry_point_declr.exec_model,
+                ExecutionModel::TessellationControl
+                    | ExecutionModel::TessellationEvaluation
+                    | ExecutionModel::Geometry
+            );
+
+            let mut vars = Vec::new();
+            let mut desc_infos = BTreeMap::<DescriptorBinding, DescriptorInfo>::new();
+            let mut desc_elems = BTreeMap::<DescriptorBinding, Option<BTreeSet<u32>>>::new();
+            let mut diags = self.module_diags.clone();
+            let exec_model = entry_point_declr.exec_model;
+            if access.uses_derivatives
+                && exec_model != ExecutionModel::Fragment
+                && !has_derivative_groups(exec_model)
+            {
+                diags.push(Diagnostic::UnsupportedDerivatives { exec_model });
+            }
+            let mut input_comp_masks = BTreeMap::new();
+            let mut io_decos = BTreeMap::new();
+            let mut io_member_decos = BTreeMap::new();
+            let mut implicit_locations = BTreeSet::new();
+            let mut push_const_bytes: Option<(usize, usize)> = None;
+            let mut writes_external_memory = is_access_known && access.writes_device_mem;
+            for (var_id, var) in module_vars.iter() {
+                if let Some(accessed_var_ids) = &accessed_var_ids {
+                    if !accessed_var_ids.contains(var_id) {
+                        continue;
+                    }
+                }
+                if let Variable::Input { location, ty, .. } = var {
+                    if !has_per_vertex_inputs {
+                        let comp_mask = if is_access_known {
+                            access.input_reads.get(var_id).copied().unwrap_or(0)
+                        } else {
+                            !0
+                        };
+                        mark_input_comps(
+                            &mut input_comp_masks,
+                            location.loc(),
+                            location.comp(),
+                            ty,
+                            comp_mask,
+                        );
+                    }
+                }
+                if let Variable::Descriptor { desc_ty, .. } = var {
+                    // Without access analysis, any writable descriptor
+                    // might be written.
+                    writes_external_memory |= if is_access_known {
+                        access.written_vars.contains(var_id)
+                    } else {
+                        is_writable_desc_ty(desc_ty)
+                    };
+                }
+                if let Variable::PushConstant { ty, .. } = var {
+                    let is_read = !is_access_known || access.vars.contains(var_id);
+                    let block_range = push_const_block_range(ty).filter(|_| is_read);
+                    if let Some((offset, end)) = block_range {
+                        // Push constants read in ways not tracked are
+                        // entirely read.
+                        let (x, y) = access
+                            .push_const_reads
+                            .get(var_id)
+                            .copied()
+                            .filter(|_| is_access_known)
+                            .unwrap_or((0, usize::MAX));
+                        let (x, y) = (x.max(offset), y.min(end));
+                        if x < y {
+                            push_const_bytes = Some(match push_const_bytes {
+                                Some((offset, end)) => (offset.min(x), end.max(y)),
+                                None => (x, y),
+                            });
+                        }
+                    }
+                }
+                if let Some(io_var) = InterfaceVariable::of(var) {
+                    if implicit_loc_var_ids.contains(var_id) {
+                        implicit_locations.insert(io_var);
+                    }
+                    let decos = IO_DECOS
+                        .iter()
+                        .filter_map(|deco| {
+                            let operands = self.deco_reg.get(*var_id, *deco).ok()?;
+                            Some((*deco, operands.to_owned()))
+                        })
+                        .collect();
+                    io_decos.insert(io_var, decos);
+                    if let Some(x) = self.io_member_decos.get(var_id) {
+                        io_member_decos.insert(io_var, x.clone());
+                    }
+                }
+                if let Variable::Descriptor { .. } | Variable::PushConstant { .. } = var {
+                    let is_listed = interface_var_ids.contains(var_id);
+                    let is_used = is_access_known && access.vars.contains(var_id);
+                    let is_storage_buffer_class = matches!(
+                        self.var_reg.get(*var_id),
+                        Ok(x) if x.store_cls == StorageClass::StorageBuffer
+                    );
+                    let mut conventions = Vec::new();
+                    if self.version >= SpirvVersion::new(1, 4) {
+                        if is_used && !is_listed {
+                            conventions.push(VersionConvention::UnlistedGlobal);
+                        }
+                    } else if is_listed {
+                        conventions.push(VersionConvention::ListedGlobal);
+                    }
+                    if is_storage_buffer_class
+                        && self.version < SpirvVersion::new(1, 3)
+                        && !self.exts.contains("SPV_KHR_storage_buffer_storage_class")
+                    {
+                        conventions.push(VersionConvention::StorageBufferClass);
+                    }
+                    for convention in conventions {
+                        diags.push(Diagnostic::MixedVersionConventions {
+                            name: var.name().map(ToOwned::to_owned),
+                            version: self.version,
+                            convention,
+                        });
+                    }
+
+                    let spec_sized_arrs = self.spec_sized_arrays.get(var_id);
+                    for spec_sized_arr in spec_sized_arrs.into_iter().flatten() {
+                        // Descriptor arrays sized by specialization constants
+                        // are fine.
+                        let (block, member) = match &spec_sized_arr.member {
+                            Some(x) => x.clone(),
+                            None => continue,
+                        };
+                        let diag = Diagnostic::SpecConstantSizedArray {
+                            block,
+                            member,
+                            spec_id: spec_sized_arr.spec_id,
+                        };
+                        if self.cfg.strict_spec_array_sizes {
+                            return Err(anyhow!("{}", diag));
+                        }
+                        if !diags.contains(&diag) {
+                            diags.push(diag);
+                        }
+                    }
+                }
+                if let Variable::Descriptor {
+                    name,
+                    desc_bind,
+                    desc_ty,
+                    nbind,
+                    ..
+                } = var
+                {
+                    if let Some(kind) = self.degenerate_sampled_imgs.get(var_id) {
+                        diags.push(Diagnostic::DegenerateSampledImage {
+                            name: name.clone(),
+                            desc_bind: *desc_bind,
+                            kind: *kind,
+                        });
+                    }
+                    let is_binding_implicit = implicit_bind_var_ids.contains(var_id);
+                    let desc_info = desc_infos.entry(*desc_bind).or_default();
+                    desc_info.is_binding_implicit |= is_binding_implicit;
+                    desc_info.is_interface_listed |= interface_var_ids.contains(var_id);
+                    desc_info.is_statically_used |=
+                        !is_access_known || access.vars.contains(var_id);
+                    desc_info.is_nonuniform_indexed |=
+                        *nbind != 1 && access.nonuniform_vars.contains(var_id);
+                    desc_info.uses_min_lod |= access.min_lod_vars.contains(var_id);
+                    desc_info.uses_sparse_residency |= access.sparse_vars.contains(var_id);
+                    desc_info.uses_trace_ray |= access.trace_ray_vars.contains(var_id);
+                    desc_info.uses_ray_query |= access.ray_query_vars.contains(var_id);
+                    let is_used = access.vars.contains(var_id);
+                    let used_elems = if !is_access_known {
+                        None
+                    } else if *nbind == 1 {
+                        Some(if is_used {
+                            BTreeSet::from([0])
+                        } else {
+                            BTreeSet::new()
+                        })
+                    } else {
+                        match access.desc_elems.0.get(var_id) {
+                            Some(x) => x.clone(),
+                            // Accessed without access chains.
+                            None if is_used => None,
+                            None => Some(BTreeSet::new()),
+                        }
+                    };
+                    let elems = desc_elems
+                        .entry(*desc_bind)
+                        .or_insert_with(|| Some(BTreeSet::new()));
+                    match (elems.as_mut(), used_elems) {
+                        (Some(x), Some(y)) => x.extend(y),
+                        _ => *elems = None,
+                    }
+                    if let Some(fmt) = self.texel_buffer_fmts.get(var_id).copied() {
+                        let (access, is_storage) = match desc_ty {
+                            DescriptorType::StorageTexelBuffer(x) => (*x, true),
+                            _ => (AccessType::ReadOnly, false),
+                        };
+                        desc_info.texel_buffer = Some(TexelBufferInfo {
+                            fmt,
+                            access,
+                            is_storage,
+                        });
+                    }
+                    if is_binding_implicit
+                        && self.cfg.missing_binding_policy == MissingBindingPolicy::WarnAndDefault
+                    {
+                        diags.push(Diagnostic::MissingBinding {
+                            name: name.clone(),
+                            desc_bind: *desc_bind,
+                        });
+                    }
+                }
+                vars.push(var.clone());
+            }
+
+            #[cfg(feature = "combine-img-samplers")]
             if self.cfg.combine_img_samplers {
                 vars = combine_img_samplers(vars);
             }
+            for (desc_bind, elems) in desc_elems {
+                if let Some(desc_info) = desc_infos.get_mut(&desc_bind) {
+                    desc_info.used_elements = match elems {
+                        Some(x) => UsedElements::Elements(x.into_iter().collect()),
+                        None => UsedElements::Unbounded,
+                    };
+                }
+            }
+            if self.cfg.reject_combined_img_samplers {
+                for var in vars.iter() {
+                    if let Variable::Descriptor {
+                        name,
+                        desc_ty: DescriptorType::CombinedImageSampler(),
+                        ..
+                    } = var
+                    {
+                        return Err(anyhow!(
+                            "combined image sampler {:?} is not supported",
+                            name.as_deref().unwrap_or("<unnamed>")
+                        ));
+                    }
+                }
+            }
+            self.collect_desc_aliases(&vars, &mut desc_infos)?;
+            let gl_vars = module_gl_vars
+                .iter()
+                .filter(|(var_id, _)| match accessed_var_ids {
+                    Some(x) => x.contains(*var_id),
+                    None => true,
+                })
+                .map(|(_, var)| var.clone())
+                .collect();
+            let shared_vars = module_shared_vars
+                .iter()
+                .filter(|(var_id, _)| match accessed_var_ids {
+                    Some(x) => x.contains(*var_id),
+                    None => true,
+                })
+                .map(|(_, var)| var.clone())
+                .collect();
+            // Entry points can't use more than one task payload.
+            let task_payload = module_task_payloads
+                .iter()
+                .find(|(var_id, _)| match accessed_var_ids {
+                    Some(x) => x.contains(*var_id),
+                    None => true,
+                })
+                .map(|(_, var)| var.clone());
+            let mut input_builtins = BTreeSet::new();
+            let mut output_builtins = BTreeSet::new();
+            for (var_id, var_alloc) in self.var_reg.iter() {
+                let is_accessed = match accessed_var_ids {
+                    Some(x) => x.contains(var_id),
+                    None => true,
+                };
+                let builtins = match var_alloc.store_cls {
+                    StorageClass::Input if is_accessed => &mut input_builtins,
+                    StorageClass::Output if is_accessed => &mut output_builtins,
+                    _ => continue,
+                };
+                let builtin = self
+                    .deco_reg
+                    .get_u32(*var_id, spirv::Decoration::BuiltIn)
+                    .ok()
+                    .and_then(spirv::BuiltIn::from_u32);
+                builtins.extend(builtin);
+                if let Some(x) = self.builtin_blocks.get(var_id) {
+                    builtins.extend(x.iter().copied());
+                }
+            }
+            let writes_point_size = if is_access_known {
+                access.writes_point_size
+            } else {
+                output_builtins.contains(&spirv::BuiltIn::PointSize)
+            };
+            let private_vars = module_private_vars
+                .iter()
+                .filter(|(var_id, _)| match accessed_var_ids {
+                    Some(x) => x.contains(*var_id),
+                    None => true,
+                })
+                .map(|(_, var)| var.clone())
+                .collect();
+            let global_vars = module_global_vars
+                .iter()
+                .filter(|(var_id, _)| match accessed_var_ids {
+                    Some(x) => x.contains(*var_id),
+                    None => true,
+                })
+                .map(|(_, var)| var.clone())
+                .collect();
+            let array_size_spec_ids = self
+                .var_reg
+                .iter()
+                .filter(|(var_id, _)| match accessed_var_ids {
+                    Some(x) => x.contains(*var_id),
+                    None => true,
+                })
+                .filter_map(|(var_id, _)| self.spec_sized_arrays.get(var_id))
+                .flatten()
+                .map(|x| x.spec_id)
+                .collect();
             let specs = self.collect_entry_point_specs()?;
             vars.extend(specs);
-            let exec_modes = self.collect_exec_modes(*id, &entry_point_declr.exec_modes)?;
+            let exec_modes = self.collect_exec_modes(&entry_point_declr.exec_modes)?;
+            let exec_mode_spec_ids = self.collect_exec_mode_spec_ids(&entry_point_declr.exec_modes);
+            let raw_exec_modes = entry_point_declr.exec_modes.clone();
             let entry_point = EntryPoint {
-                name: entry_point_declr.name.to_owned(),
+                name: if own_names {
+                    entry_point_declr.name.to_owned()
+                } else {
+                    String::new()
+                },
+                demangled_name: self.cfg.demangle(entry_point_declr.name),
                 exec_model: entry_point_declr.exec_model,
                 vars,
                 exec_modes,
+                raw_exec_modes,
+                desc_infos,
+                input_comp_masks,
+                io_decos,
+                io_member_decos,
+                gl_vars,
+                shared_vars,
+                task_payload,
+                input_builtins,
+                output_builtins,
+                private_vars,
+                global_vars,
+                literal_samplers: self.literal_samplers.clone(),
+                spec_defaults: self.collect_spec_defaults(),
+                uses_derivatives: access.uses_derivatives,
+                implicit_locations,
+                push_const_bytes: push_const_bytes.map(|(x, y)| x as u32..y as u32),
+                writes_external_memory,
+                writes_point_size,
+                array_size_spec_ids,
+                exec_mode_spec_ids,
+                diags,
             };
-            entry_points.push(entry_point);
+            entry_points.push((*id, entry_point));
         }
+        span.record("nentry_point", entry_points.len());
         Ok(entry_points)
     }
+    /// Collect the entry points along with the module-level information of
+    /// the module described by `module_info`.
+    pub fn collect_reflection(&self, module_info: ModuleInfo) -> Result<Reflection> {
+        let entry_points = self.collect_entry_points()?;
+        let consts = self
+            .interp
+            .iter()
+            .map(|(id, constant)| (*id, constant.clone()))
+            .collect();
+        let tys = self
+            .interned_tys
+            .iter()
+            .map(|(id, ty)| (*id, ty.clone()))
+            .collect();
+        Ok(Reflection {
+            module_info,
+            entry_points,
+            consts,
+            tys,
+            ty_arena: self.ty_arena.clone(),
+            coop_mat_tys: self
+                .coop_mat_tys
+                .iter()
+                .map(|(id, ty)| (*id, ty.clone()))
+                .collect(),
+            diags: self.module_diags.clone(),
+        })
+    }
 }
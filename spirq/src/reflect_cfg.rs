@@ -1,28 +1,235 @@
+//! Reflection configuration and its presets.
+//!
+//! The presets [`ReflectConfig::for_vulkan`], [`ReflectConfig::for_hlsl_dxc`],
+//! [`ReflectConfig::for_wgpu`] and [`ReflectConfig::for_opengl`] need no
+//! built-in handling. Built-in variables and the members of built-in blocks
+//! are never listed in [`EntryPoint::vars`] but in
+//! [`EntryPoint::input_builtins`] and [`EntryPoint::output_builtins`], whether
+//! they are declared as `gl_PerVertex` blocks by glslang or as separate
+//! variables by DXC and naga.
+use std::{
+    borrow::Cow,
+    convert::TryFrom,
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
 use fnv::FnvHashMap as HashMap;
 
 use crate::{
+    arena::Arena,
+    binary::{ModuleInfo, SpirvBinaryExt},
     constant::ConstantValue,
-    entry_point::EntryPoint,
+    demangle::demangle,
+    entry_point::{BorrowedEntryPoint, EntryPoint},
     error::Result,
     inspect::{FnInspector, Inspector},
-    parse::{Instr, SpirvBinary},
+    instr::{decode_op, OpEntryPoint},
+    parse::{Instr, Instrs, SpirvBinary},
     reflect::{reflect, FunctionInspector, ReflectIntermediate},
+    reflection::Reflection,
+    spirv::Op,
     var::SpecId,
 };
 
+/// What to do when a descriptor resource has no `Binding` decoration, which is
+/// common in SPIR-V targeting OpenGL.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MissingBindingPolicy {
+    /// Fail the reflection.
+    Error,
+    /// Assume `(set=0, bind=0)` and report a
+    /// [`Diagnostic::MissingBinding`](crate::diagnostic::Diagnostic) warning.
+    #[default]
+    WarnAndDefault,
+    /// Assign binding points following the largest binding point declared in
+    /// the same descriptor set, in the order of variable IDs.
+    AutoAssign,
+}
+
+/// What to do when an ID or a struct member is given different names by
+/// multiple `OpName`s or `OpMemberName`s, which is emitted by some versions
+/// of Slang and DXC. Duplicate names that are identical are always accepted.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NameCollisionPolicy {
+    /// Fail the reflection.
+    Error,
+    /// Keep the first declared name.
+    #[default]
+    FirstWins,
+    /// Keep the last declared name.
+    LastWins,
+}
+
+/// What to do when an ID or a struct member is decorated more than once by a
+/// same decoration with different operands, which is emitted by some SPIR-V
+/// linkers. Duplicate decorations with identical operands are always
+/// accepted.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DecorationCollisionPolicy {
+    /// Fail the reflection.
+    Error,
+    /// Keep the first decoration and report a
+    /// [`Diagnostic::DecorationCollision`](crate::diagnostic::Diagnostic)
+    /// warning.
+    #[default]
+    FirstWins,
+    /// Keep the last decoration and report a
+    /// [`Diagnostic::DecorationCollision`](crate::diagnostic::Diagnostic)
+    /// warning.
+    LastWins,
+}
+
+/// Number of instructions in function bodies scanned between yields in
+/// [`ReflectConfig::reflect_async`].
+const ASYNC_CHUNK_NINSTR: usize = 4096;
+
+/// Future returning pending once so that other tasks can run.
+struct YieldNow(bool);
+impl Future for YieldNow {
+    type Output = ();
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.0 {
+            Poll::Ready(())
+        } else {
+            self.0 = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
+
+/// Reflection in progress, shared by [`ReflectConfig::reflect_full`] which
+/// runs it to the end at once and [`ReflectConfig::reflect_async`] which
+/// yields between chunks of function bodies.
+struct ReflectTask<'a> {
+    module_info: ModuleInfo,
+    itm: ReflectIntermediate<'a>,
+    instrs: Instrs<'a>,
+    inspector: FunctionInspector<'a>,
+}
+impl<'a> ReflectTask<'a> {
+    /// Scan the module header and parse all global declarations.
+    fn new(cfg: &'a ReflectConfig, spv: &'a SpirvBinary) -> Result<Self> {
+        Self::new_in(cfg, spv, Arena::default())
+    }
+    /// Like [`ReflectTask::new`], but allocate intermediate tables in
+    /// `arena`.
+    fn new_in(cfg: &'a ReflectConfig, spv: &'a SpirvBinary, arena: Arena<'a>) -> Result<Self> {
+        let module_info = spv.quick_scan()?;
+        let mut itm = ReflectIntermediate::new_in(cfg, arena)?;
+        itm.id_bound = cfg.id_bound(spv)?;
+        itm.version = module_info.version;
+        let mut instrs = spv.instrs()?;
+        itm.parse_global_declrs(&mut instrs)?;
+        let inspector = FunctionInspector::with_id_bound_in(dense_id_bound(&itm, spv), arena);
+        Ok(ReflectTask {
+            module_info,
+            itm,
+            instrs,
+            inspector,
+        })
+    }
+    /// Scan at most `max_ninstr` instructions of function bodies. Returns
+    /// `true` if all function bodies have been scanned.
+    fn scan_fns(&mut self, max_ninstr: usize) -> Result<bool> {
+        // Function bodies are only scanned for resource accesses.
+        if self.itm.cfg.skips_fn_bodies() {
+            return Ok(true);
+        }
+        let ninstr =
+            self.itm
+                .parse_functions_chunk(&mut self.instrs, &mut self.inspector, max_ninstr)?;
+        Ok(ninstr < max_ninstr)
+    }
+    fn finish(self) -> Result<Reflection> {
+        self.itm.collect_reflection(self.module_info)
+    }
+}
+
+/// Bound of the IDs looked up in dense tables while function bodies are
+/// scanned. A module can't declare more results than it has words, so the
+/// tables don't grow with huge ID bounds in headers.
+fn dense_id_bound(itm: &ReflectIntermediate<'_>, spv: &SpirvBinary) -> u32 {
+    itm.id_bound.min(spv.words().len() as u32)
+}
+
+/// Function demangling entry point names, see [`ReflectConfig::demangler`].
+pub type Demangler = Arc<dyn Fn(&str) -> Option<String> + Send + Sync>;
+
 /// Reflection configuration builder.
 #[derive(Default, Clone)]
 pub struct ReflectConfig {
     pub(crate) spv: Option<SpirvBinary>,
     pub(crate) ref_all_rscs: bool,
+    pub(crate) track_static_use: bool,
+    #[cfg(feature = "combine-img-samplers")]
     pub(crate) combine_img_samplers: bool,
     pub(crate) gen_unique_names: bool,
     pub(crate) spec_values: HashMap<SpecId, ConstantValue>,
+    pub(crate) missing_binding_policy: MissingBindingPolicy,
+    pub(crate) name_collision_policy: NameCollisionPolicy,
+    pub(crate) deco_collision_policy: DecorationCollisionPolicy,
+    pub(crate) validate_ids: bool,
+    pub(crate) gl: bool,
+    pub(crate) strict_spec_array_sizes: bool,
+    pub(crate) strict_desc_aliasing: bool,
+    pub(crate) reject_combined_img_samplers: bool,
+    pub(crate) member_located_io_blocks: bool,
+    pub(crate) auto_assign_locations: bool,
+    pub(crate) private_vars: bool,
+    pub(crate) demangler: Option<Demangler>,
 }
 impl ReflectConfig {
     pub fn new() -> Self {
         Default::default()
     }
+    /// Preset for modules compiled for Vulkan, e.g., from GLSL by glslang or
+    /// shaderc. Vulkan requires every descriptor resource to be decorated
+    /// with a binding point so the absence of one fails the reflection.
+    pub fn for_vulkan() -> Self {
+        let mut out = Self::new();
+        out.missing_binding_policy(MissingBindingPolicy::Error);
+        out
+    }
+    /// Preset for modules compiled from HLSL by DXC. HLSL textures and
+    /// samplers are separate objects, so those sharing a same binding point
+    /// are combined. Resources without binding decorations, e.g., from
+    /// modules linked from DXC libraries, are assumed at binding point 0 and
+    /// reported by [`Diagnostic::MissingBinding`] warnings.
+    ///
+    /// [`Diagnostic::MissingBinding`]: crate::diagnostic::Diagnostic::MissingBinding
+    #[cfg(feature = "combine-img-samplers")]
+    pub fn for_hlsl_dxc() -> Self {
+        let mut out = Self::new();
+        out.combine_img_samplers(true)
+            .missing_binding_policy(MissingBindingPolicy::WarnAndDefault);
+        out
+    }
+    /// Preset for modules consumed by wgpu, e.g., translated from WGSL by
+    /// naga. WebGPU has no combined image samplers, requires every resource
+    /// to have an explicit binding point, and can't bind resources of
+    /// different types at a same binding point, so all of them fail the
+    /// reflection.
+    pub fn for_wgpu() -> Self {
+        let mut out = Self::new();
+        out.missing_binding_policy(MissingBindingPolicy::Error)
+            .strict_desc_aliasing(true)
+            .reject_combined_img_samplers(true);
+        out
+    }
+    /// Preset for modules consumed with `ARB_gl_spirv`. See
+    /// [`ReflectConfig::gl`]. Texture and image units default to 0 in OpenGL
+    /// so missing bindings are reported in diagnostics. Stage inputs and
+    /// outputs without locations, which some modules compiled for OpenGL
+    /// have, are assigned locations.
+    pub fn for_opengl() -> Self {
+        let mut out = Self::new();
+        out.gl(true).auto_assign_locations(true);
+        out
+    }
 
     /// SPIR-V binary to be reflected.
     pub fn spv<Spv: Into<SpirvBinary>>(&mut self, x: Spv) -> &mut Self {
@@ -31,18 +238,43 @@ impl ReflectConfig {
     }
     /// Reference all defined resources even the resource is not used by an
     /// entry point. Otherwise and by default, only the referenced resources are
-    /// assigned to entry points.
+    /// assigned to entry points. Without the `access-analysis` feature,
+    /// resources are referenced by the entry points listing them in their
+    /// interfaces in modules of SPIR-V 1.4 and later, and by all entry points
+    /// in earlier modules.
     ///
-    /// Can be faster for modules with only entry point; slower for multiple
-    /// entry points.
+    /// Function bodies are not scanned for resource accesses when this is set
+    /// and no inspector is used, which makes reflection of large modules much
+    /// faster, unless [`ReflectConfig::track_static_use`] is also set.
     pub fn ref_all_rscs(&mut self, x: bool) -> &mut Self {
         self.ref_all_rscs = x;
         self
     }
+    /// Scan function bodies for resource accesses even if
+    /// [`ReflectConfig::ref_all_rscs`] is set, so that all resources are
+    /// reported and those statically used by each entry point are flagged
+    /// by [`DescriptorInfo::is_statically_used`]. Has no effect without the
+    /// `access-analysis` feature.
+    ///
+    /// [`DescriptorInfo::is_statically_used`]: crate::entry_point::DescriptorInfo::is_statically_used
+    pub fn track_static_use(&mut self, x: bool) -> &mut Self {
+        self.track_static_use = x;
+        self
+    }
+    /// Whether all resources are considered referenced by every entry point.
+    /// Resource accesses are unknown without the `access-analysis` feature.
+    pub(crate) fn refs_all_rscs(&self) -> bool {
+        self.ref_all_rscs || cfg!(not(feature = "access-analysis"))
+    }
+    /// Whether function bodies can be skipped when no inspector is used.
+    pub(crate) fn skips_fn_bodies(&self) -> bool {
+        self.refs_all_rscs() && !self.track_static_use
+    }
     /// Combine images and samplers sharing a same binding point to combined
     /// image sampler descriptors.
     ///
     /// Faster when disabled, but useful for modules derived from HLSL.
+    #[cfg(feature = "combine-img-samplers")]
     pub fn combine_img_samplers(&mut self, x: bool) -> &mut Self {
         self.combine_img_samplers = x;
         self
@@ -59,26 +291,249 @@ impl ReflectConfig {
         self.spec_values.insert(spec_id, value);
         self
     }
+    /// Decide how descriptor resources without a `Binding` decoration are
+    /// handled. By default, binding point 0 is assumed and a diagnostic is
+    /// reported. Descriptors with a binding point chosen by SPIR-Q are marked
+    /// in [`EntryPoint::desc_infos`].
+    pub fn missing_binding_policy(&mut self, x: MissingBindingPolicy) -> &mut Self {
+        self.missing_binding_policy = x;
+        self
+    }
+
+    /// Decide how conflicting debug names of a same ID or struct member are
+    /// handled. By default, the first name is kept.
+    pub fn name_collision_policy(&mut self, x: NameCollisionPolicy) -> &mut Self {
+        self.name_collision_policy = x;
+        self
+    }
+
+    /// Decide how conflicting decorations of a same ID or struct member are
+    /// handled. By default, the first decoration is kept and a diagnostic is
+    /// reported.
+    pub fn deco_collision_policy(&mut self, x: DecorationCollisionPolicy) -> &mut Self {
+        self.deco_collision_policy = x;
+        self
+    }
+
+    /// Check that all IDs referenced by the module are below the ID bound
+    /// declared in the module header before parsing, and fail the reflection
+    /// at the first out-of-bound reference. Disabled by default because some
+    /// toolchains emit modules with a stale ID bound.
+    pub fn validate_ids(&mut self, x: bool) -> &mut Self {
+        self.validate_ids = x;
+        self
+    }
+
+    /// Fail the reflection when an array in a block is sized by a
+    /// specialization constant, which is undefined behavior in Vulkan.
+    /// Otherwise and by default, such arrays are reported by
+    /// [`Diagnostic::SpecConstantSizedArray`] warnings.
+    ///
+    /// [`Diagnostic::SpecConstantSizedArray`]: crate::diagnostic::Diagnostic::SpecConstantSizedArray
+    pub fn strict_spec_array_sizes(&mut self, x: bool) -> &mut Self {
+        self.strict_spec_array_sizes = x;
+        self
+    }
+
+    /// Fail the reflection when descriptor resources of different
+    /// `VkDescriptorType`s are aliased at a same binding point. Otherwise and
+    /// by default, such binding points are flagged by
+    /// [`DescriptorInfo::has_aliased_desc_tys`]. Aliased resources are always
+    /// listed in [`DescriptorInfo::aliases`].
+    ///
+    /// [`DescriptorInfo::has_aliased_desc_tys`]: crate::entry_point::DescriptorInfo::has_aliased_desc_tys
+    /// [`DescriptorInfo::aliases`]: crate::entry_point::DescriptorInfo::aliases
+    pub fn strict_desc_aliasing(&mut self, x: bool) -> &mut Self {
+        self.strict_desc_aliasing = x;
+        self
+    }
+
+    /// Fail the reflection when an entry point uses a combined image sampler
+    /// descriptor, which APIs like WebGPU don't have. Images and samplers
+    /// combined by [`ReflectConfig::combine_img_samplers`] are also rejected.
+    pub fn reject_combined_img_samplers(&mut self, x: bool) -> &mut Self {
+        self.reject_combined_img_samplers = x;
+        self
+    }
+    /// Reflect stage input and output blocks whose locations are decorated on
+    /// the members rather than the block variables, like
+    /// `out Block { layout(location = 1) vec4 x; }` in GLSL. Such blocks are
+    /// reported at the location of their first members, and like any other
+    /// I/O block their members have no offset. Otherwise and by default,
+    /// they are ignored like built-in blocks.
+    ///
+    /// Useful for stage linkage analysis.
+    pub fn member_located_io_blocks(&mut self, x: bool) -> &mut Self {
+        self.member_located_io_blocks = x;
+        self
+    }
+
+    /// Assign locations to stage inputs and outputs without location
+    /// decorations, which are emitted for some modules compiled for OpenGL.
+    /// Locations are assigned following the largest location declared by the
+    /// inputs or outputs in the module, in the order of variable IDs, and
+    /// are listed in [`EntryPoint::implicit_locations`]. Otherwise and by
+    /// default, such variables are ignored like built-ins.
+    ///
+    /// [`EntryPoint::implicit_locations`]: crate::entry_point::EntryPoint::implicit_locations
+    pub fn auto_assign_locations(&mut self, x: bool) -> &mut Self {
+        self.auto_assign_locations = x;
+        self
+    }
+
+    /// Collect module-scope variables in the `Private` storage class, which
+    /// are emitted for global variables by compilers like DXC and rust-gpu,
+    /// into [`EntryPoint::private_vars`]. Off by default.
+    ///
+    /// [`EntryPoint::private_vars`]: crate::entry_point::EntryPoint::private_vars
+    pub fn private_vars(&mut self, x: bool) -> &mut Self {
+        self.private_vars = x;
+        self
+    }
+    /// Demangle entry point names into [`EntryPoint::demangled_name`] with
+    /// `f`, which returns `None` for names that are not mangled. By default,
+    /// names are demangled by [`demangle`] for the manglings known to
+    /// SPIR-Q. Custom demanglers can fall back to it for other names.
+    ///
+    /// [`EntryPoint::demangled_name`]: crate::entry_point::EntryPoint::demangled_name
+    /// [`demangle`]: crate::demangle::demangle
+    pub fn demangler<F>(&mut self, f: F) -> &mut Self
+    where
+        F: Fn(&str) -> Option<String> + Send + Sync + 'static,
+    {
+        self.demangler = Some(Arc::new(f));
+        self
+    }
+    pub(crate) fn demangle(&self, name: &str) -> Option<String> {
+        match &self.demangler {
+            Some(f) => f(name),
+            None => demangle(name),
+        }
+    }
+
+    /// Reflect the module with OpenGL semantics as in `ARB_gl_spirv`, where
+    /// resources are identified by uniform locations and per-kind binding
+    /// points. The OpenGL view of the variables is reported in
+    /// [`EntryPoint::gl_vars`].
+    ///
+    /// OpenGL only has combined image samplers so this also enables
+    /// [`ReflectConfig::combine_img_samplers`] with the `combine-img-samplers`
+    /// feature, which can be disabled afterwards.
+    pub fn gl(&mut self, x: bool) -> &mut Self {
+        self.gl = x;
+        #[cfg(feature = "combine-img-samplers")]
+        if x {
+            self.combine_img_samplers = true;
+        }
+        self
+    }
 
     /// Reflect the SPIR-V binary and extract all entry points.
     pub fn reflect(&mut self) -> Result<Vec<EntryPoint>> {
+        self.reflect_full().map(|x| x.entry_points)
+    }
+    /// Reflect the SPIR-V binary and extract all entry points along with the
+    /// module header, types, constants and diagnostics of the module. The
+    /// entry points are the same as returned by [`ReflectConfig::reflect`].
+    pub fn reflect_full(&mut self) -> Result<Reflection> {
         let spv = self.spv.take().unwrap_or_default();
-        let mut itm = ReflectIntermediate::new(self)?;
-        let inspector = FunctionInspector::new();
-        reflect(&mut itm, &mut spv.instrs()?, inspector)
+        let mut task = ReflectTask::new(self, &spv)?;
+        task.scan_fns(usize::MAX)?;
+        task.finish()
+    }
+    /// Reflect the SPIR-V binary and extract all entry points like
+    /// [`ReflectConfig::reflect`], but allocate the hash tables of reflection
+    /// intermediates in `bump` instead of the global allocator. The memory is
+    /// not returned to `bump` until it is reset, so a same bump can be reset
+    /// between modules to reflect them without hitting the global allocator
+    /// for intermediates.
+    #[cfg(feature = "bumpalo")]
+    pub fn reflect_in(&mut self, bump: &bumpalo::Bump) -> Result<Vec<EntryPoint>> {
+        self.reflect_full_in(bump).map(|x| x.entry_points)
+    }
+    /// Reflect the SPIR-V binary like [`ReflectConfig::reflect_full`], with
+    /// intermediates allocated in `bump` like [`ReflectConfig::reflect_in`].
+    #[cfg(feature = "bumpalo")]
+    pub fn reflect_full_in(&mut self, bump: &bumpalo::Bump) -> Result<Reflection> {
+        let spv = self.spv.take().unwrap_or_default();
+        let mut task = ReflectTask::new_in(self, &spv, Arena::bump(bump))?;
+        task.scan_fns(usize::MAX)?;
+        task.finish()
+    }
+    /// Reflect `spv` and extract all entry points like
+    /// [`ReflectConfig::reflect`], but borrow the entry point names from `spv`
+    /// rather than copying them. The binary set by [`ReflectConfig::spv`] is
+    /// left untouched.
+    pub fn reflect_borrowed<'b>(
+        &mut self,
+        spv: &'b SpirvBinary,
+    ) -> Result<Vec<BorrowedEntryPoint<'b>>> {
+        let entry_points = {
+            let mut task = ReflectTask::new(self, spv)?;
+            task.scan_fns(usize::MAX)?;
+            task.itm.collect_entry_points_impl(false)?
+        };
+        // The intermediate only lives as long as `self` is borrowed, so the
+        // names are taken from the entry point declarations in `spv` again.
+        let mut names = HashMap::<u32, &'b str>::default();
+        let mut instrs = spv.instrs()?;
+        while let Some(instr) = instrs.next()? {
+            match decode_op(instr) {
+                Some(Op::EntryPoint) => {
+                    let op = OpEntryPoint::try_from(instr)?;
+                    names.insert(op.entry_point_id, op.name);
+                }
+                Some(Op::Capability)
+                | Some(Op::Extension)
+                | Some(Op::ExtInstImport)
+                | Some(Op::MemoryModel) => {}
+                _ => break,
+            }
+        }
+        let entry_points = entry_points
+            .into_iter()
+            .map(|(func_id, entry_point)| BorrowedEntryPoint {
+                name: Cow::Borrowed(names.get(&func_id).copied().unwrap_or_default()),
+                entry_point,
+            })
+            .collect();
+        Ok(entry_points)
+    }
+    /// Reflect the SPIR-V binary and extract all entry points like
+    /// [`ReflectConfig::reflect`], but yield to the executor periodically when
+    /// function bodies are scanned, so that reflecting a very large module
+    /// doesn't block an executor thread for long. The future doesn't depend
+    /// on any async runtime.
+    pub async fn reflect_async(&mut self) -> Result<Vec<EntryPoint>> {
+        let spv = self.spv.take().unwrap_or_default();
+        let mut task = ReflectTask::new(self, &spv)?;
+        YieldNow(false).await;
+        while !task.scan_fns(ASYNC_CHUNK_NINSTR)? {
+            YieldNow(false).await;
+        }
+        task.finish().map(|x| x.entry_points)
     }
     /// Reflect the SPIR-V binary and extract all entry points with an inspector
     /// for customized reflection subroutines.
     pub fn reflect_inspect<I: Inspector>(&mut self, inspector: &mut I) -> Result<Vec<EntryPoint>> {
         let spv = self.spv.take().unwrap_or_default();
         let mut itm = ReflectIntermediate::new(self)?;
-        let mut func_inspector = FunctionInspector::new();
+        itm.id_bound = self.id_bound(&spv)?;
+        itm.version = spv.version()?;
+        let mut func_inspector = FunctionInspector::with_id_bound(dense_id_bound(&itm, &spv));
         reflect(
             &mut itm,
             &mut spv.instrs()?,
             func_inspector.chain(inspector),
         )
     }
+    fn id_bound(&self, spv: &SpirvBinary) -> Result<u32> {
+        if self.validate_ids {
+            spv.validate_ids()
+        } else {
+            spv.id_bound()
+        }
+    }
     /// Reflect the SPIR-V binary and extract all entry points with an inspector
     /// function for customized reflection subroutines.
     pub fn reflect_inspect_by<F: FnMut(&mut ReflectIntermediate<'_>, &Instr)>(
@@ -0,0 +1,83 @@
+//! Reflection of a whole SPIR-V module.
+//!
+//! [`ReflectConfig::reflect`] only returns the entry points of a module.
+//! [`ReflectConfig::reflect_full`] returns a [`Reflection`] which also
+//! carries the module-level information the entry points are reflected from,
+//! like the module header, the types and the constants declared in the
+//! module.
+//!
+//! [`ReflectConfig::reflect`]: crate::reflect_cfg::ReflectConfig::reflect
+//! [`ReflectConfig::reflect_full`]: crate::reflect_cfg::ReflectConfig::reflect_full
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use crate::{
+    binary::ModuleInfo,
+    constant::Constant,
+    diagnostic::Diagnostic,
+    entry_point::EntryPoint,
+    spirv,
+    ty::{ScalarType, Type},
+    ty_arena::TypeArena,
+};
+
+/// Cooperative matrix type declared by `OpTypeCooperativeMatrixKHR` of
+/// `SPV_KHR_cooperative_matrix`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CooperativeMatrixType {
+    /// Type of the matrix components.
+    pub component_ty: ScalarType,
+    /// Scope of the invocations sharing the matrix.
+    pub scope: spirv::Scope,
+    /// Number of rows.
+    pub nrow: u32,
+    /// Number of columns.
+    pub ncolumn: u32,
+    /// Operand position of the matrix in `OpCooperativeMatrixMulAddKHR`.
+    pub usage: spirv::CooperativeMatrixUse,
+}
+
+/// Result of reflecting a SPIR-V module.
+#[derive(Debug, Clone)]
+pub struct Reflection {
+    /// Metadata declared in the header sections of the module.
+    pub module_info: ModuleInfo,
+    /// Entry points in the module, the same as returned by
+    /// [`ReflectConfig::reflect`].
+    ///
+    /// [`ReflectConfig::reflect`]: crate::reflect_cfg::ReflectConfig::reflect
+    pub entry_points: Vec<EntryPoint>,
+    /// Constants and specialization constants declared in the module,
+    /// indexed by their result IDs. Composite constants and specialization
+    /// constant operations that can't be folded are not listed.
+    pub consts: BTreeMap<u32, Constant>,
+    /// Types declared in the module, indexed by their result IDs. Equal
+    /// types declared more than once share a node in `ty_arena`.
+    pub tys: BTreeMap<u32, Arc<Type>>,
+    /// Types interned while the module is parsed. Types of variables can be
+    /// interned into it to share the nodes of the equal declared types.
+    pub ty_arena: TypeArena,
+    /// Cooperative matrix types declared in the module, indexed by their
+    /// result IDs. [`Type`] can't represent them, so they are not listed in
+    /// `tys`, and arrays, structs and variables of them are not reflected.
+    pub coop_mat_tys: BTreeMap<u32, CooperativeMatrixType>,
+    /// Warnings found in the module that are not specific to an entry
+    /// point. They are also listed in the diagnostics of every entry point.
+    pub diags: Vec<Diagnostic>,
+}
+impl Reflection {
+    /// Get the entry point named `name`. If multiple entry points share the
+    /// name with different execution models, the first one is returned.
+    pub fn entry_point(&self, name: &str) -> Option<&EntryPoint> {
+        self.entry_points.iter().find(|x| x.name == name)
+    }
+    /// Get the specialization constant with specialization ID `spec_id`.
+    pub fn spec_const(&self, spec_id: u32) -> Option<&Constant> {
+        self.consts.values().find(|x| x.spec_id == Some(spec_id))
+    }
+}
+impl From<Reflection> for Vec<EntryPoint> {
+    fn from(x: Reflection) -> Self {
+        x.entry_points
+    }
+}
@@ -0,0 +1,1047 @@
+//! Serialization of reflection results with [serde](https://serde.rs),
+//! enabled by the `serde` feature.
+//!
+//! [`EntryPoint`] and the SPIR-Q types it's composed of implement
+//! `Serialize` and `Deserialize`. [`Type`], [`Variable`], [`DescriptorType`],
+//! [`ExecutionMode`] and the other types defined in `spq-core` can't implement
+//! serde traits in this crate, so they can be (de)serialized as fields with
+//! the modules here instead:
+//!
+//! ```ignore
+//! #[derive(Serialize, Deserialize)]
+//! struct CachedVariable {
+//!     #[serde(with = "spirq::serde_support::variable")]
+//!     var: Variable,
+//! }
+//! ```
+//!
+//! SPIR-V enumerants like execution models and image formats are serialized
+//! by their names.
+use std::collections::{BTreeMap, BTreeSet};
+use std::convert::{TryFrom, TryInto};
+use std::fmt;
+use std::ops::Range;
+use std::str::FromStr;
+
+use num_traits::FromPrimitive;
+use ordered_float::OrderedFloat;
+use serde::{de::Error as _, ser::Error as _, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{
+    constant::{Constant, ConstantValue},
+    diagnostic::Diagnostic,
+    entry_point::{
+        DescriptorInfo, EntryPoint, InterfaceVariable, LiteralSampler, PrivateVariable,
+        RawExecutionMode, SharedVariable,
+    },
+    func::ExecutionMode,
+    gl::GlVariable,
+    spirv,
+    ty::{
+        AccelStructType, AccessType, ArrayType, CombinedImageSamplerType, DescriptorType,
+        DeviceAddressType, ImageType, MatrixAxisOrder, MatrixType, PointerType, RayQueryType,
+        SampledImageType, SamplerType, ScalarType, StorageImageType, StructMember, StructType,
+        SubpassDataType, Type, VectorType,
+    },
+    var::{DescriptorBinding, InterfaceLocation, SpecId, Variable},
+};
+
+/// (De)serialize a SPIR-V enumerant by its name.
+pub mod spirv_enum {
+    use super::*;
+
+    pub fn serialize<T: fmt::Debug, S: Serializer>(x: &T, s: S) -> Result<S::Ok, S::Error> {
+        s.collect_str(&format_args!("{:?}", x))
+    }
+    pub fn deserialize<'de, T: FromStr, D: Deserializer<'de>>(d: D) -> Result<T, D::Error> {
+        let name = String::deserialize(d)?;
+        name.parse()
+            .map_err(|_| D::Error::custom(format!("unknown spirv enumerant `{}`", name)))
+    }
+}
+/// (De)serialize a list of SPIR-V enumerants by their names.
+pub mod spirv_enums {
+    use super::*;
+
+    pub fn serialize<T: fmt::Debug, S: Serializer>(x: &[T], s: S) -> Result<S::Ok, S::Error> {
+        s.collect_seq(x.iter().map(|x| format!("{:?}", x)))
+    }
+    pub fn deserialize<'de, T: FromStr, D: Deserializer<'de>>(d: D) -> Result<Vec<T>, D::Error> {
+        Vec::<String>::deserialize(d)?
+            .into_iter()
+            .map(|name| {
+                name.parse()
+                    .map_err(|_| D::Error::custom(format!("unknown spirv enumerant `{}`", name)))
+            })
+            .collect()
+    }
+}
+
+macro_rules! with_def {
+    ($(#[$attr:meta])* $module:ident, $ty:ty, $def:ty) => {
+        $(#[$attr])*
+        pub mod $module {
+            use super::*;
+
+            pub fn serialize<S: Serializer>(x: &$ty, s: S) -> Result<S::Ok, S::Error> {
+                <$def>::try_from(x).map_err(S::Error::custom)?.serialize(s)
+            }
+            pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<$ty, D::Error> {
+                Ok(<$def>::deserialize(d)?.into())
+            }
+        }
+    };
+}
+with_def!(
+    /// (De)serialize a [`DescriptorBinding`].
+    desc_bind,
+    DescriptorBinding,
+    DescriptorBindingDef
+);
+with_def!(
+    /// (De)serialize an [`InterfaceLocation`].
+    location,
+    InterfaceLocation,
+    InterfaceLocationDef
+);
+with_def!(
+    /// (De)serialize an [`AccessType`].
+    access_ty,
+    AccessType,
+    AccessTypeDef
+);
+with_def!(
+    /// (De)serialize a [`Type`].
+    ty,
+    Type,
+    TypeDef
+);
+with_def!(
+    /// (De)serialize a [`DescriptorType`].
+    desc_ty,
+    DescriptorType,
+    DescriptorTypeDef
+);
+with_def!(
+    /// (De)serialize a [`Variable`].
+    variable,
+    Variable,
+    VariableDef
+);
+with_def!(
+    /// (De)serialize a [`Constant`].
+    constant,
+    Constant,
+    ConstantDef
+);
+with_def!(
+    /// (De)serialize an [`ExecutionMode`].
+    exec_mode,
+    ExecutionMode,
+    ExecutionModeDef
+);
+
+type ConvertResult<T> = Result<T, String>;
+
+#[derive(Serialize, Deserialize)]
+struct DescriptorBindingDef {
+    set: u32,
+    bind: u32,
+}
+impl TryFrom<&DescriptorBinding> for DescriptorBindingDef {
+    type Error = String;
+    fn try_from(x: &DescriptorBinding) -> ConvertResult<Self> {
+        Ok(DescriptorBindingDef {
+            set: x.set(),
+            bind: x.bind(),
+        })
+    }
+}
+impl From<DescriptorBindingDef> for DescriptorBinding {
+    fn from(x: DescriptorBindingDef) -> Self {
+        DescriptorBinding::new(x.set, x.bind)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct InterfaceLocationDef {
+    loc: u32,
+    comp: u32,
+}
+impl TryFrom<&InterfaceLocation> for InterfaceLocationDef {
+    type Error = String;
+    fn try_from(x: &InterfaceLocation) -> ConvertResult<Self> {
+        Ok(InterfaceLocationDef {
+            loc: x.loc(),
+            comp: x.comp(),
+        })
+    }
+}
+impl From<InterfaceLocationDef> for InterfaceLocation {
+    fn from(x: InterfaceLocationDef) -> Self {
+        InterfaceLocation::new(x.loc, x.comp)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+enum AccessTypeDef {
+    ReadOnly,
+    WriteOnly,
+    ReadWrite,
+}
+impl TryFrom<&AccessType> for AccessTypeDef {
+    type Error = String;
+    fn try_from(x: &AccessType) -> ConvertResult<Self> {
+        let out = match x {
+            AccessType::ReadOnly => AccessTypeDef::ReadOnly,
+            AccessType::WriteOnly => AccessTypeDef::WriteOnly,
+            AccessType::ReadWrite => AccessTypeDef::ReadWrite,
+        };
+        Ok(out)
+    }
+}
+impl From<AccessTypeDef> for AccessType {
+    fn from(x: AccessTypeDef) -> Self {
+        match x {
+            AccessTypeDef::ReadOnly => AccessType::ReadOnly,
+            AccessTypeDef::WriteOnly => AccessType::WriteOnly,
+            AccessTypeDef::ReadWrite => AccessType::ReadWrite,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+enum ScalarTypeDef {
+    Void,
+    Boolean,
+    Integer { bits: u32, is_signed: bool },
+    Float { bits: u32 },
+}
+impl From<&ScalarType> for ScalarTypeDef {
+    fn from(x: &ScalarType) -> Self {
+        match x {
+            ScalarType::Void => ScalarTypeDef::Void,
+            ScalarType::Boolean => ScalarTypeDef::Boolean,
+            ScalarType::Integer { bits, is_signed } => ScalarTypeDef::Integer {
+                bits: *bits,
+                is_signed: *is_signed,
+            },
+            ScalarType::Float { bits } => ScalarTypeDef::Float { bits: *bits },
+        }
+    }
+}
+impl From<ScalarTypeDef> for ScalarType {
+    fn from(x: ScalarTypeDef) -> Self {
+        match x {
+            ScalarTypeDef::Void => ScalarType::Void,
+            ScalarTypeDef::Boolean => ScalarType::Boolean,
+            ScalarTypeDef::Integer { bits, is_signed } => ScalarType::Integer { bits, is_signed },
+            ScalarTypeDef::Float { bits } => ScalarType::Float { bits },
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct VectorTypeDef {
+    scalar_ty: ScalarTypeDef,
+    nscalar: u32,
+}
+impl From<&VectorType> for VectorTypeDef {
+    fn from(x: &VectorType) -> Self {
+        VectorTypeDef {
+            scalar_ty: (&x.scalar_ty).into(),
+            nscalar: x.nscalar,
+        }
+    }
+}
+impl From<VectorTypeDef> for VectorType {
+    fn from(x: VectorTypeDef) -> Self {
+        VectorType {
+            scalar_ty: x.scalar_ty.into(),
+            nscalar: x.nscalar,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct SampledImageTypeDef {
+    scalar_ty: ScalarTypeDef,
+    #[serde(with = "spirv_enum")]
+    dim: spirv::Dim,
+    is_depth: Option<bool>,
+    is_array: bool,
+    is_multisampled: bool,
+}
+impl From<&SampledImageType> for SampledImageTypeDef {
+    fn from(x: &SampledImageType) -> Self {
+        SampledImageTypeDef {
+            scalar_ty: (&x.scalar_ty).into(),
+            dim: x.dim,
+            is_depth: x.is_depth,
+            is_array: x.is_array,
+            is_multisampled: x.is_multisampled,
+        }
+    }
+}
+impl From<SampledImageTypeDef> for SampledImageType {
+    fn from(x: SampledImageTypeDef) -> Self {
+        SampledImageType {
+            scalar_ty: x.scalar_ty.into(),
+            dim: x.dim,
+            is_depth: x.is_depth,
+            is_array: x.is_array,
+            is_multisampled: x.is_multisampled,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct StructMemberDef {
+    name: Option<String>,
+    offset: Option<usize>,
+    ty: TypeDef,
+    access_ty: AccessTypeDef,
+}
+
+#[derive(Serialize, Deserialize)]
+enum TypeDef {
+    Scalar(ScalarTypeDef),
+    Vector(VectorTypeDef),
+    Matrix {
+        vector_ty: VectorTypeDef,
+        nvector: u32,
+        /// `true` for row-major matrices.
+        is_row_major: Option<bool>,
+        stride: Option<usize>,
+    },
+    Image {
+        scalar_ty: ScalarTypeDef,
+        #[serde(with = "spirv_enum")]
+        dim: spirv::Dim,
+        is_depth: Option<bool>,
+        is_array: bool,
+        is_multisampled: bool,
+        is_sampled: Option<bool>,
+        #[serde(with = "spirv_enum")]
+        fmt: spirv::ImageFormat,
+    },
+    CombinedImageSampler(SampledImageTypeDef),
+    SampledImage(SampledImageTypeDef),
+    StorageImage {
+        #[serde(with = "spirv_enum")]
+        dim: spirv::Dim,
+        is_array: bool,
+        is_multisampled: bool,
+        #[serde(with = "spirv_enum")]
+        fmt: spirv::ImageFormat,
+    },
+    Sampler,
+    SubpassData {
+        scalar_ty: ScalarTypeDef,
+        is_multisampled: bool,
+    },
+    Array {
+        element_ty: Box<TypeDef>,
+        nelement: Option<u32>,
+        stride: Option<usize>,
+    },
+    Struct {
+        name: Option<String>,
+        members: Vec<StructMemberDef>,
+    },
+    AccelStruct,
+    DeviceAddress,
+    DevicePointer {
+        pointee_ty: Box<TypeDef>,
+        #[serde(with = "spirv_enum")]
+        store_cls: spirv::StorageClass,
+    },
+    RayQuery,
+}
+impl TryFrom<&Type> for TypeDef {
+    type Error = String;
+    fn try_from(x: &Type) -> ConvertResult<Self> {
+        let out = match x {
+            Type::Scalar(x) => TypeDef::Scalar(x.into()),
+            Type::Vector(x) => TypeDef::Vector(x.into()),
+            Type::Matrix(x) => TypeDef::Matrix {
+                vector_ty: (&x.vector_ty).into(),
+                nvector: x.nvector,
+                is_row_major: x.axis_order.map(|x| x == MatrixAxisOrder::RowMajor),
+                stride: x.stride,
+            },
+            Type::Image(x) => TypeDef::Image {
+                scalar_ty: (&x.scalar_ty).into(),
+                dim: x.dim,
+                is_depth: x.is_depth,
+                is_array: x.is_array,
+                is_multisampled: x.is_multisampled,
+                is_sampled: x.is_sampled,
+                fmt: x.fmt,
+            },
+            Type::CombinedImageSampler(x) => {
+                TypeDef::CombinedImageSampler((&x.sampled_image_ty).into())
+            }
+            Type::SampledImage(x) => TypeDef::SampledImage(x.into()),
+            Type::StorageImage(x) => TypeDef::StorageImage {
+                dim: x.dim,
+                is_array: x.is_array,
+                is_multisampled: x.is_multisampled,
+                fmt: x.fmt,
+            },
+            Type::Sampler(_) => TypeDef::Sampler,
+            Type::SubpassData(x) => TypeDef::SubpassData {
+                scalar_ty: (&x.scalar_ty).into(),
+                is_multisampled: x.is_multisampled,
+            },
+            Type::Array(x) => TypeDef::Array {
+                element_ty: Box::new(TypeDef::try_from(&*x.element_ty)?),
+                nelement: x.nelement,
+                stride: x.stride,
+            },
+            Type::Struct(x) => {
+                let members = x
+                    .members
+                    .iter()
+                    .map(|x| {
+                        Ok(StructMemberDef {
+                            name: x.name.clone(),
+                            offset: x.offset,
+                            ty: TypeDef::try_from(&x.ty)?,
+                            access_ty: AccessTypeDef::try_from(&x.access_ty)?,
+                        })
+                    })
+                    .collect::<ConvertResult<Vec<_>>>()?;
+                TypeDef::Struct {
+                    name: x.name.clone(),
+                    members,
+                }
+            }
+            Type::AccelStruct(_) => TypeDef::AccelStruct,
+            Type::DeviceAddress(_) => TypeDef::DeviceAddress,
+            Type::DevicePointer(x) => TypeDef::DevicePointer {
+                pointee_ty: Box::new(TypeDef::try_from(&*x.pointee_ty)?),
+                store_cls: x.store_cls,
+            },
+            Type::RayQuery(_) => TypeDef::RayQuery,
+            #[allow(unreachable_patterns)]
+            x => return Err(format!("type {} cannot be serialized", x)),
+        };
+        Ok(out)
+    }
+}
+impl From<TypeDef> for Type {
+    fn from(x: TypeDef) -> Self {
+        match x {
+            TypeDef::Scalar(x) => Type::Scalar(x.into()),
+            TypeDef::Vector(x) => Type::Vector(x.into()),
+            TypeDef::Matrix {
+                vector_ty,
+                nvector,
+                is_row_major,
+                stride,
+            } => Type::Matrix(MatrixType {
+                vector_ty: vector_ty.into(),
+                nvector,
+                axis_order: is_row_major.map(|x| {
+                    if x {
+                        MatrixAxisOrder::RowMajor
+                    } else {
+                        MatrixAxisOrder::ColumnMajor
+                    }
+                }),
+                stride,
+            }),
+            TypeDef::Image {
+                scalar_ty,
+                dim,
+                is_depth,
+                is_array,
+                is_multisampled,
+                is_sampled,
+                fmt,
+            } => Type::Image(ImageType {
+                scalar_ty: scalar_ty.into(),
+                dim,
+                is_depth,
+                is_array,
+                is_multisampled,
+                is_sampled,
+                fmt,
+            }),
+            TypeDef::CombinedImageSampler(x) => {
+                Type::CombinedImageSampler(CombinedImageSamplerType {
+                    sampled_image_ty: x.into(),
+                })
+            }
+            TypeDef::SampledImage(x) => Type::SampledImage(x.into()),
+            TypeDef::StorageImage {
+                dim,
+                is_array,
+                is_multisampled,
+                fmt,
+            } => Type::StorageImage(StorageImageType {
+                dim,
+                is_array,
+                is_multisampled,
+                fmt,
+            }),
+            TypeDef::Sampler => Type::Sampler(SamplerType {}),
+            TypeDef::SubpassData {
+                scalar_ty,
+                is_multisampled,
+            } => Type::SubpassData(SubpassDataType {
+                scalar_ty: scalar_ty.into(),
+                is_multisampled,
+            }),
+            TypeDef::Array {
+                element_ty,
+                nelement,
+                stride,
+            } => Type::Array(ArrayType {
+                element_ty: Box::new((*element_ty).into()),
+                nelement,
+                stride,
+            }),
+            TypeDef::Struct { name, members } => Type::Struct(StructType {
+                name,
+                members: members
+                    .into_iter()
+                    .map(|x| StructMember {
+                        name: x.name,
+                        offset: x.offset,
+                        ty: x.ty.into(),
+                        access_ty: x.access_ty.into(),
+                    })
+                    .collect(),
+            }),
+            TypeDef::AccelStruct => Type::AccelStruct(AccelStructType {}),
+            TypeDef::DeviceAddress => Type::DeviceAddress(DeviceAddressType {}),
+            TypeDef::DevicePointer {
+                pointee_ty,
+                store_cls,
+            } => Type::DevicePointer(PointerType {
+                pointee_ty: Box::new((*pointee_ty).into()),
+                store_cls,
+            }),
+            TypeDef::RayQuery => Type::RayQuery(RayQueryType {}),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+enum DescriptorTypeDef {
+    Sampler,
+    CombinedImageSampler,
+    SampledImage,
+    StorageImage(AccessTypeDef),
+    UniformTexelBuffer,
+    StorageTexelBuffer(AccessTypeDef),
+    UniformBuffer,
+    StorageBuffer(AccessTypeDef),
+    InputAttachment(u32),
+    AccelStruct,
+}
+impl TryFrom<&DescriptorType> for DescriptorTypeDef {
+    type Error = String;
+    fn try_from(x: &DescriptorType) -> ConvertResult<Self> {
+        let out = match x {
+            DescriptorType::Sampler() => DescriptorTypeDef::Sampler,
+            DescriptorType::CombinedImageSampler() => DescriptorTypeDef::CombinedImageSampler,
+            DescriptorType::SampledImage() => DescriptorTypeDef::SampledImage,
+            DescriptorType::StorageImage(x) => DescriptorTypeDef::StorageImage(x.try_into()?),
+            DescriptorType::UniformTexelBuffer() => DescriptorTypeDef::UniformTexelBuffer,
+            DescriptorType::StorageTexelBuffer(x) => {
+                DescriptorTypeDef::StorageTexelBuffer(x.try_into()?)
+            }
+            DescriptorType::UniformBuffer() => DescriptorTypeDef::UniformBuffer,
+            DescriptorType::StorageBuffer(x) => DescriptorTypeDef::StorageBuffer(x.try_into()?),
+            DescriptorType::InputAttachment(x) => DescriptorTypeDef::InputAttachment(*x),
+            DescriptorType::AccelStruct() => DescriptorTypeDef::AccelStruct,
+        };
+        Ok(out)
+    }
+}
+impl From<DescriptorTypeDef> for DescriptorType {
+    fn from(x: DescriptorTypeDef) -> Self {
+        match x {
+            DescriptorTypeDef::Sampler => DescriptorType::Sampler(),
+            DescriptorTypeDef::CombinedImageSampler => DescriptorType::CombinedImageSampler(),
+            DescriptorTypeDef::SampledImage => DescriptorType::SampledImage(),
+            DescriptorTypeDef::StorageImage(x) => DescriptorType::StorageImage(x.into()),
+            DescriptorTypeDef::UniformTexelBuffer => DescriptorType::UniformTexelBuffer(),
+            DescriptorTypeDef::StorageTexelBuffer(x) => {
+                DescriptorType::StorageTexelBuffer(x.into())
+            }
+            DescriptorTypeDef::UniformBuffer => DescriptorType::UniformBuffer(),
+            DescriptorTypeDef::StorageBuffer(x) => DescriptorType::StorageBuffer(x.into()),
+            DescriptorTypeDef::InputAttachment(x) => DescriptorType::InputAttachment(x),
+            DescriptorTypeDef::AccelStruct => DescriptorType::AccelStruct(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+enum VariableDef {
+    Input {
+        name: Option<String>,
+        location: InterfaceLocationDef,
+        ty: TypeDef,
+    },
+    Output {
+        name: Option<String>,
+        location: InterfaceLocationDef,
+        ty: TypeDef,
+    },
+    Descriptor {
+        name: Option<String>,
+        desc_bind: DescriptorBindingDef,
+        desc_ty: DescriptorTypeDef,
+        ty: TypeDef,
+        nbind: u32,
+    },
+    PushConstant {
+        name: Option<String>,
+        ty: TypeDef,
+    },
+    SpecConstant {
+        name: Option<String>,
+        spec_id: SpecId,
+        ty: TypeDef,
+    },
+}
+impl TryFrom<&Variable> for VariableDef {
+    type Error = String;
+    fn try_from(x: &Variable) -> ConvertResult<Self> {
+        let out = match x {
+            Variable::Input { name, location, ty } => VariableDef::Input {
+                name: name.clone(),
+                location: location.try_into()?,
+                ty: ty.try_into()?,
+            },
+            Variable::Output { name, location, ty } => VariableDef::Output {
+                name: name.clone(),
+                location: location.try_into()?,
+                ty: ty.try_into()?,
+            },
+            Variable::Descriptor {
+                name,
+                desc_bind,
+                desc_ty,
+                ty,
+                nbind,
+            } => VariableDef::Descriptor {
+                name: name.clone(),
+                desc_bind: desc_bind.try_into()?,
+                desc_ty: desc_ty.try_into()?,
+                ty: ty.try_into()?,
+                nbind: *nbind,
+            },
+            Variable::PushConstant { name, ty } => VariableDef::PushConstant {
+                name: name.clone(),
+                ty: ty.try_into()?,
+            },
+            Variable::SpecConstant { name, spec_id, ty } => VariableDef::SpecConstant {
+                name: name.clone(),
+                spec_id: *spec_id,
+                ty: ty.try_into()?,
+            },
+        };
+        Ok(out)
+    }
+}
+impl From<VariableDef> for Variable {
+    fn from(x: VariableDef) -> Self {
+        match x {
+            VariableDef::Input { name, location, ty } => Variable::Input {
+                name,
+                location: location.into(),
+                ty: ty.into(),
+            },
+            VariableDef::Output { name, location, ty } => Variable::Output {
+                name,
+                location: location.into(),
+                ty: ty.into(),
+            },
+            VariableDef::Descriptor {
+                name,
+                desc_bind,
+                desc_ty,
+                ty,
+                nbind,
+            } => Variable::Descriptor {
+                name,
+                desc_bind: desc_bind.into(),
+                desc_ty: desc_ty.into(),
+                ty: ty.into(),
+                nbind,
+            },
+            VariableDef::PushConstant { name, ty } => Variable::PushConstant {
+                name,
+                ty: ty.into(),
+            },
+            VariableDef::SpecConstant { name, spec_id, ty } => Variable::SpecConstant {
+                name,
+                spec_id,
+                ty: ty.into(),
+            },
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+enum ConstantValueDef {
+    Typeless(Vec<u8>),
+    Bool(bool),
+    S8(i8),
+    S16(i16),
+    S32(i32),
+    S64(i64),
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    F16(f32),
+    F32(f32),
+    F64(f64),
+}
+impl TryFrom<&ConstantValue> for ConstantValueDef {
+    type Error = String;
+    fn try_from(x: &ConstantValue) -> ConvertResult<Self> {
+        let out = match x {
+            ConstantValue::Typeless(x) => ConstantValueDef::Typeless(x.to_vec()),
+            ConstantValue::Bool(x) => ConstantValueDef::Bool(*x),
+            ConstantValue::S8(x) => ConstantValueDef::S8(*x),
+            ConstantValue::S16(x) => ConstantValueDef::S16(*x),
+            ConstantValue::S32(x) => ConstantValueDef::S32(*x),
+            ConstantValue::S64(x) => ConstantValueDef::S64(*x),
+            ConstantValue::U8(x) => ConstantValueDef::U8(*x),
+            ConstantValue::U16(x) => ConstantValueDef::U16(*x),
+            ConstantValue::U32(x) => ConstantValueDef::U32(*x),
+            ConstantValue::U64(x) => ConstantValueDef::U64(*x),
+            ConstantValue::F16(x) => ConstantValueDef::F16(x.into_inner().to_f32()),
+            ConstantValue::F32(x) => ConstantValueDef::F32(x.into_inner()),
+            ConstantValue::F64(x) => ConstantValueDef::F64(x.into_inner()),
+            #[allow(unreachable_patterns)]
+            x => return Err(format!("constant value {:?} cannot be serialized", x)),
+        };
+        Ok(out)
+    }
+}
+impl From<ConstantValueDef> for ConstantValue {
+    fn from(x: ConstantValueDef) -> Self {
+        match x {
+            ConstantValueDef::Typeless(x) => ConstantValue::Typeless(x.into_boxed_slice()),
+            ConstantValueDef::Bool(x) => ConstantValue::Bool(x),
+            ConstantValueDef::S8(x) => ConstantValue::S8(x),
+            ConstantValueDef::S16(x) => ConstantValue::S16(x),
+            ConstantValueDef::S32(x) => ConstantValue::S32(x),
+            ConstantValueDef::S64(x) => ConstantValue::S64(x),
+            ConstantValueDef::U8(x) => ConstantValue::U8(x),
+            ConstantValueDef::U16(x) => ConstantValue::U16(x),
+            ConstantValueDef::U32(x) => ConstantValue::U32(x),
+            ConstantValueDef::U64(x) => ConstantValue::U64(x),
+            // Half floats are widened losslessly so they can be narrowed back
+            // exactly.
+            ConstantValueDef::F16(x) => {
+                ConstantValue::F16(OrderedFloat(FromPrimitive::from_f32(x).unwrap()))
+            }
+            ConstantValueDef::F32(x) => ConstantValue::F32(OrderedFloat(x)),
+            ConstantValueDef::F64(x) => ConstantValue::F64(OrderedFloat(x)),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct PrivateVariableDef {
+    name: Option<String>,
+    ty: TypeDef,
+    init: Option<ConstantValueDef>,
+}
+impl TryFrom<&PrivateVariable> for PrivateVariableDef {
+    type Error = String;
+    fn try_from(x: &PrivateVariable) -> ConvertResult<Self> {
+        Ok(PrivateVariableDef {
+            name: x.name.clone(),
+            ty: (&x.ty).try_into()?,
+            init: x.init.as_ref().map(TryInto::try_into).transpose()?,
+        })
+    }
+}
+impl From<PrivateVariableDef> for PrivateVariable {
+    fn from(x: PrivateVariableDef) -> Self {
+        PrivateVariable {
+            name: x.name,
+            ty: x.ty.into(),
+            init: x.init.map(Into::into),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct ConstantDef {
+    name: Option<String>,
+    ty: TypeDef,
+    value: ConstantValueDef,
+    spec_id: Option<SpecId>,
+}
+impl TryFrom<&Constant> for ConstantDef {
+    type Error = String;
+    fn try_from(x: &Constant) -> ConvertResult<Self> {
+        Ok(ConstantDef {
+            name: x.name.clone(),
+            ty: (&x.ty).try_into()?,
+            value: (&x.value).try_into()?,
+            spec_id: x.spec_id,
+        })
+    }
+}
+impl From<ConstantDef> for Constant {
+    fn from(x: ConstantDef) -> Self {
+        Constant {
+            name: x.name,
+            ty: x.ty.into(),
+            value: x.value.into(),
+            spec_id: x.spec_id,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct ExecutionModeDef {
+    #[serde(with = "spirv_enum")]
+    exec_mode: spirv::ExecutionMode,
+    operands: Vec<ConstantDef>,
+}
+impl TryFrom<&ExecutionMode> for ExecutionModeDef {
+    type Error = String;
+    fn try_from(x: &ExecutionMode) -> ConvertResult<Self> {
+        Ok(ExecutionModeDef {
+            exec_mode: x.exec_mode,
+            operands: x
+                .operands
+                .iter()
+                .map(ConstantDef::try_from)
+                .collect::<ConvertResult<_>>()?,
+        })
+    }
+}
+impl From<ExecutionModeDef> for ExecutionMode {
+    fn from(x: ExecutionModeDef) -> Self {
+        ExecutionMode {
+            exec_mode: x.exec_mode,
+            operands: x.operands.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct IoDecorationDef {
+    #[serde(with = "spirv_enum")]
+    deco: spirv::Decoration,
+    operands: Vec<u32>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct BuiltInDef(#[serde(with = "spirv_enum")] spirv::BuiltIn);
+
+/// Entry point with maps keyed by non-string keys flattened into lists, so
+/// it can be serialized in formats like JSON.
+#[derive(Serialize, Deserialize)]
+struct EntryPointDef {
+    #[serde(with = "spirv_enum")]
+    exec_model: spirv::ExecutionModel,
+    name: String,
+    #[serde(default)]
+    demangled_name: Option<String>,
+    vars: Vec<VariableDef>,
+    exec_modes: Vec<ExecutionModeDef>,
+    raw_exec_modes: Vec<RawExecutionMode>,
+    desc_infos: Vec<(DescriptorBindingDef, DescriptorInfo)>,
+    input_comp_masks: BTreeMap<u32, u32>,
+    io_decos: Vec<(InterfaceVariable, Vec<IoDecorationDef>)>,
+    #[serde(default)]
+    io_member_decos: Vec<(InterfaceVariable, u32, Vec<IoDecorationDef>)>,
+    gl_vars: Vec<GlVariable>,
+    #[serde(default)]
+    shared_vars: Vec<SharedVariable>,
+    #[serde(default)]
+    task_payload: Option<SharedVariable>,
+    #[serde(default)]
+    input_builtins: Vec<BuiltInDef>,
+    #[serde(default)]
+    output_builtins: Vec<BuiltInDef>,
+    #[serde(default)]
+    private_vars: Vec<PrivateVariableDef>,
+    #[serde(default)]
+    global_vars: Vec<PrivateVariableDef>,
+    literal_samplers: Vec<LiteralSampler>,
+    #[serde(default)]
+    spec_defaults: Vec<(SpecId, ConstantValueDef)>,
+    #[serde(default)]
+    uses_derivatives: bool,
+    #[serde(default)]
+    implicit_locations: BTreeSet<InterfaceVariable>,
+    #[serde(default)]
+    push_const_bytes: Option<Range<u32>>,
+    #[serde(default)]
+    writes_external_memory: bool,
+    #[serde(default)]
+    writes_point_size: bool,
+    #[serde(default)]
+    array_size_spec_ids: BTreeSet<SpecId>,
+    #[serde(default)]
+    exec_mode_spec_ids: BTreeSet<SpecId>,
+    diags: Vec<Diagnostic>,
+}
+impl TryFrom<&EntryPoint> for EntryPointDef {
+    type Error = String;
+    fn try_from(x: &EntryPoint) -> ConvertResult<Self> {
+        Ok(EntryPointDef {
+            exec_model: x.exec_model,
+            name: x.name.clone(),
+            demangled_name: x.demangled_name.clone(),
+            vars: x
+                .vars
+                .iter()
+                .map(VariableDef::try_from)
+                .collect::<ConvertResult<_>>()?,
+            exec_modes: x
+                .exec_modes
+                .iter()
+                .map(ExecutionModeDef::try_from)
+                .collect::<ConvertResult<_>>()?,
+            raw_exec_modes: x.raw_exec_modes.clone(),
+            desc_infos: x
+                .desc_infos
+                .iter()
+                .map(|(k, v)| Ok((k.try_into()?, v.clone())))
+                .collect::<ConvertResult<_>>()?,
+            input_comp_masks: x.input_comp_masks.clone(),
+            io_decos: x
+                .io_decos
+                .iter()
+                .map(|(k, v)| {
+                    let decos = v
+                        .iter()
+                        .map(|(deco, operands)| IoDecorationDef {
+                            deco: *deco,
+                            operands: operands.clone(),
+                        })
+                        .collect();
+                    (*k, decos)
+                })
+                .collect(),
+            io_member_decos: x
+                .io_member_decos
+                .iter()
+                .flat_map(|(k, v)| {
+                    v.iter().map(move |(member, decos)| {
+                        let decos = decos
+                            .iter()
+                            .map(|(deco, operands)| IoDecorationDef {
+                                deco: *deco,
+                                operands: operands.clone(),
+                            })
+                            .collect();
+                        (*k, *member, decos)
+                    })
+                })
+                .collect(),
+            gl_vars: x.gl_vars.clone(),
+            shared_vars: x.shared_vars.clone(),
+            task_payload: x.task_payload.clone(),
+            input_builtins: x.input_builtins.iter().map(|x| BuiltInDef(*x)).collect(),
+            output_builtins: x.output_builtins.iter().map(|x| BuiltInDef(*x)).collect(),
+            private_vars: x
+                .private_vars
+                .iter()
+                .map(TryFrom::try_from)
+                .collect::<ConvertResult<_>>()?,
+            global_vars: x
+                .global_vars
+                .iter()
+                .map(TryFrom::try_from)
+                .collect::<ConvertResult<_>>()?,
+            literal_samplers: x.literal_samplers.clone(),
+            spec_defaults: x
+                .spec_defaults
+                .iter()
+                .map(|(k, v)| Ok((*k, v.try_into()?)))
+                .collect::<ConvertResult<_>>()?,
+            uses_derivatives: x.uses_derivatives,
+            implicit_locations: x.implicit_locations.clone(),
+            push_const_bytes: x.push_const_bytes.clone(),
+            writes_external_memory: x.writes_external_memory,
+            writes_point_size: x.writes_point_size,
+            array_size_spec_ids: x.array_size_spec_ids.clone(),
+            exec_mode_spec_ids: x.exec_mode_spec_ids.clone(),
+            diags: x.diags.clone(),
+        })
+    }
+}
+impl From<EntryPointDef> for EntryPoint {
+    fn from(x: EntryPointDef) -> Self {
+        let mut io_member_decos = BTreeMap::<_, BTreeMap<_, _>>::new();
+        for (k, member, v) in x.io_member_decos {
+            let decos = v.into_iter().map(|x| (x.deco, x.operands)).collect();
+            io_member_decos.entry(k).or_default().insert(member, decos);
+        }
+        EntryPoint {
+            exec_model: x.exec_model,
+            name: x.name,
+            demangled_name: x.demangled_name,
+            vars: x.vars.into_iter().map(Into::into).collect(),
+            exec_modes: x.exec_modes.into_iter().map(Into::into).collect(),
+            raw_exec_modes: x.raw_exec_modes,
+            desc_infos: x
+                .desc_infos
+                .into_iter()
+                .map(|(k, v)| (k.into(), v))
+                .collect(),
+            input_comp_masks: x.input_comp_masks,
+            io_decos: x
+                .io_decos
+                .into_iter()
+                .map(|(k, v)| {
+                    let decos = v.into_iter().map(|x| (x.deco, x.operands)).collect();
+                    (k, decos)
+                })
+                .collect(),
+            io_member_decos,
+            gl_vars: x.gl_vars,
+            shared_vars: x.shared_vars,
+            task_payload: x.task_payload,
+            input_builtins: x.input_builtins.into_iter().map(|x| x.0).collect(),
+            output_builtins: x.output_builtins.into_iter().map(|x| x.0).collect(),
+            private_vars: x.private_vars.into_iter().map(Into::into).collect(),
+            global_vars: x.global_vars.into_iter().map(Into::into).collect(),
+            literal_samplers: x.literal_samplers,
+            spec_defaults: x
+                .spec_defaults
+                .into_iter()
+                .map(|(k, v)| (k, v.into()))
+                .collect(),
+            uses_derivatives: x.uses_derivatives,
+            implicit_locations: x.implicit_locations,
+            push_const_bytes: x.push_const_bytes,
+            writes_external_memory: x.writes_external_memory,
+            writes_point_size: x.writes_point_size,
+            array_size_spec_ids: x.array_size_spec_ids,
+            exec_mode_spec_ids: x.exec_mode_spec_ids,
+            diags: x.diags,
+        }
+    }
+}
+impl Serialize for EntryPoint {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        EntryPointDef::try_from(self)
+            .map_err(S::Error::custom)?
+            .serialize(s)
+    }
+}
+impl<'de> Deserialize<'de> for EntryPoint {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        Ok(EntryPointDef::deserialize(d)?.into())
+    }
+}
@@ -0,0 +1,777 @@
+//! Declaration-only shader interface stubs in high-level shading languages.
+//!
+//! The stubs are reconstructed from the reflected interface of an entry point,
+//! including descriptor resources, push constants, specialization constants
+//! and stage inputs and outputs. Function bodies are not generated.
+use std::fmt::Write;
+
+use crate::{
+    entry_point::{EntryPoint, ExecutionModel},
+    error::{anyhow, Result},
+    spirv::{Dim, ImageFormat},
+    ty::{AccessType, DescriptorType, MatrixAxisOrder, ScalarType, StructType, Type},
+    var::Variable,
+};
+
+/// Target shading language of interface stubs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StubLanguage {
+    /// Vulkan-flavored GLSL.
+    Glsl,
+    /// HLSL with DXC `[[vk::*]]` attributes.
+    Hlsl,
+}
+
+/// Generate declaration-only stubs of the interface of `entry_point` in
+/// language `lang`. Variables without debug names are given placeholder names.
+pub fn generate_stubs(entry_point: &EntryPoint, lang: StubLanguage) -> Result<String> {
+    let mut writer = StubWriter {
+        lang,
+        exec_model: entry_point.exec_model,
+        structs: Vec::new(),
+        names: Vec::new(),
+        out: String::new(),
+    };
+    let mut body = String::new();
+    let mut inputs = Vec::new();
+    let mut outputs = Vec::new();
+    for var in entry_point.vars.iter() {
+        match var {
+            Variable::Input { .. } => inputs.push(var),
+            Variable::Output { .. } => outputs.push(var),
+            _ => body += &writer.var_decl(var)?,
+        }
+    }
+    body += &writer.stage_io(&inputs, true)?;
+    body += &writer.stage_io(&outputs, false)?;
+
+    let mut out = format!(
+        "// Interface of entry point `{}` ({:?}).\n",
+        entry_point.name, entry_point.exec_model
+    );
+    if lang == StubLanguage::Glsl {
+        out += "#version 460\n";
+    }
+    out += &writer.out;
+    out += &body;
+    Ok(out)
+}
+
+struct StubWriter {
+    lang: StubLanguage,
+    exec_model: ExecutionModel,
+    /// Struct types already declared and their names.
+    structs: Vec<(StructType, String)>,
+    /// Names already taken by structs, blocks and variables, which share a
+    /// scope in GLSL and HLSL.
+    names: Vec<String>,
+    /// Struct declarations, emitted before any variable.
+    out: String,
+}
+impl StubWriter {
+    fn is_glsl(&self) -> bool {
+        self.lang == StubLanguage::Glsl
+    }
+
+    fn scalar_name(&self, scalar_ty: &ScalarType) -> Result<&'static str> {
+        let name = match (self.lang, scalar_ty) {
+            (_, ScalarType::Boolean) => Some("bool"),
+            (lang, ScalarType::Integer { bits, is_signed }) => {
+                let names = match (lang, bits) {
+                    (StubLanguage::Glsl, 8) => Some(["uint8_t", "int8_t"]),
+                    (_, 16) => Some(["uint16_t", "int16_t"]),
+                    (_, 32) => Some(["uint", "int"]),
+                    (_, 64) => Some(["uint64_t", "int64_t"]),
+                    _ => None,
+                };
+                names.map(|x| x[*is_signed as usize])
+            }
+            (StubLanguage::Glsl, ScalarType::Float { bits: 16 }) => Some("float16_t"),
+            (StubLanguage::Hlsl, ScalarType::Float { bits: 16 }) => Some("half"),
+            (_, ScalarType::Float { bits: 32 }) => Some("float"),
+            (_, ScalarType::Float { bits: 64 }) => Some("double"),
+            _ => None,
+        };
+        name.ok_or_else(|| {
+            anyhow!(
+                "scalar type {} cannot be expressed in {:?}",
+                scalar_ty,
+                self.lang
+            )
+        })
+    }
+    /// Type name and array suffix of `ty`. Struct types are declared on the
+    /// first use.
+    fn ty_name(&mut self, ty: &Type) -> Result<(String, String)> {
+        let name = match ty {
+            Type::Scalar(x) => self.scalar_name(x)?.to_owned(),
+            Type::Vector(x) => {
+                if self.is_glsl() {
+                    // Validate the scalar type.
+                    self.scalar_name(&x.scalar_ty)?;
+                    format!("{}vec{}", glsl_prefix(&x.scalar_ty), x.nscalar)
+                } else {
+                    format!("{}{}", self.scalar_name(&x.scalar_ty)?, x.nscalar)
+                }
+            }
+            Type::Matrix(x) => {
+                let scalar_ty = &x.vector_ty.scalar_ty;
+                let nrow = x.vector_ty.nscalar;
+                if self.is_glsl() {
+                    self.scalar_name(scalar_ty)?;
+                    let prefix = glsl_prefix(scalar_ty);
+                    if x.nvector == nrow {
+                        format!("{}mat{}", prefix, nrow)
+                    } else {
+                        format!("{}mat{}x{}", prefix, x.nvector, nrow)
+                    }
+                } else {
+                    // DXC translates an HLSL `floatMxN` to a SPIR-V matrix of
+                    // M N-component vectors.
+                    format!("{}{}x{}", self.scalar_name(scalar_ty)?, x.nvector, nrow)
+                }
+            }
+            Type::Array(x) => {
+                let (name, suffix) = self.ty_name(&x.element_ty)?;
+                let suffix = match x.nelement {
+                    Some(n) => format!("[{}]{}", n, suffix),
+                    None => format!("[]{}", suffix),
+                };
+                return Ok((name, suffix));
+            }
+            Type::Struct(x) => self.declare_struct(x)?,
+            Type::DeviceAddress(_) | Type::DevicePointer(_) => "uint64_t".to_owned(),
+            _ => return Err(anyhow!("type {} cannot be declared in a stub", ty)),
+        };
+        Ok((name, String::new()))
+    }
+
+    fn declare_struct(&mut self, struct_ty: &StructType) -> Result<String> {
+        if let Some((_, name)) = self.structs.iter().find(|(x, _)| x == struct_ty) {
+            return Ok(name.clone());
+        }
+        let name = self.unique_struct_name(struct_ty.name.as_deref());
+        let members = self.members(struct_ty, false)?;
+        writeln!(self.out, "struct {} {{\n{}}};", name, members).unwrap();
+        self.structs.push((struct_ty.clone(), name.clone()));
+        Ok(name)
+    }
+    fn unique_struct_name(&mut self, name: Option<&str>) -> String {
+        let base = match name {
+            Some(name) => sanitize(name),
+            None => format!("Struct{}", self.structs.len()),
+        };
+        self.unique_name(base)
+    }
+    fn unique_name(&mut self, base: String) -> String {
+        let mut name = base.clone();
+        let mut i = 1;
+        while self.names.contains(&name) {
+            name = format!("{}_{}", base, i);
+            i += 1;
+        }
+        self.names.push(name.clone());
+        name
+    }
+
+    /// Member declarations of a struct. Offsets are expressed with layout
+    /// qualifiers in GLSL interface blocks and HLSL `cbuffer`s, and in
+    /// comments otherwise.
+    fn members(&mut self, struct_ty: &StructType, is_block: bool) -> Result<String> {
+        let mut out = String::new();
+        for (i, member) in struct_ty.members.iter().enumerate() {
+            let (ty_name, suffix) = self.ty_name(&member.ty)?;
+            let name = member
+                .name
+                .as_deref()
+                .map(sanitize)
+                .unwrap_or_else(|| format!("m{}", i));
+            let axis_order = match &member.ty {
+                Type::Matrix(x) => x.axis_order,
+                Type::Array(x) => match &*x.element_ty {
+                    Type::Matrix(x) => x.axis_order,
+                    _ => None,
+                },
+                _ => None,
+            };
+            match (self.lang, is_block, member.offset) {
+                (StubLanguage::Glsl, true, Some(offset)) => {
+                    let major = match axis_order {
+                        Some(MatrixAxisOrder::RowMajor) => ", row_major",
+                        _ => "",
+                    };
+                    writeln!(
+                        out,
+                        "    layout(offset = {}{}) {} {}{};",
+                        offset, major, ty_name, name, suffix
+                    )
+                    .unwrap();
+                }
+                (StubLanguage::Hlsl, true, Some(offset)) => {
+                    // Axis orders are swapped by DXC as well.
+                    let major = match axis_order {
+                        Some(MatrixAxisOrder::ColumnMajor) => "row_major ",
+                        _ => "",
+                    };
+                    writeln!(
+                        out,
+                        "    {}{} {}{} : packoffset(c{}.{});",
+                        major,
+                        ty_name,
+                        name,
+                        suffix,
+                        offset / 16,
+                        ["x", "y", "z", "w"][offset % 16 / 4]
+                    )
+                    .unwrap();
+                }
+                (_, _, Some(offset)) => {
+                    writeln!(
+                        out,
+                        "    {} {}{}; // offset {}",
+                        ty_name, name, suffix, offset
+                    )
+                    .unwrap();
+                }
+                (_, _, None) => {
+                    writeln!(out, "    {} {}{};", ty_name, name, suffix).unwrap();
+                }
+            }
+        }
+        Ok(out)
+    }
+
+    fn var_decl(&mut self, var: &Variable) -> Result<String> {
+        let mut out = String::new();
+        match var {
+            Variable::Descriptor {
+                name,
+                desc_bind,
+                desc_ty,
+                ty,
+                nbind,
+            } => {
+                let name = name
+                    .as_deref()
+                    .map(sanitize)
+                    .unwrap_or_else(|| format!("desc_{}_{}", desc_bind.set(), desc_bind.bind()));
+                let name = self.unique_name(name);
+                let suffix = match nbind {
+                    0 => "[]".to_owned(),
+                    1 => String::new(),
+                    n => format!("[{}]", n),
+                };
+                let binding = if self.is_glsl() {
+                    format!("set = {}, binding = {}", desc_bind.set(), desc_bind.bind())
+                } else {
+                    format!("[[vk::binding({}, {})]]", desc_bind.bind(), desc_bind.set())
+                };
+                if self.is_glsl() {
+                    self.glsl_desc(&mut out, &binding, &name, &suffix, desc_ty, ty)?;
+                } else {
+                    self.hlsl_desc(&mut out, &binding, &name, &suffix, desc_ty, ty, *nbind)?;
+                }
+            }
+            Variable::PushConstant { name, ty } => {
+                let name = name
+                    .as_deref()
+                    .map(sanitize)
+                    .unwrap_or_else(|| "push_consts".to_owned());
+                let name = self.unique_name(name);
+                let struct_ty = match ty {
+                    Type::Struct(x) => x,
+                    _ => return Err(anyhow!("push constant `{}` is not a struct", name)),
+                };
+                if self.is_glsl() {
+                    let block_name = self.unique_struct_name(struct_ty.name.as_deref());
+                    let members = self.members(struct_ty, true)?;
+                    writeln!(
+                        out,
+                        "layout(push_constant) uniform {} {{\n{}}} {};",
+                        block_name, members, name
+                    )
+                    .unwrap();
+                } else {
+                    let ty_name = self.declare_struct(struct_ty)?;
+                    writeln!(out, "[[vk::push_constant]] {} {};", ty_name, name).unwrap();
+                }
+            }
+            Variable::SpecConstant { name, spec_id, ty } => {
+                let name = name
+                    .as_deref()
+                    .map(sanitize)
+                    .unwrap_or_else(|| format!("spec_{}", spec_id));
+                let name = self.unique_name(name);
+                let (ty_name, _) = self.ty_name(ty)?;
+                // Default values are not reflected.
+                let value = match ty {
+                    Type::Scalar(ScalarType::Boolean) => "false",
+                    Type::Scalar(ScalarType::Float { .. }) => "0.0",
+                    _ => "0",
+                };
+                if self.is_glsl() {
+                    writeln!(
+                        out,
+                        "layout(constant_id = {}) const {} {} = {};",
+                        spec_id, ty_name, name, value
+                    )
+                    .unwrap();
+                } else {
+                    writeln!(
+                        out,
+                        "[[vk::constant_id({})]] const {} {} = {};",
+                        spec_id, ty_name, name, value
+                    )
+                    .unwrap();
+                }
+            }
+            Variable::Input { .. } | Variable::Output { .. } => unreachable!(),
+        }
+        Ok(out)
+    }
+
+    fn glsl_desc(
+        &mut self,
+        out: &mut String,
+        binding: &str,
+        name: &str,
+        suffix: &str,
+        desc_ty: &DescriptorType,
+        ty: &Type,
+    ) -> Result<()> {
+        let access = |access: &AccessType| match access {
+            AccessType::ReadOnly => "readonly ",
+            AccessType::WriteOnly => "writeonly ",
+            AccessType::ReadWrite => "",
+        };
+        match (desc_ty, ty) {
+            (DescriptorType::UniformBuffer(), Type::Struct(struct_ty))
+            | (DescriptorType::StorageBuffer(_), Type::Struct(struct_ty)) => {
+                let block_name = self.unique_struct_name(struct_ty.name.as_deref());
+                let members = self.members(struct_ty, true)?;
+                let storage = match desc_ty {
+                    DescriptorType::StorageBuffer(x) => format!("{}buffer", access(x)),
+                    _ => "uniform".to_owned(),
+                };
+                writeln!(
+                    out,
+                    "layout({}) {} {} {{\n{}}} {}{};",
+                    binding, storage, block_name, members, name, suffix
+                )
+                .unwrap();
+            }
+            (DescriptorType::Sampler(), _) => {
+                writeln!(
+                    out,
+                    "layout({}) uniform sampler {}{};",
+                    binding, name, suffix
+                )
+                .unwrap();
+            }
+            (_, Type::SampledImage(x)) => {
+                let prefix = glsl_prefix(&x.scalar_ty);
+                let dim = glsl_dim(x.dim, x.is_multisampled, x.is_array)?;
+                writeln!(
+                    out,
+                    "layout({}) uniform {}texture{} {}{};",
+                    binding, prefix, dim, name, suffix
+                )
+                .unwrap();
+            }
+            (_, Type::CombinedImageSampler(x)) => {
+                let x = &x.sampled_image_ty;
+                let prefix = glsl_prefix(&x.scalar_ty);
+                let dim = glsl_dim(x.dim, x.is_multisampled, x.is_array)?;
+                let shadow = if x.is_depth == Some(true) {
+                    "Shadow"
+                } else {
+                    ""
+                };
+                writeln!(
+                    out,
+                    "layout({}) uniform {}sampler{}{} {}{};",
+                    binding, prefix, dim, shadow, name, suffix
+                )
+                .unwrap();
+            }
+            (DescriptorType::StorageImage(a), Type::StorageImage(x))
+            | (DescriptorType::StorageTexelBuffer(a), Type::StorageImage(x)) => {
+                let dim = glsl_dim(x.dim, x.is_multisampled, x.is_array)?;
+                let (fmt, prefix) = match x.fmt {
+                    ImageFormat::Unknown => (String::new(), ""),
+                    fmt => (format!(", {}", glsl_fmt(fmt)), fmt_prefix(fmt)),
+                };
+                writeln!(
+                    out,
+                    "layout({}{}) uniform {}{}image{} {}{};",
+                    binding,
+                    fmt,
+                    access(a),
+                    prefix,
+                    dim,
+                    name,
+                    suffix
+                )
+                .unwrap();
+            }
+            (DescriptorType::InputAttachment(idx), Type::SubpassData(x)) => {
+                let prefix = glsl_prefix(&x.scalar_ty);
+                let ms = if x.is_multisampled { "MS" } else { "" };
+                writeln!(
+                    out,
+                    "layout(input_attachment_index = {}, {}) uniform {}subpassInput{} {}{};",
+                    idx, binding, prefix, ms, name, suffix
+                )
+                .unwrap();
+            }
+            (DescriptorType::AccelStruct(), _) => {
+                writeln!(
+                    out,
+                    "layout({}) uniform accelerationStructureEXT {}{};",
+                    binding, name, suffix
+                )
+                .unwrap();
+            }
+            _ => {
+                return Err(anyhow!(
+                    "descriptor `{}` of {:?} cannot be declared in a stub",
+                    name,
+                    desc_ty
+                ))
+            }
+        }
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn hlsl_desc(
+        &mut self,
+        out: &mut String,
+        binding: &str,
+        name: &str,
+        suffix: &str,
+        desc_ty: &DescriptorType,
+        ty: &Type,
+        nbind: u32,
+    ) -> Result<()> {
+        // Read-only storage images are still `RW*` textures in HLSL, only
+        // buffers have read-only variants.
+        let rw = |access: &AccessType| match access {
+            AccessType::ReadOnly => "",
+            _ => "RW",
+        };
+        match (desc_ty, ty) {
+            (DescriptorType::UniformBuffer(), Type::Struct(struct_ty)) => {
+                if nbind == 1 {
+                    let block_name = self.unique_struct_name(struct_ty.name.as_deref());
+                    let members = self.members(struct_ty, true)?;
+                    writeln!(
+                        out,
+                        "{}\ncbuffer {} {{\n{}}};",
+                        binding, block_name, members
+                    )
+                    .unwrap();
+                } else {
+                    let ty_name = self.declare_struct(struct_ty)?;
+                    writeln!(
+                        out,
+                        "{} ConstantBuffer<{}> {}{};",
+                        binding, ty_name, name, suffix
+                    )
+                    .unwrap();
+                }
+            }
+            (DescriptorType::StorageBuffer(access), Type::Struct(struct_ty)) => {
+                // A buffer of a single runtime array is a structured buffer;
+                // anything else can only be accessed by byte addresses.
+                match struct_ty.members.as_slice() {
+                    [member] if matches!(&member.ty, Type::Array(x) if x.nelement.is_none()) => {
+                        let (ty_name, _) = match &member.ty {
+                            Type::Array(x) => self.ty_name(&x.element_ty)?,
+                            _ => unreachable!(),
+                        };
+                        writeln!(
+                            out,
+                            "{} {}StructuredBuffer<{}> {}{};",
+                            binding,
+                            rw(access),
+                            ty_name,
+                            name,
+                            suffix
+                        )
+                        .unwrap();
+                    }
+                    _ => {
+                        let ty_name = self.declare_struct(struct_ty)?;
+                        writeln!(
+                            out,
+                            "{} {}ByteAddressBuffer {}{}; // layout: {}",
+                            binding,
+                            rw(access),
+                            name,
+                            suffix,
+                            ty_name
+                        )
+                        .unwrap();
+                    }
+                }
+            }
+            (DescriptorType::Sampler(), _) => {
+                writeln!(out, "{} SamplerState {}{};", binding, name, suffix).unwrap();
+            }
+            (_, Type::SampledImage(x)) => {
+                let texel_ty = format!("{}4", self.scalar_name(&x.scalar_ty)?);
+                let texture = hlsl_texture(x.dim, x.is_multisampled, x.is_array)?;
+                writeln!(
+                    out,
+                    "{} {}<{}> {}{};",
+                    binding, texture, texel_ty, name, suffix
+                )
+                .unwrap();
+            }
+            (_, Type::CombinedImageSampler(x)) => {
+                let x = &x.sampled_image_ty;
+                let texel_ty = format!("{}4", self.scalar_name(&x.scalar_ty)?);
+                let texture = hlsl_texture(x.dim, x.is_multisampled, x.is_array)?;
+                writeln!(
+                    out,
+                    "{} [[vk::combinedImageSampler]] {}<{}> {}{};",
+                    binding, texture, texel_ty, name, suffix
+                )
+                .unwrap();
+                writeln!(
+                    out,
+                    "{} [[vk::combinedImageSampler]] SamplerState {}_sampler{};",
+                    binding, name, suffix
+                )
+                .unwrap();
+            }
+            (DescriptorType::StorageImage(_), Type::StorageImage(x))
+            | (DescriptorType::StorageTexelBuffer(_), Type::StorageImage(x)) => {
+                let texel_ty = match fmt_prefix(x.fmt) {
+                    "i" => "int4",
+                    "u" => "uint4",
+                    _ => "float4",
+                };
+                let texture = hlsl_texture(x.dim, x.is_multisampled, x.is_array)?;
+                let fmt = match x.fmt {
+                    ImageFormat::Unknown => String::new(),
+                    ImageFormat::R11fG11fB10f => " [[vk::image_format(\"r11g11b10f\")]]".to_owned(),
+                    fmt => format!(
+                        " [[vk::image_format(\"{}\")]]",
+                        format!("{:?}", fmt).to_lowercase()
+                    ),
+                };
+                writeln!(
+                    out,
+                    "{}{} RW{}<{}> {}{};",
+                    binding, fmt, texture, texel_ty, name, suffix
+                )
+                .unwrap();
+            }
+            (DescriptorType::InputAttachment(idx), Type::SubpassData(x)) => {
+                let texel_ty = format!("{}4", self.scalar_name(&x.scalar_ty)?);
+                let ms = if x.is_multisampled { "MS" } else { "" };
+                writeln!(
+                    out,
+                    "{} [[vk::input_attachment_index({})]] SubpassInput{}<{}> {}{};",
+                    binding, idx, ms, texel_ty, name, suffix
+                )
+                .unwrap();
+            }
+            (DescriptorType::AccelStruct(), _) => {
+                writeln!(
+                    out,
+                    "{} RaytracingAccelerationStructure {}{};",
+                    binding, name, suffix
+                )
+                .unwrap();
+            }
+            _ => {
+                return Err(anyhow!(
+                    "descriptor `{}` of {:?} cannot be declared in a stub",
+                    name,
+                    desc_ty
+                ))
+            }
+        }
+        Ok(())
+    }
+
+    /// Declare stage inputs or outputs. HLSL stage IO is gathered in a struct.
+    fn stage_io(&mut self, vars: &[&Variable], is_input: bool) -> Result<String> {
+        let mut out = String::new();
+        if vars.is_empty() {
+            return Ok(out);
+        }
+        let dir = if is_input { "in" } else { "out" };
+        if !self.is_glsl() {
+            let struct_name = if is_input {
+                "StageInput"
+            } else {
+                "StageOutput"
+            };
+            let struct_name = self.unique_name(struct_name.to_owned());
+            writeln!(out, "struct {} {{", struct_name).unwrap();
+        }
+        for var in vars {
+            let (name, location, ty) = match var {
+                Variable::Input { name, location, ty }
+                | Variable::Output { name, location, ty } => (name, location, ty),
+                _ => unreachable!(),
+            };
+            let name = name
+                .as_deref()
+                .map(sanitize)
+                .unwrap_or_else(|| format!("{}_{}_{}", dir, location.loc(), location.comp()));
+            // Members of the HLSL stage IO structs have their own scope.
+            let name = if self.is_glsl() {
+                self.unique_name(name)
+            } else {
+                name
+            };
+            let (ty_name, suffix) = self.ty_name(ty)?;
+            if self.is_glsl() {
+                let comp = match location.comp() {
+                    0 => String::new(),
+                    x => format!(", component = {}", x),
+                };
+                // Integer and double inputs of fragment shaders can't be
+                // interpolated.
+                let scalar_ty = match ty {
+                    Type::Scalar(x) => Some(x),
+                    Type::Vector(x) => Some(&x.scalar_ty),
+                    _ => None,
+                };
+                let flat = match scalar_ty {
+                    Some(ScalarType::Float { bits: 32 }) => "",
+                    Some(_) if is_input && self.exec_model == ExecutionModel::Fragment => "flat ",
+                    _ => "",
+                };
+                writeln!(
+                    out,
+                    "layout(location = {}{}) {}{} {} {}{};",
+                    location.loc(),
+                    comp,
+                    flat,
+                    dir,
+                    ty_name,
+                    name,
+                    suffix
+                )
+                .unwrap();
+            } else {
+                let semantic = if !is_input && self.exec_model == ExecutionModel::Fragment {
+                    format!("SV_Target{}", location.loc())
+                } else {
+                    format!("TEXCOORD{}", location.loc())
+                };
+                writeln!(
+                    out,
+                    "    [[vk::location({})]] {} {}{} : {};",
+                    location.loc(),
+                    ty_name,
+                    name,
+                    suffix,
+                    semantic
+                )
+                .unwrap();
+            }
+        }
+        if !self.is_glsl() {
+            writeln!(out, "}};").unwrap();
+        }
+        Ok(out)
+    }
+}
+
+/// Replace characters not allowed in identifiers, like the dots in names
+/// generated by DXC.
+pub(crate) fn sanitize(name: &str) -> String {
+    let mut out = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect::<String>();
+    if out.is_empty() || out.starts_with(|c: char| c.is_ascii_digit()) {
+        out.insert(0, '_');
+    }
+    out
+}
+
+/// GLSL vector and matrix type name prefix like `i` in `ivec4`.
+pub(crate) fn glsl_prefix(scalar_ty: &ScalarType) -> &'static str {
+    match scalar_ty {
+        ScalarType::Boolean => "b",
+        ScalarType::Integer { bits, is_signed } => {
+            let prefixes = match bits {
+                8 => ["u8", "i8"],
+                16 => ["u16", "i16"],
+                64 => ["u64", "i64"],
+                _ => ["u", "i"],
+            };
+            prefixes[*is_signed as usize]
+        }
+        ScalarType::Float { bits: 16 } => "f16",
+        ScalarType::Float { bits: 64 } => "d",
+        _ => "",
+    }
+}
+pub(crate) fn glsl_dim(dim: Dim, is_multisampled: bool, is_array: bool) -> Result<String> {
+    let dim = match dim {
+        Dim::Dim1D => "1D",
+        Dim::Dim2D => "2D",
+        Dim::Dim3D => "3D",
+        Dim::DimCube => "Cube",
+        Dim::DimRect => "2DRect",
+        Dim::DimBuffer => "Buffer",
+        _ => {
+            return Err(anyhow!(
+                "image dimension {:?} cannot be declared in a stub",
+                dim
+            ))
+        }
+    };
+    let ms = if is_multisampled { "MS" } else { "" };
+    let array = if is_array { "Array" } else { "" };
+    Ok(format!("{}{}{}", dim, ms, array))
+}
+fn hlsl_texture(dim: Dim, is_multisampled: bool, is_array: bool) -> Result<String> {
+    let dim = match dim {
+        Dim::Dim1D => "Texture1D",
+        Dim::Dim2D | Dim::DimRect => "Texture2D",
+        Dim::Dim3D => "Texture3D",
+        Dim::DimCube => "TextureCube",
+        Dim::DimBuffer => return Ok("Buffer".to_owned()),
+        _ => {
+            return Err(anyhow!(
+                "image dimension {:?} cannot be declared in a stub",
+                dim
+            ))
+        }
+    };
+    let ms = if is_multisampled { "MS" } else { "" };
+    let array = if is_array { "Array" } else { "" };
+    Ok(format!("{}{}{}", dim, ms, array))
+}
+/// GLSL image format layout qualifier.
+fn glsl_fmt(fmt: ImageFormat) -> String {
+    match fmt {
+        ImageFormat::R11fG11fB10f => "r11f_g11f_b10f".to_owned(),
+        ImageFormat::Rgb10A2 => "rgb10_a2".to_owned(),
+        ImageFormat::Rgb10a2ui => "rgb10_a2ui".to_owned(),
+        _ => format!("{:?}", fmt)
+            .to_lowercase()
+            .replace("snorm", "_snorm"),
+    }
+}
+/// Image type name prefix of integer image formats.
+pub(crate) fn fmt_prefix(fmt: ImageFormat) -> &'static str {
+    let fmt = format!("{:?}", fmt);
+    if fmt.ends_with("ui") {
+        "u"
+    } else if fmt.ends_with('i') {
+        "i"
+    } else {
+        ""
+    }
+}
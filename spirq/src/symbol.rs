@@ -0,0 +1,121 @@
+//! Direct lookup of members nested in types by symbol paths.
+//!
+//! A symbol path is a sequence of member names separated by dots and array
+//! indices in brackets, like `lights[3].position`. Unnamed members can be
+//! selected by their indices, like `lights[3].0`. Paths end at vectors and
+//! matrices.
+use crate::{
+    error::{anyhow, Result},
+    ty::Type,
+};
+
+/// A member nested in a type.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ResolvedSymbol<'a> {
+    /// Type of the member.
+    pub ty: &'a Type,
+    /// Offset of the member in bytes from the beginning of the root type.
+    pub offset: usize,
+    /// Distance in bytes between consecutive elements of the member if it's
+    /// an array, or between the columns (or rows, if it's row-major) of the
+    /// member if it's a matrix.
+    pub stride: Option<usize>,
+}
+
+/// Symbol path lookup on [`Type`].
+pub trait TypeResolveExt {
+    /// Find the member at symbol path `sym` in the type, with its absolute
+    /// offset and stride. An empty path refers to the type itself.
+    fn resolve(&self, sym: &str) -> Result<ResolvedSymbol<'_>>;
+}
+impl TypeResolveExt for Type {
+    fn resolve(&self, sym: &str) -> Result<ResolvedSymbol<'_>> {
+        let mut ty = self;
+        let mut offset = 0;
+        for seg in parse_sym(sym)? {
+            let (child_ty, child_offset) = match (seg, ty) {
+                (Seg::Member(name), Type::Struct(x)) => {
+                    let member = match name.parse::<usize>() {
+                        Ok(i) => x.members.get(i),
+                        Err(_) => x.members.iter().find(|x| x.name.as_deref() == Some(name)),
+                    };
+                    let member =
+                        member.ok_or_else(|| anyhow!("`{}` has no member `{}`", sym, name))?;
+                    let member_offset = member
+                        .offset
+                        .ok_or_else(|| anyhow!("member `{}` in `{}` has no offset", name, sym))?;
+                    (&member.ty, member_offset)
+                }
+                (Seg::Index(i), Type::Array(x)) => {
+                    if let Some(nelement) = x.nelement {
+                        if i >= nelement as usize {
+                            return Err(anyhow!(
+                                "index {} in `{}` is out of bound {}",
+                                i,
+                                sym,
+                                nelement
+                            ));
+                        }
+                    }
+                    let stride = x
+                        .stride
+                        .ok_or_else(|| anyhow!("array in `{}` has no stride", sym))?;
+                    (&*x.element_ty, stride * i)
+                }
+                (Seg::Member(name), _) => {
+                    return Err(anyhow!("`{}` has no member `{}`", sym, name));
+                }
+                // Vector components and matrix columns are not `Type`s. Their
+                // offsets can be derived from the stride of the matrix.
+                (Seg::Index(i), _) => {
+                    return Err(anyhow!("`{}` cannot be indexed by {}", sym, i));
+                }
+            };
+            ty = child_ty;
+            offset += child_offset;
+        }
+        let stride = match ty {
+            Type::Array(x) => x.stride,
+            Type::Matrix(x) => x.stride,
+            _ => None,
+        };
+        Ok(ResolvedSymbol { ty, offset, stride })
+    }
+}
+
+enum Seg<'a> {
+    Member(&'a str),
+    Index(usize),
+}
+fn parse_sym(sym: &str) -> Result<Vec<Seg<'_>>> {
+    let mut out = Vec::new();
+    let mut rest = sym.trim();
+    while !rest.is_empty() {
+        if let Some(x) = rest.strip_prefix('[') {
+            let (idx, x) = x
+                .split_once(']')
+                .ok_or_else(|| anyhow!("unclosed bracket in `{}`", sym))?;
+            let idx = idx
+                .trim()
+                .parse()
+                .map_err(|_| anyhow!("invalid index `{}` in `{}`", idx, sym))?;
+            out.push(Seg::Index(idx));
+            rest = x;
+        } else {
+            let x = if out.is_empty() {
+                rest
+            } else {
+                rest.strip_prefix('.')
+                    .ok_or_else(|| anyhow!("expected `.` or `[` in `{}`", sym))?
+            };
+            let end = x.find(['.', '['].as_ref()).unwrap_or(x.len());
+            let name = &x[..end];
+            if name.is_empty() {
+                return Err(anyhow!("empty member name in `{}`", sym));
+            }
+            out.push(Seg::Member(name));
+            rest = &x[end..];
+        }
+    }
+    Ok(out)
+}
@@ -1,7 +1,27 @@
+use crate::binary::{GeneratorInfo, SpirvVersion};
+use crate::clspv::{clspv_kernels, ClspvAddressSpace, ClspvArgumentKind};
+use crate::demangle::demangle;
+use crate::diagnostic::{DegenerateSampledImage, ImageArrangement, VersionConvention};
+use crate::format::{
+    check_storage_image_fmts, declared_image_fmt, vk_format, vk_format_name, StorageImageFeatures,
+    StorageImageFmtError,
+};
+use crate::key::{parse_desc_bind, parse_location};
+use crate::label::LabelConfig;
+use crate::layout::{padding_holes, PaddingHole};
+use crate::layout_desc::LayoutDescription;
+use crate::patch::Patcher;
+use crate::pipeline::PushConstantRange;
+use crate::pool::{PoolOverflow, PoolSimulator, PoolSizes};
 use crate::prelude::*;
+use crate::reflection::CooperativeMatrixType;
+use crate::spirv;
 use crate::ty;
+use crate::ty_arena::{same_ty, ty_hash, TypeArena};
 use fnv::{FnvHashMap as HashMap, FnvHashSet as HashSet};
 use inline_spirv::*;
+use num_traits::FromPrimitive;
+use ordered_float::OrderedFloat;
 
 macro_rules! gen_entries(
     ($stage:ident, $src:expr, $lang:ident) => {{
@@ -581,6 +601,1016 @@ fn test_linked_list() {
     "#
     );
 }
+#[test]
+fn test_pointer_store_cls() {
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpCapability PhysicalStorageBufferAddresses
+        OpMemoryModel PhysicalStorageBuffer64 GLSL450
+        OpEntryPoint GLCompute %main "main"
+        OpExecutionMode %main LocalSize 1 1 1
+        OpDecorate %Node Block
+        OpMemberDecorate %Node 0 Offset 0
+        OpMemberDecorate %Node 1 Offset 8
+        OpMemberDecorate %Node 2 Offset 16
+        OpDecorate %Root Block
+        OpMemberDecorate %Root 0 Offset 0
+        OpDecorate %root DescriptorSet 0
+        OpDecorate %root Binding 0
+        OpTypeForwardPointer %node_ptr PhysicalStorageBuffer
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %int = OpTypeInt 32 1
+        %Node = OpTypeStruct %int %node_ptr %int
+        %node_ptr = OpTypePointer PhysicalStorageBuffer %Node
+        %Root = OpTypeStruct %node_ptr
+        %root_ptr = OpTypePointer StorageBuffer %Root
+        %root = OpVariable %root_ptr StorageBuffer
+        %main = OpFunction %void None %fn
+        %entry = OpLabel
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let entry_points = ReflectConfig::new()
+        .spv(spv)
+        .ref_all_rscs(true)
+        .reflect()
+        .unwrap();
+    let root_ty = match &entry_points[0].vars[0] {
+        Variable::Descriptor {
+            ty: Type::Struct(x),
+            ..
+        } => x,
+        x => panic!("unexpected {:?}", x),
+    };
+    let node_ptr_ty = &root_ty.members[0].ty;
+    assert_eq!(
+        node_ptr_ty.pointer_store_cls(),
+        Some(spirv::StorageClass::PhysicalStorageBuffer)
+    );
+    assert!(node_ptr_ty.is_physical_pointer());
+    let node_ty = match node_ptr_ty {
+        Type::DevicePointer(x) => &*x.pointee_ty,
+        x => panic!("unexpected {:?}", x),
+    };
+    let node_ty = match node_ty {
+        Type::Struct(x) => x,
+        x => panic!("unexpected {:?}", x),
+    };
+    // Self references are forward-declared.
+    assert!(node_ty.members[1].ty.is_physical_pointer());
+    assert_eq!(
+        node_ty.members[2].offset,
+        Some(node_ty.members[1].offset.unwrap() + crate::layout::POINTER_NBYTE)
+    );
+    assert_eq!(node_ty.members[0].ty.pointer_store_cls(), None);
+}
+#[test]
+fn test_forward_pointer_resolution() {
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpCapability PhysicalStorageBufferAddresses
+        OpMemoryModel PhysicalStorageBuffer64 GLSL450
+        OpEntryPoint GLCompute %main "main"
+        OpExecutionMode %main LocalSize 1 1 1
+        OpMemberDecorate %A 0 Offset 0
+        OpDecorate %B Block
+        OpMemberDecorate %B 0 Offset 0
+        OpMemberDecorate %B 1 Offset 8
+        OpDecorate %Root Block
+        OpMemberDecorate %Root 0 Offset 0
+        OpDecorate %root DescriptorSet 0
+        OpDecorate %root Binding 0
+        OpTypeForwardPointer %b_ptr PhysicalStorageBuffer
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %int = OpTypeInt 32 1
+        %A = OpTypeStruct %b_ptr
+        %Root = OpTypeStruct %A
+        %root_ptr = OpTypePointer StorageBuffer %Root
+        %root = OpVariable %root_ptr StorageBuffer
+        %B = OpTypeStruct %int %b_ptr
+        %b_ptr = OpTypePointer PhysicalStorageBuffer %B
+        %main = OpFunction %void None %fn
+        %entry = OpLabel
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let entry_points = ReflectConfig::new()
+        .spv(spv)
+        .ref_all_rscs(true)
+        .reflect()
+        .unwrap();
+    let root_ty = match &entry_points[0].vars[0] {
+        Variable::Descriptor {
+            ty: Type::Struct(x),
+            ..
+        } => x,
+        x => panic!("unexpected {:?}", x),
+    };
+    let a_ty = root_ty.members[0].ty.as_struct().unwrap();
+    // The pointer declared after the variable is resolved.
+    let b_ty = match &a_ty.members[0].ty {
+        Type::DevicePointer(x) => x.pointee_ty.as_struct().unwrap(),
+        x => panic!("unexpected {:?}", x),
+    };
+    assert_eq!(b_ty.members[0].ty, Type::Scalar(ty::ScalarType::i32()));
+    // The self reference is cut.
+    assert!(matches!(b_ty.members[1].ty, Type::DeviceAddress(_)));
+}
+#[test]
+fn test_forward_pointer_cycles() {
+    // Every struct points to every struct, including itself.
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpCapability PhysicalStorageBufferAddresses
+        OpMemoryModel PhysicalStorageBuffer64 GLSL450
+        OpEntryPoint GLCompute %main "main"
+        OpExecutionMode %main LocalSize 1 1 1
+        OpDecorate %Root Block
+        OpMemberDecorate %Root 0 Offset 0
+        OpDecorate %root DescriptorSet 0
+        OpDecorate %root Binding 0
+        OpMemberDecorate %S0 0 Offset 0
+        OpMemberDecorate %S0 1 Offset 8
+        OpMemberDecorate %S0 2 Offset 16
+        OpMemberDecorate %S0 3 Offset 24
+        OpMemberDecorate %S0 4 Offset 32
+        OpMemberDecorate %S0 5 Offset 40
+        OpMemberDecorate %S0 6 Offset 48
+        OpMemberDecorate %S0 7 Offset 56
+        OpMemberDecorate %S1 0 Offset 0
+        OpMemberDecorate %S1 1 Offset 8
+        OpMemberDecorate %S1 2 Offset 16
+        OpMemberDecorate %S1 3 Offset 24
+        OpMemberDecorate %S1 4 Offset 32
+        OpMemberDecorate %S1 5 Offset 40
+        OpMemberDecorate %S1 6 Offset 48
+        OpMemberDecorate %S1 7 Offset 56
+        OpMemberDecorate %S2 0 Offset 0
+        OpMemberDecorate %S2 1 Offset 8
+        OpMemberDecorate %S2 2 Offset 16
+        OpMemberDecorate %S2 3 Offset 24
+        OpMemberDecorate %S2 4 Offset 32
+        OpMemberDecorate %S2 5 Offset 40
+        OpMemberDecorate %S2 6 Offset 48
+        OpMemberDecorate %S2 7 Offset 56
+        OpMemberDecorate %S3 0 Offset 0
+        OpMemberDecorate %S3 1 Offset 8
+        OpMemberDecorate %S3 2 Offset 16
+        OpMemberDecorate %S3 3 Offset 24
+        OpMemberDecorate %S3 4 Offset 32
+        OpMemberDecorate %S3 5 Offset 40
+        OpMemberDecorate %S3 6 Offset 48
+        OpMemberDecorate %S3 7 Offset 56
+        OpMemberDecorate %S4 0 Offset 0
+        OpMemberDecorate %S4 1 Offset 8
+        OpMemberDecorate %S4 2 Offset 16
+        OpMemberDecorate %S4 3 Offset 24
+        OpMemberDecorate %S4 4 Offset 32
+        OpMemberDecorate %S4 5 Offset 40
+        OpMemberDecorate %S4 6 Offset 48
+        OpMemberDecorate %S4 7 Offset 56
+        OpMemberDecorate %S5 0 Offset 0
+        OpMemberDecorate %S5 1 Offset 8
+        OpMemberDecorate %S5 2 Offset 16
+        OpMemberDecorate %S5 3 Offset 24
+        OpMemberDecorate %S5 4 Offset 32
+        OpMemberDecorate %S5 5 Offset 40
+        OpMemberDecorate %S5 6 Offset 48
+        OpMemberDecorate %S5 7 Offset 56
+        OpMemberDecorate %S6 0 Offset 0
+        OpMemberDecorate %S6 1 Offset 8
+        OpMemberDecorate %S6 2 Offset 16
+        OpMemberDecorate %S6 3 Offset 24
+        OpMemberDecorate %S6 4 Offset 32
+        OpMemberDecorate %S6 5 Offset 40
+        OpMemberDecorate %S6 6 Offset 48
+        OpMemberDecorate %S6 7 Offset 56
+        OpMemberDecorate %S7 0 Offset 0
+        OpMemberDecorate %S7 1 Offset 8
+        OpMemberDecorate %S7 2 Offset 16
+        OpMemberDecorate %S7 3 Offset 24
+        OpMemberDecorate %S7 4 Offset 32
+        OpMemberDecorate %S7 5 Offset 40
+        OpMemberDecorate %S7 6 Offset 48
+        OpMemberDecorate %S7 7 Offset 56
+        OpTypeForwardPointer %p0 PhysicalStorageBuffer
+        OpTypeForwardPointer %p1 PhysicalStorageBuffer
+        OpTypeForwardPointer %p2 PhysicalStorageBuffer
+        OpTypeForwardPointer %p3 PhysicalStorageBuffer
+        OpTypeForwardPointer %p4 PhysicalStorageBuffer
+        OpTypeForwardPointer %p5 PhysicalStorageBuffer
+        OpTypeForwardPointer %p6 PhysicalStorageBuffer
+        OpTypeForwardPointer %p7 PhysicalStorageBuffer
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %S0 = OpTypeStruct %p0 %p1 %p2 %p3 %p4 %p5 %p6 %p7
+        %S1 = OpTypeStruct %p0 %p1 %p2 %p3 %p4 %p5 %p6 %p7
+        %S2 = OpTypeStruct %p0 %p1 %p2 %p3 %p4 %p5 %p6 %p7
+        %S3 = OpTypeStruct %p0 %p1 %p2 %p3 %p4 %p5 %p6 %p7
+        %S4 = OpTypeStruct %p0 %p1 %p2 %p3 %p4 %p5 %p6 %p7
+        %S5 = OpTypeStruct %p0 %p1 %p2 %p3 %p4 %p5 %p6 %p7
+        %S6 = OpTypeStruct %p0 %p1 %p2 %p3 %p4 %p5 %p6 %p7
+        %S7 = OpTypeStruct %p0 %p1 %p2 %p3 %p4 %p5 %p6 %p7
+        %p0 = OpTypePointer PhysicalStorageBuffer %S0
+        %p1 = OpTypePointer PhysicalStorageBuffer %S1
+        %p2 = OpTypePointer PhysicalStorageBuffer %S2
+        %p3 = OpTypePointer PhysicalStorageBuffer %S3
+        %p4 = OpTypePointer PhysicalStorageBuffer %S4
+        %p5 = OpTypePointer PhysicalStorageBuffer %S5
+        %p6 = OpTypePointer PhysicalStorageBuffer %S6
+        %p7 = OpTypePointer PhysicalStorageBuffer %S7
+        %Root = OpTypeStruct %p0
+        %root_ptr = OpTypePointer StorageBuffer %Root
+        %root = OpVariable %root_ptr StorageBuffer
+        %main = OpFunction %void None %fn
+        %entry = OpLabel
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let entry_points = ReflectConfig::new()
+        .spv(spv)
+        .ref_all_rscs(true)
+        .reflect()
+        .unwrap();
+    let root_ty = match &entry_points[0].vars[0] {
+        Variable::Descriptor {
+            ty: Type::Struct(x),
+            ..
+        } => x,
+        x => panic!("unexpected {:?}", x),
+    };
+    // Each struct is expanded at most once on a path before the cycle is
+    // cut.
+    fn depth(ty: &Type) -> usize {
+        match ty {
+            Type::DevicePointer(x) => 1 + depth(&x.pointee_ty),
+            Type::Struct(x) => x.members.iter().map(|x| depth(&x.ty)).max().unwrap_or(0),
+            _ => 0,
+        }
+    }
+    let root_depth = depth(&root_ty.members[0].ty);
+    assert!((2..=9).contains(&root_depth), "depth {}", root_depth);
+}
+#[test]
+fn test_bool_layout() {
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint GLCompute %main "main"
+        OpExecutionMode %main LocalSize 1 1 1
+        OpDecorate %Params Block
+        OpMemberDecorate %Params 0 Offset 0
+        OpMemberDecorate %Params 1 Offset 12
+        OpMemberDecorate %Params 2 Offset 16
+        OpDecorate %_arr_bool ArrayStride 4
+        OpDecorate %params DescriptorSet 0
+        OpDecorate %params Binding 0
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %bool = OpTypeBool
+        %uint = OpTypeInt 32 0
+        %uint_3 = OpConstant %uint 3
+        %v3bool = OpTypeVector %bool 3
+        %_arr_bool = OpTypeArray %bool %uint_3
+        %Params = OpTypeStruct %_arr_bool %bool %v3bool
+        %params_ptr = OpTypePointer StorageBuffer %Params
+        %params = OpVariable %params_ptr StorageBuffer
+        %main = OpFunction %void None %fn
+        %entry = OpLabel
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let entry_points = ReflectConfig::new()
+        .spv(spv)
+        .ref_all_rscs(true)
+        .reflect()
+        .unwrap();
+    let ty = match &entry_points[0].vars[0] {
+        Variable::Descriptor { ty, .. } => ty,
+        x => panic!("unexpected {:?}", x),
+    };
+    let struct_ty = ty.as_struct().unwrap();
+    assert_eq!(ty.nbyte(), None);
+    assert_eq!(struct_ty.members[0].ty.layout_nbyte(), Some(12));
+    assert_eq!(struct_ty.members[1].ty.layout_nbyte(), Some(4));
+    assert_eq!(struct_ty.members[2].ty.layout_nbyte(), Some(12));
+    assert_eq!(ty.layout_nbyte(), Some(28));
+    assert_eq!(ty.layout_min_nbyte(), Some(28));
+}
+#[test]
+fn test_image_arrangement() {
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpCapability SampledRect
+        OpCapability SampledBuffer
+        OpCapability SampledCubeArray
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint GLCompute %main "main"
+        OpExecutionMode %main LocalSize 1 1 1
+        OpDecorate %rect DescriptorSet 0
+        OpDecorate %rect Binding 0
+        OpDecorate %buf DescriptorSet 0
+        OpDecorate %buf Binding 1
+        OpDecorate %cube_arr DescriptorSet 0
+        OpDecorate %cube_arr Binding 2
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %float = OpTypeFloat 32
+        %rect_ty = OpTypeImage %float Rect 0 0 0 1 Unknown
+        %buf_ty = OpTypeImage %float Buffer 0 0 0 2 Rgba8
+        %cube_arr_ty = OpTypeImage %float Cube 0 1 0 1 Unknown
+        %rect_ptr = OpTypePointer UniformConstant %rect_ty
+        %buf_ptr = OpTypePointer UniformConstant %buf_ty
+        %cube_arr_ptr = OpTypePointer UniformConstant %cube_arr_ty
+        %rect = OpVariable %rect_ptr UniformConstant
+        %buf = OpVariable %buf_ptr UniformConstant
+        %cube_arr = OpVariable %cube_arr_ptr UniformConstant
+        %main = OpFunction %void None %fn
+        %entry = OpLabel
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let entry_points = ReflectConfig::new()
+        .spv(spv)
+        .ref_all_rscs(true)
+        .reflect()
+        .unwrap();
+    assert_eq!(entry_points[0].vars.len(), 3);
+
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint GLCompute %main "main"
+        OpExecutionMode %main LocalSize 1 1 1
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %float = OpTypeFloat 32
+        %img_ty = OpTypeImage %float 3D 0 1 0 1 Unknown
+        %main = OpFunction %void None %fn
+        %entry = OpLabel
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let entry_points = ReflectConfig::new().spv(spv).reflect().unwrap();
+    assert_eq!(
+        entry_points[0].diags,
+        vec![Diagnostic::InvalidImageArrangement {
+            ty_id: 5,
+            dim: spirv::Dim::Dim3D,
+            arrangement: ImageArrangement::Arrayed,
+        }]
+    );
+
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint GLCompute %main "main"
+        OpExecutionMode %main LocalSize 1 1 1
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %float = OpTypeFloat 32
+        %img_ty = OpTypeImage %float Cube 0 1 1 1 Unknown
+        %main = OpFunction %void None %fn
+        %entry = OpLabel
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let entry_points = ReflectConfig::new().spv(spv).reflect().unwrap();
+    assert_eq!(
+        entry_points[0].diags,
+        vec![Diagnostic::InvalidImageArrangement {
+            ty_id: 5,
+            dim: spirv::Dim::DimCube,
+            arrangement: ImageArrangement::Multisampled,
+        }]
+    );
+}
+
+#[test]
+fn test_degenerate_sampled_image() {
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpCapability InputAttachment
+        OpCapability ImageBuffer
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint Fragment %main "main"
+        OpExecutionMode %main OriginUpperLeft
+        OpName %subpass "subpass"
+        OpName %storage_imgs "storage_imgs"
+        OpName %texel_buf "texel_buf"
+        OpDecorate %subpass DescriptorSet 0
+        OpDecorate %subpass Binding 0
+        OpDecorate %subpass InputAttachmentIndex 0
+        OpDecorate %storage_imgs DescriptorSet 0
+        OpDecorate %storage_imgs Binding 2
+        OpDecorate %texel_buf DescriptorSet 0
+        OpDecorate %texel_buf Binding 1
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %float = OpTypeFloat 32
+        %uint = OpTypeInt 32 0
+        %uint_4 = OpConstant %uint 4
+        %subpass_ty = OpTypeImage %float SubpassData 0 0 0 2 Unknown
+        %buf_ty = OpTypeImage %float Buffer 0 0 0 2 Rgba8
+        %storage_ty = OpTypeImage %float 2D 0 0 0 2 Rgba8
+        %sampled_subpass_ty = OpTypeSampledImage %subpass_ty
+        %sampled_buf_ty = OpTypeSampledImage %buf_ty
+        %sampled_storage_ty = OpTypeSampledImage %storage_ty
+        %sampled_storage_arr_ty = OpTypeArray %sampled_storage_ty %uint_4
+        %subpass_ptr = OpTypePointer UniformConstant %sampled_subpass_ty
+        %buf_ptr = OpTypePointer UniformConstant %sampled_buf_ty
+        %storage_arr_ptr = OpTypePointer UniformConstant %sampled_storage_arr_ty
+        %subpass = OpVariable %subpass_ptr UniformConstant
+        %texel_buf = OpVariable %buf_ptr UniformConstant
+        %storage_imgs = OpVariable %storage_arr_ptr UniformConstant
+        %main = OpFunction %void None %fn
+        %entry = OpLabel
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let entry_points = ReflectConfig::new()
+        .spv(spv)
+        .ref_all_rscs(true)
+        .reflect()
+        .unwrap();
+    let entry_point = &entry_points[0];
+    let desc_tys = entry_point
+        .vars
+        .iter()
+        .filter_map(|var| match var {
+            Variable::Descriptor { desc_ty, nbind, .. } => Some((desc_ty.clone(), *nbind)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(desc_tys.len(), 3);
+    assert!(desc_tys.contains(&(DescriptorType::InputAttachment(0), 1)));
+    assert!(desc_tys.iter().any(|(desc_ty, nbind)| matches!(
+        desc_ty,
+        DescriptorType::StorageTexelBuffer(_)
+    ) && *nbind == 1));
+    assert!(desc_tys
+        .iter()
+        .any(|(desc_ty, nbind)| matches!(desc_ty, DescriptorType::StorageImage(_)) && *nbind == 4));
+
+    let mut diags = entry_point.diags.clone();
+    diags.sort_by_key(|x| match x {
+        Diagnostic::DegenerateSampledImage { desc_bind, .. } => desc_bind.bind(),
+        _ => u32::MAX,
+    });
+    assert_eq!(
+        diags,
+        vec![
+            Diagnostic::DegenerateSampledImage {
+                name: Some("subpass".to_owned()),
+                desc_bind: DescriptorBinding::new(0, 0),
+                kind: DegenerateSampledImage::SubpassData,
+            },
+            Diagnostic::DegenerateSampledImage {
+                name: Some("texel_buf".to_owned()),
+                desc_bind: DescriptorBinding::new(0, 1),
+                kind: DegenerateSampledImage::StorageImage,
+            },
+            Diagnostic::DegenerateSampledImage {
+                name: Some("storage_imgs".to_owned()),
+                desc_bind: DescriptorBinding::new(0, 2),
+                kind: DegenerateSampledImage::StorageImage,
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_set_binding_map() {
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint GLCompute %main "main"
+        OpExecutionMode %main LocalSize 1 1 1
+        OpName %a "a"
+        OpName %b "b"
+        OpName %c "c"
+        OpName %d "d"
+        OpDecorate %a DescriptorSet 1
+        OpDecorate %a Binding 0
+        OpDecorate %b DescriptorSet 1
+        OpDecorate %b Binding 3
+        OpDecorate %c DescriptorSet 0
+        OpDecorate %c Binding 1
+        OpDecorate %d DescriptorSet 1
+        OpDecorate %d Binding 3
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %sampler_ty = OpTypeSampler
+        %sampler_ptr = OpTypePointer UniformConstant %sampler_ty
+        %a = OpVariable %sampler_ptr UniformConstant
+        %b = OpVariable %sampler_ptr UniformConstant
+        %c = OpVariable %sampler_ptr UniformConstant
+        %d = OpVariable %sampler_ptr UniformConstant
+        %main = OpFunction %void None %fn
+        %entry = OpLabel
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let entry_points = ReflectConfig::new()
+        .spv(spv)
+        .ref_all_rscs(true)
+        .reflect()
+        .unwrap();
+    let entry_point = &entry_points[0];
+    let names = entry_point
+        .set_binding_map(1)
+        .into_iter()
+        .map(|(bind, var)| (bind, var.name()))
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec![(0, Some("a")), (3, Some("b"))]);
+    assert_eq!(entry_point.set_binding_map(0).len(), 1);
+    assert!(!entry_point.set_binding_map(0).contains_key(&0));
+    assert!(entry_point.set_binding_map(2).is_empty());
+}
+
+#[test]
+fn test_fixture_round_trip() {
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpCapability InputAttachment
+        OpCapability Int64
+        OpCapability PhysicalStorageBufferAddresses
+        OpMemoryModel PhysicalStorageBuffer64 GLSL450
+        OpEntryPoint Fragment %main "main" %uv %color %ubo %ssbo %texs %img %subpass %push
+        OpExecutionMode %main OriginUpperLeft
+        OpName %Ubo "Ubo"
+        OpMemberName %Ubo 0 "xform"
+        OpMemberName %Ubo 1 "weights"
+        OpName %ubo "ubo"
+        OpName %Ssbo "Ssbo"
+        OpName %ssbo "ssbo"
+        OpName %texs "texs"
+        OpName %img "img"
+        OpName %subpass "subpass"
+        OpName %Push "Push"
+        OpName %push "push"
+        OpName %uv "uv"
+        OpName %color "color"
+        OpName %spec "spec"
+        OpDecorate %Ubo Block
+        OpMemberDecorate %Ubo 0 Offset 0
+        OpMemberDecorate %Ubo 0 RowMajor
+        OpMemberDecorate %Ubo 0 MatrixStride 16
+        OpMemberDecorate %Ubo 1 Offset 64
+        OpDecorate %weights_ty ArrayStride 16
+        OpDecorate %ubo DescriptorSet 0
+        OpDecorate %ubo Binding 0
+        OpDecorate %Ssbo Block
+        OpMemberDecorate %Ssbo 0 Offset 0
+        OpMemberDecorate %Ssbo 0 NonWritable
+        OpMemberDecorate %Ssbo 1 Offset 8
+        OpMemberDecorate %Ssbo 1 NonWritable
+        OpDecorate %uint_arr ArrayStride 4
+        OpDecorate %ssbo DescriptorSet 0
+        OpDecorate %ssbo Binding 1
+        OpDecorate %ssbo NonWritable
+        OpDecorate %texs DescriptorSet 1
+        OpDecorate %texs Binding 0
+        OpDecorate %img DescriptorSet 1
+        OpDecorate %img Binding 4
+        OpDecorate %img NonReadable
+        OpDecorate %subpass DescriptorSet 2
+        OpDecorate %subpass Binding 0
+        OpDecorate %subpass InputAttachmentIndex 3
+        OpDecorate %Push Block
+        OpMemberDecorate %Push 0 Offset 0
+        OpDecorate %uv Location 0
+        OpDecorate %color Location 1
+        OpDecorate %color Component 1
+        OpDecorate %spec SpecId 7
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %float = OpTypeFloat 32
+        %uint = OpTypeInt 32 0
+        %ulong = OpTypeInt 64 0
+        %uint_4 = OpConstant %uint 4
+        %v2float = OpTypeVector %float 2
+        %v3float = OpTypeVector %float 3
+        %v4float = OpTypeVector %float 4
+        %mat4 = OpTypeMatrix %v4float 4
+        %weights_ty = OpTypeArray %v4float %uint_4
+        %Ubo = OpTypeStruct %mat4 %weights_ty
+        %ubo_ptr = OpTypePointer Uniform %Ubo
+        %ubo = OpVariable %ubo_ptr Uniform
+        %uint_arr = OpTypeRuntimeArray %uint
+        %Ssbo = OpTypeStruct %ulong %uint_arr
+        %ssbo_ptr = OpTypePointer StorageBuffer %Ssbo
+        %ssbo = OpVariable %ssbo_ptr StorageBuffer
+        %tex_ty = OpTypeImage %float 2D 0 0 0 1 Unknown
+        %sampled_tex_ty = OpTypeSampledImage %tex_ty
+        %texs_ty = OpTypeArray %sampled_tex_ty %uint_4
+        %texs_ptr = OpTypePointer UniformConstant %texs_ty
+        %texs = OpVariable %texs_ptr UniformConstant
+        %img_ty = OpTypeImage %uint 2D 0 0 0 2 R32ui
+        %img_ptr = OpTypePointer UniformConstant %img_ty
+        %img = OpVariable %img_ptr UniformConstant
+        %subpass_ty = OpTypeImage %float SubpassData 0 0 0 2 Unknown
+        %subpass_ptr = OpTypePointer UniformConstant %subpass_ty
+        %subpass = OpVariable %subpass_ptr UniformConstant
+        %Push = OpTypeStruct %uint
+        %push_ptr = OpTypePointer PushConstant %Push
+        %push = OpVariable %push_ptr PushConstant
+        %uv_ptr = OpTypePointer Input %v2float
+        %uv = OpVariable %uv_ptr Input
+        %color_ptr = OpTypePointer Output %v3float
+        %color = OpVariable %color_ptr Output
+        %spec = OpSpecConstant %uint 3
+        %main = OpFunction %void None %fn
+        %entry = OpLabel
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let entry_points = ReflectConfig::new()
+        .spv(spv)
+        .ref_all_rscs(true)
+        .reflect()
+        .unwrap();
+    let entry_point = &entry_points[0];
+    let fixture = crate::fixture::generate_fixture(entry_point).unwrap();
+    let fixture_entry_points = ReflectConfig::new()
+        .spv(fixture)
+        .ref_all_rscs(true)
+        .validate_ids(true)
+        .reflect()
+        .unwrap();
+    let fixture_entry_point = &fixture_entry_points[0];
+    assert_eq!(fixture_entry_point.name, entry_point.name);
+    assert_eq!(fixture_entry_point.exec_model, entry_point.exec_model);
+    assert_eq!(fixture_entry_point.vars, entry_point.vars);
+    assert_eq!(fixture_entry_point.exec_modes, entry_point.exec_modes);
+    assert_eq!(fixture_entry_point.desc_infos, entry_point.desc_infos);
+}
+
+#[test]
+fn test_reflect_cfg_presets() {
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint Fragment %main "main"
+        OpExecutionMode %main OriginUpperLeft
+        OpName %tex "tex"
+        OpName %smp "smp"
+        OpDecorate %tex DescriptorSet 0
+        OpDecorate %smp DescriptorSet 0
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %float = OpTypeFloat 32
+        %tex_ty = OpTypeImage %float 2D 0 0 0 1 Unknown
+        %tex_ptr = OpTypePointer UniformConstant %tex_ty
+        %tex = OpVariable %tex_ptr UniformConstant
+        %smp_ty = OpTypeSampler
+        %smp_ptr = OpTypePointer UniformConstant %smp_ty
+        %smp = OpVariable %smp_ptr UniformConstant
+        %main = OpFunction %void None %fn
+        %entry = OpLabel
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    assert!(ReflectConfig::for_vulkan()
+        .spv(spv)
+        .ref_all_rscs(true)
+        .reflect()
+        .is_err());
+    assert!(ReflectConfig::for_wgpu()
+        .spv(spv)
+        .ref_all_rscs(true)
+        .reflect()
+        .is_err());
+
+    let entry_points = ReflectConfig::for_hlsl_dxc()
+        .spv(spv)
+        .ref_all_rscs(true)
+        .reflect()
+        .unwrap();
+    let entry_point = &entry_points[0];
+    assert_eq!(entry_point.vars.len(), 1);
+    assert!(matches!(
+        entry_point.vars[0],
+        Variable::Descriptor {
+            desc_ty: DescriptorType::CombinedImageSampler(),
+            ..
+        }
+    ));
+    assert_eq!(entry_point.diags.len(), 2);
+    assert!(entry_point
+        .diags
+        .iter()
+        .all(|x| matches!(x, Diagnostic::MissingBinding { .. })));
+
+    let entry_points = ReflectConfig::for_opengl()
+        .spv(spv)
+        .ref_all_rscs(true)
+        .reflect()
+        .unwrap();
+    assert_eq!(entry_points[0].vars.len(), 1);
+    assert_eq!(entry_points[0].gl_vars.len(), 2);
+
+    // WebGPU has no combined image samplers.
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint Fragment %main "main"
+        OpExecutionMode %main OriginUpperLeft
+        OpDecorate %tex DescriptorSet 0
+        OpDecorate %tex Binding 0
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %float = OpTypeFloat 32
+        %img_ty = OpTypeImage %float 2D 0 0 0 1 Unknown
+        %tex_ty = OpTypeSampledImage %img_ty
+        %tex_ptr = OpTypePointer UniformConstant %tex_ty
+        %tex = OpVariable %tex_ptr UniformConstant
+        %main = OpFunction %void None %fn
+        %entry = OpLabel
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    assert!(ReflectConfig::for_vulkan()
+        .spv(spv)
+        .ref_all_rscs(true)
+        .reflect()
+        .is_ok());
+    assert!(ReflectConfig::for_wgpu()
+        .spv(spv)
+        .ref_all_rscs(true)
+        .reflect()
+        .is_err());
+}
+
+#[test]
+fn test_access_through_func_params() {
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint GLCompute %main "main"
+        OpExecutionMode %main LocalSize 1 1 1
+        OpName %imgs "imgs"
+        OpName %buf "buf"
+        OpName %unused "unused"
+        OpDecorate %imgs DescriptorSet 0
+        OpDecorate %imgs Binding 0
+        OpDecorate %Buf Block
+        OpMemberDecorate %Buf 0 Offset 0
+        OpDecorate %buf DescriptorSet 0
+        OpDecorate %buf Binding 1
+        OpDecorate %unused DescriptorSet 0
+        OpDecorate %unused Binding 2
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %float = OpTypeFloat 32
+        %uint = OpTypeInt 32 0
+        %uint_0 = OpConstant %uint 0
+        %uint_2 = OpConstant %uint 2
+        %img_ty = OpTypeImage %float 2D 0 0 0 2 R32f
+        %img_ptr = OpTypePointer UniformConstant %img_ty
+        %imgs_ty = OpTypeArray %img_ty %uint_2
+        %imgs_ptr = OpTypePointer UniformConstant %imgs_ty
+        %imgs = OpVariable %imgs_ptr UniformConstant
+        %Buf = OpTypeStruct %uint
+        %buf_ptr = OpTypePointer StorageBuffer %Buf
+        %buf = OpVariable %buf_ptr StorageBuffer
+        %unused = OpVariable %buf_ptr StorageBuffer
+        %uint_ptr = OpTypePointer StorageBuffer %uint
+        %load_fn = OpTypeFunction %uint %buf_ptr
+        %forward_fn = OpTypeFunction %uint %buf_ptr %img_ptr
+        %load = OpFunction %uint None %load_fn
+        %load_buf = OpFunctionParameter %buf_ptr
+        %load_entry = OpLabel
+        %x_ptr = OpAccessChain %uint_ptr %load_buf %uint_0
+        %x = OpLoad %uint %x_ptr
+        OpReturnValue %x
+        OpFunctionEnd
+        %forward = OpFunction %uint None %forward_fn
+        %forward_buf = OpFunctionParameter %buf_ptr
+        %forward_img = OpFunctionParameter %img_ptr
+        %forward_entry = OpLabel
+        %img = OpLoad %img_ty %forward_img
+        %y = OpFunctionCall %uint %load %forward_buf
+        OpReturnValue %y
+        OpFunctionEnd
+        %main = OpFunction %void None %fn
+        %entry = OpLabel
+        %img_elem = OpAccessChain %img_ptr %imgs %uint_0
+        %z = OpFunctionCall %uint %forward %buf %img_elem
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let entry_points = ReflectConfig::new().spv(spv).reflect().unwrap();
+    let mut names = entry_points[0]
+        .vars
+        .iter()
+        .filter_map(|var| var.name())
+        .collect::<Vec<_>>();
+    names.sort();
+    assert_eq!(names, vec!["buf", "imgs"]);
+}
+
+#[test]
+fn test_spec_constant_sized_array() {
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint GLCompute %main "main"
+        OpExecutionMode %main LocalSize 1 1 1
+        OpName %Inner "Inner"
+        OpMemberName %Inner 0 "xs"
+        OpName %Ubo "Ubo"
+        OpMemberName %Ubo 0 "inner"
+        OpName %Push "Push"
+        OpMemberName %Push 0 "ys"
+        OpDecorate %n SpecId 3
+        OpDecorate %m SpecId 4
+        OpDecorate %xs_ty ArrayStride 16
+        OpDecorate %ys_ty ArrayStride 4
+        OpMemberDecorate %Inner 0 Offset 0
+        OpDecorate %Ubo Block
+        OpMemberDecorate %Ubo 0 Offset 0
+        OpDecorate %ubo DescriptorSet 0
+        OpDecorate %ubo Binding 0
+        OpDecorate %Push Block
+        OpMemberDecorate %Push 0 Offset 0
+        OpDecorate %smps DescriptorSet 0
+        OpDecorate %smps Binding 1
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %float = OpTypeFloat 32
+        %uint = OpTypeInt 32 0
+        %v4float = OpTypeVector %float 4
+        %n = OpSpecConstant %uint 4
+        %m = OpSpecConstant %uint 2
+        %xs_ty = OpTypeArray %v4float %n
+        %Inner = OpTypeStruct %xs_ty
+        %Ubo = OpTypeStruct %Inner
+        %ubo_ptr = OpTypePointer Uniform %Ubo
+        %ubo = OpVariable %ubo_ptr Uniform
+        %ys_ty = OpTypeArray %float %m
+        %Push = OpTypeStruct %ys_ty
+        %push_ptr = OpTypePointer PushConstant %Push
+        %push = OpVariable %push_ptr PushConstant
+        %smp_ty = OpTypeSampler
+        %smps_ty = OpTypeArray %smp_ty %m
+        %smps_ptr = OpTypePointer UniformConstant %smps_ty
+        %smps = OpVariable %smps_ptr UniformConstant
+        %main = OpFunction %void None %fn
+        %entry = OpLabel
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let entry_points = ReflectConfig::new()
+        .spv(spv)
+        .ref_all_rscs(true)
+        .reflect()
+        .unwrap();
+    let mut diags = entry_points[0].diags.clone();
+    diags.sort_by_key(|x| x.to_string());
+    assert_eq!(
+        diags,
+        vec![
+            Diagnostic::SpecConstantSizedArray {
+                block: Some("Inner".to_owned()),
+                member: Some("xs".to_owned()),
+                spec_id: 3,
+            },
+            Diagnostic::SpecConstantSizedArray {
+                block: Some("Push".to_owned()),
+                member: Some("ys".to_owned()),
+                spec_id: 4,
+            },
+        ]
+    );
+
+    let err = ReflectConfig::new()
+        .spv(spv)
+        .ref_all_rscs(true)
+        .strict_spec_array_sizes(true)
+        .reflect()
+        .unwrap_err();
+    assert!(err.to_string().contains("sized by specialization constant"));
+}
+
+#[test]
+fn test_reflect_async() {
+    use std::future::Future;
+    use std::sync::Arc;
+    use std::task::{Context, Poll, Wake};
+
+    struct NoopWaker;
+    impl Wake for NoopWaker {
+        fn wake(self: Arc<Self>) {}
+    }
+    fn assert_send<T: Send>(x: T) -> T {
+        x
+    }
+
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint GLCompute %main "main"
+        OpExecutionMode %main LocalSize 1 1 1
+        OpDecorate %buf DescriptorSet 0
+        OpDecorate %buf Binding 0
+        OpDecorate %Buf Block
+        OpMemberDecorate %Buf 0 Offset 0
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %uint = OpTypeInt 32 0
+        %uint_0 = OpConstant %uint 0
+        %Buf = OpTypeStruct %uint
+        %buf_ptr = OpTypePointer StorageBuffer %Buf
+        %buf = OpVariable %buf_ptr StorageBuffer
+        %uint_ptr = OpTypePointer StorageBuffer %uint
+        %main = OpFunction %void None %fn
+        %entry = OpLabel
+        %x_ptr = OpAccessChain %uint_ptr %buf %uint_0
+        %x = OpLoad %uint %x_ptr
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let mut cfg = ReflectConfig::new();
+    cfg.spv(spv);
+    let mut fut = Box::pin(assert_send(cfg.reflect_async()));
+    let waker = Arc::new(NoopWaker).into();
+    let mut cx = Context::from_waker(&waker);
+    let mut npoll = 0;
+    let entry_points = loop {
+        npoll += 1;
+        if let Poll::Ready(x) = fut.as_mut().poll(&mut cx) {
+            break x.unwrap();
+        }
+    };
+    assert!(npoll > 1);
+    let expected = ReflectConfig::new().spv(spv).reflect().unwrap();
+    assert_eq!(entry_points, expected);
+    assert_eq!(entry_points[0].vars.len(), 1);
+}
+
 #[test]
 fn test_issue_84() {
     let _entry: EntryPoint = gen_one_entry!(
@@ -722,3 +1752,6218 @@ fn test_resource_in_chained_call() {
     // Ensure the unreferenced one is not in the map.
     assert_eq!(desc_binds.get(&DescriptorBinding::new(1, 3)), None);
 }
+#[test]
+fn test_missing_binding_policy() {
+    static SPV: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint GLCompute %main "main"
+        OpExecutionMode %main LocalSize 1 1 1
+        OpName %a "a"
+        OpName %b "b"
+        OpDecorate %s Block
+        OpMemberDecorate %s 0 Offset 0
+        OpDecorate %a DescriptorSet 1
+        OpDecorate %a Binding 3
+        OpDecorate %b DescriptorSet 1
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %float = OpTypeFloat 32
+        %s = OpTypeStruct %float
+        %ptr = OpTypePointer Uniform %s
+        %a = OpVariable %ptr Uniform
+        %b = OpVariable %ptr Uniform
+        %main = OpFunction %void None %fn
+        %entry = OpLabel
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let reflect = |policy| {
+        ReflectConfig::new()
+            .spv(SPV)
+            .ref_all_rscs(true)
+            .missing_binding_policy(policy)
+            .reflect()
+    };
+    let get_desc_bind = |entry: &EntryPoint, name: &str| {
+        entry
+            .vars
+            .iter()
+            .find_map(|x| match x {
+                Variable::Descriptor { desc_bind, .. } if x.name() == Some(name) => {
+                    Some(*desc_bind)
+                }
+                _ => None,
+            })
+            .unwrap()
+    };
+
+    assert!(reflect(MissingBindingPolicy::Error).is_err());
+
+    let entry = reflect(MissingBindingPolicy::WarnAndDefault)
+        .unwrap()
+        .pop()
+        .unwrap();
+    let desc_bind = get_desc_bind(&entry, "b");
+    assert_eq!(desc_bind, DescriptorBinding::new(0, 0));
+    assert!(entry.desc_infos[&desc_bind].is_binding_implicit);
+    assert_eq!(
+        entry.diags,
+        vec![Diagnostic::MissingBinding {
+            name: Some("b".to_owned()),
+            desc_bind,
+        }]
+    );
+
+    let entry = reflect(MissingBindingPolicy::AutoAssign)
+        .unwrap()
+        .pop()
+        .unwrap();
+    let desc_bind = get_desc_bind(&entry, "b");
+    assert_eq!(desc_bind, DescriptorBinding::new(1, 4));
+    assert!(entry.desc_infos[&desc_bind].is_binding_implicit);
+    assert!(!entry.desc_infos[&get_desc_bind(&entry, "a")].is_binding_implicit);
+    assert!(entry.diags.is_empty());
+}
+#[test]
+fn test_input_comp_masks() {
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpCapability Float64
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint Vertex %main "main" %pos %uv %color %weight %xform %out
+        OpDecorate %pos Location 0
+        OpDecorate %uv Location 1
+        OpDecorate %color Location 3
+        OpDecorate %weight Location 4
+        OpDecorate %weight Component 2
+        OpDecorate %xform Location 5
+        OpDecorate %out Location 0
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %float = OpTypeFloat 32
+        %double = OpTypeFloat 64
+        %int = OpTypeInt 32 1
+        %int_2 = OpConstant %int 2
+        %v2float = OpTypeVector %float 2
+        %v3double = OpTypeVector %double 3
+        %v4float = OpTypeVector %float 4
+        %mat2 = OpTypeMatrix %v2float 2
+        %in_v4float = OpTypePointer Input %v4float
+        %in_v3double = OpTypePointer Input %v3double
+        %in_v2float = OpTypePointer Input %v2float
+        %in_float = OpTypePointer Input %float
+        %in_mat2 = OpTypePointer Input %mat2
+        %out_v2float = OpTypePointer Output %v2float
+        %pos = OpVariable %in_v4float Input
+        %uv = OpVariable %in_v3double Input
+        %color = OpVariable %in_v4float Input
+        %weight = OpVariable %in_v2float Input
+        %xform = OpVariable %in_mat2 Input
+        %out = OpVariable %out_v2float Output
+        %main = OpFunction %void None %fn
+        %entry = OpLabel
+        %pos_val = OpLoad %v4float %pos
+        %pos_xy = OpVectorShuffle %v2float %pos_val %pos_val 0 1
+        %uv_val = OpLoad %v3double %uv
+        %uv_z = OpCompositeExtract %double %uv_val 2
+        %color_z_ptr = OpAccessChain %in_float %color %int_2
+        %color_z = OpLoad %float %color_z_ptr
+        %weight_val = OpLoad %v2float %weight
+        %xform_val = OpLoad %mat2 %xform
+        %sum = OpFAdd %v2float %pos_xy %weight_val
+        OpStore %out %sum
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let entry_points = ReflectConfig::new().spv(spv).reflect().unwrap();
+    let masks = entry_points[0]
+        .input_comp_masks
+        .iter()
+        .map(|(loc, mask)| (*loc, *mask))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        masks,
+        vec![
+            (0, 0b0011),
+            // The third component of a `dvec3` takes up two components of
+            // the next location.
+            (1, 0b0000),
+            (2, 0b0011),
+            (3, 0b0100),
+            (4, 0b1100),
+            (5, 0b0011),
+            (6, 0b0011),
+        ]
+    );
+}
+#[test]
+fn test_gl_vars() {
+    use crate::gl::{GlVariable, XfbCapture};
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpCapability AtomicStorage
+        OpCapability TransformFeedback
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint Vertex %main "main" %pos %out_pos
+        OpExecutionMode %main Xfb
+        OpName %pos "pos"
+        OpName %out_pos "out_pos"
+        OpName %tint "tint"
+        OpName %tex "tex"
+        OpName %ubo "ubo"
+        OpName %counter "counter"
+        OpDecorate %pos Location 0
+        OpDecorate %out_pos Location 0
+        OpDecorate %out_pos XfbBuffer 1
+        OpDecorate %out_pos XfbStride 16
+        OpDecorate %out_pos Offset 0
+        OpDecorate %tint Location 2
+        OpDecorate %tex Location 3
+        OpDecorate %tex Binding 1
+        OpDecorate %Ubo Block
+        OpMemberDecorate %Ubo 0 Offset 0
+        OpDecorate %ubo Binding 0
+        OpDecorate %counter Binding 0
+        OpDecorate %counter Offset 4
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %float = OpTypeFloat 32
+        %uint = OpTypeInt 32 0
+        %v4float = OpTypeVector %float 4
+        %img = OpTypeImage %float 2D 0 0 0 1 Unknown
+        %sampled_img = OpTypeSampledImage %img
+        %Ubo = OpTypeStruct %v4float
+        %in_v4float = OpTypePointer Input %v4float
+        %out_v4float = OpTypePointer Output %v4float
+        %uniform_v4float = OpTypePointer UniformConstant %v4float
+        %uniform_sampled_img = OpTypePointer UniformConstant %sampled_img
+        %uniform_Ubo = OpTypePointer Uniform %Ubo
+        %atomic_uint = OpTypePointer AtomicCounter %uint
+        %pos = OpVariable %in_v4float Input
+        %out_pos = OpVariable %out_v4float Output
+        %tint = OpVariable %uniform_v4float UniformConstant
+        %tex = OpVariable %uniform_sampled_img UniformConstant
+        %ubo = OpVariable %uniform_Ubo Uniform
+        %counter = OpVariable %atomic_uint AtomicCounter
+        %main = OpFunction %void None %fn
+        %entry = OpLabel
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let entry_points = ReflectConfig::new()
+        .spv(spv)
+        .ref_all_rscs(true)
+        .gl(true)
+        .reflect()
+        .unwrap();
+    let gl_vars = &entry_points[0].gl_vars;
+    let find = |name: &str| gl_vars.iter().find(|x| x.name() == Some(name)).unwrap();
+    assert_eq!(gl_vars.len(), 6);
+    match find("pos") {
+        GlVariable::Input { location, .. } => assert_eq!(*location, InterfaceLocation::new(0, 0)),
+        x => panic!("unexpected {:?}", x),
+    }
+    match find("out_pos") {
+        GlVariable::Output { xfb, .. } => assert_eq!(
+            *xfb,
+            Some(XfbCapture {
+                buffer: 1,
+                stride: Some(16),
+                offset: 0,
+            })
+        ),
+        x => panic!("unexpected {:?}", x),
+    }
+    match find("tint") {
+        GlVariable::Uniform { location, .. } => assert_eq!(*location, Some(2)),
+        x => panic!("unexpected {:?}", x),
+    }
+    match find("tex") {
+        GlVariable::Opaque {
+            location,
+            binding,
+            nbind,
+            ty,
+            ..
+        } => {
+            assert_eq!(*location, Some(3));
+            assert_eq!(*binding, 1);
+            assert_eq!(*nbind, 1);
+            assert!(matches!(ty, Type::CombinedImageSampler(_)));
+        }
+        x => panic!("unexpected {:?}", x),
+    }
+    assert!(matches!(
+        find("ubo"),
+        GlVariable::UniformBlock { binding: 0, .. }
+    ));
+    assert!(matches!(
+        find("counter"),
+        GlVariable::AtomicCounter {
+            binding: 0,
+            offset: 4,
+            ..
+        }
+    ));
+
+    // Not collected by default.
+    let entry_points = ReflectConfig::new()
+        .spv(spv)
+        .ref_all_rscs(true)
+        .reflect()
+        .unwrap();
+    assert!(entry_points[0].gl_vars.is_empty());
+}
+#[test]
+fn test_pipeline_shadowed_desc() {
+    let vert = gen_one_entry!(
+        vert,
+        r#"
+        #version 450 core
+        layout(set=0, binding=0) uniform sampler2D heightMap;
+        layout(set=0, binding=1) uniform Camera { mat4 view_proj; };
+        void main() { gl_Position = view_proj * textureLod(heightMap, vec2(0.0), 0.0); }
+        "#
+    );
+    let frag = gen_one_entry!(
+        frag,
+        r#"
+        #version 450 core
+        layout(set=0, binding=0) uniform sampler2D albedo;
+        layout(set=0, binding=1) uniform camera { mat4 view_proj; };
+        layout(location=0) out vec4 color;
+        void main() { color = texture(albedo, vec2(0.0)) * view_proj[0]; }
+        "#
+    );
+    let pipeline = PipelineConfig::new()
+        .merge(&[vert.clone(), frag.clone()])
+        .unwrap();
+    let desc = &pipeline.descs[&DescriptorBinding::new(0, 0)];
+    assert_eq!(
+        desc.stages,
+        vec![ExecutionModel::Vertex, ExecutionModel::Fragment]
+    );
+    assert_eq!(
+        pipeline.diags,
+        vec![Diagnostic::ShadowedDescriptor {
+            desc_bind: DescriptorBinding::new(0, 0),
+            name: "albedo".to_owned(),
+            shadowed_name: "heightMap".to_owned(),
+        }]
+    );
+    assert!(PipelineConfig::new()
+        .strict(true)
+        .merge(&[vert, frag])
+        .is_err());
+}
+#[test]
+fn test_pipeline_mutable_desc() {
+    let vert = gen_one_entry!(
+        vert,
+        r#"
+        #version 450 core
+        layout(set=0, binding=0) buffer Positions { vec4 positions[]; };
+        void main() { gl_Position = positions[gl_VertexIndex]; }
+        "#
+    );
+    let frag = gen_one_entry!(
+        frag,
+        r#"
+        #version 450 core
+        layout(set=0, binding=0) uniform texture2D albedo;
+        layout(set=0, binding=1) uniform sampler samp;
+        layout(location=0) out vec4 color;
+        void main() { color = texture(sampler2D(albedo, samp), vec2(0.0)); }
+        "#
+    );
+    assert!(PipelineConfig::new()
+        .merge(&[vert.clone(), frag.clone()])
+        .is_err());
+    let desc_bind = DescriptorBinding::new(0, 0);
+    let mutable_desc_tys = [
+        DescriptorType::SampledImage(),
+        DescriptorType::StorageBuffer(AccessType::ReadOnly),
+    ];
+    let pipeline = PipelineConfig::new()
+        .mutable_desc(desc_bind, &mutable_desc_tys)
+        .merge(&[vert.clone(), frag.clone()])
+        .unwrap();
+    let desc = &pipeline.descs[&desc_bind];
+    assert_eq!(
+        desc.stages,
+        vec![ExecutionModel::Vertex, ExecutionModel::Fragment]
+    );
+    assert_eq!(desc.mutable_desc_tys, Some(mutable_desc_tys.to_vec()));
+    assert_eq!(
+        pipeline.descs[&DescriptorBinding::new(0, 1)].mutable_desc_tys,
+        None
+    );
+    assert!(PipelineConfig::new()
+        .mutable_desc(desc_bind, &[DescriptorType::SampledImage()])
+        .merge(&[vert, frag])
+        .is_err());
+}
+#[test]
+fn test_pipeline_ycbcr_desc() {
+    let frag = gen_one_entry!(
+        frag,
+        r#"
+        #version 450 core
+        layout(set=0, binding=0) uniform sampler2D video;
+        layout(set=0, binding=1) uniform texture2D overlay;
+        layout(set=0, binding=2) uniform sampler samp;
+        layout(location=0) out vec4 color;
+        void main() {
+            color = texture(video, vec2(0.0)) + texture(sampler2D(overlay, samp), vec2(0.0));
+        }
+        "#
+    );
+    let pipeline = PipelineConfig::new()
+        .ycbcr_desc(DescriptorBinding::new(0, 0))
+        .merge(&[frag.clone()])
+        .unwrap();
+    assert!(pipeline.descs[&DescriptorBinding::new(0, 0)].requires_ycbcr_sampler);
+    assert!(!pipeline.descs[&DescriptorBinding::new(0, 1)].requires_ycbcr_sampler);
+    assert!(PipelineConfig::new()
+        .ycbcr_desc(DescriptorBinding::new(0, 1))
+        .merge(&[frag])
+        .is_err());
+}
+#[test]
+fn test_quick_scan() {
+    static SPV: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpCapability Int64
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint Vertex %vert "vert_main"
+        OpEntryPoint Fragment %frag "frag_main"
+        OpExecutionMode %frag OriginUpperLeft
+        OpName %vert "vert_main"
+        OpModuleProcessed "client vulkan100"
+        OpModuleProcessed "entry-point main"
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %vert = OpFunction %void None %fn
+        %vert_entry = OpLabel
+        OpReturn
+        OpFunctionEnd
+        %frag = OpFunction %void None %fn
+        %frag_entry = OpLabel
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let module_info = SpirvBinary::from(SPV).quick_scan().unwrap();
+    assert_eq!(
+        module_info.caps,
+        vec![spirv::Capability::Shader, spirv::Capability::Int64]
+    );
+    let entry_points = module_info
+        .entry_points
+        .iter()
+        .map(|x| (x.name.as_str(), x.exec_model))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        entry_points,
+        vec![
+            ("vert_main", ExecutionModel::Vertex),
+            ("frag_main", ExecutionModel::Fragment)
+        ]
+    );
+    assert_eq!(
+        module_info.processes,
+        vec!["client vulkan100", "entry-point main"]
+    );
+    assert!(module_info.unknown_caps.is_empty());
+    assert!(SpirvBinary::from(&SPV[..3]).quick_scan().is_err());
+
+    // Capabilities unknown to the SPIR-V headers are kept as is.
+    let mut spv = SPV.to_vec();
+    assert_eq!(spv[8], spirv::Capability::Int64 as u32);
+    spv[8] = 7777;
+    let module_info = SpirvBinary::from(spv.clone()).quick_scan().unwrap();
+    assert_eq!(module_info.caps, vec![spirv::Capability::Shader]);
+    assert_eq!(module_info.unknown_caps, vec![7777]);
+    let reflection = ReflectConfig::new().spv(spv).reflect_full().unwrap();
+    assert_eq!(reflection.module_info, module_info);
+    assert_eq!(reflection.entry_points.len(), 2);
+
+    // Vulkan 1.2 targets SPIR-V 1.5.
+    let spv = SpirvBinary::from(SPV);
+    assert_eq!(module_info.version, SpirvVersion::new(1, 5));
+    assert_eq!(spv.version().unwrap(), module_info.version);
+    assert_eq!(spv.generator().unwrap(), module_info.generator);
+    assert_eq!(module_info.id_bound, spv.id_bound().unwrap());
+    assert!(module_info.version > SpirvVersion::new(1, 3));
+    assert_eq!(module_info.version.to_string(), "1.5");
+
+    let generator = GeneratorInfo::new((8 << 16) | 11);
+    assert_eq!(generator.tool_id, 8);
+    assert_eq!(generator.version, 11);
+    assert_eq!(generator.tool, Some("Glslang Reference Front End"));
+    let generator = GeneratorInfo::new(0xffff_0001);
+    assert_eq!(generator.vendor, None);
+    assert_eq!(generator.tool, None);
+}
+#[test]
+fn test_instr_iter() {
+    use crate::binary::InstrIter;
+    let spv: &[u32] = &[
+        0x07230203,
+        0x00010500,
+        0,
+        2,
+        0,
+        (2 << 16) | 17, // OpCapability Shader
+        1,
+        (3 << 16) | 14, // OpMemoryModel Logical GLSL450
+        0,
+        1,
+        (1 << 16) | 0xffff, // Unknown opcode.
+        (4 << 16) | 21,     // OpTypeInt %1 32 0
+        1,
+        32,
+    ];
+    let mut instrs = InstrIter::new(spv).unwrap();
+    let instr = instrs.next().unwrap().unwrap();
+    assert_eq!(instr.offset(), 5);
+    assert_eq!(instr.byte_offset(), 20);
+    assert_eq!(instr.op(), Some(spirv::Op::Capability));
+    assert_eq!(instr.operands(), &[1]);
+    assert_eq!(format!("{:?}", instr), "5: OpCapability [1]");
+    let instr = instrs.next().unwrap().unwrap();
+    assert_eq!(instr.offset(), 7);
+    assert_eq!(instr.words(), &spv[7..10]);
+    let instr = instrs.next().unwrap().unwrap();
+    assert_eq!(instr.op(), None);
+    assert_eq!(format!("{:?}", instr), "10: <opcode 65535> []");
+    let err = instrs.next().unwrap().unwrap_err();
+    assert_eq!(err.to_string(), "instruction at word 11 is truncated");
+    assert!(instrs.next().is_none());
+
+    let ops = SpirvBinary::from(&spv[..10])
+        .instr_iter()
+        .unwrap()
+        .map(|x| x.map(|x| x.op()))
+        .collect::<Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(
+        ops,
+        vec![Some(spirv::Op::Capability), Some(spirv::Op::MemoryModel)]
+    );
+    assert!(InstrIter::new(&spv[..3]).is_err());
+}
+#[test]
+fn test_malformed_instrs() {
+    // OpTypeInt with its operands missing.
+    let spv: &[u32] = &[
+        0x07230203,
+        0x00010500,
+        0,
+        2,
+        0,
+        (2 << 16) | 17, // OpCapability Shader
+        1,
+        (3 << 16) | 14, // OpMemoryModel Logical GLSL450
+        0,
+        1,
+        (2 << 16) | 21, // OpTypeInt %1
+        1,
+    ];
+    let err = ReflectConfig::new().spv(spv).reflect().unwrap_err();
+    assert!(err.to_string().contains("OpTypeInt"));
+    // The decode error is kept as the cause.
+    assert!(err.chain().count() > 1);
+
+    // OpTypeInt with an excess operand.
+    let mut excess = spv[..10].to_vec();
+    excess.extend([(5 << 16) | 21, 1, 32, 0, 0]);
+    let err = ReflectConfig::new().spv(excess).reflect().unwrap_err();
+    assert!(err.to_string().contains("OpTypeInt"));
+    assert!(format!("{:#}", err).contains("1 trailing words"));
+
+    // Unknown opcodes are ignored.
+    let mut spv = spv[..10].to_vec();
+    spv.extend([(1 << 16) | 0xffff]);
+    let reflection = ReflectConfig::new().spv(spv).reflect_full().unwrap();
+    assert!(reflection.entry_points.is_empty());
+    assert_eq!(
+        reflection.diags,
+        vec![Diagnostic::UnknownDeclaration { opcode: 0xffff }]
+    );
+}
+#[test]
+fn test_memory_access_params() {
+    // Operands following memory access bits are no excess operands.
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint GLCompute %main "main"
+        OpExecutionMode %main LocalSize 1 1 1
+        OpDecorate %Data Block
+        OpMemberDecorate %Data 0 Offset 0
+        OpDecorate %data DescriptorSet 0
+        OpDecorate %data Binding 0
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %int = OpTypeInt 32 1
+        %int_0 = OpConstant %int 0
+        %Data = OpTypeStruct %int
+        %data_ptr = OpTypePointer StorageBuffer %Data
+        %int_ptr = OpTypePointer StorageBuffer %int
+        %data = OpVariable %data_ptr StorageBuffer
+        %main = OpFunction %void None %fn
+        %entry = OpLabel
+        %ptr = OpAccessChain %int_ptr %data %int_0
+        %x = OpLoad %int %ptr Aligned 4
+        OpStore %ptr %x Aligned 4
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let entry_points = ReflectConfig::new().spv(spv).reflect().unwrap();
+    assert_eq!(entry_points[0].vars.len(), 1);
+}
+#[test]
+fn test_huge_result_id() {
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint GLCompute %main "main"
+        OpExecutionMode %main LocalSize 1 1 1
+        OpDecorate %Data Block
+        OpMemberDecorate %Data 0 Offset 0
+        OpDecorate %data DescriptorSet 0
+        OpDecorate %data Binding 0
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %int = OpTypeInt 32 1
+        %int_0 = OpConstant %int 0
+        %Data = OpTypeStruct %int
+        %data_ptr = OpTypePointer StorageBuffer %Data
+        %int_ptr = OpTypePointer StorageBuffer %int
+        %data = OpVariable %data_ptr StorageBuffer
+        %main = OpFunction %void None %fn
+        %entry = OpLabel
+        %ptr = OpAccessChain %int_ptr %data %int_0
+        %x = OpLoad %int %ptr
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    // Renumber the access chain to an ID near the maximum.
+    const HUGE_ID: u32 = 0xffff_fff0;
+    let mut spv = spv.to_vec();
+    spv[3] = HUGE_ID + 1;
+    let mut i = 5;
+    while i < spv.len() {
+        let (len, opcode) = ((spv[i] >> 16) as usize, spv[i] & 0xffff);
+        if opcode == spirv::Op::AccessChain as u32 {
+            spv[i + 2] = HUGE_ID;
+        } else if opcode == spirv::Op::Load as u32 {
+            spv[i + 3] = HUGE_ID;
+        }
+        i += len;
+    }
+    let entry_points = ReflectConfig::new().spv(spv).reflect().unwrap();
+    assert_eq!(entry_points[0].vars.len(), 1);
+}
+#[test]
+fn test_id_bound() {
+    let spv: &[u32] = &[
+        0x07230203,
+        0x00010500,
+        0,
+        3,
+        0,
+        (2 << 16) | 17, // OpCapability Shader
+        1,
+        (3 << 16) | 14, // OpMemoryModel Logical GLSL450
+        0,
+        1,
+        (4 << 16) | 21, // OpTypeInt %1 32 0
+        1,
+        32,
+        0,
+        (4 << 16) | 23, // OpTypeVector %2 %1 4
+        2,
+        1,
+        4,
+    ];
+    assert_eq!(SpirvBinary::from(spv).quick_scan().unwrap().id_bound, 3);
+    ReflectConfig::new()
+        .spv(spv)
+        .validate_ids(true)
+        .reflect()
+        .unwrap();
+
+    // OpTypeVector %2 %3 4
+    let mut spv = spv.to_vec();
+    spv[16] = 3;
+    assert!(ReflectConfig::new().spv(spv.as_slice()).reflect().is_err());
+    let err = ReflectConfig::new()
+        .spv(spv.as_slice())
+        .validate_ids(true)
+        .reflect()
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "OpTypeVector (instruction #3) references id 3 out of the id bound 3"
+    );
+
+    // Truncated headers are errors rather than panics.
+    assert!(ReflectConfig::new().spv(&spv[..3]).reflect().is_err());
+}
+#[test]
+fn test_stale_id_bound() {
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint Fragment %main "main" %color %consts %data
+        OpExecutionMode %main OriginUpperLeft
+        OpDecorate %color Location 0
+        OpDecorate %Consts Block
+        OpMemberDecorate %Consts 0 Offset 0
+        OpMemberDecorate %Consts 1 Offset 4
+        OpDecorate %Data Block
+        OpMemberDecorate %Data 0 Offset 0
+        OpDecorate %data DescriptorSet 0
+        OpDecorate %data Binding 0
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %int = OpTypeInt 32 1
+        %int_0 = OpConstant %int 0
+        %int_1 = OpConstant %int 1
+        %float = OpTypeFloat 32
+        %v4float = OpTypeVector %float 4
+        %Consts = OpTypeStruct %float %float
+        %Data = OpTypeStruct %float
+        %in_v4float = OpTypePointer Input %v4float
+        %in_float = OpTypePointer Input %float
+        %consts_ptr = OpTypePointer PushConstant %Consts
+        %pc_float_ptr = OpTypePointer PushConstant %float
+        %data_ptr = OpTypePointer StorageBuffer %Data
+        %sb_float_ptr = OpTypePointer StorageBuffer %float
+        %color = OpVariable %in_v4float Input
+        %consts = OpVariable %consts_ptr PushConstant
+        %data = OpVariable %data_ptr StorageBuffer
+        %main = OpFunction %void None %fn
+        %entry = OpLabel
+        %color_y_ptr = OpAccessChain %in_float %color %int_1
+        %color_y = OpLoad %float %color_y_ptr
+        %scale_ptr = OpAccessChain %pc_float_ptr %consts %int_1
+        %scale = OpLoad %float %scale_ptr
+        %x_ptr = OpAccessChain %sb_float_ptr %data %int_0
+        OpStore %x_ptr %scale
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let entry_points = ReflectConfig::new().spv(spv).reflect().unwrap();
+    let entry = &entry_points[0];
+    assert_eq!(entry.input_comp_masks.get(&0), Some(&0b0010));
+    assert_eq!(entry.push_const_bytes, Some(4..8));
+    assert!(entry.writes_external_memory);
+
+    // IDs at and above a stale bound are tracked as well.
+    let mut stale = spv.to_vec();
+    stale[3] = 8;
+    let stale_entry_points = ReflectConfig::new().spv(stale).reflect().unwrap();
+    assert_eq!(
+        format!("{:?}", stale_entry_points),
+        format!("{:?}", entry_points)
+    );
+}
+#[test]
+fn test_grammar_decode() {
+    use crate::grammar::{decode_operands, Operand, OperandKind};
+    static SPV: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint Fragment %main "main"
+        OpExecutionMode %main OriginUpperLeft
+        OpDecorate %img DescriptorSet 0
+        OpDecorate %img Binding 1
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %float = OpTypeFloat 32
+        %v2float = OpTypeVector %float 2
+        %v4float = OpTypeVector %float 4
+        %image = OpTypeImage %float 2D 0 0 0 1 Unknown
+        %sampled_image = OpTypeSampledImage %image
+        %ptr = OpTypePointer UniformConstant %sampled_image
+        %img = OpVariable %ptr UniformConstant
+        %zero = OpConstant %float 0
+        %uv = OpConstantComposite %v2float %zero %zero
+        %main = OpFunction %void None %fn
+        %entry = OpLabel
+        %x = OpLoad %sampled_image %img
+        %y = OpImageSampleExplicitLod %v4float %x %uv Lod %zero
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let spv = SpirvBinary::from(SPV);
+    let mut instrs = spv.instrs().unwrap();
+    let mut decoded = Vec::new();
+    while let Some(instr) = instrs.next().unwrap() {
+        decoded.push((instr.op(), decode_operands(instr).unwrap()));
+    }
+    let (_, binding) = decoded
+        .iter()
+        .filter(|(op, _)| *op == spirv::Op::Decorate)
+        .last()
+        .unwrap();
+    assert_eq!(
+        binding[1],
+        Operand::Enum {
+            kind: OperandKind::Decoration,
+            value: spirv::Decoration::Binding as u32,
+        }
+    );
+    assert_eq!(binding[2], Operand::Literal(1));
+    let (_, sample) = decoded
+        .iter()
+        .find(|(op, _)| *op == spirv::Op::ImageSampleExplicitLod)
+        .unwrap();
+    assert_eq!(sample.len(), 6);
+    assert_eq!(
+        sample[4],
+        Operand::Enum {
+            kind: OperandKind::ImageOperands,
+            value: spirv::ImageOperands::LOD.bits(),
+        }
+    );
+}
+#[test]
+fn test_ext_inst_registry() {
+    use crate::inspect::ExtInstRegistry;
+    static SPV: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        %glsl = OpExtInstImport "GLSL.std.450"
+        %vendor = OpExtInstImport "NonSemantic.Vendor.Annotations"
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint GLCompute %main "main"
+        OpExecutionMode %main LocalSize 1 1 1
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %float = OpTypeFloat 32
+        %one = OpConstant %float 1
+        %main = OpFunction %void None %fn
+        %entry = OpLabel
+        %x = OpExtInst %float %glsl 13 %one
+        %y = OpExtInst %float %glsl 14 %x
+        %z = OpExtInst %void %vendor 7 %y
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let mut glsl_opcodes = Vec::new();
+    let mut non_semantic_sets = Vec::new();
+    {
+        let mut registry = ExtInstRegistry::new();
+        registry
+            .register("GLSL.std.450", |_, ext_instr| {
+                glsl_opcodes.push(ext_instr.opcode);
+                Ok(())
+            })
+            .register("NonSemantic.*", |_, ext_instr| {
+                non_semantic_sets.push(ext_instr.set_name.to_owned());
+                assert_eq!(ext_instr.opcode, 7);
+                Ok(())
+            });
+        ReflectConfig::new()
+            .spv(SPV)
+            .reflect_inspect(&mut registry)
+            .unwrap();
+    }
+    // GLSL.std.450 Sin and Cos.
+    assert_eq!(glsl_opcodes, vec![13, 14]);
+    assert_eq!(non_semantic_sets, vec!["NonSemantic.Vendor.Annotations"]);
+}
+#[test]
+fn test_from_container() {
+    static SPV: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint GLCompute %main "main"
+        OpExecutionMode %main LocalSize 1 1 1
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %main = OpFunction %void None %fn
+        %entry = OpLabel
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let spv_bytes = SPV.iter().flat_map(|x| x.to_le_bytes()).collect::<Vec<_>>();
+    let check = |spv: SpirvBinary| {
+        assert_eq!(spv.words(), SPV);
+    };
+
+    // Raw SPIR-V in both endiannesses.
+    check(SpirvBinary::from_container(&spv_bytes).unwrap());
+    let spv_bytes_be = SPV.iter().flat_map(|x| x.to_be_bytes()).collect::<Vec<_>>();
+    check(SpirvBinary::from_container(&spv_bytes_be).unwrap());
+
+    // Compiler artifact with a leading header and trailing debug data.
+    let mut artifact = b"ARTF\x01\x00\x00\x00".to_vec();
+    artifact.extend(&spv_bytes);
+    artifact.extend(b"\xff\xff\xff\xffdebug data");
+    check(SpirvBinary::from_container(&artifact).unwrap());
+
+    // DXBC container with two parts.
+    let mut dxbc = b"DXBC".to_vec();
+    dxbc.extend([0u8; 16]);
+    dxbc.extend(1u32.to_le_bytes());
+    dxbc.extend(0u32.to_le_bytes());
+    dxbc.extend(2u32.to_le_bytes());
+    let part0_offset = dxbc.len() + 8;
+    let part1_offset = part0_offset + 8 + 4;
+    dxbc.extend((part0_offset as u32).to_le_bytes());
+    dxbc.extend((part1_offset as u32).to_le_bytes());
+    dxbc.extend(b"STAT");
+    dxbc.extend(4u32.to_le_bytes());
+    dxbc.extend([0u8; 4]);
+    dxbc.extend(b"SPRV");
+    dxbc.extend((spv_bytes.len() as u32).to_le_bytes());
+    dxbc.extend(&spv_bytes);
+    check(SpirvBinary::from_container(&dxbc).unwrap());
+    // Part offsets and sizes close to the address space limit are rejected.
+    let mut bad_dxbc = dxbc.clone();
+    bad_dxbc[32..36].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert!(SpirvBinary::from_container(&bad_dxbc).is_err());
+    let mut bad_dxbc = dxbc.clone();
+    bad_dxbc[part0_offset + 4..part0_offset + 8].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert!(SpirvBinary::from_container(&bad_dxbc).is_err());
+
+    assert!(SpirvBinary::from_container(b"OpCapability Shader").is_err());
+    assert!(SpirvBinary::from_container(&[]).is_err());
+}
+
+#[test]
+fn test_stubs() {
+    use crate::stub::{generate_stubs, StubLanguage};
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint Fragment %main "main" %uv %color
+        OpExecutionMode %main OriginUpperLeft
+        OpName %Params "Params"
+        OpMemberName %Params 0 "tint"
+        OpMemberName %Params 1 "xform"
+        OpName %params "params"
+        OpName %tex "tex"
+        OpName %uv "uv"
+        OpName %color "color"
+        OpDecorate %Params Block
+        OpMemberDecorate %Params 0 Offset 0
+        OpMemberDecorate %Params 1 Offset 16
+        OpMemberDecorate %Params 1 ColMajor
+        OpMemberDecorate %Params 1 MatrixStride 16
+        OpDecorate %params DescriptorSet 0
+        OpDecorate %params Binding 0
+        OpDecorate %tex DescriptorSet 0
+        OpDecorate %tex Binding 1
+        OpDecorate %uv Location 0
+        OpDecorate %color Location 0
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %float = OpTypeFloat 32
+        %v2float = OpTypeVector %float 2
+        %v4float = OpTypeVector %float 4
+        %mat4 = OpTypeMatrix %v4float 4
+        %Params = OpTypeStruct %v4float %mat4
+        %ptr_Params = OpTypePointer Uniform %Params
+        %params = OpVariable %ptr_Params Uniform
+        %img = OpTypeImage %float 2D 0 0 0 1 Unknown
+        %sampled_img = OpTypeSampledImage %img
+        %ptr_tex = OpTypePointer UniformConstant %sampled_img
+        %tex = OpVariable %ptr_tex UniformConstant
+        %ptr_in = OpTypePointer Input %v2float
+        %uv = OpVariable %ptr_in Input
+        %ptr_out = OpTypePointer Output %v4float
+        %color = OpVariable %ptr_out Output
+        %main = OpFunction %void None %fn
+        %entry = OpLabel
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let entry_points = ReflectConfig::new()
+        .spv(spv)
+        .ref_all_rscs(true)
+        .reflect()
+        .unwrap();
+    let entry_point = &entry_points[0];
+
+    let glsl = generate_stubs(entry_point, StubLanguage::Glsl).unwrap();
+    assert_eq!(
+        glsl,
+        r#"// Interface of entry point `main` (Fragment).
+#version 460
+layout(set = 0, binding = 0) uniform Params {
+    layout(offset = 0) vec4 tint;
+    layout(offset = 16) mat4 xform;
+} params;
+layout(set = 0, binding = 1) uniform sampler2D tex;
+layout(location = 0) in vec2 uv;
+layout(location = 0) out vec4 color;
+"#
+    );
+
+    let hlsl = generate_stubs(entry_point, StubLanguage::Hlsl).unwrap();
+    assert_eq!(
+        hlsl,
+        r#"// Interface of entry point `main` (Fragment).
+[[vk::binding(0, 0)]]
+cbuffer Params {
+    float4 tint : packoffset(c0.x);
+    row_major float4x4 xform : packoffset(c1.x);
+};
+[[vk::binding(1, 0)]] [[vk::combinedImageSampler]] Texture2D<float4> tex;
+[[vk::binding(1, 0)]] [[vk::combinedImageSampler]] SamplerState tex_sampler;
+struct StageInput {
+    [[vk::location(0)]] float2 uv : TEXCOORD0;
+};
+struct StageOutput {
+    [[vk::location(0)]] float4 color : SV_Target0;
+};
+"#
+    );
+}
+#[test]
+fn test_stubs_unique_names() {
+    use crate::stub::{generate_stubs, StubLanguage};
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint Fragment %main "main" %out
+        OpExecutionMode %main OriginUpperLeft
+        OpName %Data "Data"
+        OpMemberName %Data 0 "x"
+        OpName %a "data"
+        OpName %b "data"
+        OpName %out "Data"
+        OpDecorate %Data Block
+        OpMemberDecorate %Data 0 Offset 0
+        OpDecorate %a DescriptorSet 0
+        OpDecorate %a Binding 0
+        OpDecorate %b DescriptorSet 0
+        OpDecorate %b Binding 1
+        OpDecorate %out Location 0
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %float = OpTypeFloat 32
+        %v4float = OpTypeVector %float 4
+        %Data = OpTypeStruct %v4float
+        %ptr_Data = OpTypePointer Uniform %Data
+        %a = OpVariable %ptr_Data Uniform
+        %b = OpVariable %ptr_Data Uniform
+        %ptr_out = OpTypePointer Output %v4float
+        %out = OpVariable %ptr_out Output
+        %main = OpFunction %void None %fn
+        %entry = OpLabel
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let entry_points = ReflectConfig::new()
+        .spv(spv)
+        .ref_all_rscs(true)
+        .reflect()
+        .unwrap();
+    let glsl = generate_stubs(&entry_points[0], StubLanguage::Glsl).unwrap();
+    assert_eq!(
+        glsl,
+        r#"// Interface of entry point `main` (Fragment).
+#version 460
+layout(set = 0, binding = 0) uniform Data {
+    layout(offset = 0) vec4 x;
+} data;
+layout(set = 0, binding = 1) uniform Data_1 {
+    layout(offset = 0) vec4 x;
+} data_1;
+layout(location = 0) out vec4 Data_2;
+"#
+    );
+}
+
+#[test]
+fn test_unknown_exec_mode() {
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint GLCompute %main "main"
+        OpExecutionMode %main LocalSize 4 2 1
+        OpExecutionMode %main 65535 7 8
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %main = OpFunction %void None %fn
+        %entry = OpLabel
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let entry_points = ReflectConfig::new().spv(spv).reflect().unwrap();
+    let entry_point = &entry_points[0];
+    assert_eq!(entry_point.exec_modes.len(), 1);
+    assert_eq!(
+        entry_point.exec_modes[0].exec_mode,
+        spirv::ExecutionMode::LocalSize
+    );
+    assert_eq!(entry_point.raw_exec_modes.len(), 2);
+    let raw = &entry_point.raw_exec_modes[1];
+    assert_eq!(raw.exec_mode, 65535);
+    assert_eq!(raw.operands, [7, 8]);
+    assert!(!raw.is_id);
+    assert_eq!(raw.known_exec_mode(), None);
+    assert_eq!(
+        entry_point.raw_exec_modes[0].known_exec_mode(),
+        Some(spirv::ExecutionMode::LocalSize)
+    );
+}
+
+#[test]
+fn test_shared_callee_access() {
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint GLCompute %main_a "main_a"
+        OpEntryPoint GLCompute %main_b "main_b"
+        OpExecutionMode %main_a LocalSize 1 1 1
+        OpExecutionMode %main_b LocalSize 1 1 1
+        OpDecorate %Buf Block
+        OpMemberDecorate %Buf 0 Offset 0
+        OpDecorate %x DescriptorSet 0
+        OpDecorate %x Binding 0
+        OpDecorate %y DescriptorSet 0
+        OpDecorate %y Binding 1
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %uint = OpTypeInt 32 0
+        %uint_0 = OpConstant %uint 0
+        %Buf = OpTypeStruct %uint
+        %ptr_Buf = OpTypePointer Uniform %Buf
+        %ptr_uint = OpTypePointer Uniform %uint
+        %x = OpVariable %ptr_Buf Uniform
+        %y = OpVariable %ptr_Buf Uniform
+
+        %leaf = OpFunction %void None %fn
+        %leaf_entry = OpLabel
+        %x_0 = OpAccessChain %ptr_uint %x %uint_0
+        %x_val = OpLoad %uint %x_0
+        OpReturn
+        OpFunctionEnd
+
+        %mid = OpFunction %void None %fn
+        %mid_entry = OpLabel
+        %mid_0 = OpFunctionCall %void %leaf
+        %mid_1 = OpFunctionCall %void %leaf
+        OpReturn
+        OpFunctionEnd
+
+        %top = OpFunction %void None %fn
+        %top_entry = OpLabel
+        %top_0 = OpFunctionCall %void %mid
+        %top_1 = OpFunctionCall %void %mid
+        OpReturn
+        OpFunctionEnd
+
+        %main_a = OpFunction %void None %fn
+        %main_a_entry = OpLabel
+        %main_a_0 = OpFunctionCall %void %top
+        OpReturn
+        OpFunctionEnd
+
+        %main_b = OpFunction %void None %fn
+        %main_b_entry = OpLabel
+        %y_0 = OpAccessChain %ptr_uint %y %uint_0
+        %y_val = OpLoad %uint %y_0
+        %main_b_0 = OpFunctionCall %void %mid
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let entry_points = ReflectConfig::new().spv(spv).reflect().unwrap();
+    let binds = |name: &str| {
+        let entry_point = entry_points.iter().find(|x| x.name == name).unwrap();
+        let mut binds = entry_point
+            .vars
+            .iter()
+            .filter_map(|x| match x {
+                Variable::Descriptor { desc_bind, .. } => Some(desc_bind.bind()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        binds.sort();
+        binds
+    };
+    assert_eq!(binds("main_a"), [0]);
+    assert_eq!(binds("main_b"), [0, 1]);
+}
+
+#[test]
+fn test_cooperative_matrix() {
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpCapability VulkanMemoryModel
+        OpCapability CooperativeMatrixKHR
+        OpExtension "SPV_KHR_cooperative_matrix"
+        OpExtension "SPV_KHR_vulkan_memory_model"
+        OpMemoryModel Logical Vulkan
+        OpEntryPoint GLCompute %main "main" %buf
+        OpExecutionMode %main LocalSize 32 1 1
+        OpDecorate %arr ArrayStride 4
+        OpDecorate %block Block
+        OpMemberDecorate %block 0 Offset 0
+        OpDecorate %buf DescriptorSet 0
+        OpDecorate %buf Binding 0
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %float = OpTypeFloat 32
+        %uint = OpTypeInt 32 0
+        %uint_0 = OpConstant %uint 0
+        %uint_3 = OpConstant %uint 3
+        %uint_16 = OpConstant %uint 16
+        %arr = OpTypeRuntimeArray %float
+        %block = OpTypeStruct %arr
+        %ptr_block = OpTypePointer StorageBuffer %block
+        %ptr_float = OpTypePointer StorageBuffer %float
+        %buf = OpVariable %ptr_block StorageBuffer
+        %mat = OpTypeCooperativeMatrixKHR %float %uint_3 %uint_16 %uint_16 %uint_0
+        %main = OpFunction %void None %fn
+        %entry = OpLabel
+        %ptr = OpAccessChain %ptr_float %buf %uint_0 %uint_0
+        %x = OpCooperativeMatrixLoadKHR %mat %ptr %uint_0 %uint_16
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let reflection = ReflectConfig::new().spv(spv).reflect_full().unwrap();
+    assert_eq!(reflection.entry_points.len(), 1);
+    let vars = &reflection.entry_points[0].vars;
+    assert!(vars.iter().any(|x| matches!(
+        x,
+        Variable::Descriptor {
+            desc_ty: DescriptorType::StorageBuffer(_),
+            ..
+        }
+    )));
+    let coop_mat_tys = reflection.coop_mat_tys.values().collect::<Vec<_>>();
+    assert_eq!(
+        coop_mat_tys,
+        [&CooperativeMatrixType {
+            component_ty: ty::ScalarType::f32(),
+            scope: spirv::Scope::Subgroup,
+            nrow: 16,
+            ncolumn: 16,
+            usage: spirv::CooperativeMatrixUse::MatrixAKHR,
+        }]
+    );
+}
+
+#[test]
+fn test_cooperative_matrix_nested() {
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpCapability VulkanMemoryModel
+        OpCapability CooperativeMatrixKHR
+        OpExtension "SPV_KHR_cooperative_matrix"
+        OpExtension "SPV_KHR_vulkan_memory_model"
+        OpMemoryModel Logical Vulkan
+        OpEntryPoint GLCompute %main "main" %accs
+        OpExecutionMode %main LocalSize 32 1 1
+        OpName %accs "accs"
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %float = OpTypeFloat 32
+        %uint = OpTypeInt 32 0
+        %uint_2 = OpConstant %uint 2
+        %uint_3 = OpConstant %uint 3
+        %uint_8 = OpConstant %uint 8
+        %uint_16 = OpConstant %uint 16
+        %mat = OpTypeCooperativeMatrixKHR %float %uint_3 %uint_16 %uint_8 %uint_2
+        %mat_arr = OpTypeArray %mat %uint_2
+        %Accs = OpTypeStruct %mat_arr %mat
+        %Accs_ptr = OpTypePointer Private %Accs
+        %accs = OpVariable %Accs_ptr Private
+        %main = OpFunction %void None %fn
+        %entry = OpLabel
+        %accs_val = OpLoad %Accs %accs
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let reflection = ReflectConfig::new()
+        .spv(spv)
+        .private_vars(true)
+        .reflect_full()
+        .unwrap();
+    let coop_mat_tys = reflection.coop_mat_tys.values().collect::<Vec<_>>();
+    assert_eq!(
+        coop_mat_tys,
+        [&CooperativeMatrixType {
+            component_ty: ty::ScalarType::f32(),
+            scope: spirv::Scope::Subgroup,
+            nrow: 16,
+            ncolumn: 8,
+            usage: spirv::CooperativeMatrixUse::MatrixAccumulatorKHR,
+        }]
+    );
+    // Cooperative matrices are never laid out like matrices.
+    assert!(reflection
+        .tys
+        .values()
+        .all(|x| !matches!(**x, Type::Matrix(_))));
+    assert!(reflection.entry_points[0].private_vars.is_empty());
+}
+
+#[test]
+fn test_ray_query() {
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpCapability RayQueryKHR
+        OpExtension "SPV_KHR_ray_query"
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint GLCompute %main "main"
+        OpExecutionMode %main LocalSize 1 1 1
+        OpDecorate %acc DescriptorSet 0
+        OpDecorate %acc Binding 0
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %uint = OpTypeInt 32 0
+        %float = OpTypeFloat 32
+        %v3float = OpTypeVector %float 3
+        %uint_0 = OpConstant %uint 0
+        %uint_255 = OpConstant %uint 255
+        %float_0 = OpConstant %float 0
+        %float_1 = OpConstant %float 1
+        %origin = OpConstantComposite %v3float %float_0 %float_0 %float_0
+        %dir = OpConstantComposite %v3float %float_0 %float_0 %float_1
+        %accel = OpTypeAccelerationStructureKHR
+        %ptr_accel = OpTypePointer UniformConstant %accel
+        %acc = OpVariable %ptr_accel UniformConstant
+        %ray_query = OpTypeRayQueryKHR
+        %ptr_ray_query = OpTypePointer Function %ray_query
+        %main = OpFunction %void None %fn
+        %entry = OpLabel
+        %rq = OpVariable %ptr_ray_query Function
+        %acc_val = OpLoad %accel %acc
+        OpRayQueryInitializeKHR %rq %acc_val %uint_0 %uint_255 %origin %float_0 %dir %float_1
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let entry_points = ReflectConfig::new().spv(spv).reflect().unwrap();
+    assert_eq!(entry_points.len(), 1);
+    let vars = &entry_points[0].vars;
+    assert_eq!(vars.len(), 1);
+    assert!(matches!(
+        &vars[0],
+        Variable::Descriptor {
+            desc_ty: DescriptorType::AccelStruct(),
+            ty: Type::AccelStruct(_),
+            ..
+        }
+    ));
+}
+
+#[test]
+fn test_workgroup_size_builtin() {
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint GLCompute %main "main"
+        OpExecutionMode %main LocalSize 1 1 1
+        OpDecorate %x SpecId 0
+        OpDecorate %workgroup_size BuiltIn WorkgroupSize
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %uint = OpTypeInt 32 0
+        %v3uint = OpTypeVector %uint 3
+        %x = OpSpecConstant %uint 8
+        %uint_4 = OpConstant %uint 4
+        %uint_1 = OpConstant %uint 1
+        %workgroup_size = OpSpecConstantComposite %v3uint %x %uint_4 %uint_1
+        %main = OpFunction %void None %fn
+        %entry = OpLabel
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let local_size = |cfg: &mut ReflectConfig| {
+        let entry_points = cfg.spv(spv).reflect().unwrap();
+        let exec_mode = &entry_points[0].exec_modes[0];
+        assert_eq!(exec_mode.exec_mode, spirv::ExecutionMode::LocalSize);
+        exec_mode
+            .operands
+            .iter()
+            .map(|x| (x.value.clone(), x.spec_id))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        local_size(&mut ReflectConfig::new()),
+        [
+            (ConstantValue::U32(8), Some(0)),
+            (ConstantValue::U32(4), None),
+            (ConstantValue::U32(1), None),
+        ]
+    );
+    assert_eq!(
+        local_size(ReflectConfig::new().specialize(0, ConstantValue::U32(32))),
+        [
+            (ConstantValue::U32(32), None),
+            (ConstantValue::U32(4), None),
+            (ConstantValue::U32(1), None),
+        ]
+    );
+}
+
+#[test]
+fn test_layout_description() {
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpCapability RuntimeDescriptorArray
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint Vertex %vert "vert"
+        OpEntryPoint Fragment %frag "frag"
+        OpExecutionMode %frag OriginUpperLeft
+        OpName %ubo "ubo"
+        OpName %texs "texs"
+        OpName %data "data"
+        OpDecorate %Ubo Block
+        OpMemberDecorate %Ubo 0 Offset 0
+        OpDecorate %Data Block
+        OpMemberDecorate %Data 0 Offset 0
+        OpMemberDecorate %Data 0 NonWritable
+        OpDecorate %ubo DescriptorSet 0
+        OpDecorate %ubo Binding 0
+        OpDecorate %texs DescriptorSet 0
+        OpDecorate %texs Binding 1
+        OpDecorate %data DescriptorSet 1
+        OpDecorate %data Binding 0
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %float = OpTypeFloat 32
+        %Ubo = OpTypeStruct %float
+        %ptr_ubo = OpTypePointer Uniform %Ubo
+        %ubo = OpVariable %ptr_ubo Uniform
+        %img = OpTypeImage %float 2D 0 0 0 1 Unknown
+        %sampled_img = OpTypeSampledImage %img
+        %texs_arr = OpTypeRuntimeArray %sampled_img
+        %ptr_texs = OpTypePointer UniformConstant %texs_arr
+        %texs = OpVariable %ptr_texs UniformConstant
+        %Data = OpTypeStruct %float
+        %ptr_data = OpTypePointer StorageBuffer %Data
+        %data = OpVariable %ptr_data StorageBuffer
+        %vert = OpFunction %void None %fn
+        %vert_entry = OpLabel
+        %ubo_val = OpLoad %Ubo %ubo
+        OpReturn
+        OpFunctionEnd
+        %frag = OpFunction %void None %fn
+        %frag_entry = OpLabel
+        %ubo_val2 = OpLoad %Ubo %ubo
+        %texs_val = OpLoad %texs_arr %texs
+        %data_val = OpLoad %Data %data
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let entry_points = ReflectConfig::new().spv(spv).reflect().unwrap();
+    let layout_desc = LayoutDescription::from_entry_points(&entry_points).unwrap();
+    assert_eq!(
+        layout_desc.to_yaml(),
+        r#"version: 1
+sets:
+  - set: 0
+    bindings:
+      - binding: 0
+        name: "ubo"
+        type: uniform_buffer
+        count: 1
+        stages: [vertex, fragment]
+        flags: []
+      - binding: 1
+        name: "texs"
+        type: combined_image_sampler
+        count: 0
+        stages: [fragment]
+        flags: [variable_count]
+  - set: 1
+    bindings:
+      - binding: 0
+        name: "data"
+        type: storage_buffer
+        count: 1
+        stages: [fragment]
+        flags: [non_writable]
+"#
+    );
+    assert_eq!(
+        LayoutDescription::default().to_yaml(),
+        "version: 1\nsets: []\n"
+    );
+}
+#[test]
+fn test_block_layout() {
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint GLCompute %main "main"
+        OpExecutionMode %main LocalSize 1 1 1
+        OpDecorate %Data Block
+        OpMemberDecorate %Data 0 Offset 0
+        OpMemberDecorate %Data 1 Offset 16
+        OpMemberDecorate %Data 2 ColMajor
+        OpMemberDecorate %Data 2 Offset 32
+        OpMemberDecorate %Data 2 MatrixStride 16
+        OpMemberDecorate %Data 3 Offset 80
+        OpMemberDecorate %Data 4 Offset 88
+        OpMemberDecorate %Data 5 Offset 96
+        OpMemberDecorate %Inner 0 Offset 0
+        OpDecorate %_arr_float_2 ArrayStride 4
+        OpDecorate %_rtarr_float ArrayStride 4
+        OpDecorate %data DescriptorSet 0
+        OpDecorate %data Binding 0
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %float = OpTypeFloat 32
+        %v2float = OpTypeVector %float 2
+        %v3float = OpTypeVector %float 3
+        %mat3 = OpTypeMatrix %v3float 3
+        %uint = OpTypeInt 32 0
+        %uint_2 = OpConstant %uint 2
+        %_arr_float_2 = OpTypeArray %float %uint_2
+        %_rtarr_float = OpTypeRuntimeArray %float
+        %Inner = OpTypeStruct %v2float
+        %Data = OpTypeStruct %float %v3float %mat3 %_arr_float_2 %Inner %_rtarr_float
+        %data_ptr = OpTypePointer StorageBuffer %Data
+        %data = OpVariable %data_ptr StorageBuffer
+        %main = OpFunction %void None %fn
+        %entry = OpLabel
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let entry_points = ReflectConfig::new()
+        .spv(spv)
+        .ref_all_rscs(true)
+        .reflect()
+        .unwrap();
+    let ty = match &entry_points[0].vars[0] {
+        Variable::Descriptor { ty, .. } => ty,
+        x => panic!("unexpected {:?}", x),
+    };
+    let struct_ty = ty.as_struct().unwrap();
+    let mat_ty = &struct_ty.members[2].ty;
+    let arr_ty = &struct_ty.members[3].ty;
+    let inner_ty = &struct_ty.members[4].ty;
+
+    assert_eq!(mat_ty.block_nbyte(BlockLayout::Std140), Some(48));
+    assert_eq!(arr_ty.block_nbyte(BlockLayout::Std140), Some(32));
+    assert_eq!(inner_ty.block_align(BlockLayout::Std140), Some(16));
+    assert_eq!(ty.block_nbyte(BlockLayout::Std140), Some(128));
+    assert_eq!(ty.block_min_nbyte(BlockLayout::Std140), Some(144));
+    assert_eq!(ty.block_align(BlockLayout::Std140), Some(16));
+
+    assert_eq!(mat_ty.block_nbyte(BlockLayout::Std430), Some(48));
+    assert_eq!(arr_ty.block_nbyte(BlockLayout::Std430), Some(8));
+    assert_eq!(inner_ty.block_align(BlockLayout::Std430), Some(8));
+    assert_eq!(ty.block_nbyte(BlockLayout::Std430), Some(96));
+    assert_eq!(ty.block_min_nbyte(BlockLayout::Std430), Some(112));
+    assert_eq!(ty.block_align(BlockLayout::Std430), Some(16));
+
+    assert_eq!(mat_ty.block_nbyte(BlockLayout::Scalar), Some(36));
+    assert_eq!(mat_ty.block_align(BlockLayout::Scalar), Some(4));
+    assert_eq!(ty.block_nbyte(BlockLayout::Scalar), Some(68));
+    assert_eq!(ty.block_min_nbyte(BlockLayout::Scalar), Some(72));
+    assert_eq!(ty.block_align(BlockLayout::Scalar), Some(4));
+
+    let hole = |offset, nbyte, member_idx| PaddingHole {
+        offset,
+        nbyte,
+        member_idx,
+    };
+    assert_eq!(
+        padding_holes(struct_ty, BlockLayout::Std430).unwrap(),
+        vec![hole(4, 12, Some(1)), hole(28, 4, Some(2))]
+    );
+    let inner_ty = inner_ty.as_struct().unwrap();
+    assert_eq!(
+        padding_holes(inner_ty, BlockLayout::Std140).unwrap(),
+        vec![hole(8, 8, None)]
+    );
+    assert!(padding_holes(inner_ty, BlockLayout::Std430)
+        .unwrap()
+        .is_empty());
+}
+#[test]
+fn test_texel_buffer_fmts() {
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpCapability SampledBuffer
+        OpCapability ImageBuffer
+        OpCapability StorageImageWriteWithoutFormat
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint GLCompute %main "main"
+        OpExecutionMode %main LocalSize 1 1 1
+        OpDecorate %utb DescriptorSet 0
+        OpDecorate %utb Binding 0
+        OpDecorate %stb_r DescriptorSet 0
+        OpDecorate %stb_r Binding 1
+        OpDecorate %stb_r NonWritable
+        OpDecorate %stb_w DescriptorSet 0
+        OpDecorate %stb_w Binding 2
+        OpDecorate %stb_w NonReadable
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %float = OpTypeFloat 32
+        %uint = OpTypeInt 32 0
+        %uint_4 = OpConstant %uint 4
+        %utb_img = OpTypeImage %float Buffer 0 0 0 1 Unknown
+        %utb_ty = OpTypeSampledImage %utb_img
+        %_arr_utb = OpTypeArray %utb_ty %uint_4
+        %utb_ptr = OpTypePointer UniformConstant %_arr_utb
+        %utb = OpVariable %utb_ptr UniformConstant
+        %stb_r_ty = OpTypeImage %float Buffer 0 0 0 2 R32f
+        %stb_r_ptr = OpTypePointer UniformConstant %stb_r_ty
+        %stb_r = OpVariable %stb_r_ptr UniformConstant
+        %stb_w_ty = OpTypeImage %float Buffer 0 0 0 2 Unknown
+        %stb_w_ptr = OpTypePointer UniformConstant %stb_w_ty
+        %stb_w = OpVariable %stb_w_ptr UniformConstant
+        %main = OpFunction %void None %fn
+        %entry = OpLabel
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let entry_points = ReflectConfig::new()
+        .spv(spv)
+        .ref_all_rscs(true)
+        .reflect()
+        .unwrap();
+    let entry = &entry_points[0];
+    let texel_buffer = |bind: u32| {
+        let desc_bind = DescriptorBinding::new(0, bind);
+        entry.desc_infos[&desc_bind].texel_buffer.unwrap()
+    };
+
+    let utb = texel_buffer(0);
+    assert_eq!(utb.fmt, spirv::ImageFormat::Unknown);
+    assert_eq!(utb.access, AccessType::ReadOnly);
+    assert!(!utb.is_storage);
+    assert!(!utb.requires_read_without_fmt());
+
+    let stb_r = texel_buffer(1);
+    assert_eq!(stb_r.fmt, spirv::ImageFormat::R32f);
+    assert_eq!(stb_r.access, AccessType::ReadOnly);
+    assert!(stb_r.is_storage);
+    assert!(!stb_r.requires_read_without_fmt());
+
+    let stb_w = texel_buffer(2);
+    assert_eq!(stb_w.fmt, spirv::ImageFormat::Unknown);
+    assert_eq!(stb_w.access, AccessType::WriteOnly);
+    assert!(!stb_w.requires_read_without_fmt());
+    assert!(stb_w.requires_write_without_fmt());
+}
+#[test]
+fn test_nonuniform_indexed_descs() {
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpCapability ShaderNonUniform
+        OpCapability RuntimeDescriptorArray
+        OpCapability SampledImageArrayNonUniformIndexing
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint GLCompute %main "main"
+        OpExecutionMode %main LocalSize 1 1 1
+        OpDecorate %texs DescriptorSet 0
+        OpDecorate %texs Binding 0
+        OpDecorate %imgs DescriptorSet 0
+        OpDecorate %imgs Binding 1
+        OpDecorate %uniform_texs DescriptorSet 0
+        OpDecorate %uniform_texs Binding 2
+        OpDecorate %Data Block
+        OpMemberDecorate %Data 0 Offset 0
+        OpDecorate %float_arr ArrayStride 4
+        OpDecorate %data DescriptorSet 0
+        OpDecorate %data Binding 3
+        OpDecorate %idx NonUniform
+        OpDecorate %img NonUniform
+        OpDecorate %data_elem_ptr NonUniform
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %float = OpTypeFloat 32
+        %uint = OpTypeInt 32 0
+        %uint_0 = OpConstant %uint 0
+        %uint_1 = OpConstant %uint 1
+        %uint_4 = OpConstant %uint 4
+        %uint_var_ptr = OpTypePointer Function %uint
+        %img_ty = OpTypeImage %float 2D 0 0 0 1 Unknown
+        %tex_ty = OpTypeSampledImage %img_ty
+        %tex_arr = OpTypeArray %tex_ty %uint_4
+        %tex_arr_ptr = OpTypePointer UniformConstant %tex_arr
+        %tex_ptr = OpTypePointer UniformConstant %tex_ty
+        %texs = OpVariable %tex_arr_ptr UniformConstant
+        %uniform_texs = OpVariable %tex_arr_ptr UniformConstant
+        %img_arr = OpTypeRuntimeArray %img_ty
+        %img_arr_ptr = OpTypePointer UniformConstant %img_arr
+        %img_ptr = OpTypePointer UniformConstant %img_ty
+        %imgs = OpVariable %img_arr_ptr UniformConstant
+        %float_arr = OpTypeRuntimeArray %float
+        %Data = OpTypeStruct %float_arr
+        %Data_ptr = OpTypePointer StorageBuffer %Data
+        %float_ptr = OpTypePointer StorageBuffer %float
+        %data = OpVariable %Data_ptr StorageBuffer
+        %main = OpFunction %void None %fn
+        %entry = OpLabel
+        %i = OpVariable %uint_var_ptr Function
+        %i_val = OpLoad %uint %i
+        %idx = OpCopyObject %uint %i_val
+        %tex_elem_ptr = OpAccessChain %tex_ptr %texs %idx
+        %tex = OpLoad %tex_ty %tex_elem_ptr
+        %img_elem_ptr = OpAccessChain %img_ptr %imgs %i_val
+        %img = OpLoad %img_ty %img_elem_ptr
+        %uniform_tex_elem_ptr = OpAccessChain %tex_ptr %uniform_texs %uint_1
+        %uniform_tex = OpLoad %tex_ty %uniform_tex_elem_ptr
+        %data_elem_ptr = OpAccessChain %float_ptr %data %uint_0 %idx
+        %data_elem = OpLoad %float %data_elem_ptr
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let entry_points = ReflectConfig::new().spv(spv).reflect().unwrap();
+    let entry = &entry_points[0];
+    let is_nonuniform_indexed = |bind: u32| {
+        let desc_bind = DescriptorBinding::new(0, bind);
+        entry.desc_infos[&desc_bind].is_nonuniform_indexed
+    };
+    assert!(is_nonuniform_indexed(0));
+    assert!(is_nonuniform_indexed(1));
+    assert!(!is_nonuniform_indexed(2));
+    // Non-uniform indices into the members of a single buffer don't count.
+    assert!(!is_nonuniform_indexed(3));
+}
+#[test]
+fn test_min_lod_descs() {
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpCapability MinLod
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint Fragment %main "main"
+        OpExecutionMode %main OriginUpperLeft
+        OpDecorate %tex DescriptorSet 0
+        OpDecorate %tex Binding 0
+        OpDecorate %img DescriptorSet 0
+        OpDecorate %img Binding 1
+        OpDecorate %samp DescriptorSet 0
+        OpDecorate %samp Binding 2
+        OpDecorate %lod_tex DescriptorSet 0
+        OpDecorate %lod_tex Binding 3
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %float = OpTypeFloat 32
+        %float_0 = OpConstant %float 0
+        %float_1 = OpConstant %float 1
+        %v2float = OpTypeVector %float 2
+        %v4float = OpTypeVector %float 4
+        %coord = OpConstantComposite %v2float %float_0 %float_0
+        %img_ty = OpTypeImage %float 2D 0 0 0 1 Unknown
+        %tex_ty = OpTypeSampledImage %img_ty
+        %samp_ty = OpTypeSampler
+        %tex_ptr = OpTypePointer UniformConstant %tex_ty
+        %img_ptr = OpTypePointer UniformConstant %img_ty
+        %samp_ptr = OpTypePointer UniformConstant %samp_ty
+        %tex = OpVariable %tex_ptr UniformConstant
+        %img = OpVariable %img_ptr UniformConstant
+        %samp = OpVariable %samp_ptr UniformConstant
+        %lod_tex = OpVariable %tex_ptr UniformConstant
+        %main = OpFunction %void None %fn
+        %entry = OpLabel
+        %tex_val = OpLoad %tex_ty %tex
+        %texel0 = OpImageSampleImplicitLod %v4float %tex_val %coord MinLod %float_1
+        %img_val = OpLoad %img_ty %img
+        %samp_val = OpLoad %samp_ty %samp
+        %sampled_img = OpSampledImage %tex_ty %img_val %samp_val
+        %texel1 = OpImageSampleImplicitLod %v4float %sampled_img %coord MinLod %float_1
+        %lod_tex_val = OpLoad %tex_ty %lod_tex
+        %texel2 = OpImageSampleExplicitLod %v4float %lod_tex_val %coord Lod %float_1
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let entry_points = ReflectConfig::new().spv(spv).reflect().unwrap();
+    let entry = &entry_points[0];
+    let uses_min_lod = |bind: u32| {
+        let desc_bind = DescriptorBinding::new(0, bind);
+        entry.desc_infos[&desc_bind].uses_min_lod
+    };
+    assert!(uses_min_lod(0));
+    assert!(uses_min_lod(1));
+    assert!(!uses_min_lod(2));
+    // Explicit LODs don't require `shaderResourceMinLod`.
+    assert!(!uses_min_lod(3));
+}
+#[test]
+fn test_sparse_residency_descs() {
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpCapability SparseResidency
+        OpCapability MinLod
+        OpCapability StorageImageReadWithoutFormat
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint Fragment %main "main"
+        OpExecutionMode %main OriginUpperLeft
+        OpDecorate %tex DescriptorSet 0
+        OpDecorate %tex Binding 0
+        OpDecorate %img DescriptorSet 0
+        OpDecorate %img Binding 1
+        OpDecorate %dense_tex DescriptorSet 0
+        OpDecorate %dense_tex Binding 2
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %int = OpTypeInt 32 1
+        %int_0 = OpConstant %int 0
+        %float = OpTypeFloat 32
+        %float_0 = OpConstant %float 0
+        %float_1 = OpConstant %float 1
+        %v2int = OpTypeVector %int 2
+        %v2float = OpTypeVector %float 2
+        %v4float = OpTypeVector %float 4
+        %residency = OpTypeStruct %int %v4float
+        %coord = OpConstantComposite %v2float %float_0 %float_0
+        %icoord = OpConstantComposite %v2int %int_0 %int_0
+        %img_ty = OpTypeImage %float 2D 0 0 0 1 Unknown
+        %tex_ty = OpTypeSampledImage %img_ty
+        %storage_img_ty = OpTypeImage %float 2D 0 0 0 2 Unknown
+        %tex_ptr = OpTypePointer UniformConstant %tex_ty
+        %storage_img_ptr = OpTypePointer UniformConstant %storage_img_ty
+        %tex = OpVariable %tex_ptr UniformConstant
+        %img = OpVariable %storage_img_ptr UniformConstant
+        %dense_tex = OpVariable %tex_ptr UniformConstant
+        %main = OpFunction %void None %fn
+        %entry = OpLabel
+        %tex_val = OpLoad %tex_ty %tex
+        %texel0 = OpImageSparseSampleImplicitLod %residency %tex_val %coord MinLod %float_1
+        %img_val = OpLoad %storage_img_ty %img
+        %texel1 = OpImageSparseRead %residency %img_val %icoord
+        %dense_tex_val = OpLoad %tex_ty %dense_tex
+        %texel2 = OpImageSampleImplicitLod %v4float %dense_tex_val %coord
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let entry_points = ReflectConfig::new().spv(spv).reflect().unwrap();
+    let entry = &entry_points[0];
+    let desc_info = |bind: u32| &entry.desc_infos[&DescriptorBinding::new(0, bind)];
+    assert!(desc_info(0).uses_sparse_residency);
+    assert_eq!(
+        desc_info(0).required_caps(),
+        vec![
+            spirv::Capability::MinLod,
+            spirv::Capability::SparseResidency
+        ]
+    );
+    assert!(desc_info(1).uses_sparse_residency);
+    assert!(!desc_info(1).uses_min_lod);
+    assert!(!desc_info(2).uses_sparse_residency);
+    assert!(desc_info(2).required_caps().is_empty());
+}
+#[test]
+fn test_image_op_flags_per_entry_point() {
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpCapability SparseResidency
+        OpCapability MinLod
+        OpCapability ShaderNonUniform
+        OpCapability SampledImageArrayNonUniformIndexing
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint Fragment %a "a"
+        OpEntryPoint Fragment %b "b"
+        OpExecutionMode %a OriginUpperLeft
+        OpExecutionMode %b OriginUpperLeft
+        OpDecorate %texs DescriptorSet 0
+        OpDecorate %texs Binding 0
+        OpDecorate %idx NonUniform
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %int = OpTypeInt 32 1
+        %uint = OpTypeInt 32 0
+        %uint_1 = OpConstant %uint 1
+        %uint_4 = OpConstant %uint 4
+        %float = OpTypeFloat 32
+        %float_0 = OpConstant %float 0
+        %float_1 = OpConstant %float 1
+        %v2float = OpTypeVector %float 2
+        %v4float = OpTypeVector %float 4
+        %residency = OpTypeStruct %int %v4float
+        %coord = OpConstantComposite %v2float %float_0 %float_0
+        %uint_var_ptr = OpTypePointer Function %uint
+        %img_ty = OpTypeImage %float 2D 0 0 0 1 Unknown
+        %tex_ty = OpTypeSampledImage %img_ty
+        %tex_arr = OpTypeArray %tex_ty %uint_4
+        %tex_arr_ptr = OpTypePointer UniformConstant %tex_arr
+        %tex_ptr = OpTypePointer UniformConstant %tex_ty
+        %texs = OpVariable %tex_arr_ptr UniformConstant
+        %a = OpFunction %void None %fn
+        %a_entry = OpLabel
+        %call = OpFunctionCall %void %sample
+        OpReturn
+        OpFunctionEnd
+        %sample = OpFunction %void None %fn
+        %sample_entry = OpLabel
+        %i = OpVariable %uint_var_ptr Function
+        %i_val = OpLoad %uint %i
+        %idx = OpCopyObject %uint %i_val
+        %tex_elem_ptr = OpAccessChain %tex_ptr %texs %idx
+        %tex = OpLoad %tex_ty %tex_elem_ptr
+        %texel0 = OpImageSparseSampleImplicitLod %residency %tex %coord MinLod %float_1
+        OpReturn
+        OpFunctionEnd
+        %b = OpFunction %void None %fn
+        %b_entry = OpLabel
+        %b_tex_elem_ptr = OpAccessChain %tex_ptr %texs %uint_1
+        %b_tex = OpLoad %tex_ty %b_tex_elem_ptr
+        %texel1 = OpImageSampleImplicitLod %v4float %b_tex %coord
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let entry_points = ReflectConfig::new().spv(spv).reflect().unwrap();
+    let desc_info = |name: &str| {
+        let entry = entry_points.iter().find(|x| x.name == name).unwrap();
+        entry.desc_infos[&DescriptorBinding::new(0, 0)].clone()
+    };
+    let a = desc_info("a");
+    assert!(a.is_nonuniform_indexed);
+    assert!(a.uses_min_lod);
+    assert!(a.uses_sparse_residency);
+    // Accesses made by other entry points don't leak into `b`.
+    let b = desc_info("b");
+    assert!(!b.is_nonuniform_indexed);
+    assert!(!b.uses_min_lod);
+    assert!(!b.uses_sparse_residency);
+}
+#[test]
+fn test_desc_aliases() {
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint GLCompute %main "main"
+        OpExecutionMode %main LocalSize 1 1 1
+        OpName %floats "floats"
+        OpName %uints "uints"
+        OpName %buf "buf"
+        OpName %img "img"
+        OpName %single "single"
+        OpDecorate %Floats Block
+        OpMemberDecorate %Floats 0 Offset 0
+        OpDecorate %Uints Block
+        OpMemberDecorate %Uints 0 Offset 0
+        OpMemberDecorate %Uints 0 NonWritable
+        OpDecorate %floats DescriptorSet 0
+        OpDecorate %floats Binding 0
+        OpDecorate %uints DescriptorSet 0
+        OpDecorate %uints Binding 0
+        OpDecorate %buf DescriptorSet 0
+        OpDecorate %buf Binding 1
+        OpDecorate %img DescriptorSet 0
+        OpDecorate %img Binding 1
+        OpDecorate %single DescriptorSet 0
+        OpDecorate %single Binding 2
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %float = OpTypeFloat 32
+        %uint = OpTypeInt 32 0
+        %Floats = OpTypeStruct %float
+        %Uints = OpTypeStruct %uint
+        %floats_ptr = OpTypePointer StorageBuffer %Floats
+        %uints_ptr = OpTypePointer StorageBuffer %Uints
+        %img_ty = OpTypeImage %float 2D 0 0 0 2 R32f
+        %img_ptr = OpTypePointer UniformConstant %img_ty
+        %floats = OpVariable %floats_ptr StorageBuffer
+        %uints = OpVariable %uints_ptr StorageBuffer
+        %buf = OpVariable %floats_ptr StorageBuffer
+        %img = OpVariable %img_ptr UniformConstant
+        %single = OpVariable %floats_ptr StorageBuffer
+        %main = OpFunction %void None %fn
+        %entry = OpLabel
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let entry_points = ReflectConfig::new()
+        .spv(spv)
+        .ref_all_rscs(true)
+        .reflect()
+        .unwrap();
+    let entry = &entry_points[0];
+    let desc_info = |bind: u32| &entry.desc_infos[&DescriptorBinding::new(0, bind)];
+    assert_eq!(
+        desc_info(0).aliases,
+        vec![Some("floats".to_owned()), Some("uints".to_owned())]
+    );
+    assert!(!desc_info(0).has_aliased_desc_tys);
+    assert_eq!(
+        desc_info(1).aliases,
+        vec![Some("buf".to_owned()), Some("img".to_owned())]
+    );
+    assert!(desc_info(1).has_aliased_desc_tys);
+    assert!(desc_info(2).aliases.is_empty());
+    assert!(!desc_info(2).has_aliased_desc_tys);
+
+    let err = ReflectConfig::new()
+        .spv(spv)
+        .ref_all_rscs(true)
+        .strict_desc_aliasing(true)
+        .reflect()
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "descriptor resources of different types are aliased at (set=0, bind=1)"
+    );
+}
+#[test]
+fn test_desc_static_use() {
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint GLCompute %main "main" %used %listed
+        OpExecutionMode %main LocalSize 1 1 1
+        OpDecorate %Data Block
+        OpMemberDecorate %Data 0 Offset 0
+        OpDecorate %used DescriptorSet 0
+        OpDecorate %used Binding 0
+        OpDecorate %listed DescriptorSet 0
+        OpDecorate %listed Binding 1
+        OpDecorate %unlisted DescriptorSet 0
+        OpDecorate %unlisted Binding 2
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %int = OpTypeInt 32 1
+        %int_0 = OpConstant %int 0
+        %float = OpTypeFloat 32
+        %Data = OpTypeStruct %float
+        %data_ptr = OpTypePointer StorageBuffer %Data
+        %float_ptr = OpTypePointer StorageBuffer %float
+        %used = OpVariable %data_ptr StorageBuffer
+        %listed = OpVariable %data_ptr StorageBuffer
+        %unlisted = OpVariable %data_ptr StorageBuffer
+        %main = OpFunction %void None %fn
+        %entry = OpLabel
+        %ptr = OpAccessChain %float_ptr %used %int_0
+        %x = OpLoad %float %ptr
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let entry_points = ReflectConfig::new()
+        .spv(spv)
+        .ref_all_rscs(true)
+        .track_static_use(true)
+        .reflect()
+        .unwrap();
+    let entry = &entry_points[0];
+    let desc_info = |bind: u32| &entry.desc_infos[&DescriptorBinding::new(0, bind)];
+    assert!(desc_info(0).is_interface_listed);
+    assert!(desc_info(0).is_statically_used);
+    assert!(desc_info(1).is_interface_listed);
+    assert!(!desc_info(1).is_statically_used);
+    assert!(!desc_info(2).is_interface_listed);
+    assert!(!desc_info(2).is_statically_used);
+
+    let entry_points = ReflectConfig::new().spv(spv).reflect().unwrap();
+    assert_eq!(entry_points[0].desc_infos.len(), 1);
+    let entry_points = ReflectConfig::new()
+        .spv(spv)
+        .ref_all_rscs(true)
+        .reflect()
+        .unwrap();
+    let desc_infos = &entry_points[0].desc_infos;
+    assert_eq!(desc_infos.len(), 3);
+    assert!(desc_infos.values().all(|x| x.is_statically_used));
+}
+#[test]
+fn test_ref_all_rscs_without_scan() {
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpCapability MinLod
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint Fragment %main "main" %color %texs %consts %unused
+        OpExecutionMode %main OriginUpperLeft
+        OpDecorate %color Location 0
+        OpDecorate %Consts Block
+        OpMemberDecorate %Consts 0 Offset 0
+        OpMemberDecorate %Consts 1 Offset 4
+        OpDecorate %Data Block
+        OpMemberDecorate %Data 0 Offset 0
+        OpDecorate %texs DescriptorSet 0
+        OpDecorate %texs Binding 0
+        OpDecorate %unused DescriptorSet 0
+        OpDecorate %unused Binding 1
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %int = OpTypeInt 32 1
+        %int_0 = OpConstant %int 0
+        %int_1 = OpConstant %int 1
+        %int_2 = OpConstant %int 2
+        %float = OpTypeFloat 32
+        %float_0 = OpConstant %float 0
+        %v2float = OpTypeVector %float 2
+        %v4float = OpTypeVector %float 4
+        %coord = OpConstantComposite %v2float %float_0 %float_0
+        %img_ty = OpTypeImage %float 2D 0 0 0 1 Unknown
+        %tex_ty = OpTypeSampledImage %img_ty
+        %tex_arr = OpTypeArray %tex_ty %int_2
+        %Consts = OpTypeStruct %float %float
+        %Data = OpTypeStruct %float
+        %in_v4float = OpTypePointer Input %v4float
+        %in_float = OpTypePointer Input %float
+        %tex_ptr = OpTypePointer UniformConstant %tex_ty
+        %tex_arr_ptr = OpTypePointer UniformConstant %tex_arr
+        %consts_ptr = OpTypePointer PushConstant %Consts
+        %pc_float_ptr = OpTypePointer PushConstant %float
+        %data_ptr = OpTypePointer StorageBuffer %Data
+        %color = OpVariable %in_v4float Input
+        %texs = OpVariable %tex_arr_ptr UniformConstant
+        %consts = OpVariable %consts_ptr PushConstant
+        %unused = OpVariable %data_ptr StorageBuffer
+        %main = OpFunction %void None %fn
+        %entry = OpLabel
+        %color_x_ptr = OpAccessChain %in_float %color %int_0
+        %color_x = OpLoad %float %color_x_ptr
+        %lod_ptr = OpAccessChain %pc_float_ptr %consts %int_1
+        %lod = OpLoad %float %lod_ptr
+        %tex_elem_ptr = OpAccessChain %tex_ptr %texs %int_1
+        %tex_val = OpLoad %tex_ty %tex_elem_ptr
+        %texel = OpImageSampleImplicitLod %v4float %tex_val %coord MinLod %lod
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    // Function bodies are skipped, so accesses take their unknown values.
+    let entry_points = ReflectConfig::new()
+        .spv(spv)
+        .ref_all_rscs(true)
+        .reflect()
+        .unwrap();
+    let entry = &entry_points[0];
+    assert_eq!(entry.vars.len(), 4);
+    let tex_info = &entry.desc_infos[&DescriptorBinding::new(0, 0)];
+    assert!(!tex_info.uses_min_lod);
+    assert_eq!(tex_info.used_elements, UsedElements::Unbounded);
+    assert!(entry.desc_infos[&DescriptorBinding::new(0, 1)].is_statically_used);
+    assert_eq!(entry.input_comp_masks.get(&0), Some(&0b1111));
+    assert_eq!(entry.push_const_bytes, Some(0..8));
+
+    let entry_points = ReflectConfig::new()
+        .spv(spv)
+        .ref_all_rscs(true)
+        .track_static_use(true)
+        .reflect()
+        .unwrap();
+    let entry = &entry_points[0];
+    assert_eq!(entry.vars.len(), 4);
+    let tex_info = &entry.desc_infos[&DescriptorBinding::new(0, 0)];
+    assert!(tex_info.uses_min_lod);
+    assert_eq!(tex_info.used_elements, UsedElements::Elements(vec![1]));
+    assert!(!entry.desc_infos[&DescriptorBinding::new(0, 1)].is_statically_used);
+    assert_eq!(entry.input_comp_masks.get(&0), Some(&0b0001));
+    assert_eq!(entry.push_const_bytes, Some(4..8));
+}
+#[test]
+fn test_mixed_version_conventions() {
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint GLCompute %main "main" %buf
+        OpExecutionMode %main LocalSize 1 1 1
+        OpName %buf "buf"
+        OpDecorate %Data Block
+        OpMemberDecorate %Data 0 Offset 0
+        OpDecorate %buf DescriptorSet 0
+        OpDecorate %buf Binding 0
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %int = OpTypeInt 32 1
+        %int_0 = OpConstant %int 0
+        %float = OpTypeFloat 32
+        %Data = OpTypeStruct %float
+        %data_ptr = OpTypePointer StorageBuffer %Data
+        %float_ptr = OpTypePointer StorageBuffer %float
+        %buf = OpVariable %data_ptr StorageBuffer
+        %main = OpFunction %void None %fn
+        %entry = OpLabel
+        %ptr = OpAccessChain %float_ptr %buf %int_0
+        %x = OpLoad %float %ptr
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_0
+    );
+    let entry_points = ReflectConfig::new().spv(spv).reflect().unwrap();
+    let version = SpirvVersion::new(1, 0);
+    assert_eq!(
+        entry_points[0].diags,
+        vec![
+            Diagnostic::MixedVersionConventions {
+                name: Some("buf".to_owned()),
+                version,
+                convention: VersionConvention::ListedGlobal,
+            },
+            Diagnostic::MixedVersionConventions {
+                name: Some("buf".to_owned()),
+                version,
+                convention: VersionConvention::StorageBufferClass,
+            },
+        ]
+    );
+
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint GLCompute %main "main"
+        OpExecutionMode %main LocalSize 1 1 1
+        OpName %buf "buf"
+        OpDecorate %Data Block
+        OpMemberDecorate %Data 0 Offset 0
+        OpDecorate %buf DescriptorSet 0
+        OpDecorate %buf Binding 0
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %int = OpTypeInt 32 1
+        %int_0 = OpConstant %int 0
+        %float = OpTypeFloat 32
+        %Data = OpTypeStruct %float
+        %data_ptr = OpTypePointer StorageBuffer %Data
+        %float_ptr = OpTypePointer StorageBuffer %float
+        %buf = OpVariable %data_ptr StorageBuffer
+        %main = OpFunction %void None %fn
+        %entry = OpLabel
+        %ptr = OpAccessChain %float_ptr %buf %int_0
+        %x = OpLoad %float %ptr
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let entry_points = ReflectConfig::new().spv(spv).reflect().unwrap();
+    assert_eq!(
+        entry_points[0].diags,
+        vec![Diagnostic::MixedVersionConventions {
+            name: Some("buf".to_owned()),
+            version: SpirvVersion::new(1, 5),
+            convention: VersionConvention::UnlistedGlobal,
+        }]
+    );
+    assert_eq!(entry_points[0].diags[0].to_string(), "variable `buf` in a SPIR-V 1.5 module is used but not listed in the entry point interface, as required since SPIR-V 1.4");
+}
+#[test]
+fn test_derivative_requirements() {
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpCapability ComputeDerivativeGroupLinearNV
+        OpExtension "SPV_NV_compute_shader_derivatives"
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint GLCompute %linear "linear" %tex
+        OpEntryPoint GLCompute %quads "quads"
+        OpEntryPoint GLCompute %plain "plain"
+        OpEntryPoint Fragment %frag "frag" %tex
+        OpEntryPoint Vertex %vert "vert"
+        OpExecutionMode %linear LocalSize 4 1 1
+        OpExecutionMode %linear DerivativeGroupLinearNV
+        OpExecutionMode %quads LocalSize 2 2 1
+        OpExecutionMode %plain LocalSize 1 1 1
+        OpExecutionMode %frag OriginUpperLeft
+        OpDecorate %tex DescriptorSet 0
+        OpDecorate %tex Binding 0
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %float = OpTypeFloat 32
+        %float_0 = OpConstant %float 0
+        %v2float = OpTypeVector %float 2
+        %v4float = OpTypeVector %float 4
+        %coord = OpConstantComposite %v2float %float_0 %float_0
+        %img_ty = OpTypeImage %float 2D 0 0 0 1 Unknown
+        %tex_ty = OpTypeSampledImage %img_ty
+        %tex_ptr = OpTypePointer UniformConstant %tex_ty
+        %tex = OpVariable %tex_ptr UniformConstant
+        %sample = OpFunction %void None %fn
+        %sample_entry = OpLabel
+        %tex_val = OpLoad %tex_ty %tex
+        %texel = OpImageSampleImplicitLod %v4float %tex_val %coord
+        OpReturn
+        OpFunctionEnd
+        %linear = OpFunction %void None %fn
+        %linear_entry = OpLabel
+        %0 = OpFunctionCall %void %sample
+        OpReturn
+        OpFunctionEnd
+        %quads = OpFunction %void None %fn
+        %quads_entry = OpLabel
+        %1 = OpDPdx %float %float_0
+        OpReturn
+        OpFunctionEnd
+        %plain = OpFunction %void None %fn
+        %plain_entry = OpLabel
+        OpReturn
+        OpFunctionEnd
+        %frag = OpFunction %void None %fn
+        %frag_entry = OpLabel
+        %2 = OpFunctionCall %void %sample
+        OpReturn
+        OpFunctionEnd
+        %vert = OpFunction %void None %fn
+        %vert_entry = OpLabel
+        %3 = OpFwidth %float %float_0
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let entry_points = ReflectConfig::new().spv(spv).reflect().unwrap();
+    let entry_point = |name: &str| entry_points.iter().find(|x| x.name == name).unwrap();
+
+    let linear = entry_point("linear");
+    assert!(linear.uses_derivatives);
+    let req = linear.derivative_requirements().unwrap();
+    assert_eq!(req.exec_mode, spirv::ExecutionMode::DerivativeGroupLinearNV);
+    assert!(req.is_exec_mode_declared);
+    assert_eq!(
+        req.caps,
+        &[spirv::Capability::ComputeDerivativeGroupLinearNV]
+    );
+    assert_eq!(req.spirv_exts, &["SPV_KHR_compute_shader_derivatives"]);
+
+    let req = entry_point("quads").derivative_requirements().unwrap();
+    assert_eq!(req.exec_mode, spirv::ExecutionMode::DerivativeGroupQuadsNV);
+    assert!(!req.is_exec_mode_declared);
+    assert_eq!(
+        req.caps,
+        &[spirv::Capability::ComputeDerivativeGroupQuadsNV]
+    );
+
+    assert!(!entry_point("plain").uses_derivatives);
+    assert_eq!(entry_point("plain").derivative_requirements(), None);
+
+    let frag = entry_point("frag");
+    assert!(frag.uses_derivatives);
+    assert_eq!(frag.derivative_requirements(), None);
+    assert!(frag.diags.is_empty());
+
+    let vert = entry_point("vert");
+    assert!(vert.uses_derivatives);
+    assert_eq!(vert.derivative_requirements(), None);
+    assert_eq!(
+        vert.diags,
+        vec![Diagnostic::UnsupportedDerivatives {
+            exec_model: ExecutionModel::Vertex
+        }]
+    );
+}
+#[test]
+fn test_line_table() {
+    use crate::binary::InstrIter;
+    use crate::line_info::{LineTable, SourceLocation};
+    // Extended instructions 35, 103 and 104 are `DebugSource`, `DebugLine` and
+    // `DebugNoLine`.
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpExtension "SPV_KHR_non_semantic_info"
+        %debug_info = OpExtInstImport "NonSemantic.Shader.DebugInfo.100"
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint GLCompute %main "main"
+        OpExecutionMode %main LocalSize 1 1 1
+        %file = OpString "main.comp"
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %uint = OpTypeInt 32 0
+        %uint_3 = OpConstant %uint 3
+        %uint_4 = OpConstant %uint 4
+        %uint_5 = OpConstant %uint 5
+        %src = OpExtInst %void %debug_info 35 %file
+        %main = OpFunction %void None %fn
+        OpLine %file 1 2
+        %entry = OpLabel
+        %a = OpIAdd %uint %uint_3 %uint_4
+        OpNoLine
+        %b = OpIAdd %uint %uint_3 %uint_5
+        OpLine %file 6 7
+        OpBranch %next
+        %next = OpLabel
+        %c = OpExtInst %void %debug_info 103 %src %uint_3 %uint_3 %uint_4 %uint_5
+        %d = OpIAdd %uint %uint_4 %uint_5
+        %e = OpExtInst %void %debug_info 104
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let spv = SpirvBinary::from(spv);
+    let table = LineTable::new(&spv).unwrap();
+    assert!(!table.is_empty());
+    let loc = |file: &str, line: u32, column: u32| SourceLocation {
+        file: Some(file.to_owned()),
+        line,
+        column,
+    };
+    let instrs = InstrIter::new(spv.words())
+        .unwrap()
+        .map(|x| x.unwrap())
+        .collect::<Vec<_>>();
+    let find = |op: spirv::Op, i: usize| {
+        instrs
+            .iter()
+            .enumerate()
+            .filter(|x| x.1.op() == Some(op))
+            .nth(i)
+            .unwrap()
+    };
+
+    let (idx, label) = find(spirv::Op::Label, 0);
+    assert_eq!(table.get(idx), Some(&loc("main.comp", 1, 2)));
+    assert_eq!(
+        table.get_by_offset(label.offset()),
+        Some(&loc("main.comp", 1, 2))
+    );
+    assert_eq!(
+        table.get(find(spirv::Op::IAdd, 0).0),
+        Some(&loc("main.comp", 1, 2))
+    );
+    assert_eq!(table.get(find(spirv::Op::IAdd, 1).0), None);
+    assert_eq!(
+        table.get(find(spirv::Op::Branch, 0).0),
+        Some(&loc("main.comp", 6, 7))
+    );
+    // Locations end with blocks.
+    assert_eq!(table.get(find(spirv::Op::Label, 1).0), None);
+    assert_eq!(
+        table.get(find(spirv::Op::IAdd, 2).0),
+        Some(&loc("main.comp", 3, 4))
+    );
+    assert_eq!(table.get(find(spirv::Op::Return, 0).0), None);
+    assert_eq!(table.get(find(spirv::Op::Function, 0).0), None);
+}
+#[test]
+fn test_ty_arena() {
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint GLCompute %main "main"
+        OpExecutionMode %main LocalSize 1 1 1
+        OpDecorate %Data Block
+        OpMemberDecorate %Data 0 Offset 0
+        OpDecorate %Data3 Block
+        OpMemberDecorate %Data3 0 Offset 0
+        OpDecorate %a DescriptorSet 0
+        OpDecorate %a Binding 0
+        OpDecorate %b DescriptorSet 0
+        OpDecorate %b Binding 1
+        OpDecorate %c DescriptorSet 0
+        OpDecorate %c Binding 2
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %float = OpTypeFloat 32
+        %uint = OpTypeInt 32 0
+        %Data = OpTypeStruct %float
+        %Data2 = OpTypeStruct %uint
+        %Data3 = OpTypeStruct %float
+        %data_ptr = OpTypePointer StorageBuffer %Data
+        %data2_ptr = OpTypePointer StorageBuffer %Data2
+        %a = OpVariable %data_ptr StorageBuffer
+        %b = OpVariable %data_ptr StorageBuffer
+        %c = OpVariable %data2_ptr StorageBuffer
+        %main = OpFunction %void None %fn
+        %entry = OpLabel
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let reflection = ReflectConfig::new()
+        .spv(spv)
+        .ref_all_rscs(true)
+        .reflect_full()
+        .unwrap();
+    // Types are interned as they are declared so `Data` and `Data3` share a
+    // node, and variables of them can be interned without cloning.
+    let data_tys = reflection
+        .tys
+        .values()
+        .filter(|x| ***x == *reflection.entry_points[0].vars[0].ty())
+        .collect::<Vec<_>>();
+    assert_eq!(data_tys.len(), 2);
+    assert!(same_ty(data_tys[0], data_tys[1]));
+    let mut arena = reflection.ty_arena.clone();
+    let narena = arena.len();
+    let ty = arena.intern(reflection.entry_points[0].vars[0].ty());
+    assert!(same_ty(&ty, data_tys[0]));
+    assert_eq!(arena.len(), narena);
+
+    let entry_points = reflection.entry_points;
+    let mut arena = TypeArena::new();
+    let tys = arena.intern_entry_point(&entry_points[0]);
+    assert_eq!(tys.len(), 3);
+    assert_eq!(arena.len(), 2);
+    assert!(same_ty(&tys[0], &tys[1]));
+    assert!(!same_ty(&tys[0], &tys[2]));
+    assert_eq!(*tys[0], *entry_points[0].vars[0].ty());
+
+    // `Data`, `Data2`, `f32` and `u32`.
+    let table = TypeArena::from_entry_points(&entry_points);
+    assert_eq!(table.len(), 4);
+    let types = table.types();
+    assert!(types.windows(2).all(|x| x[0].0 < x[1].0));
+    assert!(types.iter().all(|(hash, ty)| *hash == ty_hash(ty)));
+    // The hashes are stable across runs and platforms.
+    assert_eq!(
+        ty_hash(&Type::Scalar(ty::ScalarType::f32())),
+        8266349341857759446
+    );
+
+    // The same struct in another module has the same hash.
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint GLCompute %main "main"
+        OpExecutionMode %main LocalSize 1 1 1
+        OpDecorate %Data Block
+        OpMemberDecorate %Data 0 Offset 0
+        OpDecorate %x DescriptorSet 3
+        OpDecorate %x Binding 7
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %float = OpTypeFloat 32
+        %Data = OpTypeStruct %float
+        %data_ptr = OpTypePointer StorageBuffer %Data
+        %x = OpVariable %data_ptr StorageBuffer
+        %main = OpFunction %void None %fn
+        %entry = OpLabel
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let other_entry_points = ReflectConfig::new()
+        .spv(spv)
+        .ref_all_rscs(true)
+        .reflect()
+        .unwrap();
+    let other_ty = other_entry_points[0].vars[0].ty();
+    assert_eq!(ty_hash(other_ty), ty_hash(&tys[0]));
+    assert_ne!(ty_hash(other_ty), ty_hash(&tys[2]));
+}
+#[test]
+fn test_binding_labels() {
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint Fragment %main "main"
+        OpExecutionMode %main OriginUpperLeft
+        OpName %albedo_tex "albedo_tex"
+        OpName %textures "textures"
+        OpDecorate %Params Block
+        OpMemberDecorate %Params 0 Offset 0
+        OpDecorate %params DescriptorSet 1
+        OpDecorate %params Binding 0
+        OpDecorate %albedo_tex DescriptorSet 0
+        OpDecorate %albedo_tex Binding 3
+        OpDecorate %textures DescriptorSet 0
+        OpDecorate %textures Binding 4
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %float = OpTypeFloat 32
+        %uint = OpTypeInt 32 0
+        %uint_4 = OpConstant %uint 4
+        %Params = OpTypeStruct %float
+        %params_ptr = OpTypePointer Uniform %Params
+        %params = OpVariable %params_ptr Uniform
+        %img2d = OpTypeImage %float 2D 0 0 0 1 Unknown
+        %sampler2d = OpTypeSampledImage %img2d
+        %sampler2d_ptr = OpTypePointer UniformConstant %sampler2d
+        %albedo_tex = OpVariable %sampler2d_ptr UniformConstant
+        %_arr_img2d = OpTypeArray %img2d %uint_4
+        %_arr_img2d_ptr = OpTypePointer UniformConstant %_arr_img2d
+        %textures = OpVariable %_arr_img2d_ptr UniformConstant
+        %main = OpFunction %void None %fn
+        %entry = OpLabel
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let entry_points = ReflectConfig::new()
+        .spv(spv)
+        .ref_all_rscs(true)
+        .reflect()
+        .unwrap();
+    let labels = LabelConfig::new()
+        .label_entry_points(&entry_points)
+        .unwrap()
+        .into_values()
+        .collect::<Vec<_>>();
+    assert_eq!(
+        labels,
+        [
+            "s0b3 sampler2D albedo_tex (fs)",
+            "s0b4 texture2D[4] textures (fs)",
+            "s1b0 ubo _ (fs)",
+        ]
+    );
+    let labels = LabelConfig::new()
+        .template("{name}@{set}.{binding}x{count}")
+        .unnamed("?")
+        .label_entry_points(&entry_points)
+        .unwrap()
+        .into_values()
+        .collect::<Vec<_>>();
+    assert_eq!(labels, ["albedo_tex@0.3x1", "textures@0.4x4", "?@1.0x1"]);
+}
+#[test]
+fn test_ty_compatibility() {
+    let vs: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint Vertex %main "main" %vs_out
+        OpName %VsOut "VsOut"
+        OpMemberName %VsOut 0 "color"
+        OpMemberName %VsOut 1 "uv"
+        OpDecorate %VsOut Block
+        OpDecorate %vs_out Location 0
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %float = OpTypeFloat 32
+        %v2float = OpTypeVector %float 2
+        %v4float = OpTypeVector %float 4
+        %VsOut = OpTypeStruct %v4float %v2float
+        %vs_out_ptr = OpTypePointer Output %VsOut
+        %vs_out = OpVariable %vs_out_ptr Output
+        %main = OpFunction %void None %fn
+        %entry = OpLabel
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let fs: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint Fragment %main "main" %fs_in %fs_in2
+        OpExecutionMode %main OriginUpperLeft
+        OpName %FsIn "FsIn"
+        OpMemberName %FsIn 0 "albedo"
+        OpMemberName %FsIn 1 "texcoord"
+        OpDecorate %FsIn Block
+        OpDecorate %FsIn2 Block
+        OpDecorate %fs_in Location 0
+        OpDecorate %fs_in2 Location 2
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %float = OpTypeFloat 32
+        %v3float = OpTypeVector %float 3
+        %v2float = OpTypeVector %float 2
+        %v4float = OpTypeVector %float 4
+        %FsIn = OpTypeStruct %v4float %v2float
+        %FsIn2 = OpTypeStruct %v4float %v3float
+        %fs_in_ptr = OpTypePointer Input %FsIn
+        %fs_in2_ptr = OpTypePointer Input %FsIn2
+        %fs_in = OpVariable %fs_in_ptr Input
+        %fs_in2 = OpVariable %fs_in2_ptr Input
+        %main = OpFunction %void None %fn
+        %entry = OpLabel
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let vs = ReflectConfig::new()
+        .spv(vs)
+        .ref_all_rscs(true)
+        .reflect()
+        .unwrap();
+    let fs = ReflectConfig::new()
+        .spv(fs)
+        .ref_all_rscs(true)
+        .reflect()
+        .unwrap();
+    let vs_out_ty = vs[0].vars[0].ty();
+    let fs_in_ty = fs[0].vars[0].ty();
+    let fs_in2_ty = fs[0].vars[1].ty();
+    assert_ne!(vs_out_ty, fs_in_ty);
+    assert!(vs_out_ty.is_compatible_with(fs_in_ty));
+    assert!(!vs_out_ty.is_compatible_with(fs_in2_ty));
+
+    let mut offset_ty = fs_in_ty.clone();
+    if let Type::Struct(x) = &mut offset_ty {
+        x.members[1].offset = Some(16);
+    }
+    assert!(!vs_out_ty.is_compatible_with(&offset_ty));
+}
+#[test]
+fn test_exec_model_requirements() {
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability MeshShadingEXT
+        OpExtension "SPV_EXT_mesh_shader"
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint MeshEXT %main "main"
+        OpExecutionMode %main LocalSize 1 1 1
+        OpExecutionMode %main OutputVertices 3
+        OpExecutionMode %main OutputPrimitivesEXT 1
+        OpExecutionMode %main OutputTrianglesEXT
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %main = OpFunction %void None %fn
+        %entry = OpLabel
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let entry_points = ReflectConfig::new().spv(spv).reflect().unwrap();
+    let req = entry_points[0].exec_model_requirements();
+    assert_eq!(req.spirv_version, (1, 4));
+    assert_eq!(req.vulkan_version, Some((1, 1)));
+    assert_eq!(req.caps, [spirv::Capability::MeshShadingEXT]);
+    assert_eq!(req.spirv_exts, ["SPV_EXT_mesh_shader"]);
+    assert!(req.vulkan_exts.contains(&"VK_EXT_mesh_shader"));
+
+    let req = ExecutionModelRequirements::of(ExecutionModel::Fragment);
+    assert_eq!(req.spirv_version, (1, 0));
+    assert_eq!(req.vulkan_version, Some((1, 0)));
+    assert!(req.vulkan_exts.is_empty());
+    let req = ExecutionModelRequirements::of(ExecutionModel::Kernel);
+    assert_eq!(req.vulkan_version, None);
+}
+#[test]
+fn test_io_decos() {
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpCapability TransformFeedback
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint Vertex %main "main" %pos %id %vout
+        OpExecutionMode %main Xfb
+        OpDecorate %pos Location 0
+        OpDecorate %pos XfbBuffer 1
+        OpDecorate %pos XfbStride 16
+        OpDecorate %pos Offset 0
+        OpDecorate %id Location 1
+        OpDecorate %id Flat
+        OpDecorate %Vout Block
+        OpMemberDecorate %Vout 0 Offset 16
+        OpMemberDecorate %Vout 1 Offset 32
+        OpMemberDecorate %Vout 1 NoPerspective
+        OpDecorate %vout Location 2
+        OpDecorate %vout XfbBuffer 0
+        OpDecorate %vout XfbStride 48
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %float = OpTypeFloat 32
+        %int = OpTypeInt 32 1
+        %v4float = OpTypeVector %float 4
+        %v4float_ptr = OpTypePointer Output %v4float
+        %int_ptr = OpTypePointer Output %int
+        %pos = OpVariable %v4float_ptr Output
+        %id = OpVariable %int_ptr Output
+        %Vout = OpTypeStruct %v4float %v4float %v4float
+        %Vout_ptr = OpTypePointer Output %Vout
+        %vout = OpVariable %Vout_ptr Output
+        %main = OpFunction %void None %fn
+        %entry = OpLabel
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let entry_points = ReflectConfig::new()
+        .spv(spv)
+        .ref_all_rscs(true)
+        .reflect()
+        .unwrap();
+    let entry = &entry_points[0];
+    let get_output = |loc: u32| {
+        entry
+            .vars
+            .iter()
+            .find(|x| matches!(x, Variable::Output { location, .. } if location.loc() == loc))
+            .unwrap()
+    };
+    let pos_decos = entry.io_decorations(get_output(0)).unwrap();
+    assert_eq!(pos_decos.len(), 3);
+    assert_eq!(pos_decos[&spirv::Decoration::XfbBuffer], [1]);
+    assert_eq!(pos_decos[&spirv::Decoration::XfbStride], [16]);
+    assert_eq!(pos_decos[&spirv::Decoration::Offset], [0]);
+    let id_decos = entry.io_decorations(get_output(1)).unwrap();
+    assert_eq!(id_decos.len(), 1);
+    assert!(id_decos[&spirv::Decoration::Flat].is_empty());
+    assert!(entry.io_member_decorations(get_output(1), 0).is_none());
+    let vout = get_output(2);
+    let vout_decos = entry.io_decorations(vout).unwrap();
+    assert_eq!(vout_decos[&spirv::Decoration::XfbBuffer], [0]);
+    assert_eq!(vout_decos[&spirv::Decoration::XfbStride], [48]);
+    let member_decos = |i| entry.io_member_decorations(vout, i);
+    assert_eq!(member_decos(0).unwrap()[&spirv::Decoration::Offset], [16]);
+    let member1_decos = member_decos(1).unwrap();
+    assert_eq!(member1_decos.len(), 2);
+    assert_eq!(member1_decos[&spirv::Decoration::Offset], [32]);
+    assert!(member1_decos[&spirv::Decoration::NoPerspective].is_empty());
+    assert!(member_decos(2).is_none());
+}
+#[test]
+#[cfg(feature = "serde")]
+fn test_serde_round_trip() {
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint GLCompute %main "main"
+        OpExecutionMode %main LocalSize 8 4 1
+        OpName %ubo "ubo"
+        OpName %img "img"
+        OpDecorate %ubo DescriptorSet 0
+        OpDecorate %ubo Binding 0
+        OpDecorate %img DescriptorSet 0
+        OpDecorate %img Binding 1
+        OpDecorate %img NonReadable
+        OpDecorate %Data Block
+        OpMemberDecorate %Data 0 Offset 0
+        OpMemberDecorate %Data 1 Offset 16
+        OpMemberDecorate %Data 1 ColMajor
+        OpMemberDecorate %Data 1 MatrixStride 16
+        OpDecorate %count SpecId 3
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %float = OpTypeFloat 32
+        %uint = OpTypeInt 32 0
+        %v4float = OpTypeVector %float 4
+        %mat4 = OpTypeMatrix %v4float 4
+        %count = OpSpecConstant %uint 4
+        %Data = OpTypeStruct %v4float %mat4
+        %Data_ptr = OpTypePointer Uniform %Data
+        %ubo = OpVariable %Data_ptr Uniform
+        %image = OpTypeImage %float 2D 0 0 0 2 Rgba8
+        %image_ptr = OpTypePointer UniformConstant %image
+        %img = OpVariable %image_ptr UniformConstant
+        %main = OpFunction %void None %fn
+        %entry = OpLabel
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let entry_points = ReflectConfig::new()
+        .spv(spv)
+        .ref_all_rscs(true)
+        .reflect()
+        .unwrap();
+    let entry = &entry_points[0];
+    let json = serde_json::to_string(entry).unwrap();
+    let entry2: EntryPoint = serde_json::from_str(&json).unwrap();
+    assert_eq!(entry, &entry2);
+}
+#[test]
+fn test_kernel_opaque_tys() {
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Addresses
+        OpCapability Kernel
+        OpCapability Int64
+        OpCapability Pipes
+        OpCapability DeviceEnqueue
+        OpMemoryModel Physical64 OpenCL
+        OpEntryPoint Kernel %main "main"
+        OpExecutionMode %main LocalSize 64 1 1
+        OpName %counter "counter"
+        %void = OpTypeVoid
+        %uint = OpTypeInt 32 0
+        %foo = OpTypeOpaque "struct.foo"
+        %foo_ptr = OpTypePointer CrossWorkgroup %foo
+        %event = OpTypeEvent
+        %queue = OpTypeQueue
+        %pipe = OpTypePipe ReadOnly
+        %fn = OpTypeFunction %void %foo_ptr %pipe
+        %uint_ptr = OpTypePointer CrossWorkgroup %uint
+        %uint_0 = OpConstant %uint 0
+        %counter = OpVariable %uint_ptr CrossWorkgroup %uint_0
+        %main = OpFunction %void None %fn
+        %foo_arg = OpFunctionParameter %foo_ptr
+        %pipe_arg = OpFunctionParameter %pipe
+        %entry = OpLabel
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let entry_points = ReflectConfig::new()
+        .spv(spv)
+        .ref_all_rscs(true)
+        .reflect()
+        .unwrap();
+    assert_eq!(entry_points.len(), 1);
+    let entry = &entry_points[0];
+    assert_eq!(entry.exec_model, ExecutionModel::Kernel);
+    assert_eq!(entry.name, "main");
+    assert_eq!(
+        entry.global_vars,
+        [PrivateVariable {
+            name: Some("counter".to_owned()),
+            ty: Type::Scalar(ty::ScalarType::u32()),
+            init: Some(ConstantValue::U32(0)),
+        }]
+    );
+}
+#[test]
+fn test_literal_samplers() {
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Addresses
+        OpCapability Kernel
+        OpCapability LiteralSampler
+        OpMemoryModel Physical32 OpenCL
+        OpEntryPoint Kernel %main "main"
+        OpName %nearest "nearest"
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %sampler = OpTypeSampler
+        %nearest = OpConstantSampler %sampler ClampToEdge 0 Nearest
+        %linear = OpConstantSampler %sampler Repeat 1 Linear
+        %main = OpFunction %void None %fn
+        %entry = OpLabel
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let entry_points = ReflectConfig::new().spv(spv).reflect().unwrap();
+    let samplers = &entry_points[0].literal_samplers;
+    assert_eq!(
+        samplers,
+        &[
+            LiteralSampler {
+                name: Some("nearest".to_owned()),
+                addr_mode: spirv::SamplerAddressingMode::ClampToEdge,
+                is_normalized: false,
+                filter_mode: spirv::SamplerFilterMode::Nearest,
+            },
+            LiteralSampler {
+                name: None,
+                addr_mode: spirv::SamplerAddressingMode::Repeat,
+                is_normalized: true,
+                filter_mode: spirv::SamplerFilterMode::Linear,
+            },
+        ]
+    );
+}
+#[test]
+fn test_vk_formats() {
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint GLCompute %main "main"
+        OpExecutionMode %main LocalSize 1 1 1
+        OpDecorate %color DescriptorSet 0
+        OpDecorate %color Binding 0
+        OpDecorate %counts DescriptorSet 0
+        OpDecorate %counts Binding 1
+        OpDecorate %any DescriptorSet 0
+        OpDecorate %any Binding 2
+        OpDecorate %any NonWritable
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %float = OpTypeFloat 32
+        %uint = OpTypeInt 32 0
+        %uint_4 = OpConstant %uint 4
+        %rgba32f = OpTypeImage %float 2D 0 0 0 2 Rgba32f
+        %rgba32f_ptr = OpTypePointer UniformConstant %rgba32f
+        %r32ui = OpTypeImage %uint 2D 0 0 0 2 R32ui
+        %r32ui_arr = OpTypeArray %r32ui %uint_4
+        %r32ui_arr_ptr = OpTypePointer UniformConstant %r32ui_arr
+        %unknown = OpTypeImage %float 2D 0 0 0 2 Unknown
+        %unknown_ptr = OpTypePointer UniformConstant %unknown
+        %color = OpVariable %rgba32f_ptr UniformConstant
+        %counts = OpVariable %r32ui_arr_ptr UniformConstant
+        %any = OpVariable %unknown_ptr UniformConstant
+        %main = OpFunction %void None %fn
+        %entry = OpLabel
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let entry_points = ReflectConfig::new()
+        .spv(spv)
+        .ref_all_rscs(true)
+        .reflect()
+        .unwrap();
+    let fmts = entry_points[0]
+        .vars
+        .iter()
+        .map(|x| declared_image_fmt(x.ty()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        fmts,
+        [
+            Some(spirv::ImageFormat::Rgba32f),
+            Some(spirv::ImageFormat::R32ui),
+            None
+        ]
+    );
+    assert_eq!(vk_format(spirv::ImageFormat::Rgba32f), Some(109));
+    assert_eq!(
+        vk_format_name(spirv::ImageFormat::R32ui),
+        Some("VK_FORMAT_R32_UINT")
+    );
+    assert_eq!(vk_format(spirv::ImageFormat::Unknown), None);
+
+    let entry_point = &entry_points[0];
+    let desc_bind = |bind| DescriptorBinding::new(0, bind);
+    let features = StorageImageFeatures::default();
+    let errs = check_storage_image_fmts(
+        entry_point,
+        &[(desc_bind(0), 109), (desc_bind(1), 98)],
+        features,
+    );
+    assert!(errs.is_empty());
+    let errs = check_storage_image_fmts(
+        entry_point,
+        &[(desc_bind(0), 37), (desc_bind(2), 37), (desc_bind(3), 37)],
+        features,
+    );
+    assert_eq!(
+        errs,
+        [
+            StorageImageFmtError::Mismatch {
+                desc_bind: desc_bind(0),
+                fmt: spirv::ImageFormat::Rgba32f,
+                vk_fmt: 37
+            },
+            StorageImageFmtError::ReadWithoutFormat {
+                desc_bind: desc_bind(2)
+            },
+            StorageImageFmtError::NotStorageImage {
+                desc_bind: desc_bind(3)
+            },
+        ]
+    );
+    assert_eq!(
+        errs[0].to_string(),
+        "storage image at (set=0, bind=0) is declared Rgba32f but \
+        VK_FORMAT_R8G8B8A8_UNORM (37) is given; use \
+        VK_FORMAT_R32G32B32A32_SFLOAT (109) or change the declared format"
+    );
+    let features = StorageImageFeatures {
+        read_without_fmt: true,
+        ..Default::default()
+    };
+    let errs = check_storage_image_fmts(entry_point, &[(desc_bind(2), 37)], features);
+    assert!(errs.is_empty());
+}
+#[test]
+fn test_clspv_kernels() {
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpExtension "SPV_KHR_non_semantic_info"
+        %refl = OpExtInstImport "NonSemantic.ClspvReflection.5"
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint GLCompute %main "scale"
+        OpExecutionMode %main LocalSize 64 1 1
+        %kernel_name = OpString "scale"
+        %dst_name = OpString "dst"
+        %dst_ty_name = OpString "float*"
+        %factor_name = OpString "factor"
+        %texels_name = OpString "texels"
+        %next_name = OpString "next"
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %uint = OpTypeInt 32 0
+        %uint_0 = OpConstant %uint 0
+        %uint_1 = OpConstant %uint 1
+        %uint_2 = OpConstant %uint 2
+        %uint_3 = OpConstant %uint 3
+        %uint_4 = OpConstant %uint 4
+        %uint_8 = OpConstant %uint 8
+        %global = OpConstant %uint 0x119B
+        %main = OpFunction %void None %fn
+        %entry = OpLabel
+        OpReturn
+        OpFunctionEnd
+        %kernel = OpExtInst %void %refl 1 %main %kernel_name
+        %factor_info = OpExtInst %void %refl 2 %factor_name
+        %factor = OpExtInst %void %refl 7 %kernel %uint_1 %uint_0 %uint_4 %factor_info
+        %dst_info = OpExtInst %void %refl 2 %dst_name %dst_ty_name %global
+        %dst = OpExtInst %void %refl 3 %kernel %uint_0 %uint_0 %uint_1 %dst_info
+        %texels_info = OpExtInst %void %refl 2 %texels_name
+        %texels = OpExtInst %void %refl 35 %kernel %uint_2 %uint_0 %uint_2 %texels_info
+        %next_info = OpExtInst %void %refl 2 %next_name
+        %next = OpExtInst %void %refl 26 %kernel %uint_3 %uint_4 %uint_8 %next_info
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let spv = SpirvBinary::from(spv);
+    let kernels = clspv_kernels(&spv).unwrap();
+    assert_eq!(kernels.len(), 1);
+    let kernel = &kernels[0];
+    assert_eq!(kernel.name, "scale");
+    assert_eq!(kernel.args.len(), 4);
+    let dst = &kernel.args[0];
+    assert_eq!(dst.ordinal, 0);
+    assert_eq!(dst.name.as_deref(), Some("dst"));
+    assert_eq!(dst.ty_name.as_deref(), Some("float*"));
+    assert_eq!(dst.addr_space, Some(ClspvAddressSpace::Global));
+    assert_eq!(dst.kind.desc_bind(), Some(DescriptorBinding::new(0, 1)));
+    let factor = &kernel.args[1];
+    assert_eq!(factor.ordinal, 1);
+    assert_eq!(factor.name.as_deref(), Some("factor"));
+    assert_eq!(factor.addr_space, None);
+    assert_eq!(
+        factor.kind,
+        ClspvArgumentKind::PodPushConstant {
+            offset: 0,
+            nbyte: 4
+        }
+    );
+    let texels = &kernel.args[2];
+    assert_eq!(texels.name.as_deref(), Some("texels"));
+    assert_eq!(
+        texels.kind,
+        ClspvArgumentKind::UniformTexelBuffer {
+            desc_bind: DescriptorBinding::new(0, 2)
+        }
+    );
+    let next = &kernel.args[3];
+    assert_eq!(next.name.as_deref(), Some("next"));
+    assert_eq!(
+        next.kind,
+        ClspvArgumentKind::PointerPushConstant {
+            offset: 4,
+            nbyte: 8
+        }
+    );
+}
+#[test]
+fn test_parse_keys() {
+    let desc_bind = DescriptorBinding::new(1, 3);
+    assert_eq!(parse_desc_bind("1.3").unwrap(), desc_bind);
+    assert_eq!(parse_desc_bind(" 1, 3 ").unwrap(), desc_bind);
+    assert_eq!(parse_desc_bind("(set=1, bind=3)").unwrap(), desc_bind);
+    assert_eq!(parse_desc_bind("binding=3,set=1").unwrap(), desc_bind);
+    assert_eq!(parse_desc_bind(&desc_bind.to_string()).unwrap(), desc_bind);
+    assert!(parse_desc_bind("1").is_err());
+    assert!(parse_desc_bind("1.3.5").is_err());
+    assert!(parse_desc_bind("(set=1, loc=3)").is_err());
+    assert!(parse_desc_bind("set=1,set=3").is_err());
+
+    let location = InterfaceLocation::new(2, 1);
+    assert_eq!(parse_location("2.1").unwrap(), location);
+    assert_eq!(
+        parse_location("(location=2, component=1)").unwrap(),
+        location
+    );
+    assert_eq!(parse_location(&location.to_string()).unwrap(), location);
+    assert_eq!(parse_location("2").unwrap(), InterfaceLocation::new(2, 0));
+    assert_eq!(
+        parse_location("loc=2").unwrap(),
+        InterfaceLocation::new(2, 0)
+    );
+    assert!(parse_location("comp=1").is_err());
+    assert!(parse_location("x.1").is_err());
+}
+#[test]
+fn test_c_header() {
+    use crate::header::generate_c_header;
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint GLCompute %main "main"
+        OpExecutionMode %main LocalSize 1 1 1
+        OpName %Params "Params"
+        OpMemberName %Params 0 "dir"
+        OpMemberName %Params 1 "xform"
+        OpMemberName %Params 2 "weights"
+        OpMemberName %Params 3 "count"
+        OpName %Push "Push"
+        OpMemberName %Push 0 "scale"
+        OpMemberName %Push 1 "addr"
+        OpDecorate %Params Block
+        OpMemberDecorate %Params 0 Offset 0
+        OpMemberDecorate %Params 1 Offset 16
+        OpMemberDecorate %Params 1 ColMajor
+        OpMemberDecorate %Params 1 MatrixStride 16
+        OpMemberDecorate %Params 2 Offset 64
+        OpMemberDecorate %Params 3 Offset 96
+        OpDecorate %float_arr ArrayStride 16
+        OpDecorate %Push Block
+        OpMemberDecorate %Push 0 Offset 0
+        OpMemberDecorate %Push 1 Offset 8
+        OpDecorate %params DescriptorSet 0
+        OpDecorate %params Binding 2
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %float = OpTypeFloat 32
+        %uint = OpTypeInt 32 0
+        %ulong = OpTypeInt 64 0
+        %uint_2 = OpConstant %uint 2
+        %v3float = OpTypeVector %float 3
+        %mat3 = OpTypeMatrix %v3float 3
+        %float_arr = OpTypeArray %float %uint_2
+        %Params = OpTypeStruct %v3float %mat3 %float_arr %uint
+        %Params_ptr = OpTypePointer Uniform %Params
+        %params = OpVariable %Params_ptr Uniform
+        %Push = OpTypeStruct %float %ulong
+        %Push_ptr = OpTypePointer PushConstant %Push
+        %push = OpVariable %Push_ptr PushConstant
+        %main = OpFunction %void None %fn
+        %entry = OpLabel
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let entry_points = ReflectConfig::new()
+        .spv(spv)
+        .ref_all_rscs(true)
+        .reflect()
+        .unwrap();
+    let header = generate_c_header(&entry_points[0]).unwrap();
+    assert_eq!(
+        header,
+        r#"// Block layouts of entry point `main` (GLCompute).
+#pragma once
+#include <assert.h>
+#include <stddef.h>
+#include <stdint.h>
+
+struct Params_xform_Vec {
+    float value[3];
+    uint8_t _pad0[4];
+};
+
+struct Params_weights_Elem {
+    float value;
+    uint8_t _pad0[12];
+};
+
+struct Params {
+    float dir[3];
+    uint8_t _pad0[4];
+    struct Params_xform_Vec xform[3];
+    struct Params_weights_Elem weights[2];
+    uint32_t count;
+};
+static_assert(offsetof(struct Params, dir) == 0, "offset of Params::dir");
+static_assert(offsetof(struct Params, xform) == 16, "offset of Params::xform");
+static_assert(offsetof(struct Params, weights) == 64, "offset of Params::weights");
+static_assert(offsetof(struct Params, count) == 96, "offset of Params::count");
+
+struct Push {
+    float scale;
+    uint8_t _pad0[4];
+    uint64_t addr;
+};
+static_assert(offsetof(struct Push, scale) == 0, "offset of Push::scale");
+static_assert(offsetof(struct Push, addr) == 8, "offset of Push::addr");
+"#
+    );
+}
+#[test]
+fn test_c_header_names() {
+    use crate::header::generate_c_header;
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint GLCompute %main "main"
+        OpExecutionMode %main LocalSize 1 1 1
+        OpName %Data "union"
+        OpMemberName %Data 0 "class"
+        OpMemberName %Data 1 "a.b"
+        OpMemberName %Data 2 "a_b"
+        OpMemberName %Data 3 "_pad0"
+        OpMemberName %Data 4 "values"
+        OpDecorate %Data Block
+        OpMemberDecorate %Data 0 Offset 0
+        OpMemberDecorate %Data 1 Offset 4
+        OpMemberDecorate %Data 2 Offset 16
+        OpMemberDecorate %Data 3 Offset 20
+        OpMemberDecorate %Data 4 Offset 24
+        OpDecorate %_rtarr_float ArrayStride 4
+        OpDecorate %data DescriptorSet 0
+        OpDecorate %data Binding 0
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %float = OpTypeFloat 32
+        %uint = OpTypeInt 32 0
+        %_rtarr_float = OpTypeRuntimeArray %float
+        %Data = OpTypeStruct %uint %uint %uint %uint %_rtarr_float
+        %Data_ptr = OpTypePointer StorageBuffer %Data
+        %data = OpVariable %Data_ptr StorageBuffer
+        %main = OpFunction %void None %fn
+        %entry = OpLabel
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let entry_points = ReflectConfig::new()
+        .spv(spv)
+        .ref_all_rscs(true)
+        .reflect()
+        .unwrap();
+    let header = generate_c_header(&entry_points[0]).unwrap();
+    assert_eq!(
+        header,
+        r#"// Block layouts of entry point `main` (GLCompute).
+#pragma once
+#include <assert.h>
+#include <stddef.h>
+#include <stdint.h>
+#ifndef SPIRQ_RUNTIME_ARRAY_LEN
+#ifdef __cplusplus
+#define SPIRQ_RUNTIME_ARRAY_LEN 1
+#else
+#define SPIRQ_RUNTIME_ARRAY_LEN
+#endif
+#endif
+
+struct union_ {
+    uint32_t class_;
+    uint32_t a_b;
+    uint8_t _pad1[8];
+    uint32_t a_b_1;
+    uint32_t _pad0;
+    float values[SPIRQ_RUNTIME_ARRAY_LEN];
+};
+static_assert(offsetof(struct union_, class_) == 0, "offset of union_::class_");
+static_assert(offsetof(struct union_, a_b) == 4, "offset of union_::a_b");
+static_assert(offsetof(struct union_, a_b_1) == 16, "offset of union_::a_b_1");
+static_assert(offsetof(struct union_, _pad0) == 20, "offset of union_::_pad0");
+static_assert(offsetof(struct union_, values) == 24, "offset of union_::values");
+"#
+    );
+}
+#[test]
+fn test_wgsl() {
+    use crate::wgsl::generate_wgsl;
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint GLCompute %main "main"
+        OpExecutionMode %main LocalSize 1 1 1
+        OpName %Params "Params"
+        OpMemberName %Params 0 "dir"
+        OpMemberName %Params 1 "xform"
+        OpMemberName %Params 2 "weights"
+        OpMemberName %Params 3 "count"
+        OpName %params "params"
+        OpName %Points "Points"
+        OpMemberName %Points 0 "points"
+        OpName %tex "tex"
+        OpName %samp "samp"
+        OpName %img "img"
+        OpName %Push "Push"
+        OpMemberName %Push 0 "scale"
+        OpMemberName %Push 1 "bias"
+        OpName %nsample "nsample"
+        OpDecorate %Params Block
+        OpMemberDecorate %Params 0 Offset 0
+        OpMemberDecorate %Params 1 Offset 16
+        OpMemberDecorate %Params 1 ColMajor
+        OpMemberDecorate %Params 1 MatrixStride 16
+        OpMemberDecorate %Params 2 Offset 64
+        OpMemberDecorate %Params 3 Offset 96
+        OpDecorate %float_arr ArrayStride 16
+        OpDecorate %Points Block
+        OpMemberDecorate %Points 0 Offset 0
+        OpMemberDecorate %Points 0 NonWritable
+        OpDecorate %v4float_arr ArrayStride 16
+        OpDecorate %Push Block
+        OpMemberDecorate %Push 0 Offset 0
+        OpMemberDecorate %Push 1 Offset 16
+        OpDecorate %params DescriptorSet 0
+        OpDecorate %params Binding 0
+        OpDecorate %points DescriptorSet 1
+        OpDecorate %points Binding 0
+        OpDecorate %tex DescriptorSet 1
+        OpDecorate %tex Binding 1
+        OpDecorate %samp DescriptorSet 1
+        OpDecorate %samp Binding 2
+        OpDecorate %img DescriptorSet 1
+        OpDecorate %img Binding 3
+        OpDecorate %img NonReadable
+        OpDecorate %nsample SpecId 3
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %float = OpTypeFloat 32
+        %uint = OpTypeInt 32 0
+        %uint_2 = OpConstant %uint 2
+        %nsample = OpSpecConstant %uint 4
+        %v3float = OpTypeVector %float 3
+        %v4float = OpTypeVector %float 4
+        %mat3 = OpTypeMatrix %v3float 3
+        %float_arr = OpTypeArray %float %uint_2
+        %Params = OpTypeStruct %v3float %mat3 %float_arr %uint
+        %Params_ptr = OpTypePointer Uniform %Params
+        %params = OpVariable %Params_ptr Uniform
+        %v4float_arr = OpTypeRuntimeArray %v4float
+        %Points = OpTypeStruct %v4float_arr
+        %Points_ptr = OpTypePointer StorageBuffer %Points
+        %points = OpVariable %Points_ptr StorageBuffer
+        %tex_ty = OpTypeImage %float 2D 0 0 0 1 Unknown
+        %tex_ptr = OpTypePointer UniformConstant %tex_ty
+        %tex = OpVariable %tex_ptr UniformConstant
+        %samp_ty = OpTypeSampler
+        %samp_ptr = OpTypePointer UniformConstant %samp_ty
+        %samp = OpVariable %samp_ptr UniformConstant
+        %img_ty = OpTypeImage %float 2D 0 0 0 2 Rgba8
+        %img_ptr = OpTypePointer UniformConstant %img_ty
+        %img = OpVariable %img_ptr UniformConstant
+        %Push = OpTypeStruct %float %float
+        %Push_ptr = OpTypePointer PushConstant %Push
+        %push = OpVariable %Push_ptr PushConstant
+        %main = OpFunction %void None %fn
+        %entry = OpLabel
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let entry_points = ReflectConfig::new()
+        .spv(spv)
+        .ref_all_rscs(true)
+        .reflect()
+        .unwrap();
+    let wgsl = generate_wgsl(&entry_points[0]).unwrap();
+    assert_eq!(
+        wgsl,
+        r#"// Resources of entry point `main` (GLCompute).
+struct Params_weights_Elem {
+    @size(16) value: f32,
+}
+struct Params {
+    dir: vec3<f32>,
+    xform: mat3x3<f32>,
+    weights: array<Params_weights_Elem, 2>,
+    count: u32,
+}
+struct Points {
+    points: array<vec4<f32>>,
+}
+struct Push {
+    @size(16) scale: f32,
+    bias: f32,
+}
+@group(0) @binding(0) var<uniform> params: Params;
+@group(1) @binding(1) var tex: texture_2d<f32>;
+@group(1) @binding(2) var samp: sampler;
+@group(1) @binding(3) var img: texture_storage_2d<rgba8unorm, write>;
+@group(1) @binding(0) var<storage, read> desc_1_0: Points;
+var<push_constant> push_consts: Push;
+@id(3) override nsample: u32;
+"#
+    );
+}
+#[test]
+fn test_markdown() {
+    use crate::markdown::generate_markdown;
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint Fragment %main "main" %uv %color
+        OpExecutionMode %main OriginUpperLeft
+        OpName %Light "Light"
+        OpMemberName %Light 0 "position"
+        OpMemberName %Light 1 "intensity"
+        OpMemberName %Scene 0 "lights"
+        OpMemberName %Scene 1 "count"
+        OpName %scene "scene"
+        OpMemberName %Push 0 "tint"
+        OpName %uv "uv"
+        OpName %color "color"
+        OpName %nlight "nlight"
+        OpDecorate %light_arr ArrayStride 32
+        OpMemberDecorate %Light 0 Offset 0
+        OpMemberDecorate %Light 1 Offset 16
+        OpDecorate %Scene Block
+        OpMemberDecorate %Scene 0 Offset 0
+        OpMemberDecorate %Scene 1 Offset 64
+        OpDecorate %scene DescriptorSet 0
+        OpDecorate %scene Binding 1
+        OpDecorate %Push Block
+        OpMemberDecorate %Push 0 Offset 0
+        OpDecorate %uv Location 0
+        OpDecorate %color Location 0
+        OpDecorate %nlight SpecId 2
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %float = OpTypeFloat 32
+        %uint = OpTypeInt 32 0
+        %uint_2 = OpConstant %uint 2
+        %nlight = OpSpecConstant %uint 2
+        %v2float = OpTypeVector %float 2
+        %v3float = OpTypeVector %float 3
+        %v4float = OpTypeVector %float 4
+        %Light = OpTypeStruct %v3float %float
+        %light_arr = OpTypeArray %Light %uint_2
+        %Scene = OpTypeStruct %light_arr %uint
+        %Scene_ptr = OpTypePointer Uniform %Scene
+        %scene = OpVariable %Scene_ptr Uniform
+        %Push = OpTypeStruct %v4float
+        %Push_ptr = OpTypePointer PushConstant %Push
+        %push = OpVariable %Push_ptr PushConstant
+        %uv_ptr = OpTypePointer Input %v2float
+        %uv = OpVariable %uv_ptr Input
+        %color_ptr = OpTypePointer Output %v4float
+        %color = OpVariable %color_ptr Output
+        %main = OpFunction %void None %fn
+        %entry = OpLabel
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let entry_points = ReflectConfig::new()
+        .spv(spv)
+        .ref_all_rscs(true)
+        .reflect()
+        .unwrap();
+    let markdown = generate_markdown(&entry_points[0]);
+    assert_eq!(
+        markdown,
+        r#"# `main` (Fragment)
+
+## Descriptors
+
+| Set | Binding | Name | Descriptor Type | Type | Count |
+| --- | --- | --- | --- | --- | --- |
+| 0 | 1 | `scene` | UniformBuffer | [`Block_0_1`](#block_0_1) | 1 |
+
+## Push Constants
+
+| Name | Type |
+| --- | --- |
+| *unnamed* | [`PushConstants`](#pushconstants) |
+
+## Inputs
+
+| Location | Component | Name | Type |
+| --- | --- | --- | --- |
+| 0 | 0 | `uv` | `vec2<f32>` |
+
+## Outputs
+
+| Location | Component | Name | Type |
+| --- | --- | --- | --- |
+| 0 | 0 | `color` | `vec4<f32>` |
+
+## Specialization Constants
+
+| ID | Name | Type | Default |
+| --- | --- | --- | --- |
+| 2 | `nlight` | `u32` | 2 |
+
+## Types
+
+### `Light`
+
+| Offset | Size | Name | Type | Access |
+| --- | --- | --- | --- | --- |
+| 0 | 12 | `position` | `vec3<f32>` | ReadWrite |
+| 16 | 4 | `intensity` | `f32` | ReadWrite |
+
+### `Block_0_1`
+
+| Offset | Size | Name | Type | Access |
+| --- | --- | --- | --- | --- |
+| 0 | 64 | `lights` | [`Light`](#light)\[2\] | ReadWrite |
+| 64 | 4 | `count` | `u32` | ReadWrite |
+
+### `PushConstants`
+
+| Offset | Size | Name | Type | Access |
+| --- | --- | --- | --- | --- |
+| 0 | 16 | `tint` | `vec4<f32>` | ReadWrite |
+"#
+    );
+}
+#[test]
+fn test_bitfield_annotations() {
+    use crate::bitfield::{BitfieldAnnotations, BitfieldMember};
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint GLCompute %main "main"
+        OpExecutionMode %main LocalSize 1 1 1
+        OpName %Material "Material"
+        OpMemberName %Material 0 "albedo"
+        OpMemberName %Material 1 "flags"
+        OpName %Scene "Scene"
+        OpMemberName %Scene 0 "materials"
+        OpMemberName %Scene 1 "flags"
+        OpMemberDecorate %Material 0 Offset 0
+        OpMemberDecorate %Material 1 Offset 12
+        OpDecorate %mat_arr ArrayStride 16
+        OpDecorate %Scene Block
+        OpMemberDecorate %Scene 0 Offset 0
+        OpMemberDecorate %Scene 1 Offset 32
+        OpDecorate %scene DescriptorSet 0
+        OpDecorate %scene Binding 0
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %float = OpTypeFloat 32
+        %uint = OpTypeInt 32 0
+        %uint_2 = OpConstant %uint 2
+        %v3float = OpTypeVector %float 3
+        %Material = OpTypeStruct %v3float %uint
+        %mat_arr = OpTypeArray %Material %uint_2
+        %Scene = OpTypeStruct %mat_arr %uint
+        %Scene_ptr = OpTypePointer Uniform %Scene
+        %scene = OpVariable %Scene_ptr Uniform
+        %main = OpFunction %void None %fn
+        %entry = OpLabel
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let entry_points = ReflectConfig::new()
+        .spv(spv)
+        .ref_all_rscs(true)
+        .reflect()
+        .unwrap();
+    let ty = entry_points[0].vars[0].ty();
+
+    let annotations = BitfieldAnnotations::parse(
+        r#"
+        # Unrelated types are ignored.
+        Light.flags = on:1
+        Material.flags = kind:4, _:4, mask:8 # Trailing comments are allowed.
+        "#,
+    )
+    .unwrap();
+    let bitfields = annotations.walk(ty).unwrap();
+    let bitfield = |sym: &str, offset: usize, bit_offset: u32, nbit: u32| BitfieldMember {
+        sym: sym.to_owned(),
+        offset,
+        ty: Type::Scalar(ty::ScalarType::u32()),
+        bit_offset,
+        nbit,
+    };
+    assert_eq!(
+        bitfields,
+        vec![
+            bitfield("materials[0].flags.kind", 12, 0, 4),
+            bitfield("materials[0].flags.mask", 12, 8, 8),
+            bitfield("materials[1].flags.kind", 28, 0, 4),
+            bitfield("materials[1].flags.mask", 28, 8, 8),
+        ]
+    );
+
+    let annotations = BitfieldAnnotations::parse("Scene.flags = a:16, b:17").unwrap();
+    assert!(annotations.walk(ty).is_err());
+    let annotations = BitfieldAnnotations::parse("Material.albedo = a:1").unwrap();
+    assert!(annotations.walk(ty).is_err());
+    assert!(BitfieldAnnotations::parse("Material.flags = a:0").is_err());
+    assert!(BitfieldAnnotations::parse("Material.flags = a:1, a:1").is_err());
+    assert!(BitfieldAnnotations::parse("flags = a:1").is_err());
+}
+#[test]
+fn test_resolve_sym() {
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint GLCompute %main "main"
+        OpExecutionMode %main LocalSize 1 1 1
+        OpName %Light "Light"
+        OpMemberName %Light 0 "position"
+        OpMemberName %LightBlock 0 "count"
+        OpMemberName %LightBlock 1 "lights"
+        OpMemberName %LightBlock 2 "xform"
+        OpDecorate %LightBlock Block
+        OpMemberDecorate %LightBlock 0 Offset 0
+        OpMemberDecorate %LightBlock 1 Offset 16
+        OpMemberDecorate %LightBlock 2 Offset 80
+        OpMemberDecorate %LightBlock 2 ColMajor
+        OpMemberDecorate %LightBlock 2 MatrixStride 16
+        OpMemberDecorate %Light 0 Offset 0
+        OpMemberDecorate %Light 1 Offset 12
+        OpDecorate %light_arr ArrayStride 16
+        OpDecorate %light_blk DescriptorSet 0
+        OpDecorate %light_blk Binding 0
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %float = OpTypeFloat 32
+        %uint = OpTypeInt 32 0
+        %uint_4 = OpConstant %uint 4
+        %v3float = OpTypeVector %float 3
+        %v4float = OpTypeVector %float 4
+        %mat4 = OpTypeMatrix %v4float 4
+        %Light = OpTypeStruct %v3float %float
+        %light_arr = OpTypeArray %Light %uint_4
+        %LightBlock = OpTypeStruct %uint %light_arr %mat4
+        %LightBlock_ptr = OpTypePointer Uniform %LightBlock
+        %light_blk = OpVariable %LightBlock_ptr Uniform
+        %main = OpFunction %void None %fn
+        %entry = OpLabel
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let entry_points = ReflectConfig::new()
+        .spv(spv)
+        .ref_all_rscs(true)
+        .reflect()
+        .unwrap();
+    let ty = entry_points[0].vars[0].ty();
+
+    let sym = ty.resolve("lights[3].position").unwrap();
+    assert_eq!(sym.offset, 64);
+    assert!(sym.ty.is_vector());
+    assert_eq!(sym.stride, None);
+    let sym = ty.resolve("lights").unwrap();
+    assert_eq!(sym.offset, 16);
+    assert_eq!(sym.stride, Some(16));
+    // Unnamed members are selected by indices.
+    assert_eq!(ty.resolve("lights[2].1").unwrap().offset, 60);
+    let sym = ty.resolve("xform").unwrap();
+    assert_eq!((sym.offset, sym.stride), (80, Some(16)));
+    assert_eq!(ty.resolve("").unwrap().ty, ty);
+
+    assert!(ty.resolve("lights[4]").is_err());
+    assert!(ty.resolve("lights[1].color").is_err());
+    assert!(ty.resolve("count[0]").is_err());
+    assert!(ty.resolve("lights[1").is_err());
+    assert!(ty.resolve("lights..position").is_err());
+}
+#[test]
+fn test_name_collision_policy() {
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint GLCompute %main "main"
+        OpExecutionMode %main LocalSize 1 1 1
+        OpName %Data "Data"
+        OpName %Data "Data"
+        OpMemberName %Data 0 "x"
+        OpName %data "data"
+        OpName %data "data_1"
+        OpDecorate %Data Block
+        OpMemberDecorate %Data 0 Offset 0
+        OpDecorate %data DescriptorSet 0
+        OpDecorate %data Binding 0
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %float = OpTypeFloat 32
+        %Data = OpTypeStruct %float
+        %Data_ptr = OpTypePointer Uniform %Data
+        %data = OpVariable %Data_ptr Uniform
+        %main = OpFunction %void None %fn
+        %entry = OpLabel
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let reflect = |policy| {
+        ReflectConfig::new()
+            .spv(spv)
+            .ref_all_rscs(true)
+            .name_collision_policy(policy)
+            .reflect()
+    };
+    let entry_points = reflect(NameCollisionPolicy::FirstWins).unwrap();
+    assert_eq!(entry_points[0].vars[0].name(), Some("data"));
+    let entry_points = reflect(NameCollisionPolicy::LastWins).unwrap();
+    assert_eq!(entry_points[0].vars[0].name(), Some("data_1"));
+    let ty = entry_points[0].vars[0].ty().as_struct().unwrap();
+    assert_eq!(ty.name.as_deref(), Some("Data"));
+    let err = reflect(NameCollisionPolicy::Error).unwrap_err();
+    assert!(err.to_string().contains("`data` vs `data_1`"));
+}
+
+#[test]
+fn test_deco_collision_policy() {
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint GLCompute %main "main"
+        OpExecutionMode %main LocalSize 1 1 1
+        OpDecorate %Data Block
+        OpDecorate %Data Block
+        OpMemberDecorate %Data 0 Offset 0
+        OpMemberDecorate %Data 0 Offset 0
+        OpDecorate %data DescriptorSet 0
+        OpDecorate %data Binding 0
+        OpDecorate %data Binding 1
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %float = OpTypeFloat 32
+        %Data = OpTypeStruct %float
+        %Data_ptr = OpTypePointer Uniform %Data
+        %data = OpVariable %Data_ptr Uniform
+        %main = OpFunction %void None %fn
+        %entry = OpLabel
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let reflect = |policy| {
+        ReflectConfig::new()
+            .spv(spv)
+            .ref_all_rscs(true)
+            .deco_collision_policy(policy)
+            .reflect()
+    };
+    let desc_bind = |x: &Variable| match x {
+        Variable::Descriptor { desc_bind, .. } => *desc_bind,
+        _ => panic!("unexpected variable"),
+    };
+    let entry_points = reflect(DecorationCollisionPolicy::FirstWins).unwrap();
+    assert_eq!(
+        desc_bind(&entry_points[0].vars[0]),
+        DescriptorBinding::new(0, 0)
+    );
+    assert_eq!(
+        entry_points[0].diags,
+        vec![Diagnostic::DecorationCollision {
+            id: 3,
+            member_idx: None,
+            deco: spirv::Decoration::Binding,
+            operands: vec![0],
+            discarded: vec![1],
+        }]
+    );
+    let entry_points = reflect(DecorationCollisionPolicy::LastWins).unwrap();
+    assert_eq!(
+        desc_bind(&entry_points[0].vars[0]),
+        DescriptorBinding::new(0, 1)
+    );
+    assert_eq!(entry_points[0].diags.len(), 1);
+    let err = reflect(DecorationCollisionPolicy::Error).unwrap_err();
+    assert!(err.to_string().contains("decoration Binding at id 3"));
+}
+
+#[test]
+fn test_member_located_io_blocks() {
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint Vertex %main "main" %color %vout
+        OpName %vout "vout"
+        OpDecorate %color Location 0
+        OpMemberDecorate %Block 0 Location 2
+        OpMemberDecorate %Block 1 Location 3
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %float = OpTypeFloat 32
+        %v4 = OpTypeVector %float 4
+        %Block = OpTypeStruct %v4 %float
+        %v4_ptr = OpTypePointer Output %v4
+        %Block_ptr = OpTypePointer Output %Block
+        %color = OpVariable %v4_ptr Output
+        %vout = OpVariable %Block_ptr Output
+        %main = OpFunction %void None %fn
+        %entry = OpLabel
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let entry_points = ReflectConfig::new()
+        .spv(spv)
+        .ref_all_rscs(true)
+        .reflect()
+        .unwrap();
+    assert_eq!(entry_points[0].vars.len(), 1);
+    let entry_points = ReflectConfig::new()
+        .spv(spv)
+        .ref_all_rscs(true)
+        .member_located_io_blocks(true)
+        .reflect()
+        .unwrap();
+    assert_eq!(entry_points[0].vars.len(), 2);
+    let var = entry_points[0]
+        .vars
+        .iter()
+        .find(|x| x.name() == Some("vout"))
+        .unwrap();
+    if let Variable::Output { location, ty, .. } = var {
+        assert_eq!(*location, InterfaceLocation::new(2, 0));
+        let ty = ty.as_struct().unwrap();
+        assert!(ty.members.iter().all(|x| x.offset.is_none()));
+    } else {
+        panic!("unexpected variable");
+    }
+}
+
+#[test]
+fn test_auto_assign_locations() {
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint Vertex %main "main" %pos %a %b %c %vid %per_vertex
+        OpName %a "a"
+        OpName %b "b"
+        OpName %c "c"
+        OpDecorate %pos Location 1
+        OpDecorate %vid BuiltIn VertexIndex
+        OpDecorate %PerVertex Block
+        OpMemberDecorate %PerVertex 0 BuiltIn Position
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %int = OpTypeInt 32 1
+        %float = OpTypeFloat 32
+        %v2 = OpTypeVector %float 2
+        %v4 = OpTypeVector %float 4
+        %mat2 = OpTypeMatrix %v2 2
+        %PerVertex = OpTypeStruct %v4
+        %v4_in_ptr = OpTypePointer Input %v4
+        %mat2_in_ptr = OpTypePointer Input %mat2
+        %float_in_ptr = OpTypePointer Input %float
+        %int_in_ptr = OpTypePointer Input %int
+        %v4_out_ptr = OpTypePointer Output %v4
+        %PerVertex_ptr = OpTypePointer Output %PerVertex
+        %pos = OpVariable %v4_in_ptr Input
+        %a = OpVariable %mat2_in_ptr Input
+        %b = OpVariable %float_in_ptr Input
+        %c = OpVariable %v4_out_ptr Output
+        %vid = OpVariable %int_in_ptr Input
+        %per_vertex = OpVariable %PerVertex_ptr Output
+        %main = OpFunction %void None %fn
+        %entry = OpLabel
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let entry_points = ReflectConfig::new()
+        .spv(spv)
+        .ref_all_rscs(true)
+        .reflect()
+        .unwrap();
+    assert_eq!(entry_points[0].vars.len(), 1);
+    assert!(entry_points[0].implicit_locations.is_empty());
+
+    let entry_points = ReflectConfig::new()
+        .spv(spv)
+        .ref_all_rscs(true)
+        .auto_assign_locations(true)
+        .reflect()
+        .unwrap();
+    let entry = &entry_points[0];
+    assert_eq!(entry.vars.len(), 4);
+    let location = |name: &str| match entry.vars.iter().find(|x| x.name() == Some(name)) {
+        Some(Variable::Input { location, .. }) => InterfaceVariable::Input(*location),
+        Some(Variable::Output { location, .. }) => InterfaceVariable::Output(*location),
+        _ => panic!("unexpected variable"),
+    };
+    // `pos` takes location 1 and `a` takes 2 and 3.
+    assert_eq!(
+        location("a"),
+        InterfaceVariable::Input(InterfaceLocation::new(2, 0))
+    );
+    assert_eq!(
+        location("b"),
+        InterfaceVariable::Input(InterfaceLocation::new(4, 0))
+    );
+    assert_eq!(
+        location("c"),
+        InterfaceVariable::Output(InterfaceLocation::new(0, 0))
+    );
+    assert_eq!(
+        entry.implicit_locations,
+        [location("a"), location("b"), location("c")]
+            .iter()
+            .copied()
+            .collect()
+    );
+}
+
+#[test]
+fn test_pipeline_hints() {
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpCapability FragmentShadingRateKHR
+        OpCapability RuntimeDescriptorArray
+        OpExtension "SPV_KHR_fragment_shading_rate"
+        OpExtension "SPV_EXT_descriptor_indexing"
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint Vertex %main "main" %rate
+        OpDecorate %rate BuiltIn PrimitiveShadingRateKHR
+        OpDecorate %texs DescriptorSet 0
+        OpDecorate %texs Binding 0
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %int = OpTypeInt 32 1
+        %float = OpTypeFloat 32
+        %img = OpTypeImage %float 2D 0 0 0 1 Unknown
+        %imgs = OpTypeRuntimeArray %img
+        %imgs_ptr = OpTypePointer UniformConstant %imgs
+        %int_ptr = OpTypePointer Output %int
+        %texs = OpVariable %imgs_ptr UniformConstant
+        %rate = OpVariable %int_ptr Output
+        %int_0 = OpConstant %int 0
+        %main = OpFunction %void None %fn
+        %entry = OpLabel
+        OpStore %rate %int_0
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let caps = SpirvBinary::from(spv).quick_scan().unwrap().caps;
+    let entry_points = ReflectConfig::new()
+        .spv(spv)
+        .ref_all_rscs(true)
+        .reflect()
+        .unwrap();
+    let hints = PipelineHints::new(&caps, &entry_points);
+    assert_eq!(
+        hints,
+        PipelineHints {
+            uses_primitive_shading_rate: true,
+            benefits_from_descriptor_buffer: true,
+            ..Default::default()
+        }
+    );
+    // The capability alone doesn't mean the shading rate is written.
+    let unused_spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpCapability FragmentShadingRateKHR
+        OpExtension "SPV_KHR_fragment_shading_rate"
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint Vertex %main "main" %rate
+        OpDecorate %rate BuiltIn PrimitiveShadingRateKHR
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %int = OpTypeInt 32 1
+        %int_ptr = OpTypePointer Output %int
+        %rate = OpVariable %int_ptr Output
+        %main = OpFunction %void None %fn
+        %entry = OpLabel
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let caps = SpirvBinary::from(unused_spv).quick_scan().unwrap().caps;
+    let entry_points = ReflectConfig::new().spv(unused_spv).reflect().unwrap();
+    let hints = PipelineHints::new(&caps, &entry_points);
+    assert!(!hints.uses_primitive_shading_rate);
+    // Without pre-rasterization stages the shading rate can only be read.
+    let hints = PipelineHints::new(&[spirv::Capability::FragmentShadingRateKHR], &[]);
+    assert!(!hints.uses_primitive_shading_rate);
+}
+
+#[test]
+fn test_push_const_ranges() {
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint Vertex %vert "vert"
+        OpEntryPoint Fragment %frag "frag"
+        OpEntryPoint GLCompute %comp "comp"
+        OpExecutionMode %frag OriginUpperLeft
+        OpExecutionMode %comp LocalSize 1 1 1
+        OpDecorate %VertPc Block
+        OpMemberDecorate %VertPc 0 Offset 0
+        OpDecorate %FragPc Block
+        OpMemberDecorate %FragPc 0 Offset 16
+        OpMemberDecorate %FragPc 1 Offset 20
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %float = OpTypeFloat 32
+        %v4 = OpTypeVector %float 4
+        %VertPc = OpTypeStruct %v4
+        %FragPc = OpTypeStruct %float %float
+        %ptr_vert_pc = OpTypePointer PushConstant %VertPc
+        %ptr_frag_pc = OpTypePointer PushConstant %FragPc
+        %vert_pc = OpVariable %ptr_vert_pc PushConstant
+        %frag_pc = OpVariable %ptr_frag_pc PushConstant
+        %vert = OpFunction %void None %fn
+        %vert_entry = OpLabel
+        %vert_pc_val = OpLoad %VertPc %vert_pc
+        OpReturn
+        OpFunctionEnd
+        %frag = OpFunction %void None %fn
+        %frag_entry = OpLabel
+        %frag_pc_val = OpLoad %FragPc %frag_pc
+        OpReturn
+        OpFunctionEnd
+        %comp = OpFunction %void None %fn
+        %comp_entry = OpLabel
+        %comp_pc_val = OpLoad %FragPc %frag_pc
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let entry_points = ReflectConfig::new().spv(spv).reflect().unwrap();
+    let pipeline = PipelineConfig::new().merge(&entry_points).unwrap();
+    assert_eq!(
+        pipeline.push_const_ranges,
+        vec![
+            PushConstantRange {
+                offset: 0,
+                size: 16,
+                stages: vec![ExecutionModel::Vertex],
+            },
+            PushConstantRange {
+                offset: 16,
+                size: 8,
+                stages: vec![ExecutionModel::Fragment, ExecutionModel::GLCompute],
+            },
+        ]
+    );
+    assert_eq!(pipeline.push_const_ranges[1].vk_stage_flags(), 0x30);
+    let layout_desc = LayoutDescription::from_pipeline(&pipeline);
+    assert_eq!(
+        layout_desc.to_yaml(),
+        r#"version: 1
+sets: []
+push_constant_ranges:
+  - offset: 0
+    size: 16
+    stages: [vertex]
+  - offset: 16
+    size: 8
+    stages: [fragment, compute]
+"#
+    );
+}
+
+#[test]
+fn test_member_access_tys() {
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint GLCompute %main "main"
+        OpExecutionMode %main LocalSize 1 1 1
+        OpName %data "data"
+        OpName %rdata "rdata"
+        OpDecorate %Data Block
+        OpMemberDecorate %Data 0 Offset 0
+        OpMemberDecorate %Data 0 NonWritable
+        OpMemberDecorate %Data 1 Offset 4
+        OpMemberDecorate %Data 1 NonReadable
+        OpMemberDecorate %Data 2 Offset 8
+        OpDecorate %data DescriptorSet 0
+        OpDecorate %data Binding 0
+        OpDecorate %rdata DescriptorSet 0
+        OpDecorate %rdata Binding 1
+        OpDecorate %rdata NonWritable
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %float = OpTypeFloat 32
+        %Data = OpTypeStruct %float %float %float
+        %Data_ptr = OpTypePointer StorageBuffer %Data
+        %data = OpVariable %Data_ptr StorageBuffer
+        %rdata = OpVariable %Data_ptr StorageBuffer
+        %main = OpFunction %void None %fn
+        %entry = OpLabel
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let entry_points = ReflectConfig::new()
+        .spv(spv)
+        .ref_all_rscs(true)
+        .reflect()
+        .unwrap();
+    let member_access_tys = |name: &str| {
+        let var = entry_points[0]
+            .vars
+            .iter()
+            .find(|x| x.name() == Some(name))
+            .unwrap();
+        let ty = var.ty().as_struct().unwrap();
+        ty.members.iter().map(|x| x.access_ty).collect::<Vec<_>>()
+    };
+    assert_eq!(
+        member_access_tys("data"),
+        vec![
+            AccessType::ReadOnly,
+            AccessType::WriteOnly,
+            AccessType::ReadWrite
+        ]
+    );
+    // Access qualifiers of the variable apply to its members, but members
+    // decorated otherwise are kept.
+    assert_eq!(
+        member_access_tys("rdata"),
+        vec![
+            AccessType::ReadOnly,
+            AccessType::WriteOnly,
+            AccessType::ReadOnly
+        ]
+    );
+}
+
+#[test]
+fn test_push_constant_range() {
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint Vertex %vert "vert" %pc
+        OpEntryPoint Fragment %frag "frag" %pc %idx
+        OpEntryPoint GLCompute %comp "comp" %pc
+        OpExecutionMode %frag OriginUpperLeft
+        OpExecutionMode %comp LocalSize 1 1 1
+        OpDecorate %Pc Block
+        OpMemberDecorate %Pc 0 Offset 0
+        OpMemberDecorate %Pc 1 Offset 16
+        OpMemberDecorate %Pc 2 Offset 32
+        OpMemberDecorate %Pc 3 Offset 48
+        OpDecorate %arr ArrayStride 4
+        OpDecorate %idx Location 0
+        OpDecorate %idx Flat
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %float = OpTypeFloat 32
+        %int = OpTypeInt 32 1
+        %v4 = OpTypeVector %float 4
+        %int_1 = OpConstant %int 1
+        %int_2 = OpConstant %int 2
+        %int_3 = OpConstant %int 3
+        %int_4 = OpConstant %int 4
+        %arr = OpTypeArray %float %int_4
+        %Pc = OpTypeStruct %float %v4 %arr %float
+        %ptr_pc = OpTypePointer PushConstant %Pc
+        %ptr_pc_float = OpTypePointer PushConstant %float
+        %ptr_input_int = OpTypePointer Input %int
+        %pc = OpVariable %ptr_pc PushConstant
+        %idx = OpVariable %ptr_input_int Input
+        %vert = OpFunction %void None %fn
+        %vert_entry = OpLabel
+        %vert_ptr = OpAccessChain %ptr_pc_float %pc %int_1 %int_2
+        %vert_val = OpLoad %float %vert_ptr
+        OpReturn
+        OpFunctionEnd
+        %frag = OpFunction %void None %fn
+        %frag_entry = OpLabel
+        %idx_val = OpLoad %int %idx
+        %frag_ptr1 = OpAccessChain %ptr_pc_float %pc %int_2 %idx_val
+        %frag_val1 = OpLoad %float %frag_ptr1
+        %frag_ptr2 = OpAccessChain %ptr_pc_float %pc %int_3
+        %frag_val2 = OpLoad %float %frag_ptr2
+        OpReturn
+        OpFunctionEnd
+        %comp = OpFunction %void None %fn
+        %comp_entry = OpLabel
+        %comp_val = OpLoad %Pc %pc
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let entry_points = ReflectConfig::new().spv(spv).reflect().unwrap();
+    let vert = entry_points.iter().find(|x| x.name == "vert").unwrap();
+    let frag = entry_points.iter().find(|x| x.name == "frag").unwrap();
+    let comp = entry_points.iter().find(|x| x.name == "comp").unwrap();
+    assert_eq!(vert.push_const_bytes, Some(24..28));
+    assert_eq!(frag.push_const_bytes, Some(32..52));
+    assert_eq!(comp.push_const_bytes, Some(0..52));
+    assert_eq!(comp.push_constant_range(), Some(0..52));
+
+    let pipeline = PipelineConfig::new().merge(&entry_points).unwrap();
+    assert_eq!(
+        pipeline.push_const_ranges,
+        vec![
+            PushConstantRange {
+                offset: 0,
+                size: 52,
+                stages: vec![ExecutionModel::GLCompute],
+            },
+            PushConstantRange {
+                offset: 24,
+                size: 4,
+                stages: vec![ExecutionModel::Vertex],
+            },
+            PushConstantRange {
+                offset: 32,
+                size: 20,
+                stages: vec![ExecutionModel::Fragment],
+            },
+        ]
+    );
+    let member_stages = pipeline
+        .push_const_members
+        .iter()
+        .map(|x| (x.offset, x.size, x.stages.clone()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        member_stages,
+        vec![
+            (0, 4, vec![ExecutionModel::GLCompute]),
+            (
+                16,
+                16,
+                vec![ExecutionModel::Vertex, ExecutionModel::GLCompute]
+            ),
+            (
+                32,
+                16,
+                vec![ExecutionModel::Fragment, ExecutionModel::GLCompute]
+            ),
+            (
+                48,
+                4,
+                vec![ExecutionModel::Fragment, ExecutionModel::GLCompute]
+            ),
+        ]
+    );
+    assert_eq!(pipeline.push_const_members[1].vk_stage_flags(), 0x21);
+    assert_eq!(
+        pipeline.push_const_updates(),
+        vec![
+            PushConstantRange {
+                offset: 0,
+                size: 4,
+                stages: vec![ExecutionModel::GLCompute],
+            },
+            PushConstantRange {
+                offset: 16,
+                size: 16,
+                stages: vec![ExecutionModel::Vertex, ExecutionModel::GLCompute],
+            },
+            PushConstantRange {
+                offset: 32,
+                size: 20,
+                stages: vec![ExecutionModel::Fragment, ExecutionModel::GLCompute],
+            },
+        ]
+    );
+
+    // The entire block is read if function bodies are not scanned.
+    let entry_points = ReflectConfig::new()
+        .spv(spv)
+        .ref_all_rscs(true)
+        .reflect()
+        .unwrap();
+    assert!(entry_points
+        .iter()
+        .all(|x| x.push_const_bytes == Some(0..52)));
+}
+
+#[test]
+fn test_push_const_escaping_ptrs() {
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint GLCompute %copy "copy" %pc
+        OpEntryPoint GLCompute %call "call" %pc
+        OpExecutionMode %copy LocalSize 1 1 1
+        OpExecutionMode %call LocalSize 1 1 1
+        OpDecorate %Pc Block
+        OpMemberDecorate %Pc 0 Offset 0
+        OpMemberDecorate %Pc 1 Offset 16
+        OpMemberDecorate %Pc 2 Offset 32
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %float = OpTypeFloat 32
+        %int = OpTypeInt 32 1
+        %v4 = OpTypeVector %float 4
+        %int_0 = OpConstant %int 0
+        %int_1 = OpConstant %int 1
+        %int_2 = OpConstant %int 2
+        %Pc = OpTypeStruct %float %v4 %float
+        %ptr_pc = OpTypePointer PushConstant %Pc
+        %ptr_pc_v4 = OpTypePointer PushConstant %v4
+        %ptr_pc_float = OpTypePointer PushConstant %float
+        %read_fn = OpTypeFunction %float %ptr_pc_v4
+        %pc = OpVariable %ptr_pc PushConstant
+        %read = OpFunction %float None %read_fn
+        %param = OpFunctionParameter %ptr_pc_v4
+        %read_entry = OpLabel
+        %read_val = OpLoad %v4 %param
+        %read_x = OpCompositeExtract %float %read_val 0
+        OpReturnValue %read_x
+        OpFunctionEnd
+        %copy = OpFunction %void None %fn
+        %copy_entry = OpLabel
+        %direct_ptr = OpAccessChain %ptr_pc_float %pc %int_0
+        %direct_val = OpLoad %float %direct_ptr
+        %pc_copy = OpCopyObject %ptr_pc %pc
+        %copy_ptr = OpAccessChain %ptr_pc_float %pc_copy %int_2
+        %copy_val = OpLoad %float %copy_ptr
+        OpReturn
+        OpFunctionEnd
+        %call = OpFunction %void None %fn
+        %call_entry = OpLabel
+        %call_ptr = OpAccessChain %ptr_pc_v4 %pc %int_1
+        %call_val = OpFunctionCall %float %read %call_ptr
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let entry_points = ReflectConfig::new().spv(spv).reflect().unwrap();
+    // Pointers escaping the tracking of access chains can read the entire
+    // block.
+    for entry_point in entry_points.iter() {
+        assert_eq!(entry_point.push_const_bytes, Some(0..36));
+    }
+}
+
+#[test]
+fn test_input_attachments() {
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpCapability InputAttachment
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint Fragment %main "main"
+        OpExecutionMode %main OriginUpperLeft
+        OpName %gbuf "gbuf"
+        OpName %depth "depth"
+        OpDecorate %gbuf DescriptorSet 0
+        OpDecorate %gbuf Binding 1
+        OpDecorate %gbuf InputAttachmentIndex 1
+        OpDecorate %depth DescriptorSet 0
+        OpDecorate %depth Binding 0
+        OpDecorate %depth InputAttachmentIndex 0
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %float = OpTypeFloat 32
+        %uint = OpTypeInt 32 0
+        %uint_2 = OpConstant %uint 2
+        %gbuf_ty = OpTypeImage %uint SubpassData 0 0 0 2 Unknown
+        %gbuf_arr = OpTypeArray %gbuf_ty %uint_2
+        %gbuf_ptr = OpTypePointer UniformConstant %gbuf_arr
+        %gbuf = OpVariable %gbuf_ptr UniformConstant
+        %depth_ty = OpTypeImage %float SubpassData 0 0 1 2 Unknown
+        %depth_ptr = OpTypePointer UniformConstant %depth_ty
+        %depth = OpVariable %depth_ptr UniformConstant
+        %main = OpFunction %void None %fn
+        %entry = OpLabel
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_0
+    );
+    let entry_points = ReflectConfig::new()
+        .spv(spv)
+        .ref_all_rscs(true)
+        .reflect()
+        .unwrap();
+    let input_attms = entry_points[0].input_attachments();
+    let summary = input_attms
+        .iter()
+        .map(|x| {
+            (
+                x.input_attm_idx,
+                x.name.as_deref().unwrap(),
+                x.desc_bind.bind(),
+                x.bind_idx,
+                x.is_multisampled,
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        summary,
+        vec![
+            (0, "depth", 0, 0, true),
+            (1, "gbuf", 1, 0, false),
+            (2, "gbuf", 1, 1, false),
+        ]
+    );
+    assert_eq!(input_attms[0].scalar_ty, ty::ScalarType::f32());
+    assert_eq!(input_attms[1].scalar_ty, ty::ScalarType::u32());
+}
+
+#[test]
+fn test_writes_external_memory() {
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint GLCompute %ro "ro" %buf %out_img
+        OpEntryPoint GLCompute %atomic "atomic" %buf
+        OpEntryPoint GLCompute %img "img" %out_img
+        OpEntryPoint GLCompute %local "local" %shared
+        OpEntryPoint GLCompute %store "store" %buf
+        OpEntryPoint GLCompute %texel_add "texel_add" %out_img
+        OpExecutionMode %ro LocalSize 1 1 1
+        OpExecutionMode %atomic LocalSize 1 1 1
+        OpExecutionMode %img LocalSize 1 1 1
+        OpExecutionMode %local LocalSize 1 1 1
+        OpExecutionMode %store LocalSize 1 1 1
+        OpExecutionMode %texel_add LocalSize 1 1 1
+        OpDecorate %Buf Block
+        OpMemberDecorate %Buf 0 Offset 0
+        OpDecorate %buf DescriptorSet 0
+        OpDecorate %buf Binding 0
+        OpDecorate %out_img DescriptorSet 0
+        OpDecorate %out_img Binding 1
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %uint = OpTypeInt 32 0
+        %v2uint = OpTypeVector %uint 2
+        %v4uint = OpTypeVector %uint 4
+        %uint_0 = OpConstant %uint 0
+        %uint_1 = OpConstant %uint 1
+        %coord = OpConstantComposite %v2uint %uint_0 %uint_0
+        %texel = OpConstantComposite %v4uint %uint_0 %uint_0 %uint_0 %uint_0
+        %Buf = OpTypeStruct %uint
+        %buf_ptr = OpTypePointer StorageBuffer %Buf
+        %uint_ptr = OpTypePointer StorageBuffer %uint
+        %shared_ptr = OpTypePointer Workgroup %uint
+        %img_ty = OpTypeImage %uint 2D 0 0 0 2 R32ui
+        %img_ptr = OpTypePointer UniformConstant %img_ty
+        %texel_ptr = OpTypePointer Image %uint
+        %buf = OpVariable %buf_ptr StorageBuffer
+        %out_img = OpVariable %img_ptr UniformConstant
+        %shared = OpVariable %shared_ptr Workgroup
+        %ro = OpFunction %void None %fn
+        %ro_entry = OpLabel
+        %ro_ptr = OpAccessChain %uint_ptr %buf %uint_0
+        %ro_val = OpLoad %uint %ro_ptr
+        OpReturn
+        OpFunctionEnd
+        %add = OpFunction %void None %fn
+        %add_entry = OpLabel
+        %add_ptr = OpAccessChain %uint_ptr %buf %uint_0
+        %add_val = OpAtomicIAdd %uint %add_ptr %uint_1 %uint_0 %uint_1
+        OpReturn
+        OpFunctionEnd
+        %atomic = OpFunction %void None %fn
+        %atomic_entry = OpLabel
+        %atomic_call = OpFunctionCall %void %add
+        OpReturn
+        OpFunctionEnd
+        %img = OpFunction %void None %fn
+        %img_entry = OpLabel
+        %img_val = OpLoad %img_ty %out_img
+        OpImageWrite %img_val %coord %texel
+        OpReturn
+        OpFunctionEnd
+        %local = OpFunction %void None %fn
+        %local_entry = OpLabel
+        OpStore %shared %uint_1
+        OpReturn
+        OpFunctionEnd
+        %store = OpFunction %void None %fn
+        %store_entry = OpLabel
+        %store_ptr = OpAccessChain %uint_ptr %buf %uint_0
+        OpAtomicStore %store_ptr %uint_1 %uint_0 %uint_1
+        OpReturn
+        OpFunctionEnd
+        %texel_add = OpFunction %void None %fn
+        %texel_add_entry = OpLabel
+        %texel_add_ptr = OpImageTexelPointer %texel_ptr %out_img %coord %uint_0
+        %texel_add_val = OpAtomicIAdd %uint %texel_add_ptr %uint_1 %uint_0 %uint_1
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let entry_points = ReflectConfig::new().spv(spv).reflect().unwrap();
+    let writes = |name: &str| {
+        let entry_point = entry_points.iter().find(|x| x.name == name).unwrap();
+        entry_point.writes_external_memory
+    };
+    assert!(!writes("ro"));
+    assert!(writes("atomic"));
+    assert!(writes("img"));
+    assert!(!writes("local"));
+    assert!(writes("store"));
+    assert!(writes("texel_add"));
+
+    // Writable descriptors might be written if function bodies are not
+    // scanned.
+    let entry_points = ReflectConfig::new()
+        .spv(spv)
+        .ref_all_rscs(true)
+        .reflect()
+        .unwrap();
+    let ro = entry_points.iter().find(|x| x.name == "ro").unwrap();
+    assert!(ro.writes_external_memory);
+}
+
+#[test]
+fn test_shared_vars() {
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpCapability WorkgroupMemoryExplicitLayoutKHR
+        OpExtension "SPV_KHR_workgroup_memory_explicit_layout"
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint GLCompute %main "main" %tile %count
+        OpEntryPoint GLCompute %explicit "explicit" %a %b
+        OpExecutionMode %main LocalSize 64 1 1
+        OpExecutionMode %explicit LocalSize 64 1 1
+        OpName %tile "tile"
+        OpName %count "count"
+        OpDecorate %A Block
+        OpMemberDecorate %A 0 Offset 0
+        OpDecorate %B Block
+        OpMemberDecorate %B 0 Offset 0
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %float = OpTypeFloat 32
+        %uint = OpTypeInt 32 0
+        %v4float = OpTypeVector %float 4
+        %uint_0 = OpConstant %uint 0
+        %uint_64 = OpConstant %uint 64
+        %tile_ty = OpTypeArray %float %uint_64
+        %A = OpTypeStruct %uint
+        %B = OpTypeStruct %v4float
+        %tile_ptr = OpTypePointer Workgroup %tile_ty
+        %count_ptr = OpTypePointer Workgroup %uint
+        %a_ptr = OpTypePointer Workgroup %A
+        %b_ptr = OpTypePointer Workgroup %B
+        %float_ptr = OpTypePointer Workgroup %float
+        %tile = OpVariable %tile_ptr Workgroup
+        %count = OpVariable %count_ptr Workgroup
+        %a = OpVariable %a_ptr Workgroup
+        %b = OpVariable %b_ptr Workgroup
+        %main = OpFunction %void None %fn
+        %main_entry = OpLabel
+        %tile_elem = OpAccessChain %float_ptr %tile %uint_0
+        %tile_val = OpLoad %float %tile_elem
+        OpStore %count %uint_0
+        OpReturn
+        OpFunctionEnd
+        %explicit = OpFunction %void None %fn
+        %explicit_entry = OpLabel
+        %a_val = OpLoad %A %a
+        %b_val = OpLoad %B %b
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let entry_points = ReflectConfig::new().spv(spv).reflect().unwrap();
+    let main = entry_points.iter().find(|x| x.name == "main").unwrap();
+    let shared_vars = main
+        .shared_vars
+        .iter()
+        .map(|x| (x.name.as_deref().unwrap(), x.nbyte, x.is_aliased))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        shared_vars,
+        vec![("tile", Some(256), false), ("count", Some(4), false)]
+    );
+    assert_eq!(main.shared_mem_nbyte(), 260);
+
+    let explicit = entry_points.iter().find(|x| x.name == "explicit").unwrap();
+    assert_eq!(explicit.shared_vars.len(), 2);
+    assert!(explicit.shared_vars.iter().all(|x| x.is_aliased));
+    assert_eq!(explicit.shared_mem_nbyte(), 16);
+}
+
+#[test]
+fn test_pool_simulation() {
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint Fragment %main "main" %ubo %texs %ssbo
+        OpExecutionMode %main OriginUpperLeft
+        OpDecorate %Ubo Block
+        OpMemberDecorate %Ubo 0 Offset 0
+        OpDecorate %ubo DescriptorSet 0
+        OpDecorate %ubo Binding 0
+        OpDecorate %texs DescriptorSet 0
+        OpDecorate %texs Binding 1
+        OpDecorate %Ssbo Block
+        OpMemberDecorate %Ssbo 0 Offset 0
+        OpDecorate %ssbo DescriptorSet 1
+        OpDecorate %ssbo Binding 0
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %float = OpTypeFloat 32
+        %uint = OpTypeInt 32 0
+        %uint_3 = OpConstant %uint 3
+        %Ubo = OpTypeStruct %float
+        %Ssbo = OpTypeStruct %float
+        %tex_ty = OpTypeImage %float 2D 0 0 0 1 Unknown
+        %sampled_tex_ty = OpTypeSampledImage %tex_ty
+        %texs_ty = OpTypeArray %sampled_tex_ty %uint_3
+        %ubo_ptr = OpTypePointer Uniform %Ubo
+        %ssbo_ptr = OpTypePointer StorageBuffer %Ssbo
+        %texs_ptr = OpTypePointer UniformConstant %texs_ty
+        %ubo = OpVariable %ubo_ptr Uniform
+        %ssbo = OpVariable %ssbo_ptr StorageBuffer
+        %texs = OpVariable %texs_ptr UniformConstant
+        %main = OpFunction %void None %fn
+        %entry = OpLabel
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let entry_points = ReflectConfig::new()
+        .spv(spv)
+        .ref_all_rscs(true)
+        .reflect()
+        .unwrap();
+    let layout_desc = LayoutDescription::from_entry_points(&entry_points).unwrap();
+    let mut pool_sizes = PoolSizes::new(4);
+    pool_sizes
+        .desc_count(6, 2)
+        .desc_count(1, 5)
+        .desc_count(7, 8);
+
+    let mut sim = PoolSimulator::new(pool_sizes.clone());
+    sim.alloc_n(&layout_desc.sets, 2);
+    let report = sim.report();
+    assert_eq!(report.npool, 2);
+    assert_eq!(report.nset, 4);
+    assert!(report.overflows.is_empty());
+    assert_eq!(report.wasted_nset, 2);
+    let wasted = report
+        .wasted_desc_counts
+        .iter()
+        .map(|(k, v)| (*k, *v))
+        .collect::<Vec<_>>();
+    assert_eq!(wasted, vec![(1, 2), (6, 1), (7, 7)]);
+    assert!((report.fragmentation() - 10.0 / 15.0).abs() < 1e-6);
+
+    // Pools without storage buffers can't hold set 1.
+    let mut pool_sizes = PoolSizes::new(4);
+    pool_sizes.desc_count(6, 2).desc_count(1, 5);
+    let mut sim = PoolSimulator::new(pool_sizes);
+    assert_eq!(sim.alloc(&layout_desc.sets[0]), Some(0));
+    assert_eq!(sim.alloc(&layout_desc.sets[1]), None);
+    assert_eq!(
+        sim.report().overflows,
+        vec![PoolOverflow {
+            set: 1,
+            vk_desc_ty: Some(7),
+            required: 1,
+            capacity: 0,
+        }]
+    );
+}
+
+#[test]
+fn test_private_vars() {
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint GLCompute %main "main" %counter %scale
+        OpExecutionMode %main LocalSize 1 1 1
+        OpName %counter "counter"
+        OpName %scale "scale"
+        OpName %unused "unused"
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %float = OpTypeFloat 32
+        %uint = OpTypeInt 32 0
+        %float_2 = OpConstant %float 2
+        %uint_ptr = OpTypePointer Private %uint
+        %float_ptr = OpTypePointer Private %float
+        %counter = OpVariable %uint_ptr Private
+        %scale = OpVariable %float_ptr Private %float_2
+        %unused = OpVariable %float_ptr Private
+        %main = OpFunction %void None %fn
+        %entry = OpLabel
+        %counter_val = OpLoad %uint %counter
+        %scale_val = OpLoad %float %scale
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let entry_points = ReflectConfig::new().spv(spv).reflect().unwrap();
+    assert!(entry_points[0].private_vars.is_empty());
+
+    let entry_points = ReflectConfig::new()
+        .spv(spv)
+        .private_vars(true)
+        .reflect()
+        .unwrap();
+    let private_vars = &entry_points[0].private_vars;
+    assert_eq!(private_vars.len(), 2);
+    assert_eq!(private_vars[0].name.as_deref(), Some("counter"));
+    assert_eq!(private_vars[0].ty, Type::Scalar(ty::ScalarType::u32()));
+    assert_eq!(private_vars[0].init, None);
+    assert_eq!(private_vars[1].name.as_deref(), Some("scale"));
+    assert_eq!(private_vars[1].init, Some(ConstantValue::from(2.0f32)));
+}
+
+#[test]
+fn test_pipeline_affecting_spec_ids() {
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint GLCompute %main "main" %tile %data
+        OpExecutionMode %main LocalSize 1 1 1
+        OpDecorate %wg_x SpecId 0
+        OpDecorate %tile_len SpecId 1
+        OpDecorate %scale SpecId 2
+        OpDecorate %wg_size BuiltIn WorkgroupSize
+        OpDecorate %Data Block
+        OpMemberDecorate %Data 0 Offset 0
+        OpDecorate %data DescriptorSet 0
+        OpDecorate %data Binding 0
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %float = OpTypeFloat 32
+        %uint = OpTypeInt 32 0
+        %v3uint = OpTypeVector %uint 3
+        %uint_0 = OpConstant %uint 0
+        %uint_1 = OpConstant %uint 1
+        %wg_x = OpSpecConstant %uint 64
+        %wg_size = OpSpecConstantComposite %v3uint %wg_x %uint_1 %uint_1
+        %tile_len = OpSpecConstant %uint 16
+        %scale = OpSpecConstant %float 1
+        %tile_ty = OpTypeArray %float %tile_len
+        %Data = OpTypeStruct %float
+        %tile_ptr = OpTypePointer Workgroup %tile_ty
+        %data_ptr = OpTypePointer StorageBuffer %Data
+        %float_wg_ptr = OpTypePointer Workgroup %float
+        %float_sb_ptr = OpTypePointer StorageBuffer %float
+        %tile = OpVariable %tile_ptr Workgroup
+        %data = OpVariable %data_ptr StorageBuffer
+        %main = OpFunction %void None %fn
+        %main_entry = OpLabel
+        %tile_elem = OpAccessChain %float_wg_ptr %tile %uint_0
+        %tile_val = OpLoad %float %tile_elem
+        %scaled = OpFMul %float %tile_val %scale
+        %data_elem = OpAccessChain %float_sb_ptr %data %uint_0
+        OpStore %data_elem %scaled
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let entry_points = ReflectConfig::new().spv(spv).reflect().unwrap();
+    let entry_point = &entry_points[0];
+    assert_eq!(
+        entry_point
+            .array_size_spec_ids
+            .iter()
+            .copied()
+            .collect::<Vec<_>>(),
+        vec![1]
+    );
+    assert_eq!(
+        entry_point
+            .pipeline_affecting_spec_ids()
+            .into_iter()
+            .collect::<Vec<_>>(),
+        vec![0, 1]
+    );
+    assert!(entry_point.spec_defaults.contains_key(&2));
+}
+
+#[test]
+fn test_walk_all() {
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint GLCompute %main "main" %pc %ubo %img
+        OpExecutionMode %main LocalSize 1 1 1
+        OpName %pc "pc"
+        OpName %ubo "ubo"
+        OpName %PC "PC"
+        OpMemberName %PC 0 "scale"
+        OpMemberName %PC 1 "offsets"
+        OpName %UBO "UBO"
+        OpMemberName %UBO 0 "xform"
+        OpDecorate %PC Block
+        OpMemberDecorate %PC 0 Offset 0
+        OpMemberDecorate %PC 1 Offset 16
+        OpDecorate %v4arr ArrayStride 16
+        OpDecorate %UBO Block
+        OpMemberDecorate %UBO 0 Offset 0
+        OpMemberDecorate %UBO 0 ColMajor
+        OpMemberDecorate %UBO 0 MatrixStride 16
+        OpDecorate %ubo DescriptorSet 0
+        OpDecorate %ubo Binding 1
+        OpDecorate %img DescriptorSet 0
+        OpDecorate %img Binding 2
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %float = OpTypeFloat 32
+        %uint = OpTypeInt 32 0
+        %v4float = OpTypeVector %float 4
+        %mat4 = OpTypeMatrix %v4float 4
+        %uint_2 = OpConstant %uint 2
+        %v4arr = OpTypeArray %v4float %uint_2
+        %PC = OpTypeStruct %float %v4arr
+        %UBO = OpTypeStruct %mat4
+        %img_ty = OpTypeImage %float 2D 0 0 0 1 Unknown
+        %pc_ptr = OpTypePointer PushConstant %PC
+        %ubo_ptr = OpTypePointer Uniform %UBO
+        %img_ptr = OpTypePointer UniformConstant %img_ty
+        %pc = OpVariable %pc_ptr PushConstant
+        %ubo = OpVariable %ubo_ptr Uniform
+        %img = OpVariable %img_ptr UniformConstant
+        %main = OpFunction %void None %fn
+        %main_entry = OpLabel
+        %pc_val = OpLoad %PC %pc
+        %ubo_val = OpLoad %UBO %ubo
+        %img_val = OpLoad %img_ty %img
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let entry_points = ReflectConfig::new().spv(spv).reflect().unwrap();
+    let routes = entry_points[0]
+        .walk_all()
+        .map(|x| {
+            let sym = x
+                .route
+                .sym
+                .iter()
+                .map(|seg| format!("{:?}", seg))
+                .collect::<Vec<_>>()
+                .join(".");
+            (x.owner, x.var_name, sym, x.route.offset)
+        })
+        .collect::<Vec<_>>();
+    let desc_bind = DescriptorBinding::new(0, 1);
+    assert_eq!(
+        routes,
+        vec![
+            (RouteOwner::PushConstant, Some("pc"), "scale".to_owned(), 0),
+            (
+                RouteOwner::PushConstant,
+                Some("pc"),
+                "offsets.0".to_owned(),
+                16
+            ),
+            (
+                RouteOwner::PushConstant,
+                Some("pc"),
+                "offsets.1".to_owned(),
+                32
+            ),
+            (
+                RouteOwner::Descriptor(desc_bind),
+                Some("ubo"),
+                "xform".to_owned(),
+                0
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_accel_struct_usage() {
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability RayTracingKHR
+        OpCapability RayQueryKHR
+        OpExtension "SPV_KHR_ray_tracing"
+        OpExtension "SPV_KHR_ray_query"
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint RayGenerationKHR %main "main" %scene %probe %payload
+        OpDecorate %scene DescriptorSet 0
+        OpDecorate %scene Binding 0
+        OpDecorate %probe DescriptorSet 0
+        OpDecorate %probe Binding 1
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %uint = OpTypeInt 32 0
+        %float = OpTypeFloat 32
+        %v3float = OpTypeVector %float 3
+        %v4float = OpTypeVector %float 4
+        %uint_0 = OpConstant %uint 0
+        %uint_255 = OpConstant %uint 255
+        %float_0 = OpConstant %float 0
+        %float_1 = OpConstant %float 1
+        %origin = OpConstantComposite %v3float %float_0 %float_0 %float_0
+        %dir = OpConstantComposite %v3float %float_0 %float_0 %float_1
+        %accel = OpTypeAccelerationStructureNV
+        %ptr_accel = OpTypePointer UniformConstant %accel
+        %scene = OpVariable %ptr_accel UniformConstant
+        %probe = OpVariable %ptr_accel UniformConstant
+        %ptr_payload = OpTypePointer RayPayloadKHR %v4float
+        %payload = OpVariable %ptr_payload RayPayloadKHR
+        %ray_query = OpTypeRayQueryKHR
+        %ptr_ray_query = OpTypePointer Function %ray_query
+        %main = OpFunction %void None %fn
+        %entry = OpLabel
+        %rq = OpVariable %ptr_ray_query Function
+        %scene_val = OpLoad %accel %scene
+        OpTraceRayKHR %scene_val %uint_0 %uint_255 %uint_0 %uint_0 %uint_0 %origin %float_0 %dir %float_1 %payload
+        %probe_val = OpLoad %accel %probe
+        OpRayQueryInitializeKHR %rq %probe_val %uint_0 %uint_255 %origin %float_0 %dir %float_1
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let entry_points = ReflectConfig::new().spv(spv).reflect().unwrap();
+    let desc_infos = &entry_points[0].desc_infos;
+    let scene = &desc_infos[&DescriptorBinding::new(0, 0)];
+    assert!(scene.uses_trace_ray);
+    assert!(!scene.uses_ray_query);
+    let probe = &desc_infos[&DescriptorBinding::new(0, 1)];
+    assert!(!probe.uses_trace_ray);
+    assert!(probe.uses_ray_query);
+}
+
+#[test]
+fn test_reflect_full() {
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint GLCompute %main "main" %data
+        OpExecutionMode %main LocalSize 1 1 1
+        OpName %scale "scale"
+        OpDecorate %scale SpecId 3
+        OpDecorate %Data Block
+        OpMemberDecorate %Data 0 Offset 0
+        OpDecorate %data DescriptorSet 0
+        OpDecorate %data Binding 0
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %float = OpTypeFloat 32
+        %uint = OpTypeInt 32 0
+        %uint_0 = OpConstant %uint 0
+        %scale = OpSpecConstant %float 2.0
+        %Data = OpTypeStruct %float
+        %data_ptr = OpTypePointer StorageBuffer %Data
+        %float_ptr = OpTypePointer StorageBuffer %float
+        %data = OpVariable %data_ptr StorageBuffer
+        %main = OpFunction %void None %fn
+        %main_entry = OpLabel
+        %elem = OpAccessChain %float_ptr %data %uint_0
+        OpStore %elem %scale
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let reflection = ReflectConfig::new().spv(spv).reflect_full().unwrap();
+    let entry_points = ReflectConfig::new().spv(spv).reflect().unwrap();
+    assert_eq!(reflection.module_info.version, SpirvVersion::new(1, 5));
+    assert_eq!(reflection.module_info.caps, vec![spirv::Capability::Shader]);
+    assert_eq!(
+        format!("{:?}", reflection.entry_points),
+        format!("{:?}", entry_points)
+    );
+    assert!(reflection.entry_point("main").is_some());
+    assert!(reflection.entry_point("other").is_none());
+
+    let scale = reflection.spec_const(3).unwrap();
+    assert_eq!(scale.name.as_deref(), Some("scale"));
+    assert_eq!(scale.value, ConstantValue::from(2.0f32));
+    assert!(reflection.consts.values().any(|x| x.spec_id.is_none()));
+    assert!(reflection
+        .tys
+        .values()
+        .any(|x| matches!(**x, Type::Struct(_))));
+    assert!(reflection.diags.is_empty());
+    let entry_points: Vec<EntryPoint> = reflection.into();
+    assert_eq!(entry_points.len(), 1);
+}
+
+#[test]
+fn test_demangle() {
+    assert_eq!(demangle("_S4main").as_deref(), Some("main"));
+    assert_eq!(
+        demangle("_S6shader4mainp0pV").as_deref(),
+        Some("shader::main")
+    );
+    assert_eq!(demangle("_Z4mainv").as_deref(), Some("main"));
+    assert_eq!(
+        demangle("_ZN6shader4mainEv").as_deref(),
+        Some("shader::main")
+    );
+    assert_eq!(demangle("?main@@YAXXZ").as_deref(), Some("main"));
+    assert_eq!(
+        demangle("?main@shader@@YAXXZ").as_deref(),
+        Some("shader::main")
+    );
+    assert_eq!(demangle("main"), None);
+    assert_eq!(demangle("_Z"), None);
+    assert_eq!(demangle("_ZN6shader4main"), None);
+    assert_eq!(demangle("_S9main"), None);
+
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint GLCompute %main "_ZN6shader4mainEv"
+        OpEntryPoint GLCompute %plain "plain"
+        OpExecutionMode %main LocalSize 1 1 1
+        OpExecutionMode %plain LocalSize 1 1 1
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %main = OpFunction %void None %fn
+        %main_entry = OpLabel
+        OpReturn
+        OpFunctionEnd
+        %plain = OpFunction %void None %fn
+        %plain_entry = OpLabel
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let entry_points = ReflectConfig::new().spv(spv).reflect().unwrap();
+    let names = entry_points
+        .iter()
+        .map(|x| (x.name.as_str(), x.display_name()))
+        .collect::<std::collections::BTreeSet<_>>();
+    let expected = [("_ZN6shader4mainEv", "shader::main"), ("plain", "plain")];
+    assert_eq!(names, expected.iter().copied().collect());
+
+    let entry_points = ReflectConfig::new()
+        .spv(spv)
+        .demangler(|x| x.strip_prefix("pl").map(|x| x.to_uppercase()))
+        .reflect()
+        .unwrap();
+    let demangled_names = entry_points
+        .iter()
+        .map(|x| x.demangled_name.as_deref())
+        .collect::<std::collections::BTreeSet<_>>();
+    assert_eq!(
+        demangled_names,
+        [None, Some("AIN")].iter().copied().collect()
+    );
+}
+
+#[test]
+fn test_mesh_shader() {
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability MeshShadingEXT
+        OpExtension "SPV_EXT_mesh_shader"
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint TaskEXT %task "task" %task_payload
+        OpEntryPoint MeshEXT %mesh "mesh" %mesh_payload %pos %prim_id %indices %color
+        OpExecutionMode %task LocalSize 32 1 1
+        OpExecutionMode %mesh LocalSize 32 1 1
+        OpExecutionMode %mesh OutputVertices 64
+        OpExecutionMode %mesh OutputPrimitivesEXT 126
+        OpExecutionMode %mesh OutputTrianglesEXT
+        OpName %task_payload "task_payload"
+        OpName %mesh_payload "mesh_payload"
+        OpName %Payload "Payload"
+        OpMemberName %Payload 0 "meshlet_ids"
+        OpDecorate %pos BuiltIn Position
+        OpDecorate %prim_id BuiltIn PrimitiveId
+        OpDecorate %prim_id PerPrimitiveEXT
+        OpDecorate %indices BuiltIn PrimitiveTriangleIndicesEXT
+        OpDecorate %color Location 0
+        OpDecorate %color PerPrimitiveEXT
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %float = OpTypeFloat 32
+        %uint = OpTypeInt 32 0
+        %int = OpTypeInt 32 1
+        %v3uint = OpTypeVector %uint 3
+        %v4float = OpTypeVector %float 4
+        %uint_0 = OpConstant %uint 0
+        %uint_1 = OpConstant %uint 1
+        %uint_32 = OpConstant %uint 32
+        %uint_64 = OpConstant %uint 64
+        %uint_126 = OpConstant %uint 126
+        %ids_ty = OpTypeArray %uint %uint_32
+        %Payload = OpTypeStruct %ids_ty
+        %payload_ptr = OpTypePointer TaskPayloadWorkgroupEXT %Payload
+        %task_payload = OpVariable %payload_ptr TaskPayloadWorkgroupEXT
+        %mesh_payload = OpVariable %payload_ptr TaskPayloadWorkgroupEXT
+        %uint_payload_ptr = OpTypePointer TaskPayloadWorkgroupEXT %uint
+        %v4float_ptr = OpTypePointer Output %v4float
+        %int_ptr = OpTypePointer Output %int
+        %v3uint_ptr = OpTypePointer Output %v3uint
+        %int_0 = OpConstant %int 0
+        %float_0 = OpConstant %float 0
+        %zero4 = OpConstantComposite %v4float %float_0 %float_0 %float_0 %float_0
+        %zero3 = OpConstantComposite %v3uint %uint_0 %uint_0 %uint_0
+        %pos_ty = OpTypeArray %v4float %uint_64
+        %pos_ptr = OpTypePointer Output %pos_ty
+        %pos = OpVariable %pos_ptr Output
+        %prim_id_ty = OpTypeArray %int %uint_126
+        %prim_id_ptr = OpTypePointer Output %prim_id_ty
+        %prim_id = OpVariable %prim_id_ptr Output
+        %indices_ty = OpTypeArray %v3uint %uint_126
+        %indices_ptr = OpTypePointer Output %indices_ty
+        %indices = OpVariable %indices_ptr Output
+        %color_ty = OpTypeArray %v4float %uint_126
+        %color_ptr = OpTypePointer Output %color_ty
+        %color = OpVariable %color_ptr Output
+        %task = OpFunction %void None %fn
+        %task_entry = OpLabel
+        %task_id = OpAccessChain %uint_payload_ptr %task_payload %uint_0 %uint_0
+        OpStore %task_id %uint_0
+        OpEmitMeshTasksEXT %uint_1 %uint_1 %uint_1 %task_payload
+        OpFunctionEnd
+        %mesh = OpFunction %void None %fn
+        %mesh_entry = OpLabel
+        OpSetMeshOutputsEXT %uint_64 %uint_126
+        %mesh_id = OpAccessChain %uint_payload_ptr %mesh_payload %uint_0 %uint_0
+        %id = OpLoad %uint %mesh_id
+        %pos_0 = OpAccessChain %v4float_ptr %pos %uint_0
+        OpStore %pos_0 %zero4
+        %prim_id_0 = OpAccessChain %int_ptr %prim_id %uint_0
+        OpStore %prim_id_0 %int_0
+        %indices_0 = OpAccessChain %v3uint_ptr %indices %uint_0
+        OpStore %indices_0 %zero3
+        %color_0 = OpAccessChain %v4float_ptr %color %uint_0
+        OpStore %color_0 %zero4
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let entry_points = ReflectConfig::new().spv(spv).reflect().unwrap();
+    let task = entry_points.iter().find(|x| x.name == "task").unwrap();
+    let payload = task.task_payload.as_ref().unwrap();
+    assert_eq!(payload.name.as_deref(), Some("task_payload"));
+    assert_eq!(payload.nbyte, Some(128));
+    assert!(task.output_builtins.is_empty());
+    assert_eq!(task.mesh_outputs(), None);
+
+    let mesh = entry_points.iter().find(|x| x.name == "mesh").unwrap();
+    let payload = mesh.task_payload.as_ref().unwrap();
+    assert_eq!(payload.name.as_deref(), Some("mesh_payload"));
+    assert!(mesh.shared_vars.is_empty());
+    assert_eq!(
+        mesh.output_builtins.iter().copied().collect::<Vec<_>>(),
+        vec![
+            spirv::BuiltIn::Position,
+            spirv::BuiltIn::PrimitiveId,
+            spirv::BuiltIn::PrimitiveTriangleIndicesEXT,
+        ]
+    );
+    assert_eq!(
+        mesh.mesh_outputs(),
+        Some(MeshOutputs {
+            max_vertices: 64,
+            max_primitives: 126,
+            prim_ty: MeshPrimitiveType::Triangles,
+        })
+    );
+    let color = mesh
+        .vars
+        .iter()
+        .find(|x| matches!(x, Variable::Output { .. }))
+        .unwrap();
+    let decos = mesh.io_decorations(color).unwrap();
+    assert!(decos.contains_key(&spirv::Decoration::PerPrimitiveEXT));
+}
+
+#[test]
+fn test_fragment_interlock() {
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpCapability FragmentShaderPixelInterlockEXT
+        OpCapability FragmentShadingRateKHR
+        OpExtension "SPV_EXT_fragment_shader_interlock"
+        OpExtension "SPV_KHR_fragment_shading_rate"
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint Fragment %main "main" %rate
+        OpExecutionMode %main OriginUpperLeft
+        OpExecutionMode %main PixelInterlockOrderedEXT
+        OpDecorate %rate BuiltIn ShadingRateKHR
+        OpDecorate %rate Flat
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %int = OpTypeInt 32 1
+        %int_ptr = OpTypePointer Input %int
+        %rate = OpVariable %int_ptr Input
+        %main = OpFunction %void None %fn
+        %entry = OpLabel
+        OpBeginInvocationInterlockEXT
+        %rate_val = OpLoad %int %rate
+        OpEndInvocationInterlockEXT
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let entry_points = ReflectConfig::new().spv(spv).reflect().unwrap();
+    let entry_point = &entry_points[0];
+    let interlock = entry_point.fragment_interlock().unwrap();
+    assert_eq!(
+        interlock,
+        FragmentInterlock {
+            scope: InterlockScope::Pixel,
+            is_ordered: true,
+        }
+    );
+    assert_eq!(interlock.vk_feature(), "fragmentShaderPixelInterlock");
+    assert!(entry_point
+        .input_builtins
+        .contains(&spirv::BuiltIn::ShadingRateKHR));
+
+    let hints = PipelineHints::new(&[], &entry_points);
+    assert!(hints.reads_fragment_shading_rate);
+    assert!(!hints.uses_primitive_shading_rate);
+    assert_eq!(hints.fragment_interlock, Some(interlock));
+}
+
+#[test]
+fn test_pipeline_library_partition() {
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpCapability SampleRateShading
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint Vertex %vert "vert" %pos
+        OpEntryPoint Fragment %frag "frag" %sample_id %color
+        OpExecutionMode %frag OriginUpperLeft
+        OpName %pos "pos"
+        OpName %color "color"
+        OpName %ubo "ubo"
+        OpName %tex "tex"
+        OpDecorate %pos Location 0
+        OpDecorate %color Location 0
+        OpDecorate %sample_id BuiltIn SampleId
+        OpDecorate %sample_id Flat
+        OpDecorate %Data Block
+        OpMemberDecorate %Data 0 Offset 0
+        OpDecorate %ubo DescriptorSet 0
+        OpDecorate %ubo Binding 0
+        OpDecorate %tex DescriptorSet 0
+        OpDecorate %tex Binding 1
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %int = OpTypeInt 32 1
+        %float = OpTypeFloat 32
+        %v4float = OpTypeVector %float 4
+        %Data = OpTypeStruct %v4float
+        %Data_ptr = OpTypePointer Uniform %Data
+        %ubo = OpVariable %Data_ptr Uniform
+        %img = OpTypeImage %float 2D 0 0 0 1 Unknown
+        %simg = OpTypeSampledImage %img
+        %simg_ptr = OpTypePointer UniformConstant %simg
+        %tex = OpVariable %simg_ptr UniformConstant
+        %v4float_in_ptr = OpTypePointer Input %v4float
+        %v4float_out_ptr = OpTypePointer Output %v4float
+        %int_in_ptr = OpTypePointer Input %int
+        %pos = OpVariable %v4float_in_ptr Input
+        %color = OpVariable %v4float_out_ptr Output
+        %sample_id = OpVariable %int_in_ptr Input
+        %zero = OpConstantNull %v4float
+        %vert = OpFunction %void None %fn
+        %vert_entry = OpLabel
+        %pos_val = OpLoad %v4float %pos
+        %ubo_val = OpLoad %Data %ubo
+        OpReturn
+        OpFunctionEnd
+        %frag = OpFunction %void None %fn
+        %frag_entry = OpLabel
+        %sample_id_val = OpLoad %int %sample_id
+        %ubo_val2 = OpLoad %Data %ubo
+        %tex_val = OpLoad %simg %tex
+        OpStore %color %zero
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let entry_points = ReflectConfig::new().spv(spv).reflect().unwrap();
+    let pipeline = PipelineConfig::new().merge(&entry_points).unwrap();
+    let partition = PipelineLibraryPartition::new(&pipeline, &entry_points);
+    assert_eq!(
+        partition
+            .stages
+            .get(&PipelineLibraryPart::PreRasterizationShaders),
+        Some(&vec![ExecutionModel::Vertex])
+    );
+    assert_eq!(
+        partition.stages.get(&PipelineLibraryPart::FragmentShader),
+        Some(&vec![ExecutionModel::Fragment])
+    );
+    assert_eq!(partition.vertex_inputs.len(), 1);
+    assert_eq!(partition.vertex_inputs[0].name(), Some("pos"));
+    assert_eq!(partition.fragment_outputs.len(), 1);
+    assert_eq!(partition.fragment_outputs[0].name(), Some("color"));
+    assert_eq!(partition.desc_vk_flags(&DescriptorBinding::new(0, 0)), 0x6);
+    assert_eq!(partition.desc_vk_flags(&DescriptorBinding::new(0, 1)), 0x4);
+    assert_eq!(partition.desc_vk_flags(&DescriptorBinding::new(0, 2)), 0);
+    assert!(partition.push_const_parts.is_empty());
+    assert!(partition.requires_matching_multisample_state);
+}
+
+#[test]
+fn test_push_const_budget() {
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint Vertex %vert "vert" %pc
+        OpEntryPoint Fragment %frag "frag" %pc
+        OpExecutionMode %frag OriginUpperLeft
+        OpName %Pc "Pc"
+        OpMemberName %Pc 0 "scale"
+        OpMemberName %Pc 1 "weights"
+        OpMemberName %Pc 2 "color"
+        OpDecorate %Pc Block
+        OpMemberDecorate %Pc 0 Offset 0
+        OpMemberDecorate %Pc 1 Offset 120
+        OpMemberDecorate %Pc 2 Offset 240
+        OpDecorate %arr ArrayStride 4
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %float = OpTypeFloat 32
+        %int = OpTypeInt 32 1
+        %v4 = OpTypeVector %float 4
+        %int_0 = OpConstant %int 0
+        %int_4 = OpConstant %int 4
+        %arr = OpTypeArray %float %int_4
+        %Pc = OpTypeStruct %float %arr %v4
+        %ptr_pc = OpTypePointer PushConstant %Pc
+        %ptr_pc_float = OpTypePointer PushConstant %float
+        %pc = OpVariable %ptr_pc PushConstant
+        %vert = OpFunction %void None %fn
+        %vert_entry = OpLabel
+        %vert_val = OpLoad %Pc %pc
+        OpReturn
+        OpFunctionEnd
+        %frag = OpFunction %void None %fn
+        %frag_entry = OpLabel
+        %frag_ptr = OpAccessChain %ptr_pc_float %pc %int_0
+        %frag_val = OpLoad %float %frag_ptr
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let entry_points = ReflectConfig::new().spv(spv).reflect().unwrap();
+    let pipeline = PipelineConfig::new().merge(&entry_points).unwrap();
+    assert_eq!(
+        pipeline.diags,
+        vec![
+            Diagnostic::PushConstantOverBudget {
+                member: Some("weights".to_owned()),
+                offset: 120,
+                size: 16,
+                max_size: 128,
+                stages: vec![ExecutionModel::Vertex],
+            },
+            Diagnostic::PushConstantOverBudget {
+                member: Some("color".to_owned()),
+                offset: 240,
+                size: 16,
+                max_size: 128,
+                stages: vec![ExecutionModel::Vertex],
+            },
+        ]
+    );
+
+    let pipeline = PipelineConfig::new()
+        .max_push_const_size(256)
+        .merge(&entry_points)
+        .unwrap();
+    assert!(pipeline.diags.is_empty());
+    // Merged pipelines can be checked against other budgets.
+    let diags = pipeline.validate_push_const_budget(128);
+    assert_eq!(diags.len(), 2);
+    let diags = pipeline.validate_push_const_budget(200);
+    assert_eq!(
+        diags,
+        vec![Diagnostic::PushConstantOverBudget {
+            member: Some("color".to_owned()),
+            offset: 240,
+            size: 16,
+            max_size: 200,
+            stages: vec![ExecutionModel::Vertex],
+        }]
+    );
+
+    let err = PipelineConfig::new()
+        .strict(true)
+        .merge(&entry_points)
+        .unwrap_err();
+    assert!(err.to_string().contains("`weights`"));
+}
+
+#[test]
+fn test_accel_struct_usage_stages() {
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability RayTracingKHR
+        OpCapability RayQueryKHR
+        OpExtension "SPV_KHR_ray_tracing"
+        OpExtension "SPV_KHR_ray_query"
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint RayGenerationKHR %rgen "rgen" %scene %payload
+        OpEntryPoint ClosestHitKHR %rchit "rchit" %scene
+        OpDecorate %scene DescriptorSet 0
+        OpDecorate %scene Binding 0
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %uint = OpTypeInt 32 0
+        %float = OpTypeFloat 32
+        %v3float = OpTypeVector %float 3
+        %v4float = OpTypeVector %float 4
+        %uint_0 = OpConstant %uint 0
+        %uint_255 = OpConstant %uint 255
+        %float_0 = OpConstant %float 0
+        %float_1 = OpConstant %float 1
+        %origin = OpConstantComposite %v3float %float_0 %float_0 %float_0
+        %dir = OpConstantComposite %v3float %float_0 %float_0 %float_1
+        %accel = OpTypeAccelerationStructureKHR
+        %ptr_accel = OpTypePointer UniformConstant %accel
+        %scene = OpVariable %ptr_accel UniformConstant
+        %ptr_payload = OpTypePointer RayPayloadKHR %v4float
+        %payload = OpVariable %ptr_payload RayPayloadKHR
+        %ray_query = OpTypeRayQueryKHR
+        %ptr_ray_query = OpTypePointer Function %ray_query
+        %rgen = OpFunction %void None %fn
+        %rgen_entry = OpLabel
+        %scene_val = OpLoad %accel %scene
+        OpTraceRayKHR %scene_val %uint_0 %uint_255 %uint_0 %uint_0 %uint_0 %origin %float_0 %dir %float_1 %payload
+        OpReturn
+        OpFunctionEnd
+        %rchit = OpFunction %void None %fn
+        %rchit_entry = OpLabel
+        %rq = OpVariable %ptr_ray_query Function
+        %scene_val2 = OpLoad %accel %scene
+        OpRayQueryInitializeKHR %rq %scene_val2 %uint_0 %uint_255 %origin %float_0 %dir %float_1
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let entry_points = ReflectConfig::new().spv(spv).reflect().unwrap();
+    let pipeline = PipelineConfig::new().merge(&entry_points).unwrap();
+    let scene = &pipeline.descs[&DescriptorBinding::new(0, 0)];
+    assert_eq!(
+        scene.trace_ray_stages,
+        vec![ExecutionModel::RayGenerationKHR]
+    );
+    assert_eq!(scene.ray_query_stages, vec![ExecutionModel::ClosestHitKHR]);
+    assert_eq!(
+        pipeline.accel_struct_features(),
+        vec!["rayTracingPipeline", "rayQuery"]
+    );
+}
+
+#[test]
+fn test_exec_mode_id() {
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpCapability SubgroupDispatch
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint GLCompute %main "main"
+        OpExecutionModeId %main LocalSizeId %x %uint_1 %z
+        OpExecutionModeId %main SubgroupsPerWorkgroupId %nsubgroup
+        OpDecorate %x SpecId 3
+        OpDecorate %y SpecId 4
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %uint = OpTypeInt 32 0
+        %uint_1 = OpConstant %uint 1
+        %x = OpSpecConstant %uint 8
+        %y = OpSpecConstant %uint 2
+        %z = OpSpecConstantOp %uint 132 %y %uint_1
+        %nsubgroup = OpSpecConstantOp %uint 134 %x %y
+        %main = OpFunction %void None %fn
+        %entry = OpLabel
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let entry_points = ReflectConfig::new().spv(spv).reflect().unwrap();
+    let entry_point = &entry_points[0];
+    let local_size = entry_point
+        .exec_modes
+        .iter()
+        .find(|x| x.exec_mode == spirv::ExecutionMode::LocalSizeId)
+        .unwrap();
+    let values = local_size
+        .operands
+        .iter()
+        .map(|x| (x.value.clone(), x.spec_id))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        values,
+        vec![
+            (ConstantValue::U32(8), Some(3)),
+            (ConstantValue::U32(1), None),
+            (ConstantValue::U32(2), None),
+        ]
+    );
+    let nsubgroup = entry_point
+        .exec_modes
+        .iter()
+        .find(|x| x.exec_mode == spirv::ExecutionMode::SubgroupsPerWorkgroupId)
+        .unwrap();
+    assert_eq!(nsubgroup.operands[0].value, ConstantValue::U32(4));
+    let spec_ids = entry_point
+        .exec_mode_spec_ids
+        .iter()
+        .copied()
+        .collect::<Vec<_>>();
+    assert_eq!(spec_ids, vec![3, 4]);
+    assert_eq!(
+        entry_point.pipeline_affecting_spec_ids(),
+        entry_point.exec_mode_spec_ids
+    );
+}
+
+#[test]
+fn test_point_size_writes() {
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpCapability Geometry
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint Vertex %vert "vert" %vert_out
+        OpEntryPoint Geometry %geom "geom" %geom_out
+        OpExecutionMode %geom InputPoints
+        OpExecutionMode %geom Invocations 1
+        OpExecutionMode %geom OutputPoints
+        OpExecutionMode %geom OutputVertices 1
+        OpMemberDecorate %PerVertex 0 BuiltIn Position
+        OpMemberDecorate %PerVertex 1 BuiltIn PointSize
+        OpDecorate %PerVertex Block
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %int = OpTypeInt 32 1
+        %float = OpTypeFloat 32
+        %v4float = OpTypeVector %float 4
+        %int_0 = OpConstant %int 0
+        %int_1 = OpConstant %int 1
+        %float_1 = OpConstant %float 1.0
+        %zero = OpConstantNull %v4float
+        %PerVertex = OpTypeStruct %v4float %float
+        %PerVertex_ptr = OpTypePointer Output %PerVertex
+        %v4float_ptr = OpTypePointer Output %v4float
+        %float_ptr = OpTypePointer Output %float
+        %vert_out = OpVariable %PerVertex_ptr Output
+        %geom_out = OpVariable %PerVertex_ptr Output
+        %vert = OpFunction %void None %fn
+        %vert_entry = OpLabel
+        %vert_pos = OpAccessChain %v4float_ptr %vert_out %int_0
+        OpStore %vert_pos %zero
+        %vert_point_size = OpAccessChain %float_ptr %vert_out %int_1
+        OpStore %vert_point_size %float_1
+        OpReturn
+        OpFunctionEnd
+        %geom = OpFunction %void None %fn
+        %geom_entry = OpLabel
+        %geom_pos = OpAccessChain %v4float_ptr %geom_out %int_0
+        OpStore %geom_pos %zero
+        OpEmitVertex
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let entry_points = ReflectConfig::new().spv(spv).reflect().unwrap();
+    let vert = entry_points.iter().find(|x| x.name == "vert").unwrap();
+    assert!(vert.writes_point_size);
+    assert!(!vert.outputs_points());
+    assert!(!vert.misses_point_size());
+    let geom = entry_points.iter().find(|x| x.name == "geom").unwrap();
+    assert!(!geom.writes_point_size);
+    assert!(geom.outputs_points());
+    assert!(geom.misses_point_size());
+    // `PointSize` is declared in the block so it's considered written if
+    // function bodies are not scanned.
+    let entry_points = ReflectConfig::new()
+        .spv(spv)
+        .ref_all_rscs(true)
+        .reflect()
+        .unwrap();
+    assert!(entry_points.iter().all(|x| x.writes_point_size));
+}
+
+#[test]
+fn test_inject_point_size() {
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint Vertex %block_vert "block_vert" %block_out
+        OpEntryPoint Vertex %bare_vert "bare_vert" %pos
+        OpEntryPoint Fragment %frag "frag"
+        OpExecutionMode %frag OriginUpperLeft
+        OpMemberDecorate %PerVertex 0 BuiltIn Position
+        OpMemberDecorate %PerVertex 1 BuiltIn PointSize
+        OpDecorate %PerVertex Block
+        OpDecorate %pos BuiltIn Position
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %int = OpTypeInt 32 1
+        %float = OpTypeFloat 32
+        %v4float = OpTypeVector %float 4
+        %int_0 = OpConstant %int 0
+        %zero = OpConstantNull %v4float
+        %PerVertex = OpTypeStruct %v4float %float
+        %PerVertex_ptr = OpTypePointer Output %PerVertex
+        %v4float_ptr = OpTypePointer Output %v4float
+        %block_out = OpVariable %PerVertex_ptr Output
+        %pos = OpVariable %v4float_ptr Output
+        %block_vert = OpFunction %void None %fn
+        %block_vert_entry = OpLabel
+        %block_pos = OpAccessChain %v4float_ptr %block_out %int_0
+        OpStore %block_pos %zero
+        OpReturn
+        OpFunctionEnd
+        %bare_vert = OpFunction %void None %fn
+        %bare_vert_entry = OpLabel
+        OpStore %pos %zero
+        OpReturn
+        OpFunctionEnd
+        %frag = OpFunction %void None %fn
+        %frag_entry = OpLabel
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let spv = SpirvBinary::from(spv);
+    let entry_points = ReflectConfig::new().spv(spv.clone()).reflect().unwrap();
+    assert!(entry_points
+        .iter()
+        .filter(|x| x.exec_model == spirv::ExecutionModel::Vertex)
+        .all(|x| !x.writes_point_size));
+
+    // Nothing is patched unless requested.
+    let unpatched = Patcher::new().patch(&spv).unwrap();
+    assert_eq!(unpatched.words(), spv.words());
+
+    let patched = Patcher::new().inject_point_size(true).patch(&spv).unwrap();
+    let entry_points = ReflectConfig::new().spv(patched.clone()).reflect().unwrap();
+    let block_vert = entry_points
+        .iter()
+        .find(|x| x.name == "block_vert")
+        .unwrap();
+    assert!(block_vert.writes_point_size);
+    let bare_vert = entry_points.iter().find(|x| x.name == "bare_vert").unwrap();
+    assert!(bare_vert.writes_point_size);
+    assert!(bare_vert
+        .output_builtins
+        .contains(&spirv::BuiltIn::PointSize));
+    let frag = entry_points.iter().find(|x| x.name == "frag").unwrap();
+    assert!(!frag.writes_point_size);
+
+    // Patching is idempotent.
+    let repatched = Patcher::new()
+        .inject_point_size(true)
+        .patch(&patched)
+        .unwrap();
+    assert_eq!(repatched.words(), patched.words());
+}
+
+#[test]
+fn test_desc_used_elements() {
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint Fragment %main "main" %color
+        OpExecutionMode %main OriginUpperLeft
+        OpDecorate %color Location 0
+        OpDecorate %imgs DescriptorSet 0
+        OpDecorate %imgs Binding 0
+        OpDecorate %spec_imgs DescriptorSet 0
+        OpDecorate %spec_imgs Binding 1
+        OpDecorate %img DescriptorSet 0
+        OpDecorate %img Binding 2
+        OpDecorate %copied_imgs DescriptorSet 0
+        OpDecorate %copied_imgs Binding 3
+        OpDecorate %spec_idx SpecId 0
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %int = OpTypeInt 32 1
+        %uint = OpTypeInt 32 0
+        %float = OpTypeFloat 32
+        %v2float = OpTypeVector %float 2
+        %v4float = OpTypeVector %float 4
+        %int_1 = OpConstant %int 1
+        %uint_3 = OpConstant %uint 3
+        %uint_4 = OpConstant %uint 4
+        %float_0 = OpConstant %float 0.0
+        %spec_idx = OpSpecConstant %uint 0
+        %uv = OpConstantNull %v2float
+        %tex_ty = OpTypeImage %float 2D 0 0 0 1 Unknown
+        %stex_ty = OpTypeSampledImage %tex_ty
+        %stex_arr_ty = OpTypeArray %stex_ty %uint_4
+        %stex_ptr_ty = OpTypePointer UniformConstant %stex_ty
+        %stex_arr_ptr_ty = OpTypePointer UniformConstant %stex_arr_ty
+        %color_ptr_ty = OpTypePointer Output %v4float
+        %imgs = OpVariable %stex_arr_ptr_ty UniformConstant
+        %spec_imgs = OpVariable %stex_arr_ptr_ty UniformConstant
+        %img = OpVariable %stex_ptr_ty UniformConstant
+        %copied_imgs = OpVariable %stex_arr_ptr_ty UniformConstant
+        %color = OpVariable %color_ptr_ty Output
+        %sample_fn_ty = OpTypeFunction %v4float
+        %sample3 = OpFunction %v4float None %sample_fn_ty
+        %sample3_entry = OpLabel
+        %img3_ptr = OpAccessChain %stex_ptr_ty %imgs %uint_3
+        %img3 = OpLoad %stex_ty %img3_ptr
+        %texel3 = OpImageSampleExplicitLod %v4float %img3 %uv Lod %float_0
+        OpReturnValue %texel3
+        OpFunctionEnd
+        %main = OpFunction %void None %fn
+        %main_entry = OpLabel
+        %img1_ptr = OpAccessChain %stex_ptr_ty %imgs %int_1
+        %img1 = OpLoad %stex_ty %img1_ptr
+        %texel1 = OpImageSampleExplicitLod %v4float %img1 %uv Lod %float_0
+        %spec_img_ptr = OpAccessChain %stex_ptr_ty %spec_imgs %spec_idx
+        %spec_img = OpLoad %stex_ty %spec_img_ptr
+        %spec_texel = OpImageSampleExplicitLod %v4float %spec_img %uv Lod %float_0
+        %single = OpLoad %stex_ty %img
+        %single_texel = OpImageSampleExplicitLod %v4float %single %uv Lod %float_0
+        %texel3_ = OpFunctionCall %v4float %sample3
+        %imgs_copy = OpCopyObject %stex_arr_ptr_ty %copied_imgs
+        %copied_img_ptr = OpAccessChain %stex_ptr_ty %imgs_copy %int_1
+        %copied_img = OpLoad %stex_ty %copied_img_ptr
+        %copied_texel = OpImageSampleExplicitLod %v4float %copied_img %uv Lod %float_0
+        %sum0 = OpFAdd %v4float %texel1 %texel3_
+        %sum1 = OpFAdd %v4float %sum0 %spec_texel
+        %sum2 = OpFAdd %v4float %sum1 %single_texel
+        %sum3 = OpFAdd %v4float %sum2 %copied_texel
+        OpStore %color %sum3
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let entry_points = ReflectConfig::new().spv(spv).reflect().unwrap();
+    let entry_point = &entry_points[0];
+    let used_elements = |bind| {
+        entry_point.desc_infos[&DescriptorBinding::new(0, bind)]
+            .used_elements
+            .clone()
+    };
+    assert_eq!(used_elements(0), UsedElements::Elements(vec![1, 3]));
+    assert!(used_elements(0).contains(3));
+    assert!(!used_elements(0).contains(2));
+    assert_eq!(used_elements(1), UsedElements::Unbounded);
+    assert_eq!(used_elements(2), UsedElements::Elements(vec![0]));
+    // Pointers to descriptor arrays can escape the tracking of access chains.
+    let entry_points = ReflectConfig::new()
+        .spv(spv)
+        .ref_all_rscs(true)
+        .track_static_use(true)
+        .reflect()
+        .unwrap();
+    let desc_info = &entry_points[0].desc_infos[&DescriptorBinding::new(0, 3)];
+    assert_eq!(desc_info.used_elements, UsedElements::Unbounded);
+}
+
+#[test]
+fn test_small_scalar_consts() {
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpCapability Float16
+        OpCapability Int16
+        OpCapability Int8
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint GLCompute %main "main"
+        OpExecutionMode %main LocalSize 1 1 1
+        OpName %arr_var "arr_var"
+        OpName %half_var "half_var"
+        OpName %short_var "short_var"
+        OpName %uchar_var "uchar_var"
+        OpName %char_var "char_var"
+        OpName %sum_var "sum_var"
+        OpName %prod_var "prod_var"
+        OpName %conv_var "conv_var"
+        OpName %shr_var "shr_var"
+        OpName %sconv_var "sconv_var"
+        OpName %udiv_var "udiv_var"
+        OpName %eq_var "eq_var"
+        OpDecorate %spec_ushort SpecId 1
+        OpDecorate %spec_char SpecId 2
+        OpDecorate %spec_float SpecId 3
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %half = OpTypeFloat 16
+        %float = OpTypeFloat 32
+        %ushort = OpTypeInt 16 0
+        %short = OpTypeInt 16 1
+        %uchar = OpTypeInt 8 0
+        %char = OpTypeInt 8 1
+        %int = OpTypeInt 32 1
+        %bool = OpTypeBool
+        %half_1_5 = OpConstant %half 1.5
+        %ushort_2 = OpConstant %ushort 2
+        %ushort_4 = OpConstant %ushort 4
+        %short_n3 = OpConstant %short -3
+        %short_n2 = OpConstant %short -2
+        %short_n1 = OpConstant %short -1
+        %short_2 = OpConstant %short 2
+        %short_14 = OpConstant %short 14
+        %ushort_max = OpConstant %ushort 65535
+        %uchar_200 = OpConstant %uchar 200
+        %char_n5 = OpConstant %char -5
+        %spec_ushort = OpSpecConstant %ushort 60000
+        %spec_char = OpSpecConstant %char -7
+        %spec_float = OpSpecConstant %float 2.0
+        %spec_sum = OpSpecConstantOp %ushort 128 %spec_ushort %ushort_4
+        %spec_prod = OpSpecConstantOp %ushort 132 %spec_ushort %ushort_2
+        %spec_conv = OpSpecConstantOp %half 115 %spec_float
+        %spec_shr = OpSpecConstantOp %short 194 %short_n1 %short_14
+        %spec_sconv = OpSpecConstantOp %int 114 %spec_ushort
+        %spec_udiv = OpSpecConstantOp %short 134 %short_n2 %short_2
+        %spec_eq = OpSpecConstantOp %bool 170 %short_n1 %ushort_max
+        %arr = OpTypeArray %half %ushort_4
+        %arr_ptr = OpTypePointer Private %arr
+        %half_ptr = OpTypePointer Private %half
+        %short_ptr = OpTypePointer Private %short
+        %uchar_ptr = OpTypePointer Private %uchar
+        %char_ptr = OpTypePointer Private %char
+        %ushort_ptr = OpTypePointer Private %ushort
+        %int_ptr = OpTypePointer Private %int
+        %bool_ptr = OpTypePointer Private %bool
+        %arr_var = OpVariable %arr_ptr Private
+        %half_var = OpVariable %half_ptr Private %half_1_5
+        %short_var = OpVariable %short_ptr Private %short_n3
+        %uchar_var = OpVariable %uchar_ptr Private %uchar_200
+        %char_var = OpVariable %char_ptr Private %char_n5
+        %sum_var = OpVariable %ushort_ptr Private %spec_sum
+        %prod_var = OpVariable %ushort_ptr Private %spec_prod
+        %conv_var = OpVariable %half_ptr Private %spec_conv
+        %shr_var = OpVariable %short_ptr Private %spec_shr
+        %sconv_var = OpVariable %int_ptr Private %spec_sconv
+        %udiv_var = OpVariable %short_ptr Private %spec_udiv
+        %eq_var = OpVariable %bool_ptr Private %spec_eq
+        %main = OpFunction %void None %fn
+        %entry = OpLabel
+        %arr_val = OpLoad %arr %arr_var
+        %half_val = OpLoad %half %half_var
+        %short_val = OpLoad %short %short_var
+        %uchar_val = OpLoad %uchar %uchar_var
+        %char_val = OpLoad %char %char_var
+        %sum_val = OpLoad %ushort %sum_var
+        %prod_val = OpLoad %ushort %prod_var
+        %conv_val = OpLoad %half %conv_var
+        %shr_val = OpLoad %short %shr_var
+        %sconv_val = OpLoad %int %sconv_var
+        %udiv_val = OpLoad %short %udiv_var
+        %eq_val = OpLoad %bool %eq_var
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let entry_points = ReflectConfig::new()
+        .spv(spv)
+        .private_vars(true)
+        .reflect()
+        .unwrap();
+    let entry_point = &entry_points[0];
+    let private_var = |name: &str| {
+        entry_point
+            .private_vars
+            .iter()
+            .find(|x| x.name.as_deref() == Some(name))
+            .unwrap()
+    };
+    match &private_var("arr_var").ty {
+        Type::Array(x) => assert_eq!(x.nelement, Some(4)),
+        x => panic!("unexpected type {:?}", x),
+    }
+    let half = |x: f32| ConstantValue::F16(OrderedFloat(FromPrimitive::from_f32(x).unwrap()));
+    assert_eq!(private_var("half_var").init, Some(half(1.5)));
+    assert_eq!(private_var("short_var").init, Some(ConstantValue::S16(-3)));
+    assert_eq!(private_var("uchar_var").init, Some(ConstantValue::U8(200)));
+    assert_eq!(private_var("char_var").init, Some(ConstantValue::S8(-5)));
+    // Folded as 32-bit integers and wrapped around.
+    assert_eq!(private_var("sum_var").init, Some(ConstantValue::U16(60004)));
+    assert_eq!(
+        private_var("prod_var").init,
+        Some(ConstantValue::U16(54464))
+    );
+    assert_eq!(private_var("conv_var").init, Some(half(2.0)));
+    // Operands are extended as the operation interprets them rather than by
+    // their declared signedness.
+    assert_eq!(private_var("shr_var").init, Some(ConstantValue::S16(3)));
+    assert_eq!(
+        private_var("sconv_var").init,
+        Some(ConstantValue::S32(-5536))
+    );
+    assert_eq!(
+        private_var("udiv_var").init,
+        Some(ConstantValue::S16(32767))
+    );
+    assert_eq!(private_var("eq_var").init, Some(ConstantValue::Bool(true)));
+    assert_eq!(
+        entry_point.spec_defaults.get(&1),
+        Some(&ConstantValue::U16(60000))
+    );
+    assert_eq!(
+        entry_point.spec_defaults.get(&2),
+        Some(&ConstantValue::S8(-7))
+    );
+}
+#[test]
+fn test_reflect_borrowed() {
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint GLCompute %main "main"
+        OpEntryPoint GLCompute %other "_Z5otherv"
+        OpExecutionMode %main LocalSize 1 1 1
+        OpExecutionMode %other LocalSize 1 1 1
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %main = OpFunction %void None %fn
+        %main_entry = OpLabel
+        OpReturn
+        OpFunctionEnd
+        %other = OpFunction %void None %fn
+        %other_entry = OpLabel
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let spv = SpirvBinary::from(spv);
+    let mut cfg = ReflectConfig::new();
+    let borrowed = cfg.reflect_borrowed(&spv).unwrap();
+    let owned = ReflectConfig::new().spv(spv.clone()).reflect().unwrap();
+    assert_eq!(borrowed.len(), 2);
+    for entry_point in borrowed.iter() {
+        assert!(matches!(entry_point.name, std::borrow::Cow::Borrowed(_)));
+        assert!(entry_point.entry_point.name.is_empty());
+    }
+    let other = borrowed.iter().find(|x| x.name == "_Z5otherv").unwrap();
+    assert_eq!(other.entry_point.demangled_name.as_deref(), Some("other"));
+    let mut borrowed = borrowed
+        .into_iter()
+        .map(|x| x.into_owned())
+        .collect::<Vec<_>>();
+    let mut owned = owned;
+    borrowed.sort_by(|a, b| a.name.cmp(&b.name));
+    owned.sort_by(|a, b| a.name.cmp(&b.name));
+    assert_eq!(borrowed, owned);
+}
+#[test]
+#[cfg(feature = "bumpalo")]
+fn test_reflect_in() {
+    let spv: &'static [u32] = inline_spirv!(
+        r#"
+        OpCapability Shader
+        OpMemoryModel Logical GLSL450
+        OpEntryPoint GLCompute %main "main"
+        OpExecutionMode %main LocalSize 1 1 1
+        OpName %used "used"
+        OpName %unused "unused"
+        OpDecorate %Data Block
+        OpMemberDecorate %Data 0 Offset 0
+        OpDecorate %used DescriptorSet 0
+        OpDecorate %used Binding 0
+        OpDecorate %unused DescriptorSet 0
+        OpDecorate %unused Binding 1
+        %void = OpTypeVoid
+        %fn = OpTypeFunction %void
+        %uint = OpTypeInt 32 0
+        %uint_0 = OpConstant %uint 0
+        %uint_1 = OpConstant %uint 1
+        %Data = OpTypeStruct %uint
+        %Data_ptr = OpTypePointer StorageBuffer %Data
+        %uint_ptr = OpTypePointer StorageBuffer %uint
+        %write_fn = OpTypeFunction %void %Data_ptr
+        %used = OpVariable %Data_ptr StorageBuffer
+        %unused = OpVariable %Data_ptr StorageBuffer
+        %write = OpFunction %void None %write_fn
+        %param = OpFunctionParameter %Data_ptr
+        %write_entry = OpLabel
+        %ptr = OpAccessChain %uint_ptr %param %uint_0
+        OpStore %ptr %uint_1
+        OpReturn
+        OpFunctionEnd
+        %main = OpFunction %void None %fn
+        %main_entry = OpLabel
+        %call = OpFunctionCall %void %write %used
+        OpReturn
+        OpFunctionEnd
+        "#,
+        spvasm,
+        vulkan1_2
+    );
+    let expected = ReflectConfig::new().spv(spv).reflect().unwrap();
+    let mut bump = bumpalo::Bump::new();
+    for _ in 0..2 {
+        let entry_points = ReflectConfig::new().spv(spv).reflect_in(&bump).unwrap();
+        assert!(bump.allocated_bytes() > 0);
+        assert_eq!(entry_points, expected);
+        let names = entry_points[0]
+            .vars
+            .iter()
+            .filter_map(|x| x.name())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["used"]);
+        assert!(entry_points[0].writes_external_memory);
+        bump.reset();
+    }
+}
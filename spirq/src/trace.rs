@@ -0,0 +1,37 @@
+//! Optional instrumentation of reflection phases with `tracing` spans. The
+//! spans are only created with the `tracing` feature; otherwise they compile
+//! to nothing.
+//!
+//! Span durations can be collected with any `tracing` subscriber, e.g.
+//! `tracing_subscriber::fmt().with_span_events(FmtSpan::CLOSE)`.
+
+/// Guard of an entered span. The span is exited when the guard is dropped.
+#[cfg(feature = "tracing")]
+pub(crate) struct SpanGuard(pub(crate) tracing::span::EnteredSpan);
+#[cfg(not(feature = "tracing"))]
+pub(crate) struct SpanGuard;
+impl SpanGuard {
+    /// Record a count to a field declared in `trace_span!`.
+    pub(crate) fn record(&self, field: &'static str, value: usize) {
+        #[cfg(feature = "tracing")]
+        self.0.record(field, value);
+        #[cfg(not(feature = "tracing"))]
+        let _ = (field, value);
+    }
+    /// Exit the span before the guard goes out of scope.
+    pub(crate) fn exit(self) {}
+}
+
+/// Enter a debug-level span named `$name` with empty fields `$field`s to be
+/// recorded later.
+macro_rules! trace_span {
+    ($name:literal $(, $field:ident)*) => {{
+        #[cfg(feature = "tracing")]
+        let span = $crate::trace::SpanGuard(
+            tracing::debug_span!($name $(, $field = tracing::field::Empty)*).entered(),
+        );
+        #[cfg(not(feature = "tracing"))]
+        let span = $crate::trace::SpanGuard;
+        span
+    }};
+}
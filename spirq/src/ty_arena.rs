@@ -0,0 +1,274 @@
+//! Interning of reflected types.
+//!
+//! Types are interned into a [`TypeArena`] as they are declared while a
+//! module is parsed, see [`Reflection::tys`] and [`Reflection::ty_arena`].
+//! Equal types share an [`Arc<Type>`] node, which can be compared by
+//! identity with [`same_ty`] instead of walking the whole trees.
+//!
+//! Every reflected [`Variable`] still owns its [`Type`] tree because
+//! [`Variable`] is defined by `spq-core`. Interning the type of a variable
+//! into [`Reflection::ty_arena`] gives the shared node of the equal declared
+//! type without cloning it.
+//!
+//! Types can also be identified across modules by [`ty_hash`], a structural
+//! hash that doesn't change between runs, so tools like material editors can
+//! share data keyed by the struct definitions used by different shaders.
+//!
+//! [`Reflection::tys`]: crate::reflection::Reflection::tys
+//! [`Reflection::ty_arena`]: crate::reflection::Reflection::ty_arena
+use std::hash::Hasher;
+use std::sync::Arc;
+
+use fnv::{FnvHashSet as HashSet, FnvHasher};
+
+use crate::{
+    entry_point::EntryPoint,
+    ty::{AccessType, MatrixAxisOrder, ScalarType, Type},
+    var::Variable,
+};
+
+/// Interned types. Equal types interned by a same arena share a node.
+#[derive(Debug, Default, Clone)]
+pub struct TypeArena {
+    tys: HashSet<Arc<Type>>,
+}
+impl TypeArena {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Intern a type. The type is only cloned the first time it's seen.
+    pub fn intern(&mut self, ty: &Type) -> Arc<Type> {
+        if let Some(x) = self.tys.get(ty) {
+            return x.clone();
+        }
+        let x = Arc::new(ty.clone());
+        self.tys.insert(x.clone());
+        x
+    }
+    /// Intern a type and all the struct and array types nested in it.
+    pub fn intern_tree(&mut self, ty: &Type) -> Arc<Type> {
+        match ty {
+            Type::Struct(x) => {
+                for member in x.members.iter() {
+                    self.intern_tree(&member.ty);
+                }
+            }
+            Type::Array(x) => {
+                self.intern_tree(&x.element_ty);
+            }
+            _ => {}
+        }
+        self.intern(ty)
+    }
+    /// The type table of a module, interning the types of the variables of
+    /// all its entry points and the struct and array types nested in them.
+    pub fn from_entry_points<'a, I: IntoIterator<Item = &'a EntryPoint>>(entry_points: I) -> Self {
+        let mut out = Self::new();
+        for entry_point in entry_points {
+            for var in entry_point.vars.iter() {
+                out.intern_tree(var.ty());
+            }
+        }
+        out
+    }
+    /// Intern the types of variables, in the same order as `vars`.
+    pub fn intern_vars<'a, I: IntoIterator<Item = &'a Variable>>(
+        &mut self,
+        vars: I,
+    ) -> Vec<Arc<Type>> {
+        vars.into_iter().map(|x| self.intern(x.ty())).collect()
+    }
+    /// Intern the types of the variables of an entry point, in the same
+    /// order as [`EntryPoint::vars`].
+    pub fn intern_entry_point(&mut self, entry_point: &EntryPoint) -> Vec<Arc<Type>> {
+        self.intern_vars(entry_point.vars.iter())
+    }
+
+    /// Number of distinct types interned.
+    pub fn len(&self) -> usize {
+        self.tys.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.tys.is_empty()
+    }
+    /// Iterate over the distinct types interned, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = &Arc<Type>> {
+        self.tys.iter()
+    }
+    /// The distinct types interned with their [`ty_hash`]es, sorted by the
+    /// hashes so the table is in the same order in every run.
+    pub fn types(&self) -> Vec<(u64, Arc<Type>)> {
+        let mut out = self
+            .tys
+            .iter()
+            .map(|x| (ty_hash(x), x.clone()))
+            .collect::<Vec<_>>();
+        out.sort_by_key(|x| x.0);
+        out
+    }
+}
+
+/// Whether two interned types are the same node. Types interned by a same
+/// [`TypeArena`] are the same node if and only if they are equal.
+pub fn same_ty(a: &Arc<Type>, b: &Arc<Type>) -> bool {
+    Arc::ptr_eq(a, b)
+}
+
+/// Structural hash of a type. Equal types have the same hash in any run on
+/// any platform, so the hash can be stored to identify a type across shader
+/// modules. Debug names of structs and members are part of the structure, so
+/// shaders should be compiled with the same debug information.
+pub fn ty_hash(ty: &Type) -> u64 {
+    let mut hasher = TypeHasher(FnvHasher::default());
+    hasher.ty(ty);
+    hasher.0.finish()
+}
+
+/// Feeds types to a hasher in a platform-independent encoding. `Hash` is not
+/// used because its encoding of integers and enum discriminants is not
+/// guaranteed to be stable.
+struct TypeHasher(FnvHasher);
+impl TypeHasher {
+    fn u32(&mut self, x: u32) {
+        self.0.write(&x.to_le_bytes());
+    }
+    fn opt_u32(&mut self, x: Option<u32>) {
+        match x {
+            Some(x) => {
+                self.u32(1);
+                self.u32(x);
+            }
+            None => self.u32(0),
+        }
+    }
+    fn opt_usize(&mut self, x: Option<usize>) {
+        self.opt_u32(x.map(|x| x as u32));
+    }
+    fn opt_bool(&mut self, x: Option<bool>) {
+        self.opt_u32(x.map(|x| x as u32));
+    }
+    fn str(&mut self, x: Option<&str>) {
+        match x {
+            Some(x) => {
+                self.u32(x.len() as u32 + 1);
+                self.0.write(x.as_bytes());
+            }
+            None => self.u32(0),
+        }
+    }
+    fn scalar_ty(&mut self, x: &ScalarType) {
+        match x {
+            ScalarType::Void => self.u32(0),
+            ScalarType::Boolean => self.u32(1),
+            ScalarType::Integer { bits, is_signed } => {
+                self.u32(2);
+                self.u32(*bits);
+                self.u32(*is_signed as u32);
+            }
+            ScalarType::Float { bits } => {
+                self.u32(3);
+                self.u32(*bits);
+            }
+        }
+    }
+    fn ty(&mut self, ty: &Type) {
+        match ty {
+            Type::Scalar(x) => {
+                self.u32(0);
+                self.scalar_ty(x);
+            }
+            Type::Vector(x) => {
+                self.u32(1);
+                self.scalar_ty(&x.scalar_ty);
+                self.u32(x.nscalar);
+            }
+            Type::Matrix(x) => {
+                self.u32(2);
+                self.scalar_ty(&x.vector_ty.scalar_ty);
+                self.u32(x.vector_ty.nscalar);
+                self.u32(x.nvector);
+                self.opt_u32(x.axis_order.map(|x| match x {
+                    MatrixAxisOrder::ColumnMajor => 0,
+                    MatrixAxisOrder::RowMajor => 1,
+                }));
+                self.opt_usize(x.stride);
+            }
+            Type::Image(x) => {
+                self.u32(3);
+                self.scalar_ty(&x.scalar_ty);
+                self.u32(x.dim as u32);
+                self.opt_bool(x.is_depth);
+                self.u32(x.is_array as u32);
+                self.u32(x.is_multisampled as u32);
+                self.opt_bool(x.is_sampled);
+                self.u32(x.fmt as u32);
+            }
+            Type::CombinedImageSampler(x) => {
+                self.u32(4);
+                let x = &x.sampled_image_ty;
+                self.scalar_ty(&x.scalar_ty);
+                self.u32(x.dim as u32);
+                self.opt_bool(x.is_depth);
+                self.u32(x.is_array as u32);
+                self.u32(x.is_multisampled as u32);
+            }
+            Type::SampledImage(x) => {
+                self.u32(5);
+                self.scalar_ty(&x.scalar_ty);
+                self.u32(x.dim as u32);
+                self.opt_bool(x.is_depth);
+                self.u32(x.is_array as u32);
+                self.u32(x.is_multisampled as u32);
+            }
+            Type::StorageImage(x) => {
+                self.u32(6);
+                self.u32(x.dim as u32);
+                self.u32(x.is_array as u32);
+                self.u32(x.is_multisampled as u32);
+                self.u32(x.fmt as u32);
+            }
+            Type::Sampler(_) => self.u32(7),
+            Type::SubpassData(x) => {
+                self.u32(8);
+                self.scalar_ty(&x.scalar_ty);
+                self.u32(x.is_multisampled as u32);
+            }
+            Type::Array(x) => {
+                self.u32(9);
+                self.ty(&x.element_ty);
+                self.opt_u32(x.nelement);
+                self.opt_usize(x.stride);
+            }
+            Type::Struct(x) => {
+                self.u32(10);
+                self.str(x.name());
+                self.u32(x.members.len() as u32);
+                for member in x.members.iter() {
+                    self.str(member.name.as_deref());
+                    self.opt_usize(member.offset);
+                    self.u32(match member.access_ty {
+                        AccessType::ReadOnly => 1,
+                        AccessType::WriteOnly => 2,
+                        AccessType::ReadWrite => 3,
+                    });
+                    self.ty(&member.ty);
+                }
+            }
+            Type::AccelStruct(_) => self.u32(11),
+            Type::DeviceAddress(_) => self.u32(12),
+            Type::DevicePointer(x) => {
+                self.u32(13);
+                self.u32(x.store_cls as u32);
+                self.ty(&x.pointee_ty);
+            }
+            Type::RayQuery(_) => self.u32(14),
+            // Types added to `spq-core` later are told apart by their
+            // textual forms until they are encoded here.
+            _ => {
+                self.u32(15);
+                self.str(Some(&ty.to_string()));
+            }
+        }
+    }
+}
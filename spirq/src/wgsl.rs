@@ -0,0 +1,485 @@
+//! WGSL declarations of shader resources, to check the bindings of Vulkan
+//! shaders ported to WebGPU.
+//!
+//! Descriptors are declared as `@group @binding` variables, push constants as
+//! a `var<push_constant>` and specialization constants as `override`s. Push
+//! constants and descriptor arrays (as `binding_array`s) are only accepted by
+//! wgpu. WGSL structs have no explicit member offsets, so members are sized
+//! with `@size` to place the next member at its decorated offset, and array
+//! elements with strides larger than their sizes are wrapped in padded
+//! structs. Resources WGSL has no counterpart of, like combined image
+//! samplers, texel buffers and input attachments, are reported as errors.
+use std::fmt::Write;
+
+use crate::{
+    entry_point::EntryPoint,
+    error::{anyhow, Result},
+    spirv::{Dim, ImageFormat},
+    stub::sanitize,
+    ty::{AccessType, DescriptorType, MatrixAxisOrder, ScalarType, StructType, Type},
+    var::Variable,
+};
+
+/// Generate WGSL declarations of the resources used by `entry_point`,
+/// including descriptors, push constants and specialization constants.
+/// Variables without debug names are given placeholder names.
+pub fn generate_wgsl(entry_point: &EntryPoint) -> Result<String> {
+    let mut writer = WgslWriter::default();
+    let mut body = String::new();
+    for var in entry_point.vars.iter() {
+        body += &writer.var_decl(var)?;
+    }
+
+    let mut out = format!(
+        "// Resources of entry point `{}` ({:?}).\n",
+        entry_point.name, entry_point.exec_model
+    );
+    if writer.enable_f16 {
+        out += "enable f16;\n";
+    }
+    out += &writer.out;
+    out += &body;
+    Ok(out)
+}
+
+/// Size and alignment of a type in WGSL.
+#[derive(Clone, Copy)]
+struct WgslLayout {
+    nbyte: usize,
+    align: usize,
+}
+impl WgslLayout {
+    /// Distance between elements of arrays of this type.
+    fn stride(&self) -> usize {
+        (self.nbyte + self.align - 1) & !(self.align - 1)
+    }
+}
+
+#[derive(Default)]
+struct WgslWriter {
+    /// Struct types already declared, their names and WGSL layouts.
+    structs: Vec<(StructType, String, WgslLayout)>,
+    /// Names already taken. Structs and variables share the module scope.
+    names: Vec<String>,
+    /// Struct declarations, emitted before any variable.
+    out: String,
+    /// Whether any type uses 16-bit floats.
+    enable_f16: bool,
+}
+impl WgslWriter {
+    fn unique_name(&mut self, base: String) -> String {
+        let mut name = base.clone();
+        let mut i = 1;
+        while self.names.contains(&name) {
+            name = format!("{}_{}", base, i);
+            i += 1;
+        }
+        self.names.push(name.clone());
+        name
+    }
+
+    fn scalar_decl(&mut self, scalar_ty: &ScalarType) -> Result<(&'static str, WgslLayout)> {
+        let (name, nbyte) = match scalar_ty {
+            ScalarType::Boolean => ("bool", 4),
+            ScalarType::Integer {
+                bits: 32,
+                is_signed: false,
+            } => ("u32", 4),
+            ScalarType::Integer {
+                bits: 32,
+                is_signed: true,
+            } => ("i32", 4),
+            ScalarType::Float { bits: 16 } => {
+                self.enable_f16 = true;
+                ("f16", 2)
+            }
+            ScalarType::Float { bits: 32 } => ("f32", 4),
+            _ => return Err(anyhow!("{} cannot be declared in wgsl", scalar_ty)),
+        };
+        let layout = WgslLayout {
+            nbyte,
+            align: nbyte,
+        };
+        Ok((name, layout))
+    }
+    fn vector_layout(scalar_layout: WgslLayout, nscalar: u32) -> WgslLayout {
+        // 3-component vectors are aligned like 4-component ones.
+        let nalign = if nscalar == 2 { 2 } else { 4 };
+        WgslLayout {
+            nbyte: scalar_layout.nbyte * nscalar as usize,
+            align: scalar_layout.align * nalign,
+        }
+    }
+
+    /// Type name and WGSL layout of `ty`. Structs and padded wrappers are
+    /// declared on the first use, and are named after `hint`.
+    fn ty_decl(&mut self, ty: &Type, hint: &str) -> Result<(String, WgslLayout)> {
+        let out = match ty {
+            Type::Scalar(x) => {
+                let (name, layout) = self.scalar_decl(x)?;
+                (name.to_owned(), layout)
+            }
+            Type::Vector(x) => {
+                let (name, layout) = self.scalar_decl(&x.scalar_ty)?;
+                let layout = Self::vector_layout(layout, x.nscalar);
+                (format!("vec{}<{}>", x.nscalar, name), layout)
+            }
+            Type::Matrix(x) => {
+                if x.axis_order == Some(MatrixAxisOrder::RowMajor) {
+                    return Err(anyhow!(
+                        "row-major matrix `{}` cannot be declared in wgsl",
+                        hint
+                    ));
+                }
+                let scalar_ty = &x.vector_ty.scalar_ty;
+                if !matches!(scalar_ty, ScalarType::Float { .. }) {
+                    return Err(anyhow!(
+                        "matrix of {} cannot be declared in wgsl",
+                        scalar_ty
+                    ));
+                }
+                let (name, layout) = self.scalar_decl(scalar_ty)?;
+                let col_layout = Self::vector_layout(layout, x.vector_ty.nscalar);
+                let stride = x.stride.unwrap_or_else(|| col_layout.stride());
+                if stride != col_layout.stride() {
+                    return Err(anyhow!(
+                        "matrix stride {} of `{}` cannot be expressed in wgsl",
+                        stride,
+                        hint
+                    ));
+                }
+                let layout = WgslLayout {
+                    nbyte: stride * x.nvector as usize,
+                    align: col_layout.align,
+                };
+                let name = format!("mat{}x{}<{}>", x.nvector, x.vector_ty.nscalar, name);
+                (name, layout)
+            }
+            Type::Array(x) => {
+                let elem_hint = format!("{}_Elem", hint);
+                let (name, elem_layout) = self.ty_decl(&x.element_ty, &elem_hint)?;
+                let stride = x.stride.unwrap_or_else(|| elem_layout.stride());
+                let (name, elem_layout) = self.strided(name, elem_layout, stride, &elem_hint)?;
+                match x.nelement {
+                    Some(n) => {
+                        let layout = WgslLayout {
+                            nbyte: stride * n as usize,
+                            ..elem_layout
+                        };
+                        (format!("array<{}, {}>", name, n), layout)
+                    }
+                    None => {
+                        let layout = WgslLayout {
+                            nbyte: 0,
+                            ..elem_layout
+                        };
+                        (format!("array<{}>", name), layout)
+                    }
+                }
+            }
+            Type::Struct(x) => self.declare_struct(x, hint)?,
+            // WGSL has no 64-bit integers, device addresses are kept in two
+            // 32-bit words.
+            Type::DeviceAddress(_) | Type::DevicePointer(_) => {
+                let layout = WgslLayout { nbyte: 8, align: 8 };
+                ("vec2<u32>".to_owned(), layout)
+            }
+            _ => return Err(anyhow!("type {} cannot be declared in wgsl", ty)),
+        };
+        Ok(out)
+    }
+    /// Wrap an array element in a padded struct if `stride` is larger than
+    /// the WGSL array stride of the element.
+    fn strided(
+        &mut self,
+        name: String,
+        layout: WgslLayout,
+        stride: usize,
+        hint: &str,
+    ) -> Result<(String, WgslLayout)> {
+        if stride == layout.stride() {
+            return Ok((name, layout));
+        }
+        if stride < layout.stride() || stride & (layout.align - 1) != 0 {
+            return Err(anyhow!(
+                "array stride {} of `{}` cannot be expressed in wgsl",
+                stride,
+                hint
+            ));
+        }
+        let wrapper_name = self.unique_name(hint.to_owned());
+        writeln!(
+            self.out,
+            "struct {} {{\n    @size({}) value: {},\n}}",
+            wrapper_name, stride, name
+        )
+        .unwrap();
+        let layout = WgslLayout {
+            nbyte: stride,
+            align: layout.align,
+        };
+        Ok((wrapper_name, layout))
+    }
+
+    fn declare_struct(
+        &mut self,
+        struct_ty: &StructType,
+        hint: &str,
+    ) -> Result<(String, WgslLayout)> {
+        if let Some((_, name, layout)) = self.structs.iter().find(|(x, _, _)| x == struct_ty) {
+            return Ok((name.clone(), *layout));
+        }
+        let base = struct_ty
+            .name
+            .as_deref()
+            .map(sanitize)
+            .unwrap_or_else(|| hint.to_owned());
+        let name = self.unique_name(base);
+
+        let mut members = struct_ty.members.iter().enumerate().collect::<Vec<_>>();
+        members.sort_by_key(|(_, x)| x.offset);
+        // Member declarations are emitted once the offset of the next member
+        // is known.
+        let mut decls: Vec<(String, String, usize, WgslLayout)> = Vec::new();
+        let mut align = 1;
+        for (i, member) in members {
+            let member_name = member
+                .name
+                .as_deref()
+                .map(sanitize)
+                .unwrap_or_else(|| format!("m{}", i));
+            let offset = member
+                .offset
+                .ok_or_else(|| anyhow!("member `{}` of `{}` has no offset", member_name, name))?;
+            let (ty_name, layout) =
+                self.ty_decl(&member.ty, &format!("{}_{}", name, member_name))?;
+            let cur = decls.last().map(|x| x.2 + x.3.nbyte).unwrap_or(0);
+            if offset < cur || offset & (layout.align - 1) != 0 || (decls.is_empty() && offset != 0)
+            {
+                return Err(anyhow!(
+                    "offset {} of member `{}` of `{}` cannot be expressed in wgsl",
+                    offset,
+                    member_name,
+                    name
+                ));
+            }
+            decls.push((member_name, ty_name, offset, layout));
+            align = align.max(layout.align);
+        }
+        let mut body = String::new();
+        for (i, (member_name, ty_name, offset, layout)) in decls.iter().enumerate() {
+            // Members are only sized if the next member is not at its
+            // natural offset.
+            let next = decls.get(i + 1).map(|x| (x.2, x.3.align));
+            match next {
+                Some((next_offset, next_align))
+                    if next_offset
+                        != (offset + layout.nbyte + next_align - 1) & !(next_align - 1) =>
+                {
+                    let nbyte = next_offset - offset;
+                    writeln!(body, "    @size({}) {}: {},", nbyte, member_name, ty_name)
+                }
+                _ => writeln!(body, "    {}: {},", member_name, ty_name),
+            }
+            .unwrap();
+        }
+        writeln!(self.out, "struct {} {{\n{}}}", name, body).unwrap();
+        let nbyte = decls.last().map(|x| x.2 + x.3.nbyte).unwrap_or(0);
+        let layout = WgslLayout {
+            nbyte: (nbyte + align - 1) & !(align - 1),
+            align,
+        };
+        self.structs.push((struct_ty.clone(), name.clone(), layout));
+        Ok((name, layout))
+    }
+
+    fn var_decl(&mut self, var: &Variable) -> Result<String> {
+        let mut out = String::new();
+        match var {
+            Variable::Descriptor {
+                name,
+                desc_bind,
+                desc_ty,
+                ty,
+                nbind,
+            } => {
+                let name = name
+                    .as_deref()
+                    .map(sanitize)
+                    .unwrap_or_else(|| format!("desc_{}_{}", desc_bind.set(), desc_bind.bind()));
+                let name = self.unique_name(name);
+                let hint = format!("Block_{}_{}", desc_bind.set(), desc_bind.bind());
+                let (space, ty_name) = self.desc_ty_name(&name, &hint, desc_ty, ty)?;
+                let ty_name = match nbind {
+                    0 => format!("binding_array<{}>", ty_name),
+                    1 => ty_name,
+                    n => format!("binding_array<{}, {}>", ty_name, n),
+                };
+                writeln!(
+                    out,
+                    "@group({}) @binding({}) var{} {}: {};",
+                    desc_bind.set(),
+                    desc_bind.bind(),
+                    space,
+                    name,
+                    ty_name
+                )
+                .unwrap();
+            }
+            Variable::PushConstant { name, ty } => {
+                let name = name
+                    .as_deref()
+                    .map(sanitize)
+                    .unwrap_or_else(|| "push_consts".to_owned());
+                let name = self.unique_name(name);
+                let (ty_name, _) = match ty {
+                    Type::Struct(x) => self.declare_struct(x, "PushConstants")?,
+                    _ => return Err(anyhow!("push constant `{}` is not a struct", name)),
+                };
+                writeln!(out, "var<push_constant> {}: {};", name, ty_name).unwrap();
+            }
+            Variable::SpecConstant { name, spec_id, ty } => {
+                let name = name
+                    .as_deref()
+                    .map(sanitize)
+                    .unwrap_or_else(|| format!("spec_{}", spec_id));
+                let name = self.unique_name(name);
+                let ty_name = match ty {
+                    Type::Scalar(x) => self.scalar_decl(x)?.0,
+                    _ => {
+                        return Err(anyhow!(
+                            "specialization constant `{}` of type {} cannot be declared in wgsl",
+                            name,
+                            ty
+                        ))
+                    }
+                };
+                // Default values are not reflected, so they have to be given
+                // by the pipeline.
+                writeln!(out, "@id({}) override {}: {};", spec_id, name, ty_name).unwrap();
+            }
+            // Stage inputs and outputs are entry point parameters and return
+            // values in WGSL.
+            Variable::Input { .. } | Variable::Output { .. } => {}
+        }
+        Ok(out)
+    }
+
+    /// Address space (with the leading `<`) and type name of descriptor
+    /// `name`. Unnamed block types are named after `hint`.
+    fn desc_ty_name(
+        &mut self,
+        name: &str,
+        hint: &str,
+        desc_ty: &DescriptorType,
+        ty: &Type,
+    ) -> Result<(String, String)> {
+        let out = match (desc_ty, ty) {
+            (DescriptorType::UniformBuffer(), Type::Struct(x)) => {
+                let (ty_name, _) = self.declare_struct(x, hint)?;
+                ("<uniform>".to_owned(), ty_name)
+            }
+            (DescriptorType::StorageBuffer(access), Type::Struct(x)) => {
+                let (ty_name, _) = self.declare_struct(x, hint)?;
+                // WGSL has no write-only storage buffers.
+                let access = match access {
+                    AccessType::ReadOnly => "read",
+                    _ => "read_write",
+                };
+                (format!("<storage, {}>", access), ty_name)
+            }
+            (DescriptorType::Sampler(), _) => (String::new(), "sampler".to_owned()),
+            (DescriptorType::SampledImage(), Type::SampledImage(x)) => {
+                let dim = wgsl_dim(x.dim, x.is_multisampled, x.is_array)?;
+                let ty_name = if x.is_depth == Some(true) {
+                    format!("texture_depth_{}", dim)
+                } else {
+                    let (scalar_name, _) = self.scalar_decl(&x.scalar_ty)?;
+                    format!("texture_{}<{}>", dim, scalar_name)
+                };
+                (String::new(), ty_name)
+            }
+            (DescriptorType::StorageImage(access), Type::StorageImage(x)) => {
+                if x.is_multisampled {
+                    return Err(anyhow!(
+                        "multisampled storage image `{}` cannot be declared in wgsl",
+                        name
+                    ));
+                }
+                let dim = wgsl_dim(x.dim, false, x.is_array)?;
+                let fmt = wgsl_fmt(x.fmt).ok_or_else(|| {
+                    anyhow!(
+                        "storage image `{}` of format {:?} cannot be declared in wgsl",
+                        name,
+                        x.fmt
+                    )
+                })?;
+                let access = match access {
+                    AccessType::ReadOnly => "read",
+                    AccessType::WriteOnly => "write",
+                    AccessType::ReadWrite => "read_write",
+                };
+                let ty_name = format!("texture_storage_{}<{}, {}>", dim, fmt, access);
+                (String::new(), ty_name)
+            }
+            _ => {
+                return Err(anyhow!(
+                    "descriptor `{}` of {:?} cannot be declared in wgsl",
+                    name,
+                    desc_ty
+                ))
+            }
+        };
+        Ok(out)
+    }
+}
+
+/// Dimension part of WGSL texture type names, like `2d_array`.
+fn wgsl_dim(dim: Dim, is_multisampled: bool, is_array: bool) -> Result<&'static str> {
+    let dim = match (dim, is_multisampled, is_array) {
+        (Dim::Dim1D, false, false) => "1d",
+        (Dim::Dim2D, false, false) => "2d",
+        (Dim::Dim2D, false, true) => "2d_array",
+        (Dim::Dim2D, true, false) => "multisampled_2d",
+        (Dim::Dim3D, false, false) => "3d",
+        (Dim::DimCube, false, false) => "cube",
+        (Dim::DimCube, false, true) => "cube_array",
+        _ => {
+            return Err(anyhow!(
+                "image dimension {:?}{}{} cannot be declared in wgsl",
+                dim,
+                if is_multisampled {
+                    " (multisampled)"
+                } else {
+                    ""
+                },
+                if is_array { " (arrayed)" } else { "" }
+            ))
+        }
+    };
+    Ok(dim)
+}
+/// WGSL texel format of storage textures. Only the formats in the core WGSL
+/// spec are supported.
+fn wgsl_fmt(fmt: ImageFormat) -> Option<&'static str> {
+    let fmt = match fmt {
+        ImageFormat::Rgba8 => "rgba8unorm",
+        ImageFormat::Rgba8Snorm => "rgba8snorm",
+        ImageFormat::Rgba8ui => "rgba8uint",
+        ImageFormat::Rgba8i => "rgba8sint",
+        ImageFormat::Rgba16ui => "rgba16uint",
+        ImageFormat::Rgba16i => "rgba16sint",
+        ImageFormat::Rgba16f => "rgba16float",
+        ImageFormat::R32ui => "r32uint",
+        ImageFormat::R32i => "r32sint",
+        ImageFormat::R32f => "r32float",
+        ImageFormat::Rg32ui => "rg32uint",
+        ImageFormat::Rg32i => "rg32sint",
+        ImageFormat::Rg32f => "rg32float",
+        ImageFormat::Rgba32ui => "rgba32uint",
+        ImageFormat::Rgba32i => "rgba32sint",
+        ImageFormat::Rgba32f => "rgba32float",
+        _ => return None,
+    };
+    Some(fmt)
+}